@@ -0,0 +1,39 @@
+use std::time::{Duration, Instant};
+use xdevs::devstone::bench::{self, Params, Variant};
+
+/// Number of simulations per benchmark. The median simulation time is reported.
+const RUNS: usize = 10;
+
+/// USAGE:
+/// `cargo bench --bench devstone [--features small_bags]`
+///
+/// It simulates a set of DEVStone models and prints the median simulation time of each.
+/// Compare the results with and without the `small_bags` feature to measure the effect of inline port storage.
+fn main() {
+    let feature = match cfg!(feature = "small_bags") {
+        true => "small_bags",
+        false => "default",
+    };
+    println!("DEVStone benchmarks ({feature} port storage, median of {RUNS} runs)");
+    for variant in Variant::ALL {
+        let (width, depth) = match variant {
+            Variant::HOmod => (20, 20),
+            _ => (100, 100),
+        };
+        let params = Params::new(variant, width, depth);
+        let mut times: Vec<Duration> = (0..RUNS)
+            .map(|_| {
+                let mut simulator = bench::simulator(&params);
+                let start = Instant::now();
+                bench::simulate(&mut simulator);
+                start.elapsed()
+            })
+            .collect();
+        times.sort();
+        println!(
+            "{:>6} {width:>4}x{depth:<4} {:>12?}",
+            variant.name(),
+            times[RUNS / 2]
+        );
+    }
+}
@@ -1,56 +1,827 @@
-use std::env;
-use std::time::Instant;
-use xdevs::devstone::*;
+use std::fmt;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+use xdevs::devstone::bench::{self as devstone, Params, Variant};
+use xdevs::modeling::Coupled;
 use xdevs::simulation::*;
+use xdevs::tracing::{Monitor, ValueFormatter};
 
-/// USAGE:
-/// `cargo run <MODEL_TYPE> <WIDTH> <DEPTH>`
-/// - `<MODEL_TYPE>` must be `LI`, `HI`, `HO`, or `HOmod`.
-/// - `WIDTH` must be equal to or greater than 1.
-/// - `DEPTH` must be equal to or greater than 1.
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    let model_type = args
-        .get(1)
-        .expect("first argument must select the model type")
-        .clone()
-        .to_lowercase();
-    let width = args
-        .get(2)
-        .expect("second argument must select the width")
-        .parse()
-        .expect("width could not be parsed");
-    let depth = args
-        .get(3)
-        .expect("third argument must select the depth")
+#[cfg(feature = "track_alloc")]
+#[global_allocator]
+static ALLOCATOR: xdevs::memory::TrackingAllocator = xdevs::memory::TrackingAllocator;
+
+const USAGE: &str = "\
+USAGE:
+    cargo run --release --example devstone -- <COMMAND> [OPTIONS]
+
+COMMANDS:
+    devstone <MODEL_TYPE> <WIDTH> <DEPTH>   Simulates a DEVStone model (MODEL_TYPE: LI, HI, HO, or HOmod)
+    run <MODEL_JSON>                        Simulates a coupled model described in a JSON file (requires the serde feature)
+                                            with Generator, Processor, and Transducer atomic models
+    bench                                   Simulates all the DEVStone model types and reports the median times
+    speedup <MODEL_TYPE> <WIDTH> <DEPTH>    Simulates a DEVStone model with 1, 2, 4, ... threads and reports the
+                                            speedup and efficiency (requires a par_* feature)
+    features                                Lists the enabled features of the crate
+    help                                    Prints this message
+
+OPTIONS:
+    --int-delay <MS>    Internal delay of DEVStone atomic models in milliseconds (default: 0)
+    --ext-delay <MS>    External delay of DEVStone atomic models in milliseconds (default: the internal delay)
+    --repeat <N>        Number of simulations (default: 1, 10 for bench, or 3 per thread count for speedup)
+    --threads <N>       Maximum number of threads (speedup only, default: available parallelism)
+    --flatten           Flattens the model hierarchy before simulating (devstone and run)
+    --direct            Composes coupling chains into direct connections before simulating (devstone and run)
+    --monitor           Shows a live dashboard of the simulation (devstone and run)
+    --progress          Reports the progress of the simulation in the standard error (devstone and run)
+    --stats             Prints the structure statistics of the model before simulating it (devstone and run)
+    --until <TIME>      Simulation time at which the simulation stops (run only, default: infinity)
+    --output <FORMAT>   Format of the results: text, json (one object per simulation and line), or csv (default: text)";
+
+/// Error in the command line arguments or while loading a model.
+#[derive(Debug)]
+struct CliError(String);
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Subcommand selected in the command line.
+#[derive(Debug)]
+enum Command {
+    Devstone(Params),
+    Run { path: String },
+    Bench,
+    Speedup(Params),
+    Features,
+    Help,
+}
+
+/// Format of the results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Output {
+    /// Human-readable text.
+    Text,
+    /// One JSON object per simulation and line.
+    Json,
+    /// One CSV row per simulation, preceded by a header row.
+    Csv,
+}
+
+impl std::str::FromStr for Output {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Parsed command line arguments.
+#[derive(Debug)]
+struct Cli {
+    command: Command,
+    int_delay: u64,
+    ext_delay: Option<u64>,
+    repeat: Option<usize>,
+    threads: Option<usize>,
+    flatten: bool,
+    direct: bool,
+    monitor: bool,
+    progress: bool,
+    stats: bool,
+    until: f64,
+    output: Output,
+}
+
+impl Cli {
+    /// Parses the command line arguments (without the program name).
+    fn parse(args: &[String]) -> Result<Self, CliError> {
+        let mut positional = Vec::new();
+        let mut cli = Self {
+            command: Command::Help,
+            int_delay: 0,
+            ext_delay: None,
+            repeat: None,
+            threads: None,
+            flatten: false,
+            direct: false,
+            monitor: false,
+            progress: false,
+            stats: false,
+            until: f64::INFINITY,
+            output: Output::Text,
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| CliError(format!("missing value for {arg}")))
+            };
+            match arg.as_str() {
+                "--int-delay" => cli.int_delay = parse(arg, value()?)?,
+                "--ext-delay" => cli.ext_delay = Some(parse(arg, value()?)?),
+                "--repeat" => cli.repeat = Some(parse(arg, value()?)?),
+                "--threads" => cli.threads = Some(parse(arg, value()?)?),
+                "--until" => cli.until = parse(arg, value()?)?,
+                "--output" => cli.output = parse(arg, value()?)?,
+                "--flatten" => cli.flatten = true,
+                "--direct" => cli.direct = true,
+                "--monitor" => cli.monitor = true,
+                "--progress" => cli.progress = true,
+                "--stats" => cli.stats = true,
+                "-h" | "--help" => return Ok(cli),
+                _ if arg.starts_with('-') => return Err(CliError(format!("unknown option {arg}"))),
+                _ => positional.push(arg.as_str()),
+            }
+        }
+        let (command, operands) = positional
+            .split_first()
+            .ok_or_else(|| CliError("missing command".to_string()))?;
+        cli.command = match (*command, operands) {
+            ("devstone", [variant, width, depth]) => {
+                Command::Devstone(cli.devstone_params(variant, width, depth)?)
+            }
+            ("speedup", [variant, width, depth]) => {
+                Command::Speedup(cli.devstone_params(variant, width, depth)?)
+            }
+            (command @ ("devstone" | "speedup"), _) => {
+                return Err(CliError(format!(
+                    "{command} expects <MODEL_TYPE> <WIDTH> <DEPTH>"
+                )))
+            }
+            ("run", [path]) => Command::Run {
+                path: path.to_string(),
+            },
+            ("run", _) => return Err(CliError("run expects <MODEL_JSON>".to_string())),
+            ("bench", []) => Command::Bench,
+            ("features", []) => Command::Features,
+            ("help", []) => Command::Help,
+            ("bench" | "features" | "help", [arg, ..]) => {
+                return Err(CliError(format!("unexpected argument {arg}")))
+            }
+            (command, _) => return Err(CliError(format!("unknown command {command}"))),
+        };
+        if cli.repeat == Some(0) {
+            return Err(CliError("--repeat must be at least 1".to_string()));
+        }
+        if cli.threads.is_some() && !matches!(cli.command, Command::Speedup(_)) {
+            return Err(CliError("--threads is only valid for speedup".to_string()));
+        }
+        if cli.threads == Some(0) {
+            return Err(CliError("--threads must be at least 1".to_string()));
+        }
+        let simulates = matches!(cli.command, Command::Devstone(_) | Command::Run { .. });
+        if (cli.flatten || cli.direct || cli.monitor || cli.progress || cli.stats) && !simulates {
+            return Err(CliError(
+                "--flatten, --direct, --monitor, --progress, and --stats are only valid for devstone and run"
+                    .to_string(),
+            ));
+        }
+        if cli.monitor && cli.progress {
+            return Err(CliError(
+                "--monitor and --progress are mutually exclusive".to_string(),
+            ));
+        }
+        if cli.flatten && cli.direct {
+            return Err(CliError(
+                "--flatten and --direct are mutually exclusive".to_string(),
+            ));
+        }
+        if (cli.monitor || cli.stats) && cli.output != Output::Text {
+            return Err(CliError(
+                "--monitor and --stats are only valid with text output".to_string(),
+            ));
+        }
+        if cli.until != f64::INFINITY && !matches!(cli.command, Command::Run { .. }) {
+            return Err(CliError("--until is only valid for run".to_string()));
+        }
+        Ok(cli)
+    }
+
+    /// Parses the parameters of a DEVStone model.
+    fn devstone_params(&self, variant: &str, width: &str, depth: &str) -> Result<Params, CliError> {
+        let variant: Variant = variant.parse().map_err(CliError)?;
+        let (width, depth) = (parse("WIDTH", width)?, parse("DEPTH", depth)?);
+        if width < 1 || depth < 1 {
+            return Err(CliError("WIDTH and DEPTH must be at least 1".to_string()));
+        }
+        let params = Params::new(variant, width, depth);
+        Ok(params.with_delays(self.int_delay, self.ext_delay()))
+    }
+
+    /// Returns the external delay of DEVStone atomic models.
+    fn ext_delay(&self) -> u64 {
+        self.ext_delay.unwrap_or(self.int_delay)
+    }
+}
+
+/// Parses the value of an argument.
+fn parse<V: std::str::FromStr>(name: &str, value: &str) -> Result<V, CliError> {
+    value
         .parse()
-        .expect("depth could not be parsed");
+        .map_err(|_| CliError(format!("invalid value {value:?} for {name}")))
+}
 
-    let int_delay = match args.get(4) {
-        Some(arg) => arg.parse().expect("int delay could not be parsed"),
-        None => 0,
-    };
-    let ext_delay = match args.get(5) {
-        Some(arg) => arg.parse().expect("ext delay could not be parsed"),
-        None => int_delay,
-    };
+/// Loads a coupled model from a JSON file. Atomic models are taken from the [`xdevs::lib`] module.
+#[cfg(feature = "serde")]
+fn load_model(path: &str) -> Result<Coupled, CliError> {
+    use serde_json::Value;
+    use xdevs::lib::{Generator, Job, Processor, Transducer};
+    use xdevs::modeling::ModelFactory;
+
+    fn param(config: &Value, name: &str) -> Result<f64, String> {
+        config[name]
+            .as_f64()
+            .ok_or_else(|| format!("missing numeric parameter {name}"))
+    }
+
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| CliError(format!("could not read {path}: {e}")))?;
+    let mut factory = ModelFactory::new();
+    factory.register_port_type::<Job>("Job");
+    factory.register_atomic("Generator", |name, config| {
+        Ok(Box::new(Generator::new(name, param(config, "period")?)))
+    });
+    factory.register_atomic("Processor", |name, config| {
+        let service_time = param(config, "service_time")?;
+        Ok(Box::new(Processor::<Job>::new(name, service_time)))
+    });
+    factory.register_atomic("Transducer", |name, config| {
+        Ok(Box::new(Transducer::new(
+            name,
+            param(config, "observation")?,
+        )))
+    });
+    Coupled::from_json(&json, &factory).map_err(|e| CliError(format!("{path}: {e}")))
+}
+
+#[cfg(not(feature = "serde"))]
+fn load_model(_path: &str) -> Result<Coupled, CliError> {
+    Err(CliError("run requires the serde feature".to_string()))
+}
+
+/// Results of a simulation.
+#[derive(Debug)]
+struct Record {
+    /// Subcommand that ran the simulation.
+    command: &'static str,
+    /// DEVStone model type or path of the JSON model description.
+    model: String,
+    /// Parameters of DEVStone models.
+    devstone: Option<Params>,
+    /// Index of the simulation (starting from 0).
+    run: usize,
+    /// Number of atomic models.
+    n_atomics: usize,
+    creation: Duration,
+    flattening: Option<Duration>,
+    direct_connection: Option<Duration>,
+    simulator_creation: Duration,
+    simulation: Duration,
+    /// Number of simulation cycles.
+    n_cycles: usize,
+    /// Number of state transitions of all the atomic models.
+    n_transitions: usize,
+    /// Peak resident memory of the process in kilobytes, if available.
+    peak_memory_kb: Option<u64>,
+    /// Heap memory used by the simulation, if the `track_alloc` feature is enabled.
+    heap: Option<HeapUsage>,
+}
+
+/// Heap memory used by a simulation.
+#[derive(Debug, Clone, Copy)]
+struct HeapUsage {
+    /// Bytes allocated for the model and the simulator before the simulation starts.
+    model_bytes: usize,
+    /// Maximum number of bytes allocated at the same time, from the model creation to the simulation stop.
+    peak_bytes: usize,
+    /// Number of allocations from the model creation to the simulation stop.
+    n_allocations: usize,
+}
+
+/// Heap memory statistics of the process.
+#[derive(Debug, Clone, Copy)]
+struct HeapStats {
+    allocated: usize,
+    peak: usize,
+    n_allocations: usize,
+}
+
+/// Returns the heap memory statistics of the process. It is only available with the `track_alloc` feature.
+/// If `reset` is `true`, the peak of allocated memory is reset first.
+fn heap_stats(reset: bool) -> Option<HeapStats> {
+    #[cfg(feature = "track_alloc")]
+    {
+        use xdevs::memory::TrackingAllocator;
+        if reset {
+            TrackingAllocator::reset_peak();
+        }
+        let stats = TrackingAllocator::stats();
+        Some(HeapStats {
+            allocated: stats.allocated,
+            peak: stats.peak,
+            n_allocations: stats.n_allocations,
+        })
+    }
+    #[cfg(not(feature = "track_alloc"))]
+    {
+        let _ = reset;
+        None
+    }
+}
+
+impl Record {
+    /// Names of the CSV columns.
+    const CSV_HEADER: &'static str = "command,model,width,depth,int_delay,ext_delay,flatten,direct,run,n_atomics,\
+creation_s,flattening_s,direct_connection_s,simulator_creation_s,simulation_s,n_cycles,n_transitions,peak_memory_kb,heap_model_bytes,heap_peak_bytes,n_allocations,features";
+
+    fn to_json(&self) -> String {
+        let opt = |v: Option<String>| v.unwrap_or_else(|| "null".to_string());
+        let features: Vec<_> = enabled_features().map(|f| format!("\"{f}\"")).collect();
+        format!(
+            "{{\"command\":\"{}\",\"model\":{},\"width\":{},\"depth\":{},\"int_delay\":{},\"ext_delay\":{},\
+\"flatten\":{},\"direct\":{},\"run\":{},\"n_atomics\":{},\"creation_s\":{},\"flattening_s\":{},\
+\"direct_connection_s\":{},\"simulator_creation_s\":{},\
+\"simulation_s\":{},\"n_cycles\":{},\"n_transitions\":{},\"peak_memory_kb\":{},\"heap_model_bytes\":{},\"heap_peak_bytes\":{},\"n_allocations\":{},\"features\":[{}]}}",
+            self.command,
+            json_string(&self.model),
+            opt(self.devstone.map(|d| d.width.to_string())),
+            opt(self.devstone.map(|d| d.depth.to_string())),
+            opt(self.devstone.map(|d| d.int_delay.to_string())),
+            opt(self.devstone.map(|d| d.ext_delay.to_string())),
+            self.flattening.is_some(),
+            self.direct_connection.is_some(),
+            self.run,
+            self.n_atomics,
+            self.creation.as_secs_f64(),
+            opt(self.flattening.map(|d| d.as_secs_f64().to_string())),
+            opt(self.direct_connection.map(|d| d.as_secs_f64().to_string())),
+            self.simulator_creation.as_secs_f64(),
+            self.simulation.as_secs_f64(),
+            self.n_cycles,
+            self.n_transitions,
+            opt(self.peak_memory_kb.map(|m| m.to_string())),
+            opt(self.heap.map(|h| h.model_bytes.to_string())),
+            opt(self.heap.map(|h| h.peak_bytes.to_string())),
+            opt(self.heap.map(|h| h.n_allocations.to_string())),
+            features.join(","),
+        )
+    }
 
+    fn to_csv(&self) -> String {
+        let opt = |v: Option<String>| v.unwrap_or_default();
+        let features: Vec<_> = enabled_features().collect();
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            self.command,
+            csv_field(&self.model),
+            opt(self.devstone.map(|d| d.width.to_string())),
+            opt(self.devstone.map(|d| d.depth.to_string())),
+            opt(self.devstone.map(|d| d.int_delay.to_string())),
+            opt(self.devstone.map(|d| d.ext_delay.to_string())),
+            self.flattening.is_some(),
+            self.direct_connection.is_some(),
+            self.run,
+            self.n_atomics,
+            self.creation.as_secs_f64(),
+            opt(self.flattening.map(|d| d.as_secs_f64().to_string())),
+            opt(self.direct_connection.map(|d| d.as_secs_f64().to_string())),
+            self.simulator_creation.as_secs_f64(),
+            self.simulation.as_secs_f64(),
+            self.n_cycles,
+            self.n_transitions,
+            opt(self.peak_memory_kb.map(|m| m.to_string())),
+            opt(self.heap.map(|h| h.model_bytes.to_string())),
+            opt(self.heap.map(|h| h.peak_bytes.to_string())),
+            opt(self.heap.map(|h| h.n_allocations.to_string())),
+            features.join(" "),
+        )
+    }
+
+    fn print_text(&self) {
+        println!("Model creation time: {:?}", self.creation);
+        if let Some(flattening) = self.flattening {
+            println!("Model flattening time: {flattening:?}");
+        }
+        if let Some(direct_connection) = self.direct_connection {
+            println!("Direct connection time: {direct_connection:?}");
+        }
+        println!("Simulator creation time: {:?}", self.simulator_creation);
+        println!("Simulation time: {:?}", self.simulation);
+        println!(
+            "Simulation cycles: {}, state transitions: {} ({} atomic models)",
+            self.n_cycles, self.n_transitions, self.n_atomics
+        );
+        if let Some(memory) = self.peak_memory_kb {
+            println!("Peak memory: {memory} kB");
+        }
+        if let Some(heap) = self.heap {
+            println!(
+                "Heap memory: {} kB for the model, {} kB at peak ({} allocations)",
+                heap.model_bytes / 1024,
+                heap.peak_bytes / 1024,
+                heap.n_allocations
+            );
+        }
+    }
+}
+
+/// Returns the names of the enabled features.
+fn enabled_features() -> impl Iterator<Item = &'static str> {
+    (RunRecord::all_features().iter()).filter_map(|&(feature, enabled)| enabled.then_some(feature))
+}
+
+/// Encodes a string as a JSON string literal.
+fn json_string(s: &str) -> String {
+    let mut encoded = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => encoded.push_str("\\\""),
+            '\\' => encoded.push_str("\\\\"),
+            c if c.is_control() => encoded.push_str(&format!("\\u{:04x}", c as u32)),
+            c => encoded.push(c),
+        }
+    }
+    encoded.push('"');
+    encoded
+}
+
+/// Quotes a CSV field if it contains commas, quotes, or line breaks.
+fn csv_field(s: &str) -> String {
+    match s.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", s.replace('"', "\"\"")),
+        false => s.to_string(),
+    }
+}
+
+/// Returns the peak resident memory of the process in kilobytes. It is only available on Linux.
+fn peak_memory_kb() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmHWM:"))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Returns the number of atomic models and their total number of state transitions.
+fn count_atomics(model: &dyn Simulator) -> (usize, usize) {
+    match model.get_components()[..] {
+        [] if model.as_coupled().is_none() => (1, model.get_component().get_n_transitions()),
+        ref components => components.iter().fold((0, 0), |(n, t), c| {
+            let (n_c, t_c) = count_atomics(*c);
+            (n + n_c, t + t_c)
+        }),
+    }
+}
+
+/// Prints the progress of a simulation in a single line of the standard error.
+fn print_progress(progress: &Progress<f64>) {
+    let mut line = format!(
+        "\rSimulated time: {}, cycles: {} ({:.0} cycles/s), elapsed: {:.1?}",
+        progress.t,
+        progress.n_cycles,
+        progress.cycles_per_second(),
+        progress.elapsed
+    );
+    if let (Some(fraction), Some(eta)) = (progress.fraction(), progress.eta()) {
+        line.push_str(&format!(" ({:.1}%, ETA: {eta:.1?})", 100. * fraction));
+    }
+    // Pads the line to overwrite longer previous lines
+    eprint!("{line:<100}");
+    if progress.finished {
+        eprintln!();
+    }
+}
+
+/// Creates, optionally flattens or direct-connects, and simulates a model until `t_end`.
+fn measure(
+    command: &'static str,
+    model: String,
+    devstone: Option<Params>,
+    create: impl Fn() -> Result<Coupled, CliError>,
+    cli: &Cli,
+    t_end: f64,
+) -> Result<Record, CliError> {
+    let heap_start = heap_stats(true);
     let start = Instant::now();
-    let coupled = match model_type.as_str() {
-        "li" => LI::create(width, depth, int_delay, ext_delay),
-        "hi" => HI::create(width, depth, int_delay, ext_delay),
-        "ho" => HO::create(width, depth, int_delay, ext_delay),
-        "homod" => HOmod::create(width, depth, int_delay, ext_delay),
-        _ => panic!("unknown DEVStone model type"),
+    let coupled = create()?;
+    let creation = start.elapsed();
+    let (coupled, flattening) = match cli.flatten {
+        true => {
+            let start = Instant::now();
+            let coupled = coupled.flatten();
+            (coupled, Some(start.elapsed()))
+        }
+        false => (coupled, None),
     };
-    let duration = start.elapsed();
-    println!("Model creation time: {duration:?}");
+    let (coupled, direct_connection) = match cli.direct {
+        true => {
+            let start = Instant::now();
+            let coupled = coupled.direct_connect();
+            (coupled, Some(start.elapsed()))
+        }
+        false => (coupled, None),
+    };
+    if cli.stats {
+        print!("Model structure:\n{}", coupled.stats());
+    }
     let start = Instant::now();
     let mut simulator = RootCoordinator::new(coupled);
-    let duration = start.elapsed();
-    println!("Simulator creation time: {duration:?}");
+    let simulator_creation = start.elapsed();
+    let heap_model = heap_stats(false);
+    if cli.monitor {
+        let monitor = Monitor::default().formatter(ValueFormatter::new().debug::<usize>());
+        simulator.add_listener(Box::new(monitor));
+    }
     let start = Instant::now();
-    simulator.simulate(f64::INFINITY);
-    let duration = start.elapsed();
-    println!("Simulation time: {duration:?}");
+    let mut n_cycles = 0;
+    if cli.progress {
+        let interval = Duration::from_millis(200);
+        simulator.simulate_with_progress(t_end, interval, |progress| {
+            print_progress(progress);
+            n_cycles = progress.n_cycles;
+        });
+    } else {
+        let mut t_next = simulator.start_simulation();
+        while t_next < t_end {
+            t_next = simulator.step();
+            n_cycles += 1;
+        }
+        simulator.stop_simulation();
+    }
+    let simulation = start.elapsed();
+    let heap_end = heap_stats(false);
+    let (n_atomics, n_transitions) = count_atomics(&*simulator);
+    let heap = heap_start.zip(heap_model).zip(heap_end);
+    let heap = heap.map(|((start, model), end)| HeapUsage {
+        model_bytes: model.allocated.saturating_sub(start.allocated),
+        peak_bytes: end.peak.saturating_sub(start.allocated),
+        n_allocations: end.n_allocations - start.n_allocations,
+    });
+    Ok(Record {
+        command,
+        model,
+        devstone,
+        run: 0,
+        n_atomics,
+        creation,
+        flattening,
+        direct_connection,
+        simulator_creation,
+        simulation,
+        n_cycles,
+        n_transitions,
+        peak_memory_kb: peak_memory_kb(),
+        heap,
+    })
+}
+
+/// Prints the results of a simulation in the selected format.
+fn report(record: &Record, output: Output) {
+    match output {
+        Output::Text => {
+            if record.run > 0 {
+                println!();
+            }
+            record.print_text();
+        }
+        Output::Json => println!("{}", record.to_json()),
+        Output::Csv => println!("{}", record.to_csv()),
+    }
+}
+
+/// Simulates a DEVStone model or a model described in a JSON file as many times as requested.
+fn simulate(cli: &Cli) -> Result<(), CliError> {
+    if cli.output == Output::Csv {
+        println!("{}", Record::CSV_HEADER);
+    }
+    for run in 0..cli.repeat.unwrap_or(1) {
+        let mut record = match &cli.command {
+            Command::Devstone(params) => {
+                let create = || Ok(devstone::build(params));
+                let model = params.variant.to_string();
+                measure("devstone", model, Some(*params), create, cli, f64::INFINITY)?
+            }
+            Command::Run { path } => {
+                let create = || load_model(path);
+                measure("run", path.clone(), None, create, cli, cli.until)?
+            }
+            _ => unreachable!(),
+        };
+        record.run = run;
+        report(&record, cli.output);
+    }
+    Ok(())
+}
+
+/// Simulates every DEVStone model type several times. In text format, it prints the median
+/// simulation time of each model type. Otherwise, it prints the results of every simulation.
+fn bench(cli: &Cli) -> Result<(), CliError> {
+    let runs = cli.repeat.unwrap_or(10);
+    match cli.output {
+        Output::Text => println!("DEVStone benchmarks (median of {runs} runs)"),
+        Output::Csv => println!("{}", Record::CSV_HEADER),
+        Output::Json => (),
+    }
+    for variant in Variant::ALL {
+        let (width, depth) = match variant {
+            Variant::HOmod => (20, 20),
+            _ => (100, 100),
+        };
+        let params = Params::new(variant, width, depth).with_delays(cli.int_delay, cli.ext_delay());
+        let mut times = Vec::with_capacity(runs);
+        for run in 0..runs {
+            let create = || Ok(devstone::build(&params));
+            let model = variant.to_string();
+            let mut record = measure("bench", model, Some(params), create, cli, f64::INFINITY)?;
+            record.run = run;
+            match cli.output {
+                Output::Text => times.push(record.simulation),
+                output => report(&record, output),
+            }
+        }
+        if cli.output == Output::Text {
+            times.sort();
+            let median = times[runs / 2];
+            println!("{:>6} {width:>4}x{depth:<4} {median:>12?}", variant.name());
+        }
+    }
+    Ok(())
+}
+
+/// Speedup of a parallel simulation with a given number of threads.
+#[cfg(feature = "par_any")]
+#[derive(Debug)]
+struct Speedup {
+    threads: usize,
+    /// Median simulation time.
+    simulation: Duration,
+    /// Median simulation time with one thread divided by the median simulation time with `threads` threads.
+    speedup: f64,
+    /// Speedup divided by the number of threads.
+    efficiency: f64,
+}
+
+#[cfg(feature = "par_any")]
+impl Speedup {
+    /// Names of the CSV columns.
+    const CSV_HEADER: &'static str =
+        "model,width,depth,int_delay,ext_delay,threads,simulation_s,speedup,efficiency,features";
+
+    fn to_json(&self, params: &Params) -> String {
+        let features: Vec<_> = enabled_features().map(|f| format!("\"{f}\"")).collect();
+        format!(
+            "{{\"model\":\"{}\",\"width\":{},\"depth\":{},\"int_delay\":{},\"ext_delay\":{},\"threads\":{},\
+\"simulation_s\":{},\"speedup\":{},\"efficiency\":{},\"features\":[{}]}}",
+            params.variant,
+            params.width,
+            params.depth,
+            params.int_delay,
+            params.ext_delay,
+            self.threads,
+            self.simulation.as_secs_f64(),
+            self.speedup,
+            self.efficiency,
+            features.join(","),
+        )
+    }
+
+    fn to_csv(&self, params: &Params) -> String {
+        let features: Vec<_> = enabled_features().collect();
+        format!(
+            "{},{},{},{},{},{},{},{},{},{}",
+            params.variant,
+            params.width,
+            params.depth,
+            params.int_delay,
+            params.ext_delay,
+            self.threads,
+            self.simulation.as_secs_f64(),
+            self.speedup,
+            self.efficiency,
+            features.join(" "),
+        )
+    }
+}
+
+/// Returns the numbers of threads of a speedup report: powers of two up to `max_threads`,
+/// followed by `max_threads` if it is not a power of two.
+#[cfg(feature = "par_any")]
+fn thread_counts(max_threads: usize) -> Vec<usize> {
+    let mut counts: Vec<_> = std::iter::successors(Some(1_usize), |n| n.checked_mul(2))
+        .take_while(|&n| n < max_threads)
+        .collect();
+    counts.push(max_threads);
+    counts
+}
+
+/// Simulates a DEVStone model in thread pools of 1, 2, 4, ... threads and prints
+/// the median simulation time, the speedup, and the efficiency of every pool size.
+#[cfg(feature = "par_any")]
+fn speedup(params: &Params, cli: &Cli) -> Result<(), CliError> {
+    let max_threads = match cli.threads {
+        Some(threads) => threads,
+        None => std::thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+    let runs = cli.repeat.unwrap_or(3);
+    match cli.output {
+        Output::Text => {
+            let features: Vec<_> = enabled_features().collect();
+            println!(
+                "DEVStone {params} speedup (median of {runs} runs, features: {})",
+                features.join(", ")
+            );
+            println!(
+                "{:>7} {:>14} {:>8} {:>10}",
+                "threads", "time", "speedup", "efficiency"
+            );
+        }
+        Output::Csv => println!("{}", Speedup::CSV_HEADER),
+        Output::Json => (),
+    }
+    let mut sequential = None;
+    for threads in thread_counts(max_threads) {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .map_err(|e| CliError(format!("could not create a pool of {threads} threads: {e}")))?;
+        let mut times: Vec<_> = (0..runs)
+            .map(|_| {
+                // the simulator is not Send, so it is built and dropped inside the pool
+                pool.install(|| {
+                    let mut simulator = devstone::simulator(params);
+                    let start = Instant::now();
+                    devstone::simulate(&mut simulator);
+                    start.elapsed()
+                })
+            })
+            .collect();
+        times.sort();
+        let simulation = times[runs / 2];
+        let sequential = *sequential.get_or_insert(simulation);
+        let speedup = sequential.as_secs_f64() / simulation.as_secs_f64();
+        let result = Speedup {
+            threads,
+            simulation,
+            speedup,
+            efficiency: speedup / threads as f64,
+        };
+        match cli.output {
+            Output::Text => println!(
+                "{:>7} {:>14?} {:>8.2} {:>9.1}%",
+                result.threads,
+                result.simulation,
+                result.speedup,
+                100. * result.efficiency
+            ),
+            Output::Json => println!("{}", result.to_json(params)),
+            Output::Csv => println!("{}", result.to_csv(params)),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "par_any"))]
+fn speedup(_params: &Params, _cli: &Cli) -> Result<(), CliError> {
+    Err(CliError(
+        "speedup requires a par_* feature (e.g., --features par_all)".to_string(),
+    ))
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let cli = match Cli::parse(&args) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let result = match &cli.command {
+        Command::Devstone(_) | Command::Run { .. } => simulate(&cli),
+        Command::Bench => bench(&cli),
+        Command::Speedup(params) => speedup(params, &cli),
+        Command::Features => {
+            for &(feature, enabled) in RunRecord::all_features() {
+                let status = if enabled { "enabled" } else { "disabled" };
+                println!("{feature:<16} {status}");
+            }
+            Ok(())
+        }
+        Command::Help => {
+            println!("{USAGE}");
+            Ok(())
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
 }
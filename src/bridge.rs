@@ -0,0 +1,410 @@
+//! Atomic models that bridge simulations with other processes through UDP or TCP sockets
+//! (e.g., to connect two real-time simulations running on different machines, or to talk to external tools).
+//!
+//! Messages are exchanged as text: they are written with [`Display`] and parsed with [`FromStr`],
+//! so both must round-trip and messages must be written in a single line. UDP datagrams carry one message each,
+//! and TCP streams carry one message per line. Bridges are meant for real-time simulations
+//! (e.g., with the `simulate_rt` method of the `AsyncRootCoordinator`), as messages arrive in wall-clock time.
+//! With the `mqtt` feature, [`MqttPublisher`] and [`MqttSubscriber`] exchange messages through an MQTT broker instead
+//! (e.g., to feed digital twins with live sensor data). With the `shm` feature, [`ShmSender`] and [`ShmReceiver`]
+//! exchange messages through a memory-mapped ring buffer, so co-located simulations avoid the network stack.
+
+#[cfg(feature = "mqtt")]
+mod mqtt;
+#[cfg(all(feature = "shm", unix))]
+mod shm;
+
+#[cfg(feature = "mqtt")]
+pub use mqtt::{MqttPublisher, MqttSubscriber};
+#[cfg(all(feature = "shm", unix))]
+pub use shm::{ShmReceiver, ShmSender};
+
+use crate::modeling::{Atomic, Component, InPort, OutPort};
+use crate::stats::Counter;
+use crate::DynRef;
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Maximum time that background threads block on a socket before checking if the bridge was dropped.
+const POLL_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Maximum size of UDP datagrams.
+const MAX_DATAGRAM: usize = 65_507;
+
+/// Socket of a [`SocketSender`].
+enum Sink {
+    /// UDP socket and address of the remote peer.
+    Udp(UdpSocket, SocketAddr),
+    /// TCP connection with the remote peer.
+    Tcp(TcpStream),
+}
+
+/// Atomic model that writes the messages received through its `input` port to a UDP or TCP socket
+/// as soon as they arrive. It never sends messages, and it never triggers internal transitions.
+/// Messages that cannot be written are discarded and counted in its `errors` accumulator
+/// (see [`crate::simulation::RootCoordinator::stats`]).
+pub struct SocketSender<V: DynRef + Clone + Display> {
+    /// Component of the atomic model.
+    component: Component,
+    /// Socket for writing messages.
+    sink: Sink,
+    /// Number of messages that could not be written.
+    errors: Counter,
+    /// Input port for receiving messages.
+    input: InPort<V>,
+}
+
+impl<V: DynRef + Clone + Display> SocketSender<V> {
+    /// Creates a new sender that writes every message in a UDP datagram to the given address.
+    /// The local socket is bound to an ephemeral port of all the interfaces.
+    pub fn udp<A: ToSocketAddrs>(name: &str, addr: A) -> io::Result<Self> {
+        let addr = resolve(addr)?;
+        let local: SocketAddr = match addr {
+            SocketAddr::V4(_) => ([0, 0, 0, 0], 0).into(),
+            SocketAddr::V6(_) => ([0; 8], 0).into(),
+        };
+        Ok(Self::new(name, Sink::Udp(UdpSocket::bind(local)?, addr)))
+    }
+
+    /// Creates a new sender that connects to the given address and writes every message in a line of the TCP stream.
+    pub fn tcp<A: ToSocketAddrs>(name: &str, addr: A) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        Ok(Self::new(name, Sink::Tcp(stream)))
+    }
+
+    fn new(name: &str, sink: Sink) -> Self {
+        let mut component = Component::new(name);
+        let input = component.add_in_port::<V>("input");
+        let errors = component.add_counter("errors");
+        Self {
+            component,
+            sink,
+            errors,
+            input,
+        }
+    }
+
+    /// Writes a message to the socket.
+    fn write(&mut self, value: &V) -> io::Result<()> {
+        match &mut self.sink {
+            Sink::Udp(socket, addr) => socket.send_to(value.to_string().as_bytes(), *addr)?,
+            Sink::Tcp(stream) => {
+                writeln!(stream, "{value}")?;
+                0
+            }
+        };
+        Ok(())
+    }
+}
+
+impl<V: DynRef + Clone + Display> Atomic for SocketSender<V> {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn lambda(&self) {}
+
+    fn delta_int(&mut self) {}
+
+    fn delta_ext(&mut self, _e: f64) {
+        // Safety: reading messages on atomic model's input port at delta_ext
+        let values = unsafe { self.input.get_values() }.to_vec();
+        for value in values {
+            if self.write(&value).is_err() {
+                self.errors.increment();
+            }
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        f64::INFINITY
+    }
+}
+
+/// Messages received by the background threads of a [`SocketReceiver`], or the text of the messages
+/// that could not be parsed.
+type Inbox<V> = Arc<Mutex<VecDeque<Result<V, String>>>>;
+
+/// Atomic model that receives messages from a UDP or TCP socket and sends them through its `output` port.
+/// Background threads receive the messages, and the receiver polls them every `period` time units,
+/// starting at time 0. All the messages received since the last poll are sent at once.
+/// Messages that cannot be parsed are discarded and counted in its `errors` accumulator
+/// (see [`crate::simulation::RootCoordinator::stats`]). Background threads stop when the receiver is dropped.
+pub struct SocketReceiver<V: DynRef + Clone + FromStr> {
+    /// Component of the atomic model.
+    component: Component,
+    /// Local address of the socket.
+    addr: SocketAddr,
+    /// Polling period.
+    period: f64,
+    /// Time remaining until the next poll.
+    sigma: f64,
+    /// Messages received by the background threads.
+    inbox: Inbox<V>,
+    /// Messages to be sent.
+    pending: Vec<V>,
+    /// It is set to `true` when the receiver is dropped to stop the background threads.
+    closed: Arc<AtomicBool>,
+    /// Number of messages that could not be parsed.
+    errors: Counter,
+    /// Output port for sending the received messages.
+    output: OutPort<V>,
+}
+
+impl<V: DynRef + Clone + FromStr + Send> SocketReceiver<V> {
+    /// Creates a new receiver that binds a UDP socket to the given address and parses every datagram as a message.
+    /// It panics if `period` is not positive.
+    pub fn udp<A: ToSocketAddrs>(name: &str, addr: A, period: f64) -> io::Result<Self> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_read_timeout(Some(POLL_TIMEOUT))?;
+        let receiver = Self::new(name, socket.local_addr()?, period);
+        let (inbox, closed) = (receiver.inbox.clone(), receiver.closed.clone());
+        thread::spawn(move || {
+            let mut buf = vec![0; MAX_DATAGRAM];
+            while !closed.load(Ordering::SeqCst) {
+                match socket.recv(&mut buf) {
+                    Ok(n) => push(&inbox, &String::from_utf8_lossy(&buf[..n])),
+                    Err(e) if is_timeout(&e) => (),
+                    Err(_) => break,
+                }
+            }
+        });
+        Ok(receiver)
+    }
+
+    /// Creates a new receiver that listens for TCP connections on the given address and parses
+    /// every line of every connection as a message. It panics if `period` is not positive.
+    pub fn tcp<A: ToSocketAddrs>(name: &str, addr: A, period: f64) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let receiver = Self::new(name, listener.local_addr()?, period);
+        let (inbox, closed) = (receiver.inbox.clone(), receiver.closed.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if closed.load(Ordering::SeqCst) {
+                    break;
+                }
+                // failed connections are ignored
+                if let Ok(stream) = stream {
+                    let (inbox, closed) = (inbox.clone(), closed.clone());
+                    thread::spawn(move || read_lines(stream, &inbox, &closed));
+                }
+            }
+        });
+        Ok(receiver)
+    }
+
+    fn new(name: &str, addr: SocketAddr, period: f64) -> Self {
+        assert!(period > 0., "period must be positive");
+        let mut component = Component::new(name);
+        let output = component.add_out_port::<V>("output");
+        let errors = component.add_counter("errors");
+        Self {
+            component,
+            addr,
+            period,
+            sigma: 0.,
+            inbox: Arc::new(Mutex::new(VecDeque::new())),
+            pending: Vec::new(),
+            closed: Arc::new(AtomicBool::new(false)),
+            errors,
+            output,
+        }
+    }
+
+    /// Returns the local address of the socket. It is useful when binding to port 0.
+    #[inline]
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Moves the messages received by the background threads to the pending messages.
+    fn poll(&mut self) {
+        let received: Vec<_> = self.inbox.lock().unwrap().drain(..).collect();
+        for message in received {
+            match message {
+                Ok(value) => self.pending.push(value),
+                Err(_) => self.errors.increment(),
+            }
+        }
+        self.sigma = match self.pending.is_empty() {
+            true => self.period,
+            false => 0.,
+        };
+    }
+}
+
+impl<V: DynRef + Clone + FromStr + Send> Atomic for SocketReceiver<V> {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.pending.clear();
+        self.poll();
+    }
+
+    fn lambda(&self) {
+        for value in &self.pending {
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.output.add_value(value.clone()) };
+        }
+    }
+
+    fn delta_int(&mut self) {
+        match self.pending.is_empty() {
+            true => self.poll(),
+            false => {
+                self.pending.clear();
+                self.sigma = self.period;
+            }
+        }
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        self.sigma -= e;
+    }
+
+    fn ta(&self) -> f64 {
+        self.sigma
+    }
+}
+
+impl<V: DynRef + Clone + FromStr> Drop for SocketReceiver<V> {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::SeqCst);
+        // wakes up the TCP listener thread (if any) so it notices that the receiver is closed
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+/// Resolves an address to its first socket address.
+fn resolve<A: ToSocketAddrs>(addr: A) -> io::Result<SocketAddr> {
+    (addr.to_socket_addrs()?.next())
+        .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "no address to connect to"))
+}
+
+/// Returns `true` if the error is caused by a read timeout.
+fn is_timeout(e: &io::Error) -> bool {
+    matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+}
+
+/// Parses a message and pushes it to the inbox of a [`SocketReceiver`].
+fn push<V: FromStr>(inbox: &Mutex<VecDeque<Result<V, String>>>, text: &str) {
+    let text = text.trim_end_matches(['\r', '\n']);
+    let message = text.parse().map_err(|_| text.to_string());
+    inbox.lock().unwrap().push_back(message);
+}
+
+/// Reads the lines of a TCP connection until it is closed or the receiver is dropped.
+fn read_lines<V: FromStr>(
+    stream: TcpStream,
+    inbox: &Mutex<VecDeque<Result<V, String>>>,
+    closed: &AtomicBool,
+) {
+    if stream.set_read_timeout(Some(POLL_TIMEOUT)).is_err() {
+        return;
+    }
+    let mut reader = BufReader::new(stream);
+    // bytes read before a timeout are kept in the buffer until the line is complete
+    let mut line = Vec::new();
+    while !closed.load(Ordering::SeqCst) {
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => break,
+            Ok(_) => {
+                push(inbox, &String::from_utf8_lossy(&line));
+                line.clear();
+            }
+            Err(e) if is_timeout(&e) => (),
+            Err(_) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::Coupled;
+    use crate::scenario::{ScenarioPlayer, ScheduledEvent};
+    use crate::simulation::RootCoordinator;
+    use crate::stats::Summary;
+    use crate::tracing::PortRecorder;
+    use std::time::Instant;
+
+    /// Sends integers from a simulation to a receiver, and simulates the receiver once every message arrived.
+    /// `invalid` sends an invalid message to the receiver, as an external tool would do.
+    fn bridge(
+        sender: SocketSender<i32>,
+        receiver: SocketReceiver<i32>,
+        invalid: impl FnOnce(SocketAddr),
+    ) {
+        let schedule = [(0., 1), (0., 2), (1., 3)]
+            .into_iter()
+            .map(|(t, v)| ScheduledEvent::new(t, "output", v))
+            .collect();
+        let mut coupled = Coupled::new("sending");
+        coupled.add_component(Box::new(ScenarioPlayer::new("player", schedule)));
+        coupled.add_component(Box::new(sender));
+        coupled.add_ic("player", "output", "sender", "input");
+        RootCoordinator::new(coupled).simulate(f64::INFINITY);
+
+        invalid(receiver.local_addr());
+        let start = Instant::now();
+        while receiver.inbox.lock().unwrap().len() < 4 {
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "messages did not arrive"
+            );
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let mut coupled = Coupled::new("receiving");
+        coupled.add_component(Box::new(receiver));
+        let recorder = PortRecorder::<i32>::new().subscribe("receiving.receiver", "output");
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.add_listener(Box::new(recorder.clone()));
+        simulator.simulate(2.5);
+
+        // all the messages arrived before the simulation started, so they are sent at time 0
+        let mut series = recorder.series("receiving.receiver", "output");
+        series.sort_by_key(|(_, v)| *v);
+        assert_eq!(vec![(0., 1), (0., 2), (0., 3)], series);
+        let stats = simulator.stats();
+        let errors = stats.get("receiving.receiver", "errors");
+        assert!(matches!(errors, Some(Summary::Counter(1))));
+    }
+
+    #[test]
+    fn test_udp_bridge() {
+        let receiver = SocketReceiver::<i32>::udp("receiver", "127.0.0.1:0", 1.).unwrap();
+        let sender = SocketSender::<i32>::udp("sender", receiver.local_addr()).unwrap();
+        bridge(sender, receiver, |addr| {
+            let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+            socket.send_to(b"four", addr).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_tcp_bridge() {
+        let receiver = SocketReceiver::<i32>::tcp("receiver", "127.0.0.1:0", 1.).unwrap();
+        let sender = SocketSender::<i32>::tcp("sender", receiver.local_addr()).unwrap();
+        bridge(sender, receiver, |addr| {
+            writeln!(TcpStream::connect(addr).unwrap(), "four").unwrap();
+        });
+    }
+}
@@ -0,0 +1,466 @@
+//! Minimal MQTT 3.1.1 client for the [`MqttPublisher`] and [`MqttSubscriber`] atomic models.
+//! Messages are published and received with QoS 0, and connections use clean sessions
+//! without keep alive, so the broker never closes idle connections.
+
+use super::{is_timeout, push, Inbox, POLL_TIMEOUT};
+use crate::modeling::{Atomic, Component, InPort, OutPort};
+use crate::stats::Counter;
+use crate::DynRef;
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Maximum time to wait for the broker to acknowledge a connection or a subscription.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// First byte of the MQTT control packets used by the bridges.
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+const SUBSCRIBE: u8 = 0x82;
+const SUBACK: u8 = 0x90;
+const DISCONNECT: u8 = 0xE0;
+
+/// Atomic model that publishes the messages received through its `input` port to an MQTT topic
+/// as soon as they arrive. It never sends messages, and it never triggers internal transitions.
+/// Messages that cannot be published are discarded and counted in its `errors` accumulator
+/// (see [`crate::simulation::RootCoordinator::stats`]).
+pub struct MqttPublisher<V: DynRef + Clone + Display> {
+    /// Component of the atomic model.
+    component: Component,
+    /// Connection with the broker.
+    stream: TcpStream,
+    /// Topic of the published messages.
+    topic: String,
+    /// Number of messages that could not be published.
+    errors: Counter,
+    /// Input port for receiving messages.
+    input: InPort<V>,
+}
+
+impl<V: DynRef + Clone + Display> MqttPublisher<V> {
+    /// Creates a new publisher that connects to the broker at the given address
+    /// and publishes every message to `topic`.
+    pub fn new<A: ToSocketAddrs>(name: &str, broker: A, topic: &str) -> io::Result<Self> {
+        check_topic(topic)?;
+        let (stream, _) = connect(broker)?;
+        let mut component = Component::new(name);
+        let input = component.add_in_port::<V>("input");
+        let errors = component.add_counter("errors");
+        Ok(Self {
+            component,
+            stream,
+            topic: topic.to_string(),
+            errors,
+            input,
+        })
+    }
+
+    /// Publishes a message to the topic of the publisher.
+    fn publish(&mut self, value: &V) -> io::Result<()> {
+        let mut body = Vec::new();
+        put_string(&mut body, &self.topic);
+        body.extend_from_slice(value.to_string().as_bytes());
+        self.stream.write_all(&packet(PUBLISH, &body))
+    }
+}
+
+impl<V: DynRef + Clone + Display> Atomic for MqttPublisher<V> {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn lambda(&self) {}
+
+    fn delta_int(&mut self) {}
+
+    fn delta_ext(&mut self, _e: f64) {
+        // Safety: reading messages on atomic model's input port at delta_ext
+        let values = unsafe { self.input.get_values() }.to_vec();
+        for value in values {
+            if self.publish(&value).is_err() {
+                self.errors.increment();
+            }
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        f64::INFINITY
+    }
+}
+
+impl<V: DynRef + Clone + Display> Drop for MqttPublisher<V> {
+    fn drop(&mut self) {
+        let _ = self.stream.write_all(&packet(DISCONNECT, &[]));
+    }
+}
+
+/// Atomic model that subscribes to an MQTT topic filter and sends the received messages through its `output` port.
+/// A background thread receives the messages, and the subscriber polls them every `period` time units,
+/// starting at time 0. All the messages received since the last poll are sent at once.
+/// Messages that cannot be parsed are discarded and counted in its `errors` accumulator
+/// (see [`crate::simulation::RootCoordinator::stats`]). The background thread disconnects
+/// from the broker when the subscriber is dropped.
+pub struct MqttSubscriber<V: DynRef + Clone + FromStr> {
+    /// Component of the atomic model.
+    component: Component,
+    /// Polling period.
+    period: f64,
+    /// Time remaining until the next poll.
+    sigma: f64,
+    /// Messages received by the background thread.
+    inbox: Inbox<V>,
+    /// Messages to be sent.
+    pending: Vec<V>,
+    /// It is set to `true` when the subscriber is dropped to stop the background thread.
+    closed: Arc<AtomicBool>,
+    /// Number of messages that could not be parsed.
+    errors: Counter,
+    /// Output port for sending the received messages.
+    output: OutPort<V>,
+}
+
+impl<V: DynRef + Clone + FromStr + Send> MqttSubscriber<V> {
+    /// Creates a new subscriber that connects to the broker at the given address and subscribes to `filter`
+    /// (e.g., `"sensors/+/temperature"`). It returns once the broker acknowledged the subscription.
+    /// It panics if `period` is not positive.
+    pub fn new<A: ToSocketAddrs>(
+        name: &str,
+        broker: A,
+        filter: &str,
+        period: f64,
+    ) -> io::Result<Self> {
+        assert!(period > 0., "period must be positive");
+        check_topic(filter)?;
+        let (mut stream, mut buf) = connect(broker)?;
+        let mut body = 1u16.to_be_bytes().to_vec();
+        put_string(&mut body, filter);
+        body.push(0);
+        stream.write_all(&packet(SUBSCRIBE, &body))?;
+        let (header, body) = read_packet(&mut stream, &mut buf)?;
+        if header != SUBACK || body.get(2).is_none_or(|&code| code == 0x80) {
+            return Err(io::Error::other("broker refused the subscription"));
+        }
+        stream.set_read_timeout(Some(POLL_TIMEOUT))?;
+
+        let mut component = Component::new(name);
+        let output = component.add_out_port::<V>("output");
+        let errors = component.add_counter("errors");
+        let subscriber = Self {
+            component,
+            period,
+            sigma: 0.,
+            inbox: Arc::new(Mutex::new(VecDeque::new())),
+            pending: Vec::new(),
+            closed: Arc::new(AtomicBool::new(false)),
+            errors,
+            output,
+        };
+        let (inbox, closed) = (subscriber.inbox.clone(), subscriber.closed.clone());
+        thread::spawn(move || {
+            while !closed.load(Ordering::SeqCst) {
+                match read_packet(&mut stream, &mut buf) {
+                    Ok((header, body)) if header & 0xF0 == PUBLISH => {
+                        match payload(header, &body) {
+                            Some(payload) => push(&inbox, &String::from_utf8_lossy(payload)),
+                            None => break,
+                        }
+                    }
+                    Ok(_) => (),
+                    Err(e) if is_timeout(&e) => (),
+                    Err(_) => return,
+                }
+            }
+            let _ = stream.write_all(&packet(DISCONNECT, &[]));
+            let _ = stream.shutdown(Shutdown::Both);
+        });
+        Ok(subscriber)
+    }
+
+    /// Moves the messages received by the background thread to the pending messages.
+    fn poll(&mut self) {
+        let received: Vec<_> = self.inbox.lock().unwrap().drain(..).collect();
+        for message in received {
+            match message {
+                Ok(value) => self.pending.push(value),
+                Err(_) => self.errors.increment(),
+            }
+        }
+        self.sigma = match self.pending.is_empty() {
+            true => self.period,
+            false => 0.,
+        };
+    }
+}
+
+impl<V: DynRef + Clone + FromStr + Send> Atomic for MqttSubscriber<V> {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.pending.clear();
+        self.poll();
+    }
+
+    fn lambda(&self) {
+        for value in &self.pending {
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.output.add_value(value.clone()) };
+        }
+    }
+
+    fn delta_int(&mut self) {
+        match self.pending.is_empty() {
+            true => self.poll(),
+            false => {
+                self.pending.clear();
+                self.sigma = self.period;
+            }
+        }
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        self.sigma -= e;
+    }
+
+    fn ta(&self) -> f64 {
+        self.sigma
+    }
+}
+
+impl<V: DynRef + Clone + FromStr> Drop for MqttSubscriber<V> {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Checks that a topic (or topic filter) can be encoded in an MQTT packet.
+fn check_topic(topic: &str) -> io::Result<()> {
+    match topic.is_empty() || topic.len() > u16::MAX as usize {
+        true => Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "invalid MQTT topic",
+        )),
+        false => Ok(()),
+    }
+}
+
+/// Connects to a broker and waits for it to accept the connection.
+/// It returns the stream and the bytes received after the acknowledgement.
+fn connect<A: ToSocketAddrs>(broker: A) -> io::Result<(TcpStream, Vec<u8>)> {
+    let mut stream = TcpStream::connect(broker)?;
+    stream.set_nodelay(true)?;
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    // protocol name and level, clean session flag, no keep alive, and an empty client identifier
+    let body = [0, 4, b'M', b'Q', b'T', b'T', 4, 0x02, 0, 0, 0, 0];
+    stream.write_all(&packet(CONNECT, &body))?;
+    let mut buf = Vec::new();
+    match read_packet(&mut stream, &mut buf)? {
+        (CONNACK, body) if body.get(1) == Some(&0) => Ok((stream, buf)),
+        _ => Err(io::Error::other("broker refused the connection")),
+    }
+}
+
+/// Encodes an MQTT control packet with the given first byte and body.
+fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = vec![header];
+    let mut len = body.len();
+    loop {
+        let byte = (len % 128) as u8;
+        len /= 128;
+        match len {
+            0 => {
+                packet.push(byte);
+                break;
+            }
+            _ => packet.push(byte | 0x80),
+        }
+    }
+    packet.extend_from_slice(body);
+    packet
+}
+
+/// Appends a length-prefixed UTF-8 string to the body of a packet.
+fn put_string(body: &mut Vec<u8>, s: &str) {
+    body.extend_from_slice(&(s.len() as u16).to_be_bytes());
+    body.extend_from_slice(s.as_bytes());
+}
+
+/// Removes the first complete packet from `buf` and returns its first byte and its body.
+/// It returns [`None`] if the packet is not complete yet.
+fn parse_packet(buf: &mut Vec<u8>) -> io::Result<Option<(u8, Vec<u8>)>> {
+    let mut len = 0;
+    // the remaining length is encoded in up to four bytes
+    for i in 1..5 {
+        let Some(&byte) = buf.get(i) else {
+            return Ok(None);
+        };
+        len |= ((byte & 0x7F) as usize) << (7 * (i - 1));
+        if byte & 0x80 == 0 {
+            if buf.len() < i + 1 + len {
+                return Ok(None);
+            }
+            let body = buf[i + 1..i + 1 + len].to_vec();
+            let header = buf[0];
+            buf.drain(..i + 1 + len);
+            return Ok(Some((header, body)));
+        }
+    }
+    Err(io::Error::new(
+        ErrorKind::InvalidData,
+        "malformed MQTT packet",
+    ))
+}
+
+/// Reads from the stream until a complete packet is available. Bytes read before a timeout are kept in `buf`.
+fn read_packet(stream: &mut TcpStream, buf: &mut Vec<u8>) -> io::Result<(u8, Vec<u8>)> {
+    let mut chunk = [0; 4096];
+    loop {
+        if let Some(packet) = parse_packet(buf)? {
+            return Ok(packet);
+        }
+        match stream.read(&mut chunk)? {
+            0 => return Err(ErrorKind::UnexpectedEof.into()),
+            n => buf.extend_from_slice(&chunk[..n]),
+        }
+    }
+}
+
+/// Returns the payload of a PUBLISH packet, or [`None`] if the packet is malformed.
+fn payload(header: u8, body: &[u8]) -> Option<&[u8]> {
+    let topic_len = u16::from_be_bytes([*body.first()?, *body.get(1)?]) as usize;
+    // packets with QoS 1 or 2 carry a packet identifier after the topic
+    let start = 2 + topic_len + if header & 0x06 != 0 { 2 } else { 0 };
+    body.get(start..)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::Coupled;
+    use crate::scenario::{ScenarioPlayer, ScheduledEvent};
+    use crate::simulation::RootCoordinator;
+    use crate::stats::Summary;
+    use crate::tracing::PortRecorder;
+    use std::net::{SocketAddr, TcpListener};
+    use std::time::Instant;
+
+    /// Broker that accepts a subscriber and then a publisher, and forwards the messages
+    /// of the publisher to the subscriber. It returns the topic of every forwarded message.
+    fn broker() -> (SocketAddr, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = thread::spawn(move || {
+            let accept = |buf: &mut Vec<u8>| {
+                let mut stream = listener.accept().unwrap().0;
+                assert_eq!(CONNECT, read_packet(&mut stream, buf).unwrap().0);
+                stream.write_all(&[CONNACK, 2, 0, 0]).unwrap();
+                stream
+            };
+            let (mut sub_buf, mut pub_buf) = (Vec::new(), Vec::new());
+            let mut subscriber = accept(&mut sub_buf);
+            let (header, body) = read_packet(&mut subscriber, &mut sub_buf).unwrap();
+            assert_eq!((SUBSCRIBE, &b"sensors/#"[..]), (header, &body[4..13]));
+            subscriber
+                .write_all(&[SUBACK, 3, body[0], body[1], 0])
+                .unwrap();
+
+            let mut publisher = accept(&mut pub_buf);
+            let mut topics = Vec::new();
+            loop {
+                match read_packet(&mut publisher, &mut pub_buf).unwrap() {
+                    (PUBLISH, body) => {
+                        let len = u16::from_be_bytes([body[0], body[1]]) as usize;
+                        topics.push(String::from_utf8(body[2..2 + len].to_vec()).unwrap());
+                        subscriber.write_all(&packet(PUBLISH, &body)).unwrap();
+                    }
+                    (DISCONNECT, _) => break,
+                    (header, _) => panic!("unexpected packet {header:#x}"),
+                }
+            }
+            // an external tool publishes an invalid message
+            let mut body = Vec::new();
+            put_string(&mut body, "sensors/b");
+            body.extend_from_slice(b"four");
+            subscriber.write_all(&packet(PUBLISH, &body)).unwrap();
+            topics
+        });
+        (addr, handle)
+    }
+
+    #[test]
+    fn test_packet() {
+        let mut buf = packet(PUBLISH, &[7; 200]);
+        assert_eq!(&[PUBLISH, 0xC8, 0x01], &buf[..3]);
+        buf.extend_from_slice(&packet(DISCONNECT, &[]));
+        let mut partial = buf[..100].to_vec();
+        assert!(parse_packet(&mut partial).unwrap().is_none());
+        assert_eq!(
+            (PUBLISH, vec![7; 200]),
+            parse_packet(&mut buf).unwrap().unwrap()
+        );
+        assert_eq!(
+            (DISCONNECT, vec![]),
+            parse_packet(&mut buf).unwrap().unwrap()
+        );
+        assert!(buf.is_empty());
+        assert!(parse_packet(&mut vec![PUBLISH, 0x80, 0x80, 0x80, 0x80]).is_err());
+    }
+
+    #[test]
+    fn test_mqtt_bridge() {
+        let (addr, broker) = broker();
+        let subscriber = MqttSubscriber::<i32>::new("subscriber", addr, "sensors/#", 1.).unwrap();
+        let publisher = MqttPublisher::<i32>::new("publisher", addr, "sensors/a").unwrap();
+
+        let schedule = [(0., 1), (0., 2), (1., 3)]
+            .into_iter()
+            .map(|(t, v)| ScheduledEvent::new(t, "output", v))
+            .collect();
+        let mut coupled = Coupled::new("publishing");
+        coupled.add_component(Box::new(ScenarioPlayer::new("player", schedule)));
+        coupled.add_component(Box::new(publisher));
+        coupled.add_ic("player", "output", "publisher", "input");
+        // the publisher disconnects when the simulator is dropped
+        RootCoordinator::new(coupled).simulate(f64::INFINITY);
+        assert_eq!(vec!["sensors/a"; 3], broker.join().unwrap());
+
+        let start = Instant::now();
+        while subscriber.inbox.lock().unwrap().len() < 4 {
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "messages did not arrive"
+            );
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let mut coupled = Coupled::new("subscribing");
+        coupled.add_component(Box::new(subscriber));
+        let recorder = PortRecorder::<i32>::new().subscribe("subscribing.subscriber", "output");
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.add_listener(Box::new(recorder.clone()));
+        simulator.simulate(2.5);
+
+        // all the messages arrived before the simulation started, so they are sent at time 0
+        let series = recorder.series("subscribing.subscriber", "output");
+        assert_eq!(vec![(0., 1), (0., 2), (0., 3)], series);
+        let stats = simulator.stats();
+        let errors = stats.get("subscribing.subscriber", "errors");
+        assert!(matches!(errors, Some(Summary::Counter(1))));
+    }
+}
@@ -0,0 +1,449 @@
+//! Shared-memory transport for the [`ShmSender`] and [`ShmReceiver`] atomic models.
+//! Both models map the same file in memory, which contains a single-producer single-consumer ring buffer
+//! of length-prefixed messages. Files in a RAM-backed file system (e.g., `/dev/shm` in Linux) avoid disk writes.
+//! Each ring is a standalone single-producer single-consumer bridge: there is no distributed coordinator,
+//! so every process runs its own simulation and the rings do not synchronize their simulation clocks.
+
+use crate::modeling::{Atomic, Component, InPort, OutPort};
+use crate::stats::Counter;
+use crate::DynRef;
+use std::fmt::Display;
+use std::fs::{File, OpenOptions};
+use std::io::{self, ErrorKind};
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Identifier written at the beginning of ring buffer files.
+const MAGIC: u64 = u64::from_le_bytes(*b"XDEVSSHM");
+
+/// Size of the header of ring buffer files. The write and read positions are in different cache lines.
+const HEADER: usize = 128;
+
+/// Offsets of the fields of the header.
+const CAPACITY: usize = 8;
+const HEAD: usize = 64;
+const TAIL: usize = 96;
+
+/// Size of the length prefix of every message.
+const PREFIX: usize = 4;
+
+/// Ring buffer of messages in a memory-mapped file. Positions of the writer (head) and the reader (tail)
+/// only grow, and they are wrapped around the capacity of the buffer when accessing the data.
+struct Ring {
+    /// Address of the mapping.
+    ptr: *mut u8,
+    /// Number of bytes for messages.
+    capacity: u64,
+}
+
+// Safety: the mapping is only accessed through atomic positions that hand over regions of the
+// buffer between exactly one writer and one reader, so rings can be moved and shared among threads.
+unsafe impl Send for Ring {}
+unsafe impl Sync for Ring {}
+
+impl Ring {
+    /// Creates a new ring buffer file with room for `capacity` bytes of messages (including their prefixes).
+    /// Existing files are overwritten.
+    fn create(path: &Path, capacity: usize) -> io::Result<Self> {
+        if capacity <= PREFIX {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "capacity is too small",
+            ));
+        }
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.set_len((HEADER + capacity) as u64)?;
+        let ring = Self::map(&file, capacity as u64)?;
+        // Safety: the file was just created, so no other process is using the header yet
+        unsafe {
+            ring.atomic(HEAD).store(0, Ordering::Relaxed);
+            ring.atomic(TAIL).store(0, Ordering::Relaxed);
+            ring.atomic(CAPACITY)
+                .store(capacity as u64, Ordering::Relaxed);
+            ring.atomic(0).store(MAGIC, Ordering::Release);
+        }
+        Ok(ring)
+    }
+
+    /// Opens an existing ring buffer file.
+    fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let len = file.metadata()?.len();
+        let invalid = || io::Error::new(ErrorKind::InvalidData, "not a ring buffer file");
+        if len <= HEADER as u64 {
+            return Err(invalid());
+        }
+        let ring = Self::map(&file, len - HEADER as u64)?;
+        // Safety: the mapping is larger than the header
+        let (magic, capacity) = unsafe {
+            let magic = ring.atomic(0).load(Ordering::Acquire);
+            (magic, ring.atomic(CAPACITY).load(Ordering::Relaxed))
+        };
+        match magic == MAGIC && capacity == ring.capacity {
+            true => Ok(ring),
+            false => Err(invalid()),
+        }
+    }
+
+    /// Maps a ring buffer file with room for `capacity` bytes of messages in memory.
+    fn map(file: &File, capacity: u64) -> io::Result<Self> {
+        let len = HEADER + capacity as usize;
+        // Safety: we map a file that is at least `len` bytes long, and we check the result
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        match ptr == libc::MAP_FAILED {
+            true => Err(io::Error::last_os_error()),
+            false => Ok(Self {
+                ptr: ptr as *mut u8,
+                capacity,
+            }),
+        }
+    }
+
+    /// Returns a reference to the 8-byte field of the header at the given offset.
+    ///
+    /// # Safety
+    ///
+    /// The offset must be one of the fields of the header.
+    unsafe fn atomic(&self, offset: usize) -> &AtomicU64 {
+        &*(self.ptr.add(offset) as *const AtomicU64)
+    }
+
+    /// Copies bytes to the buffer, starting at the given position.
+    ///
+    /// # Safety
+    ///
+    /// Only the writer can call this method, and the region must be free.
+    unsafe fn write_at(&self, pos: u64, bytes: &[u8]) {
+        let start = (pos % self.capacity) as usize;
+        let first = bytes.len().min(self.capacity as usize - start);
+        let data = self.ptr.add(HEADER);
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), data.add(start), first);
+        std::ptr::copy_nonoverlapping(bytes.as_ptr().add(first), data, bytes.len() - first);
+    }
+
+    /// Copies bytes from the buffer, starting at the given position.
+    ///
+    /// # Safety
+    ///
+    /// Only the reader can call this method, and the region must contain a message written by the writer.
+    unsafe fn read_at(&self, pos: u64, bytes: &mut [u8]) {
+        let start = (pos % self.capacity) as usize;
+        let first = bytes.len().min(self.capacity as usize - start);
+        let data = self.ptr.add(HEADER);
+        std::ptr::copy_nonoverlapping(data.add(start), bytes.as_mut_ptr(), first);
+        std::ptr::copy_nonoverlapping(data, bytes.as_mut_ptr().add(first), bytes.len() - first);
+    }
+
+    /// Writes a message to the buffer. It returns `false` if there is not enough free space for it.
+    /// Only one process may write messages to a ring buffer.
+    fn push(&self, message: &[u8]) -> bool {
+        let len = (PREFIX + message.len()) as u64;
+        // Safety: the writer only writes to free regions, and then hands them to the reader
+        unsafe {
+            let head = self.atomic(HEAD).load(Ordering::Relaxed);
+            let tail = self.atomic(TAIL).load(Ordering::Acquire);
+            if message.len() > u32::MAX as usize || self.capacity - (head - tail) < len {
+                return false;
+            }
+            self.write_at(head, &(message.len() as u32).to_le_bytes());
+            self.write_at(head + PREFIX as u64, message);
+            self.atomic(HEAD).store(head + len, Ordering::Release);
+        }
+        true
+    }
+
+    /// Reads all the messages available in the buffer and frees their space.
+    /// Only one process may read messages from a ring buffer.
+    fn drain(&self) -> Vec<Vec<u8>> {
+        let mut messages = Vec::new();
+        // Safety: the reader only reads regions handed by the writer, and then frees them
+        unsafe {
+            let head = self.atomic(HEAD).load(Ordering::Acquire);
+            let mut tail = self.atomic(TAIL).load(Ordering::Relaxed);
+            while tail < head {
+                let mut prefix = [0; PREFIX];
+                self.read_at(tail, &mut prefix);
+                let len = u32::from_le_bytes(prefix) as u64;
+                // corrupted prefixes must not make the reader go past the messages written
+                if len > (head - tail).saturating_sub(PREFIX as u64) {
+                    tail = head;
+                    break;
+                }
+                let mut message = vec![0; len as usize];
+                self.read_at(tail + PREFIX as u64, &mut message);
+                tail += (PREFIX + message.len()) as u64;
+                messages.push(message);
+            }
+            self.atomic(TAIL).store(tail, Ordering::Release);
+        }
+        messages
+    }
+}
+
+impl Drop for Ring {
+    fn drop(&mut self) {
+        // Safety: the mapping was created with this address and length, and it is not used anymore
+        unsafe {
+            libc::munmap(
+                self.ptr as *mut libc::c_void,
+                HEADER + self.capacity as usize,
+            )
+        };
+    }
+}
+
+/// Atomic model that writes the messages received through its `input` port to a shared-memory ring buffer
+/// as soon as they arrive. It never sends messages, and it never triggers internal transitions.
+/// Messages that do not fit in the buffer are discarded and counted in its `errors` accumulator
+/// (see [`crate::simulation::RootCoordinator::stats`]).
+pub struct ShmSender<V: DynRef + Clone + Display> {
+    /// Component of the atomic model.
+    component: Component,
+    /// Ring buffer for writing messages.
+    ring: Ring,
+    /// Number of messages that could not be written.
+    errors: Counter,
+    /// Input port for receiving messages.
+    input: InPort<V>,
+}
+
+impl<V: DynRef + Clone + Display> ShmSender<V> {
+    /// Creates a new sender that writes to the ring buffer file created by a [`ShmReceiver`] in `path`.
+    pub fn open<P: AsRef<Path>>(name: &str, path: P) -> io::Result<Self> {
+        let ring = Ring::open(path.as_ref())?;
+        let mut component = Component::new(name);
+        let input = component.add_in_port::<V>("input");
+        let errors = component.add_counter("errors");
+        Ok(Self {
+            component,
+            ring,
+            errors,
+            input,
+        })
+    }
+}
+
+impl<V: DynRef + Clone + Display> Atomic for ShmSender<V> {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn lambda(&self) {}
+
+    fn delta_int(&mut self) {}
+
+    fn delta_ext(&mut self, _e: f64) {
+        // Safety: reading messages on atomic model's input port at delta_ext
+        for value in unsafe { self.input.get_values() } {
+            if !self.ring.push(value.to_string().as_bytes()) {
+                self.errors.increment();
+            }
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        f64::INFINITY
+    }
+}
+
+/// Atomic model that reads messages from a shared-memory ring buffer and sends them through its `output` port.
+/// The receiver polls the buffer every `period` time units, starting at time 0.
+/// All the messages written since the last poll are sent at once.
+/// Messages that cannot be parsed are discarded and counted in its `errors` accumulator
+/// (see [`crate::simulation::RootCoordinator::stats`]).
+pub struct ShmReceiver<V: DynRef + Clone + FromStr> {
+    /// Component of the atomic model.
+    component: Component,
+    /// Ring buffer for reading messages.
+    ring: Ring,
+    /// Polling period.
+    period: f64,
+    /// Time remaining until the next poll.
+    sigma: f64,
+    /// Messages to be sent.
+    pending: Vec<V>,
+    /// Number of messages that could not be parsed.
+    errors: Counter,
+    /// Output port for sending the received messages.
+    output: OutPort<V>,
+}
+
+impl<V: DynRef + Clone + FromStr> ShmReceiver<V> {
+    /// Creates a new receiver that creates a ring buffer file in `path` with room for `capacity` bytes of messages.
+    /// Every message takes four bytes plus the length of its text. Existing files are overwritten,
+    /// so the receiver must be created before its sender. It panics if `period` is not positive.
+    pub fn create<P: AsRef<Path>>(
+        name: &str,
+        path: P,
+        capacity: usize,
+        period: f64,
+    ) -> io::Result<Self> {
+        assert!(period > 0., "period must be positive");
+        let ring = Ring::create(path.as_ref(), capacity)?;
+        let mut component = Component::new(name);
+        let output = component.add_out_port::<V>("output");
+        let errors = component.add_counter("errors");
+        Ok(Self {
+            component,
+            ring,
+            period,
+            sigma: 0.,
+            pending: Vec::new(),
+            errors,
+            output,
+        })
+    }
+
+    /// Moves the messages written to the ring buffer to the pending messages.
+    fn poll(&mut self) {
+        for message in self.ring.drain() {
+            match String::from_utf8_lossy(&message).parse() {
+                Ok(value) => self.pending.push(value),
+                Err(_) => self.errors.increment(),
+            }
+        }
+        self.sigma = match self.pending.is_empty() {
+            true => self.period,
+            false => 0.,
+        };
+    }
+}
+
+impl<V: DynRef + Clone + FromStr> Atomic for ShmReceiver<V> {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.pending.clear();
+        self.poll();
+    }
+
+    fn lambda(&self) {
+        for value in &self.pending {
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.output.add_value(value.clone()) };
+        }
+    }
+
+    fn delta_int(&mut self) {
+        match self.pending.is_empty() {
+            true => self.poll(),
+            false => {
+                self.pending.clear();
+                self.sigma = self.period;
+            }
+        }
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        self.sigma -= e;
+    }
+
+    fn ta(&self) -> f64 {
+        self.sigma
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::Coupled;
+    use crate::scenario::{ScenarioPlayer, ScheduledEvent};
+    use crate::simulation::RootCoordinator;
+    use crate::stats::Summary;
+    use crate::tracing::PortRecorder;
+    use std::path::PathBuf;
+
+    /// Returns the path of a new ring buffer file for a test.
+    fn ring_path(test: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("xdevs_{test}_{}.ring", std::process::id()))
+    }
+
+    #[test]
+    fn test_ring() {
+        let path = ring_path("ring");
+        let writer = Ring::create(&path, 16).unwrap();
+        let reader = Ring::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(writer.push(b"abcdef"));
+        assert!(!writer.push(b"ghijkl"));
+        assert_eq!(vec![b"abcdef".to_vec()], reader.drain());
+        // the next messages wrap around the end of the buffer
+        assert!(writer.push(b"ghijkl"));
+        assert!(writer.push(b"m"));
+        assert!(!writer.push(b""));
+        assert_eq!(vec![b"ghijkl".to_vec(), b"m".to_vec()], reader.drain());
+        assert!(reader.drain().is_empty());
+        assert!(!writer.push(&[0; 13]));
+        assert!(writer.push(&[0; 12]));
+    }
+
+    #[test]
+    fn test_open_invalid() {
+        let path = ring_path("invalid");
+        std::fs::write(&path, [0; HEADER + 16]).unwrap();
+        let err = Ring::open(&path).err().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn test_shm_bridge() {
+        let path = ring_path("bridge");
+        let receiver = ShmReceiver::<i32>::create("receiver", &path, 1024, 1.).unwrap();
+        let sender = ShmSender::<i32>::open("sender", &path).unwrap();
+        let external = Ring::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let schedule = [(0., 1), (0., 2), (1., 3)]
+            .into_iter()
+            .map(|(t, v)| ScheduledEvent::new(t, "output", v))
+            .collect();
+        let mut coupled = Coupled::new("sending");
+        coupled.add_component(Box::new(ScenarioPlayer::new("player", schedule)));
+        coupled.add_component(Box::new(sender));
+        coupled.add_ic("player", "output", "sender", "input");
+        RootCoordinator::new(coupled).simulate(f64::INFINITY);
+        // an external tool writes an invalid message after the sender finished
+        assert!(external.push(b"four"));
+
+        let mut coupled = Coupled::new("receiving");
+        coupled.add_component(Box::new(receiver));
+        let recorder = PortRecorder::<i32>::new().subscribe("receiving.receiver", "output");
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.add_listener(Box::new(recorder.clone()));
+        simulator.simulate(2.5);
+
+        // all the messages were written before the simulation started, so they are sent at time 0
+        let series = recorder.series("receiving.receiver", "output");
+        assert_eq!(vec![(0., 1), (0., 2), (0., 3)], series);
+        let stats = simulator.stats();
+        let errors = stats.get("receiving.receiver", "errors");
+        assert!(matches!(errors, Some(Summary::Counter(1))));
+    }
+}
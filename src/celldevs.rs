@@ -0,0 +1,306 @@
+mod atomic;
+
+use crate::modeling::Coupled;
+use crate::DynRef;
+use atomic::CellAtomic;
+use std::fmt::Debug;
+
+/// Coordinates of a cell in the lattice (row, column).
+pub type Coordinates = (usize, usize);
+/// Relative position of a neighbor with respect to the origin cell (row offset, column offset).
+pub type Offset = (isize, isize);
+/// Messages exchanged by cells: coordinates of the sender and its new state.
+pub type CellMessage<S> = (Coordinates, S);
+
+/// Interface for the state of Cell-DEVS cells.
+pub trait Cell: DynRef + Clone + PartialEq {
+    /// Local computation function. It returns the next state of the cell given its current
+    /// state and the state of its neighbors. Neighbors outside the lattice are omitted.
+    fn local_computation(&self, neighbors: &[(Offset, Self)]) -> Self;
+
+    /// Delay function. It returns the time between the computation of a new state
+    /// and its propagation to the neighboring cells. By default, it returns 1.
+    #[inline]
+    fn delay(&self) -> f64 {
+        1.
+    }
+}
+
+/// Neighborhood shapes supported by Cell-DEVS lattices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// Moore neighborhood of the given range (i.e., square around the cell).
+    Moore(usize),
+    /// von Neumann neighborhood of the given range (i.e., diamond around the cell).
+    VonNeumann(usize),
+    /// Custom list of relative positions. The origin (`(0, 0)`) is ignored.
+    Custom(Vec<Offset>),
+}
+
+impl Neighborhood {
+    /// Returns the relative positions of the neighbors of a cell. The origin is not included.
+    pub fn offsets(&self) -> Vec<Offset> {
+        let square = |range: usize| {
+            let range = range as isize;
+            (-range..=range).flat_map(move |i| (-range..=range).map(move |j| (i, j)))
+        };
+        let offsets: Vec<Offset> = match self {
+            Self::Moore(range) => square(*range).collect(),
+            Self::VonNeumann(range) => square(*range)
+                .filter(|(i, j)| i.unsigned_abs() + j.unsigned_abs() <= *range)
+                .collect(),
+            Self::Custom(offsets) => offsets.clone(),
+        };
+        offsets.into_iter().filter(|&o| o != (0, 0)).collect()
+    }
+}
+
+/// Delay semantics of the cells when they compute a new state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DelayType {
+    /// A new state preempts any previously scheduled state that has not been propagated yet.
+    Inertial,
+    /// All the new states are propagated in order, each after its own delay.
+    Transport,
+}
+
+/// Builder of two-dimensional Cell-DEVS lattices.
+/// It creates a [`Coupled`] model with one atomic model per cell and all the neighbor couplings.
+/// Every cell is named `cell(row,column)` and has an input port `input` and an output port `output`.
+/// The lattice forwards every cell output to its own `output` port.
+#[derive(Debug, Clone)]
+pub struct CellSpace {
+    /// Name of the resulting coupled model.
+    name: String,
+    /// Number of rows and columns of the lattice.
+    shape: (usize, usize),
+    /// Neighborhood of every cell.
+    neighborhood: Neighborhood,
+    /// If `true`, the lattice borders wrap around (i.e., the lattice is a torus).
+    wrapped: bool,
+    /// Delay semantics of the cells.
+    delay_type: DelayType,
+}
+
+impl CellSpace {
+    /// Creates a new lattice builder with the given name and shape.
+    /// By default, it uses a Moore neighborhood of range 1, no wrapping, and inertial delays.
+    pub fn new(name: &str, rows: usize, columns: usize) -> Self {
+        if rows < 1 || columns < 1 {
+            panic!("lattice shape must be at least 1x1")
+        }
+        Self {
+            name: name.to_string(),
+            shape: (rows, columns),
+            neighborhood: Neighborhood::Moore(1),
+            wrapped: false,
+            delay_type: DelayType::Inertial,
+        }
+    }
+
+    /// Sets the neighborhood of the cells.
+    pub fn neighborhood(mut self, neighborhood: Neighborhood) -> Self {
+        self.neighborhood = neighborhood;
+        self
+    }
+
+    /// Sets whether the lattice borders wrap around.
+    pub fn wrapped(mut self, wrapped: bool) -> Self {
+        self.wrapped = wrapped;
+        self
+    }
+
+    /// Sets the delay semantics of the cells.
+    pub fn delay_type(mut self, delay_type: DelayType) -> Self {
+        self.delay_type = delay_type;
+        self
+    }
+
+    /// Returns the coordinates of the neighbor at the given offset of a cell.
+    /// If the neighbor lies outside the lattice, it returns [`None`].
+    fn neighbor(&self, (row, column): Coordinates, (i, j): Offset) -> Option<Coordinates> {
+        let (rows, columns) = (self.shape.0 as isize, self.shape.1 as isize);
+        let (mut row, mut column) = (row as isize + i, column as isize + j);
+        if self.wrapped {
+            row = row.rem_euclid(rows);
+            column = column.rem_euclid(columns);
+        } else if row < 0 || row >= rows || column < 0 || column >= columns {
+            return None;
+        }
+        Some((row as usize, column as usize))
+    }
+
+    /// Creates the Cell-DEVS coupled model. `initial` returns the initial state of each cell.
+    pub fn build<S: Cell>(&self, initial: impl Fn(Coordinates) -> S) -> Coupled {
+        let (rows, columns) = self.shape;
+        let offsets = self.neighborhood.offsets();
+        let mut coupled = Coupled::new(&self.name);
+        coupled.add_out_port::<CellMessage<S>>("output");
+
+        // cells are added before coupling them, so couplings can be checked
+        let mut sources = Vec::with_capacity(rows * columns);
+        for row in 0..rows {
+            for column in 0..columns {
+                let coordinates = (row, column);
+                let neighbors: Vec<_> = offsets
+                    .iter()
+                    .filter_map(|&o| Some((o, self.neighbor(coordinates, o)?)))
+                    .map(|(o, c)| (o, c, initial(c)))
+                    .collect();
+                let mut cell_sources: Vec<_> = neighbors.iter().map(|(_, c, _)| *c).collect();
+                cell_sources.sort_unstable();
+                cell_sources.dedup();
+                sources.push((coordinates, cell_sources));
+                coupled.add_component(Box::new(CellAtomic::new(
+                    coordinates,
+                    initial(coordinates),
+                    neighbors,
+                    self.delay_type,
+                )));
+            }
+        }
+        let name = |(row, column): Coordinates| format!("cell({row},{column})");
+        for (coordinates, cell_sources) in sources {
+            let cell = name(coordinates);
+            for source in cell_sources {
+                coupled.add_ic(&name(source), "output", &cell, "input");
+            }
+            coupled.add_eoc(&cell, "output", "output");
+        }
+        coupled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{Listener, PortValues, RootCoordinator};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Infected(bool);
+
+    impl Cell for Infected {
+        fn local_computation(&self, neighbors: &[(Offset, Self)]) -> Self {
+            Self(self.0 || neighbors.iter().any(|(_, n)| n.0))
+        }
+    }
+
+    struct Infections(Arc<Mutex<Vec<(f64, Coordinates)>>>);
+
+    impl Listener for Infections {
+        fn on_output(&mut self, t: f64, path: &str, _port: &str, values: &PortValues) {
+            if path == "lattice" {
+                let values = values.downcast::<CellMessage<Infected>>();
+                for (coordinates, _) in values.unwrap() {
+                    self.0.lock().unwrap().push((t, *coordinates));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_neighborhoods() {
+        assert_eq!(8, Neighborhood::Moore(1).offsets().len());
+        assert_eq!(24, Neighborhood::Moore(2).offsets().len());
+        assert_eq!(4, Neighborhood::VonNeumann(1).offsets().len());
+        assert_eq!(12, Neighborhood::VonNeumann(2).offsets().len());
+        assert_eq!(
+            1,
+            Neighborhood::Custom(vec![(0, 0), (0, 1)]).offsets().len()
+        );
+    }
+
+    #[test]
+    fn test_propagation() {
+        for delay_type in [DelayType::Inertial, DelayType::Transport] {
+            let n = 10;
+            let lattice = CellSpace::new("lattice", 1, n)
+                .neighborhood(Neighborhood::VonNeumann(1))
+                .delay_type(delay_type)
+                .build(|(_, column)| Infected(column == 0));
+            assert_eq!(n, lattice.n_components());
+            assert_eq!(2 * (n - 1), lattice.n_ics());
+
+            let infections = Arc::new(Mutex::new(Vec::new()));
+            let mut simulator = RootCoordinator::new(lattice);
+            simulator.add_listener(Box::new(Infections(infections.clone())));
+            simulator.simulate(f64::INFINITY);
+
+            let infections = infections.lock().unwrap();
+            let expected: Vec<_> = (1..n).map(|i| (i as f64, (0, i))).collect();
+            assert_eq!(expected, *infections);
+        }
+    }
+
+    /// Cell that counts up to 2 (leader) or follows its neighbor with a state-dependent delay (follower).
+    #[derive(Debug, Clone, PartialEq)]
+    struct Counter {
+        leader: bool,
+        n: usize,
+    }
+
+    impl Cell for Counter {
+        fn local_computation(&self, neighbors: &[(Offset, Self)]) -> Self {
+            let n = match self.leader {
+                true => (self.n + 1).min(2),
+                false => neighbors[0].1.n,
+            };
+            Self { n, ..*self }
+        }
+
+        fn delay(&self) -> f64 {
+            match (self.leader, self.n) {
+                (false, 1) => 10.,
+                _ => 1.,
+            }
+        }
+    }
+
+    struct Counts(Arc<Mutex<Vec<(f64, Coordinates, usize)>>>);
+
+    impl Listener for Counts {
+        fn on_output(&mut self, t: f64, path: &str, _port: &str, values: &PortValues) {
+            if path == "lattice" {
+                let values = values.downcast::<CellMessage<Counter>>();
+                for (coordinates, state) in values.unwrap() {
+                    self.0.lock().unwrap().push((t, *coordinates, state.n));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_state_dependent_delay() {
+        let lattice = CellSpace::new("lattice", 1, 2)
+            .delay_type(DelayType::Transport)
+            .build(|(_, column)| Counter {
+                leader: column == 0,
+                n: 0,
+            });
+        let counts = Arc::new(Mutex::new(Vec::new()));
+        let mut simulator = RootCoordinator::new(lattice);
+        simulator.add_listener(Box::new(Counts(counts.clone())));
+        simulator.simulate(f64::INFINITY);
+
+        // the follower schedules 1 at time 11 and then 2 at time 3, so they are propagated out of order
+        // and it must recompute its state once 1 is propagated
+        let expected = vec![
+            (1., (0, 0), 1),
+            (2., (0, 0), 2),
+            (3., (0, 1), 2),
+            (11., (0, 1), 1),
+            (12., (0, 1), 2),
+        ];
+        assert_eq!(expected, *counts.lock().unwrap());
+    }
+
+    #[test]
+    fn test_wrapped() {
+        let lattice = CellSpace::new("lattice", 3, 3)
+            .wrapped(true)
+            .build(|coordinates| Infected(coordinates == (0, 0)));
+        // every cell is a neighbor of all the other cells
+        assert_eq!(9 * 8, lattice.n_ics());
+    }
+}
@@ -0,0 +1,127 @@
+use super::{Cell, CellMessage, Coordinates, DelayType, Offset};
+use crate::modeling::*;
+use std::collections::VecDeque;
+
+/// Atomic model of a Cell-DEVS cell.
+pub(super) struct CellAtomic<S: Cell> {
+    component: Component,
+    input: InPort<CellMessage<S>>,
+    output: OutPort<CellMessage<S>>,
+    /// Coordinates of the cell in the lattice.
+    coordinates: Coordinates,
+    /// Current state of the cell.
+    state: S,
+    /// Last known state of the neighbors, with their relative position and coordinates.
+    neighbors: Vec<(Offset, Coordinates, S)>,
+    /// Delay semantics of the cell.
+    delay_type: DelayType,
+    /// Local clock of the cell. It is relative to the simulation start time.
+    clock: f64,
+    /// States waiting to be propagated, sorted by their scheduled propagation time (local clock).
+    queue: VecDeque<(f64, S)>,
+    /// Last state that was scheduled. While the queue is not empty, new states are compared against it.
+    last: Option<S>,
+}
+
+impl<S: Cell> CellAtomic<S> {
+    pub(super) fn new(
+        coordinates: Coordinates,
+        state: S,
+        neighbors: Vec<(Offset, Coordinates, S)>,
+        delay_type: DelayType,
+    ) -> Self {
+        let (row, column) = coordinates;
+        let mut component = Component::new(&format!("cell({row},{column})"));
+        let input = component.add_in_port("input");
+        let output = component.add_out_port("output");
+        Self {
+            component,
+            input,
+            output,
+            coordinates,
+            state,
+            neighbors,
+            delay_type,
+            clock: 0.,
+            queue: VecDeque::new(),
+            last: None,
+        }
+    }
+
+    /// Executes the local computation function and schedules the new state if it changed.
+    /// With transport delays, delays may depend on the state, so states are inserted in order of propagation time.
+    fn compute(&mut self) {
+        let neighbors: Vec<_> = self
+            .neighbors
+            .iter()
+            .map(|(o, _, s)| (*o, s.clone()))
+            .collect();
+        let next = self.state.local_computation(&neighbors);
+        let last = match self.queue.is_empty() {
+            true => &self.state,
+            false => self.last.as_ref().unwrap_or(&self.state),
+        };
+        if next != *last {
+            let t = self.clock + next.delay();
+            if self.delay_type == DelayType::Inertial {
+                self.queue.clear();
+            }
+            // states scheduled for the same time are propagated in order of computation
+            let i = self.queue.partition_point(|(t_queued, _)| *t_queued <= t);
+            self.queue.insert(i, (t, next.clone()));
+            self.last = Some(next);
+        }
+    }
+}
+
+impl<S: Cell> Atomic for CellAtomic<S> {
+    #[inline]
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    #[inline]
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.clock = 0.;
+        self.queue.clear();
+        self.last = None;
+        self.compute();
+    }
+
+    fn lambda(&self) {
+        if let Some((_, state)) = self.queue.front() {
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.output.add_value((self.coordinates, state.clone())) };
+        }
+    }
+
+    fn delta_int(&mut self) {
+        if let Some((t, state)) = self.queue.pop_front() {
+            self.clock = t;
+            self.state = state;
+        }
+        self.compute();
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        self.clock += e;
+        // Safety: reading messages on atomic model's input port at delta_ext
+        for (coordinates, state) in unsafe { self.input.get_values() } {
+            self.neighbors
+                .iter_mut()
+                .filter(|(_, c, _)| c == coordinates)
+                .for_each(|(_, _, s)| *s = state.clone());
+        }
+        self.compute();
+    }
+
+    fn ta(&self) -> f64 {
+        self.queue
+            .front()
+            .map_or(f64::INFINITY, |(t, _)| t - self.clock)
+    }
+}
@@ -1,9 +1,11 @@
 mod atomic;
+pub mod bench;
 pub mod hi;
 pub mod ho;
 pub mod homod;
 pub mod li;
 mod seeder;
+mod workload;
 
 use atomic::DEVStoneAtomic;
 pub use hi::HI;
@@ -17,13 +19,13 @@ use std::sync::{Arc, Mutex};
 #[cfg(test)]
 #[derive(Debug, Default, Copy, Clone)]
 pub struct TestProbe {
-    n_atomics: usize,
-    n_eics: usize,
-    n_ics: usize,
-    n_eocs: usize,
-    n_internals: usize,
-    n_externals: usize,
-    n_events: usize,
+    pub(crate) n_atomics: usize,
+    pub(crate) n_eics: usize,
+    pub(crate) n_ics: usize,
+    pub(crate) n_eocs: usize,
+    pub(crate) n_internals: usize,
+    pub(crate) n_externals: usize,
+    pub(crate) n_events: usize,
 }
 
 #[cfg(test)]
@@ -1,9 +1,7 @@
+use super::workload::Delay;
 #[cfg(test)]
 use super::SharedProbe;
 use crate::modeling::*;
-#[cfg(feature = "devstone_busy")]
-use cpu_time::ThreadTime;
-use std::time::Duration;
 
 #[derive(Debug, Clone, Default)]
 struct State {
@@ -38,8 +36,8 @@ pub(super) struct DEVStoneAtomic {
     component: Component,
     input: InPort<usize>,
     output: OutPort<usize>,
-    int_delay: Option<Duration>,
-    ext_delay: Option<Duration>,
+    int_delay: Option<Delay>,
+    ext_delay: Option<Delay>,
     state: State,
     sigma: f64,
 }
@@ -55,14 +53,8 @@ impl DEVStoneAtomic {
         let input = component.add_in_port("input");
         let output = component.add_out_port("output");
 
-        let int_delay = match int_delay > 0 {
-            true => Some(Duration::from_millis(int_delay)),
-            false => None,
-        };
-        let ext_delay = match ext_delay > 0 {
-            true => Some(Duration::from_millis(ext_delay)),
-            false => None,
-        };
+        let int_delay = Delay::new(int_delay);
+        let ext_delay = Delay::new(ext_delay);
 
         #[cfg(not(test))]
         let state = State::default();
@@ -78,23 +70,6 @@ impl DEVStoneAtomic {
             sigma: f64::INFINITY,
         }
     }
-
-    #[inline]
-    fn sleep(duration: &Option<Duration>) {
-        if let Some(duration) = duration {
-            #[cfg(feature = "devstone_busy")]
-            {
-                let now = ThreadTime::now();
-                let mut x: u32 = 0;
-                while now.elapsed() < *duration {
-                    std::hint::black_box(&mut x);
-                    x = x.wrapping_add(1);
-                }
-            }
-            #[cfg(not(feature = "devstone_busy"))]
-            std::thread::sleep(*duration);
-        }
-    }
 }
 
 impl Atomic for DEVStoneAtomic {
@@ -124,7 +99,9 @@ impl Atomic for DEVStoneAtomic {
     fn delta_int(&mut self) {
         self.state.n_internals += 1;
         self.sigma = f64::INFINITY;
-        Self::sleep(&self.int_delay);
+        if let Some(delay) = &self.int_delay {
+            delay.burn();
+        }
     }
 
     fn delta_ext(&mut self, _e: f64) {
@@ -132,7 +109,9 @@ impl Atomic for DEVStoneAtomic {
         // Safety: reading messages on atomic model's input port at delta_ext
         self.state.n_events += unsafe { self.input.get_values() }.len();
         self.sigma = 0.;
-        Self::sleep(&self.ext_delay);
+        if let Some(delay) = &self.ext_delay {
+            delay.burn();
+        }
     }
 
     #[inline]
@@ -0,0 +1,212 @@
+//! Building blocks for benchmarking DEVStone models with any harness (e.g., [criterion](https://docs.rs/criterion)).
+//!
+//! Every benchmark is described by its [`Params`]. Use [`build`] to measure the model construction only,
+//! [`simulate`] on the output of [`simulator`] to measure the simulation only, and [`end_to_end`] to measure both.
+//! For example, with criterion:
+//!
+//! ```ignore
+//! use xdevs::devstone::bench::{self, Params, Variant};
+//!
+//! let params = Params::new(Variant::HO, 100, 100);
+//! c.bench_function(&format!("build {params}"), |b| b.iter(|| bench::build(&params)));
+//! c.bench_function(&format!("simulate {params}"), |b| {
+//!     b.iter_batched(|| bench::simulator(&params), |mut s| bench::simulate(&mut s), BatchSize::LargeInput)
+//! });
+//! c.bench_function(&format!("end-to-end {params}"), |b| b.iter(|| bench::end_to_end(&params)));
+//! ```
+
+#[cfg(test)]
+use super::TestProbe;
+use super::{HOmod, HI, HO, LI};
+use crate::modeling::Coupled;
+use crate::simulation::RootCoordinator;
+use std::fmt;
+use std::str::FromStr;
+
+/// DEVStone model variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Variant {
+    /// Low level of interconnections: every coupled model has a subcoupled model and `width - 1` atomic models
+    /// that receive the input of the coupled model.
+    LI,
+    /// High level of interconnections: like [`Variant::LI`], but every atomic model also sends its output
+    /// to the next atomic model.
+    HI,
+    /// High level of interconnections with multiple outputs: like [`Variant::HI`], but coupled models have
+    /// two input ports and two output ports.
+    HO,
+    /// Like [`Variant::HO`], but the atomic models of every coupled model form layers that send their output
+    /// to all the atomic models of the previous layer, so the number of events grows exponentially with the depth.
+    HOmod,
+}
+
+impl Variant {
+    /// All the DEVStone model variants.
+    pub const ALL: [Self; 4] = [Self::LI, Self::HI, Self::HO, Self::HOmod];
+
+    /// Returns the name of the variant (e.g., `"HOmod"`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::LI => "LI",
+            Self::HI => "HI",
+            Self::HO => "HO",
+            Self::HOmod => "HOmod",
+        }
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Variants are parsed from their names, ignoring case.
+impl FromStr for Variant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|v| v.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown DEVStone model variant {s}"))
+    }
+}
+
+/// Parameters of a DEVStone benchmark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Params {
+    /// Model variant.
+    pub variant: Variant,
+    /// Width of the model (it must be equal to or greater than 1).
+    pub width: usize,
+    /// Depth of the model (it must be equal to or greater than 1).
+    pub depth: usize,
+    /// Busy work of internal transitions in milliseconds.
+    pub int_delay: u64,
+    /// Busy work of external transitions in milliseconds.
+    pub ext_delay: u64,
+}
+
+impl Params {
+    /// Creates the parameters of a DEVStone benchmark without delays.
+    pub fn new(variant: Variant, width: usize, depth: usize) -> Self {
+        Self {
+            variant,
+            width,
+            depth,
+            int_delay: 0,
+            ext_delay: 0,
+        }
+    }
+
+    /// Sets the busy work of internal and external transitions in milliseconds.
+    pub fn with_delays(mut self, int_delay: u64, ext_delay: u64) -> Self {
+        self.int_delay = int_delay;
+        self.ext_delay = ext_delay;
+        self
+    }
+}
+
+/// Parameters are displayed as `<VARIANT>/<WIDTH>x<DEPTH>` (e.g., `"LI/100x100"`),
+/// followed by `/<INT_DELAY>ms/<EXT_DELAY>ms` if any delay is not 0.
+impl fmt::Display for Params {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}x{}", self.variant, self.width, self.depth)?;
+        if self.int_delay > 0 || self.ext_delay > 0 {
+            write!(f, "/{}ms/{}ms", self.int_delay, self.ext_delay)?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds a DEVStone model. It panics if the width or the depth are 0.
+pub fn build(params: &Params) -> Coupled {
+    let Params {
+        width,
+        depth,
+        int_delay,
+        ext_delay,
+        ..
+    } = *params;
+    #[cfg(test)]
+    let probe = std::sync::Arc::new(std::sync::Mutex::new(TestProbe::default()));
+    match params.variant {
+        Variant::LI => LI::create(
+            width,
+            depth,
+            int_delay,
+            ext_delay,
+            #[cfg(test)]
+            probe,
+        ),
+        Variant::HI => HI::create(
+            width,
+            depth,
+            int_delay,
+            ext_delay,
+            #[cfg(test)]
+            probe,
+        ),
+        Variant::HO => HO::create(
+            width,
+            depth,
+            int_delay,
+            ext_delay,
+            #[cfg(test)]
+            probe,
+        ),
+        Variant::HOmod => HOmod::create(
+            width,
+            depth,
+            int_delay,
+            ext_delay,
+            #[cfg(test)]
+            probe,
+        ),
+    }
+}
+
+/// Builds a DEVStone model and its root coordinator, ready for [`simulate`].
+pub fn simulator(params: &Params) -> RootCoordinator<Coupled> {
+    RootCoordinator::new(build(params))
+}
+
+/// Simulates a DEVStone model until it becomes passive.
+pub fn simulate(simulator: &mut RootCoordinator<Coupled>) {
+    simulator.simulate(f64::INFINITY);
+}
+
+/// Builds and simulates a DEVStone model. It returns the root coordinator for inspecting the simulation.
+pub fn end_to_end(params: &Params) -> RootCoordinator<Coupled> {
+    let mut simulator = simulator(params);
+    simulate(&mut simulator);
+    simulator
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::Simulator;
+
+    #[test]
+    fn test_params() {
+        for variant in Variant::ALL {
+            assert_eq!(Ok(variant), variant.name().to_lowercase().parse());
+        }
+        assert!("XX".parse::<Variant>().is_err());
+        let params = Params::new(Variant::HOmod, 20, 10);
+        assert_eq!("HOmod/20x10", params.to_string());
+        assert_eq!("HOmod/20x10/1ms/2ms", params.with_delays(1, 2).to_string());
+    }
+
+    #[test]
+    fn test_end_to_end() {
+        for variant in Variant::ALL {
+            let params = Params::new(variant, 3, 3);
+            assert_eq!(variant.name(), build(&params).component.get_name());
+            let simulator = end_to_end(&params);
+            assert_eq!(f64::INFINITY, simulator.get_t_next());
+        }
+    }
+}
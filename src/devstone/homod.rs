@@ -75,6 +75,11 @@ impl HOmod {
                 #[cfg(test)]
                 probe.clone(),
             );
+            // The first two rows have width - 1 atomics, and the rest form a triangle
+            let triangle = (width - 1) * width.saturating_sub(2) / 2;
+            coupled.reserve_components(1 + 2 * (width - 1) + triangle);
+            coupled.reserve_eics(2 * width - 1);
+            coupled.reserve_ics((width - 1) * width + triangle);
             let subcoupled_name = subcoupled.coupled.component.get_name().to_string();
             coupled.add_component(Box::new(subcoupled.coupled));
             coupled.add_eic("input_1", &subcoupled_name, "input_1");
@@ -0,0 +1,120 @@
+#[cfg(feature = "devstone_busy")]
+use cpu_time::ThreadTime;
+use std::hint::black_box;
+#[cfg(not(feature = "devstone_busy"))]
+use std::sync::OnceLock;
+use std::time::Duration;
+#[cfg(not(feature = "devstone_busy"))]
+use std::time::Instant;
+
+/// Minimum duration of the run used for calibrating the workload.
+#[cfg(not(feature = "devstone_busy"))]
+const CALIBRATION_TIME: Duration = Duration::from_millis(50);
+
+/// Number of workload iterations that take one millisecond in this machine.
+#[cfg(not(feature = "devstone_busy"))]
+static ITERATIONS_PER_MS: OnceLock<u64> = OnceLock::new();
+
+/// Busy work that DEVStone atomic models execute in their transition functions.
+///
+/// By default, it runs a fixed number of iterations of a Dhrystone-like workload, calibrated
+/// once per process so that every millisecond of delay corresponds to one millisecond of CPU work
+/// in an idle machine. Thus, every transition costs the same amount of work regardless of the
+/// contention between threads. If the `devstone_busy` feature is activated, it spins instead
+/// until the CPU time of the current thread reaches the delay.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Delay {
+    #[cfg(not(feature = "devstone_busy"))]
+    iterations: u64,
+    #[cfg(feature = "devstone_busy")]
+    duration: Duration,
+}
+
+impl Delay {
+    /// Creates a new delay of the given milliseconds. It returns [`None`] if `ms` is 0.
+    pub(super) fn new(ms: u64) -> Option<Self> {
+        if ms == 0 {
+            return None;
+        }
+        #[cfg(not(feature = "devstone_busy"))]
+        return Some(Self {
+            iterations: ms.saturating_mul(iterations_per_ms()),
+        });
+        #[cfg(feature = "devstone_busy")]
+        return Some(Self {
+            duration: Duration::from_millis(ms),
+        });
+    }
+
+    /// Executes the busy work.
+    #[inline]
+    pub(super) fn burn(&self) {
+        #[cfg(not(feature = "devstone_busy"))]
+        workload(self.iterations);
+        #[cfg(feature = "devstone_busy")]
+        {
+            let now = ThreadTime::now();
+            while now.elapsed() < self.duration {
+                workload(1);
+            }
+        }
+    }
+}
+
+/// Returns the number of workload iterations that take one millisecond in this machine.
+/// The workload is calibrated the first time this function is called.
+#[cfg(not(feature = "devstone_busy"))]
+pub(super) fn iterations_per_ms() -> u64 {
+    *ITERATIONS_PER_MS.get_or_init(|| {
+        let mut iterations = 1024;
+        loop {
+            let start = Instant::now();
+            workload(iterations);
+            let elapsed = start.elapsed();
+            if elapsed >= CALIBRATION_TIME {
+                let per_ms = iterations as f64 / elapsed.as_secs_f64() / 1000.;
+                return (per_ms.ceil() as u64).max(1);
+            }
+            iterations *= 2;
+        }
+    })
+}
+
+/// Executes a number of iterations of a synthetic workload that, like Dhrystone,
+/// mixes integer arithmetic, array accesses, string comparisons, and branches.
+#[inline(never)]
+fn workload(iterations: u64) {
+    let mut array = [0u32; 16];
+    let (mut a, mut b) = (black_box(2u32), black_box(3u32));
+    for i in 0..iterations {
+        let index = (i % 16) as usize;
+        a = a.wrapping_mul(5).wrapping_add(b) % 1021;
+        b = match a % 3 {
+            0 => b.wrapping_add(array[index]),
+            1 => b ^ a,
+            _ => b.wrapping_sub(1),
+        };
+        array[index] = a.wrapping_add(b);
+        if black_box("DHRYSTONE PROGRAM, 1'ST STRING") < black_box("DHRYSTONE PROGRAM, 2'ND STRING")
+        {
+            a = a.wrapping_add(1);
+        }
+    }
+    black_box((a, b, array));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay() {
+        assert!(Delay::new(0).is_none());
+        #[cfg(not(feature = "devstone_busy"))]
+        {
+            let delay = Delay::new(2).unwrap();
+            assert_eq!(2 * iterations_per_ms(), delay.iterations);
+        }
+        Delay::new(1).unwrap().burn();
+    }
+}
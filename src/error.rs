@@ -0,0 +1,93 @@
+use std::fmt;
+
+/// Errors that may occur when building DEVS models.
+/// They are returned by the `try_*` methods of [`crate::modeling::Component`] and [`crate::modeling::Coupled`],
+/// whereas their panicking counterparts panic with the error message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModelingError {
+    /// The component already contains an input port with the given name.
+    /// It contains the component and port names.
+    DuplicateInPort(String, String),
+    /// The component already contains an output port with the given name.
+    /// It contains the component and port names.
+    DuplicateOutPort(String, String),
+    /// The coupled model already contains a component with the given name.
+    DuplicateComponent(String),
+    /// The coupled model does not contain a component with the given name.
+    UnknownComponent(String),
+    /// A [`crate::modeling::ComponentId`] does not belong to the coupled model.
+    ForeignComponent,
+    /// A [`crate::modeling::ComponentId`] refers to a component that was removed from the coupled model.
+    RemovedComponent,
+    /// A port handle does not belong to the coupled model or to the expected components.
+    /// It contains the name of the port.
+    ForeignPort(String),
+    /// The component does not contain an input port with the given name.
+    /// It contains the component and port names.
+    UnknownInPort(String, String),
+    /// The component does not contain an output port with the given name.
+    /// It contains the component and port names.
+    UnknownOutPort(String, String),
+    /// The ports of a coupling are not compatible.
+    /// It contains the paths of the origin and destination ports (e.g., `"component.port"`).
+    IncompatiblePorts(String, String),
+    /// The coupling already exists. It contains the names of the origin and destination ports.
+    DuplicateCoupling(String, String),
+}
+
+impl fmt::Display for ModelingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateInPort(c, p) => {
+                write!(f, "component {c} already contains input port {p}")
+            }
+            Self::DuplicateOutPort(c, p) => {
+                write!(f, "component {c} already contains output port {p}")
+            }
+            Self::DuplicateComponent(c) => {
+                write!(f, "coupled model already contains component {c}")
+            }
+            Self::UnknownComponent(c) => write!(f, "component {c} does not exist"),
+            Self::ForeignComponent => write!(f, "component does not belong to the coupled model"),
+            Self::RemovedComponent => write!(f, "component was removed from the coupled model"),
+            Self::ForeignPort(p) => write!(f, "port {p} does not belong to the coupled model"),
+            Self::UnknownInPort(c, p) => write!(f, "input port {c}.{p} does not exist"),
+            Self::UnknownOutPort(c, p) => write!(f, "output port {c}.{p} does not exist"),
+            Self::IncompatiblePorts(from, to) => {
+                write!(f, "ports {from} and {to} are not compatible")
+            }
+            Self::DuplicateCoupling(from, to) => {
+                write!(f, "coupling already exists from {from} to {to}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ModelingError {}
+
+/// Panic raised by the output function or a transition function of an atomic model during a simulation.
+/// Root coordinators re-raise it as a panic with the path of the model and the simulation time,
+/// and [`crate::simulation::RootCoordinator::try_simulate`] returns it as an error.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentPanic {
+    /// Path of the atomic model in the model hierarchy (e.g., `"root.subcoupled.atomic"`).
+    pub path: String,
+    /// Simulation time at which the model panicked.
+    pub time: f64,
+    /// Function of the model that panicked (e.g., `"lambda"` or `"delta_int"`).
+    pub function: &'static str,
+    /// Message of the original panic.
+    pub message: String,
+}
+
+impl fmt::Display for ComponentPanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "component {} panicked in {} at time {}: {}",
+            self.path, self.function, self.time, self.message
+        )
+    }
+}
+
+impl std::error::Error for ComponentPanic {}
@@ -0,0 +1,270 @@
+use crate::random::derive_seed;
+use crate::simulation::{RootCoordinator, Simulator};
+use crate::stats::ConfidenceInterval;
+#[cfg(feature = "par_experiment")]
+use rayon::prelude::*;
+use std::time::Duration;
+
+/// Result of a single simulation run of an [`Experiment`].
+#[derive(Debug, Clone)]
+pub struct Run<R> {
+    /// Index of the parameter point of the run in the parameter grid.
+    pub point: usize,
+    /// Replication number of the run (from 0 to the number of replications - 1).
+    pub replication: usize,
+    /// Result of the run.
+    pub result: R,
+    /// It is `false` if the run exceeded the wall-clock limit of the experiment and was stopped early.
+    pub completed: bool,
+}
+
+/// Results of an [`Experiment`]. Runs are sorted by parameter point and replication number.
+#[derive(Debug, Clone)]
+pub struct Results<P, R> {
+    /// Parameter grid of the experiment.
+    grid: Vec<P>,
+    /// Results of every run.
+    runs: Vec<Run<R>>,
+}
+
+impl<P, R> Results<P, R> {
+    /// Returns the parameter grid of the experiment.
+    #[inline]
+    pub fn grid(&self) -> &[P] {
+        &self.grid
+    }
+
+    /// Returns the results of every run.
+    #[inline]
+    pub fn runs(&self) -> &[Run<R>] {
+        &self.runs
+    }
+
+    /// Returns an iterator over the results of all the replications of a parameter point.
+    pub fn point(&self, point: usize) -> impl Iterator<Item = &R> {
+        self.runs
+            .iter()
+            .filter(move |r| r.point == point)
+            .map(|r| &r.result)
+    }
+
+    /// Returns an iterator over the parameter points and the results of their replications.
+    pub fn points(&self) -> impl Iterator<Item = (&P, Vec<&R>)> {
+        self.grid
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (p, self.point(i).collect()))
+    }
+
+    /// Returns the confidence interval at a given level (e.g., 0.95) of the mean of a metric over the
+    /// replications of a parameter point, or [`None`] if the point has less than two replications.
+    /// `metric` extracts the observed value from the result of a run.
+    pub fn confidence_interval<F>(
+        &self,
+        point: usize,
+        level: f64,
+        metric: F,
+    ) -> Option<ConfidenceInterval>
+    where
+        F: Fn(&R) -> f64,
+    {
+        let samples: Vec<_> = self.point(point).map(metric).collect();
+        ConfidenceInterval::from_samples(&samples, level)
+    }
+
+    /// Returns the confidence interval of the mean of a metric for every parameter point of the grid.
+    /// See [`Results::confidence_interval`] for details.
+    pub fn confidence_intervals<F>(&self, level: f64, metric: F) -> Vec<Option<ConfidenceInterval>>
+    where
+        F: Fn(&R) -> f64,
+    {
+        (0..self.grid.len())
+            .map(|point| self.confidence_interval(point, level, &metric))
+            .collect()
+    }
+}
+
+/// Runner of parameter sweeps. For every point of a parameter grid,
+/// it simulates a given number of replications of a model and collects their results.
+#[derive(Debug, Clone)]
+pub struct Experiment<P> {
+    /// Parameter grid.
+    grid: Vec<P>,
+    /// Number of replications per parameter point.
+    replications: usize,
+    /// Simulation time of each run.
+    t_end: f64,
+    /// Master seed of the experiment. If set, every run derives its own seed from it.
+    seed: Option<u64>,
+    /// Maximum wall-clock time of each run. If set, runs that exceed it are stopped early.
+    wall_limit: Option<Duration>,
+}
+
+impl<P: Clone> Experiment<P> {
+    /// Creates a new experiment with the given parameter grid and number of replications.
+    /// By default, models are simulated until they reach a passive state.
+    pub fn new(grid: impl IntoIterator<Item = P>, replications: usize) -> Self {
+        Self {
+            grid: grid.into_iter().collect(),
+            replications,
+            t_end: f64::INFINITY,
+            seed: None,
+            wall_limit: None,
+        }
+    }
+
+    /// Sets the simulation time of each run.
+    pub fn t_end(mut self, t_end: f64) -> Self {
+        self.t_end = t_end;
+        self
+    }
+
+    /// Sets the master seed of the experiment. Before simulating a run, the random number generator
+    /// streams of the model are derived from a run seed (see [`RootCoordinator::set_seed`]).
+    /// Run seeds only depend on the master seed and the run index, so results are reproducible
+    /// in both sequential and parallel executions.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets the maximum wall-clock time of each run, so runaway configurations do not stall the experiment.
+    /// Runs that exceed it are stopped after the current simulation cycle (see
+    /// [`RootCoordinator::simulate_with_budget`]), and their results are collected as usual
+    /// with [`Run::completed`] set to `false`.
+    pub fn wall_limit(mut self, wall_limit: Duration) -> Self {
+        self.wall_limit = Some(wall_limit);
+        self
+    }
+
+    /// Returns the total number of runs of the experiment.
+    #[inline]
+    pub fn n_runs(&self) -> usize {
+        self.grid.len() * self.replications
+    }
+
+    /// Executes a single run. `factory` receives the parameter point and the replication number,
+    /// and returns the root coordinator of the model and a closure to obtain the result of the run.
+    fn run_one<M, R, C, F>(&self, i: usize, factory: &F) -> Run<R>
+    where
+        M: Simulator,
+        C: FnOnce(&M) -> R,
+        F: Fn(&P, usize) -> (RootCoordinator<M>, C),
+    {
+        let (point, replication) = (i / self.replications, i % self.replications);
+        let (mut simulator, collect) = factory(&self.grid[point], replication);
+        if let Some(seed) = self.seed {
+            simulator.set_seed(derive_seed(seed, i as u64));
+        }
+        let completed = match self.wall_limit {
+            Some(wall_limit) => simulator
+                .simulate_with_budget(self.t_end, wall_limit)
+                .is_ok(),
+            None => {
+                simulator.simulate(self.t_end);
+                true
+            }
+        };
+        Run {
+            point,
+            replication,
+            result: collect(&simulator),
+            completed,
+        }
+    }
+
+    /// Executes all the runs of the experiment sequentially.
+    /// `factory` receives the parameter point and the replication number, and returns the
+    /// root coordinator of the model (with any required listener) and a closure that
+    /// obtains the result of the run from the model once the simulation is over.
+    pub fn run<M, R, C, F>(&self, factory: F) -> Results<P, R>
+    where
+        M: Simulator,
+        C: FnOnce(&M) -> R,
+        F: Fn(&P, usize) -> (RootCoordinator<M>, C),
+    {
+        let runs = (0..self.n_runs())
+            .map(|i| self.run_one(i, &factory))
+            .collect();
+        Results {
+            grid: self.grid.clone(),
+            runs,
+        }
+    }
+
+    /// Executes all the runs of the experiment in parallel. See [`Experiment::run`] for details.
+    /// Every run is executed from start to end by a single thread.
+    #[cfg(feature = "par_experiment")]
+    pub fn par_run<M, R, C, F>(&self, factory: F) -> Results<P, R>
+    where
+        P: Sync,
+        M: Simulator,
+        R: Send,
+        C: FnOnce(&M) -> R,
+        F: Fn(&P, usize) -> (RootCoordinator<M>, C) + Sync,
+    {
+        let runs = (0..self.n_runs())
+            .into_par_iter()
+            .map(|i| self.run_one(i, &factory))
+            .collect();
+        Results {
+            grid: self.grid.clone(),
+            runs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devstone::{TestProbe, LI};
+    use crate::modeling::Coupled;
+    use std::sync::{Arc, Mutex};
+
+    fn factory(
+        &(width, depth): &(usize, usize),
+        _replication: usize,
+    ) -> (RootCoordinator<Coupled>, impl FnOnce(&Coupled) -> usize) {
+        let probe = Arc::new(Mutex::new(TestProbe::default()));
+        let simulator = RootCoordinator::new(LI::create(width, depth, 0, 0, probe.clone()));
+        (simulator, move |_: &_| probe.lock().unwrap().n_events)
+    }
+
+    fn check(results: &Results<(usize, usize), usize>) {
+        assert_eq!(6, results.runs().len());
+        for (i, run) in results.runs().iter().enumerate() {
+            assert_eq!(i / 2, run.point);
+            assert_eq!(i % 2, run.replication);
+            assert!(run.completed);
+        }
+        for ((width, depth), runs) in results.points() {
+            assert_eq!(vec![&((width - 1) * (depth - 1) + 1); 2], runs);
+        }
+        let intervals = results.confidence_intervals(0.95, |&n| n as f64);
+        assert_eq!(3, intervals.len());
+        for (i, interval) in intervals.into_iter().enumerate() {
+            let interval = interval.unwrap();
+            assert_eq!(*results.point(i).next().unwrap() as f64, interval.mean);
+            assert_eq!((2, 0.), (interval.n, interval.half_width));
+        }
+    }
+
+    #[test]
+    fn test_experiment() {
+        let experiment = Experiment::new([(2, 2), (3, 4), (5, 3)], 2);
+        assert_eq!(6, experiment.n_runs());
+        check(&experiment.run(factory));
+        #[cfg(feature = "par_experiment")]
+        check(&experiment.par_run(factory));
+    }
+
+    #[test]
+    fn test_experiment_wall_limit() {
+        let experiment = Experiment::new([(2, 2), (3, 4), (5, 3)], 2);
+        check(&experiment.clone().wall_limit(Duration::MAX).run(factory));
+        let results = experiment.wall_limit(Duration::ZERO).run(factory);
+        for run in results.runs() {
+            assert!(!run.completed);
+        }
+    }
+}
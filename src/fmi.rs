@@ -0,0 +1,58 @@
+//! Interoperability with the [Functional Mock-up Interface](https://fmi-standard.org) (FMI) 2.0 for co-simulation.
+//! [`Fmu`] wraps a co-simulation Functional Mock-up Unit (FMU) as an atomic DEVS model,
+//! so continuous models exported from tools such as Modelica can be combined with DEVS models.
+//! Conversely, [`crate::export_fmu`] and [`package`] export a coupled model as a co-simulation FMU
+//! that can be imported by other co-simulation masters.
+
+mod description;
+mod export;
+mod ffi;
+mod fmu;
+
+pub use description::{Causality, ModelDescription, Variable};
+#[doc(hidden)]
+pub use export::shims as __shims;
+pub use export::{model_description, package};
+pub use fmu::Fmu;
+
+use std::fmt;
+
+/// Errors that may occur when loading or simulating FMUs.
+#[derive(Debug)]
+pub enum FmiError {
+    /// The FMU archive could not be read or extracted.
+    Io(std::io::Error),
+    /// The FMU archive is not a valid ZIP file.
+    Archive(String),
+    /// The model description of the FMU is not valid or not supported.
+    Description(String),
+    /// The shared library of the FMU could not be loaded.
+    Library(String),
+    /// The FMU does not contain a variable with the given name.
+    UnknownVariable(String),
+    /// A function of the FMU returned an error. It contains the function name and the returned status.
+    Status(&'static str, i32),
+}
+
+impl fmt::Display for FmiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "could not read FMU: {e}"),
+            Self::Archive(e) => write!(f, "invalid FMU archive: {e}"),
+            Self::Description(e) => write!(f, "invalid model description: {e}"),
+            Self::Library(e) => write!(f, "could not load FMU library: {e}"),
+            Self::UnknownVariable(v) => write!(f, "unknown FMU variable {v}"),
+            Self::Status(function, status) => {
+                write!(f, "{function} returned status {status}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FmiError {}
+
+impl From<std::io::Error> for FmiError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
@@ -0,0 +1,277 @@
+use super::FmiError;
+
+/// Causality of an FMU variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Causality {
+    /// Independent parameter. It can be set before initializing the FMU.
+    Parameter,
+    /// Parameter computed by the FMU from other parameters.
+    CalculatedParameter,
+    /// Input of the FMU. It is mapped to an input port.
+    Input,
+    /// Output of the FMU. It is mapped to an output port.
+    Output,
+    /// Internal variable of the FMU.
+    Local,
+    /// Independent variable of the FMU (usually, time).
+    Independent,
+}
+
+impl Causality {
+    /// Parses the causality attribute of a scalar variable.
+    fn parse(causality: &str) -> Result<Self, FmiError> {
+        match causality {
+            "parameter" => Ok(Self::Parameter),
+            "calculatedParameter" => Ok(Self::CalculatedParameter),
+            "input" => Ok(Self::Input),
+            "output" => Ok(Self::Output),
+            "local" => Ok(Self::Local),
+            "independent" => Ok(Self::Independent),
+            _ => Err(FmiError::Description(format!(
+                "unknown causality {causality}"
+            ))),
+        }
+    }
+
+    /// Returns the causality attribute of a scalar variable.
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Parameter => "parameter",
+            Self::CalculatedParameter => "calculatedParameter",
+            Self::Input => "input",
+            Self::Output => "output",
+            Self::Local => "local",
+            Self::Independent => "independent",
+        }
+    }
+}
+
+/// Real scalar variable of an FMU.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variable {
+    /// Name of the variable.
+    pub name: String,
+    /// Value reference used for getting and setting the variable.
+    pub value_reference: u32,
+    /// Causality of the variable.
+    pub causality: Causality,
+    /// Start value of the variable (if any).
+    pub start: Option<f64>,
+}
+
+/// Relevant contents of the `modelDescription.xml` file of an FMI 2.0 co-simulation FMU.
+/// Only real variables are considered, as DEVS ports of other types are not mapped yet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelDescription {
+    /// Name of the model.
+    pub model_name: String,
+    /// Globally unique identifier of the FMU. It is checked when the FMU is instantiated.
+    pub guid: String,
+    /// Identifier of the co-simulation model. It is the name of the shared library of the FMU.
+    pub model_identifier: String,
+    /// Real variables of the FMU.
+    pub variables: Vec<Variable>,
+}
+
+impl ModelDescription {
+    /// Parses the contents of a `modelDescription.xml` file.
+    pub fn parse(xml: &str) -> Result<Self, FmiError> {
+        let document =
+            roxmltree::Document::parse(xml).map_err(|e| FmiError::Description(e.to_string()))?;
+        let root = document.root_element();
+        if root.tag_name().name() != "fmiModelDescription" {
+            return Err(FmiError::Description(
+                "missing fmiModelDescription element".to_string(),
+            ));
+        }
+        let version = attribute(root, "fmiVersion")?;
+        if version != "2.0" {
+            return Err(FmiError::Description(format!(
+                "unsupported FMI version {version}"
+            )));
+        }
+        let co_simulation = child(root, "CoSimulation").ok_or_else(|| {
+            FmiError::Description("the FMU does not support co-simulation".to_string())
+        })?;
+
+        let mut variables = Vec::new();
+        if let Some(model_variables) = child(root, "ModelVariables") {
+            for variable in model_variables
+                .children()
+                .filter(|n| n.has_tag_name("ScalarVariable"))
+            {
+                // only real variables are mapped to DEVS ports
+                let Some(real) = child(variable, "Real") else {
+                    continue;
+                };
+                let name = attribute(variable, "name")?;
+                let value_reference =
+                    attribute(variable, "valueReference")?
+                        .parse()
+                        .map_err(|_| {
+                            FmiError::Description(format!("invalid value reference of {name}"))
+                        })?;
+                let causality = match variable.attribute("causality") {
+                    Some(causality) => Causality::parse(causality)?,
+                    None => Causality::Local,
+                };
+                let start = match real.attribute("start") {
+                    Some(start) => Some(start.parse().map_err(|_| {
+                        FmiError::Description(format!("invalid start value of {name}"))
+                    })?),
+                    None => None,
+                };
+                variables.push(Variable {
+                    name: name.to_string(),
+                    value_reference,
+                    causality,
+                    start,
+                });
+            }
+        }
+
+        Ok(Self {
+            model_name: attribute(root, "modelName")?.to_string(),
+            guid: attribute(root, "guid")?.to_string(),
+            model_identifier: attribute(co_simulation, "modelIdentifier")?.to_string(),
+            variables,
+        })
+    }
+
+    /// Returns the variable with the given name (if any).
+    pub fn variable(&self, name: &str) -> Option<&Variable> {
+        self.variables.iter().find(|v| v.name == name)
+    }
+
+    /// Returns an iterator over the variables with the given causality.
+    pub fn variables_with(&self, causality: Causality) -> impl Iterator<Item = &Variable> {
+        self.variables
+            .iter()
+            .filter(move |v| v.causality == causality)
+    }
+
+    /// Returns the contents of the `modelDescription.xml` file of a co-simulation FMU with this description.
+    pub fn to_xml(&self) -> String {
+        let mut xml = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <fmiModelDescription fmiVersion=\"2.0\" modelName=\"{}\" guid=\"{}\" \
+             generationTool=\"xdevs\" variableNamingConvention=\"flat\">\n  \
+             <CoSimulation modelIdentifier=\"{}\" canHandleVariableCommunicationStepSize=\"true\"/>\n  \
+             <ModelVariables>\n",
+            escape(&self.model_name),
+            escape(&self.guid),
+            escape(&self.model_identifier)
+        );
+        for variable in self.variables.iter() {
+            let start = match variable.start {
+                Some(start) => format!(" start=\"{start:?}\""),
+                None => String::new(),
+            };
+            xml += &format!(
+                "    <ScalarVariable name=\"{}\" valueReference=\"{}\" causality=\"{}\">\n      \
+                 <Real{start}/>\n    </ScalarVariable>\n",
+                escape(&variable.name),
+                variable.value_reference,
+                variable.causality.as_str()
+            );
+        }
+        xml += "  </ModelVariables>\n  <ModelStructure>\n";
+        // outputs are referred to by their (1-based) index in the list of model variables
+        let outputs: Vec<_> = (self.variables.iter().enumerate())
+            .filter(|(_, v)| v.causality == Causality::Output)
+            .map(|(i, _)| format!("      <Unknown index=\"{}\"/>\n", i + 1))
+            .collect();
+        if !outputs.is_empty() {
+            xml += &format!("    <Outputs>\n{}    </Outputs>\n", outputs.concat());
+        }
+        xml += "  </ModelStructure>\n</fmiModelDescription>\n";
+        xml
+    }
+}
+
+/// Escapes the special characters of an XML attribute value.
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Returns the first child element of a node with the given tag name (if any).
+fn child<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(name))
+}
+
+/// Returns a mandatory attribute of an element.
+fn attribute<'a>(node: roxmltree::Node<'a, '_>, name: &str) -> Result<&'a str, FmiError> {
+    node.attribute(name).ok_or_else(|| {
+        FmiError::Description(format!(
+            "missing attribute {name} in element {}",
+            node.tag_name().name()
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<fmiModelDescription fmiVersion="2.0" modelName="integrator" guid="{8c4e810f-3df3-4a00-8276-176fa3c9f000}">
+  <CoSimulation modelIdentifier="integrator" canHandleVariableCommunicationStepSize="true"/>
+  <ModelVariables>
+    <ScalarVariable name="k" valueReference="0" causality="parameter" variability="fixed">
+      <Real start="2.0"/>
+    </ScalarVariable>
+    <ScalarVariable name="u" valueReference="1" causality="input">
+      <Real start="0"/>
+    </ScalarVariable>
+    <ScalarVariable name="y" valueReference="2" causality="output">
+      <Real/>
+    </ScalarVariable>
+    <ScalarVariable name="enabled" valueReference="0" causality="input">
+      <Boolean start="true"/>
+    </ScalarVariable>
+  </ModelVariables>
+</fmiModelDescription>"#;
+
+    #[test]
+    fn test_model_description() {
+        let description = ModelDescription::parse(XML).unwrap();
+        assert_eq!("integrator", description.model_name);
+        assert_eq!("integrator", description.model_identifier);
+        assert_eq!("{8c4e810f-3df3-4a00-8276-176fa3c9f000}", description.guid);
+        assert_eq!(3, description.variables.len());
+        assert_eq!(
+            Some(&Variable {
+                name: "k".to_string(),
+                value_reference: 0,
+                causality: Causality::Parameter,
+                start: Some(2.),
+            }),
+            description.variable("k")
+        );
+        let inputs: Vec<_> = description.variables_with(Causality::Input).collect();
+        assert_eq!(1, inputs.len());
+        assert_eq!("u", inputs[0].name);
+        let outputs: Vec<_> = description.variables_with(Causality::Output).collect();
+        assert_eq!(1, outputs.len());
+        assert_eq!(2, outputs[0].value_reference);
+        assert_eq!(None, outputs[0].start);
+
+        let xml = XML.replace("fmiVersion=\"2.0\"", "fmiVersion=\"3.0\"");
+        assert!(matches!(
+            ModelDescription::parse(&xml),
+            Err(FmiError::Description(_))
+        ));
+        let xml = XML.replace("CoSimulation", "ModelExchange");
+        assert!(matches!(
+            ModelDescription::parse(&xml),
+            Err(FmiError::Description(_))
+        ));
+    }
+}
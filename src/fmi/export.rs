@@ -0,0 +1,737 @@
+use super::description::{Causality, ModelDescription, Variable};
+use super::ffi::*;
+use super::FmiError;
+use crate::modeling::port::{Port, Values};
+use crate::modeling::Coupled;
+use crate::simulation::Simulator;
+use crate::Shared;
+use std::ffi::{c_char, CStr, CString};
+use std::io::Write;
+use std::path::Path;
+
+/// Returns the model description of the FMU exported from a coupled model.
+/// Every input (output) port of type [`f64`] of the coupled model is mapped to a real input (output) variable
+/// with the same name. Value references are assigned in port declaration order, starting with the inputs.
+/// The model identifier is the name of the coupled model, and the GUID is derived from the variables of the model.
+pub fn model_description(model: &Coupled) -> ModelDescription {
+    let inputs = real_ports(model.get_component().get_in_ports());
+    let outputs = real_ports(model.get_component().get_out_ports());
+    let mut variables = Vec::new();
+    for (port, causality, start) in (inputs.iter().map(|p| (p, Causality::Input, Some(0.))))
+        .chain(outputs.iter().map(|p| (p, Causality::Output, None)))
+    {
+        variables.push(Variable {
+            name: port.get_name().to_string(),
+            value_reference: variables.len() as u32,
+            causality,
+            start,
+        });
+    }
+    let mut description = ModelDescription {
+        model_name: model.get_name().to_string(),
+        guid: String::new(),
+        model_identifier: model.get_name().to_string(),
+        variables,
+    };
+    description.guid = guid(&description.to_xml());
+    description
+}
+
+/// Packages an exported FMU. `model` is the coupled model exported with [`crate::export_fmu`],
+/// `library` is the path of the shared library that contains the exported FMU (i.e., the `cdylib` crate
+/// that invokes [`crate::export_fmu`]), and `fmu` is the path of the resulting FMU archive.
+/// The name of the coupled model must be a valid C identifier, as it is used as model identifier.
+pub fn package(
+    model: &Coupled,
+    library: impl AsRef<Path>,
+    fmu: impl AsRef<Path>,
+) -> Result<(), FmiError> {
+    let description = model_description(model);
+    let identifier = &description.model_identifier;
+    let mut chars = identifier.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(FmiError::Description(format!(
+            "model identifier {identifier} is not a valid C identifier"
+        )));
+    }
+    let binary = std::fs::read(library)?;
+    let (platform, extension) = LIBRARY;
+
+    let archive = |file: std::fs::File| -> zip::result::ZipResult<()> {
+        let mut writer = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default();
+        writer.start_file("modelDescription.xml", options)?;
+        writer.write_all(description.to_xml().as_bytes())?;
+        writer.start_file(
+            format!("binaries/{platform}/{identifier}.{extension}"),
+            options,
+        )?;
+        writer.write_all(&binary)?;
+        writer.finish()?;
+        Ok(())
+    };
+    archive(std::fs::File::create(fmu)?).map_err(|e| FmiError::Archive(e.to_string()))
+}
+
+/// Returns the ports of type [`f64`] of a list of ports.
+fn real_ports(ports: &[Shared<dyn Port>]) -> Vec<Shared<dyn Port>> {
+    ports
+        .iter()
+        // Safety: reading port types before the simulation starts
+        .filter(|port| unsafe { port.get_values() }.is::<Values<f64>>())
+        .cloned()
+        .collect()
+}
+
+/// Derives a GUID from the contents of a model description using the 128-bit FNV-1a hash.
+/// It only changes if the model description changes.
+fn guid(xml: &str) -> String {
+    const PRIME: u128 = 0x0000000001000000000000000000013B;
+    let hash = xml
+        .bytes()
+        .fold(0x6c62272e07bb014262b821756295c58d_u128, |hash, byte| {
+            (hash ^ byte as u128).wrapping_mul(PRIME)
+        });
+    let hex = format!("{hash:032x}");
+    format!(
+        "{{{}-{}-{}-{}-{}}}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// Real input variable of an exported FMU.
+struct Input {
+    /// Input port of the coupled model.
+    port: Shared<dyn Port>,
+    /// Current value of the variable.
+    value: f64,
+    /// It is `true` if the value changed since the last communication point.
+    changed: bool,
+}
+
+/// Instance of an exported FMU. It drives the simulation of a coupled model between communication points.
+/// Whenever the value of an input variable changes, it is injected as an event at the next communication point.
+/// Output variables hold the last value sent through their output port.
+struct Slave {
+    /// Function that builds the coupled model.
+    build: fn() -> Coupled,
+    /// Name of the instance.
+    name: CString,
+    /// Logger callback provided by the importer (if any).
+    logger: Option<(Fmi2CallbackLoggerVariadic, Fmi2ComponentEnvironment)>,
+    /// Coupled model simulated by the instance.
+    model: Coupled,
+    /// Real input variables.
+    inputs: Vec<Input>,
+    /// Real output variables: output port of the coupled model and current value.
+    outputs: Vec<(Shared<dyn Port>, f64)>,
+    /// Current communication point.
+    time: f64,
+    /// It is `true` if the simulation of the coupled model is running.
+    running: bool,
+}
+
+impl Slave {
+    /// Creates a new instance from a function that builds the coupled model.
+    fn new(build: fn() -> Coupled, name: CString) -> Self {
+        let model = build();
+        let inputs = real_ports(model.get_component().get_in_ports())
+            .into_iter()
+            .map(|port| Input {
+                port,
+                value: 0.,
+                changed: false,
+            })
+            .collect();
+        let outputs = real_ports(model.get_component().get_out_ports())
+            .into_iter()
+            .map(|port| (port, 0.))
+            .collect();
+        Self {
+            build,
+            name,
+            logger: None,
+            model,
+            inputs,
+            outputs,
+            time: 0.,
+            running: false,
+        }
+    }
+
+    /// Sends a message to the logger of the importer (if any).
+    fn log(&self, status: Fmi2Status, category: &str, message: &str) {
+        if let Some((logger, env)) = self.logger {
+            let category = CString::new(category).unwrap_or_default();
+            let message = CString::new(message).unwrap_or_default();
+            // Safety: the message is passed as an argument, so it is not interpreted as a format string
+            unsafe {
+                logger(
+                    env,
+                    self.name.as_ptr(),
+                    status,
+                    category.as_ptr(),
+                    c"%s".as_ptr(),
+                    message.as_ptr(),
+                )
+            };
+        }
+    }
+
+    /// Logs an error and returns the error status.
+    fn error(&self, message: &str) -> Fmi2Status {
+        self.log(FMI2_ERROR, "logStatusError", message);
+        FMI2_ERROR
+    }
+
+    /// Starts the simulation of the coupled model at the current communication point.
+    fn start(&mut self) {
+        let path = self.model.get_name().to_string();
+        self.model.set_path(&path);
+        self.model.start(self.time);
+        self.running = true;
+    }
+
+    /// Stops the simulation of the coupled model (if running).
+    fn stop(&mut self) {
+        if self.running {
+            self.model.stop(self.time);
+            self.running = false;
+        }
+    }
+
+    /// Rebuilds the coupled model, discarding its current state.
+    fn reset(&mut self) {
+        self.stop();
+        *self = Self {
+            logger: self.logger,
+            ..Self::new(self.build, self.name.clone())
+        };
+    }
+
+    /// Advances the simulation from the communication point `t` to `t + h`. First, inputs that changed since
+    /// the last communication point are injected at time `t`. Then, all the simulation cycles scheduled
+    /// until `t + h` (inclusive) are executed, so outputs sent at `t + h` are visible at the new communication point.
+    fn do_step(&mut self, t: f64, h: f64) -> Fmi2Status {
+        if !self.running {
+            return self.error("the FMU is not initialized");
+        }
+        if t < self.model.get_t_last() || h < 0. {
+            return self.error("invalid communication step");
+        }
+        let mut external = false;
+        for input in self.inputs.iter_mut().filter(|input| input.changed) {
+            // Safety: injecting messages between simulation phases
+            unsafe { input.port.inject(&vec![input.value]) };
+            input.changed = false;
+            external = true;
+        }
+        if external {
+            self.cycle(t);
+        }
+        let t_end = t + h;
+        while self.model.get_t_next() <= t_end {
+            self.cycle(self.model.get_t_next());
+        }
+        self.time = t_end;
+        FMI2_OK
+    }
+
+    /// Executes a simulation cycle at time `t` and updates the output variables.
+    fn cycle(&mut self, t: f64) {
+        self.model.collection(t);
+        for (port, value) in self.outputs.iter_mut() {
+            // Safety: reading messages between simulation phases
+            let values = unsafe { port.get_values() }.downcast_ref::<Values<f64>>();
+            if let Some(last) = values.and_then(|values| values.last()) {
+                *value = *last;
+            }
+        }
+        self.model.transition(t);
+    }
+}
+
+/// Implementation of the FMI 2.0 co-simulation API for exported FMUs.
+/// These functions are called by the C API shims generated by [`crate::export_fmu`], and must not be used directly.
+/// Unsupported functions return an error status.
+///
+/// # Safety
+///
+/// All the functions must be called following the FMI 2.0 standard (e.g., instances must have been
+/// returned by [`instantiate`](shims::instantiate) and not freed yet, and buffers must contain `nvr` elements).
+#[allow(clippy::missing_safety_doc)]
+pub mod shims {
+    use super::*;
+    use std::ffi::{c_int, c_uint, c_void};
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    /// Status returned by unsupported functions.
+    pub const UNSUPPORTED: c_int = FMI2_ERROR;
+
+    /// Runs a function on an instance. Panics are caught and reported as errors, as they cannot unwind into C code.
+    unsafe fn with_slave(c: *mut c_void, f: impl FnOnce(&mut Slave) -> Fmi2Status) -> Fmi2Status {
+        let Some(slave) = (c as *mut Slave).as_mut() else {
+            return FMI2_ERROR;
+        };
+        match catch_unwind(AssertUnwindSafe(|| f(slave))) {
+            Ok(status) => status,
+            Err(_) => {
+                let slave = &mut *(c as *mut Slave);
+                slave.running = false;
+                slave.error("the DEVS model panicked")
+            }
+        }
+    }
+
+    pub fn get_types_platform() -> *const c_char {
+        c"default".as_ptr()
+    }
+
+    pub fn get_version() -> *const c_char {
+        c"2.0".as_ptr()
+    }
+
+    pub unsafe fn set_debug_logging(
+        c: *mut c_void,
+        _logging_on: c_int,
+        _n_categories: usize,
+        _categories: *const *const c_char,
+    ) -> c_int {
+        with_slave(c, |_| FMI2_OK)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub unsafe fn instantiate(
+        build: fn() -> Coupled,
+        instance_name: *const c_char,
+        fmu_type: c_int,
+        guid: *const c_char,
+        _resource_location: *const c_char,
+        functions: *const c_void,
+        _visible: c_int,
+        _logging_on: c_int,
+    ) -> *mut c_void {
+        let result = catch_unwind(|| {
+            let name = match instance_name.is_null() {
+                true => CString::default(),
+                false => CStr::from_ptr(instance_name).to_owned(),
+            };
+            let mut slave = Slave::new(build, name);
+            // Safety: the callback functions are provided by the importer following the FMI 2.0 standard
+            if let Some(functions) = (functions as *const Fmi2CallbackFunctions).as_ref() {
+                let logger: Fmi2CallbackLoggerVariadic = std::mem::transmute(functions.logger);
+                slave.logger = Some((logger, functions.component_environment));
+            }
+            if fmu_type != FMI2_CO_SIMULATION {
+                slave.error("the FMU only supports co-simulation");
+                return None;
+            }
+            let expected = model_description(&slave.model).guid;
+            if guid.is_null() || CStr::from_ptr(guid).to_bytes() != expected.as_bytes() {
+                slave.error(&format!("GUID does not match {expected}"));
+                return None;
+            }
+            Some(Box::new(slave))
+        });
+        match result {
+            Ok(Some(slave)) => Box::into_raw(slave) as *mut c_void,
+            _ => std::ptr::null_mut(),
+        }
+    }
+
+    pub unsafe fn free_instance(c: *mut c_void) {
+        if !c.is_null() {
+            let _ = catch_unwind(|| drop(Box::from_raw(c as *mut Slave)));
+        }
+    }
+
+    pub unsafe fn setup_experiment(
+        c: *mut c_void,
+        _tolerance_defined: c_int,
+        _tolerance: f64,
+        start_time: f64,
+        _stop_time_defined: c_int,
+        _stop_time: f64,
+    ) -> c_int {
+        with_slave(c, |slave| {
+            slave.time = start_time;
+            FMI2_OK
+        })
+    }
+
+    pub unsafe fn enter_initialization_mode(c: *mut c_void) -> c_int {
+        with_slave(c, |_| FMI2_OK)
+    }
+
+    pub unsafe fn exit_initialization_mode(c: *mut c_void) -> c_int {
+        with_slave(c, |slave| {
+            slave.start();
+            FMI2_OK
+        })
+    }
+
+    pub unsafe fn terminate(c: *mut c_void) -> c_int {
+        with_slave(c, |slave| {
+            slave.stop();
+            FMI2_OK
+        })
+    }
+
+    pub unsafe fn reset(c: *mut c_void) -> c_int {
+        with_slave(c, |slave| {
+            slave.reset();
+            FMI2_OK
+        })
+    }
+
+    pub unsafe fn get_real(
+        c: *mut c_void,
+        vr: *const c_uint,
+        nvr: usize,
+        value: *mut f64,
+    ) -> c_int {
+        with_slave(c, |slave| {
+            for i in 0..nvr {
+                let vr = *vr.add(i) as usize;
+                *value.add(i) = match vr.checked_sub(slave.inputs.len()) {
+                    None => slave.inputs[vr].value,
+                    Some(j) if j < slave.outputs.len() => slave.outputs[j].1,
+                    _ => return slave.error(&format!("unknown value reference {vr}")),
+                };
+            }
+            FMI2_OK
+        })
+    }
+
+    pub unsafe fn set_real(
+        c: *mut c_void,
+        vr: *const c_uint,
+        nvr: usize,
+        value: *const f64,
+    ) -> c_int {
+        with_slave(c, |slave| {
+            for i in 0..nvr {
+                let vr = *vr.add(i) as usize;
+                let Some(input) = slave.inputs.get_mut(vr) else {
+                    return slave.error(&format!("value reference {vr} is not an input"));
+                };
+                input.value = *value.add(i);
+                input.changed = true;
+            }
+            FMI2_OK
+        })
+    }
+
+    pub unsafe fn do_step(
+        c: *mut c_void,
+        current_communication_point: f64,
+        communication_step_size: f64,
+        _no_set_fmu_state_prior_to_current_point: c_int,
+    ) -> c_int {
+        with_slave(c, |slave| {
+            slave.do_step(current_communication_point, communication_step_size)
+        })
+    }
+
+    pub fn get_status(_c: *mut c_void, _kind: c_int, _value: *mut c_void) -> c_int {
+        // status inquiries only apply to asynchronous steps, which are not supported
+        FMI2_DISCARD
+    }
+}
+
+/// Exports a coupled model as an FMI 2.0 co-simulation FMU. It must be invoked once in a `cdylib` crate,
+/// with the path of a function that builds the coupled model (i.e., `fn() -> Coupled`).
+/// The macro generates the C functions of the FMI 2.0 co-simulation API, which simulate a new
+/// instance of the model every time the FMU is instantiated. Then, use [`fmi::package`](crate::fmi::package)
+/// to package the compiled library together with its model description.
+///
+/// Only the input and output ports of type [`f64`] are mapped to FMU variables (see
+/// [`fmi::model_description`](crate::fmi::model_description)). When the value of an input variable changes,
+/// it is injected into its port as an event at the next communication point. Output variables hold the last
+/// value sent through their port. Functions for other variable types, FMU states, and derivatives are not supported.
+///
+/// ```ignore
+/// fn build() -> xdevs::modeling::Coupled {
+///     // ...
+/// }
+///
+/// xdevs::export_fmu!(build);
+/// ```
+#[macro_export]
+macro_rules! export_fmu {
+    ($build:path) => {
+        const _: () = {
+            use ::std::ffi::{c_char, c_int, c_uint, c_void};
+            use $crate::fmi::__shims as shims;
+
+            #[no_mangle]
+            pub unsafe extern "C" fn fmi2GetTypesPlatform() -> *const c_char {
+                shims::get_types_platform()
+            }
+            #[no_mangle]
+            pub unsafe extern "C" fn fmi2GetVersion() -> *const c_char {
+                shims::get_version()
+            }
+            #[no_mangle]
+            pub unsafe extern "C" fn fmi2SetDebugLogging(
+                c: *mut c_void,
+                logging_on: c_int,
+                n: usize,
+                categories: *const *const c_char,
+            ) -> c_int {
+                shims::set_debug_logging(c, logging_on, n, categories)
+            }
+            #[no_mangle]
+            pub unsafe extern "C" fn fmi2Instantiate(
+                instance_name: *const c_char,
+                fmu_type: c_int,
+                guid: *const c_char,
+                resource_location: *const c_char,
+                functions: *const c_void,
+                visible: c_int,
+                logging_on: c_int,
+            ) -> *mut c_void {
+                shims::instantiate(
+                    $build,
+                    instance_name,
+                    fmu_type,
+                    guid,
+                    resource_location,
+                    functions,
+                    visible,
+                    logging_on,
+                )
+            }
+            #[no_mangle]
+            pub unsafe extern "C" fn fmi2FreeInstance(c: *mut c_void) {
+                shims::free_instance(c)
+            }
+            #[no_mangle]
+            pub unsafe extern "C" fn fmi2SetupExperiment(
+                c: *mut c_void,
+                tolerance_defined: c_int,
+                tolerance: f64,
+                start_time: f64,
+                stop_time_defined: c_int,
+                stop_time: f64,
+            ) -> c_int {
+                shims::setup_experiment(
+                    c,
+                    tolerance_defined,
+                    tolerance,
+                    start_time,
+                    stop_time_defined,
+                    stop_time,
+                )
+            }
+            #[no_mangle]
+            pub unsafe extern "C" fn fmi2EnterInitializationMode(c: *mut c_void) -> c_int {
+                shims::enter_initialization_mode(c)
+            }
+            #[no_mangle]
+            pub unsafe extern "C" fn fmi2ExitInitializationMode(c: *mut c_void) -> c_int {
+                shims::exit_initialization_mode(c)
+            }
+            #[no_mangle]
+            pub unsafe extern "C" fn fmi2Terminate(c: *mut c_void) -> c_int {
+                shims::terminate(c)
+            }
+            #[no_mangle]
+            pub unsafe extern "C" fn fmi2Reset(c: *mut c_void) -> c_int {
+                shims::reset(c)
+            }
+            #[no_mangle]
+            pub unsafe extern "C" fn fmi2GetReal(
+                c: *mut c_void,
+                vr: *const c_uint,
+                nvr: usize,
+                value: *mut f64,
+            ) -> c_int {
+                shims::get_real(c, vr, nvr, value)
+            }
+            #[no_mangle]
+            pub unsafe extern "C" fn fmi2SetReal(
+                c: *mut c_void,
+                vr: *const c_uint,
+                nvr: usize,
+                value: *const f64,
+            ) -> c_int {
+                shims::set_real(c, vr, nvr, value)
+            }
+            #[no_mangle]
+            pub unsafe extern "C" fn fmi2DoStep(
+                c: *mut c_void,
+                current_communication_point: f64,
+                communication_step_size: f64,
+                no_set_fmu_state_prior_to_current_point: c_int,
+            ) -> c_int {
+                shims::do_step(
+                    c,
+                    current_communication_point,
+                    communication_step_size,
+                    no_set_fmu_state_prior_to_current_point,
+                )
+            }
+            #[no_mangle]
+            pub unsafe extern "C" fn fmi2GetStatus(
+                c: *mut c_void,
+                kind: c_int,
+                value: *mut c_void,
+            ) -> c_int {
+                shims::get_status(c, kind, value)
+            }
+            #[no_mangle]
+            pub unsafe extern "C" fn fmi2GetRealStatus(
+                c: *mut c_void,
+                kind: c_int,
+                value: *mut c_void,
+            ) -> c_int {
+                shims::get_status(c, kind, value)
+            }
+            #[no_mangle]
+            pub unsafe extern "C" fn fmi2GetIntegerStatus(
+                c: *mut c_void,
+                kind: c_int,
+                value: *mut c_void,
+            ) -> c_int {
+                shims::get_status(c, kind, value)
+            }
+            #[no_mangle]
+            pub unsafe extern "C" fn fmi2GetBooleanStatus(
+                c: *mut c_void,
+                kind: c_int,
+                value: *mut c_void,
+            ) -> c_int {
+                shims::get_status(c, kind, value)
+            }
+            #[no_mangle]
+            pub unsafe extern "C" fn fmi2GetStringStatus(
+                c: *mut c_void,
+                kind: c_int,
+                value: *mut c_void,
+            ) -> c_int {
+                shims::get_status(c, kind, value)
+            }
+            $crate::export_fmu!(@unsupported
+                fmi2GetInteger(*mut c_void, *const c_uint, usize, *mut c_int),
+                fmi2GetBoolean(*mut c_void, *const c_uint, usize, *mut c_int),
+                fmi2GetString(*mut c_void, *const c_uint, usize, *mut *const c_char),
+                fmi2SetInteger(*mut c_void, *const c_uint, usize, *const c_int),
+                fmi2SetBoolean(*mut c_void, *const c_uint, usize, *const c_int),
+                fmi2SetString(*mut c_void, *const c_uint, usize, *const *const c_char),
+                fmi2GetFMUstate(*mut c_void, *mut *mut c_void),
+                fmi2SetFMUstate(*mut c_void, *mut c_void),
+                fmi2FreeFMUstate(*mut c_void, *mut *mut c_void),
+                fmi2SerializedFMUstateSize(*mut c_void, *mut c_void, *mut usize),
+                fmi2SerializeFMUstate(*mut c_void, *mut c_void, *mut c_char, usize),
+                fmi2DeSerializeFMUstate(*mut c_void, *const c_char, usize, *mut *mut c_void),
+                fmi2GetDirectionalDerivative(
+                    *mut c_void, *const c_uint, usize, *const c_uint, usize, *const f64, *mut f64
+                ),
+                fmi2SetRealInputDerivatives(*mut c_void, *const c_uint, usize, *const c_int, *const f64),
+                fmi2GetRealOutputDerivatives(*mut c_void, *const c_uint, usize, *const c_int, *mut f64),
+                fmi2CancelStep(*mut c_void),
+            );
+        };
+    };
+    (@unsupported $($name:ident($($arg:ty),* $(,)?)),* $(,)?) => {
+        $(
+            #[no_mangle]
+            pub unsafe extern "C" fn $name($(_: $arg),*) -> ::std::ffi::c_int {
+                $crate::fmi::__shims::UNSUPPORTED
+            }
+        )*
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::shims::*;
+    use super::*;
+    use crate::lib::Delay;
+    use std::ptr::null;
+
+    /// Builds a coupled model that delays its input half a time unit.
+    fn build() -> Coupled {
+        let mut coupled = Coupled::new("delayer");
+        coupled.add_in_port::<f64>("u");
+        coupled.add_in_port::<i32>("ignored");
+        coupled.add_out_port::<f64>("y");
+        coupled.add_component(Box::new(Delay::<f64>::new("delay", 0.5)));
+        coupled.add_eic("u", "delay", "input");
+        coupled.add_eoc("delay", "output", "y");
+        coupled
+    }
+
+    #[test]
+    fn test_model_description() {
+        let description = model_description(&build());
+        assert_eq!("delayer", description.model_identifier);
+        assert_eq!(2, description.variables.len());
+        assert_eq!(Some(0.), description.variable("u").unwrap().start);
+        assert_eq!(1, description.variable("y").unwrap().value_reference);
+        assert_eq!(
+            description,
+            ModelDescription::parse(&description.to_xml()).unwrap()
+        );
+        assert_eq!(description.guid, model_description(&build()).guid);
+    }
+
+    #[test]
+    fn test_shims() {
+        let guid = CString::new(model_description(&build()).guid).unwrap();
+        unsafe {
+            let c = instantiate(
+                build,
+                c"fmu".as_ptr(),
+                1,
+                c"{}".as_ptr(),
+                null(),
+                null(),
+                0,
+                0,
+            );
+            assert!(c.is_null());
+            let c = instantiate(
+                build,
+                c"fmu".as_ptr(),
+                1,
+                guid.as_ptr(),
+                null(),
+                null(),
+                0,
+                0,
+            );
+            assert!(!c.is_null());
+            assert_eq!(FMI2_OK, setup_experiment(c, 0, 0., 0., 0, 0.));
+            assert_eq!(FMI2_OK, enter_initialization_mode(c));
+            assert_eq!(FMI2_OK, exit_initialization_mode(c));
+
+            let (u, y, unknown) = (0, 1, 2);
+            let mut value = -1.;
+            assert_eq!(FMI2_OK, set_real(c, &u, 1, &1.));
+            assert_eq!(FMI2_ERROR, set_real(c, &y, 1, &1.));
+            assert_eq!(FMI2_OK, do_step(c, 0., 0.25, 1));
+            assert_eq!(FMI2_OK, get_real(c, &y, 1, &mut value));
+            assert_eq!(0., value);
+            // the delayed input is sent at the end of the second step
+            assert_eq!(FMI2_OK, do_step(c, 0.25, 0.25, 1));
+            assert_eq!(FMI2_OK, get_real(c, &y, 1, &mut value));
+            assert_eq!(1., value);
+            assert_eq!(FMI2_ERROR, get_real(c, &unknown, 1, &mut value));
+
+            assert_eq!(FMI2_OK, reset(c));
+            assert_eq!(FMI2_ERROR, do_step(c, 0., 0.25, 1));
+            assert_eq!(FMI2_OK, terminate(c));
+            free_instance(c);
+        }
+    }
+}
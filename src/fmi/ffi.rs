@@ -0,0 +1,120 @@
+//! Types of the FMI 2.0 C API.
+
+use std::ffi::{c_char, c_int, c_uint, c_void, CStr};
+
+pub(crate) type Fmi2Component = *mut c_void;
+pub(crate) type Fmi2ComponentEnvironment = *mut c_void;
+pub(crate) type Fmi2Status = c_int;
+pub(crate) type Fmi2Type = c_int;
+pub(crate) type Fmi2Boolean = c_int;
+pub(crate) type Fmi2ValueReference = c_uint;
+pub(crate) type Fmi2String = *const c_char;
+
+/// Shared library file of an FMU for the current platform.
+#[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+pub(crate) const LIBRARY: (&str, &str) = ("linux64", "so");
+#[cfg(all(target_os = "linux", target_pointer_width = "32"))]
+pub(crate) const LIBRARY: (&str, &str) = ("linux32", "so");
+#[cfg(target_os = "macos")]
+pub(crate) const LIBRARY: (&str, &str) = ("darwin64", "dylib");
+#[cfg(all(target_os = "windows", target_pointer_width = "64"))]
+pub(crate) const LIBRARY: (&str, &str) = ("win64", "dll");
+#[cfg(all(target_os = "windows", target_pointer_width = "32"))]
+pub(crate) const LIBRARY: (&str, &str) = ("win32", "dll");
+
+pub(crate) const FMI2_OK: Fmi2Status = 0;
+pub(crate) const FMI2_WARNING: Fmi2Status = 1;
+pub(crate) const FMI2_DISCARD: Fmi2Status = 2;
+pub(crate) const FMI2_ERROR: Fmi2Status = 3;
+pub(crate) const FMI2_CO_SIMULATION: Fmi2Type = 1;
+pub(crate) const FMI2_FALSE: Fmi2Boolean = 0;
+pub(crate) const FMI2_TRUE: Fmi2Boolean = 1;
+
+/// Logger callback. The C declaration is variadic, but stable Rust cannot define variadic functions.
+/// Declaring the fixed arguments only is sound in all the supported calling conventions,
+/// as callers are responsible for cleaning up the variadic arguments, which are simply ignored.
+pub(crate) type Fmi2CallbackLogger =
+    unsafe extern "C" fn(Fmi2ComponentEnvironment, Fmi2String, Fmi2Status, Fmi2String, Fmi2String);
+/// Logger callback as declared in C. Rust can call variadic functions, so exported FMUs use this declaration.
+pub(crate) type Fmi2CallbackLoggerVariadic = unsafe extern "C" fn(
+    Fmi2ComponentEnvironment,
+    Fmi2String,
+    Fmi2Status,
+    Fmi2String,
+    Fmi2String,
+    ...
+);
+pub(crate) type Fmi2CallbackAllocateMemory = unsafe extern "C" fn(usize, usize) -> *mut c_void;
+pub(crate) type Fmi2CallbackFreeMemory = unsafe extern "C" fn(*mut c_void);
+pub(crate) type Fmi2StepFinished = unsafe extern "C" fn(Fmi2ComponentEnvironment, Fmi2Status);
+
+/// Callback functions provided to FMU instances.
+#[repr(C)]
+pub(crate) struct Fmi2CallbackFunctions {
+    pub logger: Fmi2CallbackLogger,
+    pub allocate_memory: Fmi2CallbackAllocateMemory,
+    pub free_memory: Fmi2CallbackFreeMemory,
+    pub step_finished: Option<Fmi2StepFinished>,
+    pub component_environment: Fmi2ComponentEnvironment,
+}
+
+pub(crate) type Fmi2Instantiate = unsafe extern "C" fn(
+    Fmi2String,
+    Fmi2Type,
+    Fmi2String,
+    Fmi2String,
+    *const Fmi2CallbackFunctions,
+    Fmi2Boolean,
+    Fmi2Boolean,
+) -> Fmi2Component;
+pub(crate) type Fmi2FreeInstance = unsafe extern "C" fn(Fmi2Component);
+pub(crate) type Fmi2SetupExperiment =
+    unsafe extern "C" fn(Fmi2Component, Fmi2Boolean, f64, f64, Fmi2Boolean, f64) -> Fmi2Status;
+pub(crate) type Fmi2EnterInitializationMode = unsafe extern "C" fn(Fmi2Component) -> Fmi2Status;
+pub(crate) type Fmi2ExitInitializationMode = unsafe extern "C" fn(Fmi2Component) -> Fmi2Status;
+pub(crate) type Fmi2Terminate = unsafe extern "C" fn(Fmi2Component) -> Fmi2Status;
+pub(crate) type Fmi2GetReal =
+    unsafe extern "C" fn(Fmi2Component, *const Fmi2ValueReference, usize, *mut f64) -> Fmi2Status;
+pub(crate) type Fmi2SetReal =
+    unsafe extern "C" fn(Fmi2Component, *const Fmi2ValueReference, usize, *const f64) -> Fmi2Status;
+pub(crate) type Fmi2DoStep =
+    unsafe extern "C" fn(Fmi2Component, f64, f64, Fmi2Boolean) -> Fmi2Status;
+
+extern "C" {
+    fn calloc(n: usize, size: usize) -> *mut c_void;
+    fn free(ptr: *mut c_void);
+}
+
+/// Memory allocation callback provided to FMU instances.
+pub(crate) unsafe extern "C" fn allocate_memory(n: usize, size: usize) -> *mut c_void {
+    calloc(n, size)
+}
+
+/// Memory release callback provided to FMU instances.
+pub(crate) unsafe extern "C" fn free_memory(ptr: *mut c_void) {
+    free(ptr)
+}
+
+/// Logger callback provided to FMU instances. Messages are printed to the standard error.
+/// Format arguments are not expanded, as the variadic arguments are not accessible.
+pub(crate) unsafe extern "C" fn logger(
+    _env: Fmi2ComponentEnvironment,
+    instance: Fmi2String,
+    status: Fmi2Status,
+    category: Fmi2String,
+    message: Fmi2String,
+) {
+    let instance = string(instance);
+    let category = string(category);
+    let message = string(message);
+    eprintln!("[{instance}] {category} ({status}): {message}");
+}
+
+/// Converts a C string into a Rust string. Null pointers are converted into empty strings.
+unsafe fn string<'a>(s: Fmi2String) -> std::borrow::Cow<'a, str> {
+    if s.is_null() {
+        "".into()
+    } else {
+        CStr::from_ptr(s).to_string_lossy()
+    }
+}
@@ -0,0 +1,368 @@
+use super::description::{Causality, ModelDescription};
+use super::ffi::*;
+use super::FmiError;
+use crate::modeling::{Atomic, Component, InPort, OutPort};
+use std::ffi::CString;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counter for creating unique extraction directories.
+static EXTRACTIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Functions of the FMI 2.0 co-simulation API used by [`Fmu`].
+struct Functions {
+    instantiate: Fmi2Instantiate,
+    free_instance: Fmi2FreeInstance,
+    setup_experiment: Fmi2SetupExperiment,
+    enter_initialization_mode: Fmi2EnterInitializationMode,
+    exit_initialization_mode: Fmi2ExitInitializationMode,
+    terminate: Fmi2Terminate,
+    get_real: Fmi2GetReal,
+    set_real: Fmi2SetReal,
+    do_step: Fmi2DoStep,
+}
+
+impl Functions {
+    /// Loads the functions from the shared library of an FMU.
+    ///
+    /// # Safety
+    ///
+    /// The library must implement the FMI 2.0 co-simulation API.
+    unsafe fn load(library: &libloading::Library) -> Result<Self, FmiError> {
+        unsafe fn symbol<F: Copy>(
+            library: &libloading::Library,
+            name: &str,
+        ) -> Result<F, FmiError> {
+            library
+                .get::<F>(name.as_bytes())
+                .map(|f| *f)
+                .map_err(|e| FmiError::Library(format!("{name}: {e}")))
+        }
+        Ok(Self {
+            instantiate: symbol(library, "fmi2Instantiate")?,
+            free_instance: symbol(library, "fmi2FreeInstance")?,
+            setup_experiment: symbol(library, "fmi2SetupExperiment")?,
+            enter_initialization_mode: symbol(library, "fmi2EnterInitializationMode")?,
+            exit_initialization_mode: symbol(library, "fmi2ExitInitializationMode")?,
+            terminate: symbol(library, "fmi2Terminate")?,
+            get_real: symbol(library, "fmi2GetReal")?,
+            set_real: symbol(library, "fmi2SetReal")?,
+            do_step: symbol(library, "fmi2DoStep")?,
+        })
+    }
+}
+
+/// Atomic model that wraps an FMI 2.0 co-simulation FMU.
+/// Every real input (output) variable of the FMU is mapped to an input (output) port of type [`f64`]
+/// with the same name. The FMU is advanced with a fixed communication step size:
+/// at every communication point, the model emits the current value of every output variable.
+/// Input events advance the FMU up to the time of the event before setting the new input values,
+/// so the FMU always sees the latest value received through every input port.
+/// Initial output values are emitted at time 0.
+///
+/// The FMU is instantiated when the simulation starts and freed when it stops.
+/// As DEVS transition functions cannot fail, errors returned by the FMU during the simulation cause a panic.
+pub struct Fmu {
+    /// Component of the atomic model.
+    component: Component,
+    /// Model description of the FMU.
+    description: ModelDescription,
+    /// Directory where the FMU was extracted. It is removed when the model is dropped.
+    directory: PathBuf,
+    /// Shared library of the FMU. It must outlive `functions`.
+    _library: libloading::Library,
+    /// Functions of the FMI API.
+    functions: Functions,
+    /// Callback functions provided to the FMU instance. They must outlive the instance.
+    callbacks: Box<Fmi2CallbackFunctions>,
+    /// FMU instance. It is null if the simulation is not running.
+    instance: Fmi2Component,
+    /// Communication step size.
+    step_size: f64,
+    /// Parameter values set by the user (value reference and value).
+    parameters: Vec<(Fmi2ValueReference, f64)>,
+    /// Input ports and value references of their input variables.
+    inputs: Vec<(InPort<f64>, Fmi2ValueReference)>,
+    /// Output ports and value references of their output variables.
+    outputs: Vec<(OutPort<f64>, Fmi2ValueReference)>,
+    /// Current values of the output variables.
+    output_values: Vec<f64>,
+    /// Current simulation time of the FMU.
+    time: f64,
+    /// Time remaining until the next communication point.
+    sigma: f64,
+    /// It is `true` if the output values must be emitted.
+    emitting: bool,
+}
+
+// Safety: FMU instances are only accessed through the simulator, which never runs two methods of
+// the same model concurrently. The output function only reads the cached output values.
+unsafe impl Send for Fmu {}
+unsafe impl Sync for Fmu {}
+
+impl Fmu {
+    /// Loads an FMI 2.0 co-simulation FMU from the archive in `path`.
+    /// The FMU is advanced in steps of `step_size` simulation time units. It panics if `step_size` is not positive.
+    pub fn new(name: &str, path: impl AsRef<Path>, step_size: f64) -> Result<Self, FmiError> {
+        if step_size.is_nan() || step_size <= 0. {
+            panic!("step size must be positive");
+        }
+        let directory = std::env::temp_dir().join(format!(
+            "xdevs_fmu_{}_{}",
+            std::process::id(),
+            EXTRACTIONS.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = std::fs::File::open(path)?;
+        let result = zip::ZipArchive::new(file)
+            .and_then(|mut archive| archive.extract(&directory))
+            .map_err(|e| FmiError::Archive(e.to_string()))
+            .and_then(|_| Self::from_directory(name, &directory, step_size));
+        if result.is_err() {
+            let _ = std::fs::remove_dir_all(&directory);
+        }
+        result
+    }
+
+    /// Loads an FMU from the directory where it was extracted.
+    fn from_directory(name: &str, directory: &Path, step_size: f64) -> Result<Self, FmiError> {
+        let xml = std::fs::read_to_string(directory.join("modelDescription.xml"))?;
+        let description = ModelDescription::parse(&xml)?;
+        let (platform, extension) = LIBRARY;
+        let library_path = directory
+            .join("binaries")
+            .join(platform)
+            .join(format!("{}.{extension}", description.model_identifier));
+        // Safety: loading the library runs its initialization routines, which are trusted as the FMU itself
+        let library = unsafe { libloading::Library::new(&library_path) }
+            .map_err(|e| FmiError::Library(e.to_string()))?;
+        // Safety: FMI 2.0 co-simulation FMUs implement the FMI 2.0 co-simulation API
+        let functions = unsafe { Functions::load(&library)? };
+
+        let mut component = Component::new(name);
+        let mut inputs = Vec::new();
+        for variable in description.variables_with(Causality::Input) {
+            let port = component
+                .try_add_in_port(&variable.name)
+                .map_err(|e| FmiError::Description(e.to_string()))?;
+            inputs.push((port, variable.value_reference));
+        }
+        let mut outputs = Vec::new();
+        for variable in description.variables_with(Causality::Output) {
+            let port = component
+                .try_add_out_port(&variable.name)
+                .map_err(|e| FmiError::Description(e.to_string()))?;
+            outputs.push((port, variable.value_reference));
+        }
+        let output_values = vec![0.; outputs.len()];
+
+        Ok(Self {
+            component,
+            description,
+            directory: directory.to_path_buf(),
+            _library: library,
+            functions,
+            callbacks: Box::new(Fmi2CallbackFunctions {
+                logger,
+                allocate_memory,
+                free_memory,
+                step_finished: None,
+                component_environment: std::ptr::null_mut(),
+            }),
+            instance: std::ptr::null_mut(),
+            step_size,
+            parameters: Vec::new(),
+            inputs,
+            outputs,
+            output_values,
+            time: 0.,
+            sigma: f64::INFINITY,
+            emitting: false,
+        })
+    }
+
+    /// Returns the model description of the FMU.
+    pub fn description(&self) -> &ModelDescription {
+        &self.description
+    }
+
+    /// Sets the value of a real parameter of the FMU. It is applied every time the simulation starts.
+    pub fn set_parameter(&mut self, name: &str, value: f64) -> Result<(), FmiError> {
+        let variable = self
+            .description
+            .variable(name)
+            .filter(|v| v.causality == Causality::Parameter)
+            .ok_or_else(|| FmiError::UnknownVariable(name.to_string()))?;
+        let vr = variable.value_reference;
+        match self.parameters.iter_mut().find(|(r, _)| *r == vr) {
+            Some((_, v)) => *v = value,
+            None => self.parameters.push((vr, value)),
+        }
+        Ok(())
+    }
+
+    /// Returns the current values of the output variables of the FMU, in the order of the output ports.
+    pub fn output_values(&self) -> &[f64] {
+        &self.output_values
+    }
+
+    /// Panics if an FMI function did not succeed.
+    fn check(function: &'static str, status: Fmi2Status) {
+        if status != FMI2_OK && status != FMI2_WARNING {
+            panic!("{}", FmiError::Status(function, status));
+        }
+    }
+
+    /// Creates and initializes a new FMU instance.
+    fn instantiate(&mut self) {
+        let name = CString::new(self.component.get_name()).unwrap();
+        let guid = CString::new(self.description.guid.as_str()).unwrap();
+        let resources = CString::new(format!(
+            "file://{}",
+            self.directory
+                .join("resources")
+                .to_string_lossy()
+                .replace('\\', "/")
+        ))
+        .unwrap();
+        let f = &self.functions;
+        // Safety: all the strings and callbacks outlive the call (and callbacks outlive the instance)
+        self.instance = unsafe {
+            (f.instantiate)(
+                name.as_ptr(),
+                FMI2_CO_SIMULATION,
+                guid.as_ptr(),
+                resources.as_ptr(),
+                &*self.callbacks,
+                FMI2_FALSE,
+                FMI2_FALSE,
+            )
+        };
+        if self.instance.is_null() {
+            panic!("could not instantiate FMU {}", self.component.get_name());
+        }
+        // Safety: the instance is valid and the parameter buffers match the number of values
+        unsafe {
+            for &(vr, value) in self.parameters.iter() {
+                Self::check("fmi2SetReal", (f.set_real)(self.instance, &vr, 1, &value));
+            }
+            Self::check(
+                "fmi2SetupExperiment",
+                (f.setup_experiment)(self.instance, FMI2_FALSE, 0., 0., FMI2_FALSE, 0.),
+            );
+            Self::check(
+                "fmi2EnterInitializationMode",
+                (f.enter_initialization_mode)(self.instance),
+            );
+            Self::check(
+                "fmi2ExitInitializationMode",
+                (f.exit_initialization_mode)(self.instance),
+            );
+        }
+    }
+
+    /// Terminates and frees the FMU instance (if any). Termination errors are ignored,
+    /// as the instance is freed anyway.
+    fn free(&mut self) {
+        if !self.instance.is_null() {
+            // Safety: the instance is valid and it is not used after being freed
+            unsafe {
+                (self.functions.terminate)(self.instance);
+                (self.functions.free_instance)(self.instance);
+            }
+            self.instance = std::ptr::null_mut();
+        }
+    }
+
+    /// Advances the FMU `h` time units.
+    fn do_step(&mut self, h: f64) {
+        // Safety: the instance is valid while the simulation is running
+        let status = unsafe { (self.functions.do_step)(self.instance, self.time, h, FMI2_TRUE) };
+        Self::check("fmi2DoStep", status);
+        self.time += h;
+    }
+
+    /// Reads the current values of the output variables of the FMU.
+    fn read_outputs(&mut self) {
+        let vrs: Vec<_> = self.outputs.iter().map(|(_, vr)| *vr).collect();
+        // Safety: the instance is valid and the buffers have the same length
+        let status = unsafe {
+            (self.functions.get_real)(
+                self.instance,
+                vrs.as_ptr(),
+                vrs.len(),
+                self.output_values.as_mut_ptr(),
+            )
+        };
+        Self::check("fmi2GetReal", status);
+    }
+}
+
+impl Atomic for Fmu {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.free();
+        self.instantiate();
+        self.time = 0.;
+        self.read_outputs();
+        self.sigma = 0.;
+        self.emitting = true;
+    }
+
+    fn stop(&mut self) {
+        self.free();
+    }
+
+    fn lambda(&self) {
+        if self.emitting {
+            for ((port, _), value) in self.outputs.iter().zip(self.output_values.iter()) {
+                // Safety: adding message on atomic model's output port at lambda
+                unsafe { port.add_value(*value) };
+            }
+        }
+    }
+
+    fn delta_int(&mut self) {
+        if self.emitting {
+            self.emitting = false;
+            self.sigma = self.step_size;
+        } else {
+            self.do_step(self.sigma);
+            self.read_outputs();
+            self.emitting = true;
+            self.sigma = 0.;
+        }
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        if e > 0. {
+            self.do_step(e);
+        }
+        self.sigma -= e;
+        for (port, vr) in self.inputs.iter() {
+            // Safety: reading messages on atomic model's input port at delta_ext
+            if let Some(value) = unsafe { port.get_values() }.last() {
+                // Safety: the instance is valid while the simulation is running
+                let status = unsafe { (self.functions.set_real)(self.instance, vr, 1, value) };
+                Self::check("fmi2SetReal", status);
+            }
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        self.sigma
+    }
+}
+
+impl Drop for Fmu {
+    fn drop(&mut self) {
+        self.free();
+        let _ = std::fs::remove_dir_all(&self.directory);
+    }
+}
@@ -1,6 +1,76 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bridge;
+pub mod celldevs;
 pub mod devstone;
+pub mod error;
+pub mod experiment;
+#[cfg(feature = "fmi")]
+pub mod fmi;
+/// Library of reusable atomic models for building queueing-style systems and sampled-data control loops.
+pub mod lib {
+    mod delay;
+    mod generator;
+    mod pid;
+    mod processor;
+    mod quantizer;
+    mod queue;
+    mod sampler;
+    mod signal;
+    mod transducer;
+
+    pub use delay::Delay;
+    pub use generator::{Generator, Job, TimeSampler};
+    pub use pid::{AntiWindup, Pid};
+    pub use processor::Processor;
+    pub use quantizer::Quantizer;
+    pub use queue::Queue;
+    pub use sampler::{Sampler, ZeroOrderHold};
+    pub use signal::{SignalGenerator, Waveform};
+    pub use transducer::Transducer;
+}
+pub mod markov;
+#[cfg(feature = "track_alloc")]
+pub mod memory;
 pub mod modeling;
+pub mod network;
+pub mod ode;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod qss;
+pub mod queueing;
+pub mod random;
+pub mod scenario;
 pub mod simulation;
+pub mod statechart;
+pub mod stats;
+pub mod testing;
+pub mod time;
+pub mod tracing;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+// WebAssembly targets do not support threads, so rayon cannot spawn its thread pool
+#[cfg(all(
+    target_arch = "wasm32",
+    any(feature = "par_any", feature = "par_experiment")
+))]
+compile_error!("parallel features are not supported in wasm32 targets");
+
+// FMUs are native shared libraries, so they cannot be loaded from WebAssembly modules
+#[cfg(all(target_arch = "wasm32", feature = "fmi"))]
+compile_error!("the fmi feature is not supported in wasm32 targets");
+
+// Parallel features share models between threads, so they need atomic reference counting
+#[cfg(all(feature = "single_thread", feature = "par_any"))]
+compile_error!("the single_thread feature is not compatible with the par_* features (they need Arc and Send models)");
+
+/// Shared pointer for ports and [`modeling::SharedMsg`] payloads. By default, it is [`std::sync::Arc`].
+#[cfg(not(feature = "single_thread"))]
+pub type Shared<T> = std::sync::Arc<T>;
+/// Shared pointer for ports and [`modeling::SharedMsg`] payloads. With the `single_thread` feature,
+/// it is [`std::rc::Rc`], so sequential simulations do not pay for atomic reference counting.
+#[cfg(feature = "single_thread")]
+pub type Shared<T> = std::rc::Rc<T>;
 
 /// Helper trait for avoiding verbose trait constraints.
 #[cfg(not(feature = "par_any"))]
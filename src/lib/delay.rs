@@ -0,0 +1,99 @@
+use crate::modeling::{Atomic, Component, InPort, OutPort};
+use crate::DynRef;
+use std::collections::VecDeque;
+
+/// Atomic model that forwards every message received through its `input` port
+/// through its `output` port after a constant delay. Unlike a [`super::Processor`],
+/// it can hold any number of messages at the same time.
+pub struct Delay<V: DynRef + Clone> {
+    /// Component of the atomic model.
+    component: Component,
+    /// Delay applied to every message.
+    delay: f64,
+    /// Current simulation time.
+    clock: f64,
+    /// Messages in transit and the time at which they must be forwarded.
+    pending: VecDeque<(f64, V)>,
+    /// Input port for receiving messages.
+    input: InPort<V>,
+    /// Output port for forwarding messages.
+    output: OutPort<V>,
+}
+
+impl<V: DynRef + Clone> Delay<V> {
+    /// Creates a new delay model. It panics if `delay` is negative or NaN.
+    pub fn new(name: &str, delay: f64) -> Self {
+        if delay.is_nan() || delay < 0. {
+            panic!("delay must be non-negative");
+        }
+        let mut component = Component::new(name);
+        let input = component.add_in_port::<V>("input");
+        let output = component.add_out_port::<V>("output");
+        Self {
+            component,
+            delay,
+            clock: 0.,
+            pending: VecDeque::new(),
+            input,
+            output,
+        }
+    }
+
+    /// Returns the number of messages that are in transit.
+    pub fn n_pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns the messages that must be forwarded at the next internal event.
+    fn next_batch(&self) -> impl Iterator<Item = &V> {
+        let t = self.pending.front().map(|(t, _)| *t);
+        self.pending
+            .iter()
+            .take_while(move |(time, _)| Some(*time) == t)
+            .map(|(_, v)| v)
+    }
+}
+
+impl<V: DynRef + Clone> Atomic for Delay<V> {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.clock = 0.;
+        self.pending.clear();
+    }
+
+    fn lambda(&self) {
+        for value in self.next_batch() {
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.output.add_value(value.clone()) };
+        }
+    }
+
+    fn delta_int(&mut self) {
+        let n = self.next_batch().count();
+        self.clock = self.pending[0].0;
+        self.pending.drain(..n);
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        self.clock += e;
+        // Safety: reading messages on atomic model's input port at delta_ext
+        for value in unsafe { self.input.get_values() } {
+            self.pending
+                .push_back((self.clock + self.delay, value.clone()));
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        match self.pending.front() {
+            Some((t, _)) => t - self.clock,
+            None => f64::INFINITY,
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::modeling::{Atomic, Component, InPort, OutPort};
+use crate::DynRef;
+
+/// Function that samples time intervals (e.g., interarrival or service times).
+pub trait TimeSampler: FnMut() -> f64 + DynRef {}
+
+impl<F: FnMut() -> f64 + DynRef> TimeSampler for F {}
+
+/// Job generated by a [`Generator`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Job {
+    /// Identifier of the job. Jobs are numbered sequentially from 0.
+    pub id: usize,
+    /// Simulation time at which the job was generated.
+    pub time: f64,
+}
+
+/// Atomic model that emits [`Job`]s through its `output` port.
+/// The time between emissions is given by a [`TimeSampler`], so it can be periodic or stochastic.
+/// The first job is emitted after the first sampled interval.
+/// When it receives a message through its `stop` port, it stops emitting jobs.
+pub struct Generator {
+    /// Component of the atomic model.
+    component: Component,
+    /// Sampler of the time between emissions.
+    interarrival: Box<dyn TimeSampler>,
+    /// Time remaining until the next emission.
+    sigma: f64,
+    /// Number of jobs emitted so far.
+    count: usize,
+    /// Input port for stopping the generator.
+    stop: InPort<bool>,
+    /// Output port for emitting jobs.
+    output: OutPort<Job>,
+}
+
+impl Generator {
+    /// Creates a new generator that emits a job every `period` time units.
+    pub fn new(name: &str, period: f64) -> Self {
+        Self::stochastic(name, move || period)
+    }
+
+    /// Creates a new generator whose time between emissions is sampled from `interarrival`.
+    pub fn stochastic(name: &str, interarrival: impl TimeSampler) -> Self {
+        let mut component = Component::new(name);
+        let stop = component.add_in_port::<bool>("stop");
+        let output = component.add_out_port::<Job>("output");
+        Self {
+            component,
+            interarrival: Box::new(interarrival),
+            sigma: f64::INFINITY,
+            count: 0,
+            stop,
+            output,
+        }
+    }
+
+    /// Returns the number of jobs emitted so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl Atomic for Generator {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.count = 0;
+        self.sigma = (self.interarrival)();
+    }
+
+    fn lambda(&self) {
+        let job = Job {
+            id: self.count,
+            time: self.component.get_t_next(),
+        };
+        // Safety: adding message on atomic model's output port at lambda
+        unsafe { self.output.add_value(job) };
+    }
+
+    fn delta_int(&mut self) {
+        self.count += 1;
+        self.sigma = (self.interarrival)();
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        self.sigma -= e;
+        // Safety: reading messages on atomic model's input port at delta_ext
+        if !unsafe { self.stop.is_empty() } {
+            self.sigma = f64::INFINITY;
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        self.sigma
+    }
+}
@@ -0,0 +1,228 @@
+use crate::modeling::{Atomic, Component, InPort, OutPort};
+
+/// Anti-windup strategy of a [`Pid`] controller with a saturated output.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AntiWindup {
+    /// The integral term is always updated.
+    None,
+    /// The integral term is not updated while the output is saturated and the error pushes it further into saturation.
+    Clamping,
+    /// The integral term is corrected with the difference between the saturated and the unsaturated output,
+    /// multiplied by the given tracking gain.
+    BackCalculation(f64),
+}
+
+/// Atomic model of a discrete PID controller. It receives the reference through its `setpoint` port and
+/// the output of the plant through its `measurement` port (both keep their last value), and every `period`
+/// time units it sends the control action through its `output` port. The first control action is sent after one period.
+///
+/// The control action is `kp * e + ki * sum(e * period) + kd * (e - e_prev) / period`, where `e` is the difference
+/// between the setpoint and the measurement at the sampling time. The derivative term is 0 at the first sample.
+/// If the output is saturated (see [`Pid::saturation`]), the [`AntiWindup`] strategy prevents the integral term
+/// from growing while the actuator cannot follow the controller.
+pub struct Pid {
+    /// Component of the atomic model.
+    component: Component,
+    /// Proportional gain.
+    kp: f64,
+    /// Integral gain.
+    ki: f64,
+    /// Derivative gain.
+    kd: f64,
+    /// Sampling period.
+    period: f64,
+    /// Lower and upper limits of the control action.
+    limits: (f64, f64),
+    /// Anti-windup strategy.
+    anti_windup: AntiWindup,
+    /// Initial setpoint.
+    initial_setpoint: f64,
+    /// Current setpoint.
+    setpoint: f64,
+    /// Last measurement.
+    measurement: f64,
+    /// Integral term.
+    integral: f64,
+    /// Error at the previous sample, if any.
+    prev_error: Option<f64>,
+    /// Last control action.
+    action: f64,
+    /// Time remaining until the next sample.
+    sigma: f64,
+    /// Input port for receiving setpoints.
+    setpoint_port: InPort<f64>,
+    /// Input port for receiving measurements.
+    measurement_port: InPort<f64>,
+    /// Output port for sending control actions.
+    output: OutPort<f64>,
+}
+
+impl Pid {
+    /// Creates a new PID controller with the given gains and sampling period, a setpoint of 0,
+    /// and no saturation. It panics if `period` is not positive.
+    pub fn new(name: &str, kp: f64, ki: f64, kd: f64, period: f64) -> Self {
+        assert!(period > 0., "period must be positive");
+        let mut component = Component::new(name);
+        let setpoint_port = component.add_in_port::<f64>("setpoint");
+        let measurement_port = component.add_in_port::<f64>("measurement");
+        let output = component.add_out_port::<f64>("output");
+        Self {
+            component,
+            kp,
+            ki,
+            kd,
+            period,
+            limits: (f64::NEG_INFINITY, f64::INFINITY),
+            anti_windup: AntiWindup::Clamping,
+            initial_setpoint: 0.,
+            setpoint: 0.,
+            measurement: 0.,
+            integral: 0.,
+            prev_error: None,
+            action: 0.,
+            sigma: period,
+            setpoint_port,
+            measurement_port,
+            output,
+        }
+    }
+
+    /// Sets the initial setpoint.
+    pub fn setpoint(mut self, setpoint: f64) -> Self {
+        self.initial_setpoint = setpoint;
+        self.setpoint = setpoint;
+        self
+    }
+
+    /// Limits the control action to `[min, max]`. It panics if `min` is greater than `max`.
+    pub fn saturation(mut self, min: f64, max: f64) -> Self {
+        assert!(
+            min <= max,
+            "lower limit must not be greater than upper limit"
+        );
+        self.limits = (min, max);
+        self
+    }
+
+    /// Sets the anti-windup strategy. By default, it is [`AntiWindup::Clamping`].
+    pub fn anti_windup(mut self, anti_windup: AntiWindup) -> Self {
+        self.anti_windup = anti_windup;
+        self
+    }
+
+    /// Returns the last control action (0 before the first sample).
+    pub fn action(&self) -> f64 {
+        self.action
+    }
+
+    /// Returns the current value of the integral term.
+    pub fn integral(&self) -> f64 {
+        self.integral
+    }
+
+    /// Computes the control action and the integral term at the next sample.
+    fn control(&self) -> (f64, f64) {
+        let error = self.setpoint - self.measurement;
+        let derivative = match self.prev_error {
+            Some(prev) => self.kd * (error - prev) / self.period,
+            None => 0.,
+        };
+        let mut integral = self.integral + self.ki * error * self.period;
+        let unsaturated = self.kp * error + integral + derivative;
+        let action = unsaturated.clamp(self.limits.0, self.limits.1);
+        match self.anti_windup {
+            AntiWindup::None => (),
+            AntiWindup::Clamping => {
+                if action != unsaturated && (unsaturated - action) * error > 0. {
+                    integral = self.integral;
+                }
+            }
+            AntiWindup::BackCalculation(gain) => {
+                integral += gain * (action - unsaturated) * self.period;
+            }
+        }
+        (action, integral)
+    }
+}
+
+impl Atomic for Pid {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.setpoint = self.initial_setpoint;
+        self.measurement = 0.;
+        self.integral = 0.;
+        self.prev_error = None;
+        self.action = 0.;
+        self.sigma = self.period;
+    }
+
+    fn lambda(&self) {
+        // Safety: adding message on atomic model's output port at lambda
+        unsafe { self.output.add_value(self.control().0) };
+    }
+
+    fn delta_int(&mut self) {
+        (self.action, self.integral) = self.control();
+        self.prev_error = Some(self.setpoint - self.measurement);
+        self.sigma = self.period;
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        self.sigma -= e;
+        // Safety: reading messages on atomic model's input ports at delta_ext
+        if let Some(&setpoint) = unsafe { self.setpoint_port.get_values() }.last() {
+            self.setpoint = setpoint;
+        }
+        // Safety: reading messages on atomic model's input ports at delta_ext
+        if let Some(&measurement) = unsafe { self.measurement_port.get_values() }.last() {
+            self.measurement = measurement;
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        self.sigma
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::AtomicTester;
+
+    /// Returns the control actions of a controller at t = 1 and t = 2 with setpoint 1
+    /// and measurements 0 and 0.5, and the final value of its integral term.
+    fn actions(pid: Pid) -> (Vec<(f64, f64)>, f64) {
+        let mut tester = AtomicTester::new(pid.setpoint(1.));
+        let mut actions = Vec::new();
+        tester.advance_to(1.);
+        actions.extend(tester.outputs::<f64>("output"));
+        tester.inject("measurement", 0.5).advance_to(1.5);
+        tester.advance_to(2.);
+        actions.extend(tester.outputs::<f64>("output"));
+        (actions, tester.finish().integral())
+    }
+
+    #[test]
+    fn test_pid() {
+        let pid = || Pid::new("pid", 2., 1., 0.5, 1.);
+        // 2 * 1 + 1 + 0, and 2 * 0.5 + 1.5 + 0.5 * (0.5 - 1)
+        assert_eq!((vec![(1., 3.), (2., 2.25)], 1.5), actions(pid()));
+
+        let saturated = pid().saturation(-2., 2.);
+        assert_eq!((vec![(1., 2.), (2., 1.25)], 0.5), actions(saturated));
+        let saturated = pid().saturation(-2., 2.).anti_windup(AntiWindup::None);
+        assert_eq!((vec![(1., 2.), (2., 2.)], 1.5), actions(saturated));
+        let saturated = pid()
+            .saturation(-2., 2.)
+            .anti_windup(AntiWindup::BackCalculation(1.));
+        // the integral term is corrected by 2 - 3 at the first sample
+        assert_eq!((vec![(1., 2.), (2., 1.25)], 0.5), actions(saturated));
+    }
+}
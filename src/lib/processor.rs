@@ -0,0 +1,101 @@
+use super::TimeSampler;
+use crate::modeling::{Atomic, Component, InPort, OutPort};
+use crate::DynRef;
+
+/// Atomic model that processes one message at a time. When idle, it accepts a message through its `input` port
+/// and becomes busy during a service time given by a [`TimeSampler`]. Then, it sends the message through
+/// its `output` port and becomes idle again. Messages that arrive while the processor is busy are dropped.
+pub struct Processor<V: DynRef + Clone> {
+    /// Component of the atomic model.
+    component: Component,
+    /// Sampler of the service time.
+    service: Box<dyn TimeSampler>,
+    /// Message being processed. It is [`None`] if the processor is idle.
+    job: Option<V>,
+    /// Time remaining until the current message is processed.
+    sigma: f64,
+    /// Number of messages dropped because the processor was busy.
+    n_dropped: usize,
+    /// Input port for receiving messages.
+    input: InPort<V>,
+    /// Output port for sending processed messages.
+    output: OutPort<V>,
+}
+
+impl<V: DynRef + Clone> Processor<V> {
+    /// Creates a new processor with a constant service time.
+    pub fn new(name: &str, service_time: f64) -> Self {
+        Self::stochastic(name, move || service_time)
+    }
+
+    /// Creates a new processor whose service time is sampled from `service`.
+    pub fn stochastic(name: &str, service: impl TimeSampler) -> Self {
+        let mut component = Component::new(name);
+        let input = component.add_in_port::<V>("input");
+        let output = component.add_out_port::<V>("output");
+        Self {
+            component,
+            service: Box::new(service),
+            job: None,
+            sigma: f64::INFINITY,
+            n_dropped: 0,
+            input,
+            output,
+        }
+    }
+
+    /// Returns `true` if the processor is busy.
+    pub fn is_busy(&self) -> bool {
+        self.job.is_some()
+    }
+
+    /// Returns the number of messages dropped because the processor was busy.
+    pub fn n_dropped(&self) -> usize {
+        self.n_dropped
+    }
+}
+
+impl<V: DynRef + Clone> Atomic for Processor<V> {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.job = None;
+        self.sigma = f64::INFINITY;
+        self.n_dropped = 0;
+    }
+
+    fn lambda(&self) {
+        if let Some(job) = &self.job {
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.output.add_value(job.clone()) };
+        }
+    }
+
+    fn delta_int(&mut self) {
+        self.job = None;
+        self.sigma = f64::INFINITY;
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        self.sigma -= e;
+        // Safety: reading messages on atomic model's input port at delta_ext
+        let mut values = unsafe { self.input.get_values() }.iter();
+        if self.job.is_none() {
+            if let Some(job) = values.next() {
+                self.job = Some(job.clone());
+                self.sigma = (self.service)();
+            }
+        }
+        self.n_dropped += values.count();
+    }
+
+    fn ta(&self) -> f64 {
+        self.sigma
+    }
+}
@@ -0,0 +1,135 @@
+use crate::modeling::{Atomic, Component, InPort, OutPort};
+
+/// Atomic model that quantizes the values received through its `input` port to multiples of a quantum
+/// (e.g., to model the resolution of an analog-to-digital converter). It only sends the quantized value
+/// through its `output` port when it changes. With hysteresis, the input must exceed the midpoint between
+/// two levels by the hysteresis band to change the level, which filters out the chattering of noisy inputs.
+pub struct Quantizer {
+    /// Component of the atomic model.
+    component: Component,
+    /// Distance between quantization levels.
+    quantum: f64,
+    /// Hysteresis band.
+    hysteresis: f64,
+    /// Current quantization level.
+    level: Option<f64>,
+    /// Whether the current level must be sent.
+    changed: bool,
+    /// Input port for receiving values.
+    input: InPort<f64>,
+    /// Output port for sending quantized values.
+    output: OutPort<f64>,
+}
+
+impl Quantizer {
+    /// Creates a new quantizer without hysteresis. It panics if `quantum` is not positive.
+    pub fn new(name: &str, quantum: f64) -> Self {
+        assert!(quantum > 0., "quantum must be positive");
+        let mut component = Component::new(name);
+        let input = component.add_in_port::<f64>("input");
+        let output = component.add_out_port::<f64>("output");
+        Self {
+            component,
+            quantum,
+            hysteresis: 0.,
+            level: None,
+            changed: false,
+            input,
+            output,
+        }
+    }
+
+    /// Sets the hysteresis band. It panics if it is negative.
+    pub fn hysteresis(mut self, hysteresis: f64) -> Self {
+        assert!(hysteresis >= 0., "hysteresis must be non-negative");
+        self.hysteresis = hysteresis;
+        self
+    }
+
+    /// Returns the current quantization level, if any value has been received.
+    pub fn level(&self) -> Option<f64> {
+        self.level
+    }
+
+    /// Updates the quantization level with a new value.
+    fn quantize(&mut self, value: f64) {
+        let candidate = (value / self.quantum).round() * self.quantum;
+        let change = match self.level {
+            Some(level) => {
+                candidate != level && (value - level).abs() >= self.quantum / 2. + self.hysteresis
+            }
+            None => true,
+        };
+        if change {
+            self.level = Some(candidate);
+            self.changed = true;
+        }
+    }
+}
+
+impl Atomic for Quantizer {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.level = None;
+        self.changed = false;
+    }
+
+    fn lambda(&self) {
+        if let (true, Some(level)) = (self.changed, self.level) {
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.output.add_value(level) };
+        }
+    }
+
+    fn delta_int(&mut self) {
+        self.changed = false;
+    }
+
+    fn delta_ext(&mut self, _e: f64) {
+        // Safety: reading messages on atomic model's input port at delta_ext
+        if let Some(&value) = unsafe { self.input.get_values() }.last() {
+            self.quantize(value);
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        match self.changed {
+            true => 0.,
+            false => f64::INFINITY,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::AtomicTester;
+
+    #[test]
+    fn test_quantizer() {
+        let mut tester = AtomicTester::new(Quantizer::new("quantizer", 1.).hysteresis(0.2));
+        let mut levels = Vec::new();
+        for (t, value) in [
+            (0., 0.2),
+            (1., 0.9),
+            (2., 1.2),
+            (3., 1.6),
+            (4., 1.8),
+            (5., 0.4),
+        ] {
+            tester.inject("input", value).advance_to(t);
+            // quantized values are sent right after the input
+            tester.advance_to(t);
+            levels.extend(tester.outputs::<f64>("output"));
+        }
+        // 1.6 is within the hysteresis band of level 1, but 1.8 is not
+        assert_eq!(vec![(0., 0.), (1., 1.), (4., 2.), (5., 0.)], levels);
+    }
+}
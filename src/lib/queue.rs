@@ -0,0 +1,113 @@
+use crate::modeling::{Atomic, Component, InPort, OutPort};
+use crate::DynRef;
+use std::collections::VecDeque;
+
+/// FIFO queue with limited capacity. Messages received through its `input` port are stored
+/// and forwarded one at a time through its `output` port. After forwarding a message, the queue waits
+/// until it receives a message through its `done` port (e.g., from a [`super::Processor`]) to forward the next one.
+/// Messages that arrive when the queue is full are dropped.
+pub struct Queue<V: DynRef + Clone> {
+    /// Component of the atomic model.
+    component: Component,
+    /// Maximum number of waiting messages.
+    capacity: usize,
+    /// Waiting messages.
+    buffer: VecDeque<V>,
+    /// It is `true` if the downstream model is ready to receive a new message.
+    ready: bool,
+    /// Number of messages dropped because the queue was full.
+    n_dropped: usize,
+    /// Input port for receiving messages.
+    input: InPort<V>,
+    /// Input port for receiving notifications of processed messages.
+    done: InPort<V>,
+    /// Output port for forwarding messages.
+    output: OutPort<V>,
+}
+
+impl<V: DynRef + Clone> Queue<V> {
+    /// Creates a new queue that can store up to `capacity` waiting messages.
+    pub fn new(name: &str, capacity: usize) -> Self {
+        let mut component = Component::new(name);
+        let input = component.add_in_port::<V>("input");
+        let done = component.add_in_port::<V>("done");
+        let output = component.add_out_port::<V>("output");
+        Self {
+            component,
+            capacity,
+            buffer: VecDeque::new(),
+            ready: true,
+            n_dropped: 0,
+            input,
+            done,
+            output,
+        }
+    }
+
+    /// Creates a new queue with unlimited capacity.
+    pub fn unbounded(name: &str) -> Self {
+        Self::new(name, usize::MAX)
+    }
+
+    /// Returns the number of waiting messages.
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns `true` if there are no waiting messages.
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Returns the number of messages dropped because the queue was full.
+    pub fn n_dropped(&self) -> usize {
+        self.n_dropped
+    }
+}
+
+impl<V: DynRef + Clone> Atomic for Queue<V> {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.buffer.clear();
+        self.ready = true;
+        self.n_dropped = 0;
+    }
+
+    fn lambda(&self) {
+        // Safety: adding message on atomic model's output port at lambda
+        unsafe { self.output.add_value(self.buffer[0].clone()) };
+    }
+
+    fn delta_int(&mut self) {
+        self.buffer.pop_front();
+        self.ready = false;
+    }
+
+    fn delta_ext(&mut self, _e: f64) {
+        // Safety: reading messages on atomic model's input port at delta_ext
+        if !unsafe { self.done.is_empty() } {
+            self.ready = true;
+        }
+        // Safety: reading messages on atomic model's input port at delta_ext
+        for value in unsafe { self.input.get_values() } {
+            match self.buffer.len() < self.capacity {
+                true => self.buffer.push_back(value.clone()),
+                false => self.n_dropped += 1,
+            }
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        match self.ready && !self.buffer.is_empty() {
+            true => 0.,
+            false => f64::INFINITY,
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use crate::modeling::{Atomic, Component, InPort, OutPort};
+use crate::DynRef;
+
+/// Atomic model that samples the messages received through its `input` port every `period` time units.
+/// At every sampling time, it sends the last message received so far through its `output` port
+/// (nothing is sent before the first message arrives). The first sample is taken after one period.
+/// Messages that arrive at a sampling time are considered in the next sample.
+pub struct Sampler<V: DynRef + Clone> {
+    /// Component of the atomic model.
+    component: Component,
+    /// Sampling period.
+    period: f64,
+    /// Time remaining until the next sample.
+    sigma: f64,
+    /// Last message received.
+    value: Option<V>,
+    /// Input port for receiving messages.
+    input: InPort<V>,
+    /// Output port for sending samples.
+    output: OutPort<V>,
+}
+
+impl<V: DynRef + Clone> Sampler<V> {
+    /// Creates a new sampler. It panics if `period` is not positive.
+    pub fn new(name: &str, period: f64) -> Self {
+        assert!(period > 0., "period must be positive");
+        let mut component = Component::new(name);
+        let input = component.add_in_port::<V>("input");
+        let output = component.add_out_port::<V>("output");
+        Self {
+            component,
+            period,
+            sigma: period,
+            value: None,
+            input,
+            output,
+        }
+    }
+
+    /// Returns the last message received, if any.
+    pub fn value(&self) -> Option<&V> {
+        self.value.as_ref()
+    }
+}
+
+impl<V: DynRef + Clone> Atomic for Sampler<V> {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.sigma = self.period;
+        self.value = None;
+    }
+
+    fn lambda(&self) {
+        if let Some(value) = &self.value {
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.output.add_value(value.clone()) };
+        }
+    }
+
+    fn delta_int(&mut self) {
+        self.sigma = self.period;
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        self.sigma -= e;
+        // Safety: reading messages on atomic model's input port at delta_ext
+        if let Some(value) = unsafe { self.input.get_values() }.last() {
+            self.value = Some(value.clone());
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        self.sigma
+    }
+}
+
+/// Atomic model that reconstructs a piecewise-constant signal from the samples received through its `input` port.
+/// It holds the last sample and only sends it through its `output` port when it differs from the previous one,
+/// so downstream models (e.g., a continuous plant) only react to actual changes of the signal.
+/// The initial value is sent at the beginning of the simulation.
+pub struct ZeroOrderHold<V: DynRef + Clone> {
+    /// Component of the atomic model.
+    component: Component,
+    /// Initial value of the signal.
+    initial: V,
+    /// Current value of the signal.
+    value: V,
+    /// Whether the current value must be sent.
+    changed: bool,
+    /// Input port for receiving samples.
+    input: InPort<V>,
+    /// Output port for sending changes of the signal.
+    output: OutPort<V>,
+}
+
+impl<V: DynRef + Clone + PartialEq> ZeroOrderHold<V> {
+    /// Creates a new zero-order hold with the given initial value.
+    pub fn new(name: &str, initial: V) -> Self {
+        let mut component = Component::new(name);
+        let input = component.add_in_port::<V>("input");
+        let output = component.add_out_port::<V>("output");
+        Self {
+            component,
+            value: initial.clone(),
+            initial,
+            changed: true,
+            input,
+            output,
+        }
+    }
+
+    /// Returns the current value of the signal.
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+}
+
+impl<V: DynRef + Clone + PartialEq> Atomic for ZeroOrderHold<V> {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.value = self.initial.clone();
+        self.changed = true;
+    }
+
+    fn lambda(&self) {
+        if self.changed {
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.output.add_value(self.value.clone()) };
+        }
+    }
+
+    fn delta_int(&mut self) {
+        self.changed = false;
+    }
+
+    fn delta_ext(&mut self, _e: f64) {
+        // Safety: reading messages on atomic model's input port at delta_ext
+        if let Some(value) = unsafe { self.input.get_values() }.last() {
+            if *value != self.value {
+                self.value = value.clone();
+                self.changed = true;
+            }
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        match self.changed {
+            true => 0.,
+            false => f64::INFINITY,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::AtomicTester;
+
+    #[test]
+    fn test_sampler() {
+        let mut tester = AtomicTester::new(Sampler::<f64>::new("sampler", 1.));
+        tester.advance_to(1.);
+        assert!(tester.outputs::<f64>("output").is_empty());
+        tester.inject("input", 3.).advance_to(1.5);
+        tester.inject("input", 4.).advance_to(2.);
+        assert_eq!(vec![(2., 3.)], tester.outputs::<f64>("output"));
+        tester.advance_to(3.);
+        assert_eq!(vec![(3., 4.)], tester.outputs::<f64>("output"));
+    }
+
+    #[test]
+    fn test_zero_order_hold() {
+        let mut tester = AtomicTester::new(ZeroOrderHold::new("hold", 0.));
+        tester.advance_to(0.);
+        assert_eq!(vec![(0., 0.)], tester.outputs::<f64>("output"));
+        tester.inject("input", 0.).advance_to(1.);
+        tester.advance_to(1.);
+        assert!(tester.outputs::<f64>("output").is_empty());
+        // changes are sent right after the sample
+        tester.inject("input", 2.).advance_to(2.);
+        tester.advance_to(2.);
+        assert_eq!(vec![(2., 2.)], tester.outputs::<f64>("output"));
+        assert_eq!(2., *tester.finish().value());
+    }
+}
@@ -0,0 +1,256 @@
+use crate::modeling::{Atomic, Component, OutPort};
+use std::f64::consts::TAU;
+
+/// Waveform of a [`SignalGenerator`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Waveform {
+    /// Constant value (e.g., white noise around a mean value with [`SignalGenerator::noise`]).
+    Constant(f64),
+    /// Step from `initial` to `last` at time `time`.
+    Step { initial: f64, last: f64, time: f64 },
+    /// Ramp with the given slope that starts rising from `initial` at time `start`.
+    Ramp {
+        initial: f64,
+        slope: f64,
+        start: f64,
+    },
+    /// Sine wave `offset + amplitude * sin(2 * pi * frequency * t + phase)`.
+    Sine {
+        amplitude: f64,
+        frequency: f64,
+        phase: f64,
+        offset: f64,
+    },
+    /// Pulse-width modulated (square) wave that is `high` during the first `duty` fraction of every period,
+    /// and `low` during the rest of the period.
+    Pwm {
+        low: f64,
+        high: f64,
+        period: f64,
+        duty: f64,
+    },
+}
+
+impl Waveform {
+    /// Returns the value of the waveform at time `t`.
+    pub fn value(&self, t: f64) -> f64 {
+        match *self {
+            Self::Constant(value) => value,
+            Self::Step {
+                initial,
+                last,
+                time,
+            } => match t < time {
+                true => initial,
+                false => last,
+            },
+            Self::Ramp {
+                initial,
+                slope,
+                start,
+            } => initial + slope * (t - start).max(0.),
+            Self::Sine {
+                amplitude,
+                frequency,
+                phase,
+                offset,
+            } => offset + amplitude * (TAU * frequency * t + phase).sin(),
+            Self::Pwm {
+                low,
+                high,
+                period,
+                duty,
+            } => match (t / period).fract() < duty {
+                true => high,
+                false => low,
+            },
+        }
+    }
+}
+
+/// Atomic model that sends samples of a [`Waveform`] through its `output` port every `period` time units,
+/// starting at time 0. Optionally, it adds Gaussian noise to the samples (see [`SignalGenerator::noise`]) and
+/// delays every sample by a uniformly distributed jitter (see [`SignalGenerator::jitter`]). Random samples are
+/// drawn from the random number generator stream of the component, so simulations are reproducible with
+/// [`crate::simulation::RootCoordinator::set_seed`].
+pub struct SignalGenerator {
+    /// Component of the atomic model.
+    component: Component,
+    /// Waveform of the signal.
+    waveform: Waveform,
+    /// Sampling period.
+    period: f64,
+    /// Maximum delay of every sample.
+    jitter: f64,
+    /// Standard deviation of the noise.
+    noise: f64,
+    /// Current simulation time.
+    clock: f64,
+    /// Index of the next sample.
+    k: u64,
+    /// Time and value of the next sample.
+    next: (f64, f64),
+    /// Output port for sending samples.
+    output: OutPort<f64>,
+}
+
+impl SignalGenerator {
+    /// Creates a new signal generator without noise or jitter. It panics if `period` is not positive.
+    pub fn new(name: &str, waveform: Waveform, period: f64) -> Self {
+        assert!(period > 0., "period must be positive");
+        let mut component = Component::new(name);
+        let output = component.add_out_port::<f64>("output");
+        Self {
+            component,
+            waveform,
+            period,
+            jitter: 0.,
+            noise: 0.,
+            clock: 0.,
+            k: 0,
+            next: (0., waveform.value(0.)),
+            output,
+        }
+    }
+
+    /// Delays every sample by a random time uniformly distributed in `[0, jitter)`.
+    /// Samples are still taken from the waveform at their actual time.
+    /// It panics if `jitter` is negative or not less than the period.
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        assert!(
+            (0. ..self.period).contains(&jitter),
+            "jitter must be non-negative and less than the period"
+        );
+        self.jitter = jitter;
+        self
+    }
+
+    /// Adds Gaussian noise with zero mean and the given standard deviation to every sample.
+    /// It panics if `std_dev` is negative.
+    pub fn noise(mut self, std_dev: f64) -> Self {
+        assert!(std_dev >= 0., "standard deviation must be non-negative");
+        self.noise = std_dev;
+        self
+    }
+
+    /// Computes the time and value of the next sample.
+    fn schedule(&mut self) {
+        let mut t = self.k as f64 * self.period;
+        if self.jitter > 0. {
+            t += self.component.rng().uniform(0., self.jitter);
+        }
+        let mut value = self.waveform.value(t);
+        if self.noise > 0. {
+            value += self.component.rng().normal(0., self.noise);
+        }
+        self.next = (t, value);
+    }
+}
+
+impl Atomic for SignalGenerator {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.clock = 0.;
+        self.k = 0;
+        self.schedule();
+    }
+
+    fn lambda(&self) {
+        // Safety: adding message on atomic model's output port at lambda
+        unsafe { self.output.add_value(self.next.1) };
+    }
+
+    fn delta_int(&mut self) {
+        self.clock = self.next.0;
+        self.k += 1;
+        self.schedule();
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        self.clock += e;
+    }
+
+    fn ta(&self) -> f64 {
+        self.next.0 - self.clock
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::AtomicTester;
+
+    /// Returns the samples sent by a generator until time `t_end` (excluded).
+    fn samples(generator: SignalGenerator, t_end: f64) -> Vec<(f64, f64)> {
+        let mut tester = AtomicTester::new(generator);
+        let mut samples = Vec::new();
+        while tester.t_next() < t_end {
+            tester.advance_to(tester.t_next());
+            samples.extend(tester.outputs::<f64>("output"));
+        }
+        samples
+    }
+
+    #[test]
+    fn test_waveforms() {
+        let step = Waveform::Step {
+            initial: 0.,
+            last: 1.,
+            time: 1.5,
+        };
+        let ramp = Waveform::Ramp {
+            initial: 1.,
+            slope: 2.,
+            start: 1.,
+        };
+        let pwm = Waveform::Pwm {
+            low: 0.,
+            high: 5.,
+            period: 2.,
+            duty: 0.25,
+        };
+        let expected = [
+            (step, [0., 0., 1., 1.]),
+            (ramp, [1., 1., 3., 5.]),
+            (pwm, [5., 0., 5., 0.]),
+        ];
+        for (waveform, values) in expected {
+            let generator = SignalGenerator::new("generator", waveform, 1.);
+            let expected: Vec<_> = (0..4).map(|k| k as f64).zip(values).collect();
+            assert_eq!(expected, samples(generator, 4.));
+        }
+        let sine = Waveform::Sine {
+            amplitude: 2.,
+            frequency: 0.25,
+            phase: 0.,
+            offset: 1.,
+        };
+        for ((_, value), expected) in samples(SignalGenerator::new("sine", sine, 1.), 4.)
+            .into_iter()
+            .zip([1., 3., 1., -1.])
+        {
+            assert!((value - expected).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_noise_and_jitter() {
+        let generator = SignalGenerator::new("generator", Waveform::Constant(3.), 1.)
+            .noise(0.5)
+            .jitter(0.2);
+        let samples = samples(generator, 10_000.);
+        assert_eq!(10_000, samples.len());
+        for (k, (t, _)) in samples.iter().enumerate() {
+            assert!((0. ..0.2).contains(&(t - k as f64)));
+        }
+        let mean = samples.iter().map(|(_, v)| v).sum::<f64>() / 10_000.;
+        assert!((mean - 3.).abs() < 0.05);
+    }
+}
@@ -0,0 +1,180 @@
+use super::Job;
+use crate::modeling::{Atomic, Component, InPort, OutPort};
+
+/// Atomic model that computes throughput and latency statistics of a system during an observation period.
+/// It receives generated [`Job`]s through its `arrived` port and processed jobs through its `solved` port.
+/// At the end of the observation period, it sends a message through its `stop` port
+/// (e.g., to stop a [`super::Generator`]) and ignores any further job.
+pub struct Transducer {
+    /// Component of the atomic model.
+    component: Component,
+    /// Duration of the observation period.
+    observation: f64,
+    /// Current simulation time.
+    clock: f64,
+    /// It is `true` while the observation period has not finished.
+    active: bool,
+    /// Number of jobs generated during the observation period.
+    n_arrived: usize,
+    /// Number of jobs processed during the observation period.
+    n_solved: usize,
+    /// Sum of the latencies of the jobs processed during the observation period.
+    total_latency: f64,
+    /// Input port for receiving generated jobs.
+    arrived: InPort<Job>,
+    /// Input port for receiving processed jobs.
+    solved: InPort<Job>,
+    /// Output port for notifying the end of the observation period.
+    stop: OutPort<bool>,
+}
+
+impl Transducer {
+    /// Creates a new transducer with the given observation period.
+    pub fn new(name: &str, observation: f64) -> Self {
+        let mut component = Component::new(name);
+        let arrived = component.add_in_port::<Job>("arrived");
+        let solved = component.add_in_port::<Job>("solved");
+        let stop = component.add_out_port::<bool>("stop");
+        Self {
+            component,
+            observation,
+            clock: 0.,
+            active: true,
+            n_arrived: 0,
+            n_solved: 0,
+            total_latency: 0.,
+            arrived,
+            solved,
+            stop,
+        }
+    }
+
+    /// Returns the number of jobs generated during the observation period.
+    pub fn n_arrived(&self) -> usize {
+        self.n_arrived
+    }
+
+    /// Returns the number of jobs processed during the observation period.
+    pub fn n_solved(&self) -> usize {
+        self.n_solved
+    }
+
+    /// Returns the number of processed jobs per time unit. It is [`None`] if no time has elapsed yet.
+    pub fn throughput(&self) -> Option<f64> {
+        (self.clock > 0.).then(|| self.n_solved as f64 / self.clock)
+    }
+
+    /// Returns the mean time between the generation and the processing of jobs.
+    /// It is [`None`] if no job has been processed yet.
+    pub fn mean_latency(&self) -> Option<f64> {
+        (self.n_solved > 0).then(|| self.total_latency / self.n_solved as f64)
+    }
+}
+
+impl Atomic for Transducer {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.clock = 0.;
+        self.active = true;
+        self.n_arrived = 0;
+        self.n_solved = 0;
+        self.total_latency = 0.;
+    }
+
+    fn lambda(&self) {
+        // Safety: adding message on atomic model's output port at lambda
+        unsafe { self.stop.add_value(true) };
+    }
+
+    fn delta_int(&mut self) {
+        self.clock = self.observation;
+        self.active = false;
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        if !self.active {
+            return;
+        }
+        self.clock += e;
+        // Safety: reading messages on atomic model's input port at delta_ext
+        self.n_arrived += unsafe { self.arrived.get_values() }.len();
+        // Safety: reading messages on atomic model's input port at delta_ext
+        for job in unsafe { self.solved.get_values() } {
+            self.n_solved += 1;
+            self.total_latency += self.clock - job.time;
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        match self.active {
+            true => self.observation - self.clock,
+            false => f64::INFINITY,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::{Generator, Processor, Queue};
+    use crate::modeling::Coupled;
+    use crate::simulation::RootCoordinator;
+    use crate::testing::AtomicTester;
+    use crate::tracing::{MemorySink, TracePhase};
+
+    #[test]
+    fn test_transducer() {
+        let mut tester = AtomicTester::new(Transducer::new("transducer", 10.));
+        let job = |id, time| Job { id, time };
+        tester.inject("arrived", job(0, 1.)).advance_to(1.);
+        tester.inject("arrived", job(1, 2.)).advance_to(2.);
+        tester.inject("solved", job(0, 1.)).advance_to(4.);
+        tester.inject("solved", job(1, 2.)).advance_to(8.);
+        assert_eq!(Some(4.5), tester.model().mean_latency());
+        tester.advance_to(10.);
+        assert_eq!(vec![(10., true)], tester.outputs::<bool>("stop"));
+        tester.inject("arrived", job(2, 11.)).advance_to(11.);
+        let transducer = tester.finish();
+        assert_eq!(2, transducer.n_arrived());
+        assert_eq!(2, transducer.n_solved());
+        assert_eq!(Some(0.2), transducer.throughput());
+    }
+
+    #[test]
+    fn test_gpt() {
+        let mut gpt = Coupled::new("gpt");
+        gpt.add_component(Box::new(Generator::new("generator", 1.)));
+        gpt.add_component(Box::new(Queue::<Job>::unbounded("queue")));
+        gpt.add_component(Box::new(Processor::<Job>::new("processor", 2.)));
+        gpt.add_component(Box::new(Transducer::new("transducer", 10.5)));
+        gpt.add_ic("generator", "output", "queue", "input");
+        gpt.add_ic("generator", "output", "transducer", "arrived");
+        gpt.add_ic("queue", "output", "processor", "input");
+        gpt.add_ic("processor", "output", "queue", "done");
+        gpt.add_ic("processor", "output", "transducer", "solved");
+        gpt.add_ic("transducer", "stop", "generator", "stop");
+
+        let sink = MemorySink::new();
+        let mut simulator = RootCoordinator::new(gpt);
+        simulator.add_trace_sinks(vec![Box::new(sink.clone())], None);
+        simulator.simulate(f64::INFINITY);
+        // jobs generated at 1, 2, ..., 10 are queued and processed at 3, 5, ..., 21
+        let solved: Vec<_> = sink
+            .records()
+            .into_iter()
+            .filter(|r| r.phase == TracePhase::Output && r.path == "gpt.processor")
+            .map(|r| r.t)
+            .collect();
+        assert_eq!(
+            (1..=10).map(|i| 1. + 2. * i as f64).collect::<Vec<_>>(),
+            solved
+        );
+    }
+}
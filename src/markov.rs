@@ -0,0 +1,258 @@
+use crate::modeling::{Atomic, Component, OutPort};
+use crate::random::Rng;
+use crate::stats::{Counter, TimeWeighted};
+
+/// Dynamics of a [`MarkovChain`].
+#[derive(Debug, Clone, PartialEq)]
+enum Dynamics {
+    /// Discrete-time chain with a transition probability matrix and the time between steps.
+    Discrete(Vec<Vec<f64>>, f64),
+    /// Continuous-time chain with a transition rate matrix.
+    Continuous(Vec<Vec<f64>>),
+}
+
+/// Atomic model of a discrete- or continuous-time Markov chain with states `0..n`.
+/// It sends the new state through its `output` port every time the chain jumps to a different state.
+/// Holding times and jumps are sampled with the random number generator stream of the component,
+/// so simulations are reproducible with [`crate::simulation::RootCoordinator::set_seed`].
+///
+/// The chain reports the time-weighted average of its state index in its `state` accumulator
+/// (meaningful when the index counts something, such as failed units or customers), and
+/// the number of jumps in its `jumps` accumulator (see [`crate::simulation::RootCoordinator::stats`]).
+pub struct MarkovChain {
+    /// Component of the atomic model.
+    component: Component,
+    /// Transition probabilities or rates.
+    dynamics: Dynamics,
+    /// Initial state.
+    initial: usize,
+    /// Current state.
+    state: usize,
+    /// Next state.
+    next: usize,
+    /// Current simulation time.
+    clock: f64,
+    /// Time remaining until the next jump.
+    sigma: f64,
+    /// Time-weighted average of the state index.
+    state_stat: TimeWeighted,
+    /// Number of jumps.
+    jumps: Counter,
+    /// Output port for sending new states.
+    output: OutPort<usize>,
+}
+
+impl MarkovChain {
+    /// Creates a new discrete-time Markov chain that starts in state `initial` and takes a step every `period`
+    /// time units. `probabilities[i][j]` is the probability of moving from state `i` to state `j` in one step.
+    /// Consecutive steps that do not change the state are merged into a single, geometrically distributed holding time.
+    /// It panics if the matrix is not square, if any row does not add up to 1, or if `initial` is not a state.
+    pub fn discrete(name: &str, probabilities: Vec<Vec<f64>>, period: f64, initial: usize) -> Self {
+        check_matrix(name, &probabilities, initial);
+        for (i, row) in probabilities.iter().enumerate() {
+            assert!(
+                (row.iter().sum::<f64>() - 1.).abs() < 1e-9,
+                "row {i} of the transition matrix of {name} does not add up to 1"
+            );
+        }
+        assert!(period > 0., "period of {name} must be positive");
+        Self::new(name, Dynamics::Discrete(probabilities, period), initial)
+    }
+
+    /// Creates a new continuous-time Markov chain that starts in state `initial`. `rates[i][j]` is
+    /// the transition rate from state `i` to state `j`. Diagonal elements are ignored, as the holding time
+    /// in state `i` is exponentially distributed with the sum of the rates of its row as rate.
+    /// It panics if the matrix is not square, if any rate is negative, or if `initial` is not a state.
+    pub fn continuous(name: &str, rates: Vec<Vec<f64>>, initial: usize) -> Self {
+        check_matrix(name, &rates, initial);
+        Self::new(name, Dynamics::Continuous(rates), initial)
+    }
+
+    fn new(name: &str, dynamics: Dynamics, initial: usize) -> Self {
+        let mut component = Component::new(name);
+        let output = component.add_out_port::<usize>("output");
+        let state_stat = component.add_time_weighted("state", initial as f64);
+        let jumps = component.add_counter("jumps");
+        Self {
+            component,
+            dynamics,
+            initial,
+            state: initial,
+            next: initial,
+            clock: 0.,
+            sigma: f64::INFINITY,
+            state_stat,
+            jumps,
+            output,
+        }
+    }
+
+    /// Returns the current state of the chain.
+    pub fn state(&self) -> usize {
+        self.state
+    }
+
+    /// Samples the holding time in the current state and the next state.
+    fn schedule(&mut self) {
+        let rng = self.component.rng();
+        let (sigma, next) = match &self.dynamics {
+            Dynamics::Discrete(probabilities, period) => {
+                let row = &probabilities[self.state];
+                let p_stay = row[self.state];
+                // Number of steps until the first state change is geometrically distributed
+                let steps = match p_stay {
+                    p if p >= 1. => f64::INFINITY,
+                    p if p <= 0. => 1.,
+                    p => 1. + ((1. - rng.next_f64()).ln() / p.ln()).floor(),
+                };
+                (steps * period, jump(rng, row, self.state))
+            }
+            Dynamics::Continuous(rates) => {
+                let row = &rates[self.state];
+                let rate: f64 = (row.iter().enumerate())
+                    .filter(|(j, _)| *j != self.state)
+                    .map(|(_, r)| r)
+                    .sum();
+                match rate > 0. {
+                    true => (rng.exponential(1. / rate), jump(rng, row, self.state)),
+                    false => (f64::INFINITY, self.state),
+                }
+            }
+        };
+        (self.sigma, self.next) = (sigma, next);
+    }
+}
+
+/// Checks that a transition matrix is square and non-negative, and that `initial` is a state.
+fn check_matrix(name: &str, matrix: &[Vec<f64>], initial: usize) {
+    let n = matrix.len();
+    assert!(
+        initial < n,
+        "initial state {initial} of {name} is not a state"
+    );
+    for (i, row) in matrix.iter().enumerate() {
+        assert_eq!(
+            n,
+            row.len(),
+            "row {i} of the matrix of {name} has {} columns instead of {n}",
+            row.len()
+        );
+        assert!(
+            row.iter().all(|x| *x >= 0.),
+            "row {i} of the matrix of {name} has negative elements"
+        );
+    }
+}
+
+/// Samples a state different from `state` with probability proportional to the weights of `row`.
+/// If all the weights are zero, it returns `state`.
+fn jump(rng: &mut Rng, row: &[f64], state: usize) -> usize {
+    let weights = row.iter().enumerate().filter(|(j, _)| *j != state);
+    let total: f64 = weights.clone().map(|(_, w)| w).sum();
+    let mut x = rng.uniform(0., total);
+    let mut last = state;
+    for (j, w) in weights.filter(|(_, w)| **w > 0.) {
+        if x < *w {
+            return j;
+        }
+        x -= w;
+        last = j;
+    }
+    // rounding errors may leave a tiny remainder
+    last
+}
+
+impl Atomic for MarkovChain {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.state = self.initial;
+        self.clock = 0.;
+        self.schedule();
+    }
+
+    fn lambda(&self) {
+        // Safety: adding message on atomic model's output port at lambda
+        unsafe { self.output.add_value(self.next) };
+    }
+
+    fn delta_int(&mut self) {
+        self.clock += self.sigma;
+        self.state = self.next;
+        self.state_stat.update(self.clock, self.state as f64);
+        self.jumps.increment();
+        self.schedule();
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        self.clock += e;
+        self.sigma -= e;
+    }
+
+    fn ta(&self) -> f64 {
+        self.sigma
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::Coupled;
+    use crate::simulation::RootCoordinator;
+    use crate::stats::Summary;
+
+    /// Simulates a chain and returns the time-weighted average of its state and its number of jumps.
+    fn simulate(chain: MarkovChain, t_end: f64) -> (f64, u64) {
+        let mut coupled = Coupled::new("root");
+        coupled.add_component(Box::new(chain));
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.set_seed(7);
+        simulator.simulate(t_end);
+        let stats = simulator.stats();
+        let Some(Summary::TimeWeighted { mean, .. }) = stats.get("root.chain", "state") else {
+            panic!("missing state accumulator");
+        };
+        let Some(Summary::Counter(jumps)) = stats.get("root.chain", "jumps") else {
+            panic!("missing jumps accumulator");
+        };
+        (*mean, *jumps)
+    }
+
+    #[test]
+    fn test_ctmc() {
+        // Repairable unit: it fails at rate 0.1 and it is repaired at rate 0.9, so it is down 10% of the time
+        let rates = vec![vec![0., 0.1], vec![0.9, 0.]];
+        let (unavailability, jumps) =
+            simulate(MarkovChain::continuous("chain", rates, 0), 100_000.);
+        assert!((unavailability - 0.1).abs() < 0.01);
+        // A cycle takes 1 / 0.1 + 1 / 0.9 time units on average
+        let cycles = 100_000. / (1. / 0.1 + 1. / 0.9);
+        assert!((jumps as f64 / 2. / cycles - 1.).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_dtmc() {
+        // Stationary distribution of this chain is (0.75, 0.25)
+        let probabilities = vec![vec![0.9, 0.1], vec![0.3, 0.7]];
+        let chain = MarkovChain::discrete("chain", probabilities, 1., 0);
+        let (p_1, _) = simulate(chain, 100_000.);
+        assert!((p_1 - 0.25).abs() < 0.01);
+
+        // Absorbing states stop the chain (statistics are computed until the last jump at t = 2)
+        let probabilities = vec![vec![0., 1.], vec![0., 1.]];
+        let chain = MarkovChain::discrete("chain", probabilities, 2., 0);
+        assert_eq!((0., 1), simulate(chain, 10.));
+    }
+
+    #[test]
+    #[should_panic(expected = "row 1 of the transition matrix of chain does not add up to 1")]
+    fn test_dtmc_invalid() {
+        MarkovChain::discrete("chain", vec![vec![1., 0.], vec![0.5, 0.6]], 1., 0);
+    }
+}
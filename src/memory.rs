@@ -0,0 +1,131 @@
+//! Heap memory tracking for benchmarks.
+//!
+//! Install [`TrackingAllocator`] as the global allocator of your binary to measure the heap memory
+//! used by your models and simulations:
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOCATOR: xdevs::memory::TrackingAllocator = xdevs::memory::TrackingAllocator;
+//! ```
+//!
+//! Then, call [`TrackingAllocator::reset_peak`] before the section of interest and
+//! [`TrackingAllocator::stats`] after it.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Bytes currently allocated.
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+/// Maximum number of bytes allocated at the same time since the last reset.
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+/// Number of allocations (including reallocations) since the program started.
+static N_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Heap memory statistics collected by [`TrackingAllocator`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    /// Bytes currently allocated.
+    pub allocated: usize,
+    /// Maximum number of bytes allocated at the same time since the last call to
+    /// [`TrackingAllocator::reset_peak`].
+    pub peak: usize,
+    /// Number of allocations (including reallocations) since the program started.
+    pub n_allocations: usize,
+}
+
+/// Global allocator that delegates to the [`System`] allocator and keeps track of the allocated memory.
+/// Counters are updated with relaxed atomic operations, so the overhead is small but not negligible.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TrackingAllocator;
+
+impl TrackingAllocator {
+    /// Returns the current heap memory statistics.
+    pub fn stats() -> AllocStats {
+        AllocStats {
+            allocated: ALLOCATED.load(Ordering::Relaxed),
+            peak: PEAK.load(Ordering::Relaxed),
+            n_allocations: N_ALLOCATIONS.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Resets the peak of allocated memory to the memory currently allocated.
+    pub fn reset_peak() {
+        PEAK.store(ALLOCATED.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+
+    /// Records that `size` bytes were allocated.
+    #[inline]
+    fn record_alloc(size: usize) {
+        let allocated = ALLOCATED.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK.fetch_max(allocated, Ordering::Relaxed);
+        N_ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records that `size` bytes were deallocated.
+    #[inline]
+    fn record_dealloc(size: usize) {
+        ALLOCATED.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            Self::record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            Self::record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        Self::record_dealloc(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            Self::record_dealloc(layout.size());
+            Self::record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracking_allocator() {
+        // the allocator is not installed as the global allocator of the tests, so only these calls count
+        let layout = Layout::array::<u64>(128).unwrap();
+        TrackingAllocator::reset_peak();
+        let before = TrackingAllocator::stats();
+        unsafe {
+            let ptr = TrackingAllocator.alloc(layout);
+            let stats = TrackingAllocator::stats();
+            assert_eq!(before.allocated + 1024, stats.allocated);
+            assert_eq!(before.n_allocations + 1, stats.n_allocations);
+
+            let ptr = TrackingAllocator.realloc(ptr, layout, 4096);
+            let stats = TrackingAllocator::stats();
+            assert_eq!(before.allocated + 4096, stats.allocated);
+            assert_eq!(before.allocated + 4096, stats.peak);
+
+            TrackingAllocator.dealloc(ptr, Layout::from_size_align(4096, layout.align()).unwrap());
+        }
+        let stats = TrackingAllocator::stats();
+        assert_eq!(before.allocated, stats.allocated);
+        assert_eq!(before.allocated + 4096, stats.peak);
+        assert_eq!(before.n_allocations + 2, stats.n_allocations);
+    }
+}
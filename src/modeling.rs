@@ -1,9 +1,24 @@
 pub mod atomic;
+pub mod builder;
 pub mod component;
 pub mod coupled;
+mod diagram;
+mod fsm;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod msg;
 pub mod port;
+mod simulator_enum;
+pub mod static_coupled;
+mod structure;
 
 pub use atomic::Atomic;
+pub use builder::{BuildError, CoupledBuilder};
 pub use component::Component;
-pub use coupled::Coupled;
+pub use coupled::{ComponentId, Coupled, Select, Subcomponent};
+pub use fsm::FsmAtomic;
+#[cfg(feature = "serde")]
+pub use json::ModelFactory;
+pub use msg::{Msg, SharedMsg};
 pub use port::{InPort, OutPort};
+pub use structure::{Distribution, ModelStats};
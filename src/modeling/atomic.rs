@@ -1,12 +1,15 @@
 use super::Component;
+use crate::simulation::StateSnapshot;
+use crate::time::Time;
+use std::any::Any;
 
-/// Interface for atomic DEVS models.
-pub trait Atomic {
+/// Interface for atomic DEVS models. `T` is the simulation time type (by default, [`f64`]).
+pub trait Atomic<T: Time = f64> {
     /// Returns reference to inner component.
-    fn get_component(&self) -> &Component;
+    fn get_component(&self) -> &Component<T>;
 
     /// Returns mutable reference to inner component.
-    fn get_component_mut(&mut self) -> &mut Component;
+    fn get_component_mut(&mut self) -> &mut Component<T>;
 
     /// Method for performing any operation before simulating. By default, it does nothing.
     #[inline]
@@ -26,10 +29,18 @@ pub trait Atomic {
     /// External transition function of the atomic DEVS model.
     /// `e` corresponds to the elapsed time since the last state transition of the model.
     /// This is the only method where implementers can safely manipulate their [`super::InPort`] structs.
-    fn delta_ext(&mut self, e: f64);
+    fn delta_ext(&mut self, e: T);
 
     /// Time advance function of the atomic DEVS model.
-    fn ta(&self) -> f64;
+    fn ta(&self) -> T;
+
+    /// Lookahead of the atomic DEVS model, i.e., the minimum time between the reception
+    /// of an input event and the emission of any output event triggered by it.
+    /// It is used by conservative distributed coordinators. By default, it returns 0.
+    #[inline]
+    fn lookahead(&self) -> T {
+        T::ZERO
+    }
 
     /// Confluent transition function of the atomic DEVS model.
     /// By default, it first triggers [`Atomic::delta_int`].
@@ -37,6 +48,27 @@ pub trait Atomic {
     #[inline]
     fn delta_conf(&mut self) {
         self.delta_int();
-        self.delta_ext(0.);
+        self.delta_ext(T::ZERO);
+    }
+
+    /// Returns a copy of the state of the atomic DEVS model (e.g., a boxed clone of its state variables).
+    /// Optimistic coordinators save the state before every transition to roll the model back if needed.
+    /// The simulation state of the inner component (i.e., simulation times and random number generator)
+    /// is saved automatically. By default, it returns [`None`], as models do not support state saving.
+    #[inline]
+    fn save_state(&self) -> Option<Box<dyn Any>> {
+        None
+    }
+
+    /// Restores a state previously returned by [`Atomic::save_state`]. By default, it does nothing.
+    #[inline]
+    fn restore_state(&mut self, _state: &dyn Any) {}
+
+    /// Returns the atomic DEVS model as an observable [`StateSnapshot`], so listeners can sample its state.
+    /// Models that implement [`StateSnapshot`] must override this method to return `Some(self)`.
+    /// By default, it returns [`None`].
+    #[inline]
+    fn as_state_snapshot(&self) -> Option<&dyn StateSnapshot> {
+        None
     }
 }
@@ -0,0 +1,373 @@
+use super::{Component, Coupled};
+use crate::simulation::Simulator;
+use crate::DynRef;
+use std::collections::HashSet;
+use std::fmt;
+use std::fmt::Debug;
+
+/// Errors detected by a [`CoupledBuilder`] when validating a coupled model.
+/// Components are identified by their name, and ports of the coupled model itself
+/// belong to the component with the name of the coupled model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// The coupled model already contains an input port with the given name.
+    DuplicateInPort(String),
+    /// The coupled model already contains an output port with the given name.
+    DuplicateOutPort(String),
+    /// The coupled model already contains a component with the given name.
+    DuplicateComponent(String),
+    /// A coupling refers to a component that does not exist.
+    UnknownComponent(String),
+    /// A coupling refers to an input port that does not exist. It contains the component and port names.
+    UnknownInPort(String, String),
+    /// A coupling refers to an output port that does not exist. It contains the component and port names.
+    UnknownOutPort(String, String),
+    /// The ports of a coupling are not compatible. It contains the origin and destination ports.
+    IncompatiblePorts(String, String),
+    /// The coupling was added more than once. It contains the origin and destination ports.
+    DuplicateCoupling(String, String),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateInPort(p) => write!(f, "duplicate input port {p}"),
+            Self::DuplicateOutPort(p) => write!(f, "duplicate output port {p}"),
+            Self::DuplicateComponent(c) => write!(f, "duplicate component {c}"),
+            Self::UnknownComponent(c) => write!(f, "unknown component {c}"),
+            Self::UnknownInPort(c, p) => write!(f, "unknown input port {c}.{p}"),
+            Self::UnknownOutPort(c, p) => write!(f, "unknown output port {c}.{p}"),
+            Self::IncompatiblePorts(from, to) => {
+                write!(f, "ports {from} and {to} are not compatible")
+            }
+            Self::DuplicateCoupling(from, to) => {
+                write!(f, "duplicate coupling from {from} to {to}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// Kind of coupling stored by a [`CoupledBuilder`] until the model is built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum CouplingKind {
+    Eic,
+    Ic,
+    Eoc,
+}
+
+/// A coupling stored by a [`CoupledBuilder`]. Components are [`None`] for ports of the coupled model.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct PendingCoupling {
+    kind: CouplingKind,
+    component_from: Option<String>,
+    port_from: String,
+    component_to: Option<String>,
+    port_to: String,
+}
+
+/// Fluent builder for [`Coupled`] models.
+/// Unlike the methods of [`Coupled`], the builder does not panic when the model is not valid.
+/// Instead, [`CoupledBuilder::build`] validates the whole structure and returns all the errors at once.
+pub struct CoupledBuilder {
+    /// Coupled model under construction.
+    coupled: Coupled,
+    /// Couplings to be added when building the model.
+    couplings: Vec<PendingCoupling>,
+    /// Errors detected while adding ports and components.
+    errors: Vec<BuildError>,
+}
+
+impl CoupledBuilder {
+    /// Creates a new builder for a coupled model with the provided name.
+    pub fn new(name: &str) -> Self {
+        Self {
+            coupled: Coupled::new(name),
+            couplings: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Adds a new input port of type `T` to the coupled model.
+    pub fn in_port<T: DynRef + Clone>(mut self, name: &str) -> Self {
+        match self.coupled.component.get_in_port(name) {
+            Some(_) => self
+                .errors
+                .push(BuildError::DuplicateInPort(name.to_string())),
+            None => {
+                self.coupled.add_in_port::<T>(name);
+            }
+        }
+        self
+    }
+
+    /// Adds a new output port of type `T` to the coupled model.
+    pub fn out_port<T: DynRef + Clone>(mut self, name: &str) -> Self {
+        match self.coupled.component.get_out_port(name) {
+            Some(_) => self
+                .errors
+                .push(BuildError::DuplicateOutPort(name.to_string())),
+            None => {
+                self.coupled.add_out_port::<T>(name);
+            }
+        }
+        self
+    }
+
+    /// Adds a new component to the coupled model.
+    pub fn component<T: Simulator>(mut self, component: Box<T>) -> Self {
+        match self.coupled.get_component_id(component.get_name()) {
+            Some(_) => {
+                let name = component.get_name().to_string();
+                self.errors.push(BuildError::DuplicateComponent(name));
+            }
+            None => {
+                self.coupled.add_component(component);
+            }
+        }
+        self
+    }
+
+    /// Adds a new EIC from the input port `port_from` of the coupled model
+    /// to the input port `port_to` of the component `component_to`.
+    pub fn eic(self, port_from: &str, component_to: &str, port_to: &str) -> Self {
+        self.coupling(
+            CouplingKind::Eic,
+            None,
+            port_from,
+            Some(component_to),
+            port_to,
+        )
+    }
+
+    /// Adds a new IC from the output port `port_from` of the component `component_from`
+    /// to the input port `port_to` of the component `component_to`.
+    pub fn ic(
+        self,
+        component_from: &str,
+        port_from: &str,
+        component_to: &str,
+        port_to: &str,
+    ) -> Self {
+        let (from, to) = (Some(component_from), Some(component_to));
+        self.coupling(CouplingKind::Ic, from, port_from, to, port_to)
+    }
+
+    /// Adds a new EOC from the output port `port_from` of the component `component_from`
+    /// to the output port `port_to` of the coupled model.
+    pub fn eoc(self, component_from: &str, port_from: &str, port_to: &str) -> Self {
+        self.coupling(
+            CouplingKind::Eoc,
+            Some(component_from),
+            port_from,
+            None,
+            port_to,
+        )
+    }
+
+    /// Validates the coupled model. If it is valid, it returns the model.
+    /// Otherwise, it returns all the errors found, in the order in which they were introduced.
+    pub fn build(mut self) -> Result<Coupled, Vec<BuildError>> {
+        let mut errors = std::mem::take(&mut self.errors);
+        let mut visited = HashSet::new();
+        for coupling in &self.couplings {
+            if let Err(e) = self.validate(coupling) {
+                errors.push(e);
+            } else if !visited.insert(coupling) {
+                let from = self.port_path(&coupling.component_from, &coupling.port_from);
+                let to = self.port_path(&coupling.component_to, &coupling.port_to);
+                errors.push(BuildError::DuplicateCoupling(from, to));
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        for c in &self.couplings {
+            let (from, to) = (c.component_from.as_deref(), c.component_to.as_deref());
+            match (c.kind, from, to) {
+                (CouplingKind::Eic, _, Some(to)) => {
+                    self.coupled.add_eic(&c.port_from, to, &c.port_to)
+                }
+                (CouplingKind::Ic, Some(from), Some(to)) => {
+                    self.coupled.add_ic(from, &c.port_from, to, &c.port_to)
+                }
+                (CouplingKind::Eoc, Some(from), _) => {
+                    self.coupled.add_eoc(from, &c.port_from, &c.port_to)
+                }
+                _ => unreachable!(),
+            }
+        }
+        Ok(self.coupled)
+    }
+
+    /// Stores a new coupling until the model is built.
+    fn coupling(
+        mut self,
+        kind: CouplingKind,
+        component_from: Option<&str>,
+        port_from: &str,
+        component_to: Option<&str>,
+        port_to: &str,
+    ) -> Self {
+        self.couplings.push(PendingCoupling {
+            kind,
+            component_from: component_from.map(str::to_string),
+            port_from: port_from.to_string(),
+            component_to: component_to.map(str::to_string),
+            port_to: port_to.to_string(),
+        });
+        self
+    }
+
+    /// Returns the component with the given name, or the coupled model itself if `name` is [`None`].
+    fn find_component(&self, name: &Option<String>) -> Result<&Component, BuildError> {
+        match name {
+            None => Ok(&self.coupled.component),
+            Some(name) => match self.coupled.get_component_id(name) {
+                Some(id) => Ok(self.coupled.get_component_by_id(id)),
+                None => Err(BuildError::UnknownComponent(name.clone())),
+            },
+        }
+    }
+
+    /// Returns the path of a port used in error messages.
+    fn port_path(&self, component: &Option<String>, port: &str) -> String {
+        let component = component.as_deref().unwrap_or(self.coupled.get_name());
+        format!("{component}.{port}")
+    }
+
+    /// Checks that the components and ports of a coupling exist and are compatible.
+    fn validate(&self, coupling: &PendingCoupling) -> Result<(), BuildError> {
+        let comp_from = self.find_component(&coupling.component_from)?;
+        let comp_to = self.find_component(&coupling.component_to)?;
+        // EICs go from an input port, whereas ICs and EOCs go from an output port
+        let p_from = match coupling.kind {
+            CouplingKind::Eic => comp_from.get_in_port(&coupling.port_from),
+            _ => comp_from.get_out_port(&coupling.port_from),
+        };
+        let p_from = p_from.ok_or_else(|| {
+            let (c, p) = (comp_from.get_name().to_string(), coupling.port_from.clone());
+            match coupling.kind {
+                CouplingKind::Eic => BuildError::UnknownInPort(c, p),
+                _ => BuildError::UnknownOutPort(c, p),
+            }
+        })?;
+        // EOCs go to an output port, whereas EICs and ICs go to an input port
+        let p_to = match coupling.kind {
+            CouplingKind::Eoc => comp_to.get_out_port(&coupling.port_to),
+            _ => comp_to.get_in_port(&coupling.port_to),
+        };
+        let p_to = p_to.ok_or_else(|| {
+            let (c, p) = (comp_to.get_name().to_string(), coupling.port_to.clone());
+            match coupling.kind {
+                CouplingKind::Eoc => BuildError::UnknownOutPort(c, p),
+                _ => BuildError::UnknownInPort(c, p),
+            }
+        })?;
+        if !p_from.is_compatible(&*p_to) {
+            let from = self.port_path(&coupling.component_from, &coupling.port_from);
+            let to = self.port_path(&coupling.component_to, &coupling.port_to);
+            return Err(BuildError::IncompatiblePorts(from, to));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::{Atomic, InPort, OutPort};
+
+    struct Relay {
+        component: Component,
+        input: InPort<usize>,
+        output: OutPort<usize>,
+    }
+
+    impl Relay {
+        fn new(name: &str) -> Box<Self> {
+            let mut component = Component::new(name);
+            let input = component.add_in_port::<usize>("input");
+            let output = component.add_out_port::<usize>("output");
+            Box::new(Self {
+                component,
+                input,
+                output,
+            })
+        }
+    }
+
+    impl Atomic for Relay {
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+
+        fn lambda(&self) {
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.output.add_value(1) };
+        }
+
+        fn delta_int(&mut self) {}
+
+        fn delta_ext(&mut self, _e: f64) {
+            // Safety: reading messages on atomic model's input port at delta_ext
+            assert!(!unsafe { self.input.get_values() }.is_empty());
+        }
+
+        fn ta(&self) -> f64 {
+            f64::INFINITY
+        }
+    }
+
+    #[test]
+    fn test_builder() {
+        let coupled = CoupledBuilder::new("coupled")
+            .in_port::<usize>("input")
+            .out_port::<usize>("output")
+            .component(Relay::new("a"))
+            .component(Relay::new("b"))
+            .eic("input", "a", "input")
+            .ic("a", "output", "b", "input")
+            .eoc("b", "output", "output")
+            .build()
+            .unwrap();
+        assert_eq!(2, coupled.n_components());
+        assert_eq!(1, coupled.n_eics());
+        assert_eq!(1, coupled.n_ics());
+        assert_eq!(1, coupled.n_eocs());
+    }
+
+    #[test]
+    fn test_builder_errors() {
+        let errors = CoupledBuilder::new("coupled")
+            .in_port::<usize>("input")
+            .in_port::<usize>("input")
+            .out_port::<bool>("output")
+            .component(Relay::new("a"))
+            .component(Relay::new("a"))
+            .eic("input", "b", "input")
+            .eic("input", "a", "in")
+            .ic("a", "output", "a", "input")
+            .ic("a", "output", "a", "input")
+            .eoc("a", "output", "output")
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(
+            vec![
+                BuildError::DuplicateInPort("input".to_string()),
+                BuildError::DuplicateComponent("a".to_string()),
+                BuildError::UnknownComponent("b".to_string()),
+                BuildError::UnknownInPort("a".to_string(), "in".to_string()),
+                BuildError::DuplicateCoupling("a.output".to_string(), "a.input".to_string()),
+                BuildError::IncompatiblePorts("a.output".to_string(), "coupled.output".to_string()),
+            ],
+            errors
+        );
+    }
+}
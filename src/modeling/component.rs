@@ -1,37 +1,81 @@
-use super::port::{Bag, InPort, OutPort, Port};
-use crate::DynRef;
+use super::msg::{Msg, Stamp};
+use super::port::{Bag, InPort, OutPort, Port, PortTracker};
+use crate::error::ModelingError;
+use crate::random::Rng;
+#[cfg(feature = "debug_checks")]
+use crate::simulation::invariants::check_empty;
+use crate::simulation::profile::{ComponentProfile, Phase};
+use crate::simulation::BagPolicy;
+use crate::stats::{Counter, Statistic, Tally, TimeWeighted};
+use crate::time::Time;
+use crate::{DynRef, Shared};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::time::Instant;
 
 /// DEVS component. Models must comprise a component to fulfill the [`crate::simulation::Simulator`] trait.
-pub struct Component {
+/// `T` is the simulation time type (by default, [`f64`]).
+pub struct Component<T: Time = f64> {
     /// Name of the DEVS component.
     name: String,
     /// Time of the last component state transition.
-    t_last: f64,
+    t_last: T,
     /// Time for the next component state transition.
-    t_next: f64,
+    t_next: T,
+    /// Number of state transitions triggered since the component was created.
+    n_transitions: usize,
     /// Input ports map. Keys are the port IDs, and values correspond to the index of the port in `in_ports`.
     in_map: HashMap<String, usize>,
     /// Output ports map. Keys are the port IDs, and values correspond to the index of the port in `out_ports`.
     out_map: HashMap<String, usize>,
     /// Input port set of the DEVS component (serialized for better performance).
-    in_ports: Vec<Arc<dyn Port>>,
+    in_ports: Vec<Shared<dyn Port>>,
     /// Output port set of the DEVS component (serialized for better performance).
-    out_ports: Vec<Arc<dyn Port>>,
+    out_ports: Vec<Shared<dyn Port>>,
+    /// Output ports that carry [`Msg`] envelopes.
+    msg_ports: Vec<Shared<dyn Stamp<T>>>,
+    /// Indices of the input ports that received messages in the current simulation cycle.
+    dirty_in: Vec<usize>,
+    /// Indices of the output ports that received messages in the current simulation cycle.
+    dirty_out: Vec<usize>,
+    /// Input ports that received messages since the last call to [`Component::track_input`].
+    in_tracker: Shared<PortTracker>,
+    /// Output ports that received messages since the last call to [`Component::track_output`].
+    out_tracker: Shared<PortTracker>,
+    /// Path of the DEVS component in the model hierarchy. It is set when the simulation starts.
+    path: Shared<str>,
+    /// Random number generator stream of the DEVS component.
+    rng: Rng,
+    /// It is `true` if the DEVS component is simulated with Classic DEVS semantics.
+    /// It is set by the parent coupled model when the simulation starts.
+    classic: bool,
+    /// Time spent in every simulation function. It is [`None`] if profiling is disabled.
+    profile: Option<Box<ComponentProfile>>,
+    /// Accumulators of the component and their names.
+    stats: Vec<(String, Statistic)>,
 }
 
-impl Component {
+impl<T: Time> Component<T> {
     /// It creates a new component with the provided name.
     pub fn new(name: &str) -> Self {
         Self {
             name: name.to_string(),
-            t_last: 0.,
-            t_next: f64::INFINITY,
+            t_last: T::ZERO,
+            t_next: T::INFINITY,
+            n_transitions: 0,
             in_map: HashMap::new(),
             out_map: HashMap::new(),
             in_ports: Vec::new(),
             out_ports: Vec::new(),
+            msg_ports: Vec::new(),
+            dirty_in: Vec::new(),
+            dirty_out: Vec::new(),
+            in_tracker: Shared::default(),
+            out_tracker: Shared::default(),
+            path: Shared::from(name),
+            rng: Rng::stream(0, name),
+            classic: false,
+            profile: None,
+            stats: Vec::new(),
         }
     }
 
@@ -41,64 +85,338 @@ impl Component {
         &self.name
     }
 
+    /// Returns the path of the component in the model hierarchy (e.g., `"root.subcoupled.atomic"`).
+    /// Until the simulation starts, it is the name of the component.
+    #[inline]
+    pub fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    /// Sets the path of the component in the model hierarchy.
+    #[inline]
+    pub(crate) fn set_path(&mut self, path: &str) {
+        self.path = Shared::from(path);
+    }
+
+    /// Returns the random number generator stream of the component.
+    /// By default, it is derived from the name of the component and the master seed 0.
+    /// Use [`crate::simulation::RootCoordinator::set_seed`] to derive the streams from another master seed.
+    /// Note that the stream can only be used in transition functions, as [`crate::modeling::Atomic::lambda`]
+    /// cannot mutate the state of the model.
+    #[inline]
+    pub fn rng(&mut self) -> &mut Rng {
+        &mut self.rng
+    }
+
+    /// Returns a reference to the random number generator stream of the component.
+    #[inline]
+    pub(crate) fn get_rng(&self) -> &Rng {
+        &self.rng
+    }
+
+    /// Sets the random number generator stream of the component.
+    #[inline]
+    pub(crate) fn set_rng(&mut self, rng: Rng) {
+        self.rng = rng;
+    }
+
+    /// Renames the component. It is used when inlining components into other coupled models.
+    #[inline]
+    pub(crate) fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+
+    /// Returns `true` if the component is simulated with Classic DEVS semantics.
+    #[inline]
+    pub(crate) fn is_classic(&self) -> bool {
+        self.classic
+    }
+
+    /// Sets whether the component is simulated with Classic DEVS semantics.
+    #[inline]
+    pub(crate) fn set_classic(&mut self, classic: bool) {
+        self.classic = classic;
+    }
+
+    /// Returns the time spent in every simulation function, or [`None`] if profiling is disabled.
+    #[inline]
+    pub(crate) fn get_profile(&self) -> Option<&ComponentProfile> {
+        self.profile.as_deref()
+    }
+
+    /// Enables or disables profiling. Enabling profiling resets the measured times.
+    #[inline]
+    pub(crate) fn set_profiling(&mut self, enabled: bool) {
+        self.profile = enabled.then(Box::default);
+    }
+
+    /// Returns the current instant if profiling is enabled. It must be passed to [`Component::profile_record`].
+    #[inline]
+    pub(crate) fn profile_start(&self) -> Option<Instant> {
+        self.profile.as_ref().map(|_| Instant::now())
+    }
+
+    /// Adds the time elapsed since `start` to a simulation function if profiling is enabled.
+    #[inline]
+    pub(crate) fn profile_record(&mut self, phase: Phase, start: Option<Instant>) {
+        if let (Some(profile), Some(start)) = (self.profile.as_mut(), start) {
+            profile.record(phase, start);
+        }
+    }
+
+    /// Adds a new statistic accumulator. It panics if there is already an accumulator with the same name.
+    fn add_stat(&mut self, name: &str, stat: Statistic) {
+        if self.stats.iter().any(|(n, _)| n == name) {
+            panic!("component {} already contains statistic {name}", self.path);
+        }
+        self.stats.push((name.to_string(), stat));
+    }
+
+    /// Adds a new [`Counter`] and returns a handle to it.
+    /// It panics if there is already an accumulator with the same name.
+    pub fn add_counter(&mut self, name: &str) -> Counter {
+        let counter = Counter::default();
+        self.add_stat(name, Statistic::Counter(counter.clone()));
+        counter
+    }
+
+    /// Adds a new [`Tally`] and returns a handle to it.
+    /// It panics if there is already an accumulator with the same name.
+    pub fn add_tally(&mut self, name: &str) -> Tally {
+        let tally = Tally::default();
+        self.add_stat(name, Statistic::Tally(tally.clone()));
+        tally
+    }
+
+    /// Adds a new [`TimeWeighted`] accumulator with the value of its variable at time zero and returns a handle to it.
+    /// It panics if there is already an accumulator with the same name.
+    pub fn add_time_weighted(&mut self, name: &str, initial: f64) -> TimeWeighted {
+        let time_weighted = TimeWeighted::new(initial);
+        self.add_stat(name, Statistic::TimeWeighted(time_weighted.clone()));
+        time_weighted
+    }
+
+    /// Returns the statistic accumulators of the component and their names.
+    #[inline]
+    pub(crate) fn get_stats(&self) -> &[(String, Statistic)] {
+        &self.stats
+    }
+
+    /// Resets all the statistic accumulators of the component.
+    #[inline]
+    pub(crate) fn reset_stats(&self) {
+        self.stats.iter().for_each(|(_, stat)| stat.reset());
+    }
+
     /// Returns the time for the last component state transition.
     #[inline]
-    pub fn get_t_last(&self) -> f64 {
+    pub fn get_t_last(&self) -> T {
         self.t_last
     }
 
     /// Returns the time for the next component state transition.
     #[inline]
-    pub fn get_t_next(&self) -> f64 {
+    pub fn get_t_next(&self) -> T {
         self.t_next
     }
 
+    /// Returns the number of state transitions triggered since the component was created.
+    /// Only atomic models count their state transitions, so it is always 0 for coupled models.
+    #[inline]
+    pub fn get_n_transitions(&self) -> usize {
+        self.n_transitions
+    }
+
+    /// Increments the number of state transitions triggered by the component.
+    #[inline]
+    pub(crate) fn count_transition(&mut self) {
+        self.n_transitions += 1;
+    }
+
+    /// Sets the number of state transitions triggered since the component was created.
+    #[inline]
+    pub(crate) fn set_n_transitions(&mut self, n_transitions: usize) {
+        self.n_transitions = n_transitions;
+    }
+
     /// Sets the time for the for the last and next component state transitions.
     #[inline]
-    pub(crate) fn set_sim_t(&mut self, t_last: f64, t_next: f64) {
+    pub(crate) fn set_sim_t(&mut self, t_last: T, t_next: T) {
         self.t_last = t_last;
         self.t_next = t_next;
     }
 
-    /// Adds a new input port of type `T` and returns a reference to it.
+    /// Adds a new input port of type `M` and returns a reference to it.
     /// It panics if there is already an input port with the same name.
-    pub fn add_in_port<T: DynRef + Clone>(&mut self, name: &str) -> InPort<T> {
+    pub fn add_in_port<M: DynRef + Clone>(&mut self, name: &str) -> InPort<M> {
+        self.try_add_in_port(name).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a new input port of type `M` and returns a reference to it.
+    /// It returns an error if there is already an input port with the same name.
+    pub fn try_add_in_port<M: DynRef + Clone>(
+        &mut self,
+        name: &str,
+    ) -> Result<InPort<M>, ModelingError> {
         if self.in_map.contains_key(name) {
-            panic!("component already contains input port with the name provided");
+            let (c, p) = (self.name.clone(), name.to_string());
+            return Err(ModelingError::DuplicateInPort(c, p));
         }
         self.in_map.insert(name.to_string(), self.in_ports.len());
-        let bag = Bag::new();
+        let bag = Bag::new(name, Some((self.in_tracker.clone(), self.in_ports.len())));
         self.in_ports.push(bag.clone());
-        InPort(bag)
+        Ok(InPort(bag))
+    }
+
+    /// Adds a vector of `n` input ports of type `M` and returns references to them.
+    /// Ports are named after their index (e.g., `"input[0]"`, `"input[1]"`...), so they can also be coupled by name.
+    /// It panics if there is already an input port with any of these names.
+    pub fn add_in_port_vec<M: DynRef + Clone>(&mut self, name: &str, n: usize) -> Vec<InPort<M>> {
+        self.try_add_in_port_vec(name, n)
+            .unwrap_or_else(|e| panic!("{e}"))
     }
 
-    /// Adds a new output port of type `T` and returns a reference to it.
+    /// Adds a vector of `n` input ports of type `M` and returns references to them.
+    /// It returns an error if there is already an input port with any of their names. In that case, no port is added.
+    pub fn try_add_in_port_vec<M: DynRef + Clone>(
+        &mut self,
+        name: &str,
+        n: usize,
+    ) -> Result<Vec<InPort<M>>, ModelingError> {
+        let names: Vec<_> = (0..n).map(|i| format!("{name}[{i}]")).collect();
+        if let Some(p) = names.iter().find(|p| self.in_map.contains_key(*p)) {
+            let (c, p) = (self.name.clone(), p.clone());
+            return Err(ModelingError::DuplicateInPort(c, p));
+        }
+        names.iter().map(|p| self.try_add_in_port(p)).collect()
+    }
+
+    /// Adds a new output port of type `M` and returns a reference to it.
     /// It panics if there is already an output port with the same name.
-    pub fn add_out_port<T: DynRef + Clone>(&mut self, name: &str) -> OutPort<T> {
+    pub fn add_out_port<M: DynRef + Clone>(&mut self, name: &str) -> OutPort<M> {
+        self.try_add_out_port(name)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a new output port of type `M` and returns a reference to it.
+    /// It returns an error if there is already an output port with the same name.
+    pub fn try_add_out_port<M: DynRef + Clone>(
+        &mut self,
+        name: &str,
+    ) -> Result<OutPort<M>, ModelingError> {
         if self.out_map.contains_key(name) {
-            panic!("component already contains output port with the name provided");
+            let (c, p) = (self.name.clone(), name.to_string());
+            return Err(ModelingError::DuplicateOutPort(c, p));
         }
         self.out_map.insert(name.to_string(), self.out_ports.len());
-        let bag = Bag::new();
+        let bag = Bag::new(name, Some((self.out_tracker.clone(), self.out_ports.len())));
         self.out_ports.push(bag.clone());
-        OutPort(bag)
+        Ok(OutPort(bag))
+    }
+
+    /// Adds a vector of `n` output ports of type `M` and returns references to them.
+    /// Ports are named after their index (e.g., `"output[0]"`, `"output[1]"`...), so they can also be coupled by name.
+    /// It panics if there is already an output port with any of these names.
+    pub fn add_out_port_vec<M: DynRef + Clone>(&mut self, name: &str, n: usize) -> Vec<OutPort<M>> {
+        self.try_add_out_port_vec(name, n)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a vector of `n` output ports of type `M` and returns references to them.
+    /// It returns an error if there is already an output port with any of their names. In that case, no port is added.
+    pub fn try_add_out_port_vec<M: DynRef + Clone>(
+        &mut self,
+        name: &str,
+        n: usize,
+    ) -> Result<Vec<OutPort<M>>, ModelingError> {
+        let names: Vec<_> = (0..n).map(|i| format!("{name}[{i}]")).collect();
+        if let Some(p) = names.iter().find(|p| self.out_map.contains_key(*p)) {
+            let (c, p) = (self.name.clone(), p.clone());
+            return Err(ModelingError::DuplicateOutPort(c, p));
+        }
+        names.iter().map(|p| self.try_add_out_port(p)).collect()
     }
 
-    /// Returns `true` if all the input ports of the model are empty.
+    /// Adds a new output port that carries [`Msg`] envelopes of type `M` and returns a reference to it.
+    /// The emission time and the path of the component are recorded in every envelope sent through the port.
+    /// It panics if there is already an output port with the same name.
+    pub fn add_msg_out_port<M: DynRef + Clone>(&mut self, name: &str) -> OutPort<Msg<M, T>> {
+        self.try_add_msg_out_port(name)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a new output port that carries [`Msg`] envelopes of type `M` and returns a reference to it.
+    /// It returns an error if there is already an output port with the same name.
+    pub fn try_add_msg_out_port<M: DynRef + Clone>(
+        &mut self,
+        name: &str,
+    ) -> Result<OutPort<Msg<M, T>>, ModelingError> {
+        let port = self.try_add_out_port::<Msg<M, T>>(name)?;
+        self.msg_ports.push(port.0.clone());
+        Ok(port)
+    }
+
+    /// Populates the metadata of the envelopes sent through the output ports of the component.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed by a simulator right after the output function of its model.
+    #[inline]
+    pub(crate) unsafe fn stamp_output(&self, t: T) {
+        for port in self.msg_ports.iter() {
+            port.stamp(t, &self.path);
+        }
+    }
+
+    /// Records the input ports of the model that contain messages, so [`Component::clear_input`] only clears them.
+    /// It returns `true` if any input port contains messages.
     ///
     /// # Safety
     ///
     /// This method can only be executed when implementing the [`crate::simulation::Simulator::transition`]
     /// method to determine whether to execute the internal, external, or confluent transition function.
     #[inline]
-    pub(crate) unsafe fn is_input_empty(&self) -> bool {
-        self.in_ports.iter().all(|p| p.is_empty())
+    pub(crate) unsafe fn track_input(&mut self) -> bool {
+        Self::track(&self.in_tracker, &self.in_ports, &mut self.dirty_in);
+        !self.dirty_in.is_empty()
+    }
+
+    /// Records the output ports of the model that contain messages, so [`Component::clear_output`] only clears them.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed when implementing the [`crate::simulation::Simulator::collection`]
+    /// method, right after the model sent its output messages.
+    #[inline]
+    pub(crate) unsafe fn track_output(&mut self) {
+        Self::track(&self.out_tracker, &self.out_ports, &mut self.dirty_out);
+    }
+
+    /// Moves the ports marked by `tracker` to `dirty`, sorted and without duplicates.
+    /// Only ports that still contain messages are kept.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed by [`Component::track_input`] or [`Component::track_output`].
+    #[inline]
+    unsafe fn track(tracker: &PortTracker, ports: &[Shared<dyn Port>], dirty: &mut Vec<usize>) {
+        dirty.clear();
+        tracker.take(dirty);
+        dirty.sort_unstable();
+        dirty.dedup();
+        dirty.retain(|&i| !ports[i].is_empty());
+    }
+
+    /// Returns the indices of the input ports recorded by [`Component::track_input`].
+    #[inline]
+    pub(crate) fn dirty_inputs(&self) -> &[usize] {
+        &self.dirty_in
     }
 
     /// Returns a reference to an input port with the given name.
     /// If the component does not have any input port with this name, it returns [`None`].
     #[inline]
-    pub(crate) fn get_in_port(&self, port_name: &str) -> Option<Arc<dyn Port>> {
+    pub(crate) fn get_in_port(&self, port_name: &str) -> Option<Shared<dyn Port>> {
         let i = *self.in_map.get(port_name)?;
         Some(self.in_ports.get(i)?.clone())
     }
@@ -106,28 +424,79 @@ impl Component {
     /// Returns a reference to an output port with the given name.
     /// If the component does not have any output port with this name, it returns [`None`].
     #[inline]
-    pub(crate) fn get_out_port(&self, port_name: &str) -> Option<Arc<dyn Port>> {
+    pub(crate) fn get_out_port(&self, port_name: &str) -> Option<Shared<dyn Port>> {
         let i = *self.out_map.get(port_name)?;
         Some(self.out_ports.get(i)?.clone())
     }
 
-    /// Clears all the input ports of the model.
+    /// Returns a slice with all the input ports of the model.
+    #[inline]
+    pub(crate) fn get_in_ports(&self) -> &[Shared<dyn Port>] {
+        &self.in_ports
+    }
+
+    /// Returns a slice with all the output ports of the model.
+    #[inline]
+    pub(crate) fn get_out_ports(&self) -> &[Shared<dyn Port>] {
+        &self.out_ports
+    }
+
+    /// Applies a [`BagPolicy`] to all the ports of the model.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed by a coordinator before the simulation starts.
+    #[inline]
+    pub(crate) unsafe fn set_bag_policy(&self, policy: BagPolicy) {
+        for port in self.in_ports.iter().chain(self.out_ports.iter()) {
+            port.set_capacity(policy.reserve, policy.max_capacity);
+        }
+    }
+
+    /// Clears the input ports of the model recorded by [`Component::track_input`].
     ///
     /// # Safety
     ///
-    /// This method can only be executed when implementing [`crate::simulation::Simulator::clear_ports`] method.
+    /// This method can only be executed when implementing the [`crate::simulation::Simulator::transition`] method.
     #[inline]
     pub(crate) unsafe fn clear_input(&mut self) {
-        self.in_ports.iter_mut().for_each(|p| p.clear());
+        self.dirty_in
+            .drain(..)
+            .for_each(|i| self.in_ports[i].clear());
+        #[cfg(feature = "debug_checks")]
+        check_empty(self, &self.in_ports, "after clearing the input ports");
     }
 
-    /// Clears all the output ports of the model.
+    /// Clears the output ports of the model recorded by [`Component::track_output`].
     ///
     /// # Safety
     ///
-    /// This method can only be executed when implementing [`crate::simulation::Simulator::clear_ports`] method.
+    /// This method can only be executed when implementing the [`crate::simulation::Simulator::transition`] method.
     #[inline]
     pub(crate) unsafe fn clear_output(&mut self) {
-        self.out_ports.iter_mut().for_each(|p| p.clear());
+        self.dirty_out
+            .drain(..)
+            .for_each(|i| self.out_ports[i].clear());
+        #[cfg(feature = "debug_checks")]
+        check_empty(self, &self.out_ports, "after clearing the output ports");
+    }
+
+    /// Clears all the ports of the model, regardless of whether they were recorded as containing messages.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed by a simulator between simulation cycles.
+    #[inline]
+    pub(crate) unsafe fn clear_ports(&mut self) {
+        self.dirty_in.clear();
+        self.dirty_out.clear();
+        self.in_tracker.reset();
+        self.out_tracker.reset();
+        (self.in_ports.iter().chain(&self.out_ports)).for_each(|p| p.clear());
+        #[cfg(feature = "debug_checks")]
+        {
+            check_empty(self, &self.in_ports, "after clearing all the ports");
+            check_empty(self, &self.out_ports, "after clearing all the ports");
+        }
     }
 }
@@ -1,44 +1,261 @@
-use super::port::Port;
-use super::{Component, InPort, OutPort};
-use crate::simulation::Simulator;
-use crate::DynRef;
+use super::diagram;
+use super::port::{Adapter, Port};
+use super::{Component, InPort, ModelStats, OutPort};
+use crate::error::ModelingError;
+#[cfg(feature = "par_couplings")]
+use crate::simulation::MergeOrder;
+use crate::simulation::{Scheduler, Simulator};
+use crate::time::Time;
+use crate::{DynRef, Shared};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-pub(crate) type Coupling = (Arc<dyn Port>, Arc<dyn Port>);
+pub(crate) type Coupling = (Shared<dyn Port>, Shared<dyn Port>);
 
-/// Coupled DEVS model.
-pub struct Coupled {
+/// Atomic output port connected to an output port of a nested coupled model,
+/// and the adapter of the corresponding coupling (if any).
+type Source = (Shared<dyn Port>, Option<Shared<dyn Port>>);
+
+/// Coupling map. Keys are destination port keys, and values are maps from source port keys
+/// to the indices of the couplings between both ports.
+type CouplingMap = HashMap<usize, HashMap<usize, Vec<usize>>>;
+
+/// Couplings of a coupled model arranged for parallel propagation. Couplings are grouped by destination port,
+/// and groups are propagated in parallel, so every port is written by only one thread.
+///
+/// With the `par_buffers` feature, destination ports with several sources receive messages in two steps.
+/// First, every coupling fills its own buffer (all the couplings are propagated in parallel).
+/// Then, the buffers of every destination port are moved into it (destinations are merged in parallel).
+#[cfg(feature = "par_couplings")]
+#[derive(Default)]
+pub(crate) struct ParCouplings {
+    /// Couplings whose destination is a buffer. They are only used with the `par_buffers` feature.
+    fills: Vec<Coupling>,
+    /// Couplings grouped by destination port. With the `par_buffers` feature, the source of couplings
+    /// with several sources per destination is the buffer that gathers the messages of the original source.
+    merges: Vec<Vec<Coupling>>,
+}
+
+#[cfg(feature = "par_couplings")]
+impl ParCouplings {
+    /// Adds groups of couplings that share their destination port.
+    /// Messages of every group are merged in the same order as the couplings of the group.
+    fn extend(&mut self, groups: Vec<Vec<Coupling>>) {
+        #[cfg(feature = "par_buffers")]
+        let groups = groups.into_iter().map(|group| match group.len() {
+            1 => group,
+            _ => (group.into_iter())
+                .map(|(port_to, port_from)| {
+                    let buffer = port_from.new_buffer();
+                    self.fills.push((buffer.clone(), port_from));
+                    (port_to, buffer)
+                })
+                .collect(),
+        });
+        self.merges.extend(groups);
+    }
+
+    /// Removes all the couplings.
+    pub(crate) fn clear(&mut self) {
+        self.fills.clear();
+        self.merges.clear();
+    }
+
+    /// Propagates messages through all the couplings in parallel.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed by a coupled model when propagating messages,
+    /// as in [`Port::propagate`].
+    pub(crate) unsafe fn propagate(&self) {
+        use rayon::prelude::*;
+
+        #[cfg(feature = "par_buffers")]
+        self.fills.par_iter().for_each(|(buffer, port_from)| {
+            // Safety: every buffer has only one source
+            unsafe { port_from.propagate(&**buffer) };
+        });
+        self.merges.par_iter().for_each(|group| {
+            // sources of groups with several couplings are buffers that have only one destination
+            let buffered = cfg!(feature = "par_buffers") && group.len() > 1;
+            group.iter().for_each(|(port_to, port_from)| {
+                // Safety: every destination port is written by only one thread
+                unsafe {
+                    match buffered {
+                        true => {
+                            port_from.propagate_move(&**port_to);
+                            // couplings with adapters clone the messages instead of moving them
+                            port_from.clear();
+                        }
+                        false => port_from.propagate(&**port_to),
+                    }
+                }
+            });
+        });
+    }
+}
+
+/// Select function of Classic DEVS coupled models (see [`Coupled::set_select`]).
+/// It receives the names of the imminent components and returns the index of the one that must be executed.
+pub trait Select: Fn(&[&str]) -> usize + DynRef {}
+
+impl<F: Fn(&[&str]) -> usize + DynRef> Select for F {}
+
+/// Subcomponent of a [`Coupled`] model. By default, coupled models store their subcomponents as
+/// `Box<dyn Simulator>`, so they can contain any model, but every simulation step of a subcomponent is a virtual call.
+/// If all the subcomponents of a coupled model belong to a closed set of types, declare an enum with
+/// the [`crate::simulator_enum`] macro and use it as the subcomponent type (e.g., `Coupled<f64, MyComponents>`).
+/// Then, simulation steps are dispatched with a `match`, so the compiler can inline them.
+pub trait Subcomponent<T: Time = f64>: DynRef {
+    /// Returns a reference to the subcomponent as a simulator.
+    fn as_simulator(&self) -> &dyn Simulator<T>;
+
+    /// Returns a mutable reference to the subcomponent as a simulator.
+    fn as_simulator_mut(&mut self) -> &mut dyn Simulator<T>;
+
+    /// Starts the simulation of the subcomponent (see [`Simulator::start`]).
+    #[inline]
+    fn start(&mut self, t_start: T) -> T {
+        self.as_simulator_mut().start(t_start)
+    }
+
+    /// Stops the simulation of the subcomponent (see [`Simulator::stop`]).
+    #[inline]
+    fn stop(&mut self, t_stop: T) {
+        self.as_simulator_mut().stop(t_stop)
+    }
+
+    /// Executes the collection phase of the subcomponent (see [`Simulator::collection`]).
+    #[inline]
+    fn collection(&mut self, t: T) {
+        self.as_simulator_mut().collection(t)
+    }
+
+    /// Executes the transition phase of the subcomponent (see [`Simulator::transition`]).
+    #[inline]
+    fn transition(&mut self, t: T) -> T {
+        self.as_simulator_mut().transition(t)
+    }
+}
+
+impl<T: Time> Subcomponent<T> for Box<dyn Simulator<T>> {
+    #[inline]
+    fn as_simulator(&self) -> &dyn Simulator<T> {
+        self.as_ref()
+    }
+
+    #[inline]
+    fn as_simulator_mut(&mut self) -> &mut dyn Simulator<T> {
+        self.as_mut()
+    }
+}
+
+/// Counter used for assigning a unique ID to every coupled model.
+static N_COUPLED: AtomicUsize = AtomicUsize::new(0);
+
+/// Handle of a component of a coupled model. It is returned by [`Coupled::add_component`],
+/// and allows adding couplings without looking up components by their name.
+/// Handles stay valid when other components are removed, and the handle of a removed component is rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ComponentId {
+    /// Unique ID of the coupled model that contains the component.
+    model: usize,
+    /// Serial number of the component in the coupled model. It is never reused.
+    serial: usize,
+}
+
+/// Returns a key that uniquely identifies a port as long as it is alive.
+/// Adapters are identified by the port that finally receives their messages.
+#[inline]
+pub(crate) fn port_key(port: &Shared<dyn Port>) -> usize {
+    match port.target() {
+        Some(target) => port_key(target),
+        None => Shared::as_ptr(port) as *const () as usize,
+    }
+}
+
+/// Replaces an input port of a nested coupled model by the atomic input ports it is connected to, according to `targets`.
+/// Adapters are retargeted to the resolved ports.
+fn resolve_to(
+    port: Shared<dyn Port>,
+    targets: &HashMap<usize, Vec<Shared<dyn Port>>>,
+) -> Vec<Shared<dyn Port>> {
+    let resolved = targets.get(&port_key(&port)).cloned();
+    match (resolved, port.target()) {
+        (Some(p_tos), Some(_)) => p_tos
+            .into_iter()
+            .map(|p_to| port.retarget(p_to).unwrap())
+            .collect(),
+        (Some(p_tos), None) => p_tos,
+        (None, _) => vec![port],
+    }
+}
+
+/// Coupled DEVS model. `T` is the simulation time type (by default, [`f64`]).
+pub struct Coupled<T: Time = f64, C: Subcomponent<T> = Box<dyn Simulator<T>>> {
     /// Component wrapped by the coupled model.
-    pub(crate) component: Component,
+    pub(crate) component: Component<T>,
+    /// Unique ID of the coupled model. It is used for checking [`ComponentId`] handles.
+    id: usize,
     /// Components map. Keys are components' IDs.
     comps_map: HashMap<String, usize>,
-    /// External input couplings map.
-    eic_map: HashMap<String, HashMap<String, usize>>,
-    /// Internal couplings map.
-    ic_map: HashMap<String, HashMap<String, usize>>,
-    /// External output couplings map.
-    eoc_map: HashMap<String, HashMap<String, usize>>,
+    /// Serial number of every component (see [`ComponentId`]). It is sorted, as components keep their order.
+    serials: Vec<usize>,
+    /// Serial number of the next component.
+    next_serial: usize,
+    /// External input couplings map. Keys are destination port keys, and values are
+    /// maps from source port keys to the indices of the couplings in `eics`.
+    eic_map: CouplingMap,
+    /// Internal couplings map. Keys are destination port keys, and values are
+    /// maps from source port keys to the indices of the couplings in `ics`.
+    ic_map: CouplingMap,
+    /// External output couplings map. Keys are destination port keys, and values are
+    /// maps from source port keys to the indices of the couplings in `eocs`.
+    eoc_map: CouplingMap,
     /// Components of the DEVS coupled model (serialized for better performance).
-    pub(crate) components: Vec<Box<dyn Simulator>>,
+    pub(crate) components: Vec<C>,
     /// External input couplings (serialized for better performance).
     pub(crate) eics: Vec<Coupling>,
     /// Internal couplings (serialized for better performance).
     pub(crate) ics: Vec<Coupling>,
     /// External output couplings (serialized for better performance).
     pub(crate) eocs: Vec<Coupling>,
+    /// Scheduler of the components. It is built when the simulation starts.
+    pub(crate) scheduler: Scheduler<T>,
+    /// External input couplings arranged for parallel propagation.
+    #[cfg(feature = "par_couplings")]
+    pub(crate) par_eics: ParCouplings,
+    /// External output and internal couplings arranged for parallel propagation.
     #[cfg(feature = "par_couplings")]
-    pub(crate) par_eics: Vec<Vec<Coupling>>,
+    pub(crate) par_xxcs: ParCouplings,
+    /// Select function for Classic DEVS semantics. If [`None`], the coupled model follows Parallel DEVS semantics
+    /// (unless it is a subcomponent of a Classic DEVS coupled model).
+    select: Option<Box<dyn Select>>,
+    /// Order in which messages are merged into ports when couplings are propagated in parallel.
     #[cfg(feature = "par_couplings")]
-    pub(crate) par_xxcs: Vec<Vec<Coupling>>,
+    pub(crate) merge_order: MergeOrder,
+    /// It is `true` if couplings whose source port has no other coupling move messages instead of cloning them.
+    #[cfg(not(feature = "par_couplings"))]
+    pub(crate) move_messages: bool,
+    /// It is `true` for nested models processed by [`Coupled::direct_connect`]. Their input ports
+    /// do not receive messages, as couplings are connected to the ports of their subcomponents.
+    pub(crate) direct: bool,
+    /// Keys of the destination and source ports of the couplings composed by [`Coupled::direct_connect`]
+    /// before composing them (i.e., ports of subcomponents), indexed by the keys of their current ports.
+    direct_ends: HashMap<(usize, usize), (usize, usize)>,
 }
 
-impl Coupled {
-    /// Creates a new coupled DEVS model with the provided name.
-    pub fn new(name: &str) -> Self {
+impl<T: Time, C: Subcomponent<T>> Coupled<T, C> {
+    /// Creates a new coupled DEVS model with the provided name and a custom [`Subcomponent`] type
+    /// (e.g., `Coupled::<f64, MyComponents>::custom("coupled")`).
+    pub fn custom(name: &str) -> Self {
         Self {
             component: Component::new(name),
+            id: N_COUPLED.fetch_add(1, Ordering::Relaxed),
             comps_map: HashMap::new(),
+            serials: Vec::new(),
+            next_serial: 0,
             eic_map: HashMap::new(),
             ic_map: HashMap::new(),
             eoc_map: HashMap::new(),
@@ -46,13 +263,46 @@ impl Coupled {
             eics: Vec::new(),
             ics: Vec::new(),
             eocs: Vec::new(),
+            scheduler: Scheduler::default(),
             #[cfg(feature = "par_couplings")]
-            par_eics: Vec::new(),
+            par_eics: ParCouplings::default(),
             #[cfg(feature = "par_couplings")]
-            par_xxcs: Vec::new(),
+            par_xxcs: ParCouplings::default(),
+            select: None,
+            #[cfg(feature = "par_couplings")]
+            merge_order: MergeOrder::default(),
+            #[cfg(not(feature = "par_couplings"))]
+            move_messages: true,
+            direct: false,
+            direct_ends: HashMap::new(),
         }
     }
 
+    /// Reserves capacity for at least `additional` more components.
+    pub fn reserve_components(&mut self, additional: usize) {
+        self.comps_map.reserve(additional);
+        self.serials.reserve(additional);
+        self.components.reserve(additional);
+    }
+
+    /// Reserves capacity for at least `additional` more external input couplings.
+    pub fn reserve_eics(&mut self, additional: usize) {
+        self.eic_map.reserve(additional);
+        self.eics.reserve(additional);
+    }
+
+    /// Reserves capacity for at least `additional` more internal couplings.
+    pub fn reserve_ics(&mut self, additional: usize) {
+        self.ic_map.reserve(additional);
+        self.ics.reserve(additional);
+    }
+
+    /// Reserves capacity for at least `additional` more external output couplings.
+    pub fn reserve_eocs(&mut self, additional: usize) {
+        self.eoc_map.reserve(additional);
+        self.eocs.reserve(additional);
+    }
+
     /// Returns the number of components in the coupled model.
     #[inline]
     pub fn n_components(&self) -> usize {
@@ -62,53 +312,314 @@ impl Coupled {
     /// Returns the number of external input couplings in the coupled model.
     #[inline]
     pub fn n_eics(&self) -> usize {
-        self.eic_map.values().map(|eics| eics.len()).sum()
+        self.eics.len()
     }
 
     /// Returns the number of internal couplings in the coupled model.
     #[inline]
     pub fn n_ics(&self) -> usize {
-        self.ic_map.values().map(|ics| ics.len()).sum()
+        self.ics.len()
     }
 
     /// Returns the number of external output couplings in the coupled model.
     #[inline]
     pub fn n_eocs(&self) -> usize {
-        self.eoc_map.values().map(|eocs| eocs.len()).sum()
+        self.eocs.len()
+    }
+
+    /// Returns the structure statistics of the model and its subcomponents (e.g., depth, number of
+    /// atomic models and couplings, or fan-in and fan-out of ports). It is useful for sanity-checking
+    /// generated models before simulating them. Couplings of statically composed models are not counted.
+    pub fn stats(&self) -> ModelStats {
+        ModelStats::new(self)
+    }
+
+    /// Returns a [Mermaid](https://mermaid.js.org) flowchart of the model hierarchy, so it can be embedded
+    /// in Markdown documents and web pages. Coupled models are subgraphs, atomic models are nodes,
+    /// and couplings are edges labeled with the names of their ports.
+    pub fn to_mermaid(&self) -> String {
+        diagram::mermaid(self)
+    }
+
+    /// Returns a [PlantUML](https://plantuml.com) component diagram of the model hierarchy.
+    /// Coupled models are rectangles, atomic models are components,
+    /// and couplings are arrows labeled with the names of their ports.
+    pub fn to_plantuml(&self) -> String {
+        diagram::plantuml(self)
     }
 
-    /// Adds a new input port of type `T` and returns a reference to it.
+    /// Adds a new input port of type `M` and returns a reference to it.
     /// It panics if there is already an input port with the same name.
     #[inline]
-    pub fn add_in_port<T: DynRef + Clone>(&mut self, name: &str) -> InPort<T> {
-        self.component.add_in_port::<T>(name)
+    pub fn add_in_port<M: DynRef + Clone>(&mut self, name: &str) -> InPort<M> {
+        self.component.add_in_port::<M>(name)
     }
 
-    /// Adds a new output port of type `T` and returns a reference to it.
+    /// Adds a new input port of type `M` and returns a reference to it.
+    /// It returns an error if there is already an input port with the same name.
+    #[inline]
+    pub fn try_add_in_port<M: DynRef + Clone>(
+        &mut self,
+        name: &str,
+    ) -> Result<InPort<M>, ModelingError> {
+        self.component.try_add_in_port::<M>(name)
+    }
+
+    /// Adds a new output port of type `M` and returns a reference to it.
     /// It panics if there is already an output port with the same name.
     #[inline]
-    pub fn add_out_port<T: DynRef + Clone>(&mut self, name: &str) -> OutPort<T> {
-        self.component.add_out_port::<T>(name)
+    pub fn add_out_port<M: DynRef + Clone>(&mut self, name: &str) -> OutPort<M> {
+        self.component.add_out_port::<M>(name)
+    }
+
+    /// Adds a new output port of type `M` and returns a reference to it.
+    /// It returns an error if there is already an output port with the same name.
+    #[inline]
+    pub fn try_add_out_port<M: DynRef + Clone>(
+        &mut self,
+        name: &str,
+    ) -> Result<OutPort<M>, ModelingError> {
+        self.component.try_add_out_port::<M>(name)
+    }
+
+    /// Adds a vector of `n` input ports of type `M` and returns references to them.
+    /// Ports are named after their index (e.g., `"input[0]"`, `"input[1]"`...), so they can also be coupled by name.
+    /// It panics if there is already an input port with any of these names.
+    #[inline]
+    pub fn add_in_port_vec<M: DynRef + Clone>(&mut self, name: &str, n: usize) -> Vec<InPort<M>> {
+        self.component.add_in_port_vec::<M>(name, n)
+    }
+
+    /// Adds a vector of `n` input ports of type `M` and returns references to them.
+    /// It returns an error if there is already an input port with any of their names. In that case, no port is added.
+    #[inline]
+    pub fn try_add_in_port_vec<M: DynRef + Clone>(
+        &mut self,
+        name: &str,
+        n: usize,
+    ) -> Result<Vec<InPort<M>>, ModelingError> {
+        self.component.try_add_in_port_vec::<M>(name, n)
+    }
+
+    /// Adds a vector of `n` output ports of type `M` and returns references to them.
+    /// Ports are named after their index (e.g., `"output[0]"`, `"output[1]"`...), so they can also be coupled by name.
+    /// It panics if there is already an output port with any of these names.
+    #[inline]
+    pub fn add_out_port_vec<M: DynRef + Clone>(&mut self, name: &str, n: usize) -> Vec<OutPort<M>> {
+        self.component.add_out_port_vec::<M>(name, n)
+    }
+
+    /// Adds a vector of `n` output ports of type `M` and returns references to them.
+    /// It returns an error if there is already an output port with any of their names. In that case, no port is added.
+    #[inline]
+    pub fn try_add_out_port_vec<M: DynRef + Clone>(
+        &mut self,
+        name: &str,
+        n: usize,
+    ) -> Result<Vec<OutPort<M>>, ModelingError> {
+        self.component.try_add_out_port_vec::<M>(name, n)
     }
 
-    /// Adds a new component to the coupled model.
+    /// Adds a new subcomponent to the coupled model and returns its [`ComponentId`].
+    /// Use it for coupled models with a custom [`Subcomponent`] type (see [`crate::simulator_enum`]).
     /// If there is already a component with the same name as the new component, it panics.
-    pub fn add_component<T: Simulator>(&mut self, component: Box<T>) {
-        let component_name = component.get_name();
+    pub fn add_subcomponent(&mut self, component: impl Into<C>) -> ComponentId {
+        self.try_add_subcomponent(component)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a new subcomponent to the coupled model and returns its [`ComponentId`].
+    /// If there is already a component with the same name as the new component, it returns an error.
+    pub fn try_add_subcomponent(
+        &mut self,
+        component: impl Into<C>,
+    ) -> Result<ComponentId, ModelingError> {
+        let mut component = component.into();
+        let simulator = component.as_simulator_mut();
+        let component_name = simulator.get_name();
         if self.comps_map.contains_key(component_name) {
-            panic!("coupled model already contains component with the name provided")
+            let name = component_name.to_string();
+            return Err(ModelingError::DuplicateComponent(name));
         }
+        // paths are kept up to date so diagnostics can tell apart components with the same name
+        let path = format!("{}.{component_name}", self.component.get_path());
+        simulator.set_path(&path);
+        let index = self.components.len();
         self.comps_map
-            .insert(component_name.to_string(), self.components.len());
+            .insert(simulator.get_name().to_string(), index);
         self.components.push(component);
+        let serial = self.next_serial;
+        self.next_serial += 1;
+        self.serials.push(serial);
+        Ok(ComponentId {
+            model: self.id,
+            serial,
+        })
     }
 
-    /// Returns a reference to a component with the provided name.
+    /// Removes a component from the coupled model, together with all the couplings to and from its ports.
+    /// It returns the removed component, or [`None`] if the model does not contain any component with that name.
+    /// [`ComponentId`]s of the remaining components are still valid, but the ID of the removed one is rejected.
+    pub fn remove_component(&mut self, name: &str) -> Option<C> {
+        let index = self.comps_map.remove(name)?;
+        let mut component = self.components.remove(index);
+        self.serials.remove(index);
+        component.as_simulator_mut().set_path(name);
+        for i in self.comps_map.values_mut() {
+            if *i > index {
+                *i -= 1;
+            }
+        }
+        let inner = component.as_simulator().get_component();
+        let is_in = |port: &Shared<dyn Port>| {
+            let key = port_key(port);
+            inner.get_in_ports().iter().any(|p| port_key(p) == key)
+        };
+        let is_out = |port: &Shared<dyn Port>| {
+            let key = port_key(port);
+            inner.get_out_ports().iter().any(|p| port_key(p) == key)
+        };
+        Self::remove_couplings(&mut self.eic_map, &mut self.eics, |(p_to, _)| is_in(p_to));
+        Self::remove_couplings(&mut self.ic_map, &mut self.ics, |(p_to, p_from)| {
+            is_in(p_to) || is_out(p_from)
+        });
+        Self::remove_couplings(&mut self.eoc_map, &mut self.eocs, |(_, p_from)| {
+            is_out(p_from)
+        });
+        Some(component)
+    }
+
+    /// Returns the [`ComponentId`] of the component with the provided name.
     /// If the coupled model does not contain any model with that name, it returns [`None`].
     #[inline]
-    fn get_component(&self, name: &str) -> Option<&Component> {
-        let index = *self.comps_map.get(name)?;
-        Some(self.components.get(index)?.get_component())
+    pub fn get_component_id(&self, name: &str) -> Option<ComponentId> {
+        Some(ComponentId {
+            model: self.id,
+            serial: self.serials[*self.comps_map.get(name)?],
+        })
+    }
+
+    /// Returns a reference to the component with the provided path in the model hierarchy
+    /// (e.g., `"top.subnet.router3"`). Paths start with the name of this coupled model, as in
+    /// [`Component::get_path`]. If there is no component with that path, it returns [`None`].
+    /// Subcomponents of nested coupled models with a custom [`Subcomponent`] type are not searched.
+    pub fn find(&self, path: &str) -> Option<&dyn Simulator<T>> {
+        let rest = path.strip_prefix(self.get_name())?;
+        if rest.is_empty() {
+            return Some(self);
+        }
+        let rest = rest.strip_prefix('.')?;
+        let name = rest.split('.').next().unwrap();
+        let component = self.components[*self.comps_map.get(name)?].as_simulator();
+        match component.as_coupled() {
+            Some(coupled) => coupled.find(rest),
+            None => (rest == name).then_some(component),
+        }
+    }
+
+    /// Returns a mutable reference to the component with the provided path in the model hierarchy.
+    /// See [`Coupled::find`] for details.
+    pub fn find_mut(&mut self, path: &str) -> Option<&mut dyn Simulator<T>> {
+        let rest = path.strip_prefix(self.get_name())?;
+        if rest.is_empty() {
+            return Some(self);
+        }
+        let rest = rest.strip_prefix('.')?;
+        let name = rest.split('.').next().unwrap();
+        let component = self.components[*self.comps_map.get(name)?].as_simulator_mut();
+        if component.as_coupled().is_some() {
+            return component.as_coupled_mut().unwrap().find_mut(rest);
+        }
+        (rest == name).then_some(component)
+    }
+
+    /// Returns a reference to the model of type `S` with the provided path in the model hierarchy.
+    /// If there is no component with that path or it is not of type `S`, it returns [`None`].
+    /// See [`Coupled::find`] for details.
+    pub fn find_as<S: Simulator<T>>(&self, path: &str) -> Option<&S> {
+        self.find(path)?.downcast_ref()
+    }
+
+    /// Returns a mutable reference to the model of type `S` with the provided path in the model hierarchy.
+    /// If there is no component with that path or it is not of type `S`, it returns [`None`].
+    /// See [`Coupled::find`] for details.
+    pub fn find_as_mut<S: Simulator<T>>(&mut self, path: &str) -> Option<&mut S> {
+        self.find_mut(path)?.downcast_mut()
+    }
+
+    /// Sets the select function of the coupled model, so it is simulated with Classic DEVS semantics.
+    /// When several subcomponents are imminent at the same time, only the one chosen by `select` executes
+    /// its output and internal transition functions. Subcomponents that receive its messages execute their
+    /// external transition function, even if they were imminent. The remaining imminent subcomponents
+    /// are executed in the next simulation cycles, which take place at the same simulation time.
+    ///
+    /// `select` receives the names of the imminent subcomponents in the order in which they were added to the model,
+    /// and returns the index of the chosen one. Nested coupled models are also simulated with Classic DEVS semantics.
+    /// If they do not have a select function, they choose their first imminent subcomponent.
+    /// Note that Classic DEVS models must not send messages to themselves.
+    pub fn set_select(&mut self, select: impl Select) {
+        self.select = Some(Box::new(select));
+    }
+
+    /// Returns `true` if the coupled model is simulated with Classic DEVS semantics.
+    #[inline]
+    pub(crate) fn is_classic(&self) -> bool {
+        self.select.is_some() || self.component.is_classic()
+    }
+
+    /// Selects the only imminent subcomponent that is executed in the current simulation cycle
+    /// of a Classic DEVS coupled model. It must be called right after finding the imminent subcomponents.
+    pub(crate) fn select_imminent(&mut self) {
+        let imminent = self.scheduler.imminent();
+        if imminent.len() > 1 {
+            let k = match &self.select {
+                Some(select) => {
+                    let names: Vec<_> = imminent
+                        .iter()
+                        .map(|&i| self.components[i].as_simulator().get_name())
+                        .collect();
+                    let k = select(&names);
+                    if k >= names.len() {
+                        let path = self.component.get_path();
+                        panic!("select function of {path} returned an invalid index {k}");
+                    }
+                    k
+                }
+                None => 0,
+            };
+            self.scheduler.select_imminent(k);
+        }
+    }
+
+    /// Returns a reference to a component with the provided name.
+    /// If the coupled model does not contain any model with that name, it returns an error.
+    #[inline]
+    fn get_component(&self, name: &str) -> Result<&Component<T>, ModelingError> {
+        match self.comps_map.get(name) {
+            Some(&index) => Ok(self.components[index].as_simulator().get_component()),
+            None => Err(ModelingError::UnknownComponent(name.to_string())),
+        }
+    }
+
+    /// Returns a reference to a component with the provided [`ComponentId`].
+    /// It panics if the ID does not belong to the coupled model.
+    #[inline]
+    pub(crate) fn get_component_by_id(&self, id: ComponentId) -> &Component<T> {
+        self.try_get_component_by_id(id)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Returns a reference to a component with the provided [`ComponentId`].
+    /// It returns an error if the ID does not belong to the coupled model or the component was removed.
+    #[inline]
+    fn try_get_component_by_id(&self, id: ComponentId) -> Result<&Component<T>, ModelingError> {
+        if id.model != self.id {
+            return Err(ModelingError::ForeignComponent);
+        }
+        match self.serials.binary_search(&id.serial) {
+            Ok(index) => Ok(self.components[index].as_simulator().get_component()),
+            Err(_) => Err(ModelingError::RemovedComponent),
+        }
     }
 
     /// Adds a new EIC to the model.
@@ -121,27 +632,48 @@ impl Coupled {
     /// - ports are not compatible.
     /// - coupling already exists.
     pub fn add_eic(&mut self, port_from: &str, component_to: &str, port_to: &str) {
-        let p_from = self
-            .component
-            .get_in_port(port_from)
-            .expect("port_from does not exist");
-        let comp_to = self
-            .get_component(component_to)
-            .expect("component_to does not exist");
-        let p_to = comp_to
-            .get_in_port(port_to)
-            .expect("port_to does not exist");
-        if !p_from.is_compatible(&*p_to) {
-            panic!("ports are not compatible")
-        }
-        let source_key = port_from.to_string();
-        let destination_key = component_to.to_string() + "-" + port_to;
-        let coups = self.eic_map.entry(destination_key).or_default();
-        if coups.contains_key(&source_key) {
-            panic!("coupling already exists");
-        }
-        coups.insert(source_key, self.eics.len());
-        self.eics.push((p_to, p_from));
+        self.try_add_eic(port_from, component_to, port_to)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a new EIC to the model. See [`Coupled::add_eic`].
+    /// Instead of panicking, it returns an error if the coupling is not valid.
+    pub fn try_add_eic(
+        &mut self,
+        port_from: &str,
+        component_to: &str,
+        port_to: &str,
+    ) -> Result<(), ModelingError> {
+        let comp_to = self.get_component(component_to)?;
+        let (p_from, p_to) = self.eic_ports(port_from, comp_to, port_to)?;
+        Self::insert_coupling(&mut self.eic_map, &mut self.eics, p_from, p_to)
+    }
+
+    /// Adds a new EIC to the model.
+    /// You must provide the input port name of the coupled model,
+    /// the receiving component ID, and its input port name.
+    /// This method panics if:
+    /// - the origin port does not exist.
+    /// - the destination component does not belong to the model.
+    /// - the destination port does not exist.
+    /// - ports are not compatible.
+    /// - coupling already exists.
+    pub fn add_eic_by_id(&mut self, port_from: &str, component_to: ComponentId, port_to: &str) {
+        self.try_add_eic_by_id(port_from, component_to, port_to)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a new EIC to the model. See [`Coupled::add_eic_by_id`].
+    /// Instead of panicking, it returns an error if the coupling is not valid.
+    pub fn try_add_eic_by_id(
+        &mut self,
+        port_from: &str,
+        component_to: ComponentId,
+        port_to: &str,
+    ) -> Result<(), ModelingError> {
+        let comp_to = self.try_get_component_by_id(component_to)?;
+        let (p_from, p_to) = self.eic_ports(port_from, comp_to, port_to)?;
+        Self::insert_coupling(&mut self.eic_map, &mut self.eics, p_from, p_to)
     }
 
     /// Adds a new IC to the model.
@@ -161,29 +693,59 @@ impl Coupled {
         component_to: &str,
         port_to: &str,
     ) {
-        let comp_from = self
-            .get_component(component_from)
-            .expect("component_from does not exist");
-        let p_from = comp_from
-            .get_out_port(port_from)
-            .expect("port_from does not exist");
-        let comp_to = self
-            .get_component(component_to)
-            .expect("component_to does not exist");
-        let p_to = comp_to
-            .get_in_port(port_to)
-            .expect("port_to does not exist");
-        if !p_from.is_compatible(&*p_to) {
-            panic!("ports are not compatible")
-        }
-        let source_key = component_from.to_string() + "-" + port_from;
-        let destination_key = component_to.to_string() + "-" + port_to;
-        let coups = self.ic_map.entry(destination_key).or_default();
-        if coups.contains_key(&source_key) {
-            panic!("coupling already exists");
-        }
-        coups.insert(source_key, self.ics.len());
-        self.ics.push((p_to, p_from));
+        self.try_add_ic(component_from, port_from, component_to, port_to)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a new IC to the model. See [`Coupled::add_ic`].
+    /// Instead of panicking, it returns an error if the coupling is not valid.
+    pub fn try_add_ic(
+        &mut self,
+        component_from: &str,
+        port_from: &str,
+        component_to: &str,
+        port_to: &str,
+    ) -> Result<(), ModelingError> {
+        let comp_from = self.get_component(component_from)?;
+        let comp_to = self.get_component(component_to)?;
+        let (p_from, p_to) = Self::ic_ports(comp_from, port_from, comp_to, port_to)?;
+        Self::insert_coupling(&mut self.ic_map, &mut self.ics, p_from, p_to)
+    }
+
+    /// Adds a new IC to the model.
+    /// You must provide the sending component ID, its output port name,
+    /// the receiving component ID, and its input port name.
+    /// This method panics if:
+    /// - the origin component does not belong to the model.
+    /// - the origin port does not exist.
+    /// - the destination component does not belong to the model.
+    /// - the destination port does not exist.
+    /// - ports are not compatible.
+    /// - coupling already exists.
+    pub fn add_ic_by_id(
+        &mut self,
+        component_from: ComponentId,
+        port_from: &str,
+        component_to: ComponentId,
+        port_to: &str,
+    ) {
+        self.try_add_ic_by_id(component_from, port_from, component_to, port_to)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a new IC to the model. See [`Coupled::add_ic_by_id`].
+    /// Instead of panicking, it returns an error if the coupling is not valid.
+    pub fn try_add_ic_by_id(
+        &mut self,
+        component_from: ComponentId,
+        port_from: &str,
+        component_to: ComponentId,
+        port_to: &str,
+    ) -> Result<(), ModelingError> {
+        let comp_from = self.try_get_component_by_id(component_from)?;
+        let comp_to = self.try_get_component_by_id(component_to)?;
+        let (p_from, p_to) = Self::ic_ports(comp_from, port_from, comp_to, port_to)?;
+        Self::insert_coupling(&mut self.ic_map, &mut self.ics, p_from, p_to)
     }
 
     /// Adds a new EOC to the model.
@@ -196,62 +758,1463 @@ impl Coupled {
     /// - ports are not compatible.
     /// - coupling already exists.
     pub fn add_eoc(&mut self, component_from: &str, port_from: &str, port_to: &str) {
-        let comp_from = self
+        self.try_add_eoc(component_from, port_from, port_to)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a new EOC to the model. See [`Coupled::add_eoc`].
+    /// Instead of panicking, it returns an error if the coupling is not valid.
+    pub fn try_add_eoc(
+        &mut self,
+        component_from: &str,
+        port_from: &str,
+        port_to: &str,
+    ) -> Result<(), ModelingError> {
+        let comp_from = self.get_component(component_from)?;
+        let (p_from, p_to) = self.eoc_ports(comp_from, port_from, port_to)?;
+        Self::insert_coupling(&mut self.eoc_map, &mut self.eocs, p_from, p_to)
+    }
+
+    /// Adds a new EOC to the model.
+    /// You must provide the sending component ID, its output port name,
+    /// and the output port name of the coupled model.
+    /// This method panics if:
+    /// - the origin component does not belong to the model.
+    /// - the origin port does not exist.
+    /// - the destination port does not exist.
+    /// - ports are not compatible.
+    /// - coupling already exists.
+    pub fn add_eoc_by_id(&mut self, component_from: ComponentId, port_from: &str, port_to: &str) {
+        self.try_add_eoc_by_id(component_from, port_from, port_to)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a new EOC to the model. See [`Coupled::add_eoc_by_id`].
+    /// Instead of panicking, it returns an error if the coupling is not valid.
+    pub fn try_add_eoc_by_id(
+        &mut self,
+        component_from: ComponentId,
+        port_from: &str,
+        port_to: &str,
+    ) -> Result<(), ModelingError> {
+        let comp_from = self.try_get_component_by_id(component_from)?;
+        let (p_from, p_to) = self.eoc_ports(comp_from, port_from, port_to)?;
+        Self::insert_coupling(&mut self.eoc_map, &mut self.eocs, p_from, p_to)
+    }
+
+    /// Removes an EIC from the model.
+    /// You must provide the input port name of the coupled model,
+    /// the receiving component name, and its input port name.
+    /// It returns `false` if the coupling does not exist.
+    pub fn remove_eic(&mut self, port_from: &str, component_to: &str, port_to: &str) -> bool {
+        let p_from = self.component.get_in_port(port_from);
+        let p_to = self
+            .get_component(component_to)
+            .ok()
+            .and_then(|c| c.get_in_port(port_to));
+        match (p_from, p_to) {
+            (Some(p_from), Some(p_to)) => {
+                Self::remove_coupling(&mut self.eic_map, &mut self.eics, &p_from, &p_to)
+            }
+            _ => false,
+        }
+    }
+
+    /// Removes an IC from the model.
+    /// You must provide the sending component name, its output port name,
+    /// the receiving component name, and its input port name.
+    /// It returns `false` if the coupling does not exist.
+    pub fn remove_ic(
+        &mut self,
+        component_from: &str,
+        port_from: &str,
+        component_to: &str,
+        port_to: &str,
+    ) -> bool {
+        let p_from = self
             .get_component(component_from)
-            .expect("component_from does not exist");
-        let p_from = comp_from
-            .get_out_port(port_from)
-            .expect("port_from does not exist");
+            .ok()
+            .and_then(|c| c.get_out_port(port_from));
         let p_to = self
-            .component
-            .get_out_port(port_to)
-            .expect("port_to does not exist");
+            .get_component(component_to)
+            .ok()
+            .and_then(|c| c.get_in_port(port_to));
+        match (p_from, p_to) {
+            (Some(p_from), Some(p_to)) => {
+                Self::remove_coupling(&mut self.ic_map, &mut self.ics, &p_from, &p_to)
+            }
+            _ => false,
+        }
+    }
+
+    /// Removes an EOC from the model.
+    /// You must provide the sending component name, its output port name,
+    /// and the output port name of the coupled model.
+    /// It returns `false` if the coupling does not exist.
+    pub fn remove_eoc(&mut self, component_from: &str, port_from: &str, port_to: &str) -> bool {
+        let p_from = self
+            .get_component(component_from)
+            .ok()
+            .and_then(|c| c.get_out_port(port_from));
+        let p_to = self.component.get_out_port(port_to);
+        match (p_from, p_to) {
+            (Some(p_from), Some(p_to)) => {
+                Self::remove_coupling(&mut self.eoc_map, &mut self.eocs, &p_from, &p_to)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the origin and destination ports of a new EIC. It returns an error if the ports do not
+    /// exist or are not compatible.
+    fn eic_ports(
+        &self,
+        port_from: &str,
+        comp_to: &Component<T>,
+        port_to: &str,
+    ) -> Result<Coupling, ModelingError> {
+        let p_from = Self::in_port(&self.component, port_from)?;
+        let p_to = Self::in_port(comp_to, port_to)?;
+        Self::check_compatible(&self.component, p_from, comp_to, p_to)
+    }
+
+    /// Returns the origin and destination ports of a new IC. It returns an error if the ports do not
+    /// exist or are not compatible.
+    fn ic_ports(
+        comp_from: &Component<T>,
+        port_from: &str,
+        comp_to: &Component<T>,
+        port_to: &str,
+    ) -> Result<Coupling, ModelingError> {
+        let p_from = Self::out_port(comp_from, port_from)?;
+        let p_to = Self::in_port(comp_to, port_to)?;
+        Self::check_compatible(comp_from, p_from, comp_to, p_to)
+    }
+
+    /// Returns the origin and destination ports of a new EOC. It returns an error if the ports do not
+    /// exist or are not compatible.
+    fn eoc_ports(
+        &self,
+        comp_from: &Component<T>,
+        port_from: &str,
+        port_to: &str,
+    ) -> Result<Coupling, ModelingError> {
+        let p_from = Self::out_port(comp_from, port_from)?;
+        let p_to = Self::out_port(&self.component, port_to)?;
+        Self::check_compatible(comp_from, p_from, &self.component, p_to)
+    }
+
+    /// Returns an input port of a component. It returns an error if the port does not exist.
+    fn in_port(component: &Component<T>, port: &str) -> Result<Shared<dyn Port>, ModelingError> {
+        component.get_in_port(port).ok_or_else(|| {
+            let (c, p) = (component.get_name().to_string(), port.to_string());
+            ModelingError::UnknownInPort(c, p)
+        })
+    }
+
+    /// Returns an output port of a component. It returns an error if the port does not exist.
+    fn out_port(component: &Component<T>, port: &str) -> Result<Shared<dyn Port>, ModelingError> {
+        component.get_out_port(port).ok_or_else(|| {
+            let (c, p) = (component.get_name().to_string(), port.to_string());
+            ModelingError::UnknownOutPort(c, p)
+        })
+    }
+
+    /// Returns the origin and destination ports of a coupling if they are compatible.
+    fn check_compatible(
+        comp_from: &Component<T>,
+        p_from: Shared<dyn Port>,
+        comp_to: &Component<T>,
+        p_to: Shared<dyn Port>,
+    ) -> Result<Coupling, ModelingError> {
         if !p_from.is_compatible(&*p_to) {
-            panic!("ports are not compatible")
+            let from = format!("{}.{}", comp_from.get_name(), p_from.get_name());
+            let to = format!("{}.{}", comp_to.get_name(), p_to.get_name());
+            return Err(ModelingError::IncompatiblePorts(from, to));
+        }
+        Ok((p_from, p_to))
+    }
+
+    /// Returns an adapter that maps the messages of a coupling between ports of different types.
+    /// It returns an error if the message types of the ports are not `A` and `B`, respectively.
+    fn adapter<A, B, F>(
+        comp_from: &Component<T>,
+        p_from: &Shared<dyn Port>,
+        comp_to: &Component<T>,
+        p_to: Shared<dyn Port>,
+        map: F,
+    ) -> Result<Shared<dyn Port>, ModelingError>
+    where
+        A: DynRef + Clone,
+        B: DynRef + Clone,
+        F: Fn(&A) -> B + DynRef,
+    {
+        let to = format!("{}.{}", comp_to.get_name(), p_to.get_name());
+        match Adapter::new(&**p_from, p_to, map) {
+            Some(adapter) => Ok(adapter),
+            None => {
+                let from = format!("{}.{}", comp_from.get_name(), p_from.get_name());
+                Err(ModelingError::IncompatiblePorts(from, to))
+            }
+        }
+    }
+
+    /// Adds a new EIC that maps every message of type `A` to a message of type `B` with an adapter function.
+    /// It avoids adding atomic models that just convert messages between components with different port types.
+    /// See [`Coupled::add_eic`]. This method also panics if the message types of the ports are not `A` and `B`.
+    pub fn add_eic_map<A, B, F>(
+        &mut self,
+        port_from: &str,
+        component_to: &str,
+        port_to: &str,
+        map: F,
+    ) where
+        A: DynRef + Clone,
+        B: DynRef + Clone,
+        F: Fn(&A) -> B + DynRef,
+    {
+        self.try_add_eic_map(port_from, component_to, port_to, map)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a new EIC with an adapter function to the model. See [`Coupled::add_eic_map`].
+    /// Instead of panicking, it returns an error if the coupling is not valid.
+    pub fn try_add_eic_map<A, B, F>(
+        &mut self,
+        port_from: &str,
+        component_to: &str,
+        port_to: &str,
+        map: F,
+    ) -> Result<(), ModelingError>
+    where
+        A: DynRef + Clone,
+        B: DynRef + Clone,
+        F: Fn(&A) -> B + DynRef,
+    {
+        let comp_to = self.get_component(component_to)?;
+        let p_from = Self::in_port(&self.component, port_from)?;
+        let p_to = Self::in_port(comp_to, port_to)?;
+        let p_to = Self::adapter(&self.component, &p_from, comp_to, p_to, map)?;
+        Self::insert_coupling(&mut self.eic_map, &mut self.eics, p_from, p_to)
+    }
+
+    /// Adds a new IC that maps every message of type `A` to a message of type `B` with an adapter function.
+    /// It avoids adding atomic models that just convert messages between components with different port types.
+    /// See [`Coupled::add_ic`]. This method also panics if the message types of the ports are not `A` and `B`.
+    pub fn add_ic_map<A, B, F>(
+        &mut self,
+        component_from: &str,
+        port_from: &str,
+        component_to: &str,
+        port_to: &str,
+        map: F,
+    ) where
+        A: DynRef + Clone,
+        B: DynRef + Clone,
+        F: Fn(&A) -> B + DynRef,
+    {
+        self.try_add_ic_map(component_from, port_from, component_to, port_to, map)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a new IC with an adapter function to the model. See [`Coupled::add_ic_map`].
+    /// Instead of panicking, it returns an error if the coupling is not valid.
+    pub fn try_add_ic_map<A, B, F>(
+        &mut self,
+        component_from: &str,
+        port_from: &str,
+        component_to: &str,
+        port_to: &str,
+        map: F,
+    ) -> Result<(), ModelingError>
+    where
+        A: DynRef + Clone,
+        B: DynRef + Clone,
+        F: Fn(&A) -> B + DynRef,
+    {
+        let comp_from = self.get_component(component_from)?;
+        let comp_to = self.get_component(component_to)?;
+        let p_from = Self::out_port(comp_from, port_from)?;
+        let p_to = Self::in_port(comp_to, port_to)?;
+        let p_to = Self::adapter(comp_from, &p_from, comp_to, p_to, map)?;
+        Self::insert_coupling(&mut self.ic_map, &mut self.ics, p_from, p_to)
+    }
+
+    /// Adds a new EOC that maps every message of type `A` to a message of type `B` with an adapter function.
+    /// It avoids adding atomic models that just convert messages between components with different port types.
+    /// See [`Coupled::add_eoc`]. This method also panics if the message types of the ports are not `A` and `B`.
+    pub fn add_eoc_map<A, B, F>(
+        &mut self,
+        component_from: &str,
+        port_from: &str,
+        port_to: &str,
+        map: F,
+    ) where
+        A: DynRef + Clone,
+        B: DynRef + Clone,
+        F: Fn(&A) -> B + DynRef,
+    {
+        self.try_add_eoc_map(component_from, port_from, port_to, map)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a new EOC with an adapter function to the model. See [`Coupled::add_eoc_map`].
+    /// Instead of panicking, it returns an error if the coupling is not valid.
+    pub fn try_add_eoc_map<A, B, F>(
+        &mut self,
+        component_from: &str,
+        port_from: &str,
+        port_to: &str,
+        map: F,
+    ) -> Result<(), ModelingError>
+    where
+        A: DynRef + Clone,
+        B: DynRef + Clone,
+        F: Fn(&A) -> B + DynRef,
+    {
+        let comp_from = self.get_component(component_from)?;
+        let p_from = Self::out_port(comp_from, port_from)?;
+        let p_to = Self::out_port(&self.component, port_to)?;
+        let p_to = Self::adapter(comp_from, &p_from, &self.component, p_to, map)?;
+        Self::insert_coupling(&mut self.eoc_map, &mut self.eocs, p_from, p_to)
+    }
+
+    /// Adds a new EIC to the model from port handles.
+    /// You must provide the input port of the coupled model and the input port of the receiving component.
+    /// As both ports share the same message type, compatibility is checked at compile time.
+    /// The receiving component must be added to the model before coupling its ports.
+    /// This method panics if a port does not belong to the model or its components, or if the coupling already exists.
+    pub fn add_typed_eic<M: DynRef + Clone>(&mut self, port_from: &InPort<M>, port_to: &InPort<M>) {
+        self.try_add_typed_eic(port_from, port_to)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a new EIC to the model from port handles. See [`Coupled::add_typed_eic`].
+    /// Instead of panicking, it returns an error if the coupling is not valid.
+    pub fn try_add_typed_eic<M: DynRef + Clone>(
+        &mut self,
+        port_from: &InPort<M>,
+        port_to: &InPort<M>,
+    ) -> Result<(), ModelingError> {
+        let p_from: Shared<dyn Port> = port_from.0.clone();
+        let p_to: Shared<dyn Port> = port_to.0.clone();
+        self.check_own_port(&p_from, true)?;
+        self.check_component_port(&p_to, true)?;
+        Self::insert_coupling(&mut self.eic_map, &mut self.eics, p_from, p_to)
+    }
+
+    /// Adds a new IC to the model from port handles.
+    /// You must provide the output port of the sending component and the input port of the receiving component.
+    /// As both ports share the same message type, compatibility is checked at compile time.
+    /// Both components must be added to the model before coupling their ports.
+    /// This method panics if a port does not belong to the components of the model, or if the coupling already exists.
+    pub fn add_typed_ic<M: DynRef + Clone>(&mut self, port_from: &OutPort<M>, port_to: &InPort<M>) {
+        self.try_add_typed_ic(port_from, port_to)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a new IC to the model from port handles. See [`Coupled::add_typed_ic`].
+    /// Instead of panicking, it returns an error if the coupling is not valid.
+    pub fn try_add_typed_ic<M: DynRef + Clone>(
+        &mut self,
+        port_from: &OutPort<M>,
+        port_to: &InPort<M>,
+    ) -> Result<(), ModelingError> {
+        let p_from: Shared<dyn Port> = port_from.0.clone();
+        let p_to: Shared<dyn Port> = port_to.0.clone();
+        self.check_component_port(&p_from, false)?;
+        self.check_component_port(&p_to, true)?;
+        Self::insert_coupling(&mut self.ic_map, &mut self.ics, p_from, p_to)
+    }
+
+    /// Adds a new EOC to the model from port handles.
+    /// You must provide the output port of the sending component and the output port of the coupled model.
+    /// As both ports share the same message type, compatibility is checked at compile time.
+    /// The sending component must be added to the model before coupling its ports.
+    /// This method panics if a port does not belong to the model or its components, or if the coupling already exists.
+    pub fn add_typed_eoc<M: DynRef + Clone>(
+        &mut self,
+        port_from: &OutPort<M>,
+        port_to: &OutPort<M>,
+    ) {
+        self.try_add_typed_eoc(port_from, port_to)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a new EOC to the model from port handles. See [`Coupled::add_typed_eoc`].
+    /// Instead of panicking, it returns an error if the coupling is not valid.
+    pub fn try_add_typed_eoc<M: DynRef + Clone>(
+        &mut self,
+        port_from: &OutPort<M>,
+        port_to: &OutPort<M>,
+    ) -> Result<(), ModelingError> {
+        let p_from: Shared<dyn Port> = port_from.0.clone();
+        let p_to: Shared<dyn Port> = port_to.0.clone();
+        self.check_component_port(&p_from, false)?;
+        self.check_own_port(&p_to, false)?;
+        Self::insert_coupling(&mut self.eoc_map, &mut self.eocs, p_from, p_to)
+    }
+
+    /// Returns an error if a port handle is not an input (or output) port of the coupled model.
+    fn check_own_port(&self, port: &Shared<dyn Port>, input: bool) -> Result<(), ModelingError> {
+        let ports = match input {
+            true => self.component.get_in_ports(),
+            false => self.component.get_out_ports(),
+        };
+        let key = port_key(port);
+        match ports.iter().any(|p| port_key(p) == key) {
+            true => Ok(()),
+            false => Err(ModelingError::ForeignPort(port.get_name().to_string())),
         }
-        let source_key = component_from.to_string() + "-" + port_from;
-        let destination_key = port_to.to_string();
-        let coups = self.eoc_map.entry(destination_key).or_default();
-        if coups.contains_key(&source_key) {
-            panic!("coupling already exists");
+    }
+
+    /// Returns an error if a port handle is not an input (or output) port of any component of the coupled model.
+    fn check_component_port(
+        &self,
+        port: &Shared<dyn Port>,
+        input: bool,
+    ) -> Result<(), ModelingError> {
+        let key = port_key(port);
+        let found = self.components.iter().any(|c| {
+            let component = c.as_simulator().get_component();
+            let ports = match input {
+                true => component.get_in_ports(),
+                false => component.get_out_ports(),
+            };
+            ports.iter().any(|p| port_key(p) == key)
+        });
+        match found {
+            true => Ok(()),
+            false => Err(ModelingError::ForeignPort(port.get_name().to_string())),
+        }
+    }
+
+    /// Inserts couplings whose ends may be ports of nested coupled models. These ports are replaced by the
+    /// atomic ports they are connected to, according to `targets` (for input ports) and `sources` (for output ports).
+    /// Adapters are retargeted to the resolved ports, and they are chained if necessary.
+    /// Every path leads to its own coupling, even if several paths connect the same pair of ports,
+    /// so messages are delivered as many times as in the original hierarchy.
+    /// If `record` is `true`, the original ports of the resolved couplings are recorded (see [`Coupled::direct_connect`]).
+    fn insert_resolved(
+        &mut self,
+        eics: Vec<Coupling>,
+        ics: Vec<Coupling>,
+        eocs: Vec<Coupling>,
+        targets: &HashMap<usize, Vec<Shared<dyn Port>>>,
+        sources: &HashMap<usize, Vec<Source>>,
+        record: bool,
+    ) {
+        let resolve_from = |port: Shared<dyn Port>| {
+            sources
+                .get(&port_key(&port))
+                .cloned()
+                .unwrap_or_else(|| vec![(port, None)])
+        };
+        let chain = |adapter: &Option<Shared<dyn Port>>, p_to: Shared<dyn Port>| match adapter {
+            Some(adapter) => adapter.retarget(p_to).unwrap(),
+            None => p_to,
+        };
+        let mut ends = Vec::new();
+        for (p_to, p_from) in eics {
+            let end = (port_key(&p_to), port_key(&p_from));
+            for p_to in resolve_to(p_to, targets) {
+                let p_from = p_from.clone();
+                let key = (port_key(&p_to), port_key(&p_from));
+                Self::push_coupling(&mut self.eic_map, &mut self.eics, p_from, p_to);
+                ends.push((key, end));
+            }
+        }
+        for (p_to, p_from) in ics {
+            let end = (port_key(&p_to), port_key(&p_from));
+            let p_tos = resolve_to(p_to, targets);
+            for (p_from, adapter) in resolve_from(p_from) {
+                for p_to in p_tos.iter().cloned() {
+                    let (p_from, p_to) = (p_from.clone(), chain(&adapter, p_to));
+                    let key = (port_key(&p_to), port_key(&p_from));
+                    Self::push_coupling(&mut self.ic_map, &mut self.ics, p_from, p_to);
+                    ends.push((key, end));
+                }
+            }
+        }
+        for (p_to, p_from) in eocs {
+            let end = (port_key(&p_to), port_key(&p_from));
+            for (p_from, adapter) in resolve_from(p_from) {
+                let p_to = chain(&adapter, p_to.clone());
+                let key = (port_key(&p_to), port_key(&p_from));
+                Self::push_coupling(&mut self.eoc_map, &mut self.eocs, p_from, p_to);
+                ends.push((key, end));
+            }
+        }
+        if record {
+            // couplings between the same pair of ports cross the same subcomponents, so any of their ends is valid
+            let composed = ends.into_iter().filter(|(key, end)| key != end);
+            self.direct_ends.extend(composed);
+        }
+    }
+
+    /// Returns the keys of the destination and source ports of a coupling. If the coupling was composed by
+    /// [`Coupled::direct_connect`], it returns the keys of the ports that it connected before composing it.
+    #[inline]
+    pub(crate) fn coupling_ends(&self, (p_to, p_from): &Coupling) -> (usize, usize) {
+        let key = (port_key(p_to), port_key(p_from));
+        self.direct_ends.get(&key).copied().unwrap_or(key)
+    }
+
+    /// Returns `true` if the couplings of the model can move messages from source ports with a single destination.
+    /// Direct-connected models share the ports of their subcomponents with other levels of the hierarchy,
+    /// so their couplings always clone messages.
+    #[cfg(not(feature = "par_couplings"))]
+    #[inline]
+    pub(crate) fn is_move_safe(&self) -> bool {
+        !self.direct && self.direct_ends.is_empty()
+    }
+
+    /// Registers a new coupling in the provided coupling map and coupling vector.
+    /// It returns an error if the coupling already exists.
+    fn insert_coupling(
+        map: &mut CouplingMap,
+        couplings: &mut Vec<Coupling>,
+        p_from: Shared<dyn Port>,
+        p_to: Shared<dyn Port>,
+    ) -> Result<(), ModelingError> {
+        if Self::contains_coupling(map, &p_from, &p_to) {
+            let (from, to) = (p_from.get_name().to_string(), p_to.get_name().to_string());
+            return Err(ModelingError::DuplicateCoupling(from, to));
+        }
+        Self::push_coupling(map, couplings, p_from, p_to);
+        Ok(())
+    }
+
+    /// Registers a new coupling in the provided coupling map and coupling vector,
+    /// even if there is already a coupling between the same pair of ports.
+    fn push_coupling(
+        map: &mut CouplingMap,
+        couplings: &mut Vec<Coupling>,
+        p_from: Shared<dyn Port>,
+        p_to: Shared<dyn Port>,
+    ) {
+        let coups = map.entry(port_key(&p_to)).or_default();
+        coups
+            .entry(port_key(&p_from))
+            .or_default()
+            .push(couplings.len());
+        couplings.push((p_to, p_from));
+    }
+
+    /// Returns `true` if the provided coupling map contains a coupling.
+    #[inline]
+    fn contains_coupling(
+        map: &CouplingMap,
+        p_from: &Shared<dyn Port>,
+        p_to: &Shared<dyn Port>,
+    ) -> bool {
+        map.get(&port_key(p_to))
+            .is_some_and(|coups| coups.contains_key(&port_key(p_from)))
+    }
+
+    /// Removes a coupling from the provided coupling map and coupling vector.
+    /// It returns `false` if the coupling does not exist.
+    fn remove_coupling(
+        map: &mut CouplingMap,
+        couplings: &mut Vec<Coupling>,
+        p_from: &Shared<dyn Port>,
+        p_to: &Shared<dyn Port>,
+    ) -> bool {
+        let exists = Self::contains_coupling(map, p_from, p_to);
+        if exists {
+            let (key_to, key_from) = (port_key(p_to), port_key(p_from));
+            Self::remove_couplings(map, couplings, |(to, from)| {
+                port_key(to) == key_to && port_key(from) == key_from
+            });
+        }
+        exists
+    }
+
+    /// Removes all the couplings that satisfy a predicate from the provided coupling vector,
+    /// and rebuilds the coupling map accordingly.
+    fn remove_couplings<F: Fn(&Coupling) -> bool>(
+        map: &mut CouplingMap,
+        couplings: &mut Vec<Coupling>,
+        remove: F,
+    ) {
+        couplings.retain(|coupling| !remove(coupling));
+        map.clear();
+        for (i, (p_to, p_from)) in couplings.iter().enumerate() {
+            map.entry(port_key(p_to))
+                .or_default()
+                .entry(port_key(p_from))
+                .or_default()
+                .push(i);
         }
-        coups.insert(source_key, self.eocs.len());
-        self.eocs.push((p_to, p_from));
     }
 
     #[cfg(feature = "par_couplings")]
     #[inline]
     pub(crate) fn build_par_eics(&mut self) {
-        for coups in self.eic_map.values() {
-            let mut x = Vec::new();
-            for &source in coups.values() {
-                x.push(self.eics[source].clone());
-            }
-            self.par_eics.push(x);
-        }
+        let groups = self.par_groups(&self.eic_map, &self.eics);
+        self.par_eics.extend(groups);
     }
 
     #[cfg(feature = "par_couplings")]
     #[inline]
     pub(crate) fn build_par_ics(&mut self) {
-        for coups in self.ic_map.values() {
-            let mut x = Vec::new();
-            for &source in coups.values() {
-                x.push(self.ics[source].clone());
-            }
-            self.par_xxcs.push(x);
-        }
+        let groups = self.par_groups(&self.ic_map, &self.ics);
+        self.par_xxcs.extend(groups);
     }
 
     #[cfg(feature = "par_couplings")]
     #[inline]
     pub(crate) fn build_par_eocs(&mut self) {
-        for coups in self.eoc_map.values() {
-            let mut x = Vec::new();
-            for &source in coups.values() {
-                x.push(self.eocs[source].clone());
+        let groups = self.par_groups(&self.eoc_map, &self.eocs);
+        self.par_xxcs.extend(groups);
+    }
+
+    /// Groups couplings by destination port, so every group can be propagated in parallel.
+    /// With [`MergeOrder::Deterministic`], couplings of every group are sorted by their index,
+    /// so messages are merged into the destination port in coupling declaration order.
+    #[cfg(feature = "par_couplings")]
+    fn par_groups(&self, map: &CouplingMap, couplings: &[Coupling]) -> Vec<Vec<Coupling>> {
+        map.values()
+            .map(|coups| {
+                let mut indices: Vec<_> = coups.values().flatten().copied().collect();
+                if self.merge_order == MergeOrder::Deterministic {
+                    indices.sort_unstable();
+                }
+                indices.into_iter().map(|i| couplings[i].clone()).collect()
+            })
+            .collect()
+    }
+}
+
+impl<T: Time> Coupled<T> {
+    /// Creates a new coupled DEVS model with the provided name.
+    #[inline]
+    pub fn new(name: &str) -> Self {
+        Self::custom(name)
+    }
+
+    /// Creates a new coupled DEVS model with the provided name and room for
+    /// `n_components` components and `n_couplings` internal couplings.
+    /// Use [`Coupled::reserve_eics`] and [`Coupled::reserve_eocs`] to make room for external couplings.
+    pub fn with_capacity(name: &str, n_components: usize, n_couplings: usize) -> Self {
+        let mut coupled = Self::new(name);
+        coupled.reserve_components(n_components);
+        coupled.reserve_ics(n_couplings);
+        coupled
+    }
+
+    /// Adds a new component to the coupled model and returns its [`ComponentId`].
+    /// If there is already a component with the same name as the new component, it panics.
+    pub fn add_component<S: Simulator<T>>(&mut self, component: Box<S>) -> ComponentId {
+        self.add_boxed_component(component)
+    }
+
+    /// Adds a new component to the coupled model and returns its [`ComponentId`].
+    /// If there is already a component with the same name as the new component, it returns an error.
+    pub fn try_add_component<S: Simulator<T>>(
+        &mut self,
+        component: Box<S>,
+    ) -> Result<ComponentId, ModelingError> {
+        self.try_add_boxed_component(component)
+    }
+
+    /// Adds a new boxed component to the coupled model and returns its [`ComponentId`].
+    /// If there is already a component with the same name as the new component, it panics.
+    pub(crate) fn add_boxed_component(&mut self, component: Box<dyn Simulator<T>>) -> ComponentId {
+        self.try_add_boxed_component(component)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a new boxed component to the coupled model and returns its [`ComponentId`].
+    /// If there is already a component with the same name as the new component, it returns an error.
+    #[inline]
+    pub(crate) fn try_add_boxed_component(
+        &mut self,
+        component: Box<dyn Simulator<T>>,
+    ) -> Result<ComponentId, ModelingError> {
+        self.try_add_subcomponent(component)
+    }
+
+    /// Recursively inlines all the nested coupled models, so the resulting model only contains atomic models.
+    /// EIC/IC/EOC chains are rewritten into direct couplings between ports of atomic models,
+    /// and inlined components are renamed after their path in the model (e.g., `"subcoupled.atomic"`).
+    /// Every EIC/IC/EOC chain leads to its own coupling, even if several chains connect the same pair of ports,
+    /// so the flattened model delivers as many messages as the original one.
+    /// Models must be flattened before starting the simulation.
+    /// The select function of the model is kept, but select functions of nested coupled models are dropped.
+    pub fn flatten(self) -> Self {
+        let mut flat = Self::new(self.get_name());
+        flat.component = self.component;
+        flat.select = self.select;
+        // Input ports of nested coupled models, and the atomic input ports they are connected to
+        let mut targets: HashMap<usize, Vec<Shared<dyn Port>>> = HashMap::new();
+        // Output ports of nested coupled models, and the atomic output ports connected to them
+        let mut sources: HashMap<usize, Vec<Source>> = HashMap::new();
+        for component in self.components {
+            let child = match component.into_coupled() {
+                Ok(child) => child.flatten(),
+                Err(atomic) => {
+                    flat.add_boxed_component(atomic);
+                    continue;
+                }
+            };
+            // Ports of the nested coupled model that are not coupled to any atomic port are dropped
+            for port in child.component.get_in_ports() {
+                targets.entry(port_key(port)).or_default();
+            }
+            for port in child.component.get_out_ports() {
+                sources.entry(port_key(port)).or_default();
+            }
+            for (p_to, p_from) in child.eics {
+                targets.entry(port_key(&p_from)).or_default().push(p_to);
+            }
+            for (p_to, p_from) in child.eocs {
+                let adapter = p_to.target().is_some().then(|| p_to.clone());
+                let key = port_key(&p_to);
+                sources.entry(key).or_default().push((p_from, adapter));
+            }
+            for (p_to, p_from) in child.ics {
+                Self::push_coupling(&mut flat.ic_map, &mut flat.ics, p_from, p_to);
+            }
+            for mut atomic in child.components {
+                let name = format!("{}.{}", child.component.get_name(), atomic.get_name());
+                atomic.get_component_mut().set_name(&name);
+                flat.add_boxed_component(atomic);
+            }
+        }
+        flat.insert_resolved(self.eics, self.ics, self.eocs, &targets, &sources, false);
+        flat
+    }
+
+    /// Composes the EIC/IC/EOC chains that cross nested coupled models into direct couplings between
+    /// ports of atomic models, so messages are copied once regardless of the number of levels they cross.
+    /// Every chain leads to its own coupling, even if several chains connect the same pair of ports.
+    /// Unlike [`Coupled::flatten`], components stay where they are: every coupling is moved to the innermost
+    /// coupled model that contains both ends. Nested coupled models keep their EICs, but only for finding
+    /// the subcomponents that must execute their transitions. Thus, ports of nested coupled models no longer
+    /// receive messages, and listeners are only notified of the output messages of atomic models and of the
+    /// model itself. Models must be direct-connected right before starting the simulation, and their couplings
+    /// must not be modified afterwards.
+    pub fn direct_connect(mut self) -> Self {
+        let targets = self.connect_directly();
+        // Input ports of the model do receive messages, so its EICs are composed as well
+        let eics = std::mem::take(&mut self.eics);
+        self.eic_map.clear();
+        self.insert_resolved(
+            eics,
+            Vec::new(),
+            Vec::new(),
+            &targets,
+            &HashMap::new(),
+            true,
+        );
+        self
+    }
+
+    /// Recursively composes the ICs and EOCs of the model and its nested coupled models.
+    /// It returns the input ports of the nested coupled models and the atomic input ports they are connected to.
+    fn connect_directly(&mut self) -> HashMap<usize, Vec<Shared<dyn Port>>> {
+        // Input ports of nested coupled models, and the atomic input ports they are connected to
+        let mut targets: HashMap<usize, Vec<Shared<dyn Port>>> = HashMap::new();
+        // Output ports of nested coupled models, and the atomic output ports connected to them
+        let mut sources: HashMap<usize, Vec<Source>> = HashMap::new();
+        for component in self.components.iter_mut() {
+            // Nested models that were already direct-connected do not have EOCs
+            let child = match component.as_coupled_mut() {
+                Some(child) if !child.direct => child,
+                _ => continue,
+            };
+            let child_targets = child.connect_directly();
+            for port in child.component.get_in_ports() {
+                targets.entry(port_key(port)).or_default();
+            }
+            for port in child.component.get_out_ports() {
+                sources.entry(port_key(port)).or_default();
+            }
+            for (p_to, p_from) in child.eics.iter() {
+                let p_tos = resolve_to(p_to.clone(), &child_targets);
+                targets.entry(port_key(p_from)).or_default().extend(p_tos);
+            }
+            for (p_to, p_from) in std::mem::take(&mut child.eocs) {
+                let adapter = p_to.target().is_some().then(|| p_to.clone());
+                let key = port_key(&p_to);
+                sources.entry(key).or_default().push((p_from, adapter));
+            }
+            child.eoc_map.clear();
+            child.direct = true;
+        }
+        let ics = std::mem::take(&mut self.ics);
+        let eocs = std::mem::take(&mut self.eocs);
+        self.ic_map.clear();
+        self.eoc_map.clear();
+        self.insert_resolved(Vec::new(), ics, eocs, &targets, &sources, true);
+        targets
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::Atomic;
+    use crate::simulation::RootCoordinator;
+    use std::sync::{Arc, Mutex};
+
+    struct Relay {
+        component: Component,
+        input: InPort<usize>,
+        output: OutPort<usize>,
+        received: Arc<Mutex<usize>>,
+        sigma: f64,
+    }
+
+    impl Relay {
+        fn new(name: &str, sigma: f64) -> Self {
+            let mut component = Component::new(name);
+            let input = component.add_in_port("input");
+            let output = component.add_out_port("output");
+            Self {
+                component,
+                input,
+                output,
+                received: Arc::new(Mutex::new(0)),
+                sigma,
+            }
+        }
+    }
+
+    impl Atomic for Relay {
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+
+        fn lambda(&self) {
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.output.add_value(1) };
+        }
+
+        fn delta_int(&mut self) {
+            self.sigma = f64::INFINITY;
+        }
+
+        fn delta_ext(&mut self, _e: f64) {
+            // Safety: reading messages on atomic model's input port at delta_ext
+            *self.received.lock().unwrap() += unsafe { self.input.get_values() }.len();
+        }
+
+        fn ta(&self) -> f64 {
+            self.sigma
+        }
+    }
+
+    /// Atomic model that records the messages it receives.
+    struct Sink {
+        component: Component,
+        input: InPort<String>,
+        received: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Sink {
+        fn new(name: &str) -> Self {
+            let mut component = Component::new(name);
+            let input = component.add_in_port("input");
+            Self {
+                component,
+                input,
+                received: Arc::new(Mutex::new(Vec::new())),
+            }
+        }
+    }
+
+    impl Atomic for Sink {
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+
+        fn lambda(&self) {}
+
+        fn delta_int(&mut self) {}
+
+        fn delta_ext(&mut self, _e: f64) {
+            // Safety: reading messages on atomic model's input port at delta_ext
+            let values = unsafe { self.input.get_values() };
+            self.received.lock().unwrap().extend_from_slice(values);
+        }
+
+        fn ta(&self) -> f64 {
+            f64::INFINITY
+        }
+    }
+
+    /// Atomic model that logs its output and external transition functions.
+    struct Pinger {
+        component: Component,
+        output: OutPort<usize>,
+        log: Arc<Mutex<Vec<String>>>,
+        sigma: f64,
+    }
+
+    impl Pinger {
+        fn new(name: &str, log: &Arc<Mutex<Vec<String>>>) -> Self {
+            let mut component = Component::new(name);
+            component.add_in_port::<usize>("input");
+            let output = component.add_out_port("output");
+            Self {
+                component,
+                output,
+                log: log.clone(),
+                sigma: 1.,
             }
-            self.par_xxcs.push(x);
+        }
+    }
+
+    impl Atomic for Pinger {
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+
+        fn lambda(&self) {
+            let name = self.component.get_name();
+            self.log.lock().unwrap().push(format!("{name}.lambda"));
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.output.add_value(1) };
+        }
+
+        fn delta_int(&mut self) {
+            self.sigma = f64::INFINITY;
+        }
+
+        fn delta_ext(&mut self, e: f64) {
+            let name = self.component.get_name();
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("{name}.delta_ext({e})"));
+            self.sigma -= e;
+        }
+
+        fn ta(&self) -> f64 {
+            self.sigma
+        }
+    }
+
+    /// Builds a coupled model with two pingers that are imminent at time 1 and send messages to each other.
+    fn pingers(name: &str, log: &Arc<Mutex<Vec<String>>>) -> Coupled {
+        let mut coupled = Coupled::new(name);
+        coupled.add_component(Box::new(Pinger::new("a", log)));
+        coupled.add_component(Box::new(Pinger::new("b", log)));
+        coupled.add_ic("a", "output", "b", "input");
+        coupled.add_ic("b", "output", "a", "input");
+        coupled
+    }
+
+    #[test]
+    fn test_select() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut coupled = pingers("coupled", &log);
+        coupled.set_select(|imminent: &[&str]| imminent.iter().position(|&c| c == "b").unwrap());
+        RootCoordinator::new(coupled).simulate(f64::INFINITY);
+        let expected = ["b.lambda", "a.delta_ext(1)", "a.lambda", "b.delta_ext(0)"];
+        assert_eq!(expected.as_slice(), log.lock().unwrap().as_slice());
+
+        // nested coupled models without select function choose their first imminent subcomponent
+        log.lock().unwrap().clear();
+        let mut root = Coupled::new("root");
+        root.add_component(Box::new(pingers("coupled", &log)));
+        root.set_select(|_: &[&str]| 0);
+        RootCoordinator::new(root).simulate(f64::INFINITY);
+        let expected = ["a.lambda", "b.delta_ext(1)", "b.lambda", "a.delta_ext(0)"];
+        assert_eq!(expected.as_slice(), log.lock().unwrap().as_slice());
+
+        // Parallel DEVS executes both models at once
+        log.lock().unwrap().clear();
+        RootCoordinator::new(pingers("coupled", &log)).simulate(f64::INFINITY);
+        let expected = ["a.lambda", "b.lambda", "a.delta_ext(0)", "b.delta_ext(0)"];
+        let mut actual = log.lock().unwrap().clone();
+        actual[2..].sort();
+        assert_eq!(expected.as_slice(), actual.as_slice());
+    }
+
+    #[test]
+    fn test_typed_couplings() {
+        let mut coupled = Coupled::new("coupled");
+        let output = coupled.add_out_port::<usize>("output");
+        let a = Relay::new("a", 0.);
+        let b = Relay::new("b", f64::INFINITY);
+        let received = b.received.clone();
+        // handles are kept, as components must be added before coupling their ports
+        let (a_output, b_input) = (OutPort(a.output.0.clone()), InPort(b.input.0.clone()));
+        coupled.add_component(Box::new(a));
+        coupled.add_component(Box::new(b));
+        coupled.add_typed_ic(&a_output, &b_input);
+        coupled.add_typed_eoc(&a_output, &output);
+        assert_eq!(1, coupled.n_ics());
+        assert_eq!(1, coupled.n_eocs());
+
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.simulate(f64::INFINITY);
+        assert_eq!(1, *received.lock().unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "coupling already exists")]
+    fn test_typed_duplicate_coupling() {
+        let mut coupled = Coupled::new("coupled");
+        let a = Relay::new("a", 0.);
+        let b = Relay::new("b", f64::INFINITY);
+        let (a_output, b_input) = (OutPort(a.output.0.clone()), InPort(b.input.0.clone()));
+        coupled.add_component(Box::new(a));
+        coupled.add_component(Box::new(b));
+        coupled.add_typed_ic(&a_output, &b_input);
+        coupled.add_ic("a", "output", "b", "input");
+    }
+
+    #[test]
+    fn test_typed_foreign_ports() {
+        let mut coupled = Coupled::new("coupled");
+        let input = coupled.add_in_port::<usize>("input");
+        let output = coupled.add_out_port::<usize>("output");
+        coupled.add_component(Box::new(Relay::new("a", 0.)));
+        let other = Relay::new("b", 0.);
+        let foreign = |port: &str| Err(ModelingError::ForeignPort(port.into()));
+        assert_eq!(
+            foreign("input"),
+            coupled.try_add_typed_eic(&input, &other.input)
+        );
+        assert_eq!(
+            foreign("output"),
+            coupled.try_add_typed_eoc(&other.output, &output)
+        );
+        let (a, b) = (
+            OutPort(other.output.0.clone()),
+            InPort(other.input.0.clone()),
+        );
+        coupled.add_component(Box::new(other));
+        // ports of the coupled model are not ports of its components, and vice versa
+        assert_eq!(foreign("input"), coupled.try_add_typed_eic(&b, &b));
+        assert_eq!(foreign("output"), coupled.try_add_typed_eoc(&a, &a));
+        coupled.add_typed_eic(&input, &b);
+        coupled.add_typed_ic(&a, &b);
+        coupled.add_typed_eoc(&a, &output);
+        assert_eq!(
+            (1, 1, 1),
+            (coupled.n_eics(), coupled.n_ics(), coupled.n_eocs())
+        );
+    }
+
+    #[test]
+    fn test_adapters() {
+        let mut coupled = Coupled::new("coupled");
+        coupled.add_component(Box::new(Relay::new("a", 0.)));
+        let sink = Sink::new("sink");
+        let received = sink.received.clone();
+        coupled.add_component(Box::new(sink));
+        coupled.add_ic_map("a", "output", "sink", "input", |v: &usize| format!("v{v}"));
+        assert_eq!(
+            Err(ModelingError::IncompatiblePorts(
+                "a.output".into(),
+                "sink.input".into()
+            )),
+            coupled.try_add_ic_map("a", "output", "sink", "input", |v: &usize| *v)
+        );
+        assert_eq!(
+            Err(ModelingError::DuplicateCoupling(
+                "output".into(),
+                "input".into()
+            )),
+            coupled.try_add_ic_map("a", "output", "sink", "input", |_: &usize| String::new())
+        );
+
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.simulate(f64::INFINITY);
+        assert_eq!(vec!["v1".to_string()], *received.lock().unwrap());
+        assert!(simulator.remove_ic("a", "output", "sink", "input"));
+        assert_eq!(0, simulator.n_ics());
+    }
+
+    #[test]
+    fn test_flatten_adapters() {
+        for (flatten, direct) in [(false, false), (true, false), (false, true)] {
+            let mut inner = Coupled::new("inner");
+            inner.add_in_port::<f64>("input");
+            inner.add_out_port::<f64>("output");
+            inner.add_component(Box::new(Relay::new("a", 0.)));
+            let inner_sink = Sink::new("sink");
+            let inner_received = inner_sink.received.clone();
+            inner.add_component(Box::new(inner_sink));
+            inner.add_eic_map("input", "sink", "input", |v: &f64| format!("in{v}"));
+            inner.add_eoc_map("a", "output", "output", |v: &usize| *v as f64 * 2.);
+
+            let mut coupled = Coupled::new("coupled");
+            let sink = Sink::new("sink");
+            let received = sink.received.clone();
+            coupled.add_component(Box::new(inner));
+            coupled.add_component(Box::new(sink));
+            coupled.add_ic_map("inner", "output", "sink", "input", |v: &f64| format!("{v}"));
+            coupled.add_ic_map("inner", "output", "inner", "input", |v: &f64| v + 1.);
+            let coupled = match (flatten, direct) {
+                (true, _) => coupled.flatten(),
+                (_, true) => coupled.direct_connect(),
+                _ => coupled,
+            };
+            assert_eq!(2, coupled.n_ics());
+
+            let mut simulator = RootCoordinator::new(coupled);
+            simulator.simulate(f64::INFINITY);
+            assert_eq!(vec!["2".to_string()], *received.lock().unwrap());
+            assert_eq!(vec!["in3".to_string()], *inner_received.lock().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_flatten_parallel_paths() {
+        for (flatten, direct) in [(false, false), (true, false), (false, true)] {
+            // a reaches b through two output ports of c1 that are coupled to the same input port of c2
+            let mut c1 = Coupled::new("c1");
+            c1.add_out_port::<usize>("o1");
+            c1.add_out_port::<usize>("o2");
+            c1.add_component(Box::new(Relay::new("a", 0.)));
+            c1.add_eoc("a", "output", "o1");
+            c1.add_eoc("a", "output", "o2");
+            let mut c2 = Coupled::new("c2");
+            c2.add_in_port::<usize>("i");
+            let b = Relay::new("b", f64::INFINITY);
+            let received = b.received.clone();
+            c2.add_component(Box::new(b));
+            c2.add_eic("i", "b", "input");
+
+            let mut coupled = Coupled::new("coupled");
+            coupled.add_component(Box::new(c1));
+            coupled.add_component(Box::new(c2));
+            coupled.add_ic("c1", "o1", "c2", "i");
+            coupled.add_ic("c1", "o2", "c2", "i");
+            let coupled = match (flatten, direct) {
+                (true, _) => coupled.flatten(),
+                (_, true) => coupled.direct_connect(),
+                _ => coupled,
+            };
+            assert_eq!(2, coupled.n_ics());
+
+            let mut simulator = RootCoordinator::new(coupled);
+            simulator.simulate(f64::INFINITY);
+            assert_eq!(2, *received.lock().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_port_vecs() {
+        let mut coupled = Coupled::new("coupled");
+        let inputs = coupled.add_in_port_vec::<usize>("input", 3);
+        let outputs = coupled.add_out_port_vec::<usize>("output", 3);
+        assert_eq!((3, 3), (inputs.len(), outputs.len()));
+        coupled.add_in_port::<usize>("extra[1]");
+        assert_eq!(
+            Err(ModelingError::DuplicateInPort(
+                "coupled".into(),
+                "extra[1]".into()
+            )),
+            coupled.try_add_in_port_vec::<usize>("extra", 2).map(|_| ())
+        );
+        assert_eq!(4, coupled.component.get_in_ports().len());
+
+        let mut relay = Relay::new("a", 0.);
+        let relay_inputs = relay.component.add_in_port_vec::<usize>("in", 2);
+        coupled.add_component(Box::new(relay));
+        coupled.add_eic("input[2]", "a", "in[1]");
+        coupled.add_typed_eic(&inputs[0], &relay_inputs[0]);
+        coupled.add_eoc("a", "output", "output[1]");
+        assert_eq!(2, coupled.n_eics());
+        assert_eq!(
+            Err(ModelingError::UnknownInPort("a".into(), "in[2]".into())),
+            coupled.try_add_eic("input[0]", "a", "in[2]")
+        );
+    }
+
+    #[test]
+    fn test_with_capacity() {
+        let mut coupled = Coupled::with_capacity("coupled", 2, 1);
+        coupled.reserve_eics(1);
+        assert!(coupled.components.capacity() >= 2 && coupled.ics.capacity() >= 1);
+        assert!(coupled.eics.capacity() >= 1 && coupled.eocs.capacity() == 0);
+        coupled.add_in_port::<usize>("input");
+        let components = coupled.components.as_ptr();
+        coupled.add_component(Box::new(Relay::new("a", 0.)));
+        coupled.add_component(Box::new(Relay::new("b", 0.)));
+        coupled.add_eic("input", "a", "input");
+        coupled.add_ic("a", "output", "b", "input");
+        assert_eq!(components, coupled.components.as_ptr());
+        assert_eq!(
+            (2, 1, 1),
+            (coupled.n_components(), coupled.n_eics(), coupled.n_ics())
+        );
+    }
+
+    #[test]
+    fn test_find() {
+        let mut subnet = Coupled::new("subnet");
+        subnet.add_component(Box::new(Relay::new("router3", 0.)));
+        let mut top = Coupled::new("top");
+        top.add_component(Box::new(Relay::new("router3", 0.)));
+        top.add_component(Box::new(subnet));
+
+        for path in ["top", "top.router3", "top.subnet", "top.subnet.router3"] {
+            assert_eq!(path, top.find(path).unwrap().get_component().get_path());
+        }
+        assert!(top.find("top.subnet").unwrap().as_coupled().is_some());
+        assert!(top.find("top.router3").unwrap().as_coupled().is_none());
+        for path in ["subnet.router3", "top.router", "top.router3.x", "top."] {
+            assert!(top.find(path).is_none());
+        }
+        let subnet = top
+            .find_mut("top.subnet")
+            .unwrap()
+            .as_coupled_mut()
+            .unwrap();
+        let router = subnet.remove_component("router3").unwrap();
+        assert_eq!("router3", router.get_component().get_path());
+        assert!(top.find_mut("top.subnet.router3").is_none());
+    }
+
+    #[test]
+    fn test_downcast() {
+        let mut top = Coupled::new("top");
+        top.add_component(Box::new(Relay::new("a", 1.)));
+        top.add_component(Box::new(Relay::new("b", f64::INFINITY)));
+        top.add_ic("a", "output", "b", "input");
+        let mut simulator = RootCoordinator::new(top);
+        simulator.simulate(f64::INFINITY);
+
+        let a = simulator.get_components()[0];
+        assert!(a.is::<Relay>() && !a.is::<Coupled>());
+        assert_eq!(f64::INFINITY, a.downcast_ref::<Relay>().unwrap().sigma);
+        let b = simulator.find_as::<Relay>("top.b").unwrap();
+        assert_eq!(1, *b.received.lock().unwrap());
+        assert!(simulator.find_as::<Coupled>("top").is_some());
+        assert!(simulator.find_as::<Coupled>("top.b").is_none());
+        simulator.find_as_mut::<Relay>("top.b").unwrap().sigma = 0.;
+        assert_eq!(0., simulator.find_as::<Relay>("top.b").unwrap().sigma);
+    }
+
+    #[test]
+    fn test_component_ids() {
+        let mut coupled = Coupled::new("coupled");
+        coupled.add_in_port::<usize>("input");
+        coupled.add_out_port::<usize>("output");
+        let a = coupled.add_component(Box::new(Relay::new("a", f64::INFINITY)));
+        let b = coupled.add_component(Box::new(Relay::new("b", f64::INFINITY)));
+        assert_eq!(Some(a), coupled.get_component_id("a"));
+        assert_eq!(None, coupled.get_component_id("c"));
+        coupled.add_eic_by_id("input", a, "input");
+        coupled.add_ic_by_id(a, "output", b, "input");
+        coupled.add_eoc_by_id(b, "output", "output");
+        assert_eq!(1, coupled.n_eics());
+        assert_eq!(1, coupled.n_ics());
+        assert_eq!(1, coupled.n_eocs());
+    }
+
+    #[test]
+    fn test_removed_component_ids() {
+        let mut coupled = Coupled::new("coupled");
+        coupled.add_out_port::<usize>("output");
+        let a = coupled.add_component(Box::new(Relay::new("a", f64::INFINITY)));
+        let b = coupled.add_component(Box::new(Relay::new("b", f64::INFINITY)));
+        let c = coupled.add_component(Box::new(Relay::new("c", f64::INFINITY)));
+        coupled.remove_component("a");
+        // IDs of the remaining components still refer to them
+        coupled.add_ic_by_id(b, "output", c, "input");
+        assert_eq!(Some(c), coupled.get_component_id("c"));
+        assert_eq!(
+            Err(ModelingError::RemovedComponent),
+            coupled.try_add_eoc_by_id(a, "output", "output")
+        );
+        // a new component with the same name gets a new ID
+        let new_a = coupled.add_component(Box::new(Relay::new("a", f64::INFINITY)));
+        assert_ne!(a, new_a);
+        coupled.add_eoc_by_id(new_a, "output", "output");
+        assert_eq!(
+            Err(ModelingError::RemovedComponent),
+            coupled.try_add_ic_by_id(c, "output", a, "input")
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "component does not belong to the coupled model")]
+    fn test_foreign_component_id() {
+        let mut other = Coupled::new("other");
+        let a = other.add_component(Box::new(Relay::new("a", f64::INFINITY)));
+        let mut coupled = Coupled::new("coupled");
+        coupled.add_in_port::<usize>("input");
+        coupled.add_component(Box::new(Relay::new("a", f64::INFINITY)));
+        coupled.add_eic_by_id("input", a, "input");
+    }
+
+    #[test]
+    fn test_modeling_errors() {
+        let mut coupled = Coupled::new("coupled");
+        coupled.add_in_port::<usize>("input");
+        coupled.add_out_port::<bool>("output");
+        let a = coupled.add_component(Box::new(Relay::new("a", f64::INFINITY)));
+        let mut other = Coupled::<f64>::new("other");
+        let foreign = other.add_component(Box::new(Relay::new("a", f64::INFINITY)));
+
+        assert_eq!(
+            Err(ModelingError::DuplicateInPort(
+                "coupled".into(),
+                "input".into()
+            )),
+            coupled.try_add_in_port::<usize>("input").map(|_| ())
+        );
+        assert_eq!(
+            Err(ModelingError::DuplicateComponent("a".into())),
+            coupled.try_add_component(Box::new(Relay::new("a", f64::INFINITY)))
+        );
+        assert_eq!(
+            Err(ModelingError::UnknownComponent("b".into())),
+            coupled.try_add_eic("input", "b", "input")
+        );
+        assert_eq!(
+            Err(ModelingError::UnknownInPort("a".into(), "in".into())),
+            coupled.try_add_eic("input", "a", "in")
+        );
+        assert_eq!(
+            Err(ModelingError::UnknownOutPort(
+                "coupled".into(),
+                "out".into()
+            )),
+            coupled.try_add_eoc("a", "output", "out")
+        );
+        assert_eq!(
+            Err(ModelingError::IncompatiblePorts(
+                "a.output".into(),
+                "coupled.output".into()
+            )),
+            coupled.try_add_eoc("a", "output", "output")
+        );
+        assert_eq!(
+            Err(ModelingError::ForeignComponent),
+            coupled.try_add_ic_by_id(a, "output", foreign, "input")
+        );
+        assert_eq!(Ok(()), coupled.try_add_ic("a", "output", "a", "input"));
+        assert_eq!(
+            Err(ModelingError::DuplicateCoupling(
+                "output".into(),
+                "input".into()
+            )),
+            coupled.try_add_ic("a", "output", "a", "input")
+        );
+        assert_eq!(1, coupled.n_ics());
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut coupled = Coupled::new("coupled");
+        coupled.add_in_port::<usize>("input");
+        coupled.add_out_port::<usize>("output");
+        for name in ["a", "b", "c"] {
+            coupled.add_component(Box::new(Relay::new(name, f64::INFINITY)));
+            coupled.add_eic("input", name, "input");
+            coupled.add_eoc(name, "output", "output");
+        }
+        coupled.add_ic("a", "output", "b", "input");
+        coupled.add_ic("b", "output", "c", "input");
+        coupled.add_ic("c", "output", "a", "input");
+
+        assert!(coupled.remove_ic("a", "output", "b", "input"));
+        assert!(!coupled.remove_ic("a", "output", "b", "input"));
+        assert!(!coupled.remove_eoc("d", "output", "output"));
+        assert!(coupled.remove_eic("input", "c", "input"));
+        assert_eq!(
+            (2, 2, 3),
+            (coupled.n_eics(), coupled.n_ics(), coupled.n_eocs())
+        );
+
+        let b = coupled.remove_component("b").unwrap();
+        assert_eq!("b", b.get_name());
+        assert!(coupled.remove_component("b").is_none());
+        assert_eq!(2, coupled.n_components());
+        assert_eq!(
+            (1, 1, 2),
+            (coupled.n_eics(), coupled.n_ics(), coupled.n_eocs())
+        );
+        assert_eq!(2, coupled.get_component_id("c").unwrap().serial);
+        // the remaining couplings can be removed and added again
+        assert!(coupled.remove_ic("c", "output", "a", "input"));
+        coupled.add_ic("c", "output", "a", "input");
+        coupled.add_eic("input", "c", "input");
+
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.simulate(f64::INFINITY);
+    }
+
+    #[test]
+    fn test_flatten() {
+        use crate::devstone::{HOmod, TestProbe, HI, HO, LI};
+        type Create = fn(usize, usize, u64, u64, Arc<Mutex<TestProbe>>) -> Coupled;
+
+        let models: [(Create, usize); 4] = [
+            (LI::create, 3),
+            (HI::create, 3),
+            (HO::create, 3),
+            (HOmod::create, 2),
+        ];
+        for (create, width) in models {
+            let nested = Arc::new(Mutex::new(TestProbe::default()));
+            let flat = Arc::new(Mutex::new(TestProbe::default()));
+            let mut simulator = RootCoordinator::new(create(width, 4, 0, 0, nested.clone()));
+            simulator.simulate(f64::INFINITY);
+            let coupled = create(width, 4, 0, 0, flat.clone()).flatten();
+            assert!(coupled
+                .components
+                .iter()
+                .all(|c| c.get_components().is_empty()));
+            let mut simulator = RootCoordinator::new(coupled);
+            simulator.simulate(f64::INFINITY);
+
+            let (nested, flat) = (nested.lock().unwrap(), flat.lock().unwrap());
+            assert_eq!(nested.n_internals, flat.n_internals);
+            assert_eq!(nested.n_externals, flat.n_externals);
+            assert_eq!(nested.n_events, flat.n_events);
+        }
+    }
+
+    #[test]
+    fn test_direct_connect() {
+        use crate::devstone::{HOmod, TestProbe, HI, HO, LI};
+        type Create = fn(usize, usize, u64, u64, Arc<Mutex<TestProbe>>) -> Coupled;
+
+        let models: [(Create, usize); 4] = [
+            (LI::create, 3),
+            (HI::create, 3),
+            (HO::create, 3),
+            (HOmod::create, 2),
+        ];
+        for (create, width) in models {
+            let nested = Arc::new(Mutex::new(TestProbe::default()));
+            let direct = Arc::new(Mutex::new(TestProbe::default()));
+            let mut simulator = RootCoordinator::new(create(width, 4, 0, 0, nested.clone()));
+            simulator.simulate(f64::INFINITY);
+            let coupled = create(width, 4, 0, 0, direct.clone()).direct_connect();
+            // the root model contains the seeder and the DEVStone coupled model
+            let child = coupled.components[1].as_coupled().unwrap();
+            assert_eq!(0, child.n_eocs());
+            assert!(child.direct);
+            let mut simulator = RootCoordinator::new(coupled);
+            simulator.simulate(f64::INFINITY);
+
+            let (nested, direct) = (nested.lock().unwrap(), direct.lock().unwrap());
+            assert_eq!(nested.n_internals, direct.n_internals);
+            assert_eq!(nested.n_externals, direct.n_externals);
+            assert_eq!(nested.n_events, direct.n_events);
         }
     }
 }
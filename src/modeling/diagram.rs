@@ -0,0 +1,230 @@
+use super::coupled::port_key;
+use super::{Component, Coupled, Subcomponent};
+use crate::simulation::Simulator;
+use crate::time::Time;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Component of a diagram. Coupled models contain the nodes of their subcomponents.
+struct Node {
+    id: usize,
+    name: String,
+    /// It is `None` for atomic models.
+    children: Option<Vec<Node>>,
+}
+
+/// Coupling of a diagram between two nodes.
+struct Edge {
+    from: usize,
+    port_from: String,
+    to: usize,
+    port_to: String,
+}
+
+/// Diagram of a model hierarchy, independent of the output format.
+struct Diagram {
+    root: Node,
+    edges: Vec<Edge>,
+}
+
+impl Diagram {
+    fn new<T: Time, C: Subcomponent<T>>(model: &Coupled<T, C>) -> Self {
+        let mut builder = Builder::default();
+        let root = builder.coupled(model);
+        // Couplings of direct-connected models may connect ports of any level of the hierarchy
+        let edges = (builder.couplings.into_iter())
+            .filter_map(|(key_from, key_to)| {
+                let (from, port_from) = builder.ports.get(&key_from)?.clone();
+                let (to, port_to) = builder.ports.get(&key_to)?.clone();
+                Some(Edge {
+                    from,
+                    port_from,
+                    to,
+                    port_to,
+                })
+            })
+            .collect();
+        Self { root, edges }
+    }
+}
+
+#[derive(Default)]
+struct Builder {
+    n_nodes: usize,
+    /// Owner node and name of every port, by port key.
+    ports: HashMap<usize, (usize, String)>,
+    /// Keys of the source and destination ports of every coupling.
+    couplings: Vec<(usize, usize)>,
+}
+
+impl Builder {
+    /// Creates a node for a component and registers its ports.
+    fn node<T: Time>(&mut self, component: &Component<T>) -> Node {
+        let id = self.n_nodes;
+        self.n_nodes += 1;
+        for port in (component.get_in_ports().iter()).chain(component.get_out_ports()) {
+            let name = port.get_name().to_string();
+            self.ports.insert(port_key(port), (id, name));
+        }
+        Node {
+            id,
+            name: component.get_name().to_string(),
+            children: None,
+        }
+    }
+
+    fn coupled<T: Time, C: Subcomponent<T>>(&mut self, model: &Coupled<T, C>) -> Node {
+        let mut node = self.node(&model.component);
+        // Couplings of the model are listed before the couplings of its subcomponents
+        let couplings = model.eics.iter().chain(&model.ics).chain(&model.eocs);
+        let couplings = couplings.map(|(p_to, p_from)| (port_key(p_from), port_key(p_to)));
+        self.couplings.extend(couplings);
+        let children = (model.get_components().into_iter())
+            .map(|c| self.simulator(c))
+            .collect();
+        node.children = Some(children);
+        node
+    }
+
+    fn simulator<T: Time>(&mut self, model: &dyn Simulator<T>) -> Node {
+        match model.as_coupled() {
+            Some(coupled) => self.coupled(coupled),
+            // Statically composed models do not expose their couplings
+            None if !model.get_components().is_empty() => {
+                let mut node = self.node(model.get_component());
+                let children = (model.get_components().into_iter())
+                    .map(|c| self.simulator(c))
+                    .collect();
+                node.children = Some(children);
+                node
+            }
+            None => self.node(model.get_component()),
+        }
+    }
+}
+
+/// Escapes double quotes of labels, as both Mermaid and PlantUML use them as delimiters.
+fn label(text: &str) -> String {
+    text.replace('"', "'")
+}
+
+/// Returns a [Mermaid](https://mermaid.js.org) flowchart of a coupled model.
+pub(crate) fn mermaid<T: Time, C: Subcomponent<T>>(model: &Coupled<T, C>) -> String {
+    let diagram = Diagram::new(model);
+    let mut res = String::from("flowchart LR\n");
+    mermaid_node(&mut res, &diagram.root, 4);
+    for edge in &diagram.edges {
+        writeln!(
+            res,
+            "    n{} -->|\"{} -> {}\"| n{}",
+            edge.from,
+            label(&edge.port_from),
+            label(&edge.port_to),
+            edge.to
+        )
+        .unwrap();
+    }
+    res
+}
+
+/// Writes a node of a Mermaid flowchart and its children.
+fn mermaid_node(res: &mut String, node: &Node, indent: usize) {
+    let name = label(&node.name);
+    match &node.children {
+        Some(children) => {
+            writeln!(res, "{:indent$}subgraph n{}[\"{name}\"]", "", node.id).unwrap();
+            for child in children {
+                mermaid_node(res, child, indent + 4);
+            }
+            writeln!(res, "{:indent$}end", "").unwrap();
+        }
+        None => writeln!(res, "{:indent$}n{}[\"{name}\"]", "", node.id).unwrap(),
+    }
+}
+
+/// Returns a [PlantUML](https://plantuml.com) component diagram of a coupled model.
+pub(crate) fn plantuml<T: Time, C: Subcomponent<T>>(model: &Coupled<T, C>) -> String {
+    let diagram = Diagram::new(model);
+    let mut res = String::from("@startuml\n");
+    plantuml_node(&mut res, &diagram.root, 0);
+    for edge in &diagram.edges {
+        writeln!(
+            res,
+            "n{} --> n{} : {} -> {}",
+            edge.from, edge.to, edge.port_from, edge.port_to
+        )
+        .unwrap();
+    }
+    res.push_str("@enduml\n");
+    res
+}
+
+/// Writes a node of a PlantUML component diagram and its children.
+fn plantuml_node(res: &mut String, node: &Node, indent: usize) {
+    let name = label(&node.name);
+    match &node.children {
+        Some(children) => {
+            writeln!(res, "{:indent$}rectangle \"{name}\" as n{} {{", "", node.id).unwrap();
+            for child in children {
+                plantuml_node(res, child, indent + 2);
+            }
+            writeln!(res, "{:indent$}}}", "").unwrap();
+        }
+        None => writeln!(res, "{:indent$}component \"{name}\" as n{}", "", node.id).unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lib::{Generator, Job, Processor};
+    use crate::modeling::Coupled;
+
+    fn model() -> Coupled {
+        let mut inner = Coupled::new("inner");
+        inner.add_in_port::<Job>("input");
+        inner.add_out_port::<Job>("output");
+        inner.add_component(Box::new(Processor::<Job>::new("processor", 1.)));
+        inner.add_eic("input", "processor", "input");
+        inner.add_eoc("processor", "output", "output");
+        let mut root = Coupled::new("root");
+        root.add_component(Box::new(Generator::new("generator", 1.)));
+        root.add_component(Box::new(inner));
+        root.add_ic("generator", "output", "inner", "input");
+        root
+    }
+
+    #[test]
+    fn test_mermaid() {
+        let expected = "\
+flowchart LR
+    subgraph n0[\"root\"]
+        n1[\"generator\"]
+        subgraph n2[\"inner\"]
+            n3[\"processor\"]
+        end
+    end
+    n1 -->|\"output -> input\"| n2
+    n2 -->|\"input -> input\"| n3
+    n3 -->|\"output -> output\"| n2
+";
+        assert_eq!(expected, model().to_mermaid());
+    }
+
+    #[test]
+    fn test_plantuml() {
+        let expected = "\
+@startuml
+rectangle \"root\" as n0 {
+  component \"generator\" as n1
+  rectangle \"inner\" as n2 {
+    component \"processor\" as n3
+  }
+}
+n1 --> n2 : output -> input
+n2 --> n3 : input -> input
+n3 --> n2 : output -> output
+@enduml
+";
+        assert_eq!(expected, model().to_plantuml());
+    }
+}
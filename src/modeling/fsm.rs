@@ -0,0 +1,305 @@
+use super::{Atomic, Component, InPort, OutPort};
+use crate::time::Time;
+use crate::DynRef;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// Timed transition of an [`FsmAtomic`].
+struct Timeout<S, O, T> {
+    /// Time spent in the state before the transition.
+    delay: T,
+    /// Next state.
+    next: S,
+    /// Message sent through the `output` port right before the transition, if any.
+    output: Option<O>,
+}
+
+/// Atomic model defined as a finite state machine. Instead of implementing the [`Atomic`] trait,
+/// declare its states `S` and transitions with [`FsmAtomic::timed`] and [`FsmAtomic::on`]:
+///
+/// - Timed transitions fire after the machine has spent a given time in a state.
+///   They may send a message through the `output` port right before leaving the state.
+/// - Input-triggered transitions fire when the machine receives a given message of type `I`
+///   through its `input` port while in a state. Messages without a matching transition are ignored,
+///   and the machine keeps the time remaining until its timed transition.
+///
+/// Entering a state (even the same state) restarts the timer of its timed transition.
+/// Messages received in the same simulation cycle are processed in order.
+/// As in every DEVS model, output messages can only be sent by timed transitions.
+/// To react to an input message with an output message, add an input-triggered transition
+/// to an intermediate state with a timed transition with no delay.
+pub struct FsmAtomic<S, I, O: Clone, T: Time = f64> {
+    /// Component of the atomic model.
+    component: Component<T>,
+    /// Initial state.
+    initial: S,
+    /// Current state.
+    state: S,
+    /// Time remaining until the timed transition of the current state.
+    sigma: T,
+    /// Timed transition of every state.
+    timeouts: HashMap<S, Timeout<S, O, T>>,
+    /// Input-triggered transitions of every state.
+    triggers: HashMap<S, Vec<(I, S)>>,
+    /// Input port for receiving events.
+    input: InPort<I>,
+    /// Output port for sending events.
+    output: OutPort<O>,
+}
+
+impl<S, I, O, T> FsmAtomic<S, I, O, T>
+where
+    S: DynRef + Clone + Eq + Hash + Debug,
+    I: DynRef + Clone + PartialEq,
+    O: DynRef + Clone,
+    T: Time,
+{
+    /// Creates a new finite state machine without transitions that starts in the `initial` state.
+    pub fn new(name: &str, initial: S) -> Self {
+        let mut component = Component::new(name);
+        let input = component.add_in_port::<I>("input");
+        let output = component.add_out_port::<O>("output");
+        Self {
+            component,
+            state: initial.clone(),
+            initial,
+            sigma: T::INFINITY,
+            timeouts: HashMap::new(),
+            triggers: HashMap::new(),
+            input,
+            output,
+        }
+    }
+
+    /// Adds a timed transition from state `from` to state `to` after spending `delay` in `from`.
+    /// If `output` is not [`None`], it is sent right before the transition.
+    /// It panics if `from` already has a timed transition or if `delay` is negative.
+    pub fn timed(mut self, from: S, delay: T, to: S, output: Option<O>) -> Self {
+        assert!(
+            delay >= T::ZERO,
+            "timed transition from state {from:?} has a negative delay"
+        );
+        assert!(
+            !self.timeouts.contains_key(&from),
+            "state {from:?} already has a timed transition"
+        );
+        let timeout = Timeout {
+            delay,
+            next: to,
+            output,
+        };
+        self.timeouts.insert(from, timeout);
+        self
+    }
+
+    /// Adds a transition from state `from` to state `to` when the machine receives `event` while in `from`.
+    /// If there are several transitions for the same state and event, the first one prevails.
+    pub fn on(mut self, from: S, event: I, to: S) -> Self {
+        self.triggers.entry(from).or_default().push((event, to));
+        self
+    }
+
+    /// Returns the current state of the machine.
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    /// Moves the machine to a new state and restarts the timer of its timed transition.
+    fn enter(&mut self, state: S) {
+        self.sigma = match self.timeouts.get(&state) {
+            Some(timeout) => timeout.delay,
+            None => T::INFINITY,
+        };
+        self.state = state;
+    }
+}
+
+impl<S, I, O, T> Atomic<T> for FsmAtomic<S, I, O, T>
+where
+    S: DynRef + Clone + Eq + Hash + Debug,
+    I: DynRef + Clone + PartialEq,
+    O: DynRef + Clone,
+    T: Time,
+{
+    fn get_component(&self) -> &Component<T> {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component<T> {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.enter(self.initial.clone());
+    }
+
+    fn lambda(&self) {
+        if let Some(output) = (self.timeouts.get(&self.state)).and_then(|t| t.output.as_ref()) {
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.output.add_value(output.clone()) };
+        }
+    }
+
+    fn delta_int(&mut self) {
+        let next = self.timeouts[&self.state].next.clone();
+        self.enter(next);
+    }
+
+    fn delta_ext(&mut self, e: T) {
+        self.sigma = self.sigma - e;
+        // Safety: reading messages on atomic model's input port at delta_ext
+        let events = unsafe { self.input.get_values() }.to_vec();
+        for event in events {
+            let next = (self.triggers.get(&self.state))
+                .and_then(|triggers| triggers.iter().find(|(e, _)| *e == event))
+                .map(|(_, next)| next.clone());
+            if let Some(next) = next {
+                self.enter(next);
+            }
+        }
+    }
+
+    fn ta(&self) -> T {
+        self.sigma
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::Coupled;
+    use crate::simulation::RootCoordinator;
+    use crate::tracing::{MemorySink, TracePhase, ValueFormatter};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Light {
+        Red,
+        Green,
+        Yellow,
+        Off,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Command {
+        TurnOff,
+        TurnOn,
+    }
+
+    fn traffic_light() -> FsmAtomic<Light, Command, &'static str> {
+        FsmAtomic::new("light", Light::Red)
+            .timed(Light::Red, 30., Light::Green, Some("green"))
+            .timed(Light::Green, 25., Light::Yellow, Some("yellow"))
+            .timed(Light::Yellow, 5., Light::Red, Some("red"))
+            .on(Light::Red, Command::TurnOff, Light::Off)
+            .on(Light::Green, Command::TurnOff, Light::Off)
+            .on(Light::Yellow, Command::TurnOff, Light::Off)
+            .on(Light::Off, Command::TurnOn, Light::Red)
+    }
+
+    /// Returns the time and value of the messages sent by the traffic light.
+    fn outputs(sink: &MemorySink) -> Vec<(f64, String)> {
+        (sink.records().into_iter())
+            .filter(|r| r.phase == TracePhase::Output && r.path == "root.light")
+            .map(|r| (r.t, r.values[0].clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_fsm_timed() {
+        let sink = MemorySink::new();
+        let mut coupled = Coupled::new("root");
+        coupled.add_component(Box::new(traffic_light()));
+        let mut simulator = RootCoordinator::new(coupled);
+        let formatter = ValueFormatter::new().debug::<&str>();
+        simulator.add_trace_sinks(vec![Box::new(sink.clone())], Some(formatter));
+        simulator.simulate(100.);
+        let expected = [
+            (30., "green"),
+            (55., "yellow"),
+            (60., "red"),
+            (90., "green"),
+        ];
+        let expected: Vec<_> = (expected.into_iter())
+            .map(|(t, v)| (t, format!("{v:?}")))
+            .collect();
+        assert_eq!(expected, outputs(&sink));
+    }
+
+    /// Atomic model that sends a sequence of commands at given times.
+    struct Commands {
+        component: Component,
+        commands: Vec<(f64, Command)>,
+        next: usize,
+        clock: f64,
+        output: OutPort<Command>,
+    }
+
+    impl Atomic for Commands {
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+
+        fn start(&mut self) {
+            (self.next, self.clock) = (0, 0.);
+        }
+
+        fn lambda(&self) {
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.output.add_value(self.commands[self.next].1.clone()) };
+        }
+
+        fn delta_int(&mut self) {
+            self.clock = self.commands[self.next].0;
+            self.next += 1;
+        }
+
+        fn delta_ext(&mut self, _e: f64) {}
+
+        fn ta(&self) -> f64 {
+            match self.commands.get(self.next) {
+                Some((t, _)) => t - self.clock,
+                None => f64::INFINITY,
+            }
+        }
+    }
+
+    #[test]
+    fn test_fsm_inputs() {
+        let mut component = Component::new("commands");
+        let output = component.add_out_port::<Command>("output");
+        let commands = Commands {
+            component,
+            // the first command is ignored, as the light is not off
+            commands: vec![
+                (10., Command::TurnOn),
+                (40., Command::TurnOff),
+                (50., Command::TurnOn),
+            ],
+            next: 0,
+            clock: 0.,
+            output,
+        };
+        let sink = MemorySink::new();
+        let mut coupled = Coupled::new("root");
+        coupled.add_component(Box::new(commands));
+        coupled.add_component(Box::new(traffic_light()));
+        coupled.add_ic("commands", "output", "light", "input");
+        let mut simulator = RootCoordinator::new(coupled);
+        let formatter = ValueFormatter::new().debug::<&str>();
+        simulator.add_trace_sinks(vec![Box::new(sink.clone())], Some(formatter));
+        simulator.simulate(100.);
+        let expected = [(30., "green"), (80., "green")];
+        let expected: Vec<_> = (expected.into_iter())
+            .map(|(t, v)| (t, format!("{v:?}")))
+            .collect();
+        assert_eq!(expected, outputs(&sink));
+
+        let light = simulator.find_as::<FsmAtomic<Light, Command, &str>>("root.light");
+        assert_eq!(Light::Green, *light.unwrap().state());
+    }
+}
@@ -0,0 +1,397 @@
+use super::Coupled;
+use crate::error::ModelingError;
+use crate::simulation::Simulator;
+use crate::DynRef;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Debug;
+
+/// Function that creates an atomic model from its name and its configuration.
+type AtomicConstructor = Box<dyn Fn(&str, &Value) -> Result<Box<dyn Simulator>, String>>;
+/// Function that adds a port with the given name to a coupled model.
+type PortConstructor = fn(&mut Coupled, &str) -> Result<(), ModelingError>;
+
+/// Errors that may occur when loading a coupled model from a JSON description.
+#[derive(Debug)]
+pub enum JsonError {
+    /// The model description is not valid.
+    Parse(serde_json::Error),
+    /// The model description refers to an atomic model type that is not registered in the factory.
+    UnknownAtomic(String),
+    /// The model description refers to a port type that is not registered in the factory.
+    UnknownPortType(String),
+    /// The factory could not create an atomic model. It contains the model name and the error message.
+    Atomic(String, String),
+    /// The model description is not a valid DEVS model (e.g., a coupling refers to a port that does not exist).
+    Modeling(ModelingError),
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "invalid model description: {e}"),
+            Self::UnknownAtomic(t) => write!(f, "unknown atomic model type {t}"),
+            Self::UnknownPortType(t) => write!(f, "unknown port type {t}"),
+            Self::Atomic(name, e) => write!(f, "could not create atomic model {name}: {e}"),
+            Self::Modeling(e) => write!(f, "invalid model: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+impl From<serde_json::Error> for JsonError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Parse(e)
+    }
+}
+
+impl From<ModelingError> for JsonError {
+    fn from(e: ModelingError) -> Self {
+        Self::Modeling(e)
+    }
+}
+
+/// Registry of atomic model types and port types used to build models from JSON descriptions.
+/// By default, it contains the port types `bool`, `i32`, `i64`, `isize`, `u32`, `u64`, `usize`,
+/// `f32`, `f64`, and `String`.
+pub struct ModelFactory {
+    /// Atomic model constructors. Keys are the atomic model type names.
+    atomics: HashMap<String, AtomicConstructor>,
+    /// Input and output port constructors. Keys are the port type names.
+    ports: HashMap<String, (PortConstructor, PortConstructor)>,
+}
+
+impl ModelFactory {
+    /// Creates a new factory with the default port types.
+    pub fn new() -> Self {
+        let mut factory = Self {
+            atomics: HashMap::new(),
+            ports: HashMap::new(),
+        };
+        factory.register_port_type::<bool>("bool");
+        factory.register_port_type::<i32>("i32");
+        factory.register_port_type::<i64>("i64");
+        factory.register_port_type::<isize>("isize");
+        factory.register_port_type::<u32>("u32");
+        factory.register_port_type::<u64>("u64");
+        factory.register_port_type::<usize>("usize");
+        factory.register_port_type::<f32>("f32");
+        factory.register_port_type::<f64>("f64");
+        factory.register_port_type::<String>("String");
+        factory
+    }
+
+    /// Registers a new atomic model type. The constructor receives the name of the new model
+    /// and its configuration (i.e., the `config` field of the description, or `null` if missing).
+    pub fn register_atomic<F>(&mut self, type_name: &str, constructor: F)
+    where
+        F: Fn(&str, &Value) -> Result<Box<dyn Simulator>, String> + 'static,
+    {
+        self.atomics
+            .insert(type_name.to_string(), Box::new(constructor));
+    }
+
+    /// Registers a new port type for the ports of coupled models.
+    pub fn register_port_type<T: DynRef + Clone>(&mut self, type_name: &str) {
+        fn add_in<T: DynRef + Clone>(
+            coupled: &mut Coupled,
+            name: &str,
+        ) -> Result<(), ModelingError> {
+            coupled.try_add_in_port::<T>(name).map(|_| ())
+        }
+        fn add_out<T: DynRef + Clone>(
+            coupled: &mut Coupled,
+            name: &str,
+        ) -> Result<(), ModelingError> {
+            coupled.try_add_out_port::<T>(name).map(|_| ())
+        }
+        self.ports
+            .insert(type_name.to_string(), (add_in::<T>, add_out::<T>));
+    }
+
+    /// Creates an atomic model.
+    fn build_atomic(
+        &self,
+        name: &str,
+        type_name: &str,
+        config: &Value,
+    ) -> Result<Box<dyn Simulator>, JsonError> {
+        let constructor = self
+            .atomics
+            .get(type_name)
+            .ok_or_else(|| JsonError::UnknownAtomic(type_name.to_string()))?;
+        constructor(name, config).map_err(|e| JsonError::Atomic(name.to_string(), e))
+    }
+
+    /// Creates a coupled model and all its subcomponents.
+    fn build_coupled(&self, description: &CoupledDescription) -> Result<Coupled, JsonError> {
+        let mut coupled = Coupled::new(&description.name);
+        for port in &description.in_ports {
+            let (add_in, _) = self.port_constructors(&port.port_type)?;
+            add_in(&mut coupled, &port.name)?;
+        }
+        for port in &description.out_ports {
+            let (_, add_out) = self.port_constructors(&port.port_type)?;
+            add_out(&mut coupled, &port.name)?;
+        }
+        for component in &description.components {
+            let component = match component {
+                ComponentDescription::Atomic {
+                    name,
+                    atomic_type,
+                    config,
+                } => self.build_atomic(name, atomic_type, config)?,
+                ComponentDescription::Coupled(description) => {
+                    Box::new(self.build_coupled(description)?)
+                }
+            };
+            coupled.try_add_boxed_component(component)?;
+        }
+        for c in &description.eic {
+            coupled.try_add_eic(&c.port_from, &c.component_to, &c.port_to)?;
+        }
+        for c in &description.ic {
+            coupled.try_add_ic(&c.component_from, &c.port_from, &c.component_to, &c.port_to)?;
+        }
+        for c in &description.eoc {
+            coupled.try_add_eoc(&c.component_from, &c.port_from, &c.port_to)?;
+        }
+        Ok(coupled)
+    }
+
+    /// Returns the input and output port constructors of a given port type.
+    fn port_constructors(
+        &self,
+        type_name: &str,
+    ) -> Result<(PortConstructor, PortConstructor), JsonError> {
+        self.ports
+            .get(type_name)
+            .copied()
+            .ok_or_else(|| JsonError::UnknownPortType(type_name.to_string()))
+    }
+}
+
+impl Default for ModelFactory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Description of a port of a coupled model.
+#[derive(Deserialize)]
+struct PortDescription {
+    name: String,
+    #[serde(rename = "type")]
+    port_type: String,
+}
+
+/// Description of a component of a coupled model.
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum ComponentDescription {
+    Atomic {
+        name: String,
+        #[serde(rename = "type")]
+        atomic_type: String,
+        #[serde(default)]
+        config: Value,
+    },
+    Coupled(CoupledDescription),
+}
+
+/// Description of an external input coupling.
+#[derive(Deserialize)]
+struct EicDescription {
+    port_from: String,
+    component_to: String,
+    port_to: String,
+}
+
+/// Description of an internal coupling.
+#[derive(Deserialize)]
+struct IcDescription {
+    component_from: String,
+    port_from: String,
+    component_to: String,
+    port_to: String,
+}
+
+/// Description of an external output coupling.
+#[derive(Deserialize)]
+struct EocDescription {
+    component_from: String,
+    port_from: String,
+    port_to: String,
+}
+
+/// Description of a coupled model.
+#[derive(Deserialize)]
+struct CoupledDescription {
+    name: String,
+    #[serde(default)]
+    in_ports: Vec<PortDescription>,
+    #[serde(default)]
+    out_ports: Vec<PortDescription>,
+    #[serde(default)]
+    components: Vec<ComponentDescription>,
+    #[serde(default)]
+    eic: Vec<EicDescription>,
+    #[serde(default)]
+    ic: Vec<IcDescription>,
+    #[serde(default)]
+    eoc: Vec<EocDescription>,
+}
+
+impl Coupled {
+    /// Creates a coupled model from a JSON description. Atomic models and port types
+    /// are created by the provided factory. The description has the following format:
+    ///
+    /// ```json
+    /// {
+    ///     "name": "coupled",
+    ///     "in_ports": [{"name": "input", "type": "usize"}],
+    ///     "out_ports": [{"name": "output", "type": "usize"}],
+    ///     "components": [
+    ///         {"kind": "atomic", "name": "a", "type": "MyAtomic", "config": {"period": 1.0}},
+    ///         {"kind": "coupled", "name": "b", "components": [], "eic": [], "ic": [], "eoc": []}
+    ///     ],
+    ///     "eic": [{"port_from": "input", "component_to": "a", "port_to": "input"}],
+    ///     "ic": [{"component_from": "a", "port_from": "output", "component_to": "b", "port_to": "input"}],
+    ///     "eoc": [{"component_from": "a", "port_from": "output", "port_to": "output"}]
+    /// }
+    /// ```
+    ///
+    /// All the fields but `name` are optional for coupled models.
+    /// If any component or coupling is not valid, it returns a [`JsonError::Modeling`] error.
+    pub fn from_json(json: &str, factory: &ModelFactory) -> Result<Self, JsonError> {
+        let description: CoupledDescription = serde_json::from_str(json)?;
+        factory.build_coupled(&description)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::{Atomic, Component, InPort, OutPort};
+    use crate::simulation::RootCoordinator;
+    use std::sync::{Arc, Mutex};
+
+    struct Counter {
+        component: Component,
+        input: InPort<usize>,
+        output: OutPort<usize>,
+        count: Arc<Mutex<usize>>,
+        sigma: f64,
+    }
+
+    impl Counter {
+        fn new(name: &str, sigma: f64, count: Arc<Mutex<usize>>) -> Self {
+            let mut component = Component::new(name);
+            let input = component.add_in_port("input");
+            let output = component.add_out_port("output");
+            Self {
+                component,
+                input,
+                output,
+                count,
+                sigma,
+            }
+        }
+    }
+
+    impl Atomic for Counter {
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+
+        fn lambda(&self) {
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.output.add_value(1) };
+        }
+
+        fn delta_int(&mut self) {
+            self.sigma = f64::INFINITY;
+        }
+
+        fn delta_ext(&mut self, _e: f64) {
+            // Safety: reading messages on atomic model's input port at delta_ext
+            *self.count.lock().unwrap() += unsafe { self.input.get_values() }.len();
+            self.sigma = 0.;
+        }
+
+        fn ta(&self) -> f64 {
+            self.sigma
+        }
+    }
+
+    const MODEL: &str = r#"{
+        "name": "root",
+        "components": [
+            {"kind": "atomic", "name": "seeder", "type": "Counter", "config": {"sigma": 0.0}},
+            {
+                "kind": "coupled",
+                "name": "inner",
+                "in_ports": [{"name": "input", "type": "usize"}],
+                "components": [
+                    {"kind": "atomic", "name": "a", "type": "Counter"},
+                    {"kind": "atomic", "name": "b", "type": "Counter"}
+                ],
+                "eic": [{"port_from": "input", "component_to": "a", "port_to": "input"}],
+                "ic": [{"component_from": "a", "port_from": "output", "component_to": "b", "port_to": "input"}]
+            }
+        ],
+        "ic": [{"component_from": "seeder", "port_from": "output", "component_to": "inner", "port_to": "input"}]
+    }"#;
+
+    fn factory(count: Arc<Mutex<usize>>) -> ModelFactory {
+        let mut factory = ModelFactory::new();
+        factory.register_atomic("Counter", move |name, config| {
+            let sigma = match config.get("sigma") {
+                Some(sigma) => sigma.as_f64().ok_or("sigma must be a number")?,
+                None => f64::INFINITY,
+            };
+            Ok(Box::new(Counter::new(name, sigma, count.clone())))
+        });
+        factory
+    }
+
+    #[test]
+    fn test_from_json() {
+        let count = Arc::new(Mutex::new(0));
+        let coupled = Coupled::from_json(MODEL, &factory(count.clone())).unwrap();
+        assert_eq!(2, coupled.n_components());
+        assert_eq!(1, coupled.n_ics());
+
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.simulate(f64::INFINITY);
+        assert_eq!(2, *count.lock().unwrap());
+    }
+
+    #[test]
+    fn test_from_json_errors() {
+        let factory = factory(Arc::new(Mutex::new(0)));
+        let result = Coupled::from_json("{", &factory);
+        assert!(matches!(result, Err(JsonError::Parse(_))));
+        let json =
+            r#"{"name": "root", "components": [{"kind": "atomic", "name": "a", "type": "Foo"}]}"#;
+        let result = Coupled::from_json(json, &factory);
+        assert!(matches!(result, Err(JsonError::UnknownAtomic(t)) if t == "Foo"));
+        let json = r#"{"name": "root", "in_ports": [{"name": "input", "type": "Foo"}]}"#;
+        let result = Coupled::from_json(json, &factory);
+        assert!(matches!(result, Err(JsonError::UnknownPortType(t)) if t == "Foo"));
+        let json = r#"{"name": "root", "components": [{"kind": "atomic", "name": "a", "type": "Counter", "config": {"sigma": "x"}}]}"#;
+        let result = Coupled::from_json(json, &factory);
+        assert!(matches!(result, Err(JsonError::Atomic(name, _)) if name == "a"));
+        let json = r#"{"name": "root", "eic": [{"port_from": "input", "component_to": "a", "port_to": "input"}]}"#;
+        let result = Coupled::from_json(json, &factory);
+        let expected = ModelingError::UnknownComponent("a".to_string());
+        assert!(matches!(result, Err(JsonError::Modeling(e)) if e == expected));
+    }
+}
@@ -0,0 +1,220 @@
+use super::port::Bag;
+use crate::time::Time;
+use crate::{DynRef, Shared};
+use std::fmt::{self, Debug};
+use std::ops::Deref;
+
+/// Message payload shared by all its receivers. When a message of an output port of type `SharedMsg<T>`
+/// is propagated through several couplings, receivers get a new reference to the same payload instead of a deep copy.
+/// Thus, models can exchange large values (e.g., images or matrices) without cloning them in every coupling.
+/// Use [`super::OutPort::add_value_shared`] to emit values. Receivers can only read shared payloads,
+/// so they must clone them (e.g., with [`Shared::unwrap_or_clone`]) to modify them.
+pub type SharedMsg<T> = Shared<T>;
+
+/// Message envelope that records when and by whom a value was emitted.
+/// Output ports created with [`super::Component::add_msg_out_port`] carry envelopes, and their metadata
+/// is populated automatically by the simulator right after the output function of the emitting model.
+/// As envelopes are propagated unchanged, receivers always see the atomic model that produced the value,
+/// regardless of the depth of the model hierarchy. Envelopes forwarded from an input port keep their metadata.
+/// `T` is the simulation time type (by default, [`f64`]).
+#[derive(Clone, PartialEq)]
+pub struct Msg<M, T: Time = f64> {
+    /// Carried value.
+    value: M,
+    /// Simulation time when the value was emitted.
+    time: T,
+    /// Path of the component that emitted the value (e.g., `"root.subcoupled.atomic"`).
+    source: Shared<str>,
+}
+
+impl<M, T: Time> Msg<M, T> {
+    /// Creates a new envelope for a value. Its metadata is populated when the value is emitted.
+    #[inline]
+    pub fn new(value: M) -> Self {
+        Self {
+            value,
+            time: T::INFINITY,
+            source: Shared::from(""),
+        }
+    }
+
+    /// Returns a reference to the carried value.
+    #[inline]
+    pub fn value(&self) -> &M {
+        &self.value
+    }
+
+    /// Consumes the envelope and returns the carried value.
+    #[inline]
+    pub fn into_value(self) -> M {
+        self.value
+    }
+
+    /// Returns the simulation time when the value was emitted.
+    /// It is infinity if the value has not been emitted yet.
+    #[inline]
+    pub fn time(&self) -> T {
+        self.time
+    }
+
+    /// Returns the path of the component that emitted the value.
+    /// It is empty if the value has not been emitted yet.
+    #[inline]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+impl<M, T: Time> From<M> for Msg<M, T> {
+    #[inline]
+    fn from(value: M) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<M, T: Time> Deref for Msg<M, T> {
+    type Target = M;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<M: Debug, T: Time> Debug for Msg<M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} (from {} at {:?})",
+            self.value, self.source, self.time
+        )
+    }
+}
+
+/// Trait implemented by the bags of output ports that carry [`Msg`] envelopes.
+pub(crate) trait Stamp<T: Time>: DynRef {
+    /// Populates the metadata of the envelopes in the bag that have not been emitted before.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed by a simulator right after the output function of its model.
+    unsafe fn stamp(&self, t: T, source: &Shared<str>);
+}
+
+impl<M: DynRef, T: Time> Stamp<T> for Bag<Msg<M, T>> {
+    unsafe fn stamp(&self, t: T, source: &Shared<str>) {
+        for msg in (*self.get()).iter_mut().filter(|msg| msg.source.is_empty()) {
+            msg.time = t;
+            msg.source = source.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::{Atomic, Component, Coupled, InPort, OutPort};
+    use crate::simulation::RootCoordinator;
+    use std::sync::Mutex;
+
+    /// Atomic model that emits a value at time 1.
+    struct Emitter {
+        component: Component,
+        output: OutPort<Msg<i32>>,
+        sigma: f64,
+    }
+
+    impl Atomic for Emitter {
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+
+        fn lambda(&self) {
+            // Safety: adding messages on atomic model's output port at lambda
+            unsafe { self.output.add_value(Msg::new(7)) };
+        }
+
+        fn delta_int(&mut self) {
+            self.sigma = f64::INFINITY;
+        }
+
+        fn delta_ext(&mut self, _e: f64) {}
+
+        fn ta(&self) -> f64 {
+            self.sigma
+        }
+    }
+
+    /// Atomic model that stores the received envelopes.
+    struct Receiver {
+        component: Component,
+        input: InPort<Msg<i32>>,
+        received: Shared<Mutex<Vec<Msg<i32>>>>,
+    }
+
+    impl Atomic for Receiver {
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+
+        fn lambda(&self) {}
+
+        fn delta_int(&mut self) {}
+
+        fn delta_ext(&mut self, _e: f64) {
+            // Safety: reading messages on atomic model's input port at delta_ext
+            let values = unsafe { self.input.get_values() };
+            self.received.lock().unwrap().extend_from_slice(values);
+        }
+
+        fn ta(&self) -> f64 {
+            f64::INFINITY
+        }
+    }
+
+    #[test]
+    fn test_msg() {
+        let mut component = Component::new("emitter");
+        let output = component.add_msg_out_port("output");
+        let mut sub = Coupled::new("sub");
+        sub.add_out_port::<Msg<i32>>("output");
+        sub.add_component(Box::new(Emitter {
+            component,
+            output,
+            sigma: 1.,
+        }));
+        sub.add_eoc("emitter", "output", "output");
+
+        let mut component = Component::new("receiver");
+        let input = component.add_in_port("input");
+        let received = Shared::new(Mutex::new(Vec::new()));
+        let mut root = Coupled::new("root");
+        root.add_component(Box::new(sub));
+        root.add_component(Box::new(Receiver {
+            component,
+            input,
+            received: received.clone(),
+        }));
+        root.add_ic("sub", "output", "receiver", "input");
+
+        let mut simulator = RootCoordinator::new(root);
+        simulator.simulate(f64::INFINITY);
+        let received = received.lock().unwrap();
+        assert_eq!(1, received.len());
+        assert_eq!(7, *received[0].value());
+        assert_eq!(1., received[0].time());
+        assert_eq!("root.sub.emitter", received[0].source());
+        assert_eq!(
+            "7 (from root.sub.emitter at 1.0)",
+            format!("{:?}", received[0])
+        );
+    }
+}
@@ -1,14 +1,62 @@
-use crate::DynRef;
+mod phase;
+#[cfg(feature = "small_bags")]
+mod small_vec;
+
+use super::msg::SharedMsg;
+use crate::{DynRef, Shared};
 use std::any::Any;
 use std::cell::UnsafeCell;
+use std::fmt::Debug;
+use std::marker::PhantomData;
 use std::ops::Deref;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+pub(crate) use phase::AtomicPhase;
+
+/// Storage of the messages of a port of type `T`.
+#[cfg(not(feature = "small_bags"))]
+pub(crate) type Values<T> = Vec<T>;
+
+/// Storage of the messages of a port of type `T`. Up to four messages are stored inline.
+#[cfg(feature = "small_bags")]
+pub(crate) type Values<T> = small_vec::SmallVec<T, 4>;
 
 /// Trait implemented by DEVS ports. It does not consider message types nor port directions.
 pub(crate) trait Port: DynRef {
     /// Port-to-any conversion.
     fn as_any(&self) -> &dyn Any;
 
+    /// Returns the name of the port.
+    fn get_name(&self) -> &str;
+
+    /// Returns a reference to the messages in the port as an [`Any`] trait object.
+    /// Implementers can downcast it to [`Values<T>`], where `T` is the message type of the port.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed by a [`crate::simulation::Listener`] notifier
+    /// between simulation phases, when no model is manipulating the port.
+    unsafe fn get_values(&self) -> &dyn Any;
+
+    /// Injects messages into the port. `values` must be a reference to a `Vec<T>`,
+    /// where `T` is the message type of the port. It returns `false` if `values` is not compatible.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed by a coordinator between simulation phases,
+    /// when no model is manipulating the port.
+    unsafe fn inject(&self, values: &dyn Any) -> bool;
+
+    /// Returns a copy of the messages in the port as a boxed `Vec<T>`,
+    /// where `T` is the message type of the port.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed by a coordinator between simulation phases,
+    /// when no model is manipulating the port.
+    unsafe fn clone_values(&self) -> Box<dyn Any>;
+
     /// Returns `true` if the port does not contain any value.
     ///
     /// # Safety
@@ -17,6 +65,14 @@ pub(crate) trait Port: DynRef {
     /// [`super::Component`] when checking if the port is empty.
     unsafe fn is_empty(&self) -> bool;
 
+    /// Returns the number of values in the port.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed by a [`crate::simulation::Listener`] notifier
+    /// between simulation phases, when no model is manipulating the port.
+    unsafe fn len(&self) -> usize;
+
     /// It clears all the values in the port.
     ///
     /// # Safety
@@ -24,6 +80,15 @@ pub(crate) trait Port: DynRef {
     /// This method must only be executed by the [`super::Component`] when clearing its ports.
     unsafe fn clear(&self);
 
+    /// Reserves capacity for at least `reserve` values and sets the maximum capacity that
+    /// the port keeps when it is cleared. Ports with more capacity release the excess memory.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed by a coordinator before the simulation starts,
+    /// when no model is manipulating the port.
+    unsafe fn set_capacity(&self, reserve: usize, max_capacity: usize);
+
     /// Returns `true` if other port is compatible.
     fn is_compatible(&self, other: &dyn Port) -> bool;
 
@@ -34,17 +99,116 @@ pub(crate) trait Port: DynRef {
     /// This method can only be executed by a [`super::Coupled`] model when propagating
     /// messages in its [`crate::simulation::Simulator`] trait implementation.
     unsafe fn propagate(&self, port_to: &dyn Port);
+
+    /// Moves messages from the port to other receiving port, leaving the port empty.
+    /// Couplings with adapters map the messages, so they are not moved but propagated as usual.
+    ///
+    /// # Safety
+    ///
+    /// In addition to the invariants of [`Port::propagate`], the receiving port must be the only
+    /// destination of the port, and nobody can read the port until it is cleared.
+    unsafe fn propagate_move(&self, port_to: &dyn Port);
+
+    /// Creates an empty bag that can receive the messages propagated from the port.
+    /// Coupled models use these bags to buffer messages when propagating couplings in parallel.
+    #[cfg(feature = "par_buffers")]
+    fn new_buffer(&self) -> Shared<dyn Port>;
+
+    /// Returns the port that finally receives the messages injected into an adapter.
+    /// By default, it returns [`None`], as regular ports are not adapters.
+    #[inline]
+    fn target(&self) -> Option<&Shared<dyn Port>> {
+        None
+    }
+
+    /// Returns a copy of an adapter that injects the mapped messages into another port.
+    /// By default, it returns [`None`], as regular ports are not adapters.
+    #[inline]
+    fn retarget(&self, _target: Shared<dyn Port>) -> Option<Shared<dyn Port>> {
+        None
+    }
+}
+
+/// Indices of the ports of a component that received messages since they were last tracked.
+/// Ports of the same component may receive messages from different threads, hence the mutex.
+#[derive(Debug, Default)]
+pub(super) struct PortTracker(Mutex<Vec<usize>>);
+
+impl PortTracker {
+    /// Marks the port with the given index as dirty.
+    #[inline]
+    fn mark(&self, index: usize) {
+        self.0.lock().unwrap().push(index);
+    }
+
+    /// Moves the indices of the dirty ports to `dirty` and resets the tracker.
+    /// Indices may be repeated, and their ports may be empty again.
+    #[inline]
+    pub(super) fn take(&self, dirty: &mut Vec<usize>) {
+        dirty.append(&mut self.0.lock().unwrap());
+    }
+
+    /// Resets the tracker.
+    #[inline]
+    pub(super) fn reset(&self) {
+        self.0.lock().unwrap().clear();
+    }
 }
 
 /// Bag of DEVS messages. Each port has its own bag.
 #[derive(Debug)]
-pub(super) struct Bag<T>(UnsafeCell<Vec<T>>);
+pub(super) struct Bag<T> {
+    /// Name of the port that owns the bag.
+    name: String,
+    /// Messages in the bag.
+    values: UnsafeCell<Values<T>>,
+    /// Maximum capacity that the bag keeps when it is cleared.
+    max_capacity: AtomicUsize,
+    /// Tracker of the component that owns the bag and index of the bag in it.
+    /// Bags that do not belong to any component (e.g., buffers) are not tracked.
+    tracker: Option<(Shared<PortTracker>, usize)>,
+}
 
 impl<T> Bag<T> {
-    /// Creates a new message bag wrapped in an [`Arc`].
+    /// Creates a new message bag for the port with the given name wrapped in an [`Shared`].
+    /// If `tracker` is provided, the bag marks its index as dirty in it when it receives messages.
     #[inline]
-    pub(super) fn new() -> Arc<Self> {
-        Arc::new(Self(UnsafeCell::new(Vec::new())))
+    pub(super) fn new(name: &str, tracker: Option<(Shared<PortTracker>, usize)>) -> Shared<Self> {
+        Shared::new(Self {
+            name: name.to_string(),
+            values: UnsafeCell::new(Values::new()),
+            max_capacity: AtomicUsize::new(usize::MAX),
+            tracker,
+        })
+    }
+
+    /// Converts a port into the bag of messages of type `T` that it points to.
+    /// It returns [`None`] if the port is not a bag of messages of type `T` (e.g., it is an adapter).
+    pub(super) fn downcast(port: Shared<dyn Port>) -> Option<Shared<Self>>
+    where
+        T: DynRef + Clone,
+    {
+        port.as_any().is::<Self>().then(|| {
+            // Safety: the port points to a bag of type `T`, so the allocation was created as an `Shared<Self>`
+            unsafe { Shared::from_raw(Shared::into_raw(port) as *const Self) }
+        })
+    }
+
+    /// Propagates messages from the bag to other bag of the same type without dynamic dispatch.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed by a coupled model when propagating messages,
+    /// as in [`Port::propagate`].
+    #[inline]
+    pub(super) unsafe fn propagate_to(&self, bag_to: &Self)
+    where
+        T: Clone,
+    {
+        let values = self.borrow();
+        if !values.is_empty() {
+            bag_to.borrow_write().extend_from_slice(values);
+        }
     }
 
     /// Returns a reference to the vector of messages in the bag.
@@ -55,7 +219,7 @@ impl<T> Bag<T> {
     /// - The caller is an [`InPort`] struct and fulfills the aditional invariants.
     /// - The caller executed the [`Port::propagate`] method and fulfills the additional invariants.
     #[inline]
-    unsafe fn borrow(&self) -> &Vec<T> {
+    unsafe fn borrow(&self) -> &Values<T> {
         &*self.get()
     }
 
@@ -68,15 +232,32 @@ impl<T> Bag<T> {
     /// - The caller executed the [`Port::propagate`] method and fulfills the additional invariants.
     #[allow(clippy::mut_from_ref)]
     #[inline]
-    unsafe fn borrow_mut(&self) -> &mut Vec<T> {
+    unsafe fn borrow_mut(&self) -> &mut Values<T> {
         &mut *self.get()
     }
+
+    /// Returns a mutable reference to the vector of messages in the bag for adding new messages.
+    /// If the bag is empty, its port is marked as dirty in the tracker of its component.
+    ///
+    /// # Safety:
+    ///
+    /// The caller must fulfill the invariants of [`Bag::borrow_mut`].
+    #[allow(clippy::mut_from_ref)]
+    #[inline]
+    unsafe fn borrow_write(&self) -> &mut Values<T> {
+        if let Some((tracker, index)) = &self.tracker {
+            if self.borrow().is_empty() {
+                tracker.mark(*index);
+            }
+        }
+        self.borrow_mut()
+    }
 }
 
 impl<T> Deref for Bag<T> {
-    type Target = UnsafeCell<Vec<T>>;
+    type Target = UnsafeCell<Values<T>>;
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.values
     }
 }
 
@@ -94,14 +275,61 @@ impl<T: DynRef + Clone> Port for Bag<T> {
         self
     }
 
+    #[inline]
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    unsafe fn get_values(&self) -> &dyn Any {
+        self.borrow()
+    }
+
+    #[inline]
+    unsafe fn inject(&self, values: &dyn Any) -> bool {
+        match values.downcast_ref::<Vec<T>>() {
+            Some(values) => {
+                self.borrow_write().extend_from_slice(values);
+                true
+            }
+            None => false,
+        }
+    }
+
+    #[inline]
+    unsafe fn clone_values(&self) -> Box<dyn Any> {
+        Box::new(self.borrow().to_vec())
+    }
+
     #[inline]
     unsafe fn is_empty(&self) -> bool {
         self.borrow().is_empty()
     }
 
+    #[inline]
+    unsafe fn len(&self) -> usize {
+        self.borrow().len()
+    }
+
     #[inline]
     unsafe fn clear(&self) {
-        self.borrow_mut().clear();
+        let values = self.borrow_mut();
+        values.clear();
+        // cleared bags keep their memory for the next cycles unless they grew too much
+        let max_capacity = self.max_capacity.load(Ordering::Relaxed);
+        if values.capacity() > max_capacity {
+            values.shrink_to(max_capacity);
+        }
+    }
+
+    #[inline]
+    unsafe fn set_capacity(&self, reserve: usize, max_capacity: usize) {
+        self.max_capacity.store(max_capacity, Ordering::Relaxed);
+        let values = self.borrow_mut();
+        values.reserve(reserve.saturating_sub(values.len()));
+        if values.capacity() > max_capacity {
+            values.shrink_to(max_capacity.max(values.len()));
+        }
     }
 
     #[inline]
@@ -111,15 +339,168 @@ impl<T: DynRef + Clone> Port for Bag<T> {
 
     #[inline]
     unsafe fn propagate(&self, port_to: &dyn Port) {
-        let port_to = port_to.as_any().downcast_ref::<Bag<T>>().unwrap();
-        port_to.borrow_mut().extend_from_slice(self.borrow());
+        match port_to.as_any().downcast_ref::<Bag<T>>() {
+            Some(port_to) => port_to.borrow_write().extend_from_slice(self.borrow()),
+            // couplings with adapters map the messages before injecting them into the receiving port
+            None => assert!(port_to.inject(self.borrow())),
+        }
+    }
+
+    #[inline]
+    unsafe fn propagate_move(&self, port_to: &dyn Port) {
+        match port_to.as_any().downcast_ref::<Bag<T>>() {
+            Some(port_to) => port_to.borrow_write().append(self.borrow_mut()),
+            None => self.propagate(port_to),
+        }
+    }
+
+    #[cfg(feature = "par_buffers")]
+    #[inline]
+    fn new_buffer(&self) -> Shared<dyn Port> {
+        Self::new(&self.name, None)
+    }
+}
+
+/// Receiving end of a coupling between ports of different message types.
+/// It maps messages of type `A` to messages of type `B` with a function of type `F`,
+/// and injects them into its target port (a bag of type `B` or another adapter).
+pub(super) struct Adapter<A, B, F> {
+    /// Function that maps the propagated messages.
+    map: Shared<F>,
+    /// Port that receives the mapped messages.
+    target: Shared<dyn Port>,
+    /// Message types of the adapter.
+    types: PhantomData<fn(&A) -> B>,
+}
+
+impl<A, B, F> Adapter<A, B, F>
+where
+    A: DynRef + Clone,
+    B: DynRef + Clone,
+    F: Fn(&A) -> B + DynRef,
+{
+    /// Creates a new adapter between two ports wrapped in an [`Shared`].
+    /// It returns [`None`] if the message types of the ports are not `A` and `B`, respectively.
+    #[inline]
+    pub(super) fn new(source: &dyn Port, target: Shared<dyn Port>, map: F) -> Option<Shared<Self>> {
+        let compatible = source.as_any().is::<Bag<A>>() && target.as_any().is::<Bag<B>>();
+        compatible.then(|| {
+            Shared::new(Self {
+                map: Shared::new(map),
+                target,
+                types: PhantomData,
+            })
+        })
+    }
+}
+
+impl<A, B, F> Port for Adapter<A, B, F>
+where
+    A: DynRef + Clone,
+    B: DynRef + Clone,
+    F: Fn(&A) -> B + DynRef,
+{
+    #[inline]
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    #[inline]
+    fn get_name(&self) -> &str {
+        self.target.get_name()
+    }
+
+    #[inline]
+    unsafe fn get_values(&self) -> &dyn Any {
+        self.target.get_values()
+    }
+
+    /// Maps and injects messages into the target port. `values` must be a reference to a [`Values<A>`].
+    #[inline]
+    unsafe fn inject(&self, values: &dyn Any) -> bool {
+        let Some(values) = values.downcast_ref::<Values<A>>() else {
+            return false;
+        };
+        match self.target.as_any().downcast_ref::<Bag<B>>() {
+            Some(target) => {
+                let target = target.borrow_write();
+                values.iter().for_each(|v| target.push((self.map)(v)));
+                true
+            }
+            // chains of adapters are only built when flattening models
+            None => {
+                let mut mapped = Values::new();
+                values.iter().for_each(|v| mapped.push((self.map)(v)));
+                self.target.inject(&mapped)
+            }
+        }
+    }
+
+    #[inline]
+    unsafe fn clone_values(&self) -> Box<dyn Any> {
+        self.target.clone_values()
+    }
+
+    #[inline]
+    unsafe fn is_empty(&self) -> bool {
+        self.target.is_empty()
+    }
+
+    #[inline]
+    unsafe fn len(&self) -> usize {
+        self.target.len()
+    }
+
+    #[inline]
+    unsafe fn clear(&self) {
+        self.target.clear();
+    }
+
+    #[inline]
+    unsafe fn set_capacity(&self, reserve: usize, max_capacity: usize) {
+        self.target.set_capacity(reserve, max_capacity);
+    }
+
+    #[inline]
+    fn is_compatible(&self, other: &dyn Port) -> bool {
+        other.as_any().downcast_ref::<Bag<A>>().is_some()
+    }
+
+    #[inline]
+    unsafe fn propagate(&self, port_to: &dyn Port) {
+        self.target.propagate(port_to);
+    }
+
+    #[inline]
+    unsafe fn propagate_move(&self, port_to: &dyn Port) {
+        self.target.propagate_move(port_to);
+    }
+
+    #[cfg(feature = "par_buffers")]
+    #[inline]
+    fn new_buffer(&self) -> Shared<dyn Port> {
+        self.target.new_buffer()
+    }
+
+    #[inline]
+    fn target(&self) -> Option<&Shared<dyn Port>> {
+        Some(&self.target)
+    }
+
+    #[inline]
+    fn retarget(&self, target: Shared<dyn Port>) -> Option<Shared<dyn Port>> {
+        Some(Shared::new(Self {
+            map: self.map.clone(),
+            target,
+            types: PhantomData,
+        }))
     }
 }
 
 /// Input port. This structure only allows reading messages. Thus, it cannot inject messages.
 /// Note that we do not implement the [`Clone`] trait in purpose, as we want to avoid their misuse.
 #[derive(Debug)]
-pub struct InPort<T>(pub(super) Arc<Bag<T>>);
+pub struct InPort<T>(pub(super) Shared<Bag<T>>);
 
 impl<T: Clone> InPort<T> {
     /// Returns `true` if the underlying bag is empty. Otherwise, it returns `false`.
@@ -130,6 +511,8 @@ impl<T: Clone> InPort<T> {
     /// Furthermore, this port must be one of the input ports of the implementer.
     #[inline]
     pub unsafe fn is_empty(&self) -> bool {
+        AtomicPhase::Output
+            .forbid("input ports cannot be read in the output function of atomic models");
         self.0.borrow().is_empty()
     }
 
@@ -141,14 +524,16 @@ impl<T: Clone> InPort<T> {
     /// Furthermore, this port must be one of the input ports of the implementer.
     #[inline]
     pub unsafe fn get_values(&self) -> &[T] {
-        self.0.borrow()
+        AtomicPhase::Output
+            .forbid("input ports cannot be read in the output function of atomic models");
+        self.0.borrow().as_slice()
     }
 }
 
 /// Output port. This structure only injecting messages. Thus, it cannot read messages.
 /// Note that we do not implement the [`Clone`] trait in purpose, as we want to avoid their misuse.
 #[derive(Debug)]
-pub struct OutPort<T: Clone>(pub(super) Arc<Bag<T>>);
+pub struct OutPort<T: Clone>(pub(super) Shared<Bag<T>>);
 
 impl<T: Clone> OutPort<T> {
     /// Adds a new value to the output port.
@@ -159,7 +544,10 @@ impl<T: Clone> OutPort<T> {
     /// Furthermore, this port must be one of the output ports of the implementer.
     #[inline]
     pub unsafe fn add_value(&self, value: T) {
-        self.0.borrow_mut().push(value);
+        AtomicPhase::Transition.forbid(
+            "output ports cannot receive messages in the transition functions of atomic models",
+        );
+        self.0.borrow_write().push(value);
     }
 
     /// Adds new values from a slice to the output port.
@@ -170,6 +558,105 @@ impl<T: Clone> OutPort<T> {
     /// Furthermore, this port must be one of the output ports of the implementer.
     #[inline]
     pub unsafe fn add_values(&self, values: &[T]) {
-        self.0.borrow_mut().extend_from_slice(values);
+        AtomicPhase::Transition.forbid(
+            "output ports cannot receive messages in the transition functions of atomic models",
+        );
+        self.0.borrow_write().extend_from_slice(values);
+    }
+}
+
+impl<T> OutPort<SharedMsg<T>> {
+    /// Adds a new value to the output port, wrapped in a [`SharedMsg`]. Couplings propagate
+    /// a reference to the value instead of cloning it.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be called when implementing the [`super::Atomic::lambda`] method.
+    /// Furthermore, this port must be one of the output ports of the implementer.
+    #[inline]
+    pub unsafe fn add_value_shared(&self, value: T) {
+        self.add_value(Shared::new(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bag_capacity() {
+        let bag = Bag::<usize>::new("bag", None);
+        let (port, output) = (bag.clone() as Shared<dyn Port>, OutPort(bag.clone()));
+        // Safety: the test is the only one manipulating the port
+        unsafe {
+            port.set_capacity(8, 16);
+            assert!(bag.borrow().capacity() >= 8);
+            output.add_values(&[0; 8]);
+            port.clear();
+            assert!(bag.borrow().capacity() >= 8);
+            output.add_values(&[0; 64]);
+            assert!(bag.borrow().capacity() >= 64);
+            port.clear();
+            assert!(bag.borrow().capacity() <= 16);
+        }
+    }
+
+    #[test]
+    fn test_propagate_move() {
+        let (from, to) = (
+            Bag::<String>::new("from", None),
+            Bag::<String>::new("to", None),
+        );
+        let output = OutPort(from.clone());
+        // Safety: the test is the only one manipulating the ports
+        unsafe {
+            output.add_value("a".to_string());
+            from.propagate(&*to);
+            output.add_value("b".to_string());
+            from.propagate_move(&*to);
+            assert!(from.borrow().is_empty());
+            assert_eq!(&["a", "a", "b"], &to.borrow()[..]);
+        }
+    }
+
+    #[test]
+    fn test_shared_msg() {
+        let bag = |name| Bag::<SharedMsg<Vec<u8>>>::new(name, None);
+        let (from, to_a, to_b) = (bag("from"), bag("to_a"), bag("to_b"));
+        let output = OutPort(from.clone());
+        // Safety: the test is the only one manipulating the ports
+        unsafe {
+            output.add_value_shared(vec![0; 1024]);
+            from.propagate(&*to_a);
+            from.propagate(&*to_b);
+            let (a, b) = (&to_a.borrow()[0], &to_b.borrow()[0]);
+            assert!(Shared::ptr_eq(a, b));
+            assert_eq!(3, Shared::strong_count(a));
+        }
+    }
+
+    #[test]
+    fn test_port_tracker() {
+        let tracker = Shared::new(PortTracker::default());
+        let bag = |name, i| Bag::<usize>::new(name, Some((tracker.clone(), i)));
+        let (a, b, c) = (bag("a", 0), bag("b", 1), bag("c", 2));
+        let (output, buffer) = (OutPort(a.clone()), Bag::<usize>::new("buffer", None));
+        let mut dirty = Vec::new();
+        // Safety: the test is the only one manipulating the ports
+        unsafe {
+            output.add_value(1);
+            output.add_value(2);
+            buffer.borrow_mut().push(3);
+            buffer.propagate(&*c);
+            buffer.propagate(&*c);
+            tracker.take(&mut dirty);
+            assert_eq!(vec![0, 2], dirty);
+            assert!(b.is_empty());
+            a.clear();
+            output.add_value(1);
+            dirty.clear();
+            tracker.take(&mut dirty);
+            assert_eq!(vec![0], dirty);
+        }
     }
 }
@@ -0,0 +1,79 @@
+#[cfg(all(debug_assertions, not(feature = "unchecked_ports")))]
+use std::cell::Cell;
+
+/// Function of an atomic model that the current thread is executing. In debug builds, ports use it to check
+/// that models fulfill the safety contracts of [`super::InPort`] and [`super::OutPort`], unless the
+/// `unchecked_ports` feature is enabled. Release builds do not track it at all.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum AtomicPhase {
+    /// The thread is executing the output function of an atomic model.
+    Output,
+    /// The thread is executing a transition function of an atomic model.
+    Transition,
+}
+
+#[cfg(all(debug_assertions, not(feature = "unchecked_ports")))]
+thread_local! {
+    /// Phase that the current thread is executing (if any).
+    static PHASE: Cell<Option<AtomicPhase>> = const { Cell::new(None) };
+}
+
+/// Guard returned by [`AtomicPhase::enter`]. It restores the previous phase of the thread when dropped.
+pub(crate) struct PhaseGuard {
+    #[cfg(all(debug_assertions, not(feature = "unchecked_ports")))]
+    previous: Option<AtomicPhase>,
+}
+
+impl AtomicPhase {
+    /// Marks that the current thread executes this phase until the returned guard is dropped.
+    #[inline]
+    pub(crate) fn enter(self) -> PhaseGuard {
+        PhaseGuard {
+            #[cfg(all(debug_assertions, not(feature = "unchecked_ports")))]
+            previous: PHASE.replace(Some(self)),
+        }
+    }
+
+    /// Panics with the provided message if the current thread is executing this phase.
+    /// It does nothing in release builds or if the `unchecked_ports` feature is enabled.
+    #[inline]
+    pub(crate) fn forbid(self, message: &str) {
+        #[cfg(all(debug_assertions, not(feature = "unchecked_ports")))]
+        assert!(PHASE.get() != Some(self), "{message}");
+        #[cfg(not(all(debug_assertions, not(feature = "unchecked_ports"))))]
+        let _ = message;
+    }
+}
+
+#[cfg(all(debug_assertions, not(feature = "unchecked_ports")))]
+impl Drop for PhaseGuard {
+    #[inline]
+    fn drop(&mut self) {
+        PHASE.set(self.previous);
+    }
+}
+
+#[cfg(all(test, debug_assertions, not(feature = "unchecked_ports")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phase_guard() {
+        AtomicPhase::Output.forbid("unreachable");
+        let output = AtomicPhase::Output.enter();
+        AtomicPhase::Transition.forbid("unreachable");
+        let transition = AtomicPhase::Transition.enter();
+        assert_eq!(Some(AtomicPhase::Transition), PHASE.get());
+        drop(transition);
+        assert_eq!(Some(AtomicPhase::Output), PHASE.get());
+        drop(output);
+        assert_eq!(None, PHASE.get());
+    }
+
+    #[test]
+    #[should_panic(expected = "forbidden")]
+    fn test_forbidden_phase() {
+        let _phase = AtomicPhase::Output.enter();
+        AtomicPhase::Output.forbid("forbidden");
+    }
+}
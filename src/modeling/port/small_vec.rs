@@ -0,0 +1,239 @@
+use std::fmt::{self, Debug};
+use std::mem::MaybeUninit;
+use std::ops::{Deref, DerefMut};
+
+/// Vector that stores up to `N` values inline, without allocating memory.
+/// When it receives more than `N` values, they are moved to the heap, where they stay until the vector is dropped.
+/// Most ports carry zero or one message per simulation cycle, so their bags never allocate memory.
+pub(crate) struct SmallVec<T, const N: usize> {
+    /// Number of values stored inline. It is only meaningful when `heap` is [`None`].
+    len: usize,
+    /// Inline storage. Only the first `len` values are initialized.
+    inline: [MaybeUninit<T>; N],
+    /// Heap storage, used once the vector spills.
+    heap: Option<Vec<T>>,
+}
+
+impl<T, const N: usize> SmallVec<T, N> {
+    /// Creates a new, empty vector.
+    #[inline]
+    pub(crate) fn new() -> Self {
+        Self {
+            len: 0,
+            inline: [const { MaybeUninit::uninit() }; N],
+            heap: None,
+        }
+    }
+
+    /// Returns a slice with all the values of the vector.
+    #[inline]
+    pub(crate) fn as_slice(&self) -> &[T] {
+        match &self.heap {
+            Some(heap) => heap,
+            // Safety: the first `len` inline values are initialized
+            None => unsafe { std::slice::from_raw_parts(self.inline.as_ptr().cast(), self.len) },
+        }
+    }
+
+    /// Returns a mutable slice with all the values of the vector.
+    #[inline]
+    pub(crate) fn as_mut_slice(&mut self) -> &mut [T] {
+        match &mut self.heap {
+            Some(heap) => heap,
+            // Safety: the first `len` inline values are initialized
+            None => unsafe {
+                std::slice::from_raw_parts_mut(self.inline.as_mut_ptr().cast(), self.len)
+            },
+        }
+    }
+
+    /// Returns the number of values that the vector can store without allocating memory.
+    #[inline]
+    pub(crate) fn capacity(&self) -> usize {
+        match &self.heap {
+            Some(heap) => heap.capacity(),
+            None => N,
+        }
+    }
+
+    /// Moves the inline values to the heap, reserving capacity for at least `capacity` values.
+    #[cold]
+    fn spill(&mut self, capacity: usize) {
+        let mut heap = Vec::with_capacity(capacity.max(2 * N));
+        // Safety: the first `len` inline values are initialized, and they are moved only once,
+        // as `len` is reset before the heap storage becomes active
+        unsafe {
+            heap.extend(self.inline[..self.len].iter().map(|v| v.assume_init_read()));
+        }
+        self.len = 0;
+        self.heap = Some(heap);
+    }
+
+    /// Appends a value to the vector.
+    #[inline]
+    pub(crate) fn push(&mut self, value: T) {
+        if self.heap.is_none() && self.len == N {
+            self.spill(N + 1);
+        }
+        match &mut self.heap {
+            Some(heap) => heap.push(value),
+            None => {
+                self.inline[self.len].write(value);
+                self.len += 1;
+            }
+        }
+    }
+
+    /// Reserves capacity for at least `additional` more values.
+    #[inline]
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        match &mut self.heap {
+            Some(heap) => heap.reserve(additional),
+            None if self.len + additional > N => self.spill(self.len + additional),
+            None => (),
+        }
+    }
+
+    /// Shrinks the heap storage of the vector (if any) with a lower bound.
+    #[inline]
+    pub(crate) fn shrink_to(&mut self, min_capacity: usize) {
+        if let Some(heap) = &mut self.heap {
+            heap.shrink_to(min_capacity);
+        }
+    }
+
+    /// Moves all the values of another vector to the vector, leaving the other vector empty but keeping its storage.
+    #[inline]
+    pub(crate) fn append(&mut self, other: &mut Self) {
+        if let (Some(heap), Some(values)) = (&mut self.heap, &mut other.heap) {
+            heap.append(values);
+            return;
+        }
+        self.reserve(other.len());
+        match &mut other.heap {
+            Some(values) => values.drain(..).for_each(|v| self.push(v)),
+            None => {
+                let len = std::mem::take(&mut other.len);
+                // Safety: the first `len` inline values were initialized, and they are moved only once,
+                // as `len` is already reset
+                unsafe {
+                    (other.inline[..len].iter()).for_each(|v| self.push(v.assume_init_read()));
+                }
+            }
+        }
+    }
+
+    /// Removes all the values of the vector, keeping its storage.
+    #[inline]
+    pub(crate) fn clear(&mut self) {
+        match &mut self.heap {
+            Some(heap) => heap.clear(),
+            None => {
+                let len = std::mem::take(&mut self.len);
+                // Safety: the first `len` inline values were initialized, and `len` is already reset
+                unsafe {
+                    self.inline[..len]
+                        .iter_mut()
+                        .for_each(|v| v.assume_init_drop());
+                }
+            }
+        }
+    }
+}
+
+impl<T: Clone, const N: usize> SmallVec<T, N> {
+    /// Clones and appends all the values of a slice to the vector.
+    #[inline]
+    pub(crate) fn extend_from_slice(&mut self, values: &[T]) {
+        self.reserve(values.len());
+        match &mut self.heap {
+            Some(heap) => heap.extend_from_slice(values),
+            // values are pushed one by one, so the vector remains valid if a clone panics
+            None => values.iter().for_each(|v| self.push(v.clone())),
+        }
+    }
+}
+
+impl<T, const N: usize> Default for SmallVec<T, N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for SmallVec<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, const N: usize> Deref for SmallVec<T, N> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> DerefMut for SmallVec<T, N> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut_slice()
+    }
+}
+
+impl<T: Debug, const N: usize> Debug for SmallVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_small_vec() {
+        let counter = Rc::new(());
+        let mut values = SmallVec::<Rc<()>, 2>::new();
+        values.push(counter.clone());
+        values.extend_from_slice(std::slice::from_ref(&counter));
+        assert_eq!((2, 2), (values.len(), values.capacity()));
+        assert_eq!(3, Rc::strong_count(&counter));
+        values.clear();
+        assert!(values.is_empty());
+        assert_eq!(1, Rc::strong_count(&counter));
+
+        values.extend_from_slice(&[counter.clone(), counter.clone(), counter.clone()]);
+        assert_eq!(3, values.len());
+        assert!(values.capacity() >= 3);
+        assert_eq!(4, Rc::strong_count(&counter));
+        values.clear();
+        assert!(values.capacity() >= 3);
+        values.push(counter.clone());
+        drop(values);
+        assert_eq!(1, Rc::strong_count(&counter));
+    }
+
+    #[test]
+    fn test_small_vec_append() {
+        let counter = Rc::new(());
+        let (mut values, mut other) = (SmallVec::<Rc<()>, 2>::new(), SmallVec::new());
+        values.push(counter.clone());
+        other.push(counter.clone());
+        values.append(&mut other);
+        assert_eq!((2, 0), (values.len(), other.len()));
+        other.extend_from_slice(&[counter.clone(), counter.clone(), counter.clone()]);
+        values.append(&mut other);
+        assert_eq!((5, 0), (values.len(), other.len()));
+        assert!(other.capacity() >= 3);
+        other.extend_from_slice(&[counter.clone(), counter.clone(), counter.clone()]);
+        values.append(&mut other);
+        assert_eq!((8, 0), (values.len(), other.len()));
+        assert_eq!(9, Rc::strong_count(&counter));
+        drop(values);
+        assert_eq!(1, Rc::strong_count(&counter));
+    }
+}
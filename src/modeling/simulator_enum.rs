@@ -0,0 +1,317 @@
+//! Enum-based dispatch of subcomponents (see the [`crate::simulator_enum`] macro).
+
+/// Declares an enum whose variants wrap simulators of a closed set of types. The enum implements
+/// [`crate::simulation::Simulator`] and [`crate::modeling::Subcomponent`], so it can be used as the subcomponent
+/// type of a [`crate::modeling::Coupled`] model. Then, the simulation steps of the subcomponents are dispatched
+/// with a `match` instead of a virtual call, so the compiler can inline them.
+///
+/// Every variant must wrap a different type that implements [`crate::simulation::Simulator`],
+/// as the macro implements [`From`] for all of them. Nested coupled models are supported too,
+/// but [`crate::modeling::Coupled::flatten`] and [`crate::modeling::Coupled::direct_connect`]
+/// are only available for coupled models with boxed subcomponents.
+///
+/// # Example
+///
+/// ```
+/// use xdevs::lib::{Generator, Job, Processor, Transducer};
+/// use xdevs::modeling::Coupled;
+/// use xdevs::simulation::RootCoordinator;
+///
+/// xdevs::simulator_enum! {
+///     /// Subcomponents of the experimental frame and processor model.
+///     pub enum EfpComponent {
+///         Generator(Generator),
+///         Processor(Processor<Job>),
+///         Transducer(Transducer),
+///     }
+/// }
+///
+/// let mut efp = Coupled::<f64, EfpComponent>::custom("efp");
+/// efp.add_subcomponent(Generator::new("generator", 3.));
+/// efp.add_subcomponent(Processor::new("processor", 5.));
+/// efp.add_subcomponent(Transducer::new("transducer", 100.));
+/// efp.add_ic("generator", "output", "processor", "input");
+/// efp.add_ic("generator", "output", "transducer", "arrived");
+/// efp.add_ic("processor", "output", "transducer", "solved");
+/// efp.add_ic("transducer", "stop", "generator", "stop");
+///
+/// let mut simulator = RootCoordinator::new(efp);
+/// simulator.simulate(f64::INFINITY);
+/// let transducer = simulator.find_as::<Transducer>("efp.transducer").unwrap();
+/// assert_eq!(33, transducer.n_arrived());
+/// ```
+///
+/// The simulation time type is [`f64`] by default. Use `time: <TYPE>,` before the list of variants
+/// to select another time type.
+#[macro_export]
+macro_rules! simulator_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            time: $time:ty,
+            $($variant:ident($vtype:ty)),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[allow(clippy::large_enum_variant)]
+        $vis enum $name {
+            $(
+                #[allow(missing_docs)]
+                $variant($vtype),
+            )*
+        }
+
+        $(
+            impl From<$vtype> for $name {
+                #[inline]
+                fn from(component: $vtype) -> Self {
+                    Self::$variant(component)
+                }
+            }
+        )*
+
+        impl $crate::modeling::Subcomponent<$time> for $name {
+            #[inline]
+            fn as_simulator(&self) -> &dyn $crate::simulation::Simulator<$time> {
+                match self {
+                    $(Self::$variant(c) => c,)*
+                }
+            }
+
+            #[inline]
+            fn as_simulator_mut(&mut self) -> &mut dyn $crate::simulation::Simulator<$time> {
+                match self {
+                    $(Self::$variant(c) => c,)*
+                }
+            }
+
+            #[inline]
+            fn start(&mut self, t_start: $time) -> $time {
+                match self {
+                    $(Self::$variant(c) => $crate::simulation::Simulator::<$time>::start(c, t_start),)*
+                }
+            }
+
+            #[inline]
+            fn stop(&mut self, t_stop: $time) {
+                match self {
+                    $(Self::$variant(c) => $crate::simulation::Simulator::<$time>::stop(c, t_stop),)*
+                }
+            }
+
+            #[inline]
+            fn collection(&mut self, t: $time) {
+                match self {
+                    $(Self::$variant(c) => $crate::simulation::Simulator::<$time>::collection(c, t),)*
+                }
+            }
+
+            #[inline]
+            fn transition(&mut self, t: $time) -> $time {
+                match self {
+                    $(Self::$variant(c) => $crate::simulation::Simulator::<$time>::transition(c, t),)*
+                }
+            }
+        }
+
+        impl $crate::simulation::Simulator<$time> for $name {
+            #[inline]
+            fn get_component(&self) -> &$crate::modeling::Component<$time> {
+                $crate::modeling::Subcomponent::<$time>::as_simulator(self).get_component()
+            }
+
+            #[inline]
+            fn get_component_mut(&mut self) -> &mut $crate::modeling::Component<$time> {
+                $crate::modeling::Subcomponent::<$time>::as_simulator_mut(self).get_component_mut()
+            }
+
+            fn get_components(&self) -> Vec<&dyn $crate::simulation::Simulator<$time>> {
+                $crate::modeling::Subcomponent::<$time>::as_simulator(self).get_components()
+            }
+
+            fn lookahead(&self) -> $time {
+                $crate::modeling::Subcomponent::<$time>::as_simulator(self).lookahead()
+            }
+
+            fn into_coupled(
+                self: Box<Self>,
+            ) -> Result<
+                Box<$crate::modeling::Coupled<$time>>,
+                Box<dyn $crate::simulation::Simulator<$time>>,
+            > {
+                match *self {
+                    $(Self::$variant(c) => $crate::simulation::Simulator::<$time>::into_coupled(Box::new(c)),)*
+                }
+            }
+
+            fn as_coupled(&self) -> Option<&$crate::modeling::Coupled<$time>> {
+                $crate::modeling::Subcomponent::<$time>::as_simulator(self).as_coupled()
+            }
+
+            fn as_coupled_mut(&mut self) -> Option<&mut $crate::modeling::Coupled<$time>> {
+                $crate::modeling::Subcomponent::<$time>::as_simulator_mut(self).as_coupled_mut()
+            }
+
+            fn state_snapshot(
+                &self,
+            ) -> Option<Vec<(&'static str, $crate::simulation::StateValue)>> {
+                $crate::modeling::Subcomponent::<$time>::as_simulator(self).state_snapshot()
+            }
+
+            fn save_snapshot(&self) -> Option<$crate::simulation::Snapshot<$time>> {
+                $crate::modeling::Subcomponent::<$time>::as_simulator(self).save_snapshot()
+            }
+
+            fn restore_snapshot(&mut self, snapshot: &$crate::simulation::Snapshot<$time>) {
+                $crate::modeling::Subcomponent::<$time>::as_simulator_mut(self).restore_snapshot(snapshot);
+            }
+
+            fn seed_rng(&mut self, master_seed: u64, path: &str) {
+                $crate::modeling::Subcomponent::<$time>::as_simulator_mut(self).seed_rng(master_seed, path);
+            }
+
+            fn set_path(&mut self, path: &str) {
+                $crate::modeling::Subcomponent::<$time>::as_simulator_mut(self).set_path(path);
+            }
+
+            fn set_merge_order(&mut self, order: $crate::simulation::MergeOrder) {
+                $crate::modeling::Subcomponent::<$time>::as_simulator_mut(self).set_merge_order(order);
+            }
+
+            fn set_move_messages(&mut self, enabled: bool) {
+                $crate::modeling::Subcomponent::<$time>::as_simulator_mut(self).set_move_messages(enabled);
+            }
+
+            fn set_bag_policy(&mut self, policy: $crate::simulation::BagPolicy) {
+                $crate::modeling::Subcomponent::<$time>::as_simulator_mut(self).set_bag_policy(policy);
+            }
+
+            fn set_profiling(&mut self, enabled: bool) {
+                $crate::modeling::Subcomponent::<$time>::as_simulator_mut(self).set_profiling(enabled);
+            }
+
+            fn set_sim_t(&mut self, t_last: $time, t_next: $time) {
+                $crate::modeling::Subcomponent::<$time>::as_simulator_mut(self).set_sim_t(t_last, t_next);
+            }
+
+            fn clear_input(&mut self) {
+                $crate::modeling::Subcomponent::<$time>::as_simulator_mut(self).clear_input();
+            }
+
+            fn clear_output(&mut self) {
+                $crate::modeling::Subcomponent::<$time>::as_simulator_mut(self).clear_output();
+            }
+
+            fn clear(&mut self) {
+                $crate::modeling::Subcomponent::<$time>::as_simulator_mut(self).clear();
+            }
+
+            #[inline]
+            fn start(&mut self, t_start: $time) -> $time {
+                $crate::modeling::Subcomponent::<$time>::start(self, t_start)
+            }
+
+            #[inline]
+            fn stop(&mut self, t_stop: $time) {
+                $crate::modeling::Subcomponent::<$time>::stop(self, t_stop);
+            }
+
+            #[inline]
+            fn collection(&mut self, t: $time) {
+                $crate::modeling::Subcomponent::<$time>::collection(self, t);
+            }
+
+            #[inline]
+            fn transition(&mut self, t: $time) -> $time {
+                $crate::modeling::Subcomponent::<$time>::transition(self, t)
+            }
+        }
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($variant:ident($vtype:ty)),* $(,)?
+        }
+    ) => {
+        $crate::simulator_enum! {
+            $(#[$meta])*
+            $vis enum $name {
+                time: f64,
+                $($variant($vtype)),*
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lib::{Generator, Job, Processor, Transducer};
+    use crate::modeling::Coupled;
+    use crate::simulation::{RootCoordinator, Simulator};
+
+    crate::simulator_enum! {
+        /// Subcomponents of the experimental frame.
+        enum EfComponent {
+            Generator(Generator),
+            Transducer(Transducer),
+        }
+    }
+
+    crate::simulator_enum! {
+        /// Subcomponents of the experimental frame and processor model.
+        enum EfpComponent {
+            Ef(Coupled<f64, EfComponent>),
+            Processor(Processor<Job>),
+        }
+    }
+
+    #[test]
+    fn test_simulator_enum() {
+        let mut ef = Coupled::new("ef");
+        ef.add_in_port::<Job>("solved");
+        ef.add_out_port::<Job>("output");
+        ef.add_component(Box::new(Generator::new("generator", 3.)));
+        ef.add_component(Box::new(Transducer::new("transducer", 100.)));
+        ef.add_ic("transducer", "stop", "generator", "stop");
+        ef.add_ic("generator", "output", "transducer", "arrived");
+        ef.add_eic("solved", "transducer", "solved");
+        ef.add_eoc("generator", "output", "output");
+        let mut efp = Coupled::new("efp");
+        efp.add_component(Box::new(ef));
+        efp.add_component(Box::new(Processor::<Job>::new("processor", 5.)));
+        efp.add_ic("ef", "output", "processor", "input");
+        efp.add_ic("processor", "output", "ef", "solved");
+        let mut simulator = RootCoordinator::new(efp);
+        simulator.simulate(f64::INFINITY);
+        let transducer = simulator
+            .find_as::<Transducer>("efp.ef.transducer")
+            .unwrap();
+        let expected = (transducer.n_arrived(), transducer.n_solved());
+
+        let mut ef = Coupled::<f64, EfComponent>::custom("ef");
+        ef.add_in_port::<Job>("solved");
+        ef.add_out_port::<Job>("output");
+        ef.add_subcomponent(Generator::new("generator", 3.));
+        ef.add_subcomponent(Transducer::new("transducer", 100.));
+        ef.add_ic("transducer", "stop", "generator", "stop");
+        ef.add_ic("generator", "output", "transducer", "arrived");
+        ef.add_eic("solved", "transducer", "solved");
+        ef.add_eoc("generator", "output", "output");
+        let mut efp = Coupled::<f64, EfpComponent>::custom("efp");
+        efp.add_subcomponent(ef);
+        efp.add_subcomponent(Processor::new("processor", 5.));
+        efp.add_ic("ef", "output", "processor", "input");
+        efp.add_ic("processor", "output", "ef", "solved");
+        assert!(efp.find_as::<Processor<Job>>("efp.processor").is_some());
+        assert!(efp.find("efp.ef.generator").is_none());
+
+        let mut simulator = RootCoordinator::new(efp);
+        simulator.simulate(f64::INFINITY);
+        assert_eq!(f64::INFINITY, simulator.get_t_next());
+        let ef = simulator.get_components()[0];
+        assert!(ef.as_coupled().is_none());
+        let transducer = ef.get_components()[1].downcast_ref::<Transducer>().unwrap();
+        assert_eq!(expected, (transducer.n_arrived(), transducer.n_solved()));
+        assert_eq!("efp.ef.transducer", transducer.get_component().get_path());
+    }
+}
@@ -0,0 +1,724 @@
+//! Statically composed coupled models (see the [`crate::coupled`] macro).
+//!
+//! Regular [`super::Coupled`] models store their subcomponents as `Box<dyn Simulator>` and their couplings
+//! as pairs of `Shared<dyn Port>`, so every call to a subcomponent and every message propagation goes through
+//! dynamic dispatch. Models whose structure is known at compile time can be declared with the
+//! [`crate::coupled`] macro instead. It generates a struct with one typed field per subcomponent, and its
+//! couplings are resolved to [`Link`]s between bags of a concrete message type when the model is built.
+//! Thus, the compiler can inline the simulation of the subcomponents and the propagation of messages.
+//!
+//! The items of this module are used by the code generated by the macro, so you do not need to use them directly.
+
+use super::port::{Bag, Port};
+use super::Component;
+use crate::error::ModelingError;
+use crate::simulation::{BagPolicy, MergeOrder, Simulator, Snapshot};
+use crate::time::Time;
+use crate::{DynRef, Shared};
+
+/// Coupling between two ports with messages of type `M`, resolved when the model is built.
+pub struct Link<M> {
+    /// Bag of the source port.
+    from: Shared<Bag<M>>,
+    /// Bag of the destination port.
+    to: Shared<Bag<M>>,
+}
+
+impl<M: DynRef + Clone> Link<M> {
+    /// Creates a new link from an input port of a model to an input port of one of its subcomponents (EIC).
+    /// It panics if any port does not exist or if their message type is not `M`.
+    pub fn eic<T: Time>(
+        model: &Component<T>,
+        port_from: &str,
+        component_to: &Component<T>,
+        port_to: &str,
+    ) -> Self {
+        let from = model.get_in_port(port_from).ok_or_else(|| {
+            ModelingError::UnknownInPort(model.get_name().to_string(), port_from.to_string())
+        });
+        let to = in_port(component_to, port_to);
+        Self::new(from, model, component_to, to)
+    }
+
+    /// Creates a new link from an output port of a subcomponent to an input port of other subcomponent (IC).
+    /// It panics if any port does not exist or if their message type is not `M`.
+    pub fn ic<T: Time>(
+        component_from: &Component<T>,
+        port_from: &str,
+        component_to: &Component<T>,
+        port_to: &str,
+    ) -> Self {
+        let from = out_port(component_from, port_from);
+        let to = in_port(component_to, port_to);
+        Self::new(from, component_from, component_to, to)
+    }
+
+    /// Creates a new link from an output port of a subcomponent to an output port of its parent model (EOC).
+    /// It panics if any port does not exist or if their message type is not `M`.
+    pub fn eoc<T: Time>(
+        component_from: &Component<T>,
+        port_from: &str,
+        model: &Component<T>,
+        port_to: &str,
+    ) -> Self {
+        let from = out_port(component_from, port_from);
+        let to = out_port(model, port_to);
+        Self::new(from, component_from, model, to)
+    }
+
+    /// Creates a new link between two ports. It panics if any port does not exist
+    /// or if their message type is not `M`.
+    fn new<T: Time>(
+        from: Result<Shared<dyn Port>, ModelingError>,
+        component_from: &Component<T>,
+        component_to: &Component<T>,
+        to: Result<Shared<dyn Port>, ModelingError>,
+    ) -> Self {
+        let (from, to) = (
+            from.unwrap_or_else(|e| panic!("{e}")),
+            to.unwrap_or_else(|e| panic!("{e}")),
+        );
+        let incompatible = || {
+            let from = format!("{}.{}", component_from.get_name(), from.get_name());
+            let to = format!("{}.{}", component_to.get_name(), to.get_name());
+            panic!("{}", ModelingError::IncompatiblePorts(from, to))
+        };
+        match (Bag::downcast(from.clone()), Bag::downcast(to.clone())) {
+            (Some(from), Some(to)) => Self { from, to },
+            _ => incompatible(),
+        }
+    }
+
+    /// Propagates the messages of the source port to the destination port.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed by a coupled model when propagating messages.
+    #[inline]
+    pub unsafe fn propagate(&self) {
+        self.from.propagate_to(&self.to);
+    }
+}
+
+/// Returns the input port of a component with the provided name.
+fn in_port<T: Time>(
+    component: &Component<T>,
+    port: &str,
+) -> Result<Shared<dyn Port>, ModelingError> {
+    component.get_in_port(port).ok_or_else(|| {
+        ModelingError::UnknownInPort(component.get_name().to_string(), port.to_string())
+    })
+}
+
+/// Returns the output port of a component with the provided name.
+fn out_port<T: Time>(
+    component: &Component<T>,
+    port: &str,
+) -> Result<Shared<dyn Port>, ModelingError> {
+    component.get_out_port(port).ok_or_else(|| {
+        ModelingError::UnknownOutPort(component.get_name().to_string(), port.to_string())
+    })
+}
+
+/// List of [`Link`]s with different message types. Lists are nested pairs (e.g., `(Link<A>, (Link<B>, ()))`),
+/// so they can be of any length and every link is propagated without dynamic dispatch.
+pub trait Links: DynRef {
+    /// Propagates the messages of all the links.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed by a coupled model when propagating messages.
+    unsafe fn propagate(&self);
+}
+
+impl Links for () {
+    #[inline]
+    unsafe fn propagate(&self) {}
+}
+
+impl<M: DynRef + Clone, L: Links> Links for (Link<M>, L) {
+    #[inline]
+    unsafe fn propagate(&self) {
+        self.0.propagate();
+        self.1.propagate();
+    }
+}
+
+/// Structure of a statically composed coupled model. It is implemented by the [`crate::coupled`] macro,
+/// and the implementation of [`Simulator`] generated by the macro relies on it.
+pub trait Composition<T: Time>: DynRef {
+    /// Returns a reference to the component of the model.
+    fn component(&self) -> &Component<T>;
+
+    /// Returns a mutable reference to the component of the model.
+    fn component_mut(&mut self) -> &mut Component<T>;
+
+    /// Returns references to all the subcomponents.
+    fn children(&self) -> Vec<&dyn Simulator<T>>;
+
+    /// Returns mutable references to all the subcomponents.
+    fn children_mut(&mut self) -> Vec<&mut dyn Simulator<T>>;
+
+    /// Starts all the subcomponents. It returns their minimum next simulation time.
+    fn start_children(&mut self, t_start: T) -> T;
+
+    /// Stops all the subcomponents.
+    fn stop_children(&mut self, t_stop: T);
+
+    /// Executes the collection phase of all the subcomponents.
+    fn collection_children(&mut self, t: T);
+
+    /// Executes the transition phase of all the subcomponents. It returns their minimum next simulation time.
+    fn transition_children(&mut self, t: T) -> T;
+
+    /// Propagates messages according to the EICs.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed by the model when propagating messages.
+    unsafe fn propagate_input(&self);
+
+    /// Propagates messages according to the ICs and EOCs.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed by the model when propagating messages.
+    unsafe fn propagate_output(&self);
+}
+
+/// Returns the minimum of two simulation times.
+#[inline]
+pub fn min_time<T: Time>(a: T, b: T) -> T {
+    match b < a {
+        true => b,
+        false => a,
+    }
+}
+
+/// Returns the minimum lookahead of all the subcomponents.
+pub fn lookahead<T: Time, C: Composition<T>>(model: &C) -> T {
+    (model.children().iter()).fold(T::INFINITY, |t, c| min_time(t, c.lookahead()))
+}
+
+/// Saves the state of the model and its subcomponents.
+pub fn save_snapshot<T: Time, C: Composition<T>>(model: &C) -> Option<Snapshot<T>> {
+    let children = (model.children().iter())
+        .map(|c| c.save_snapshot())
+        .collect::<Option<_>>()?;
+    Some(Snapshot::new(model.component(), None, children))
+}
+
+/// Restores the state of the model and its subcomponents.
+pub fn restore_snapshot<T: Time, C: Composition<T>>(model: &mut C, snapshot: &Snapshot<T>) {
+    snapshot.restore(model.component_mut());
+    for (component, child) in model.children_mut().into_iter().zip(snapshot.children()) {
+        component.restore_snapshot(child);
+    }
+}
+
+/// Derives the random number generator streams of the model and its subcomponents from a master seed.
+pub fn seed_rng<T: Time, C: Composition<T>>(model: &mut C, master_seed: u64, path: &str) {
+    (model.component_mut()).set_rng(crate::random::Rng::stream(master_seed, path));
+    for component in model.children_mut() {
+        let path = format!("{path}.{}", component.get_name());
+        component.seed_rng(master_seed, &path);
+    }
+}
+
+/// Sets the path of the model and its subcomponents.
+pub fn set_path<T: Time, C: Composition<T>>(model: &mut C, path: &str) {
+    model.component_mut().set_path(path);
+    for component in model.children_mut() {
+        let path = format!("{path}.{}", component.get_name());
+        component.set_path(&path);
+    }
+}
+
+/// Sets the merge order of the subcomponents. Statically composed models always propagate messages
+/// in coupling declaration order.
+pub fn set_merge_order<T: Time, C: Composition<T>>(model: &mut C, order: MergeOrder) {
+    model
+        .children_mut()
+        .into_iter()
+        .for_each(|c| c.set_merge_order(order));
+}
+
+/// Enables or disables moving messages in the subcomponents. Statically composed models always clone messages.
+pub fn set_move_messages<T: Time, C: Composition<T>>(model: &mut C, enabled: bool) {
+    model
+        .children_mut()
+        .into_iter()
+        .for_each(|c| c.set_move_messages(enabled));
+}
+
+/// Applies a [`BagPolicy`] to the ports of the model and its subcomponents.
+pub fn set_bag_policy<T: Time, C: Composition<T>>(model: &mut C, policy: BagPolicy) {
+    // Safety: coordinator configuring the ports before the simulation starts
+    unsafe { model.component().set_bag_policy(policy) };
+    model
+        .children_mut()
+        .into_iter()
+        .for_each(|c| c.set_bag_policy(policy));
+}
+
+/// Enables or disables the profiling of the model and its subcomponents.
+pub fn set_profiling<T: Time, C: Composition<T>>(model: &mut C, enabled: bool) {
+    model.component_mut().set_profiling(enabled);
+    model
+        .children_mut()
+        .into_iter()
+        .for_each(|c| c.set_profiling(enabled));
+}
+
+/// Starts the simulation of the model and its subcomponents.
+#[inline]
+pub fn start<T: Time, C: Composition<T>>(model: &mut C, t_start: T) -> T {
+    model.component().reset_stats();
+    let t_next = model.start_children(t_start);
+    model.component_mut().set_sim_t(t_start, t_next);
+    t_next
+}
+
+/// Stops the simulation of the model and its subcomponents.
+#[inline]
+pub fn stop<T: Time, C: Composition<T>>(model: &mut C, t_stop: T) {
+    model.stop_children(t_stop);
+    model.component_mut().set_sim_t(t_stop, T::INFINITY);
+}
+
+/// Executes the collection phase of the subcomponents and propagates messages according to the ICs and EOCs.
+#[inline]
+pub fn collection<T: Time, C: Composition<T>>(model: &mut C, t: T) {
+    if t >= model.component().get_t_next() {
+        model.collection_children(t);
+        // Safety: coupled model propagating messages and recording its output
+        unsafe {
+            model.propagate_output();
+            model.component_mut().track_output();
+        }
+    }
+}
+
+/// Propagates messages according to the EICs and executes the transition phase of the subcomponents.
+/// Unlike regular coupled models, all the subcomponents are visited, as they are inlined.
+#[inline]
+pub fn transition<T: Time, C: Composition<T>>(model: &mut C, t: T) -> T {
+    // Safety: coupled model checking its input ports
+    let is_external = unsafe { model.component_mut().track_input() };
+    if is_external {
+        // Safety: coupled model propagating messages and clearing its input
+        unsafe {
+            model.propagate_input();
+            model.component_mut().clear_input();
+        }
+    }
+    let is_internal = t >= model.component().get_t_next();
+    if is_internal {
+        // Safety: coupled model clearing its output
+        unsafe { model.component_mut().clear_output() };
+    }
+    if !is_external && !is_internal {
+        return model.component().get_t_next();
+    }
+    let t_next = model.transition_children(t);
+    model.component_mut().set_sim_t(t, t_next);
+    t_next
+}
+
+/// Declares a statically composed coupled model (see the [`crate::modeling::static_coupled`] module).
+///
+/// The macro generates a struct with the given name, a public field for every subcomponent,
+/// and a `new` constructor that receives the name of the model and its subcomponents.
+/// The struct implements [`Simulator`], so it can be simulated by any coordinator or nested
+/// in other models. Every coupling must specify the message type of its ports,
+/// and the constructor panics if the ports do not exist or their message type is different.
+///
+/// Statically composed models follow Parallel DEVS semantics and visit all their subcomponents in every
+/// simulation cycle, so they are intended for small, fixed structures. Their subcomponents are not listed by
+/// [`Simulator::get_components`], so listeners and the profiler treat them as a single model.
+/// Couplings with adapters are not supported.
+///
+/// # Example
+///
+/// ```
+/// use xdevs::lib::{Generator, Job, Processor, Transducer};
+/// use xdevs::simulation::RootCoordinator;
+///
+/// xdevs::coupled! {
+///     /// Experimental frame and processor.
+///     pub struct Efp {
+///         components: {
+///             generator: Generator,
+///             processor: Processor<Job>,
+///             transducer: Transducer,
+///         },
+///         in_ports: {},
+///         out_ports: { output: Job },
+///         eics: {},
+///         ics: {
+///             generator.output => processor.input: Job,
+///             generator.output => transducer.arrived: Job,
+///             processor.output => transducer.solved: Job,
+///             transducer.stop => generator.stop: bool,
+///         },
+///         eocs: { processor.output => output: Job },
+///     }
+/// }
+///
+/// let efp = Efp::new(
+///     "efp",
+///     Generator::new("generator", 3.),
+///     Processor::new("processor", 5.),
+///     Transducer::new("transducer", 100.),
+/// );
+/// let mut simulator = RootCoordinator::new(efp);
+/// simulator.simulate(f64::INFINITY);
+/// assert_eq!(33, simulator.transducer.n_arrived());
+/// ```
+///
+/// The simulation time type is [`f64`] by default. Use `time: <TYPE>,` before the list of components
+/// to select another time type.
+#[macro_export]
+macro_rules! coupled {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            components: { $($field:ident: $ftype:ty),* $(,)? },
+            $($rest:tt)*
+        }
+    ) => {
+        $crate::coupled! {
+            $(#[$meta])*
+            $vis struct $name {
+                time: f64,
+                components: { $($field: $ftype),* },
+                $($rest)*
+            }
+        }
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            time: $time:ty,
+            components: { $($field:ident: $ftype:ty),* $(,)? },
+            in_ports: { $($in_port:ident: $in_type:ty),* $(,)? },
+            out_ports: { $($out_port:ident: $out_type:ty),* $(,)? },
+            eics: { $($eic_from:ident => $eic_to:ident.$eic_port:ident: $eic_type:ty),* $(,)? },
+            ics: { $($ic_from:ident.$ic_port_from:ident => $ic_to:ident.$ic_port_to:ident: $ic_type:ty),* $(,)? },
+            eocs: { $($eoc_from:ident.$eoc_port:ident => $eoc_to:ident: $eoc_type:ty),* $(,)? } $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            /// Component of the model.
+            __component: $crate::modeling::Component<$time>,
+            $(
+                #[allow(missing_docs)]
+                pub $field: $ftype,
+            )*
+            /// EICs of the model.
+            __eics: $crate::__links_type!($($eic_type),*),
+            /// ICs of the model.
+            __ics: $crate::__links_type!($($ic_type),*),
+            /// EOCs of the model.
+            __eocs: $crate::__links_type!($($eoc_type),*),
+        }
+
+        impl $name {
+            /// Creates a new model with the provided name and subcomponents.
+            /// It panics if any coupling is not valid.
+            $vis fn new(name: &str, $($field: $ftype),*) -> Self {
+                use $crate::modeling::static_coupled::Link;
+                use $crate::simulation::Simulator;
+                #[allow(unused_mut)]
+                let mut component = $crate::modeling::Component::<$time>::new(name);
+                $(let _ = component.add_in_port::<$in_type>(stringify!($in_port));)*
+                $(let _ = component.add_out_port::<$out_type>(stringify!($out_port));)*
+                let __eics = $crate::__links!($(
+                    Link::eic(
+                        &component,
+                        stringify!($eic_from),
+                        Simulator::<$time>::get_component(&$eic_to),
+                        stringify!($eic_port),
+                    )
+                ),*);
+                let __ics = $crate::__links!($(
+                    Link::ic(
+                        Simulator::<$time>::get_component(&$ic_from),
+                        stringify!($ic_port_from),
+                        Simulator::<$time>::get_component(&$ic_to),
+                        stringify!($ic_port_to),
+                    )
+                ),*);
+                let __eocs = $crate::__links!($(
+                    Link::eoc(
+                        Simulator::<$time>::get_component(&$eoc_from),
+                        stringify!($eoc_port),
+                        &component,
+                        stringify!($eoc_to),
+                    )
+                ),*);
+                Self {
+                    __component: component,
+                    $($field,)*
+                    __eics,
+                    __ics,
+                    __eocs,
+                }
+            }
+        }
+
+        impl $crate::modeling::static_coupled::Composition<$time> for $name {
+            #[inline]
+            fn component(&self) -> &$crate::modeling::Component<$time> {
+                &self.__component
+            }
+
+            #[inline]
+            fn component_mut(&mut self) -> &mut $crate::modeling::Component<$time> {
+                &mut self.__component
+            }
+
+            fn children(&self) -> Vec<&dyn $crate::simulation::Simulator<$time>> {
+                vec![$(&self.$field),*]
+            }
+
+            fn children_mut(&mut self) -> Vec<&mut dyn $crate::simulation::Simulator<$time>> {
+                vec![$(&mut self.$field),*]
+            }
+
+            #[inline]
+            fn start_children(&mut self, t_start: $time) -> $time {
+                use $crate::modeling::static_coupled::min_time;
+                use $crate::simulation::Simulator;
+                let t_next = <$time as $crate::time::Time>::INFINITY;
+                $(let t_next = min_time(t_next, Simulator::<$time>::start(&mut self.$field, t_start));)*
+                t_next
+            }
+
+            #[inline]
+            fn stop_children(&mut self, t_stop: $time) {
+                $($crate::simulation::Simulator::<$time>::stop(&mut self.$field, t_stop);)*
+            }
+
+            #[inline]
+            fn collection_children(&mut self, t: $time) {
+                $($crate::simulation::Simulator::<$time>::collection(&mut self.$field, t);)*
+            }
+
+            #[inline]
+            fn transition_children(&mut self, t: $time) -> $time {
+                use $crate::modeling::static_coupled::min_time;
+                use $crate::simulation::Simulator;
+                let t_next = <$time as $crate::time::Time>::INFINITY;
+                $(let t_next = min_time(t_next, Simulator::<$time>::transition(&mut self.$field, t));)*
+                t_next
+            }
+
+            #[inline]
+            unsafe fn propagate_input(&self) {
+                $crate::modeling::static_coupled::Links::propagate(&self.__eics);
+            }
+
+            #[inline]
+            unsafe fn propagate_output(&self) {
+                $crate::modeling::static_coupled::Links::propagate(&self.__eocs);
+                $crate::modeling::static_coupled::Links::propagate(&self.__ics);
+            }
+        }
+
+        impl $crate::simulation::Simulator<$time> for $name {
+            #[inline]
+            fn get_component(&self) -> &$crate::modeling::Component<$time> {
+                &self.__component
+            }
+
+            #[inline]
+            fn get_component_mut(&mut self) -> &mut $crate::modeling::Component<$time> {
+                &mut self.__component
+            }
+
+            fn lookahead(&self) -> $time {
+                $crate::modeling::static_coupled::lookahead(self)
+            }
+
+            fn into_coupled(
+                self: Box<Self>,
+            ) -> Result<
+                Box<$crate::modeling::Coupled<$time>>,
+                Box<dyn $crate::simulation::Simulator<$time>>,
+            > {
+                Err(self)
+            }
+
+            fn as_coupled(&self) -> Option<&$crate::modeling::Coupled<$time>> {
+                None
+            }
+
+            fn as_coupled_mut(&mut self) -> Option<&mut $crate::modeling::Coupled<$time>> {
+                None
+            }
+
+            fn save_snapshot(&self) -> Option<$crate::simulation::Snapshot<$time>> {
+                $crate::modeling::static_coupled::save_snapshot(self)
+            }
+
+            fn restore_snapshot(&mut self, snapshot: &$crate::simulation::Snapshot<$time>) {
+                $crate::modeling::static_coupled::restore_snapshot(self, snapshot);
+            }
+
+            fn seed_rng(&mut self, master_seed: u64, path: &str) {
+                $crate::modeling::static_coupled::seed_rng(self, master_seed, path);
+            }
+
+            fn set_path(&mut self, path: &str) {
+                $crate::modeling::static_coupled::set_path(self, path);
+            }
+
+            fn set_merge_order(&mut self, order: $crate::simulation::MergeOrder) {
+                $crate::modeling::static_coupled::set_merge_order(self, order);
+            }
+
+            fn set_move_messages(&mut self, enabled: bool) {
+                $crate::modeling::static_coupled::set_move_messages(self, enabled);
+            }
+
+            fn set_bag_policy(&mut self, policy: $crate::simulation::BagPolicy) {
+                $crate::modeling::static_coupled::set_bag_policy(self, policy);
+            }
+
+            fn set_profiling(&mut self, enabled: bool) {
+                $crate::modeling::static_coupled::set_profiling(self, enabled);
+            }
+
+            #[inline]
+            fn start(&mut self, t_start: $time) -> $time {
+                $crate::modeling::static_coupled::start(self, t_start)
+            }
+
+            #[inline]
+            fn stop(&mut self, t_stop: $time) {
+                $crate::modeling::static_coupled::stop(self, t_stop);
+            }
+
+            #[inline]
+            fn collection(&mut self, t: $time) {
+                $crate::modeling::static_coupled::collection(self, t);
+            }
+
+            #[inline]
+            fn transition(&mut self, t: $time) -> $time {
+                $crate::modeling::static_coupled::transition(self, t)
+            }
+        }
+    };
+}
+
+/// Type of a list of [`Link`]s with the provided message types.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __links_type {
+    () => { () };
+    ($ty:ty $(, $rest:ty)*) => {
+        ($crate::modeling::static_coupled::Link<$ty>, $crate::__links_type!($($rest),*))
+    };
+}
+
+/// List of [`Link`]s built from the provided expressions.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __links {
+    () => { () };
+    ($link:expr $(, $rest:expr)*) => {
+        ($link, $crate::__links!($($rest),*))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lib::{Generator, Job, Processor, Transducer};
+    use crate::modeling::Coupled;
+    use crate::simulation::{RootCoordinator, Simulator};
+
+    crate::coupled! {
+        /// Processor with its own input and output ports.
+        struct Server {
+            components: { processor: Processor<Job> },
+            in_ports: { input: Job },
+            out_ports: { output: Job },
+            eics: { input => processor.input: Job },
+            ics: {},
+            eocs: { processor.output => output: Job },
+        }
+    }
+
+    crate::coupled! {
+        /// Experimental frame and server.
+        struct Efp {
+            components: {
+                generator: Generator,
+                server: Server,
+                transducer: Transducer,
+            },
+            in_ports: {},
+            out_ports: {},
+            eics: {},
+            ics: {
+                generator.output => server.input: Job,
+                generator.output => transducer.arrived: Job,
+                server.output => transducer.solved: Job,
+                transducer.stop => generator.stop: bool,
+            },
+            eocs: {},
+        }
+    }
+
+    #[test]
+    fn test_static_coupled() {
+        let mut root = Coupled::new("efp");
+        root.add_component(Box::new(Generator::new("generator", 3.)));
+        root.add_component(Box::new(Processor::<Job>::new("processor", 5.)));
+        root.add_component(Box::new(Transducer::new("transducer", 100.)));
+        root.add_ic("generator", "output", "processor", "input");
+        root.add_ic("generator", "output", "transducer", "arrived");
+        root.add_ic("processor", "output", "transducer", "solved");
+        root.add_ic("transducer", "stop", "generator", "stop");
+        let mut simulator = RootCoordinator::new(root);
+        simulator.simulate(f64::INFINITY);
+        let transducer = simulator.find_as::<Transducer>("efp.transducer").unwrap();
+        let expected = (transducer.n_arrived(), transducer.n_solved());
+
+        let server = Server::new("server", Processor::new("processor", 5.));
+        let efp = Efp::new(
+            "efp",
+            Generator::new("generator", 3.),
+            server,
+            Transducer::new("transducer", 100.),
+        );
+        let mut simulator = RootCoordinator::new(efp);
+        simulator.simulate(f64::INFINITY);
+        let transducer = &simulator.transducer;
+        assert_eq!(expected, (transducer.n_arrived(), transducer.n_solved()));
+        assert_eq!(f64::INFINITY, simulator.get_t_next());
+        assert_eq!(
+            "efp.server.processor",
+            Simulator::get_component(&simulator.server.processor).get_path()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "input port processor.unknown does not exist")]
+    fn test_static_coupled_errors() {
+        crate::coupled! {
+            struct Wrong {
+                components: { processor: Processor<Job> },
+                in_ports: { input: Job },
+                out_ports: {},
+                eics: { input => processor.unknown: Job },
+                ics: {},
+                eocs: {},
+            }
+        }
+        Wrong::new("wrong", Processor::new("processor", 1.));
+    }
+}
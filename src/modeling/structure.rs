@@ -0,0 +1,219 @@
+use super::coupled::port_key;
+use super::{Coupled, Subcomponent};
+use crate::simulation::Simulator;
+use crate::time::Time;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Number of ports with every fan-in or fan-out value. Keys are numbers of couplings,
+/// and values are the number of ports with that many couplings.
+pub type Distribution = BTreeMap<usize, usize>;
+
+/// Structure statistics of a model hierarchy. It is obtained with [`Coupled::stats`].
+/// Its [`fmt::Display`] implementation prints a human-readable summary.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelStats {
+    /// Number of levels of coupled models (e.g., 1 if the model only contains atomic models).
+    pub depth: usize,
+    /// Number of atomic models.
+    pub n_atomics: usize,
+    /// Number of coupled models, including the model itself.
+    pub n_coupled: usize,
+    /// Number of external input couplings of all the coupled models.
+    pub n_eics: usize,
+    /// Number of internal couplings of all the coupled models.
+    pub n_ics: usize,
+    /// Number of external output couplings of all the coupled models.
+    pub n_eocs: usize,
+    /// Fan-in of the input ports of all the subcomponents (i.e., the number of couplings that send messages to them).
+    pub fan_in: Distribution,
+    /// Fan-out of the output ports of all the subcomponents (i.e., the number of couplings that read messages from them).
+    pub fan_out: Distribution,
+    /// Path and number of subcomponents of the coupled model with the most subcomponents.
+    pub largest: Option<(String, usize)>,
+}
+
+impl ModelStats {
+    /// Collects the structure statistics of a coupled model and its subcomponents.
+    pub(crate) fn new<T: Time, C: Subcomponent<T>>(model: &Coupled<T, C>) -> Self {
+        let mut collector = Collector::default();
+        collector.coupled(model, model.get_name(), 1);
+        collector.finish()
+    }
+
+    /// Collects the structure statistics of any model. Atomic models have a depth of 0.
+    pub(crate) fn of<T: Time>(model: &dyn Simulator<T>) -> Self {
+        let mut collector = Collector::default();
+        match model.as_coupled() {
+            Some(coupled) => collector.coupled(coupled, model.get_name(), 1),
+            None if !model.get_components().is_empty() => {
+                collector.composite(model.get_components(), model.get_name(), 1)
+            }
+            None => collector.stats.n_atomics += 1,
+        }
+        collector.finish()
+    }
+
+    /// Returns the total number of couplings.
+    pub fn n_couplings(&self) -> usize {
+        self.n_eics + self.n_ics + self.n_eocs
+    }
+}
+
+impl fmt::Display for ModelStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Depth: {}", self.depth)?;
+        writeln!(
+            f,
+            "Components: {} atomic models, {} coupled models",
+            self.n_atomics, self.n_coupled
+        )?;
+        writeln!(
+            f,
+            "Couplings: {} EICs, {} ICs, {} EOCs",
+            self.n_eics, self.n_ics, self.n_eocs
+        )?;
+        writeln!(f, "Fan-in of input ports: {}", Summary(&self.fan_in))?;
+        writeln!(f, "Fan-out of output ports: {}", Summary(&self.fan_out))?;
+        if let Some((path, n)) = &self.largest {
+            writeln!(f, "Largest coupled model: {path} ({n} components)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats the minimum, mean, and maximum of a distribution.
+struct Summary<'a>(&'a Distribution);
+
+impl fmt::Display for Summary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let n: usize = self.0.values().sum();
+        let (Some(min), Some(max)) = (self.0.keys().next(), self.0.keys().next_back()) else {
+            return write!(f, "no ports");
+        };
+        let total: usize = self.0.iter().map(|(k, v)| k * v).sum();
+        let mean = total as f64 / n as f64;
+        write!(f, "min {min}, mean {mean:.2}, max {max} ({n} ports)")
+    }
+}
+
+/// Collects the structure statistics of a model hierarchy.
+#[derive(Default)]
+struct Collector {
+    stats: ModelStats,
+    /// Number of couplings that send messages to every port, by port key.
+    fan_in: HashMap<usize, usize>,
+    /// Number of couplings that read messages from every port, by port key.
+    fan_out: HashMap<usize, usize>,
+    /// Keys of the input ports of all the subcomponents.
+    in_ports: Vec<usize>,
+    /// Keys of the output ports of all the subcomponents.
+    out_ports: Vec<usize>,
+}
+
+impl Collector {
+    fn coupled<T: Time, C: Subcomponent<T>>(
+        &mut self,
+        model: &Coupled<T, C>,
+        path: &str,
+        depth: usize,
+    ) {
+        self.stats.n_eics += model.eics.len();
+        self.stats.n_ics += model.ics.len();
+        self.stats.n_eocs += model.eocs.len();
+        for (p_to, p_from) in model.eics.iter().chain(&model.ics).chain(&model.eocs) {
+            *self.fan_in.entry(port_key(p_to)).or_default() += 1;
+            *self.fan_out.entry(port_key(p_from)).or_default() += 1;
+        }
+        self.composite(model.get_components(), path, depth);
+    }
+
+    /// Collects the statistics of the subcomponents of a coupled model.
+    fn composite<T: Time>(&mut self, components: Vec<&dyn Simulator<T>>, path: &str, depth: usize) {
+        self.stats.n_coupled += 1;
+        self.stats.depth = self.stats.depth.max(depth);
+        let largest = self.stats.largest.as_ref();
+        if largest.is_none_or(|(_, n)| components.len() > *n) {
+            self.stats.largest = Some((path.to_string(), components.len()));
+        }
+        for component in components {
+            let child = component.get_component();
+            self.in_ports
+                .extend(child.get_in_ports().iter().map(port_key));
+            self.out_ports
+                .extend(child.get_out_ports().iter().map(port_key));
+            let path = format!("{path}.{}", child.get_name());
+            match component.as_coupled() {
+                Some(coupled) => self.coupled(coupled, &path, depth + 1),
+                // Statically composed models do not expose their couplings
+                None if !component.get_components().is_empty() => {
+                    self.composite(component.get_components(), &path, depth + 1)
+                }
+                None => self.stats.n_atomics += 1,
+            }
+        }
+    }
+
+    fn finish(mut self) -> ModelStats {
+        for key in &self.in_ports {
+            let n = self.fan_in.get(key).copied().unwrap_or_default();
+            *self.stats.fan_in.entry(n).or_default() += 1;
+        }
+        for key in &self.out_ports {
+            let n = self.fan_out.get(key).copied().unwrap_or_default();
+            *self.stats.fan_out.entry(n).or_default() += 1;
+        }
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lib::{Generator, Job, Processor};
+    use crate::modeling::Coupled;
+
+    #[test]
+    fn test_model_stats() {
+        let mut inner = Coupled::new("inner");
+        inner.add_in_port::<Job>("input");
+        inner.add_out_port::<Job>("output");
+        for i in 0..3 {
+            let name = format!("processor_{i}");
+            inner.add_component(Box::new(Processor::<Job>::new(&name, 1.)));
+            inner.add_eic("input", &name, "input");
+            inner.add_eoc(&name, "output", "output");
+        }
+        let mut root = Coupled::new("root");
+        root.add_component(Box::new(Generator::new("generator", 1.)));
+        root.add_component(Box::new(inner));
+        root.add_ic("generator", "output", "inner", "input");
+
+        let stats = root.stats();
+        assert_eq!(2, stats.depth);
+        assert_eq!(4, stats.n_atomics);
+        assert_eq!(2, stats.n_coupled);
+        assert_eq!((3, 1, 3), (stats.n_eics, stats.n_ics, stats.n_eocs));
+        assert_eq!(7, stats.n_couplings());
+        // generator.stop has no couplings, inner.input and the processors' inputs have one
+        assert_eq!(
+            vec![(0, 1), (1, 4)],
+            stats.fan_in.into_iter().collect::<Vec<_>>()
+        );
+        // inner.output has no couplings, generator.output and the processors' outputs have one
+        assert_eq!(
+            vec![(0, 1), (1, 4)],
+            stats.fan_out.into_iter().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(("root.inner".to_string(), 3)), stats.largest);
+    }
+
+    #[test]
+    fn test_model_stats_display() {
+        let mut root = Coupled::new("root");
+        root.add_component(Box::new(Generator::new("generator", 1.)));
+        let text = root.stats().to_string();
+        assert!(text.contains("Components: 1 atomic models, 1 coupled models"));
+        assert!(text.contains("Fan-out of output ports: min 0, mean 0.00, max 0 (1 ports)"));
+        assert!(text.contains("Largest coupled model: root (1 components)"));
+    }
+}
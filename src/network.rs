@@ -0,0 +1,142 @@
+//! Library of atomic models for communication networks (e.g., to study the performance of network protocols).
+//! [`Node`]s send, forward, and receive [`Packet`]s, and [`Link`]s connect them with a given propagation delay,
+//! bandwidth, buffer size, and loss probability. Random samples are drawn from the random number generator
+//! streams of the components, so simulations are reproducible with [`crate::simulation::RootCoordinator::set_seed`].
+
+mod link;
+mod node;
+
+pub use link::Link;
+pub use node::Node;
+
+/// Network packet with a payload of type `P` (e.g., the header of a protocol, or `()` for plain data).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet<P = ()> {
+    /// Sequence number of the packet at its source node.
+    pub id: u64,
+    /// Address of the source node.
+    pub src: usize,
+    /// Address of the destination node.
+    pub dst: usize,
+    /// Size of the packet in bytes.
+    pub size: usize,
+    /// Simulation time at which the packet was sent by its source node.
+    pub time: f64,
+    /// Payload of the packet.
+    pub payload: P,
+}
+
+impl<P> Packet<P> {
+    /// Creates a new packet to the given destination. Its source address, sequence number,
+    /// and creation time are set by the [`Node`] that sends it.
+    pub fn new(dst: usize, size: usize, payload: P) -> Self {
+        Self {
+            id: 0,
+            src: 0,
+            dst,
+            size,
+            time: 0.,
+            payload,
+        }
+    }
+
+    /// Returns the size of the packet in bits.
+    pub fn bits(&self) -> f64 {
+        self.size as f64 * 8.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::Coupled;
+    use crate::scenario::{ScenarioPlayer, ScheduledEvent};
+    use crate::simulation::RootCoordinator;
+    use crate::stats::Summary;
+
+    /// Returns a scenario player that sends packets of 125 bytes (1000 bits) to the given destinations.
+    fn player(packets: &[(f64, usize)]) -> ScenarioPlayer<Packet> {
+        let schedule = (packets.iter())
+            .map(|(t, dst)| ScheduledEvent::new(*t, "output", Packet::new(*dst, 125, ())))
+            .collect();
+        ScenarioPlayer::new("player", schedule)
+    }
+
+    fn counter(simulator: &RootCoordinator<Coupled>, path: &str, name: &str) -> u64 {
+        match simulator.stats().get(path, name) {
+            Some(Summary::Counter(n)) => *n,
+            summary => panic!("unexpected summary {summary:?}"),
+        }
+    }
+
+    fn mean(simulator: &RootCoordinator<Coupled>, path: &str, name: &str) -> f64 {
+        match simulator.stats().get(path, name) {
+            Some(Summary::Tally { mean, .. }) => *mean,
+            summary => panic!("unexpected summary {summary:?}"),
+        }
+    }
+
+    #[test]
+    fn test_link() {
+        // Transmissions take 1 time unit and propagation takes 0.5 time units
+        let mut coupled = Coupled::new("network");
+        coupled.add_component(Box::new(player(&[(0., 1), (0., 1), (0., 1), (1., 1)])));
+        coupled.add_component(Box::new(Link::<()>::new("link", 0.5, 1000.).capacity(1)));
+        coupled.add_ic("player", "output", "link", "input");
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.simulate(f64::INFINITY);
+
+        // The third packet is dropped, and the fourth one waits until the second one is transmitted
+        assert_eq!(1, counter(&simulator, "network.link", "dropped"));
+        assert_eq!(3, counter(&simulator, "network.link", "delivered"));
+        assert_eq!(
+            (1.5 + 2.5 + 2.5) / 3.,
+            mean(&simulator, "network.link", "latency")
+        );
+    }
+
+    #[test]
+    fn test_link_loss() {
+        let packets: Vec<_> = (0..10_000).map(|i| (i as f64, 1)).collect();
+        let mut coupled = Coupled::new("network");
+        coupled.add_component(Box::new(player(&packets)));
+        let link = Link::<()>::new("link", 0., f64::INFINITY).loss(0.25);
+        coupled.add_component(Box::new(link));
+        coupled.add_ic("player", "output", "link", "input");
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.set_seed(42);
+        simulator.simulate(f64::INFINITY);
+
+        let lost = counter(&simulator, "network.link", "lost");
+        assert!((lost as f64 / 10_000. - 0.25).abs() < 0.02);
+        let delivered = counter(&simulator, "network.link", "delivered");
+        assert_eq!(10_000, lost + delivered);
+        assert_eq!(0., mean(&simulator, "network.link", "latency"));
+    }
+
+    #[test]
+    fn test_routing() {
+        // Line topology 0 -> 1 -> 2, where node 1 only knows how to reach node 2
+        let mut coupled = Coupled::new("network");
+        coupled.add_component(Box::new(player(&[(0., 2), (0., 3), (1., 0)])));
+        coupled.add_component(Box::new(Node::<()>::new("node_0", 0, 1).default_route(0)));
+        coupled.add_component(Box::new(Node::<()>::new("node_1", 1, 1).route(2, 0)));
+        coupled.add_component(Box::new(Node::<()>::new("node_2", 2, 0)));
+        for i in 0..2 {
+            let link = format!("link_{i}");
+            coupled.add_component(Box::new(Link::<()>::new(&link, 1., f64::INFINITY)));
+            coupled.add_ic(&format!("node_{i}"), "output_0", &link, "input");
+            coupled.add_ic(&link, "output", &format!("node_{}", i + 1), "input");
+        }
+        coupled.add_ic("player", "output", "node_0", "send");
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.simulate(f64::INFINITY);
+
+        // Packets to node 3 are discarded by node 1, and packets to node 0 are received by node 0 itself
+        assert_eq!(1, counter(&simulator, "network.node_2", "received"));
+        assert_eq!(2., mean(&simulator, "network.node_2", "latency"));
+        assert_eq!(1, counter(&simulator, "network.node_1", "unroutable"));
+        assert_eq!(1, counter(&simulator, "network.node_0", "received"));
+        assert_eq!(2, counter(&simulator, "network.node_0", "forwarded"));
+    }
+}
@@ -0,0 +1,190 @@
+use super::Packet;
+use crate::modeling::{Atomic, Component, InPort, OutPort};
+use crate::stats::{Counter, Tally, TimeWeighted};
+use crate::DynRef;
+use std::collections::VecDeque;
+
+/// Atomic model of a unidirectional network link. Packets arrive through its `input` port, wait in a FIFO buffer
+/// until the link is free, are transmitted one at a time at the bandwidth of the link, and leave through
+/// its `output` port after the propagation delay. Transmitted packets may be lost with a given probability,
+/// sampled with the random number generator stream of the component. Packets that arrive when the buffer is full are dropped.
+///
+/// The link reports the following accumulators (see [`crate::simulation::RootCoordinator::stats`]):
+/// `latency` (time since every delivered packet arrived at the link), `queue_length` (time-weighted number of
+/// buffered packets), and `delivered`, `lost`, and `dropped` (number of packets).
+pub struct Link<P: Clone = ()> {
+    /// Component of the atomic model.
+    component: Component,
+    /// Propagation delay.
+    delay: f64,
+    /// Bandwidth in bits per time unit.
+    bandwidth: f64,
+    /// Probability of losing a transmitted packet.
+    loss: f64,
+    /// Maximum number of buffered packets.
+    capacity: usize,
+    /// Current simulation time.
+    clock: f64,
+    /// Buffered packets and their arrival times at the link.
+    queue: VecDeque<(f64, Packet<P>)>,
+    /// Packet being transmitted, the time at which its transmission is over, and its arrival time at the link.
+    transmitting: Option<(f64, f64, Packet<P>)>,
+    /// Packets being propagated, their delivery times, and their arrival times at the link.
+    in_flight: VecDeque<(f64, f64, Packet<P>)>,
+    /// Time in the link of every delivered packet.
+    latency: Tally,
+    /// Number of buffered packets.
+    queue_length: TimeWeighted,
+    /// Number of delivered packets.
+    delivered: Counter,
+    /// Number of packets lost during their transmission.
+    lost: Counter,
+    /// Number of packets dropped because the buffer was full.
+    dropped: Counter,
+    /// Input port for receiving packets.
+    input: InPort<Packet<P>>,
+    /// Output port for delivering packets.
+    output: OutPort<Packet<P>>,
+}
+
+impl<P: DynRef + Clone> Link<P> {
+    /// Creates a new lossless link with an unbounded buffer, the given propagation delay, and
+    /// the given bandwidth in bits per time unit (use [`f64::INFINITY`] to neglect transmission times).
+    /// It panics if the delay is negative or if the bandwidth is not positive.
+    pub fn new(name: &str, delay: f64, bandwidth: f64) -> Self {
+        assert!(delay >= 0., "delay of link {name} must not be negative");
+        assert!(bandwidth > 0., "bandwidth of link {name} must be positive");
+        let mut component = Component::new(name);
+        let input = component.add_in_port::<Packet<P>>("input");
+        let output = component.add_out_port::<Packet<P>>("output");
+        let latency = component.add_tally("latency");
+        let queue_length = component.add_time_weighted("queue_length", 0.);
+        let delivered = component.add_counter("delivered");
+        let lost = component.add_counter("lost");
+        let dropped = component.add_counter("dropped");
+        Self {
+            component,
+            delay,
+            bandwidth,
+            loss: 0.,
+            capacity: usize::MAX,
+            clock: 0.,
+            queue: VecDeque::new(),
+            transmitting: None,
+            in_flight: VecDeque::new(),
+            latency,
+            queue_length,
+            delivered,
+            lost,
+            dropped,
+            input,
+            output,
+        }
+    }
+
+    /// Sets the probability of losing a transmitted packet. It panics if it is not in `[0, 1]`.
+    pub fn loss(mut self, probability: f64) -> Self {
+        assert!(
+            (0. ..=1.).contains(&probability),
+            "loss probability of link {} must be in [0, 1]",
+            self.component.get_name()
+        );
+        self.loss = probability;
+        self
+    }
+
+    /// Limits the number of buffered packets (i.e., the packet being transmitted is not counted).
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Returns the number of buffered packets.
+    pub fn queue_len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns the time of the next transmission or delivery, or infinity if the link is idle.
+    fn next_event(&self) -> f64 {
+        let t_done = self.transmitting.as_ref().map_or(f64::INFINITY, |t| t.0);
+        let t_delivery = self.in_flight.front().map_or(f64::INFINITY, |t| t.0);
+        t_done.min(t_delivery)
+    }
+
+    /// Starts transmitting the next buffered packet if the link is free.
+    fn transmit(&mut self) {
+        if self.transmitting.is_none() {
+            if let Some((t_arrival, packet)) = self.queue.pop_front() {
+                let t_done = self.clock + packet.bits() / self.bandwidth;
+                self.transmitting = Some((t_done, t_arrival, packet));
+            }
+        }
+        self.queue_length
+            .update(self.clock, self.queue.len() as f64);
+    }
+}
+
+impl<P: DynRef + Clone> Atomic for Link<P> {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.clock = 0.;
+        self.queue.clear();
+        self.transmitting = None;
+        self.in_flight.clear();
+    }
+
+    fn lambda(&self) {
+        let t_next = self.next_event();
+        for (_, _, packet) in self.in_flight.iter().take_while(|(t, _, _)| *t == t_next) {
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.output.add_value(packet.clone()) };
+        }
+    }
+
+    fn delta_int(&mut self) {
+        self.clock = self.next_event();
+        while let Some((_, t_arrival, _)) =
+            self.in_flight.front().filter(|(t, _, _)| *t == self.clock)
+        {
+            self.latency.record(self.clock - t_arrival);
+            self.delivered.increment();
+            self.in_flight.pop_front();
+        }
+        if let Some((t_done, t_arrival, packet)) = self.transmitting.take() {
+            match t_done == self.clock {
+                true if self.loss > 0. && self.component.rng().bernoulli(self.loss) => {
+                    self.lost.increment()
+                }
+                true => self
+                    .in_flight
+                    .push_back((self.clock + self.delay, t_arrival, packet)),
+                false => self.transmitting = Some((t_done, t_arrival, packet)),
+            }
+        }
+        self.transmit();
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        self.clock += e;
+        // Safety: reading messages on atomic model's input port at delta_ext
+        let packets = unsafe { self.input.get_values() }.to_vec();
+        for packet in packets {
+            match self.queue.len() < self.capacity || self.transmitting.is_none() {
+                true => self.queue.push_back((self.clock, packet)),
+                false => self.dropped.increment(),
+            }
+            self.transmit();
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        self.next_event() - self.clock
+    }
+}
@@ -0,0 +1,186 @@
+use super::Packet;
+use crate::modeling::{Atomic, Component, InPort, OutPort};
+use crate::stats::{Counter, Tally};
+use crate::DynRef;
+use std::collections::HashMap;
+
+/// Atomic model of a network node (e.g., a host or a router) with a static routing table.
+/// It is meant to be the starting point of protocol models, which can wrap it or replace it.
+///
+/// The node has the following ports:
+///
+/// - `send`: packets to be sent by the node. The node sets their source address, their sequence number,
+///   and their creation time before routing them.
+/// - `input`: packets received from the network (i.e., from [`super::Link`]s).
+/// - `receive`: packets addressed to the node.
+/// - `output_0`, `output_1`, etc.: packets forwarded to other nodes (usually, through [`super::Link`]s).
+///
+/// Packets are routed without delay. Packets without a route to their destination are discarded.
+/// The node reports the following accumulators (see [`crate::simulation::RootCoordinator::stats`]):
+/// `latency` (time since every received packet was created), and `received`, `forwarded`, and `unroutable`
+/// (number of packets).
+pub struct Node<P: Clone = ()> {
+    /// Component of the atomic model.
+    component: Component,
+    /// Address of the node.
+    address: usize,
+    /// Index of the output port towards every destination address.
+    routes: HashMap<usize, usize>,
+    /// Index of the output port for destinations without a route, if any.
+    default_route: Option<usize>,
+    /// Sequence number of the next packet sent by the node.
+    sequence: u64,
+    /// Current simulation time.
+    clock: f64,
+    /// Packets to be forwarded and the index of their output port ([`None`] for the `receive` port).
+    pending: Vec<(Option<usize>, Packet<P>)>,
+    /// Time since the creation of every received packet.
+    latency: Tally,
+    /// Number of packets received.
+    received: Counter,
+    /// Number of packets forwarded.
+    forwarded: Counter,
+    /// Number of packets discarded for lack of a route.
+    unroutable: Counter,
+    /// Input port for packets to be sent.
+    send: InPort<Packet<P>>,
+    /// Input port for packets from the network.
+    input: InPort<Packet<P>>,
+    /// Output port for packets addressed to the node.
+    receive: OutPort<Packet<P>>,
+    /// Output ports for forwarding packets.
+    outputs: Vec<OutPort<Packet<P>>>,
+}
+
+impl<P: DynRef + Clone> Node<P> {
+    /// Creates a new node with the given address, `n_outputs` output ports, and an empty routing table.
+    pub fn new(name: &str, address: usize, n_outputs: usize) -> Self {
+        let mut component = Component::new(name);
+        let send = component.add_in_port::<Packet<P>>("send");
+        let input = component.add_in_port::<Packet<P>>("input");
+        let receive = component.add_out_port::<Packet<P>>("receive");
+        let outputs = (0..n_outputs)
+            .map(|i| component.add_out_port::<Packet<P>>(&format!("output_{i}")))
+            .collect();
+        let latency = component.add_tally("latency");
+        let received = component.add_counter("received");
+        let forwarded = component.add_counter("forwarded");
+        let unroutable = component.add_counter("unroutable");
+        Self {
+            component,
+            address,
+            routes: HashMap::new(),
+            default_route: None,
+            sequence: 0,
+            clock: 0.,
+            pending: Vec::new(),
+            latency,
+            received,
+            forwarded,
+            unroutable,
+            send,
+            input,
+            receive,
+            outputs,
+        }
+    }
+
+    /// Forwards the packets addressed to `dst` through the `port`-th output port.
+    /// It panics if the node does not have such output port.
+    pub fn route(mut self, dst: usize, port: usize) -> Self {
+        self.check_port(port);
+        self.routes.insert(dst, port);
+        self
+    }
+
+    /// Forwards the packets without a route through the `port`-th output port.
+    /// It panics if the node does not have such output port.
+    pub fn default_route(mut self, port: usize) -> Self {
+        self.check_port(port);
+        self.default_route = Some(port);
+        self
+    }
+
+    /// Returns the address of the node.
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
+    fn check_port(&self, port: usize) {
+        assert!(
+            port < self.outputs.len(),
+            "node {} does not have output port output_{port}",
+            self.component.get_name()
+        );
+    }
+
+    /// Delivers or forwards a packet.
+    fn forward(&mut self, packet: Packet<P>) {
+        if packet.dst == self.address {
+            self.latency.record(self.clock - packet.time);
+            self.received.increment();
+            self.pending.push((None, packet));
+            return;
+        }
+        match self.routes.get(&packet.dst).copied().or(self.default_route) {
+            Some(port) => {
+                self.forwarded.increment();
+                self.pending.push((Some(port), packet));
+            }
+            None => self.unroutable.increment(),
+        }
+    }
+}
+
+impl<P: DynRef + Clone> Atomic for Node<P> {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.sequence = 0;
+        self.clock = 0.;
+        self.pending.clear();
+    }
+
+    fn lambda(&self) {
+        for (port, packet) in &self.pending {
+            let port = match port {
+                Some(i) => &self.outputs[*i],
+                None => &self.receive,
+            };
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { port.add_value(packet.clone()) };
+        }
+    }
+
+    fn delta_int(&mut self) {
+        self.pending.clear();
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        self.clock += e;
+        // Safety: reading messages on atomic model's input ports at delta_ext
+        let (sent, received) = unsafe { (self.send.get_values(), self.input.get_values()) };
+        let (sent, received) = (sent.to_vec(), received.to_vec());
+        for mut packet in sent {
+            (packet.src, packet.id, packet.time) = (self.address, self.sequence, self.clock);
+            self.sequence += 1;
+            self.forward(packet);
+        }
+        for packet in received {
+            self.forward(packet);
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        match self.pending.is_empty() {
+            true => f64::INFINITY,
+            false => 0.,
+        }
+    }
+}
@@ -0,0 +1,486 @@
+//! Atomic models of hybrid systems, whose continuous dynamics are solved with classic numerical integration methods.
+//! Unlike the QSS models of [`crate::qss`], which quantize the state, [`HybridOde`] discretizes time
+//! with an adaptive step size, so it is suited for stiffer plants or plants with many state variables.
+
+use crate::modeling::{Atomic, Component, InPort, OutPort};
+
+/// Derivative function of a [`HybridOde`]: `f(t, x, u, dx)` writes the derivative of the state `x`
+/// at time `t` with inputs `u` into `dx`.
+pub type Derivative = Box<dyn Fn(f64, &[f64], &[f64], &mut [f64]) + Send + Sync>;
+
+/// Zero-crossing function `g(t, x, u)` of an event of a [`HybridOde`].
+type Condition = Box<dyn Fn(f64, &[f64], &[f64]) -> f64 + Send + Sync>;
+
+/// Function that updates the state of a [`HybridOde`] when an event fires.
+type Reset = Box<dyn Fn(&mut [f64]) + Send + Sync>;
+
+/// Size of the first integration step, and of the first step after every zero-crossing event.
+const INITIAL_STEP: f64 = 1e-3;
+
+/// Nodes of the Dormand-Prince (RK45) method.
+const C: [f64; 7] = [0., 1. / 5., 3. / 10., 4. / 5., 8. / 9., 1., 1.];
+/// Coefficients of the Dormand-Prince (RK45) method.
+const A: [[f64; 6]; 7] = [
+    [0., 0., 0., 0., 0., 0.],
+    [1. / 5., 0., 0., 0., 0., 0.],
+    [3. / 40., 9. / 40., 0., 0., 0., 0.],
+    [44. / 45., -56. / 15., 32. / 9., 0., 0., 0.],
+    [
+        19372. / 6561.,
+        -25360. / 2187.,
+        64448. / 6561.,
+        -212. / 729.,
+        0.,
+        0.,
+    ],
+    [
+        9017. / 3168.,
+        -355. / 33.,
+        46732. / 5247.,
+        49. / 176.,
+        -5103. / 18656.,
+        0.,
+    ],
+    [
+        35. / 384.,
+        0.,
+        500. / 1113.,
+        125. / 192.,
+        -2187. / 6784.,
+        11. / 84.,
+    ],
+];
+/// Difference between the fifth- and fourth-order weights of the Dormand-Prince (RK45) method.
+const E: [f64; 7] = [
+    35. / 384. - 5179. / 57600.,
+    0.,
+    500. / 1113. - 7571. / 16695.,
+    125. / 192. - 393. / 640.,
+    -2187. / 6784. + 92097. / 339200.,
+    11. / 84. - 187. / 2100.,
+    -1. / 40.,
+];
+
+/// Zero-crossing event of a [`HybridOde`].
+struct Event {
+    /// Name of the event, sent through the `event` port.
+    name: String,
+    /// Zero-crossing function.
+    condition: Condition,
+    /// Function that updates the state when the event fires.
+    reset: Reset,
+}
+
+/// Next internal transition of a [`HybridOde`].
+#[derive(Debug, Clone, PartialEq)]
+struct Step {
+    /// Time of the transition.
+    t: f64,
+    /// State after the transition.
+    x: Vec<f64>,
+    /// Whether the state is sent through the `output` port.
+    sample: bool,
+    /// Index of the zero-crossing event that fires, if any.
+    event: Option<usize>,
+}
+
+/// Atomic model of a hybrid system with continuous state `x`, piecewise-constant inputs `u`,
+/// and dynamics `dx/dt = f(t, x, u)` solved with the Dormand-Prince (RK45) method with adaptive step size.
+/// Every integration step is an internal transition, so the time advance of the model is the size of the next step.
+///
+/// The model receives its inputs through the `input_0`, `input_1`, etc. ports (every input keeps its last value),
+/// and sends its state through its `output` port every `period` time units (see [`HybridOde::sample`])
+/// or, by default, after every integration step. Zero-crossing events (see [`HybridOde::event`]) are located
+/// within every step and trigger an internal transition that updates the state, sends it through the `output` port,
+/// and sends the name of the event through the `event` port.
+pub struct HybridOde {
+    /// Component of the atomic model.
+    component: Component,
+    /// Derivative function.
+    derivative: Derivative,
+    /// Zero-crossing events.
+    events: Vec<Event>,
+    /// Initial state.
+    initial: Vec<f64>,
+    /// Relative tolerance of the integration steps.
+    rel_tol: f64,
+    /// Absolute tolerance of the integration steps.
+    abs_tol: f64,
+    /// Maximum step size.
+    max_step: f64,
+    /// Sampling period of the output, if any.
+    period: Option<f64>,
+    /// Current simulation time.
+    t: f64,
+    /// Current state.
+    x: Vec<f64>,
+    /// Current inputs.
+    u: Vec<f64>,
+    /// Size of the next integration step.
+    h: f64,
+    /// Number of samples sent.
+    n_samples: u64,
+    /// Next internal transition.
+    next: Option<Step>,
+    /// Input ports.
+    inputs: Vec<InPort<f64>>,
+    /// Output port for sending the state.
+    output: OutPort<Vec<f64>>,
+    /// Output port for sending the names of the zero-crossing events.
+    event: OutPort<String>,
+}
+
+impl HybridOde {
+    /// Creates a new hybrid system with the given initial state, `n_inputs` input ports (initially 0),
+    /// and derivative function. By default, the relative and absolute tolerances are `1e-6` and `1e-9`,
+    /// the step size is not bounded, and the state is sent after every integration step.
+    pub fn new(
+        name: &str,
+        initial: &[f64],
+        n_inputs: usize,
+        derivative: impl Fn(f64, &[f64], &[f64], &mut [f64]) + Send + Sync + 'static,
+    ) -> Self {
+        let mut component = Component::new(name);
+        let inputs = (0..n_inputs)
+            .map(|i| component.add_in_port::<f64>(&format!("input_{i}")))
+            .collect();
+        let output = component.add_out_port::<Vec<f64>>("output");
+        let event = component.add_out_port::<String>("event");
+        Self {
+            component,
+            derivative: Box::new(derivative),
+            events: Vec::new(),
+            initial: initial.to_vec(),
+            rel_tol: 1e-6,
+            abs_tol: 1e-9,
+            max_step: f64::INFINITY,
+            period: None,
+            t: 0.,
+            x: initial.to_vec(),
+            u: vec![0.; n_inputs],
+            h: INITIAL_STEP,
+            n_samples: 0,
+            next: None,
+            inputs,
+            output,
+            event,
+        }
+    }
+
+    /// Sets the relative and absolute tolerances of the local error of the integration steps.
+    /// It panics if they are not positive.
+    pub fn tolerance(mut self, rel_tol: f64, abs_tol: f64) -> Self {
+        assert!(rel_tol > 0. && abs_tol > 0., "tolerances must be positive");
+        (self.rel_tol, self.abs_tol) = (rel_tol, abs_tol);
+        self
+    }
+
+    /// Limits the size of the integration steps (e.g., to capture explicit changes of the derivative over time).
+    /// It panics if it is not positive.
+    pub fn max_step(mut self, max_step: f64) -> Self {
+        assert!(max_step > 0., "maximum step size must be positive");
+        self.max_step = max_step;
+        self.h = self.h.min(max_step);
+        self
+    }
+
+    /// Sends the state through the `output` port every `period` time units (starting at time 0)
+    /// instead of after every integration step. It panics if the period is not positive.
+    pub fn sample(mut self, period: f64) -> Self {
+        assert!(period > 0., "sampling period must be positive");
+        self.period = Some(period);
+        self
+    }
+
+    /// Adds a zero-crossing event that fires when `condition(t, x, u)` goes from a positive value
+    /// to zero or a negative value (e.g., the height of a falling ball). When the event fires,
+    /// `reset` updates the state (e.g., to reverse the velocity of the ball).
+    /// If several events fire within the same step, only the earliest one is considered.
+    /// Crossings are only detected at the end of every integration step, so limit the step size
+    /// (see [`HybridOde::max_step`]) if the condition may cross zero twice within a step.
+    pub fn event(
+        mut self,
+        name: &str,
+        condition: impl Fn(f64, &[f64], &[f64]) -> f64 + Send + Sync + 'static,
+        reset: impl Fn(&mut [f64]) + Send + Sync + 'static,
+    ) -> Self {
+        self.events.push(Event {
+            name: name.to_string(),
+            condition: Box::new(condition),
+            reset: Box::new(reset),
+        });
+        self
+    }
+
+    /// Returns the current state.
+    pub fn state(&self) -> &[f64] {
+        &self.x
+    }
+
+    /// Performs an integration step of size `h` from the current state.
+    /// It returns the new state and the norm of the local error relative to the tolerances.
+    fn rk_step(&self, h: f64) -> (Vec<f64>, f64) {
+        let n = self.x.len();
+        let mut k = vec![vec![0.; n]; 7];
+        let mut x = self.x.clone();
+        for stage in 0..7 {
+            for (i, x_i) in x.iter_mut().enumerate() {
+                let increment: f64 = (0..stage).map(|j| A[stage][j] * k[j][i]).sum();
+                *x_i = self.x[i] + h * increment;
+            }
+            (self.derivative)(self.t + C[stage] * h, &x, &self.u, &mut k[stage]);
+        }
+        // the last stage is evaluated at the new state
+        let error = (0..n)
+            .map(|i| {
+                let error = h * (0..7).map(|j| E[j] * k[j][i]).sum::<f64>();
+                let scale = self.abs_tol + self.rel_tol * self.x[i].abs().max(x[i].abs());
+                (error / scale).abs()
+            })
+            .fold(0., f64::max);
+        (x, error)
+    }
+
+    /// Returns the value of the condition of an event at a given time and state.
+    fn condition(&self, event: usize, t: f64, x: &[f64]) -> f64 {
+        (self.events[event].condition)(t, x, &self.u)
+    }
+
+    /// Plans the next internal transition: the next sample, the next zero-crossing event,
+    /// or the next integration step.
+    fn plan(&mut self) {
+        let t_sample = match self.period {
+            Some(period) => self.n_samples as f64 * period,
+            None => f64::INFINITY,
+        };
+        if t_sample <= self.t {
+            let (t, x) = (self.t, self.x.clone());
+            self.next = Some(Step {
+                t,
+                x,
+                sample: true,
+                event: None,
+            });
+            return;
+        }
+        loop {
+            let h = self.h.min(t_sample - self.t);
+            if !h.is_finite() {
+                self.next = None;
+                return;
+            }
+            let (x, error) = self.rk_step(h);
+            let factor = match error {
+                0. => 5.,
+                error => (0.9 * error.powf(-0.2)).clamp(0.2, 5.),
+            };
+            if error > 1. {
+                self.h = h * factor;
+                continue;
+            }
+            // steps shortened to reach the next sample do not shrink the next steps
+            self.h = match h < self.h {
+                true => self.h.max(h * factor),
+                false => h * factor,
+            }
+            .min(self.max_step);
+            let crossing = (0..self.events.len())
+                .filter(|&i| {
+                    self.condition(i, self.t, &self.x) > 0.
+                        && self.condition(i, self.t + h, &x) <= 0.
+                })
+                .map(|i| {
+                    let (h, x) = self.locate(i, h);
+                    (h, x, i)
+                })
+                .min_by(|a, b| a.0.total_cmp(&b.0));
+            self.next = Some(match crossing {
+                Some((h, mut x, i)) => {
+                    (self.events[i].reset)(&mut x);
+                    Step {
+                        t: self.t + h,
+                        x,
+                        sample: true,
+                        event: Some(i),
+                    }
+                }
+                None if h == t_sample - self.t => Step {
+                    t: t_sample,
+                    x,
+                    sample: true,
+                    event: None,
+                },
+                None => Step {
+                    t: self.t + h,
+                    x,
+                    sample: self.period.is_none(),
+                    event: None,
+                },
+            });
+            return;
+        }
+    }
+
+    /// Locates the zero crossing of an event within a step of size `h` by bisection.
+    /// It returns the time elapsed until the crossing and the state right after it.
+    fn locate(&self, event: usize, h: f64) -> (f64, Vec<f64>) {
+        let (mut low, mut high) = (0., h);
+        let mut x = self.rk_step(h).0;
+        while high - low > 1e-12 * (1. + self.t.abs()) {
+            let mid = (low + high) / 2.;
+            let x_mid = self.rk_step(mid).0;
+            match self.condition(event, self.t + mid, &x_mid) > 0. {
+                true => low = mid,
+                false => (high, x) = (mid, x_mid),
+            }
+        }
+        (high, x)
+    }
+
+    /// Integrates the state until time `t` without looking for zero crossings.
+    fn advance(&mut self, t: f64) {
+        while self.t < t {
+            let h = self.h.min(t - self.t);
+            let (x, error) = self.rk_step(h);
+            if error > 1. {
+                self.h = h * (0.9 * error.powf(-0.2)).max(0.2);
+                continue;
+            }
+            (self.t, self.x) = (self.t + h, x);
+        }
+        self.t = t;
+    }
+}
+
+impl Atomic for HybridOde {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.t = 0.;
+        self.x = self.initial.clone();
+        self.u.iter_mut().for_each(|u| *u = 0.);
+        self.h = self.max_step.min(INITIAL_STEP);
+        self.n_samples = 0;
+        self.plan();
+    }
+
+    fn lambda(&self) {
+        let Some(next) = &self.next else { return };
+        if next.sample {
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.output.add_value(next.x.clone()) };
+        }
+        if let Some(i) = next.event {
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.event.add_value(self.events[i].name.clone()) };
+        }
+    }
+
+    fn delta_int(&mut self) {
+        let next = self.next.take().unwrap();
+        if self
+            .period
+            .is_some_and(|period| next.t >= self.n_samples as f64 * period)
+        {
+            self.n_samples += 1;
+        }
+        if next.event.is_some() {
+            // the state is discontinuous, so the step size is adapted again from scratch
+            self.h = self.max_step.min(INITIAL_STEP);
+        }
+        (self.t, self.x) = (next.t, next.x);
+        self.plan();
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        self.advance(self.t + e);
+        for (u, input) in self.u.iter_mut().zip(&self.inputs) {
+            // Safety: reading messages on atomic model's input port at delta_ext
+            if let Some(value) = unsafe { input.get_values() }.last() {
+                *u = *value;
+            }
+        }
+        self.plan();
+    }
+
+    fn ta(&self) -> f64 {
+        match &self.next {
+            Some(next) => next.t - self.t,
+            None => f64::INFINITY,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::Coupled;
+    use crate::scenario::{ScenarioPlayer, ScheduledEvent};
+    use crate::simulation::RootCoordinator;
+    use crate::tracing::{MemorySink, TracePhase, ValueFormatter};
+
+    /// Simulates a model until `t_end` and returns the time and value of the messages sent through a port.
+    fn outputs(coupled: Coupled, port: &str, t_end: f64) -> Vec<(f64, String)> {
+        let sink = MemorySink::new();
+        let mut simulator = RootCoordinator::new(coupled);
+        let formatter = ValueFormatter::new().debug::<Vec<f64>>().debug::<String>();
+        simulator.add_trace_sinks(vec![Box::new(sink.clone())], Some(formatter));
+        simulator.simulate(t_end);
+        (sink.records().into_iter())
+            .filter(|r| r.phase == TracePhase::Output && r.path == "root.ode")
+            .filter(|r| r.port.as_deref() == Some(port))
+            .map(|r| (r.t, r.values[0].clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_bouncing_ball() {
+        // Ball dropped from a height of 10 m that keeps 80% of its speed after every bounce
+        let g = 9.81;
+        let ball = HybridOde::new("ode", &[10., 0.], 0, move |_, x, _, dx| {
+            (dx[0], dx[1]) = (x[1], -g);
+        })
+        .event(
+            "bounce",
+            |_, x, _| x[0],
+            |x| (x[0], x[1]) = (0., -0.8 * x[1]),
+        );
+        let mut coupled = Coupled::new("root");
+        coupled.add_component(Box::new(ball));
+        let bounces = outputs(coupled, "event", 5.);
+
+        let t_1 = (2. * 10. / g).sqrt();
+        let t_2 = t_1 + 2. * 0.8 * t_1;
+        assert_eq!(2, bounces.len());
+        for ((t, name), expected) in bounces.into_iter().zip([t_1, t_2]) {
+            assert_eq!("\"bounce\"", name);
+            assert!((t - expected).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_sampled_inputs() {
+        // Integrator of its input, which changes from 0 to 2 at t = 1
+        let ode = HybridOde::new("ode", &[1.], 1, |_, _, u, dx| dx[0] = u[0]).sample(0.5);
+        let schedule = vec![ScheduledEvent::new(1., "output", 2.)];
+        let mut coupled = Coupled::new("root");
+        coupled.add_component(Box::new(ScenarioPlayer::new("player", schedule)));
+        coupled.add_component(Box::new(ode));
+        coupled.add_ic("player", "output", "ode", "input_0");
+        let samples = outputs(coupled, "output", 2.25);
+
+        let expected = [(0., 1.), (0.5, 1.), (1., 1.), (1.5, 2.), (2., 3.)];
+        assert_eq!(expected.len(), samples.len());
+        for ((t, x), (t_expected, x_expected)) in samples.into_iter().zip(expected) {
+            assert_eq!(t_expected, t);
+            let x: f64 = x.trim_matches(['[', ']']).parse().unwrap();
+            assert!((x - x_expected).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use crate::modeling::{Atomic, Component, Coupled, InPort, OutPort};
+use crate::simulation::RootCoordinator;
+use pyo3::call::PyCallArgs;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use std::fmt;
+
+/// Message exchanged by models defined in Python. It wraps an arbitrary Python object.
+pub struct PyMessage(Py<PyAny>);
+
+impl Clone for PyMessage {
+    fn clone(&self) -> Self {
+        Python::attach(|py| Self(self.0.clone_ref(py)))
+    }
+}
+
+impl fmt::Debug for PyMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Python::attach(|py| match self.0.bind(py).repr() {
+            Ok(repr) => write!(f, "{repr}"),
+            Err(_) => write!(f, "<unprintable Python object>"),
+        })
+    }
+}
+
+/// Atomic model whose behavior is defined by a Python object.
+/// The object must implement the methods `delta_int()`, `delta_ext(e, inputs)`, `lambda_()`, and `ta()`.
+/// Optionally, it may also implement `delta_conf(inputs)`, `start()`, and `stop()`.
+/// Inputs are dictionaries that map input port names to lists of messages, and `lambda_()` must return
+/// a dictionary that maps output port names to lists of messages (or `None` if there is no output).
+struct PyAtomic {
+    /// Component of the atomic model.
+    component: Component,
+    /// Python object that defines the behavior of the model.
+    model: Py<PyAny>,
+    /// Input ports of the model and their names.
+    inputs: Vec<(String, InPort<PyMessage>)>,
+    /// Output ports of the model and their names.
+    outputs: Vec<(String, OutPort<PyMessage>)>,
+}
+
+impl PyAtomic {
+    /// Calls a method of the Python object. It panics if the method raises an exception.
+    fn call<'py, A>(&self, py: Python<'py>, method: &str, args: A) -> Bound<'py, PyAny>
+    where
+        A: PyCallArgs<'py>,
+    {
+        match self.model.bind(py).call_method1(method, args) {
+            Ok(result) => result,
+            Err(e) => {
+                let name = self.component.get_name();
+                panic!("Python model {name} raised an exception in {method}: {e}")
+            }
+        }
+    }
+
+    /// Calls a method of the Python object only if it is defined.
+    fn call_optional<'py, A>(&self, py: Python<'py>, method: &str, args: A) -> bool
+    where
+        A: PyCallArgs<'py>,
+    {
+        let defined = self.model.bind(py).hasattr(method).unwrap_or(false);
+        if defined {
+            self.call(py, method, args);
+        }
+        defined
+    }
+
+    /// Returns a dictionary with the messages of the non-empty input ports.
+    fn collect_inputs<'py>(&self, py: Python<'py>) -> Bound<'py, PyDict> {
+        let dict = PyDict::new(py);
+        for (name, port) in &self.inputs {
+            // Safety: reading messages on atomic model's input port at delta_ext or delta_conf
+            let values = unsafe { port.get_values() };
+            if !values.is_empty() {
+                let list = PyList::new(py, values.iter().map(|v| v.0.clone_ref(py))).unwrap();
+                dict.set_item(name, list).unwrap();
+            }
+        }
+        dict
+    }
+}
+
+impl Atomic for PyAtomic {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        Python::attach(|py| self.call_optional(py, "start", ()));
+    }
+
+    fn stop(&mut self) {
+        Python::attach(|py| self.call_optional(py, "stop", ()));
+    }
+
+    fn lambda(&self) {
+        Python::attach(|py| {
+            let result = self.call(py, "lambda_", ());
+            if result.is_none() {
+                return;
+            }
+            let name = self.component.get_name();
+            let dict = result
+                .cast::<PyDict>()
+                .unwrap_or_else(|_| panic!("lambda_ of Python model {name} must return a dict"));
+            for (port, values) in dict.iter() {
+                let port: String = port.extract().expect("port names must be strings");
+                let (_, port) = self
+                    .outputs
+                    .iter()
+                    .find(|(p, _)| *p == port)
+                    .unwrap_or_else(|| panic!("Python model {name} has no output port {port}"));
+                for value in values.try_iter().expect("port values must be iterable") {
+                    let value = PyMessage(value.unwrap().unbind());
+                    // Safety: adding messages on atomic model's output port at lambda
+                    unsafe { port.add_value(value) };
+                }
+            }
+        });
+    }
+
+    fn delta_int(&mut self) {
+        Python::attach(|py| {
+            self.call(py, "delta_int", ());
+        });
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        Python::attach(|py| {
+            let inputs = self.collect_inputs(py);
+            self.call(py, "delta_ext", (e, inputs));
+        });
+    }
+
+    fn delta_conf(&mut self) {
+        Python::attach(|py| {
+            let inputs = self.collect_inputs(py);
+            if !self.call_optional(py, "delta_conf", (inputs.clone(),)) {
+                self.call(py, "delta_int", ());
+                self.call(py, "delta_ext", (0., inputs));
+            }
+        });
+    }
+
+    fn ta(&self) -> f64 {
+        Python::attach(|py| {
+            let name = self.component.get_name();
+            self.call(py, "ta", ())
+                .extract()
+                .unwrap_or_else(|_| panic!("ta of Python model {name} must return a float"))
+        })
+    }
+}
+
+/// Python handle of an atomic model. The model is moved into its parent when added to a coupled model.
+#[pyclass(name = "Atomic", module = "xdevs", unsendable)]
+struct PyAtomicHandle(Option<PyAtomic>);
+
+#[pymethods]
+impl PyAtomicHandle {
+    /// Creates a new atomic model from a Python object and the names of its input and output ports.
+    #[new]
+    #[pyo3(signature = (name, model, in_ports=Vec::new(), out_ports=Vec::new()))]
+    fn new(name: &str, model: Py<PyAny>, in_ports: Vec<String>, out_ports: Vec<String>) -> Self {
+        let mut component = Component::new(name);
+        let inputs = in_ports
+            .into_iter()
+            .map(|p| (p.clone(), component.add_in_port(&p)))
+            .collect();
+        let outputs = out_ports
+            .into_iter()
+            .map(|p| (p.clone(), component.add_out_port(&p)))
+            .collect();
+        Self(Some(PyAtomic {
+            component,
+            model,
+            inputs,
+            outputs,
+        }))
+    }
+}
+
+/// Python handle of a coupled model. The model is moved into its parent when added to another coupled model,
+/// or into the root coordinator when creating a simulator.
+#[pyclass(name = "Coupled", module = "xdevs", unsendable)]
+struct PyCoupledHandle(Option<Coupled>);
+
+impl PyCoupledHandle {
+    /// Returns a mutable reference to the coupled model.
+    fn model(&mut self) -> PyResult<&mut Coupled> {
+        self.0
+            .as_mut()
+            .ok_or_else(|| PyValueError::new_err("coupled model was moved"))
+    }
+}
+
+#[pymethods]
+impl PyCoupledHandle {
+    /// Creates a new coupled model.
+    #[new]
+    fn new(name: &str) -> Self {
+        Self(Some(Coupled::new(name)))
+    }
+
+    /// Adds a new input port to the coupled model.
+    fn add_in_port(&mut self, name: &str) -> PyResult<()> {
+        self.model()?.add_in_port::<PyMessage>(name);
+        Ok(())
+    }
+
+    /// Adds a new output port to the coupled model.
+    fn add_out_port(&mut self, name: &str) -> PyResult<()> {
+        self.model()?.add_out_port::<PyMessage>(name);
+        Ok(())
+    }
+
+    /// Moves an atomic or coupled model into the coupled model.
+    fn add_component(&mut self, component: &Bound<'_, PyAny>) -> PyResult<()> {
+        let moved = || PyValueError::new_err("component was already moved");
+        if let Ok(atomic) = component.cast::<PyAtomicHandle>() {
+            let atomic = atomic.borrow_mut().0.take().ok_or_else(moved)?;
+            self.model()?.add_component(Box::new(atomic));
+        } else if let Ok(coupled) = component.cast::<PyCoupledHandle>() {
+            let coupled = coupled.borrow_mut().0.take().ok_or_else(moved)?;
+            self.model()?.add_component(Box::new(coupled));
+        } else {
+            return Err(PyValueError::new_err("component must be Atomic or Coupled"));
+        }
+        Ok(())
+    }
+
+    /// Adds a new EIC to the coupled model.
+    fn add_eic(&mut self, port_from: &str, component_to: &str, port_to: &str) -> PyResult<()> {
+        self.model()?.add_eic(port_from, component_to, port_to);
+        Ok(())
+    }
+
+    /// Adds a new IC to the coupled model.
+    fn add_ic(
+        &mut self,
+        component_from: &str,
+        port_from: &str,
+        component_to: &str,
+        port_to: &str,
+    ) -> PyResult<()> {
+        let model = self.model()?;
+        model.add_ic(component_from, port_from, component_to, port_to);
+        Ok(())
+    }
+
+    /// Adds a new EOC to the coupled model.
+    fn add_eoc(&mut self, component_from: &str, port_from: &str, port_to: &str) -> PyResult<()> {
+        self.model()?.add_eoc(component_from, port_from, port_to);
+        Ok(())
+    }
+}
+
+/// Wrapper for moving the root coordinator into [`Python::detach`].
+struct Detached<'a>(&'a mut RootCoordinator<Coupled>);
+
+// Safety: the closure passed to Python::detach runs in the calling thread,
+// and the coordinator does not contain any reference bound to the interpreter
+unsafe impl Send for Detached<'_> {}
+
+/// Python handle of a root coordinator.
+#[pyclass(name = "RootCoordinator", module = "xdevs", unsendable)]
+struct PyRootCoordinator(RootCoordinator<Coupled>);
+
+#[pymethods]
+impl PyRootCoordinator {
+    /// Creates a new root coordinator. The coupled model is moved into the coordinator.
+    #[new]
+    fn new(model: &mut PyCoupledHandle) -> PyResult<Self> {
+        let model = model
+            .0
+            .take()
+            .ok_or_else(|| PyValueError::new_err("coupled model was moved"))?;
+        Ok(Self(RootCoordinator::new(model)))
+    }
+
+    /// Runs a simulation for a given period of time.
+    /// The interpreter is released during the simulation, so models simulated
+    /// in parallel threads can call their Python callbacks.
+    #[pyo3(signature = (t_end=f64::INFINITY))]
+    fn simulate(&mut self, py: Python<'_>, t_end: f64) {
+        let simulator = Detached(&mut self.0);
+        py.detach(move || {
+            let simulator = simulator;
+            simulator.0.simulate(t_end)
+        });
+    }
+}
+
+/// Python module with the xDEVS bindings.
+#[pymodule]
+fn xdevs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyAtomicHandle>()?;
+    m.add_class::<PyCoupledHandle>()?;
+    m.add_class::<PyRootCoordinator>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    #[test]
+    fn test_python_models() {
+        Python::initialize();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "xdevs").unwrap();
+            xdevs(&module).unwrap();
+            let locals = PyDict::new(py);
+            locals.set_item("xdevs", module).unwrap();
+            let code = CString::new(
+                r#"
+class Generator:
+    def __init__(self, n):
+        self.n = n
+    def lambda_(self):
+        return {"output": [self.n]}
+    def delta_int(self):
+        self.n -= 1
+    def delta_ext(self, e, inputs):
+        pass
+    def ta(self):
+        return 1.0 if self.n > 0 else float("inf")
+
+class Sink:
+    def __init__(self):
+        self.received = []
+    def lambda_(self):
+        return None
+    def delta_int(self):
+        pass
+    def delta_ext(self, e, inputs):
+        self.received.extend(inputs["input"])
+    def ta(self):
+        return float("inf")
+
+sink = Sink()
+coupled = xdevs.Coupled("coupled")
+coupled.add_component(xdevs.Atomic("generator", Generator(3), out_ports=["output"]))
+coupled.add_component(xdevs.Atomic("sink", sink, in_ports=["input"]))
+coupled.add_ic("generator", "output", "sink", "input")
+xdevs.RootCoordinator(coupled).simulate()
+"#,
+            )
+            .unwrap();
+            py.run(&code, None, Some(&locals)).unwrap();
+            let received: Vec<i32> = py
+                .eval(c"sink.received", None, Some(&locals))
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(vec![3, 2, 1], received);
+        });
+    }
+}
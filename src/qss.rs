@@ -0,0 +1,215 @@
+mod dynamics;
+mod function;
+mod integrator;
+
+use crate::modeling::Coupled;
+pub use dynamics::{SystemDynamics, Variables};
+pub use function::{Function, StaticFunction};
+use integrator::Integrator;
+pub use integrator::{Qss1Integrator, Qss2Integrator};
+
+/// Messages exchanged by QSS models: value and slope of a piecewise polynomial trajectory
+/// at the time the message is sent. First-order models always send null slopes.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Signal {
+    /// Value of the trajectory.
+    pub value: f64,
+    /// Slope of the trajectory.
+    pub slope: f64,
+}
+
+impl Signal {
+    /// Creates a new signal.
+    #[inline]
+    pub fn new(value: f64, slope: f64) -> Self {
+        Self { value, slope }
+    }
+}
+
+/// Quantized state system methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QssMethod {
+    /// First-order QSS. It uses [`Qss1Integrator`] atomic models.
+    Qss1,
+    /// Second-order QSS. It uses [`Qss2Integrator`] atomic models.
+    Qss2,
+}
+
+/// State variable of an [`OdeSystem`].
+struct StateVariable {
+    name: String,
+    initial: f64,
+    quantum: f64,
+    derivative: Function,
+}
+
+/// Builder of coupled models that solve systems of ordinary differential equations with QSS methods.
+/// Each state variable `x` is simulated by an integrator named `x` and a static function named `dx`,
+/// which computes the derivative of `x` from the values of all the state variables.
+/// The resulting coupled model sends the trajectory of `x` on its output port `x`.
+pub struct OdeSystem {
+    /// Name of the resulting coupled model.
+    name: String,
+    /// QSS method used by the integrators.
+    method: QssMethod,
+    /// State variables of the system, in declaration order.
+    states: Vec<StateVariable>,
+}
+
+impl OdeSystem {
+    /// Creates a new, empty system of ordinary differential equations.
+    pub fn new(name: &str, method: QssMethod) -> Self {
+        Self {
+            name: name.to_string(),
+            method,
+            states: Vec::new(),
+        }
+    }
+
+    /// Adds a new state variable with its initial value, its quantum, and its derivative function.
+    /// The derivative function receives the values of all the state variables in declaration order.
+    pub fn state(
+        mut self,
+        name: &str,
+        initial: f64,
+        quantum: f64,
+        derivative: impl Fn(&[f64]) -> f64 + Send + Sync + 'static,
+    ) -> Self {
+        self.states.push(StateVariable {
+            name: name.to_string(),
+            initial,
+            quantum,
+            derivative: Box::new(derivative),
+        });
+        self
+    }
+
+    /// Creates the coupled model of the system.
+    pub fn build(self) -> Coupled {
+        match self.method {
+            QssMethod::Qss1 => self.build_with::<Qss1Integrator>(),
+            QssMethod::Qss2 => self.build_with::<Qss2Integrator>(),
+        }
+    }
+
+    /// Creates the coupled model of the system using integrators of type `I`.
+    fn build_with<I: Integrator>(self) -> Coupled {
+        let mut coupled = Coupled::new(&self.name);
+        let initial: Vec<f64> = self.states.iter().map(|s| s.initial).collect();
+        let names: Vec<_> = self.states.iter().map(|s| s.name.clone()).collect();
+        for state in self.states {
+            coupled.add_out_port::<Signal>(&state.name);
+            let integrator = I::create(&state.name, state.initial, state.quantum);
+            let function_name = format!("d{}", state.name);
+            let function = StaticFunction::new(&function_name, &initial, state.derivative);
+            coupled.add_component(Box::new(integrator));
+            coupled.add_component(Box::new(function));
+        }
+        for name in &names {
+            coupled.add_ic(&format!("d{name}"), "output", name, "input");
+            coupled.add_eoc(name, "output", name);
+        }
+        for (i, name) in names.iter().enumerate() {
+            for function in &names {
+                coupled.add_ic(
+                    name,
+                    "output",
+                    &format!("d{function}"),
+                    &format!("input_{i}"),
+                );
+            }
+        }
+        coupled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{Listener, PortValues, RootCoordinator};
+    use std::sync::{Arc, Mutex};
+
+    /// Listener that stores the last signal of each output port of the root model.
+    struct LastSignals(Arc<Mutex<Vec<(String, f64, Signal)>>>);
+
+    impl Listener for LastSignals {
+        fn on_output(&mut self, t: f64, path: &str, port: &str, values: &PortValues) {
+            if path == "ode" {
+                let signal = *values.downcast::<Signal>().unwrap().last().unwrap();
+                let mut signals = self.0.lock().unwrap();
+                signals.retain(|(p, _, _)| p != port);
+                signals.push((port.to_string(), t, signal));
+            }
+        }
+    }
+
+    /// Simulates the system until `t_end` and returns the value of each state variable at `t_end`.
+    fn simulate(system: Coupled, t_end: f64) -> Vec<(String, f64)> {
+        let signals = Arc::new(Mutex::new(Vec::new()));
+        let mut simulator = RootCoordinator::new(system);
+        simulator.add_listener(Box::new(LastSignals(signals.clone())));
+        simulator.simulate(t_end);
+        let signals = signals.lock().unwrap();
+        signals
+            .iter()
+            .map(|(port, t, s)| (port.clone(), s.value + s.slope * (t_end - t)))
+            .collect()
+    }
+
+    #[test]
+    fn test_exponential_decay() {
+        for (method, tolerance) in [(QssMethod::Qss1, 1e-2), (QssMethod::Qss2, 1e-3)] {
+            let system = OdeSystem::new("ode", method).state("x", 1., 1e-3, |x| -x[0]);
+            let x = simulate(system.build(), 1.);
+            assert_eq!("x", x[0].0);
+            assert!((x[0].1 - (-1f64).exp()).abs() < tolerance);
+        }
+    }
+
+    #[test]
+    fn test_oscillator() {
+        let system = OdeSystem::new("ode", QssMethod::Qss2)
+            .state("x", 1., 1e-4, |s| s[1])
+            .state("v", 0., 1e-4, |s| -s[0]);
+        let t_end = std::f64::consts::PI / 2.;
+        let states = simulate(system.build(), t_end);
+        assert_eq!(2, states.len());
+        for (name, value) in states {
+            match name.as_str() {
+                "x" => assert!((value - t_end.cos()).abs() < 1e-2),
+                "v" => assert!((value + t_end.sin()).abs() < 1e-2),
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_system_dynamics() {
+        // Logistic growth of a population and its resources, which are consumed by the population
+        let (rate, capacity) = (0.5, 1000.);
+        let system = SystemDynamics::new("ode", QssMethod::Qss2)
+            .stock("population", 10., 1e-2)
+            .stock("resources", 1000., 1e-2)
+            .auxiliary("crowding", move |v| v["population"] / capacity)
+            .flow("births", None, Some("population"), move |v| {
+                rate * v["population"] * (1. - v["crowding"])
+            })
+            .flow("consumption", Some("resources"), None, |v| v["births"]);
+        let t_end = 10.;
+        let states: std::collections::HashMap<_, _> =
+            simulate(system.build(), t_end).into_iter().collect();
+        let expected = capacity / (1. + (capacity / 10. - 1.) * (-rate * t_end).exp());
+        assert!((states["population"] / expected - 1.).abs() < 1e-3);
+        // every birth consumes a unit of resources
+        assert!((states["population"] + states["resources"] - 1010.).abs() < 1e-1);
+    }
+
+    #[test]
+    #[should_panic(expected = "stock water does not exist")]
+    fn test_system_dynamics_invalid() {
+        SystemDynamics::new("ode", QssMethod::Qss1)
+            .stock("tank", 1., 1e-3)
+            .flow("outflow", Some("tank"), Some("water"), |v| v["tank"])
+            .build();
+    }
+}
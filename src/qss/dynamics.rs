@@ -0,0 +1,181 @@
+use super::{OdeSystem, QssMethod};
+use crate::modeling::Coupled;
+use std::collections::HashMap;
+use std::ops::Index;
+use std::sync::Arc;
+
+/// Equation of a flow or an auxiliary variable.
+type Equation = Box<dyn Fn(&Variables) -> f64 + Send + Sync>;
+
+/// Values of the variables of a [`SystemDynamics`] model, indexed by their names (e.g., `v["population"]`).
+/// Equations can read the values of every stock, and of the flows and auxiliary variables declared before them.
+pub struct Variables<'a> {
+    /// Index of every variable.
+    indices: &'a HashMap<String, usize>,
+    /// Values of the variables. Variables that are not computed yet are NaN.
+    values: &'a [f64],
+}
+
+impl Variables<'_> {
+    /// Returns the value of a variable. It panics if the variable does not exist.
+    pub fn get(&self, name: &str) -> f64 {
+        self[name]
+    }
+}
+
+impl Index<&str> for Variables<'_> {
+    type Output = f64;
+
+    fn index(&self, name: &str) -> &f64 {
+        match self.indices.get(name) {
+            Some(&i) => &self.values[i],
+            None => panic!("variable {name} does not exist"),
+        }
+    }
+}
+
+/// Stock of a [`SystemDynamics`] model.
+struct Stock {
+    name: String,
+    initial: f64,
+    quantum: f64,
+}
+
+/// Equations of a [`SystemDynamics`] model, shared by the derivative functions of all its stocks.
+struct Equations {
+    /// Index of every variable (stocks first, and then flows and auxiliary variables in declaration order).
+    indices: HashMap<String, usize>,
+    /// Equations of flows and auxiliary variables in declaration order.
+    equations: Vec<Equation>,
+    /// Inflows (`+1`) and outflows (`-1`) of every stock, as pairs of variable index and sign.
+    flows: Vec<Vec<(usize, f64)>>,
+}
+
+impl Equations {
+    /// Returns the derivative of the `i`-th stock given the values of all the stocks.
+    fn derivative(&self, i: usize, stocks: &[f64]) -> f64 {
+        let mut values = stocks.to_vec();
+        values.resize(self.indices.len(), f64::NAN);
+        for (j, equation) in self.equations.iter().enumerate() {
+            let variables = Variables {
+                indices: &self.indices,
+                values: &values,
+            };
+            values[stocks.len() + j] = equation(&variables);
+        }
+        self.flows[i]
+            .iter()
+            .map(|(j, sign)| sign * values[*j])
+            .sum()
+    }
+}
+
+/// Builder of system dynamics (stock-and-flow) models that are solved with QSS methods.
+/// Stocks accumulate the difference between their inflows and outflows, and flows and auxiliary
+/// variables are computed from the values of the stocks and of previously declared flows and auxiliary variables.
+/// The resulting coupled model is built with an [`OdeSystem`] with a state variable per stock,
+/// so it sends the trajectory of every stock on an output port with the name of the stock.
+pub struct SystemDynamics {
+    /// Name of the resulting coupled model.
+    name: String,
+    /// QSS method used by the integrators.
+    method: QssMethod,
+    /// Stocks of the model, in declaration order.
+    stocks: Vec<Stock>,
+    /// Flows of the model and their source and target stocks, in declaration order.
+    flows: Vec<(String, Option<String>, Option<String>)>,
+    /// Names and equations of flows and auxiliary variables, in declaration order.
+    equations: Vec<(String, Equation)>,
+}
+
+impl SystemDynamics {
+    /// Creates a new, empty system dynamics model.
+    pub fn new(name: &str, method: QssMethod) -> Self {
+        Self {
+            name: name.to_string(),
+            method,
+            stocks: Vec::new(),
+            flows: Vec::new(),
+            equations: Vec::new(),
+        }
+    }
+
+    /// Adds a new stock with its initial value and its quantum.
+    pub fn stock(mut self, name: &str, initial: f64, quantum: f64) -> Self {
+        self.stocks.push(Stock {
+            name: name.to_string(),
+            initial,
+            quantum,
+        });
+        self
+    }
+
+    /// Adds a new flow from stock `from` to stock `to` with the given rate equation.
+    /// Use [`None`] for flows from or to the outside of the model (i.e., sources and sinks).
+    pub fn flow(
+        mut self,
+        name: &str,
+        from: Option<&str>,
+        to: Option<&str>,
+        rate: impl Fn(&Variables) -> f64 + Send + Sync + 'static,
+    ) -> Self {
+        let (from, to) = (from.map(str::to_string), to.map(str::to_string));
+        self.flows.push((name.to_string(), from, to));
+        self.equations.push((name.to_string(), Box::new(rate)));
+        self
+    }
+
+    /// Adds a new auxiliary variable with the given equation.
+    pub fn auxiliary(
+        mut self,
+        name: &str,
+        equation: impl Fn(&Variables) -> f64 + Send + Sync + 'static,
+    ) -> Self {
+        self.equations.push((name.to_string(), Box::new(equation)));
+        self
+    }
+
+    /// Creates the coupled model of the system.
+    /// It panics if two variables share the same name or if a flow refers to a stock that does not exist.
+    pub fn build(self) -> Coupled {
+        let mut indices = HashMap::new();
+        let names =
+            (self.stocks.iter().map(|s| &s.name)).chain(self.equations.iter().map(|e| &e.0));
+        for (i, name) in names.enumerate() {
+            assert!(
+                indices.insert(name.clone(), i).is_none(),
+                "variable {name} is declared more than once"
+            );
+        }
+        let stock = |name: &Option<String>| {
+            name.as_ref().map(|name| match indices.get(name) {
+                Some(&i) if i < self.stocks.len() => i,
+                _ => panic!("stock {name} does not exist"),
+            })
+        };
+        let mut flows = vec![Vec::new(); self.stocks.len()];
+        for (name, from, to) in &self.flows {
+            let flow = indices[name];
+            if let Some(i) = stock(from) {
+                flows[i].push((flow, -1.));
+            }
+            if let Some(i) = stock(to) {
+                flows[i].push((flow, 1.));
+            }
+        }
+        let equations = Arc::new(Equations {
+            indices,
+            equations: self.equations.into_iter().map(|(_, e)| e).collect(),
+            flows,
+        });
+
+        let mut system = OdeSystem::new(&self.name, self.method);
+        for (i, stock) in self.stocks.into_iter().enumerate() {
+            let equations = equations.clone();
+            system = system.state(&stock.name, stock.initial, stock.quantum, move |x| {
+                equations.derivative(i, x)
+            });
+        }
+        system.build()
+    }
+}
@@ -0,0 +1,91 @@
+use super::Signal;
+use crate::modeling::*;
+
+/// Step used for estimating the slope of the output of static functions.
+const SLOPE_STEP: f64 = 1e-6;
+
+/// Function of a set of signal values.
+pub type Function = Box<dyn Fn(&[f64]) -> f64 + Send + Sync>;
+
+/// Static function of QSS signals. It has `n` input ports named `input_0` to `input_{n-1}`
+/// and sends the result of the function on its `output` port every time an input changes.
+/// The slope of the output is estimated numerically from the slopes of the inputs.
+pub struct StaticFunction {
+    component: Component,
+    inputs: Vec<InPort<Signal>>,
+    output: OutPort<Signal>,
+    /// Function applied to the input values.
+    function: Function,
+    /// Last known input signals, updated to the time of the last transition.
+    values: Vec<Signal>,
+    sigma: f64,
+}
+
+impl StaticFunction {
+    /// Creates a new static function with the given initial input values.
+    /// The number of input ports corresponds to the number of initial values.
+    pub fn new(
+        name: &str,
+        initial: &[f64],
+        function: impl Fn(&[f64]) -> f64 + Send + Sync + 'static,
+    ) -> Self {
+        let mut component = Component::new(name);
+        let inputs = (0..initial.len())
+            .map(|i| component.add_in_port(&format!("input_{i}")))
+            .collect();
+        let output = component.add_out_port("output");
+        Self {
+            component,
+            inputs,
+            output,
+            function: Box::new(function),
+            values: initial.iter().map(|&v| Signal::new(v, 0.)).collect(),
+            sigma: f64::INFINITY,
+        }
+    }
+}
+
+impl Atomic for StaticFunction {
+    #[inline]
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    #[inline]
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn lambda(&self) {
+        let values: Vec<f64> = self.values.iter().map(|s| s.value).collect();
+        let next: Vec<f64> = self
+            .values
+            .iter()
+            .map(|s| s.value + s.slope * SLOPE_STEP)
+            .collect();
+        let value = (self.function)(&values);
+        let slope = ((self.function)(&next) - value) / SLOPE_STEP;
+        // Safety: adding message on atomic model's output port at lambda
+        unsafe { self.output.add_value(Signal::new(value, slope)) };
+    }
+
+    fn delta_int(&mut self) {
+        self.sigma = f64::INFINITY;
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        for (signal, input) in self.values.iter_mut().zip(self.inputs.iter()) {
+            signal.value += signal.slope * e;
+            // Safety: reading messages on atomic model's input port at delta_ext
+            if let Some(&last) = unsafe { input.get_values() }.last() {
+                *signal = last;
+            }
+        }
+        self.sigma = 0.;
+    }
+
+    #[inline]
+    fn ta(&self) -> f64 {
+        self.sigma
+    }
+}
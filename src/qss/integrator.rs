@@ -0,0 +1,238 @@
+use super::Signal;
+use crate::modeling::*;
+use crate::DynRef;
+
+/// Common interface of QSS integrators used by [`super::OdeSystem`].
+pub(super) trait Integrator: Atomic + DynRef {
+    /// Creates a new integrator with the given initial state and quantum.
+    fn create(name: &str, initial: f64, quantum: f64) -> Self;
+}
+
+/// Returns the smallest positive root of `a * t^2 + b * t + c`, or infinity if there is none.
+fn min_positive_root(a: f64, b: f64, c: f64) -> f64 {
+    let roots = match a == 0. {
+        true if b == 0. => return f64::INFINITY,
+        true => [-c / b, f64::INFINITY],
+        false => {
+            let discriminant = b * b - 4. * a * c;
+            if discriminant < 0. {
+                return f64::INFINITY;
+            }
+            let sqrt = discriminant.sqrt();
+            [(-b + sqrt) / (2. * a), (-b - sqrt) / (2. * a)]
+        }
+    };
+    roots
+        .into_iter()
+        .filter(|&t| t > 0.)
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// First-order quantized state system (QSS1) integrator.
+/// It receives the derivative of the state on its `input` port
+/// and sends the quantized state on its `output` port.
+/// Slopes of incoming signals are ignored, and outgoing signals have a null slope.
+pub struct Qss1Integrator {
+    component: Component,
+    input: InPort<Signal>,
+    output: OutPort<Signal>,
+    /// Quantum of the integrator.
+    quantum: f64,
+    /// State at the last transition.
+    x: f64,
+    /// Derivative of the state.
+    dx: f64,
+    /// Quantized state.
+    q: f64,
+    sigma: f64,
+}
+
+impl Qss1Integrator {
+    /// Creates a new QSS1 integrator with the given initial state and quantum.
+    /// The initial state is sent at the beginning of the simulation.
+    pub fn new(name: &str, initial: f64, quantum: f64) -> Self {
+        if quantum <= 0. {
+            panic!("quantum must be greater than 0")
+        }
+        let mut component = Component::new(name);
+        let input = component.add_in_port("input");
+        let output = component.add_out_port("output");
+        Self {
+            component,
+            input,
+            output,
+            quantum,
+            x: initial,
+            dx: 0.,
+            q: initial,
+            sigma: 0.,
+        }
+    }
+
+    /// Returns the time until the state reaches the next quantization level.
+    fn next_sigma(&self) -> f64 {
+        if self.dx > 0. {
+            ((self.q + self.quantum - self.x) / self.dx).max(0.)
+        } else if self.dx < 0. {
+            ((self.q - self.quantum - self.x) / self.dx).max(0.)
+        } else {
+            f64::INFINITY
+        }
+    }
+}
+
+impl Integrator for Qss1Integrator {
+    #[inline]
+    fn create(name: &str, initial: f64, quantum: f64) -> Self {
+        Self::new(name, initial, quantum)
+    }
+}
+
+impl Atomic for Qss1Integrator {
+    #[inline]
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    #[inline]
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn lambda(&self) {
+        let value = self.x + self.dx * self.sigma;
+        // Safety: adding message on atomic model's output port at lambda
+        unsafe { self.output.add_value(Signal::new(value, 0.)) };
+    }
+
+    fn delta_int(&mut self) {
+        self.x += self.dx * self.sigma;
+        self.q = self.x;
+        self.sigma = self.next_sigma();
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        self.x += self.dx * e;
+        // Safety: reading messages on atomic model's input port at delta_ext
+        if let Some(signal) = unsafe { self.input.get_values() }.last() {
+            self.dx = signal.value;
+        }
+        self.sigma = self.next_sigma();
+    }
+
+    #[inline]
+    fn ta(&self) -> f64 {
+        self.sigma
+    }
+}
+
+/// Second-order quantized state system (QSS2) integrator.
+/// It receives the derivative of the state (value and slope) on its `input` port
+/// and sends the piecewise linear quantized state (value and slope) on its `output` port.
+pub struct Qss2Integrator {
+    component: Component,
+    input: InPort<Signal>,
+    output: OutPort<Signal>,
+    /// Quantum of the integrator.
+    quantum: f64,
+    /// State at the last transition.
+    x: f64,
+    /// Derivative of the state at the last transition.
+    u: f64,
+    /// Slope of the derivative of the state.
+    mu: f64,
+    /// Quantized state at the last transition.
+    q: f64,
+    /// Slope of the quantized state.
+    mq: f64,
+    sigma: f64,
+}
+
+impl Qss2Integrator {
+    /// Creates a new QSS2 integrator with the given initial state and quantum.
+    /// The initial state is sent at the beginning of the simulation.
+    pub fn new(name: &str, initial: f64, quantum: f64) -> Self {
+        if quantum <= 0. {
+            panic!("quantum must be greater than 0")
+        }
+        let mut component = Component::new(name);
+        let input = component.add_in_port("input");
+        let output = component.add_out_port("output");
+        Self {
+            component,
+            input,
+            output,
+            quantum,
+            x: initial,
+            u: 0.,
+            mu: 0.,
+            q: initial,
+            mq: 0.,
+            sigma: 0.,
+        }
+    }
+
+    /// Returns the time until the state and the quantized state differ in one quantum.
+    fn next_sigma(&self) -> f64 {
+        let (a, b, c) = (self.mu / 2., self.u - self.mq, self.x - self.q);
+        if c.abs() >= self.quantum {
+            return 0.;
+        }
+        let upper = min_positive_root(a, b, c - self.quantum);
+        let lower = min_positive_root(a, b, c + self.quantum);
+        upper.min(lower)
+    }
+}
+
+impl Integrator for Qss2Integrator {
+    #[inline]
+    fn create(name: &str, initial: f64, quantum: f64) -> Self {
+        Self::new(name, initial, quantum)
+    }
+}
+
+impl Atomic for Qss2Integrator {
+    #[inline]
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    #[inline]
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn lambda(&self) {
+        let s = self.sigma;
+        let value = self.x + self.u * s + self.mu * s * s / 2.;
+        let slope = self.u + self.mu * s;
+        // Safety: adding message on atomic model's output port at lambda
+        unsafe { self.output.add_value(Signal::new(value, slope)) };
+    }
+
+    fn delta_int(&mut self) {
+        let s = self.sigma;
+        self.x += self.u * s + self.mu * s * s / 2.;
+        self.u += self.mu * s;
+        self.q = self.x;
+        self.mq = self.u;
+        self.sigma = self.next_sigma();
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        self.x += self.u * e + self.mu * e * e / 2.;
+        self.u += self.mu * e;
+        self.q += self.mq * e;
+        // Safety: reading messages on atomic model's input port at delta_ext
+        if let Some(signal) = unsafe { self.input.get_values() }.last() {
+            self.u = signal.value;
+            self.mu = signal.slope;
+        }
+        self.sigma = self.next_sigma();
+    }
+
+    #[inline]
+    fn ta(&self) -> f64 {
+        self.sigma
+    }
+}
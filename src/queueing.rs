@@ -0,0 +1,220 @@
+//! Library of atomic models for queueing networks (e.g., M/M/1 and M/M/c queues, or Jackson networks).
+//! [`Source`]s generate customers, [`Station`]s serve them, [`Router`]s split their flows, and [`Sink`]s absorb
+//! them and measure their sojourn times. All the models exchange [`crate::lib::Job`]s, so they can also be
+//! combined with the models of [`crate::lib`]. Random samples are drawn from the random number generator
+//! streams of the components, so simulations are reproducible with [`crate::simulation::RootCoordinator::set_seed`].
+//! [`MmcMetrics`] provides the analytical results of M/M/c queues to validate simulation results.
+
+mod router;
+mod sink;
+mod source;
+mod station;
+
+use crate::modeling::Coupled;
+use crate::random::Rng;
+pub use router::{Router, RoutingPolicy};
+pub use sink::Sink;
+pub use source::Source;
+pub use station::Station;
+
+/// Probability distribution of interarrival or service times.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeDistribution {
+    /// Constant time.
+    Deterministic(f64),
+    /// Exponential distribution with the given mean.
+    Exponential(f64),
+    /// Uniform distribution in the interval `[low, high)`.
+    Uniform(f64, f64),
+    /// Erlang distribution with `k` phases and the given mean (i.e., the sum of `k` exponential samples).
+    Erlang(usize, f64),
+}
+
+impl TimeDistribution {
+    /// Draws a sample of the distribution.
+    pub fn sample(&self, rng: &mut Rng) -> f64 {
+        match *self {
+            Self::Deterministic(t) => t,
+            Self::Exponential(mean) => rng.exponential(mean),
+            Self::Uniform(low, high) => rng.uniform(low, high),
+            Self::Erlang(k, mean) => (0..k).map(|_| rng.exponential(mean / k as f64)).sum(),
+        }
+    }
+
+    /// Returns the mean of the distribution.
+    pub fn mean(&self) -> f64 {
+        match *self {
+            Self::Deterministic(t) => t,
+            Self::Exponential(mean) | Self::Erlang(_, mean) => mean,
+            Self::Uniform(low, high) => (low + high) / 2.,
+        }
+    }
+}
+
+/// Analytical steady-state metrics of an M/M/c queue (Poisson arrivals, exponential service times,
+/// `c` servers, and an unbounded FIFO queue).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MmcMetrics {
+    /// Utilization of the servers (i.e., the fraction of time that every server is busy).
+    pub utilization: f64,
+    /// Probability that an arriving customer has to wait (Erlang C formula).
+    pub p_wait: f64,
+    /// Mean number of waiting customers.
+    pub queue_length: f64,
+    /// Mean number of customers in the system (waiting or in service).
+    pub n_customers: f64,
+    /// Mean time in queue.
+    pub waiting_time: f64,
+    /// Mean time in the system (waiting and in service).
+    pub sojourn_time: f64,
+}
+
+impl MmcMetrics {
+    /// Computes the metrics of an M/M/c queue with the given arrival rate, service rate (of every server),
+    /// and number of servers. It returns [`None`] if the queue is unstable (i.e., its utilization is not below 1).
+    pub fn new(arrival_rate: f64, service_rate: f64, servers: usize) -> Option<Self> {
+        let c = servers as f64;
+        let a = arrival_rate / service_rate;
+        let utilization = a / c;
+        if servers == 0 || !utilization.is_finite() || utilization >= 1. {
+            return None;
+        }
+        // terms a^k / k! for k = 0..c
+        let mut term = 1.;
+        let mut sum = 0.;
+        for k in 0..servers {
+            sum += term;
+            term *= a / (k + 1) as f64;
+        }
+        let tail = term / (1. - utilization);
+        let p_wait = tail / (sum + tail);
+        let queue_length = p_wait * utilization / (1. - utilization);
+        let waiting_time = queue_length / arrival_rate;
+        Some(Self {
+            utilization,
+            p_wait,
+            queue_length,
+            n_customers: queue_length + a,
+            waiting_time,
+            sojourn_time: waiting_time + 1. / service_rate,
+        })
+    }
+}
+
+/// Creates a coupled model of an M/M/c queue: a [`Source`] of Poisson arrivals named `source`,
+/// a [`Station`] with exponential service times named `station`, and a [`Sink`] named `sink`.
+pub fn mmc(name: &str, arrival_rate: f64, service_rate: f64, servers: usize) -> Coupled {
+    let mut coupled = Coupled::new(name);
+    coupled.add_component(Box::new(Source::poisson("source", arrival_rate)));
+    coupled.add_component(Box::new(Station::markovian(
+        "station",
+        servers,
+        service_rate,
+    )));
+    coupled.add_component(Box::new(Sink::new("sink")));
+    coupled.add_ic("source", "output", "station", "input");
+    coupled.add_ic("station", "output", "sink", "input");
+    coupled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::RootCoordinator;
+    use crate::stats::Summary;
+
+    /// Returns the mean of a tally or time-weighted accumulator of a simulation.
+    fn mean(simulator: &RootCoordinator<Coupled>, path: &str, name: &str) -> f64 {
+        match simulator.stats().get(path, name) {
+            Some(Summary::Tally { mean, .. } | Summary::TimeWeighted { mean, .. }) => *mean,
+            summary => panic!("unexpected summary {summary:?}"),
+        }
+    }
+
+    #[test]
+    fn test_mmc_metrics() {
+        let mm1 = MmcMetrics::new(0.5, 1., 1).unwrap();
+        assert_eq!((0.5, 0.5), (mm1.utilization, mm1.p_wait));
+        assert_eq!((1., 2.), (mm1.n_customers, mm1.sojourn_time));
+        let mm2 = MmcMetrics::new(1., 1., 2).unwrap();
+        assert!((mm2.p_wait - 1. / 3.).abs() < 1e-12);
+        assert!((mm2.queue_length - 1. / 3.).abs() < 1e-12);
+        assert!(MmcMetrics::new(2., 1., 2).is_none());
+    }
+
+    #[test]
+    fn test_mmc_simulation() {
+        for (servers, arrival_rate) in [(1, 0.5), (3, 2.)] {
+            let expected = MmcMetrics::new(arrival_rate, 1., servers).unwrap();
+            let mut simulator = RootCoordinator::new(mmc("mmc", arrival_rate, 1., servers));
+            simulator.set_seed(42);
+            simulator.simulate(50_000.);
+            let sojourn_time = mean(&simulator, "mmc.sink", "sojourn_time");
+            assert!((sojourn_time / expected.sojourn_time - 1.).abs() < 0.05);
+            let busy_servers = mean(&simulator, "mmc.station", "busy_servers");
+            assert!((busy_servers / servers as f64 / expected.utilization - 1.).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_network() {
+        // Deterministic network with a router that balances the load of two stations
+        let mut coupled = Coupled::new("network");
+        let source = Source::new("source", TimeDistribution::Deterministic(1.)).limit(10);
+        coupled.add_component(Box::new(source));
+        coupled.add_component(Box::new(Router::round_robin("router", 2)));
+        coupled.add_component(Box::new(Sink::new("sink")));
+        for i in 0..2 {
+            let service = TimeDistribution::Deterministic(1.5);
+            let station = Station::new(&format!("station_{i}"), 1, service).capacity(0);
+            coupled.add_component(Box::new(station));
+            coupled.add_ic(
+                "router",
+                &format!("output_{i}"),
+                &format!("station_{i}"),
+                "input",
+            );
+            coupled.add_ic(&format!("station_{i}"), "output", "sink", "input");
+        }
+        coupled.add_ic("source", "output", "router", "input");
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.simulate(f64::INFINITY);
+
+        let stats = simulator.stats();
+        assert_eq!(
+            Some(&Summary::Counter(10)),
+            stats.get("network.sink", "departures")
+        );
+        assert_eq!(1.5, mean(&simulator, "network.sink", "sojourn_time"));
+        assert_eq!(
+            Some(&Summary::Counter(0)),
+            stats.get("network.station_0", "dropped")
+        );
+    }
+
+    #[test]
+    fn test_probabilistic_router() {
+        let mut coupled = Coupled::new("network");
+        coupled.add_component(Box::new(Source::poisson("source", 1.).limit(10_000)));
+        coupled.add_component(Box::new(Router::probabilistic("router", &[1., 3.])));
+        for i in 0..2 {
+            coupled.add_component(Box::new(Sink::new(&format!("sink_{i}"))));
+            coupled.add_ic(
+                "router",
+                &format!("output_{i}"),
+                &format!("sink_{i}"),
+                "input",
+            );
+        }
+        coupled.add_ic("source", "output", "router", "input");
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.simulate(f64::INFINITY);
+
+        let stats = simulator.stats();
+        let Some(&Summary::Counter(n)) = stats.get("network.sink_1", "departures") else {
+            panic!("missing departures");
+        };
+        assert!((n as f64 / 10_000. - 0.75).abs() < 0.02);
+        assert_eq!(0., mean(&simulator, "network.sink_1", "sojourn_time"));
+    }
+}
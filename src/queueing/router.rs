@@ -0,0 +1,146 @@
+use crate::lib::Job;
+use crate::modeling::{Atomic, Component, InPort, OutPort};
+
+/// Routing policy of a [`Router`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum RoutingPolicy {
+    /// Every customer is sent through a random output port. The `i`-th weight is proportional to the
+    /// probability of choosing the `i`-th output port (e.g., `[0.3, 0.7]`).
+    Probabilistic(Vec<f64>),
+    /// Customers are sent through every output port in turn.
+    RoundRobin(usize),
+}
+
+impl RoutingPolicy {
+    /// Returns the number of output ports of the policy.
+    fn n_outputs(&self) -> usize {
+        match self {
+            Self::Probabilistic(weights) => weights.len(),
+            Self::RoundRobin(n) => *n,
+        }
+    }
+}
+
+/// Atomic model that forwards the customers received through its `input` port to one of its
+/// output ports (`output_0`, `output_1`, etc.) without delay, following a [`RoutingPolicy`].
+/// Random choices use the random number generator stream of the component.
+pub struct Router {
+    /// Component of the atomic model.
+    component: Component,
+    /// Routing policy.
+    policy: RoutingPolicy,
+    /// Index of the next output port of round-robin policies.
+    turn: usize,
+    /// Customers to be forwarded and the index of their output port.
+    pending: Vec<(usize, Job)>,
+    /// Input port for receiving customers.
+    input: InPort<Job>,
+    /// Output ports for forwarding customers.
+    outputs: Vec<OutPort<Job>>,
+}
+
+impl Router {
+    /// Creates a new router with the given routing policy. It panics if the policy has no output ports,
+    /// or if the weights of a probabilistic policy are negative or add up to zero.
+    pub fn new(name: &str, policy: RoutingPolicy) -> Self {
+        assert!(
+            policy.n_outputs() > 0,
+            "router {name} must have at least one output port"
+        );
+        if let RoutingPolicy::Probabilistic(weights) = &policy {
+            assert!(
+                weights.iter().all(|w| *w >= 0.) && weights.iter().sum::<f64>() > 0.,
+                "weights of router {name} must be non-negative and add up to a positive value"
+            );
+        }
+        let mut component = Component::new(name);
+        let input = component.add_in_port::<Job>("input");
+        let outputs = (0..policy.n_outputs())
+            .map(|i| component.add_out_port::<Job>(&format!("output_{i}")))
+            .collect();
+        Self {
+            component,
+            policy,
+            turn: 0,
+            pending: Vec::new(),
+            input,
+            outputs,
+        }
+    }
+
+    /// Creates a new router that sends every customer through the `i`-th output port with probability
+    /// proportional to `weights[i]`.
+    pub fn probabilistic(name: &str, weights: &[f64]) -> Self {
+        Self::new(name, RoutingPolicy::Probabilistic(weights.to_vec()))
+    }
+
+    /// Creates a new router that sends customers through `n` output ports in turn.
+    pub fn round_robin(name: &str, n: usize) -> Self {
+        Self::new(name, RoutingPolicy::RoundRobin(n))
+    }
+
+    /// Returns the index of the output port of the next customer.
+    fn route(&mut self) -> usize {
+        match &self.policy {
+            RoutingPolicy::Probabilistic(weights) => {
+                let total: f64 = weights.iter().sum();
+                let mut x = self.component.rng().uniform(0., total);
+                for (i, w) in weights.iter().enumerate() {
+                    if x < *w {
+                        return i;
+                    }
+                    x -= w;
+                }
+                // rounding errors may leave a tiny remainder
+                weights.iter().rposition(|w| *w > 0.).unwrap()
+            }
+            RoutingPolicy::RoundRobin(n) => {
+                let i = self.turn;
+                self.turn = (self.turn + 1) % n;
+                i
+            }
+        }
+    }
+}
+
+impl Atomic for Router {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.turn = 0;
+        self.pending.clear();
+    }
+
+    fn lambda(&self) {
+        for (i, job) in &self.pending {
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.outputs[*i].add_value(*job) };
+        }
+    }
+
+    fn delta_int(&mut self) {
+        self.pending.clear();
+    }
+
+    fn delta_ext(&mut self, _e: f64) {
+        // Safety: reading messages on atomic model's input port at delta_ext
+        let jobs = unsafe { self.input.get_values() }.to_vec();
+        for job in jobs {
+            let i = self.route();
+            self.pending.push((i, job));
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        match self.pending.is_empty() {
+            true => f64::INFINITY,
+            false => 0.,
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use crate::lib::Job;
+use crate::modeling::{Atomic, Component, InPort};
+use crate::stats::{Counter, Tally};
+
+/// Atomic model that absorbs the customers received through its `input` port.
+/// It reports the sojourn time of every customer (i.e., the time since it was generated by
+/// a [`super::Source`]) in its `sojourn_time` accumulator, and the number of customers
+/// in its `departures` accumulator (see [`crate::simulation::RootCoordinator::stats`]).
+pub struct Sink {
+    /// Component of the atomic model.
+    component: Component,
+    /// Sojourn time of every customer.
+    sojourn_time: Tally,
+    /// Number of customers received.
+    departures: Counter,
+    /// Input port for receiving customers.
+    input: InPort<Job>,
+}
+
+impl Sink {
+    /// Creates a new sink.
+    pub fn new(name: &str) -> Self {
+        let mut component = Component::new(name);
+        let input = component.add_in_port::<Job>("input");
+        let sojourn_time = component.add_tally("sojourn_time");
+        let departures = component.add_counter("departures");
+        Self {
+            component,
+            sojourn_time,
+            departures,
+            input,
+        }
+    }
+
+    /// Returns the number of customers received.
+    pub fn n_departures(&self) -> u64 {
+        self.departures.value()
+    }
+
+    /// Returns the mean sojourn time of the customers received. It is NaN if no customer has been received.
+    pub fn mean_sojourn_time(&self) -> f64 {
+        self.sojourn_time.mean()
+    }
+}
+
+impl Atomic for Sink {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn lambda(&self) {}
+
+    fn delta_int(&mut self) {}
+
+    fn delta_ext(&mut self, e: f64) {
+        let t = self.component.get_t_last() + e;
+        // Safety: reading messages on atomic model's input port at delta_ext
+        for job in unsafe { self.input.get_values() } {
+            self.sojourn_time.record(t - job.time);
+            self.departures.increment();
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        f64::INFINITY
+    }
+}
@@ -0,0 +1,98 @@
+use super::TimeDistribution;
+use crate::lib::Job;
+use crate::modeling::{Atomic, Component, OutPort};
+
+/// Atomic model that generates customers (i.e., [`Job`]s) through its `output` port.
+/// Interarrival times are sampled from a [`TimeDistribution`] with the random number generator stream
+/// of the component, so arrivals are reproducible with [`crate::simulation::RootCoordinator::set_seed`].
+pub struct Source {
+    /// Component of the atomic model.
+    component: Component,
+    /// Distribution of the interarrival times.
+    interarrival: TimeDistribution,
+    /// Maximum number of customers to generate.
+    limit: usize,
+    /// Number of customers generated so far.
+    count: usize,
+    /// Time remaining until the next arrival.
+    sigma: f64,
+    /// Output port for sending customers.
+    output: OutPort<Job>,
+}
+
+impl Source {
+    /// Creates a new source whose interarrival times are sampled from `interarrival`.
+    pub fn new(name: &str, interarrival: TimeDistribution) -> Self {
+        let mut component = Component::new(name);
+        let output = component.add_out_port::<Job>("output");
+        Self {
+            component,
+            interarrival,
+            limit: usize::MAX,
+            count: 0,
+            sigma: f64::INFINITY,
+            output,
+        }
+    }
+
+    /// Creates a new source of Poisson arrivals with the given arrival rate.
+    pub fn poisson(name: &str, rate: f64) -> Self {
+        Self::new(name, TimeDistribution::Exponential(1. / rate))
+    }
+
+    /// Limits the number of customers that the source generates. Then, it becomes passive.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Returns the number of customers generated so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Samples the time until the next arrival, or infinity if the limit has been reached.
+    fn next_arrival(&mut self) -> f64 {
+        match self.count < self.limit {
+            true => self.interarrival.sample(self.component.rng()),
+            false => f64::INFINITY,
+        }
+    }
+}
+
+impl Atomic for Source {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.count = 0;
+        self.sigma = self.next_arrival();
+    }
+
+    fn lambda(&self) {
+        let job = Job {
+            id: self.count,
+            time: self.component.get_t_next(),
+        };
+        // Safety: adding message on atomic model's output port at lambda
+        unsafe { self.output.add_value(job) };
+    }
+
+    fn delta_int(&mut self) {
+        self.count += 1;
+        self.sigma = self.next_arrival();
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        self.sigma -= e;
+    }
+
+    fn ta(&self) -> f64 {
+        self.sigma
+    }
+}
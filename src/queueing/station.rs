@@ -0,0 +1,166 @@
+use super::TimeDistribution;
+use crate::lib::Job;
+use crate::modeling::{Atomic, Component, InPort, OutPort};
+use crate::stats::{Counter, Tally, TimeWeighted};
+use std::collections::VecDeque;
+
+/// Atomic model of a service station with one or more identical servers and a FIFO queue
+/// (e.g., an M/M/1 or M/M/c queue). Customers arrive through its `input` port, wait in the queue
+/// until a server is free, and leave through its `output` port when their service is over.
+/// Service times are sampled from a [`TimeDistribution`] with the random number generator stream
+/// of the component. Customers that arrive when the queue is full are dropped.
+///
+/// The station reports the following accumulators (see [`crate::simulation::RootCoordinator::stats`]):
+/// `waiting_time` (time in queue of every customer that starts its service), `queue_length`
+/// and `busy_servers` (time-weighted number of waiting customers and busy servers), and `dropped`.
+pub struct Station {
+    /// Component of the atomic model.
+    component: Component,
+    /// Number of servers.
+    servers: usize,
+    /// Maximum number of waiting customers.
+    capacity: usize,
+    /// Distribution of the service times.
+    service: TimeDistribution,
+    /// Current simulation time.
+    clock: f64,
+    /// Waiting customers and their arrival times at the station.
+    queue: VecDeque<(f64, Job)>,
+    /// Customers in service and the times at which their services are over.
+    in_service: Vec<(f64, Job)>,
+    /// Time in queue of every customer.
+    waiting_time: Tally,
+    /// Number of waiting customers.
+    queue_length: TimeWeighted,
+    /// Number of busy servers.
+    busy_servers: TimeWeighted,
+    /// Number of customers dropped because the queue was full.
+    dropped: Counter,
+    /// Input port for receiving customers.
+    input: InPort<Job>,
+    /// Output port for sending served customers.
+    output: OutPort<Job>,
+}
+
+impl Station {
+    /// Creates a new station with `servers` servers, an unbounded queue,
+    /// and service times sampled from `service`. It panics if `servers` is 0.
+    pub fn new(name: &str, servers: usize, service: TimeDistribution) -> Self {
+        assert!(servers > 0, "station {name} must have at least one server");
+        let mut component = Component::new(name);
+        let input = component.add_in_port::<Job>("input");
+        let output = component.add_out_port::<Job>("output");
+        let waiting_time = component.add_tally("waiting_time");
+        let queue_length = component.add_time_weighted("queue_length", 0.);
+        let busy_servers = component.add_time_weighted("busy_servers", 0.);
+        let dropped = component.add_counter("dropped");
+        Self {
+            component,
+            servers,
+            capacity: usize::MAX,
+            service,
+            clock: 0.,
+            queue: VecDeque::new(),
+            in_service: Vec::new(),
+            waiting_time,
+            queue_length,
+            busy_servers,
+            dropped,
+            input,
+            output,
+        }
+    }
+
+    /// Creates a new station with `servers` servers, an unbounded queue,
+    /// and exponential service times with the given service rate (i.e., an M/M/c queue if arrivals are Poisson).
+    pub fn markovian(name: &str, servers: usize, rate: f64) -> Self {
+        Self::new(name, servers, TimeDistribution::Exponential(1. / rate))
+    }
+
+    /// Limits the number of waiting customers (i.e., customers in service are not counted).
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Returns the number of waiting customers.
+    pub fn queue_len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns the number of busy servers.
+    pub fn busy_servers(&self) -> usize {
+        self.in_service.len()
+    }
+
+    /// Returns the time at which the next service is over, or infinity if all the servers are idle.
+    fn next_departure(&self) -> f64 {
+        (self.in_service.iter())
+            .map(|(t, _)| *t)
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    /// Starts serving waiting customers while there are free servers.
+    fn serve(&mut self) {
+        while self.in_service.len() < self.servers {
+            let Some((t_arrival, job)) = self.queue.pop_front() else {
+                break;
+            };
+            self.waiting_time.record(self.clock - t_arrival);
+            let t_done = self.clock + self.service.sample(self.component.rng());
+            self.in_service.push((t_done, job));
+        }
+        self.queue_length
+            .update(self.clock, self.queue.len() as f64);
+        self.busy_servers
+            .update(self.clock, self.in_service.len() as f64);
+    }
+}
+
+impl Atomic for Station {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.clock = 0.;
+        self.queue.clear();
+        self.in_service.clear();
+    }
+
+    fn lambda(&self) {
+        let t_next = self.next_departure();
+        for (_, job) in self.in_service.iter().filter(|(t, _)| *t == t_next) {
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.output.add_value(*job) };
+        }
+    }
+
+    fn delta_int(&mut self) {
+        self.clock = self.next_departure();
+        let clock = self.clock;
+        self.in_service.retain(|(t, _)| *t != clock);
+        self.serve();
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        self.clock += e;
+        // Safety: reading messages on atomic model's input port at delta_ext
+        let jobs = unsafe { self.input.get_values() }.to_vec();
+        for job in jobs {
+            match self.queue.len() < self.capacity || self.in_service.len() < self.servers {
+                true => self.queue.push_back((self.clock, job)),
+                false => self.dropped.increment(),
+            }
+            self.serve();
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        self.next_departure() - self.clock
+    }
+}
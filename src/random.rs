@@ -0,0 +1,199 @@
+/// Advances a SplitMix64 state and returns the next output.
+#[inline]
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// FNV-1a hash of a string. Unlike [`std::hash::DefaultHasher`], it is stable across platforms and Rust versions.
+fn fnv1a(s: &str) -> u64 {
+    s.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Derives the seed of the `index`-th child stream of a master seed (e.g., the seed of a run of an experiment).
+pub fn derive_seed(master_seed: u64, index: u64) -> u64 {
+    let mut state = master_seed ^ index.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    splitmix64(&mut state)
+}
+
+/// Pseudo-random number generator (xoshiro256++) seeded with SplitMix64.
+/// It is fast, has a period of 2^256 - 1, and produces the same sequence in every platform.
+/// Every [`crate::modeling::Component`] owns an independent stream, so simulations are reproducible
+/// regardless of the execution order of the models (e.g., when using parallel features).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    /// State of the generator.
+    s: [u64; 4],
+}
+
+impl Rng {
+    /// Creates a new generator from a seed.
+    pub fn new(seed: u64) -> Self {
+        let mut state = seed;
+        Self {
+            s: std::array::from_fn(|_| splitmix64(&mut state)),
+        }
+    }
+
+    /// Creates a new generator for the stream `id` (e.g., the path of a component) of a master seed.
+    /// Different streams of the same master seed are statistically independent.
+    pub fn stream(master_seed: u64, id: &str) -> Self {
+        let mut state = master_seed;
+        Self::new(splitmix64(&mut state) ^ fnv1a(id))
+    }
+
+    /// Returns a uniformly distributed [`u64`].
+    #[inline]
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.s;
+        let result = (s[0].wrapping_add(s[3])).rotate_left(23).wrapping_add(s[0]);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    /// Returns a uniformly distributed [`f64`] in the interval `[0, 1)`.
+    #[inline]
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1. / (1u64 << 53) as f64)
+    }
+
+    /// Returns a uniformly distributed integer in the interval `[0, n)`. It panics if `n` is zero.
+    pub fn below(&mut self, n: u64) -> u64 {
+        assert!(n > 0, "upper bound must be positive");
+        // rejection sampling to avoid modulo bias
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let x = self.next_u64();
+            if x < zone {
+                return x % n;
+            }
+        }
+    }
+
+    /// Returns a uniformly distributed [`f64`] in the interval `[low, high)`.
+    #[inline]
+    pub fn uniform(&mut self, low: f64, high: f64) -> f64 {
+        low + (high - low) * self.next_f64()
+    }
+
+    /// Returns `true` with probability `p`.
+    #[inline]
+    pub fn bernoulli(&mut self, p: f64) -> bool {
+        self.next_f64() < p
+    }
+
+    /// Returns an exponentially distributed [`f64`] with the given mean.
+    #[inline]
+    pub fn exponential(&mut self, mean: f64) -> f64 {
+        -mean * (1. - self.next_f64()).ln()
+    }
+
+    /// Returns a normally distributed [`f64`] with the given mean and standard deviation.
+    pub fn normal(&mut self, mean: f64, std_dev: f64) -> f64 {
+        // Box-Muller transform
+        let u1 = 1. - self.next_f64();
+        let u2 = self.next_f64();
+        mean + std_dev * (-2. * u1.ln()).sqrt() * (2. * std::f64::consts::PI * u2).cos()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::{Atomic, Component, Coupled};
+    use crate::simulation::RootCoordinator;
+    use crate::tracing::{MemorySink, ValueFormatter};
+
+    #[test]
+    fn test_rng() {
+        let (mut a, mut b) = (Rng::new(42), Rng::new(42));
+        assert!((0..100).all(|_| a.next_u64() == b.next_u64()));
+        assert_ne!(Rng::stream(42, "a"), Rng::stream(42, "b"));
+        assert_ne!(Rng::stream(42, "a"), Rng::stream(43, "a"));
+        assert_ne!(derive_seed(42, 0), derive_seed(42, 1));
+
+        let mut rng = Rng::new(0);
+        let n = 100_000;
+        let mean = (0..n).map(|_| rng.exponential(2.)).sum::<f64>() / n as f64;
+        assert!((mean - 2.).abs() < 0.05);
+        let mean = (0..n).map(|_| rng.normal(1., 3.)).sum::<f64>() / n as f64;
+        assert!((mean - 1.).abs() < 0.05);
+        assert!((0..n)
+            .map(|_| rng.uniform(-1., 1.))
+            .all(|x| (-1. ..1.).contains(&x)));
+        assert!((0..n).all(|_| rng.below(3) < 3));
+    }
+
+    /// Atomic model that emits the sampled time of its internal transitions.
+    struct Poisson {
+        component: Component,
+        output: crate::modeling::OutPort<f64>,
+        sigma: f64,
+    }
+
+    impl Atomic for Poisson {
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+
+        fn start(&mut self) {
+            self.sigma = self.component.rng().exponential(1.);
+        }
+
+        fn lambda(&self) {
+            // Safety: adding messages on atomic model's output port at lambda
+            unsafe { self.output.add_value(self.sigma) };
+        }
+
+        fn delta_int(&mut self) {
+            self.sigma = self.component.rng().exponential(1.);
+        }
+
+        fn delta_ext(&mut self, _e: f64) {}
+
+        fn ta(&self) -> f64 {
+            self.sigma
+        }
+    }
+
+    fn trajectory(seed: u64) -> Vec<String> {
+        let mut coupled = Coupled::new("coupled");
+        for name in ["a", "b"] {
+            let mut component = Component::new(name);
+            let output = component.add_out_port("output");
+            coupled.add_component(Box::new(Poisson {
+                component,
+                output,
+                sigma: 0.,
+            }));
+        }
+        let sink = MemorySink::new();
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.set_seed(seed);
+        let formatter = ValueFormatter::new().debug::<f64>();
+        simulator.add_trace_sinks(vec![Box::new(sink.clone())], Some(formatter));
+        simulator.simulate(10.);
+        sink.records().iter().map(|r| r.to_string()).collect()
+    }
+
+    #[test]
+    fn test_reproducible_streams() {
+        assert_eq!(trajectory(1), trajectory(1));
+        assert_ne!(trajectory(1), trajectory(2));
+    }
+}
@@ -0,0 +1,462 @@
+use crate::modeling::{Atomic, Component, OutPort};
+use crate::DynRef;
+use std::collections::HashMap;
+use std::fmt::{self, Debug};
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Event of a scenario: at time `time`, the message `value` is sent through the output port `port`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+pub struct ScheduledEvent<V> {
+    /// Simulation time of the event.
+    pub time: f64,
+    /// Name of the output port.
+    pub port: String,
+    /// Message sent through the output port.
+    pub value: V,
+}
+
+impl<V> ScheduledEvent<V> {
+    /// Creates a new scheduled event.
+    pub fn new(time: f64, port: &str, value: V) -> Self {
+        Self {
+            time,
+            port: port.to_string(),
+            value,
+        }
+    }
+}
+
+/// Errors that may occur when loading a scenario.
+#[derive(Debug)]
+pub enum ScenarioError {
+    /// The scenario file could not be read.
+    Io(io::Error),
+    /// A line of a CSV scenario is not valid. It contains the line number (starting at 1) and the error message.
+    Csv(usize, String),
+    /// The JSON scenario is not valid.
+    #[cfg(feature = "serde")]
+    Json(serde_json::Error),
+    /// An event is scheduled at an invalid time (i.e., negative, infinite, or NaN).
+    InvalidTime(f64),
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "could not read scenario: {e}"),
+            Self::Csv(line, e) => write!(f, "invalid scenario at line {line}: {e}"),
+            #[cfg(feature = "serde")]
+            Self::Json(e) => write!(f, "invalid scenario: {e}"),
+            Self::InvalidTime(t) => write!(f, "invalid event time {t}"),
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+impl From<io::Error> for ScenarioError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for ScenarioError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+/// Atomic model that sends a schedule of timed events through its output ports.
+/// It creates one output port of type `V` for every port name found in the schedule.
+/// Events scheduled at the same time are sent in the same output bag, in the order of the schedule.
+pub struct ScenarioPlayer<V: DynRef + Clone> {
+    /// Component of the atomic model.
+    component: Component,
+    /// Output ports of the model.
+    outputs: Vec<OutPort<V>>,
+    /// Scheduled events, sorted by time. The port is the index in `outputs`.
+    events: Vec<(f64, usize, V)>,
+    /// Index of the next event to be sent.
+    next: usize,
+    /// Current simulation time.
+    clock: f64,
+}
+
+impl<V: DynRef + Clone> ScenarioPlayer<V> {
+    /// Creates a new scenario player from a schedule of events.
+    /// It panics if any event is scheduled at a negative, infinite, or NaN time.
+    pub fn new(name: &str, schedule: Vec<ScheduledEvent<V>>) -> Self {
+        Self::try_new(name, schedule).expect("invalid scenario")
+    }
+
+    /// Creates a new scenario player from a CSV schedule.
+    /// Every line must follow the format `time,port,value`, where `value` is parsed with [`FromStr`]
+    /// and may contain commas. Empty lines, lines starting with `#`, and a `time,port,value` header are ignored.
+    pub fn from_csv<R: Read>(name: &str, reader: R) -> Result<Self, ScenarioError>
+    where
+        V: FromStr,
+        V::Err: Debug,
+    {
+        let mut schedule = Vec::new();
+        for (i, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<_> = line.splitn(3, ',').map(str::trim).collect();
+            if i == 0 && fields == ["time", "port", "value"] {
+                continue;
+            }
+            let (time, port, value) = match fields[..] {
+                [time, port, value] => (time, port, value),
+                _ => return Err(ScenarioError::Csv(i + 1, "expected 3 fields".to_string())),
+            };
+            let time = time
+                .parse()
+                .map_err(|e| ScenarioError::Csv(i + 1, format!("invalid time: {e}")))?;
+            let value = value
+                .parse()
+                .map_err(|e| ScenarioError::Csv(i + 1, format!("invalid value: {e:?}")))?;
+            schedule.push(ScheduledEvent::new(time, port, value));
+        }
+        Self::try_new(name, schedule)
+    }
+
+    /// Creates a new scenario player from a CSV file. See [`ScenarioPlayer::from_csv`] for the file format.
+    pub fn from_csv_file<P: AsRef<Path>>(name: &str, path: P) -> Result<Self, ScenarioError>
+    where
+        V: FromStr,
+        V::Err: Debug,
+    {
+        Self::from_csv(name, std::fs::File::open(path)?)
+    }
+
+    /// Creates a new scenario player from a JSON schedule.
+    /// The schedule must be an array of objects with the fields `time`, `port`, and `value`.
+    #[cfg(feature = "serde")]
+    pub fn from_json(name: &str, json: &str) -> Result<Self, ScenarioError>
+    where
+        V: serde::de::DeserializeOwned,
+    {
+        Self::try_new(name, serde_json::from_str(json)?)
+    }
+
+    /// Creates a new scenario player. It returns an error if any event time is not valid.
+    fn try_new(name: &str, schedule: Vec<ScheduledEvent<V>>) -> Result<Self, ScenarioError> {
+        let mut component = Component::new(name);
+        let mut outputs = Vec::new();
+        let mut ports = HashMap::new();
+        let mut events = Vec::with_capacity(schedule.len());
+        for event in schedule {
+            if !(event.time >= 0. && event.time.is_finite()) {
+                return Err(ScenarioError::InvalidTime(event.time));
+            }
+            let port = *ports.entry(event.port).or_insert_with_key(|port| {
+                outputs.push(component.add_out_port::<V>(port));
+                outputs.len() - 1
+            });
+            events.push((event.time, port, event.value));
+        }
+        // stable sort: events scheduled at the same time keep their relative order
+        events.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(Self {
+            component,
+            outputs,
+            events,
+            next: 0,
+            clock: 0.,
+        })
+    }
+
+    /// Returns the index of the first event scheduled after the next event.
+    fn next_batch(&self) -> usize {
+        let t = self.events[self.next].0;
+        self.events[self.next..]
+            .iter()
+            .position(|(time, _, _)| *time != t)
+            .map_or(self.events.len(), |i| self.next + i)
+    }
+}
+
+/// Interpolation of the time series replayed by a [`ScenarioPlayer`] (see [`ScenarioPlayer::from_csv_columns`]).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interpolation {
+    /// Values are sent at their recorded times only (downstream models hold them until the next value).
+    Recorded,
+    /// Values are sent every given period from the first recorded time, holding the last recorded value.
+    Hold(f64),
+    /// Values are sent every given period from the first recorded time, interpolating linearly between recorded values.
+    Linear(f64),
+}
+
+impl Interpolation {
+    /// Resamples a time series sorted by time.
+    fn resample(&self, series: &[(f64, f64)]) -> Vec<(f64, f64)> {
+        let (period, linear) = match *self {
+            Self::Recorded => return series.to_vec(),
+            Self::Hold(period) => (period, false),
+            Self::Linear(period) => (period, true),
+        };
+        let (Some(first), Some(last)) = (series.first(), series.last()) else {
+            return Vec::new();
+        };
+        let mut samples = Vec::new();
+        let mut j = 0;
+        for k in 0.. {
+            let t = first.0 + k as f64 * period;
+            if t > last.0 {
+                break;
+            }
+            while j + 1 < series.len() && series[j + 1].0 <= t {
+                j += 1;
+            }
+            let value = match series.get(j + 1) {
+                Some(&(t_next, next)) if linear => {
+                    let (t_prev, prev) = series[j];
+                    prev + (next - prev) * (t - t_prev) / (t_next - t_prev)
+                }
+                _ => series[j].1,
+            };
+            samples.push((t, value));
+        }
+        samples
+    }
+}
+
+impl ScenarioPlayer<f64> {
+    /// Creates a new scenario player that replays columns of a CSV time series (e.g., measured sensor data).
+    /// The first line must be a header with the names of the columns, and fields are separated by commas.
+    /// Every column in `columns` is sent through an output port with the name of the column, and the times
+    /// of the samples are taken from the `time` column. Empty lines and lines starting with `#` are ignored.
+    /// It panics if the period of the interpolation is not positive.
+    pub fn from_csv_columns<R: Read>(
+        name: &str,
+        reader: R,
+        time: &str,
+        columns: &[&str],
+        interpolation: Interpolation,
+    ) -> Result<Self, ScenarioError> {
+        if let Interpolation::Hold(period) | Interpolation::Linear(period) = interpolation {
+            assert!(period > 0., "period must be positive");
+        }
+        let mut header: Option<Vec<String>> = None;
+        let mut indices = Vec::new();
+        let mut series = vec![Vec::new(); columns.len()];
+        for (i, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<_> = line.split(',').map(str::trim).collect();
+            let Some(header) = &header else {
+                for column in std::iter::once(&time).chain(columns) {
+                    match fields.iter().position(|f| f == column) {
+                        Some(j) => indices.push(j),
+                        None => {
+                            return Err(ScenarioError::Csv(
+                                i + 1,
+                                format!("missing column {column}"),
+                            ))
+                        }
+                    }
+                }
+                header = Some(fields.iter().map(|f| f.to_string()).collect());
+                continue;
+            };
+            if fields.len() != header.len() {
+                let msg = format!("expected {} fields", header.len());
+                return Err(ScenarioError::Csv(i + 1, msg));
+            }
+            let parse = |j: usize| {
+                (fields[j].parse::<f64>())
+                    .map_err(|e| ScenarioError::Csv(i + 1, format!("invalid {}: {e}", header[j])))
+            };
+            let t = parse(indices[0])?;
+            for (series, &j) in series.iter_mut().zip(&indices[1..]) {
+                series.push((t, parse(j)?));
+            }
+        }
+        let mut schedule = Vec::new();
+        for (column, mut series) in columns.iter().zip(series) {
+            series.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let samples = interpolation.resample(&series).into_iter();
+            schedule.extend(samples.map(|(t, value)| ScheduledEvent::new(t, column, value)));
+        }
+        let mut player = Self::try_new(name, schedule)?;
+        // columns without samples still have an output port
+        for column in columns {
+            if player.component.get_out_port(column).is_none() {
+                player.component.add_out_port::<f64>(column);
+            }
+        }
+        Ok(player)
+    }
+
+    /// Creates a new scenario player that replays columns of a CSV file.
+    /// See [`ScenarioPlayer::from_csv_columns`] for the file format.
+    pub fn from_csv_columns_file<P: AsRef<Path>>(
+        name: &str,
+        path: P,
+        time: &str,
+        columns: &[&str],
+        interpolation: Interpolation,
+    ) -> Result<Self, ScenarioError> {
+        let file = std::fs::File::open(path)?;
+        Self::from_csv_columns(name, file, time, columns, interpolation)
+    }
+}
+
+impl<V: DynRef + Clone> Atomic for ScenarioPlayer<V> {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.next = 0;
+        self.clock = 0.;
+    }
+
+    fn lambda(&self) {
+        for (_, port, value) in &self.events[self.next..self.next_batch()] {
+            // Safety: adding messages on atomic model's output port at lambda
+            unsafe { self.outputs[*port].add_value(value.clone()) };
+        }
+    }
+
+    fn delta_int(&mut self) {
+        self.clock = self.events[self.next].0;
+        self.next = self.next_batch();
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        self.clock += e;
+    }
+
+    fn ta(&self) -> f64 {
+        match self.events.get(self.next) {
+            Some((time, _, _)) => (time - self.clock).max(0.),
+            None => f64::INFINITY,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{Listener, PortValues, RootCoordinator};
+    use std::sync::{Arc, Mutex};
+
+    type Bags = Vec<(f64, String, Vec<i32>)>;
+
+    struct Outputs(Arc<Mutex<Bags>>);
+
+    impl Listener for Outputs {
+        fn on_output(&mut self, t: f64, _path: &str, port: &str, values: &PortValues) {
+            let values = values.downcast::<i32>().unwrap().to_vec();
+            self.0.lock().unwrap().push((t, port.to_string(), values));
+        }
+    }
+
+    fn play(player: ScenarioPlayer<i32>) -> Bags {
+        let outputs = Arc::new(Mutex::new(Vec::new()));
+        let mut simulator = RootCoordinator::new(player);
+        simulator.add_listener(Box::new(Outputs(outputs.clone())));
+        simulator.simulate(f64::INFINITY);
+        let outputs = outputs.lock().unwrap();
+        outputs.clone()
+    }
+
+    #[test]
+    fn test_csv_scenario() {
+        let csv = "time,port,value\n# comment\n2.5,b,3\n1,a,1\n\n1,a,2\n2.5,a,4\n";
+        let player = ScenarioPlayer::from_csv("player", csv.as_bytes()).unwrap();
+        assert_eq!(
+            vec![
+                (1., "a".to_string(), vec![1, 2]),
+                // port b is created first, as it appears first in the schedule
+                (2.5, "b".to_string(), vec![3]),
+                (2.5, "a".to_string(), vec![4]),
+            ],
+            play(player)
+        );
+    }
+
+    #[test]
+    fn test_invalid_csv() {
+        let err = ScenarioPlayer::<i32>::from_csv("p", "1,a,1\n2,a\n".as_bytes());
+        assert!(matches!(err, Err(ScenarioError::Csv(2, _))));
+        let err = ScenarioPlayer::<i32>::from_csv("p", "1,a,x\n".as_bytes());
+        assert!(matches!(err, Err(ScenarioError::Csv(1, _))));
+        let err = ScenarioPlayer::<i32>::from_csv("p", "-1,a,1\n".as_bytes());
+        assert!(matches!(err, Err(ScenarioError::InvalidTime(_))));
+    }
+
+    #[test]
+    fn test_csv_time_series() {
+        let csv = "time, temperature, humidity\n0,10,50\n3,8,40\n1,12,55\n";
+        let player = |columns: &[&str], interpolation| {
+            let reader = csv.as_bytes();
+            ScenarioPlayer::from_csv_columns("p", reader, "time", columns, interpolation).unwrap()
+        };
+        let events = |player: ScenarioPlayer<f64>| -> Vec<(f64, f64)> {
+            player.events.iter().map(|(t, _, v)| (*t, *v)).collect()
+        };
+        let recorded = player(&["humidity", "temperature"], Interpolation::Recorded);
+        assert!(recorded.component.get_out_port("temperature").is_some());
+        assert_eq!(6, recorded.events.len());
+        let hold = player(&["temperature"], Interpolation::Hold(1.));
+        assert_eq!(
+            vec![(0., 10.), (1., 12.), (2., 12.), (3., 8.)],
+            events(hold)
+        );
+        let linear = player(&["temperature"], Interpolation::Linear(1.));
+        assert_eq!(
+            vec![(0., 10.), (1., 12.), (2., 10.), (3., 8.)],
+            events(linear)
+        );
+
+        let reader = "time,temperature\n0,1\n1\n".as_bytes();
+        let err = ScenarioPlayer::from_csv_columns(
+            "p",
+            reader,
+            "time",
+            &["temperature"],
+            Interpolation::Recorded,
+        );
+        assert!(matches!(err, Err(ScenarioError::Csv(3, _))));
+        let reader = "time,temperature\n0,1\n".as_bytes();
+        let err = ScenarioPlayer::from_csv_columns(
+            "p",
+            reader,
+            "time",
+            &["pressure"],
+            Interpolation::Recorded,
+        );
+        assert!(matches!(err, Err(ScenarioError::Csv(1, _))));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_scenario() {
+        let json =
+            r#"[{"time": 0, "port": "a", "value": 7}, {"time": 3, "port": "a", "value": 8}]"#;
+        let player = ScenarioPlayer::from_json("player", json).unwrap();
+        assert_eq!(
+            vec![
+                (0., "a".to_string(), vec![7]),
+                (3., "a".to_string(), vec![8]),
+            ],
+            play(player)
+        );
+    }
+}
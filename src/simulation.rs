@@ -1,16 +1,99 @@
-use crate::modeling::{Atomic, Component, Coupled};
+#[cfg(feature = "async")]
+mod asynchronous;
+#[cfg(not(target_arch = "wasm32"))]
+mod cmb;
+mod control;
+mod debugger;
+mod flat;
+#[cfg(feature = "debug_checks")]
+pub(crate) mod invariants;
+mod listener;
+pub(crate) mod profile;
+mod progress;
+mod replay;
+mod run_record;
+mod scheduler;
+mod snapshot;
+#[cfg(not(target_arch = "wasm32"))]
+mod time_warp;
+mod zero_delay;
+
+use crate::error::ComponentPanic;
+#[cfg(not(feature = "par_couplings"))]
+use crate::modeling::coupled::Coupling;
+use crate::modeling::port::AtomicPhase;
+use crate::modeling::{Atomic, Component, Coupled, Subcomponent};
+use crate::random::Rng;
+use crate::stats::StatsReport;
+use crate::time::{Fixed, Rational, Time};
+use crate::tracing::{TraceSink, Tracer, ValueFormatter};
 use crate::DynRef;
+use debugger::{DebugState, Debugger};
+use listener::Listeners;
+use profile::Phase;
 #[cfg(feature = "par_any")]
 use rayon::prelude::*;
+use run_record::RunInfo;
+use std::any::Any;
+use std::cmp::Ordering;
 use std::ops::{Deref, DerefMut};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use zero_delay::ZeroDelayGuard;
+
+#[cfg(feature = "async")]
+pub use asynchronous::{AsyncRootCoordinator, EventSender, InputBinding, SendError};
+#[cfg(not(target_arch = "wasm32"))]
+pub use cmb::CmbCoordinator;
+pub use control::SimulationHandle;
+pub use debugger::{Break, Breakpoint};
+pub use flat::FlatCoordinator;
+#[cfg(feature = "debug_checks")]
+pub use invariants::check_invariants;
+pub use listener::{Listener, PortValues, StateRecorder, StateSample, StateSnapshot, StateValue};
+pub use profile::{ComponentProfile, Profile};
+pub use progress::Progress;
+pub use replay::{Replay, ReplayError, ReplayEvent, ReplayPorts};
+pub use run_record::RunRecord;
+pub(crate) use scheduler::Scheduler;
+pub use snapshot::Snapshot;
+#[cfg(not(target_arch = "wasm32"))]
+pub use time_warp::{TimeWarpCoordinator, TimeWarpStats};
+
+/// Helper trait for downcasting simulators to their concrete type. It is implemented for every type.
+///
+/// Note that boxed simulators (i.e., `Box<dyn Simulator>`) are also [`Any`], so these methods must be
+/// called on the simulator itself (e.g., `component.as_ref().as_any()`). We recommend using
+/// the `downcast_ref` and `downcast_mut` methods of `dyn Simulator` instead.
+pub trait AsAny {
+    /// Returns a reference to the value as [`Any`].
+    fn as_any(&self) -> &dyn Any;
+
+    /// Returns a mutable reference to the value as [`Any`].
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<S: Any> AsAny for S {
+    #[inline]
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    #[inline]
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
 
 /// Interface for simulating DEVS models. All DEVS models must implement this trait.
-pub trait Simulator: DynRef {
+/// `T` is the simulation time type (by default, [`f64`]).
+pub trait Simulator<T: Time = f64>: DynRef + AsAny {
     /// Returns reference to inner [`Component`].
-    fn get_component(&self) -> &Component;
+    fn get_component(&self) -> &Component<T>;
 
     /// Returns mutable reference to inner [`Component`].
-    fn get_component_mut(&mut self) -> &mut Component;
+    fn get_component_mut(&mut self) -> &mut Component<T>;
 
     /// Returns the name of the inner DEVS [`Component`].
     #[inline]
@@ -20,19 +103,95 @@ pub trait Simulator: DynRef {
 
     /// Returns the time for the last state transition of the inner DEVS [`Component`].
     #[inline]
-    fn get_t_last(&self) -> f64 {
+    fn get_t_last(&self) -> T {
         self.get_component().get_t_last()
     }
 
     /// Returns the time for the next state transition of the inner DEVS [`Component`].
     #[inline]
-    fn get_t_next(&self) -> f64 {
+    fn get_t_next(&self) -> T {
         self.get_component().get_t_next()
     }
 
+    /// Returns the subcomponents of the model.
+    /// By default, it returns an empty vector, as atomic models do not have subcomponents.
+    #[inline]
+    fn get_components(&self) -> Vec<&dyn Simulator<T>> {
+        Vec::new()
+    }
+
+    /// Returns the lookahead of the model, i.e., the minimum time between the reception
+    /// of an input event and the emission of any output event triggered by it.
+    fn lookahead(&self) -> T;
+
+    /// Converts a boxed simulator into a [`Coupled`] model.
+    /// If the simulator is not a coupled model, it is returned unchanged as an error.
+    fn into_coupled(self: Box<Self>) -> Result<Box<Coupled<T>>, Box<dyn Simulator<T>>>;
+
+    /// Returns a reference to the simulator as a [`Coupled`] model,
+    /// or [`None`] if the simulator is not a coupled model.
+    fn as_coupled(&self) -> Option<&Coupled<T>>;
+
+    /// Returns a mutable reference to the simulator as a [`Coupled`] model,
+    /// or [`None`] if the simulator is not a coupled model.
+    fn as_coupled_mut(&mut self) -> Option<&mut Coupled<T>>;
+
+    /// Returns the observable state variables of the model (see [`StateSnapshot`]).
+    /// By default, it returns [`None`], as only atomic models may have observable state.
+    #[inline]
+    fn state_snapshot(&self) -> Option<Vec<(&'static str, StateValue)>> {
+        None
+    }
+
+    /// Saves the state of the model (and of its subcomponents) between two simulation cycles.
+    /// It returns [`None`] if any atomic model does not support state saving (see [`Atomic::save_state`]).
+    fn save_snapshot(&self) -> Option<Snapshot<T>>;
+
+    /// Restores a state previously saved with [`Simulator::save_snapshot`].
+    fn restore_snapshot(&mut self, snapshot: &Snapshot<T>);
+
+    /// Derives the random number generator stream of the inner DEVS [`Component`] (and of its subcomponents)
+    /// from a master seed. `path` is the path of the model in the model hierarchy.
+    #[inline]
+    fn seed_rng(&mut self, master_seed: u64, path: &str) {
+        self.get_component_mut()
+            .set_rng(Rng::stream(master_seed, path));
+    }
+
+    /// Sets the path of the inner DEVS [`Component`] (and of its subcomponents) in the model hierarchy.
+    #[inline]
+    fn set_path(&mut self, path: &str) {
+        self.get_component_mut().set_path(path);
+    }
+
+    /// Sets the order in which the inner DEVS [`Component`] (and its subcomponents) merge messages
+    /// that different couplings send to the same port. By default, it does nothing.
+    #[inline]
+    fn set_merge_order(&mut self, _order: MergeOrder) {}
+
+    /// Enables or disables moving messages through couplings whose source port has no other coupling,
+    /// instead of cloning them. Coordinators disable it when output ports must be read after the collection phase.
+    /// By default, it does nothing.
+    #[inline]
+    fn set_move_messages(&mut self, _enabled: bool) {}
+
+    /// Applies a [`BagPolicy`] to the ports of the inner DEVS [`Component`] (and of its subcomponents).
+    #[inline]
+    fn set_bag_policy(&mut self, policy: BagPolicy) {
+        // Safety: coordinator configuring the ports before the simulation starts
+        unsafe { self.get_component().set_bag_policy(policy) };
+    }
+
+    /// Enables or disables the profiling of the inner DEVS [`Component`] (and of its subcomponents).
+    /// Enabling profiling resets the measured times.
+    #[inline]
+    fn set_profiling(&mut self, enabled: bool) {
+        self.get_component_mut().set_profiling(enabled);
+    }
+
     /// Sets the tine for the last and next state transitions of the inner DEVS [`Component`].
     #[inline]
-    fn set_sim_t(&mut self, t_last: f64, t_next: f64) {
+    fn set_sim_t(&mut self, t_last: T, t_next: T) {
         self.get_component_mut().set_sim_t(t_last, t_next);
     }
 
@@ -51,110 +210,382 @@ pub trait Simulator: DynRef {
     /// Removes all the messages from all the ports.
     #[inline]
     fn clear(&mut self) {
-        let component = self.get_component_mut();
         // Safety: simulator clearing its ports
-        unsafe {
-            component.clear_input();
-            component.clear_output();
-        }
+        unsafe { self.get_component_mut().clear_ports() };
     }
 
     /// It starts the simulation, setting the initial time to t_start.
-    fn start(&mut self, t_start: f64) -> f64;
+    fn start(&mut self, t_start: T) -> T;
 
     /// It stops the simulation, setting the last time to t_stop.
-    fn stop(&mut self, t_stop: f64);
+    fn stop(&mut self, t_stop: T);
 
     /// Executes output functions and propagates messages according to ICs and EOCs.
-    fn collection(&mut self, t: f64);
+    fn collection(&mut self, t: T);
 
     /// Propagates messages according to EICs and executes model transition functions.
-    fn transition(&mut self, t: f64) -> f64;
+    fn transition(&mut self, t: T) -> T;
 }
 
-impl<T: Atomic + DynRef> Simulator for T {
+/// Implements [`Simulator`] for all the [`Atomic`] models with a given time type.
+/// A blanket implementation generic over the time type would conflict with the implementation for [`Coupled`].
+macro_rules! impl_atomic_simulator {
+    ([$($generics:tt)*] $time:ty) => {
+        impl<$($generics)* A: Atomic<$time> + DynRef> Simulator<$time> for A {
+            #[inline]
+            fn get_component(&self) -> &Component<$time> {
+                Atomic::get_component(self)
+            }
+
+            #[inline]
+            fn get_component_mut(&mut self) -> &mut Component<$time> {
+                Atomic::get_component_mut(self)
+            }
+
+            #[inline]
+            fn lookahead(&self) -> $time {
+                Atomic::lookahead(self)
+            }
+
+            #[inline]
+            fn into_coupled(self: Box<Self>) -> Result<Box<Coupled<$time>>, Box<dyn Simulator<$time>>> {
+                Err(self)
+            }
+
+            #[inline]
+            fn as_coupled(&self) -> Option<&Coupled<$time>> {
+                None
+            }
+
+            #[inline]
+            fn as_coupled_mut(&mut self) -> Option<&mut Coupled<$time>> {
+                None
+            }
+
+            #[inline]
+            fn state_snapshot(&self) -> Option<Vec<(&'static str, StateValue)>> {
+                Atomic::as_state_snapshot(self).map(|s| s.snapshot())
+            }
+
+            fn save_snapshot(&self) -> Option<Snapshot<$time>> {
+                let state = Atomic::save_state(self)?;
+                Some(Snapshot::new(Atomic::get_component(self), Some(state), Vec::new()))
+            }
+
+            fn restore_snapshot(&mut self, snapshot: &Snapshot<$time>) {
+                snapshot.restore(Atomic::get_component_mut(self));
+                if let Some(state) = snapshot.state() {
+                    Atomic::restore_state(self, state);
+                }
+            }
+
+            fn start(&mut self, t_start: $time) -> $time {
+                self.get_component().reset_stats();
+                Atomic::start(self);
+                let t_next = t_start + check_ta(Atomic::get_component(self), Atomic::ta(self));
+                self.set_sim_t(t_start, t_next);
+                t_next
+            }
+
+            fn stop(&mut self, t_stop: $time) {
+                self.set_sim_t(t_stop, <$time>::INFINITY);
+                Atomic::stop(self);
+            }
+
+            fn collection(&mut self, t: $time) {
+                if t >= self.get_t_next() {
+                    let start = self.get_component().profile_start();
+                    {
+                        let _phase = AtomicPhase::Output.enter();
+                        attribute(self, t, "lambda", |m| Atomic::lambda(m));
+                    }
+                    self.get_component_mut().profile_record(Phase::Lambda, start);
+                    // Safety: simulator stamping its output right after the output function
+                    unsafe { Atomic::get_component(self).stamp_output(t) };
+                    // Safety: simulator recording its output right after the output function
+                    unsafe { Atomic::get_component_mut(self).track_output() };
+                }
+            }
+
+            fn transition(&mut self, t: $time) -> $time {
+                let t_next = self.get_t_next();
+                let start = self.get_component().profile_start();
+                let _phase = AtomicPhase::Transition.enter();
+                // Safety: simulator executing its transition function
+                if unsafe { self.get_component_mut().track_input() } {
+                    // In Classic DEVS, imminent models that receive messages were not selected
+                    if t == t_next && !self.get_component().is_classic() {
+                        attribute(self, t, "delta_conf", Atomic::delta_conf);
+                        self.get_component_mut().profile_record(Phase::DeltaConf, start);
+                        self.clear_output();
+                    } else {
+                        let e = t - self.get_t_last();
+                        attribute(self, t, "delta_ext", |m| Atomic::delta_ext(m, e));
+                        self.get_component_mut().profile_record(Phase::DeltaExt, start);
+                    }
+                    self.clear_input();
+                } else if t == t_next {
+                    attribute(self, t, "delta_int", Atomic::delta_int);
+                    self.get_component_mut().profile_record(Phase::DeltaInt, start);
+                    self.clear_output();
+                } else {
+                    return t_next;
+                }
+                let t_next = t + check_ta(Atomic::get_component(self), Atomic::ta(self));
+                self.set_sim_t(t, t_next);
+                self.get_component_mut().count_transition();
+                t_next
+            }
+        }
+    };
+}
+
+/// Calls the output function or a transition function of an atomic model. If it panics, the panic is re-raised
+/// as a [`ComponentPanic`] with the path of the model, the simulation time, and the name of the function.
+/// Root coordinators convert it back into a panic with a readable message (see [`RootCoordinator::try_simulate`]).
+#[inline(always)]
+fn attribute<T: Time, A: Atomic<T>, R>(
+    model: &mut A,
+    t: T,
+    function: &'static str,
+    f: impl FnOnce(&mut A) -> R,
+) -> R {
+    match panic::catch_unwind(AssertUnwindSafe(|| f(model))) {
+        Ok(res) => res,
+        Err(payload) => {
+            // panics of nested simulations are already attributed
+            let payload = match payload.downcast::<ComponentPanic>() {
+                Ok(info) => panic::resume_unwind(info),
+                Err(payload) => payload,
+            };
+            let message = match (
+                payload.downcast_ref::<&str>(),
+                payload.downcast_ref::<String>(),
+            ) {
+                (Some(message), _) => message.to_string(),
+                (_, Some(message)) => message.clone(),
+                _ => "unknown panic payload".to_string(),
+            };
+            panic::resume_unwind(Box::new(ComponentPanic {
+                path: Atomic::get_component(model).get_path().to_string(),
+                time: t.as_f64(),
+                function,
+                message,
+            }))
+        }
+    }
+}
+
+/// Runs a simulation phase. Panics of atomic models (see [`attribute`]) are returned as errors,
+/// whereas any other panic is propagated.
+#[inline]
+pub(crate) fn contain<R>(f: impl FnOnce() -> R) -> Result<R, ComponentPanic> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| match payload.downcast() {
+        Ok(info) => *info,
+        Err(payload) => panic::resume_unwind(payload),
+    })
+}
+
+/// Runs a simulation phase. Panics of atomic models are re-raised with their path and simulation time.
+#[inline]
+pub(crate) fn attributed<R>(f: impl FnOnce() -> R) -> R {
+    contain(f).unwrap_or_else(|e| raise(e))
+}
+
+/// Panics with the message of the panic of an atomic model.
+#[cold]
+fn raise(info: ComponentPanic) -> ! {
+    panic!("{info}")
+}
+
+/// Checks that the time advance of an atomic model is neither negative nor NaN, as it would corrupt the schedule.
+/// It panics with the path of the component and the time advance otherwise. Time advances are only checked
+/// in debug builds, unless the `checked_ta` feature is enabled.
+#[inline]
+fn check_ta<T: Time>(component: &Component<T>, ta: T) -> T {
+    if cfg!(any(debug_assertions, feature = "checked_ta"))
+        && matches!(ta.partial_cmp(&T::ZERO), None | Some(Ordering::Less))
+    {
+        panic!(
+            "component {} returned an invalid time advance: {ta:?}",
+            component.get_path()
+        );
+    }
+    ta
+}
+
+impl_atomic_simulator!([] f64);
+impl_atomic_simulator!([const DECIMALS: u32,] Fixed<DECIMALS>);
+impl_atomic_simulator!([] Rational);
+
+impl<T: Time> dyn Simulator<T> {
+    /// Returns `true` if the simulator is of type `S`.
     #[inline]
-    fn get_component(&self) -> &Component {
-        Atomic::get_component(self)
+    pub fn is<S: Simulator<T>>(&self) -> bool {
+        self.as_any().is::<S>()
     }
 
+    /// Returns a reference to the simulator as a model of type `S` (e.g., to read the
+    /// final state of an atomic model after a simulation), or [`None`] if it is not of type `S`.
     #[inline]
-    fn get_component_mut(&mut self) -> &mut Component {
-        Atomic::get_component_mut(self)
+    pub fn downcast_ref<S: Simulator<T>>(&self) -> Option<&S> {
+        self.as_any().downcast_ref()
     }
 
-    fn start(&mut self, t_start: f64) -> f64 {
-        Atomic::start(self);
-        let t_next = t_start + self.ta();
-        self.set_sim_t(t_start, t_next);
-        t_next
+    /// Returns a mutable reference to the simulator as a model of type `S`,
+    /// or [`None`] if it is not of type `S`.
+    #[inline]
+    pub fn downcast_mut<S: Simulator<T>>(&mut self) -> Option<&mut S> {
+        self.as_any_mut().downcast_mut()
     }
+}
 
-    fn stop(&mut self, t_stop: f64) {
-        self.set_sim_t(t_stop, f64::INFINITY);
-        Atomic::stop(self);
+impl<T: Time, C: Subcomponent<T>> Simulator<T> for Coupled<T, C> {
+    #[inline]
+    fn get_component(&self) -> &Component<T> {
+        &self.component
     }
 
-    fn collection(&mut self, t: f64) {
-        if t >= self.get_t_next() {
-            Atomic::lambda(self)
-        }
+    #[inline]
+    fn get_component_mut(&mut self) -> &mut Component<T> {
+        &mut self.component
     }
 
-    fn transition(&mut self, t: f64) -> f64 {
-        let t_next = self.get_t_next();
-        // Safety: simulator executing its transition function
-        if !unsafe { self.get_component().is_input_empty() } {
-            if t == t_next {
-                Atomic::delta_conf(self);
-                self.clear_output();
-            } else {
-                let e = t - self.get_t_last();
-                Atomic::delta_ext(self, e);
-            }
-            self.clear_input();
-        } else if t == t_next {
-            Atomic::delta_int(self);
-            self.clear_output();
-        } else {
-            return t_next;
+    #[inline]
+    fn get_components(&self) -> Vec<&dyn Simulator<T>> {
+        self.components.iter().map(|c| c.as_simulator()).collect()
+    }
+
+    /// Returns the minimum lookahead of all the subcomponents.
+    fn lookahead(&self) -> T {
+        self.components
+            .iter()
+            .map(|c| c.as_simulator().lookahead())
+            .min_by(|a, b| a.total_cmp(b))
+            .unwrap_or(T::INFINITY)
+    }
+
+    /// Coupled models with a custom [`Subcomponent`] type are returned as an error.
+    #[inline]
+    fn into_coupled(self: Box<Self>) -> Result<Box<Coupled<T>>, Box<dyn Simulator<T>>> {
+        match (*self).as_any().is::<Coupled<T>>() {
+            true => Ok((self as Box<dyn Any>).downcast().unwrap()),
+            false => Err(self),
         }
-        let t_next = t + Atomic::ta(self);
-        self.set_sim_t(t, t_next);
-        t_next
     }
-}
 
-impl Simulator for Coupled {
+    /// Coupled models with a custom [`Subcomponent`] type return [`None`].
     #[inline]
-    fn get_component(&self) -> &Component {
-        &self.component
+    fn as_coupled(&self) -> Option<&Coupled<T>> {
+        self.as_any().downcast_ref()
     }
 
+    /// Coupled models with a custom [`Subcomponent`] type return [`None`].
     #[inline]
-    fn get_component_mut(&mut self) -> &mut Component {
-        &mut self.component
+    fn as_coupled_mut(&mut self) -> Option<&mut Coupled<T>> {
+        self.as_any_mut().downcast_mut()
+    }
+
+    fn save_snapshot(&self) -> Option<Snapshot<T>> {
+        let children = self
+            .components
+            .iter()
+            .map(|c| c.as_simulator().save_snapshot())
+            .collect::<Option<_>>()?;
+        Some(Snapshot::new(&self.component, None, children))
+    }
+
+    /// Restores the snapshots of all the subcomponents and rebuilds the scheduler with their next simulation times.
+    fn restore_snapshot(&mut self, snapshot: &Snapshot<T>) {
+        snapshot.restore(&mut self.component);
+        for (component, child) in self.components.iter_mut().zip(snapshot.children()) {
+            component.as_simulator_mut().restore_snapshot(child);
+        }
+        let times = (self.components.iter())
+            .map(|c| c.as_simulator().get_t_next())
+            .collect();
+        self.scheduler = Scheduler::new(self, times)
+            .unwrap_or_else(|e| panic!("invalid coupling in {}: {e}", self.component.get_path()));
+    }
+
+    fn seed_rng(&mut self, master_seed: u64, path: &str) {
+        self.component.set_rng(Rng::stream(master_seed, path));
+        for component in self.components.iter_mut().map(|c| c.as_simulator_mut()) {
+            let path = format!("{path}.{}", component.get_name());
+            component.seed_rng(master_seed, &path);
+        }
+    }
+
+    fn set_path(&mut self, path: &str) {
+        self.component.set_path(path);
+        for component in self.components.iter_mut().map(|c| c.as_simulator_mut()) {
+            let path = format!("{path}.{}", component.get_name());
+            component.set_path(&path);
+        }
+    }
+
+    fn set_merge_order(&mut self, order: MergeOrder) {
+        #[cfg(feature = "par_couplings")]
+        {
+            self.merge_order = order;
+        }
+        for component in self.components.iter_mut().map(|c| c.as_simulator_mut()) {
+            component.set_merge_order(order);
+        }
+    }
+
+    fn set_move_messages(&mut self, enabled: bool) {
+        #[cfg(not(feature = "par_couplings"))]
+        {
+            self.move_messages = enabled;
+        }
+        for component in self.components.iter_mut().map(|c| c.as_simulator_mut()) {
+            component.set_move_messages(enabled);
+        }
+    }
+
+    fn set_bag_policy(&mut self, policy: BagPolicy) {
+        // Safety: coordinator configuring the ports before the simulation starts
+        unsafe { self.component.set_bag_policy(policy) };
+        for component in self.components.iter_mut().map(|c| c.as_simulator_mut()) {
+            component.set_bag_policy(policy);
+        }
+    }
+
+    fn set_profiling(&mut self, enabled: bool) {
+        self.component.set_profiling(enabled);
+        for component in self.components.iter_mut().map(|c| c.as_simulator_mut()) {
+            component.set_profiling(enabled);
+        }
     }
 
     /// Iterates over all the subcomponents to call their [`Simulator::start`]
-    /// method and obtain the next simulation time.
+    /// method and builds the scheduler of the subcomponents with their next simulation times.
     ///
     /// If the feature `par_start` is activated, the iteration is parallelized.
-    fn start(&mut self, t_start: f64) -> f64 {
+    fn start(&mut self, t_start: T) -> T {
+        self.component.reset_stats();
+        let classic = self.is_classic();
+        for component in self.components.iter_mut().map(|c| c.as_simulator_mut()) {
+            component.get_component_mut().set_classic(classic);
+        }
         #[cfg(feature = "par_start")]
         let iter = self.components.par_iter_mut();
         #[cfg(not(feature = "par_start"))]
         let iter = self.components.iter_mut();
+        let times = iter.map(|c| c.start(t_start)).collect();
+        self.scheduler = Scheduler::new(self, times)
+            .unwrap_or_else(|e| panic!("invalid coupling in {}: {e}", self.component.get_path()));
         // we obtain the minimum next time of all the subcomponents
-        let t_next = iter
-            .map(|c| c.start(t_start))
-            .min_by(|a, b| a.total_cmp(b))
-            .unwrap_or(f64::INFINITY);
+        let t_next = self.scheduler.t_next();
         // and set the inner component's last and next times
         self.set_sim_t(t_start, t_next);
 
         #[cfg(feature = "par_couplings")]
         {
+            // couplings may have changed since the last simulation
+            self.par_eics.clear();
+            self.par_xxcs.clear();
             self.build_par_eics();
             self.build_par_eocs();
             self.build_par_ics();
@@ -167,128 +598,1103 @@ impl Simulator for Coupled {
     /// method and obtain the next simulation time.
     ///
     /// If the feature `par_stop` is activated, the iteration is parallelized.
-    fn stop(&mut self, t_stop: f64) {
+    fn stop(&mut self, t_stop: T) {
         #[cfg(feature = "par_stop")]
         let iter = self.components.par_iter_mut();
         #[cfg(not(feature = "par_stop"))]
         let iter = self.components.iter_mut();
         iter.for_each(|c| c.stop(t_stop));
         // we set the inner component's last and next times accordingly
-        self.set_sim_t(t_stop, f64::INFINITY);
+        self.set_sim_t(t_stop, T::INFINITY);
     }
 
-    /// Iterates over all the subcomponents to call their [`Simulator::collection`] method.
+    /// Iterates over the imminent subcomponents to call their [`Simulator::collection`] method.
     /// If the feature `par_collection` is activated, the iteration is parallelized.
-    /// Then, it iterates over all the EOCs and propagates messages accordingly.
+    /// Then, it iterates over the EOCs and ICs of the imminent subcomponents and propagates messages accordingly.
+    /// Subcomponents that receive messages are scheduled for the transition phase.
     ///
-    /// If the feature `par_eoc` is activated, the iteration is parallelized.
-    fn collection(&mut self, t: f64) {
+    /// If the feature `par_couplings` is activated, the iteration over all the couplings is parallelized.
+    fn collection(&mut self, t: T) {
         if t >= self.get_t_next() {
+            self.scheduler.find_imminent(t);
+            if self.is_classic() {
+                self.select_imminent();
+            }
             #[cfg(feature = "par_collection")]
-            let iter = self.components.par_iter_mut();
+            select_mut(&mut self.components, self.scheduler.imminent())
+                .into_par_iter()
+                .for_each(|c| c.collection(t));
             #[cfg(not(feature = "par_collection"))]
-            let iter = self.components.iter_mut();
-            iter.for_each(|c| c.collection(t));
+            for &i in self.scheduler.imminent() {
+                self.components[i].collection(t);
+            }
+
+            let start = self.component.profile_start();
 
             #[cfg(feature = "par_couplings")]
-            self.par_xxcs.par_iter().for_each(|coups| {
-                coups.iter().for_each(|(port_to, port_from)| {
-                    // Safety: coupled model propagating messages
-                    unsafe { port_from.propagate(&**port_to) };
-                });
-            });
+            {
+                // Safety: coupled model propagating messages
+                unsafe { self.par_xxcs.propagate() };
+                let ics = match self.scheduler.is_dense() {
+                    true => (0..self.ics.len()).collect(),
+                    false => self.scheduler.imminent_couplings().1,
+                };
+                for k in ics {
+                    // Safety: coupled model checking its subcomponents' output ports
+                    if !unsafe { self.ics[k].1.is_empty() } {
+                        self.scheduler.activate_ic(k);
+                        if let Some((i, key)) = self.scheduler.ic_entry(k) {
+                            signal_direct(self.components[i].as_simulator_mut(), key);
+                        }
+                    }
+                }
+            }
 
             #[cfg(not(feature = "par_couplings"))]
             {
-                self.eocs.iter().for_each(|(port_to, port_from)| {
+                let (eocs, ics) = match self.scheduler.is_dense() {
+                    true => (
+                        (0..self.eocs.len()).collect(),
+                        (0..self.ics.len()).collect(),
+                    ),
+                    false => self.scheduler.imminent_couplings(),
+                };
+                let moves = self.move_messages;
+                for k in eocs {
                     // Safety: coupled model propagating messages
-                    unsafe { port_from.propagate(&**port_to) };
-                });
-                self.ics.iter().for_each(|(port_to, port_from)| {
-                    // Safety: coupled model propagating messages
-                    unsafe { port_from.propagate(&**port_to) };
-                });
+                    unsafe { propagate(&self.eocs[k], moves && self.scheduler.moves_eoc(k)) };
+                }
+                for k in ics {
+                    // Safety: coupled model checking its subcomponents' output ports
+                    if !unsafe { self.ics[k].1.is_empty() } {
+                        // Safety: coupled model propagating messages
+                        unsafe { propagate(&self.ics[k], moves && self.scheduler.moves_ic(k)) };
+                        self.scheduler.activate_ic(k);
+                        if let Some((i, key)) = self.scheduler.ic_entry(k) {
+                            signal_direct(self.components[i].as_simulator_mut(), key);
+                        }
+                    }
+                }
             }
+            self.component.profile_record(Phase::Propagation, start);
+            // Safety: coupled model recording its output right after propagating messages
+            unsafe { self.component.track_output() };
         }
     }
 
-    /// Iterates over all the EICs and ICs and propagates messages accordingly.
-    /// If the feature `par_xic` is activated, the iteration is parallelized.
-    /// Then, it iterates over all the subcomponents to:
+    /// Iterates over the EICs of the input ports that received messages and propagates messages accordingly.
+    /// If the feature `par_couplings` is activated, all the EICs are propagated in parallel.
+    /// Then, it iterates over the imminent subcomponents and the subcomponents that received messages to:
     /// 1. Call their [`Simulator::transition`] method
     /// 2. Clear their ports
-    /// 3. obtain their next simulation time.
+    /// 3. Reschedule them according to their next simulation time.
     ///
     /// If the feature `par_transition` is activated, the iteration is parallelized.
-    fn transition(&mut self, t: f64) -> f64 {
+    fn transition(&mut self, t: T) -> T {
         // Safety: simulator checking if its input is empty
-        let is_external = !unsafe { self.get_component().is_input_empty() };
+        let mut is_external = unsafe { self.component.track_input() };
         // Propagate messages according to EICs only if there are messages in the input ports
         if is_external {
+            let start = self.component.profile_start();
             #[cfg(feature = "par_couplings")]
-            self.par_eics.par_iter().for_each(|coups| {
-                coups.iter().for_each(|(port_to, port_from)| {
+            // Safety: coupled model propagating messages
+            unsafe {
+                self.par_eics.propagate()
+            };
+            let inputs = self.component.dirty_inputs().iter().copied();
+            for k in self.scheduler.input_couplings(inputs) {
+                #[cfg(not(feature = "par_couplings"))]
+                {
+                    let moves = self.move_messages && self.scheduler.moves_eic(k);
                     // Safety: coupled model propagating messages
-                    unsafe { port_from.propagate(&**port_to) };
-                });
-            });
-            #[cfg(not(feature = "par_couplings"))]
-            self.eics.iter().for_each(|(port_to, port_from)| {
-                // Safety: coupled model propagating messages
-                unsafe { port_from.propagate(&**port_to) };
-            });
+                    unsafe { propagate(&self.eics[k], moves) };
+                }
+                self.scheduler.activate_eic(k);
+                if let Some((i, key)) = self.scheduler.eic_entry(k) {
+                    signal_direct(self.components[i].as_simulator_mut(), key);
+                }
+            }
             self.clear_input();
+            self.component.profile_record(Phase::Propagation, start);
+        }
+        // Direct-connected models are signaled instead of receiving messages in their input ports
+        let signaled = self.scheduler.take_signaled();
+        if !signaled.is_empty() {
+            for k in self.scheduler.input_couplings(signaled.into_iter()) {
+                self.scheduler.activate_eic(k);
+                if let Some((i, key)) = self.scheduler.eic_entry(k) {
+                    signal_direct(self.components[i].as_simulator_mut(), key);
+                }
+            }
+            is_external = true;
         }
-        let is_internal = t >= self.get_t_next();
+        // In Classic DEVS, imminent models that receive messages were not selected
+        let classic = self.is_classic();
+        let is_internal = t >= self.get_t_next() && !(classic && is_external);
         if is_internal {
             self.clear_output();
+            // Classic DEVS models already marked their selected subcomponent as active
+            if !classic {
+                self.scheduler.find_imminent(t);
+            }
         }
         // Nested call only if there are messages in the input ports or if the time has come
         if is_external || is_internal {
             #[cfg(feature = "par_transition")]
-            let iterator = self.components.par_iter_mut();
+            {
+                let mut active = self.scheduler.take_active();
+                active.sort_unstable();
+                let t_nexts: Vec<_> = select_mut(&mut self.components, &active)
+                    .into_par_iter()
+                    .map(|c| c.transition(t))
+                    .collect();
+                for (&i, t_next) in active.iter().zip(t_nexts) {
+                    self.scheduler.update(i, t_next);
+                }
+                self.scheduler.restore_active(active);
+            }
             #[cfg(not(feature = "par_transition"))]
-            let iterator = self.components.iter_mut();
-            let t_next = iterator
-                .map(|c| c.transition(t))
-                .min_by(|a, b| a.total_cmp(b))
-                .unwrap_or(f64::INFINITY);
-            self.set_sim_t(t, t_next);
+            {
+                let active = self.scheduler.take_active();
+                for &i in &active {
+                    let t_next = self.components[i].transition(t);
+                    self.scheduler.update(i, t_next);
+                }
+                self.scheduler.restore_active(active);
+            }
+            self.set_sim_t(t, self.scheduler.t_next());
         }
         self.get_t_next()
     }
 }
 
+/// Propagates messages through a coupling. If `moves` is `true`, messages are moved from the source port
+/// instead of cloned, so the source port must not have any other coupling.
+///
+/// # Safety
+///
+/// This function can only be executed by a [`Coupled`] model when propagating messages.
+#[cfg(not(feature = "par_couplings"))]
+#[inline]
+unsafe fn propagate((port_to, port_from): &Coupling, moves: bool) {
+    match moves {
+        true => port_from.propagate_move(&**port_to),
+        false => port_from.propagate(&**port_to),
+    }
+}
+
+/// Signals the input port with the provided key of a direct-connected coupled model (see [`Coupled::direct_connect`]).
+#[inline]
+fn signal_direct<T: Time>(component: &mut dyn Simulator<T>, key: usize) {
+    if let Some(coupled) = component.as_coupled_mut() {
+        coupled.scheduler.signal(key);
+    }
+}
+
+/// Returns mutable references to the components with the provided indices, which must be sorted
+/// in ascending order without duplicates. It allows iterating over the active subcomponents
+/// of a coupled model in parallel without visiting the rest of subcomponents.
+#[cfg(any(feature = "par_collection", feature = "par_transition"))]
+fn select_mut<'a, C>(components: &'a mut [C], indices: &[usize]) -> Vec<&'a mut C> {
+    let mut selected = Vec::with_capacity(indices.len());
+    let (mut rest, mut offset) = (components, 0);
+    for &i in indices {
+        let (component, tail) = std::mem::take(&mut rest)[i - offset..]
+            .split_first_mut()
+            .expect("component index out of bounds");
+        selected.push(component);
+        (rest, offset) = (tail, i + 1);
+    }
+    selected
+}
+
+/// Order in which messages that different couplings send to the same port are merged into the port.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergeOrder {
+    /// Messages are merged in any order. The order is the same in every run of sequential simulations,
+    /// but it may change between runs when the `par_couplings` feature is activated.
+    #[default]
+    Unspecified,
+    /// Messages are merged in the order in which their couplings were added to the coupled model,
+    /// regardless of the parallel features. Couplings are grouped by destination port and sorted
+    /// when the simulation starts, so this mode does not slow down simulation cycles.
+    Deterministic,
+}
+
+/// Memory policy of the bags that store the messages of ports.
+/// Bags keep their memory when they are cleared after every simulation cycle, so they are recycled
+/// and only allocate memory when they receive more messages than ever before.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BagPolicy {
+    /// Number of messages that every bag can store without allocating memory from the beginning.
+    /// Use it to avoid allocations in the first simulation cycles (by default, 0).
+    pub reserve: usize,
+    /// Maximum number of messages that cleared bags can store without allocating memory.
+    /// Bags that receive bursts of messages release the excess memory after every cycle
+    /// (by default, [`usize::MAX`], so bags never release memory).
+    pub max_capacity: usize,
+}
+
+impl Default for BagPolicy {
+    fn default() -> Self {
+        Self {
+            reserve: 0,
+            max_capacity: usize::MAX,
+        }
+    }
+}
+
 /// Root coordinator for sequential simulations of DEVS models.
-pub struct RootCoordinator<T>(T);
+/// `T` is the simulation time type of the model (by default, [`f64`]).
+pub struct RootCoordinator<M, T: Time = f64> {
+    /// DEVS model to be simulated.
+    model: M,
+    /// Order in which messages are merged into ports.
+    merge_order: MergeOrder,
+    /// Memory policy of the bags of ports.
+    bag_policy: BagPolicy,
+    /// It is `true` if the time spent by every component is measured.
+    profiling: bool,
+    /// Listeners notified of the simulation progress.
+    listeners: Listeners<T>,
+    /// Handle for controlling the simulation from other threads.
+    handle: SimulationHandle,
+    /// Time of the last simulation cycle.
+    t_last: T,
+    /// Breakpoints for debugging the simulation.
+    debugger: Debugger<M, T>,
+    /// Guard against zero-delay loops.
+    zero_delay: ZeroDelayGuard<T>,
+    /// Bookkeeping of the simulation runs for [`RunRecord`]s.
+    run_info: RunInfo<T>,
+}
 
-impl<T: Simulator> RootCoordinator<T> {
+impl<T: Time, M: Simulator<T>> RootCoordinator<M, T> {
     /// Creates a new root coordinator from a DEVS-compliant model.
-    pub fn new(model: T) -> Self {
-        Self(model)
+    pub fn new(model: M) -> Self {
+        Self::with_merge_order(model, MergeOrder::default())
+    }
+
+    /// Creates a new root coordinator that merges messages into ports following a given [`MergeOrder`].
+    /// Use [`MergeOrder::Deterministic`] for order-sensitive models simulated with parallel features.
+    pub fn with_merge_order(model: M, merge_order: MergeOrder) -> Self {
+        Self {
+            model,
+            merge_order,
+            bag_policy: BagPolicy::default(),
+            profiling: false,
+            listeners: Listeners::default(),
+            handle: SimulationHandle::default(),
+            t_last: T::ZERO,
+            debugger: Debugger::default(),
+            zero_delay: ZeroDelayGuard::default(),
+            run_info: RunInfo::new(),
+        }
+    }
+
+    /// Returns a [`SimulationHandle`] for pausing, resuming, single-stepping, or aborting
+    /// [`RootCoordinator::simulate`] and [`RootCoordinator::simulate_until`] from another thread.
+    pub fn handle(&self) -> SimulationHandle {
+        self.handle.clone()
+    }
+
+    /// Derives the random number generator streams of all the components from a master seed.
+    /// Every stream depends only on the master seed and the path of its component, so simulations
+    /// are reproducible regardless of the execution order of the models (e.g., when using parallel features).
+    pub fn set_seed(&mut self, seed: u64) {
+        let path = self.model.get_name().to_string();
+        self.model.seed_rng(seed, &path);
+        self.run_info.seed = Some(seed);
     }
 
-    /// Runs a simulation for a given period of time.
-    pub fn simulate(&mut self, t_end: f64) {
-        let mut t_next = self.start(0.);
-        while t_next < t_end {
-            self.collection(t_next);
-            t_next = self.transition(t_next);
+    /// Sets the memory policy of the bags that store the messages of ports.
+    /// It is applied to all the ports of the model when the simulation starts.
+    pub fn set_bag_policy(&mut self, policy: BagPolicy) {
+        self.bag_policy = policy;
+    }
+
+    /// Enables or disables the profiler. When enabled, the wall-clock time that every component spends
+    /// in its output function, transition functions, and message propagation is measured in the next
+    /// simulations and reported by [`RootCoordinator::profile`]. Profiling is disabled by default,
+    /// and it is not supported when targeting WebAssembly.
+    pub fn set_profiling(&mut self, enabled: bool) {
+        self.profiling = enabled;
+    }
+
+    /// Sets the maximum number of consecutive simulation cycles at the same simulation time
+    /// (by default, 100000). Illegitimate models (e.g., cycles of components with zero time advance)
+    /// would otherwise execute state transitions forever without advancing the simulation time.
+    /// When the limit is reached, the simulation runs a few more cycles to find out which atomic models
+    /// are involved, and then it panics with their paths. Use [`None`] to disable the guard.
+    pub fn set_zero_delay_limit(&mut self, limit: Option<usize>) {
+        self.zero_delay.set_limit(limit);
+    }
+
+    /// Returns the time spent by every component in the last simulation, sorted by total time in
+    /// descending order. The report is empty unless profiling was enabled with [`RootCoordinator::set_profiling`].
+    pub fn profile(&self) -> Profile {
+        Profile::new(&self.model)
+    }
+
+    /// Returns the summaries of the statistic accumulators of every component in the last simulation
+    /// (see the [`crate::stats`] module). Time-weighted averages are computed until the last simulation cycle.
+    pub fn stats(&self) -> StatsReport {
+        StatsReport::new(&self.model, self.t_last.as_f64())
+    }
+
+    /// Returns the metadata of the last simulation run (or of the current one, if it has not stopped yet):
+    /// crate version and features, seed, model structure, simulation and wall-clock times, and event counts.
+    /// Store it next to the results of your experiments to make them reproducible.
+    pub fn run_record(&self) -> RunRecord {
+        self.run_info.record(&self.model, self.t_last)
+    }
+
+    /// Registers a new [`Listener`] that will be notified of the simulation progress.
+    pub fn add_listener(&mut self, listener: Box<dyn Listener<T>>) {
+        self.listeners.push(listener);
+    }
+
+    /// Registers a list of [`TraceSink`]s that will receive a [`crate::tracing::TraceRecord`]
+    /// for every simulation event. Output messages are formatted with `formatter`, if any.
+    pub fn add_trace_sinks(
+        &mut self,
+        sinks: Vec<Box<dyn TraceSink>>,
+        formatter: Option<ValueFormatter>,
+    ) {
+        let tracer = Tracer::new(self.model.get_name(), sinks, formatter);
+        self.add_listener(Box::new(tracer));
+    }
+
+    /// Starts the simulation at time zero. It returns the time of the first simulation cycle.
+    /// Use it with [`RootCoordinator::step`] and [`RootCoordinator::stop_simulation`]
+    /// to drive the simulation one cycle at a time.
+    /// If the simulation is paused at a breakpoint, the paused simulation is stopped first.
+    pub fn start_simulation(&mut self) -> T {
+        if let DebugState::Paused(_) = self.debugger.state {
+            self.stop_simulation();
+        }
+        self.handle.reset();
+        self.zero_delay.reset();
+        self.t_last = T::ZERO;
+        let path = self.model.get_name().to_string();
+        self.model.set_path(&path);
+        self.model.set_merge_order(self.merge_order);
+        self.model.set_bag_policy(self.bag_policy);
+        self.model.set_profiling(self.profiling);
+        // listeners and breakpoints read the output ports after the collection phase
+        let inspected = !self.listeners.is_empty() || !self.debugger.is_empty();
+        self.model.set_move_messages(!inspected);
+        self.run_info.start(&self.model);
+        let t_next = self.model.start(T::ZERO);
+        if !self.listeners.is_empty() {
+            self.listeners.start(&self.model, T::ZERO);
+        }
+        self.debugger.start();
+        t_next
+    }
+
+    /// Executes the next simulation cycle. It returns the time of the following simulation cycle.
+    /// If the simulation is paused at a breakpoint, it only completes the paused simulation cycle.
+    pub fn step(&mut self) -> T {
+        self.try_step().unwrap_or_else(|e| raise(e))
+    }
+
+    /// Executes the next simulation cycle as [`RootCoordinator::step`]. If the output function or
+    /// a transition function of an atomic model panics, it returns the panic as an error.
+    fn try_step(&mut self) -> Result<T, ComponentPanic> {
+        if let DebugState::Paused(t) = self.debugger.state {
+            return self.try_transition_phase(t);
         }
-        self.stop(t_next);
+        let t = self.model.get_t_next();
+        self.try_collection_phase(t)?;
+        self.try_transition_phase(t)
+    }
+
+    /// Stops the simulation at the time of the next simulation cycle.
+    /// If the simulation is paused at a breakpoint, the paused simulation cycle is completed first.
+    pub fn stop_simulation(&mut self) {
+        if let DebugState::Paused(t) = self.debugger.state {
+            self.transition_phase(t);
+        }
+        self.debugger.state = DebugState::Idle;
+        let t_stop = self.model.get_t_next();
+        self.model.stop(t_stop);
+        self.run_info.stop();
+        if !self.listeners.is_empty() {
+            let record = self.run_record();
+            self.listeners.stop(t_stop, &record);
+        }
+    }
+
+    /// Executes the collection phase of the simulation cycle at time `t`.
+    fn collection_phase(&mut self, t: T) {
+        self.try_collection_phase(t).unwrap_or_else(|e| raise(e));
+    }
+
+    /// Executes the collection phase of the simulation cycle at time `t`.
+    /// If an atomic model panics, the panic is returned as an error.
+    fn try_collection_phase(&mut self, t: T) -> Result<(), ComponentPanic> {
+        #[cfg(feature = "debug_checks")]
+        check_invariants(&self.model, t);
+        self.t_last = t;
+        self.run_info.n_cycles += 1;
+        contain(|| self.model.collection(t))?;
+        if !self.listeners.is_empty() {
+            self.listeners.collection(&self.model, t);
+        }
+        Ok(())
+    }
+
+    /// Executes the transition phase of the simulation cycle at time `t`.
+    /// It returns the time of the following simulation cycle.
+    fn transition_phase(&mut self, t: T) -> T {
+        self.try_transition_phase(t).unwrap_or_else(|e| raise(e))
+    }
+
+    /// Executes the transition phase of the simulation cycle at time `t`.
+    /// It returns the time of the following simulation cycle, or the panic of an atomic model as an error.
+    fn try_transition_phase(&mut self, t: T) -> Result<T, ComponentPanic> {
+        self.debugger.state = DebugState::Running;
+        let t_next = contain(|| self.model.transition(t))?;
+        if !self.listeners.is_empty() {
+            self.listeners.transition(&self.model, t);
+        }
+        self.zero_delay.check(&self.model, t);
+        Ok(t_next)
+    }
+
+    /// Registers a new [`Breakpoint`] for [`RootCoordinator::run_to_breakpoint`] and returns its identifier.
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint<M, T>) -> usize {
+        if self.debugger.state != DebugState::Idle {
+            // output ports of the simulation in progress must keep their messages from now on
+            self.model.set_move_messages(false);
+        }
+        self.debugger.add(breakpoint)
+    }
+
+    /// Removes a breakpoint. It returns `false` if there is no breakpoint with the provided identifier.
+    pub fn remove_breakpoint(&mut self, id: usize) -> bool {
+        self.debugger.remove(id)
+    }
+
+    /// Removes all the breakpoints.
+    pub fn clear_breakpoints(&mut self) {
+        self.debugger.clear();
+    }
+
+    /// Runs the simulation until a [`Breakpoint`] triggers. It starts a new simulation if there is
+    /// no simulation in progress, and it continues the paused simulation otherwise.
+    ///
+    /// When a breakpoint triggers, the simulation is paused right after the collection phase of the
+    /// current simulation cycle and it returns the [`Break`] that describes it. Call this method again
+    /// to continue the simulation. If the next simulation cycle is at or after `t_end`, the simulation
+    /// is stopped and it returns [`None`]. The simulation can also be aborted with a [`SimulationHandle`].
+    pub fn run_to_breakpoint(&mut self, t_end: T) -> Option<Break<T>> {
+        let mut t_next = match self.debugger.state {
+            DebugState::Idle => {
+                let t_next = self.start_simulation();
+                self.run_info.t_end = t_end;
+                t_next
+            }
+            DebugState::Running => self.model.get_t_next(),
+            DebugState::Paused(t) => self.transition_phase(t),
+        };
+        while t_next < t_end && self.handle.proceed() {
+            let t = t_next;
+            self.collection_phase(t);
+            if let Some(brk) = self.debugger.check(&self.model, t) {
+                self.debugger.state = DebugState::Paused(t);
+                return Some(brk);
+            }
+            t_next = self.transition_phase(t);
+        }
+        self.stop_simulation();
+        None
+    }
+
+    /// Returns the messages of an output port of the component with the provided path, or [`None`]
+    /// if there is no such component or port. Output ports only contain messages while the simulation
+    /// is paused at a breakpoint (see [`RootCoordinator::run_to_breakpoint`]).
+    pub fn output_values(&self, path: &str, port: &str) -> Option<PortValues<'_>> {
+        let component = debugger::find(&self.model, path)?.get_component();
+        let port = component
+            .get_out_ports()
+            .iter()
+            .find(|p| p.get_name() == port)?;
+        Some(PortValues::new(port.as_ref()))
+    }
+
+    /// Returns the messages of an input port of the component with the provided path, or [`None`]
+    /// if there is no such component or port. While the simulation is paused at a breakpoint, input ports
+    /// contain the messages sent by sibling components. Messages sent to a coupled model are forwarded
+    /// to its subcomponents in the transition phase.
+    pub fn input_values(&self, path: &str, port: &str) -> Option<PortValues<'_>> {
+        let component = debugger::find(&self.model, path)?.get_component();
+        let port = component
+            .get_in_ports()
+            .iter()
+            .find(|p| p.get_name() == port)?;
+        Some(PortValues::new(port.as_ref()))
+    }
+
+    /// Runs a simulation for a given period of time or until it is aborted with a [`SimulationHandle`].
+    pub fn simulate(&mut self, t_end: T) {
+        let mut t_next = self.start_simulation();
+        self.run_info.t_end = t_end;
+        while t_next < t_end && self.handle.proceed() {
+            t_next = self.step();
+        }
+        self.stop_simulation();
+    }
+
+    /// Runs a simulation for a given period of time as [`RootCoordinator::simulate`]. If the output function
+    /// or a transition function of an atomic model panics, it returns the panic as a [`ComponentPanic`]
+    /// with the path of the model and the simulation time instead of propagating it. In this case,
+    /// the simulation is not stopped, and the state of the model is undefined.
+    pub fn try_simulate(&mut self, t_end: T) -> Result<(), ComponentPanic> {
+        let mut t_next = self.start_simulation();
+        self.run_info.t_end = t_end;
+        while t_next < t_end && self.handle.proceed() {
+            t_next = self.try_step()?;
+        }
+        self.stop_simulation();
+        Ok(())
+    }
+
+    /// Runs a simulation for a given period of time as [`RootCoordinator::simulate`], reporting its [`Progress`]
+    /// to `report` every `interval` of wall-clock time (e.g., to drive a progress bar).
+    /// `report` is called one last time after the simulation stops, with [`Progress::finished`] set to `true`.
+    pub fn simulate_with_progress<F: FnMut(&Progress<T>)>(
+        &mut self,
+        t_end: T,
+        interval: Duration,
+        mut report: F,
+    ) {
+        let mut t_report = interval;
+        let progress = self.simulate_tracked(t_end, |progress| {
+            if progress.elapsed >= t_report {
+                report(progress);
+                t_report = progress.elapsed + interval;
+            }
+            true
+        });
+        report(&progress);
+    }
+
+    /// Runs a simulation for a given period of time as [`RootCoordinator::simulate`], unless it takes
+    /// more than `wall_limit` of wall-clock time. Then, it stops the simulation after the current
+    /// simulation cycle (calling the `stop` method of the model as usual) and returns its [`Progress`] as an error,
+    /// so you know how far the simulation got. Otherwise, it returns the [`Progress`] of the complete simulation.
+    pub fn simulate_with_budget(
+        &mut self,
+        t_end: T,
+        wall_limit: Duration,
+    ) -> Result<Progress<T>, Progress<T>> {
+        let mut exceeded = false;
+        let progress = self.simulate_tracked(t_end, |progress| {
+            exceeded = progress.elapsed > wall_limit;
+            !exceeded
+        });
+        match exceeded {
+            true => Err(progress),
+            false => Ok(progress),
+        }
+    }
+
+    /// Runs a simulation for a given period of time as [`RootCoordinator::simulate`], keeping track of its
+    /// [`Progress`]. After every simulation cycle, it calls `proceed`, and stops the simulation if it returns `false`.
+    /// It returns the final progress of the simulation.
+    fn simulate_tracked<F: FnMut(&Progress<T>) -> bool>(
+        &mut self,
+        t_end: T,
+        mut proceed: F,
+    ) -> Progress<T> {
+        let start = Instant::now();
+        let mut progress = Progress {
+            t: T::ZERO,
+            t_end,
+            n_cycles: 0,
+            elapsed: Duration::ZERO,
+            finished: false,
+        };
+        let mut t_next = self.start_simulation();
+        self.run_info.t_end = t_end;
+        while t_next < t_end && self.handle.proceed() {
+            progress.t = t_next;
+            t_next = self.step();
+            progress.n_cycles += 1;
+            progress.elapsed = start.elapsed();
+            if !proceed(&progress) {
+                break;
+            }
+        }
+        self.stop_simulation();
+        progress.elapsed = start.elapsed();
+        progress.finished = true;
+        progress
+    }
+
+    /// Runs a simulation until `stop` returns `true`, the model becomes passive,
+    /// or the simulation is aborted with a [`SimulationHandle`].
+    /// `stop` is evaluated after every simulation cycle with the time of the cycle and the root model.
+    /// It returns the time of the last simulation cycle.
+    pub fn simulate_until<F: FnMut(T, &M) -> bool>(&mut self, mut stop: F) -> T {
+        let (mut t, mut t_next) = (T::ZERO, self.start_simulation());
+        while t_next < T::INFINITY && self.handle.proceed() {
+            t = t_next;
+            t_next = self.step();
+            if stop(t, &self.model) {
+                break;
+            }
+        }
+        self.stop_simulation();
+        t
+    }
+}
+
+impl<M: Simulator> RootCoordinator<M> {
+    /// Re-executes a simulation run recorded in a replay file (see [`Replay`]).
+    /// `ports` must register the message type of every input port of the root model.
+    /// It returns the time of the last simulation cycle.
+    pub fn replay<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        ports: &ReplayPorts,
+    ) -> Result<f64, ReplayError> {
+        self.replay_log(&Replay::load(path)?, ports)
+    }
+
+    /// Re-executes a recorded simulation run. The root model is seeded with the recorded seed,
+    /// and every recorded message is injected at the same time and in the same external transition
+    /// as in the original run. It returns the time of the last simulation cycle.
+    pub fn replay_log(&mut self, replay: &Replay, ports: &ReplayPorts) -> Result<f64, ReplayError> {
+        ports.check(self.model.get_component())?;
+        let mut events = Vec::with_capacity(replay.events.len());
+        for event in &replay.events {
+            // ports were checked, so unknown ports are not part of the root model
+            let port = self.model.get_component().get_in_port(&event.port);
+            let port = port.ok_or_else(|| ReplayError::UnknownPort(event.port.clone()))?;
+            let values = ports.decode(&event.port, &event.value)?;
+            events.push((event.batch, event.time, event.confluent, port, values));
+        }
+        if let Some(seed) = replay.seed {
+            self.set_seed(seed);
+        }
+        let notify = !self.listeners.is_empty();
+        let mut t_next = self.start_simulation();
+        let mut events = events.into_iter().peekable();
+        while let Some(&(batch, t, confluent, _, _)) = events.peek() {
+            while t_next < t || (t_next == t && !confluent) {
+                t_next = self.step();
+            }
+            if t >= t_next {
+                attributed(|| self.model.collection(t));
+                if notify {
+                    self.listeners.collection(&self.model, t);
+                }
+            }
+            while let Some((_, _, _, port, values)) = events.next_if(|e| e.0 == batch) {
+                // Safety: root coordinator injecting messages between simulation phases
+                unsafe { port.inject(values.as_ref()) };
+            }
+            self.t_last = t;
+            t_next = attributed(|| self.model.transition(t));
+            if notify {
+                self.listeners.transition(&self.model, t);
+            }
+        }
+        let mut t = replay.events.last().map_or(0., |e| e.time);
+        while t_next <= replay.end {
+            t = t_next;
+            t_next = self.step();
+        }
+        self.stop_simulation();
+        Ok(t)
     }
 }
 
-impl<T> Deref for RootCoordinator<T> {
-    type Target = T;
+impl<M, T: Time> Deref for RootCoordinator<M, T> {
+    type Target = M;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.model
     }
 }
 
-impl<T> DerefMut for RootCoordinator<T> {
+impl<M, T: Time> DerefMut for RootCoordinator<M, T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.model
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::Generator;
+    use crate::modeling::OutPort;
+    use std::sync::{Arc, Mutex};
+
+    /// Atomic model that emits its value at time 1.
+    struct Emitter {
+        component: Component,
+        output: OutPort<usize>,
+        value: usize,
+        sigma: f64,
+    }
+
+    impl Atomic for Emitter {
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+
+        fn start(&mut self) {
+            self.sigma = 1.;
+        }
+
+        fn lambda(&self) {
+            // Safety: adding messages on atomic model's output port at lambda
+            unsafe { self.output.add_value(self.value) };
+        }
+
+        fn delta_int(&mut self) {
+            self.sigma = f64::INFINITY;
+        }
+
+        fn delta_ext(&mut self, _e: f64) {}
+
+        fn ta(&self) -> f64 {
+            self.sigma
+        }
+    }
+
+    /// Listener that stores the messages sent through the output port of the root model.
+    struct Observer(Arc<Mutex<Vec<usize>>>);
+
+    impl Listener for Observer {
+        fn on_output(&mut self, _t: f64, path: &str, _port: &str, values: &PortValues) {
+            if path == "root" {
+                let values = values.downcast::<usize>().unwrap();
+                self.0.lock().unwrap().extend_from_slice(values);
+            }
+        }
+    }
+
+    #[test]
+    fn test_simulate_until() {
+        let mut simulator = RootCoordinator::new(Generator::new("generator", 2.));
+        assert_eq!(10., simulator.simulate_until(|_, g| g.count() == 5));
+        assert_eq!(5, simulator.count());
+        let t = simulator.simulate_until(|t, _| t > 5.);
+        assert_eq!((6., 3), (t, simulator.count()));
+    }
+
+    #[test]
+    fn test_simulate_with_progress() {
+        let mut simulator = RootCoordinator::new(Generator::new("generator", 2.));
+        let mut reports = Vec::new();
+        simulator.simulate_with_progress(10., Duration::ZERO, |p| {
+            reports.push((p.t, p.n_cycles, p.finished))
+        });
+        // the generator is imminent at times 2, 4, 6, and 8
+        let expected = [(2., 1), (4., 2), (6., 3), (8., 4), (8., 4)];
+        let expected: Vec<_> = (expected.iter().enumerate())
+            .map(|(i, &(t, n))| (t, n, i == 4))
+            .collect();
+        assert_eq!(expected, reports);
+
+        let mut n_reports = 0;
+        simulator.simulate_with_progress(10., Duration::MAX, |p| {
+            n_reports += 1;
+            assert!(p.finished);
+            assert_eq!(Some(1.), p.fraction());
+        });
+        assert_eq!(1, n_reports);
+    }
+
+    #[test]
+    fn test_simulate_with_budget() {
+        let mut simulator = RootCoordinator::new(Generator::new("generator", 2.));
+        let progress = simulator.simulate_with_budget(10., Duration::MAX).unwrap();
+        assert_eq!((8., 4), (progress.t, progress.n_cycles));
+        assert_eq!(4, simulator.count());
+
+        let progress = simulator
+            .simulate_with_budget(10., Duration::ZERO)
+            .unwrap_err();
+        // the budget is checked after every simulation cycle
+        assert_eq!((2., 1), (progress.t, progress.n_cycles));
+        assert_eq!(1, simulator.count());
+        assert_eq!(f64::INFINITY, simulator.get_t_next());
+    }
+
+    /// Atomic model whose time advance is `first` until its first internal transition, and `next` afterwards.
+    struct Spinner {
+        component: Component,
+        first: f64,
+        next: f64,
+        sigma: f64,
+    }
+
+    impl Spinner {
+        fn new(first: f64, next: f64) -> Self {
+            Self {
+                component: Component::new("spinner"),
+                first,
+                next,
+                sigma: f64::INFINITY,
+            }
+        }
+    }
+
+    impl Atomic for Spinner {
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+
+        fn start(&mut self) {
+            self.sigma = self.first;
+        }
+
+        fn lambda(&self) {}
+
+        fn delta_int(&mut self) {
+            self.sigma = self.next;
+        }
+
+        fn delta_ext(&mut self, _e: f64) {}
+
+        fn ta(&self) -> f64 {
+            self.sigma
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "at time 3.0. Components involved in the last 10 cycles: root.spinner (10 transitions)"
+    )]
+    fn test_zero_delay_loop() {
+        let mut root = Coupled::new("root");
+        root.add_component(Box::new(Generator::new("generator", 1.)));
+        root.add_component(Box::new(Spinner::new(3., 0.)));
+        let mut simulator = RootCoordinator::new(root);
+        simulator.set_zero_delay_limit(Some(10));
+        simulator.simulate(3.);
+        simulator.simulate(f64::INFINITY);
+    }
+
+    #[cfg(any(debug_assertions, feature = "checked_ta"))]
+    #[test]
+    #[should_panic(expected = "component root.spinner returned an invalid time advance: NaN")]
+    fn test_nan_time_advance() {
+        let mut root = Coupled::new("root");
+        root.add_component(Box::new(Spinner::new(1., f64::NAN)));
+        RootCoordinator::new(root).simulate(f64::INFINITY);
+    }
+
+    #[cfg(any(debug_assertions, feature = "checked_ta"))]
+    #[test]
+    #[should_panic(expected = "component spinner returned an invalid time advance: -1.0")]
+    fn test_negative_time_advance() {
+        RootCoordinator::new(Spinner::new(-1., 0.)).simulate(f64::INFINITY);
+    }
+
+    /// Atomic model that panics in its first internal transition.
+    struct Bomb {
+        component: Component,
+    }
+
+    impl Atomic for Bomb {
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+
+        fn lambda(&self) {}
+
+        fn delta_int(&mut self) {
+            panic!("boom");
+        }
+
+        fn delta_ext(&mut self, _e: f64) {}
+
+        fn ta(&self) -> f64 {
+            1.5
+        }
+    }
+
+    fn bomb_model() -> Coupled {
+        let mut coupled = Coupled::new("coupled");
+        coupled.add_component(Box::new(Bomb {
+            component: Component::new("bomb"),
+        }));
+        let mut root = Coupled::new("root");
+        root.add_component(Box::new(Generator::new("generator", 1.)));
+        root.add_component(Box::new(coupled));
+        root
+    }
+
+    #[test]
+    fn test_try_simulate() {
+        let mut simulator = RootCoordinator::new(bomb_model());
+        let expected = ComponentPanic {
+            path: "root.coupled.bomb".to_string(),
+            time: 1.5,
+            function: "delta_int",
+            message: "boom".to_string(),
+        };
+        assert_eq!(Err(expected), simulator.try_simulate(f64::INFINITY));
+        assert_eq!(Ok(()), simulator.try_simulate(1.));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "component root.coupled.bomb panicked in delta_int at time 1.5: boom"
+    )]
+    fn test_component_panic() {
+        RootCoordinator::new(bomb_model()).simulate(f64::INFINITY);
+    }
+
+    #[test]
+    fn test_merge_order() {
+        let mut root = Coupled::new("root");
+        root.add_out_port::<usize>("output");
+        for value in 0..16 {
+            let mut component = Component::new(&format!("emitter_{value}"));
+            let output = component.add_out_port("output");
+            root.add_component(Box::new(Emitter {
+                component,
+                output,
+                value,
+                sigma: f64::INFINITY,
+            }));
+        }
+        for value in (0..16).rev() {
+            root.add_eoc(&format!("emitter_{value}"), "output", "output");
+        }
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let mut simulator = RootCoordinator::with_merge_order(root, MergeOrder::Deterministic);
+        simulator.add_listener(Box::new(Observer(received.clone())));
+        let expected: Vec<_> = (0..16).rev().collect();
+        for _ in 0..4 {
+            simulator.simulate(f64::INFINITY);
+            assert_eq!(expected, *received.lock().unwrap());
+            received.lock().unwrap().clear();
+        }
+    }
+
+    #[test]
+    fn test_move_messages() {
+        let mut root = Coupled::new("root");
+        root.add_out_port::<usize>("output");
+        root.add_out_port::<usize>("copy");
+        for value in 0..2 {
+            let mut component = Component::new(&format!("emitter_{value}"));
+            let output = component.add_out_port("output");
+            root.add_component(Box::new(Emitter {
+                component,
+                output,
+                value,
+                sigma: f64::INFINITY,
+            }));
+            root.add_eoc(&format!("emitter_{value}"), "output", "output");
+        }
+        root.add_eoc("emitter_1", "output", "copy");
+        let mut simulator = RootCoordinator::new(root);
+        for inspected in [false, true] {
+            if inspected {
+                simulator.add_breakpoint(Breakpoint::at_time(f64::INFINITY));
+            }
+            let t = simulator.start_simulation();
+            simulator.collection_phase(t);
+            let len = |path, port| simulator.output_values(path, port).unwrap().len();
+            // the message of the first emitter is moved, as it only has one destination
+            let moved = cfg!(not(feature = "par_couplings")) && !inspected;
+            assert_eq!(!moved as usize, len("root.emitter_0", "output"));
+            assert_eq!(1, len("root.emitter_1", "output"));
+            assert_eq!((2, 1), (len("root", "output"), len("root", "copy")));
+            simulator.transition_phase(t);
+            // only the ports that contained messages are cleared, but all of them end up empty
+            let len = |path, port| simulator.output_values(path, port).unwrap().len();
+            assert_eq!(
+                0,
+                len("root.emitter_0", "output") + len("root.emitter_1", "output")
+            );
+            assert_eq!(0, len("root", "output") + len("root", "copy"));
+            simulator.stop_simulation();
+        }
+    }
+
+    #[test]
+    fn test_profile() {
+        let mut root = Coupled::new("root");
+        root.add_out_port::<usize>("output");
+        for value in 0..4 {
+            let mut component = Component::new(&format!("emitter_{value}"));
+            let output = component.add_out_port("output");
+            root.add_component(Box::new(Emitter {
+                component,
+                output,
+                value,
+                sigma: f64::INFINITY,
+            }));
+            root.add_eoc(&format!("emitter_{value}"), "output", "output");
+        }
+        let mut simulator = RootCoordinator::new(root);
+        simulator.simulate(f64::INFINITY);
+        assert!(simulator.profile().components().is_empty());
+
+        simulator.set_profiling(true);
+        simulator.simulate(f64::INFINITY);
+        let profile = simulator.profile();
+        assert_eq!(5, profile.components().len());
+        let totals: Vec<_> = profile.components().iter().map(|c| c.total()).collect();
+        assert!(totals.windows(2).all(|w| w[0] >= w[1]));
+        assert_eq!(1, profile.get("root").unwrap().calls);
+        for value in 0..4 {
+            let emitter = profile.get(&format!("root.emitter_{value}")).unwrap();
+            assert_eq!(2, emitter.calls);
+            assert!(emitter.delta_ext.is_zero() && emitter.propagation.is_zero());
+        }
+        assert_eq!(6, profile.to_string().lines().count());
+    }
+
+    #[test]
+    fn test_simulation_handle() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let thread = std::thread::spawn(move || {
+            let mut simulator = RootCoordinator::new(Generator::new("generator", 1.));
+            let handle = simulator.handle();
+            handle.pause();
+            tx.send(handle).unwrap();
+            simulator.simulate(f64::INFINITY);
+            simulator.count()
+        });
+        let handle = rx.recv().unwrap();
+        assert!(handle.is_paused());
+        handle.step();
+        handle.step();
+        while handle.cycles() < 2 {
+            std::thread::yield_now();
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        assert_eq!(2, handle.cycles());
+        handle.resume();
+        while handle.cycles() < 100 {
+            std::thread::yield_now();
+        }
+        handle.abort();
+        let count = thread.join().unwrap();
+        assert_eq!(handle.cycles(), count as u64);
     }
 }
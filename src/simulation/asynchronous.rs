@@ -0,0 +1,737 @@
+use super::listener::Listeners;
+use super::run_record::RunInfo;
+use super::{attributed, Listener, Replay, ReplayError, ReplayPorts, RunRecord, Simulator};
+use crate::modeling::port::{Port, Values};
+use crate::{DynRef, Shared};
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Debug};
+use std::future::{poll_fn, Future};
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::pin::pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+/// External event: name of the input port of the root model, and boxed `Vec<V>`.
+type Event = (String, Box<dyn Any + Send>);
+
+/// Output binding: output port of the root model, and callback that receives its `Values<V>`.
+type Actuator = (Shared<dyn Port>, Box<dyn FnMut(f64, &dyn Any)>);
+
+/// Errors that may occur when sending events to an [`AsyncRootCoordinator`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SendError {
+    /// The root model does not contain an input port with the given name.
+    UnknownPort(String),
+    /// The message type is not compatible with the input port with the given name.
+    IncompatiblePort(String),
+    /// The root coordinator was dropped.
+    Closed,
+}
+
+impl fmt::Display for SendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownPort(p) => write!(f, "input port {p} does not exist"),
+            Self::IncompatiblePort(p) => {
+                write!(f, "message type is not compatible with input port {p}")
+            }
+            Self::Closed => write!(f, "root coordinator was dropped"),
+        }
+    }
+}
+
+impl std::error::Error for SendError {}
+
+/// Events pending to be injected into the root model.
+#[derive(Default)]
+struct Inbox {
+    /// Pending events, in order of arrival.
+    events: VecDeque<Event>,
+    /// Waker of the simulation future while it waits for events.
+    waker: Option<Waker>,
+    /// Number of live [`EventSender`]s.
+    senders: usize,
+    /// It is `true` if the root coordinator was dropped.
+    closed: bool,
+}
+
+impl Inbox {
+    /// Wakes up the simulation future if it is waiting for events.
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Handle for sending external events to the root model of an [`AsyncRootCoordinator`].
+/// Senders can be cloned and moved to other tasks or threads. Events are injected between simulation cycles.
+pub struct EventSender {
+    /// Events pending to be injected.
+    inbox: Arc<Mutex<Inbox>>,
+    /// Input ports of the root model and the type ID of their messages.
+    ports: Arc<HashMap<String, TypeId>>,
+}
+
+impl EventSender {
+    /// Sends a message to an input port of the root model.
+    /// It returns an error if the port does not exist, the message type is not compatible with the port,
+    /// or the root coordinator was dropped.
+    pub fn send<V: DynRef + Clone + Send>(&self, port: &str, value: V) -> Result<(), SendError> {
+        match self.ports.get(port) {
+            None => return Err(SendError::UnknownPort(port.to_string())),
+            Some(&id) if id != TypeId::of::<Values<V>>() => {
+                return Err(SendError::IncompatiblePort(port.to_string()))
+            }
+            _ => (),
+        }
+        let mut inbox = self.inbox.lock().unwrap();
+        if inbox.closed {
+            return Err(SendError::Closed);
+        }
+        inbox
+            .events
+            .push_back((port.to_string(), Box::new(vec![value])));
+        inbox.wake();
+        Ok(())
+    }
+}
+
+impl Clone for EventSender {
+    fn clone(&self) -> Self {
+        self.inbox.lock().unwrap().senders += 1;
+        Self {
+            inbox: self.inbox.clone(),
+            ports: self.ports.clone(),
+        }
+    }
+}
+
+impl Drop for EventSender {
+    fn drop(&mut self) {
+        let mut inbox = self.inbox.lock().unwrap();
+        inbox.senders -= 1;
+        // passive models stop waiting for events when the last sender is dropped
+        if inbox.senders == 0 {
+            inbox.wake();
+        }
+    }
+}
+
+/// Binding of a hardware driver (GPIO, serial, CAN, etc.) to an input port of the root model.
+/// Driver callbacks call [`InputBinding::trigger`] when a hardware event occurs,
+/// so the model receives the event in an external transition at the current real time.
+pub struct InputBinding<V> {
+    /// Sender used for injecting the events.
+    sender: EventSender,
+    /// Name of the bound input port.
+    port: String,
+    /// Type of the messages of the bound input port.
+    value: PhantomData<fn(V)>,
+}
+
+impl<V: DynRef + Clone + Send> InputBinding<V> {
+    /// Returns the name of the bound input port.
+    pub fn port(&self) -> &str {
+        &self.port
+    }
+
+    /// Injects a message into the bound input port.
+    /// It returns an error if the root coordinator was dropped.
+    pub fn trigger(&self, value: V) -> Result<(), SendError> {
+        self.sender.send(&self.port, value)
+    }
+}
+
+impl<V> Clone for InputBinding<V> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            port: self.port.clone(),
+            value: PhantomData,
+        }
+    }
+}
+
+/// Reason why an [`AsyncRootCoordinator`] stopped waiting.
+enum Wake {
+    /// External events arrived.
+    Events(Vec<Event>),
+    /// The time to wait elapsed.
+    Timeout,
+    /// There are no senders left, so no more events can arrive.
+    Closed,
+}
+
+/// Root coordinator whose simulation loop is a future, so it can be embedded in asynchronous services.
+/// It does not depend on any particular runtime: real-time simulations receive the sleep function
+/// of the runtime (e.g., `tokio::time::sleep`). External events are sent with [`EventSender`]s
+/// and injected into the input ports of the root model between simulation cycles.
+/// The crate does not depend on tokio, so there is no built-in tokio adapter (see [`AsyncRootCoordinator::simulate_rt`]).
+pub struct AsyncRootCoordinator<M> {
+    /// DEVS model to be simulated.
+    model: M,
+    /// Events pending to be injected.
+    inbox: Arc<Mutex<Inbox>>,
+    /// Listeners notified of the simulation progress.
+    listeners: Listeners<f64>,
+    /// Actuators bound to output ports of the root model.
+    actuators: Vec<Actuator>,
+    /// Bookkeeping of the simulation runs for [`RunRecord`]s (including the master seed, if any).
+    run_info: RunInfo<f64>,
+    /// Time of the last simulation cycle.
+    t_last: f64,
+    /// Message types of the input ports and log of the recorded simulation, if any.
+    recording: Option<(ReplayPorts, Replay)>,
+}
+
+impl<M: Simulator> AsyncRootCoordinator<M> {
+    /// Creates a new asynchronous root coordinator from a DEVS-compliant model.
+    pub fn new(model: M) -> Self {
+        Self {
+            model,
+            inbox: Arc::default(),
+            listeners: Listeners::default(),
+            actuators: Vec::new(),
+            run_info: RunInfo::new(),
+            t_last: 0.,
+            recording: None,
+        }
+    }
+
+    /// Derives the random number generator streams of all the components from a master seed.
+    /// See [`super::RootCoordinator::set_seed`] for more details.
+    pub fn set_seed(&mut self, seed: u64) {
+        let path = self.model.get_name().to_string();
+        self.model.seed_rng(seed, &path);
+        self.run_info.seed = Some(seed);
+    }
+
+    /// Returns the metadata of the last simulation run. See [`super::RootCoordinator::run_record`] for more details.
+    pub fn run_record(&self) -> RunRecord {
+        self.run_info.record(&self.model, self.t_last)
+    }
+
+    /// Records the next simulations, so they can be re-executed with [`super::RootCoordinator::replay`].
+    /// `ports` must register the message type of every input port of the root model.
+    pub fn record(&mut self, ports: ReplayPorts) -> Result<(), ReplayError> {
+        ports.check(self.model.get_component())?;
+        self.recording = Some((ports, Replay::default()));
+        Ok(())
+    }
+
+    /// Returns the log of the last recorded simulation, if any.
+    /// Save it with [`Replay::save`] for replaying the simulation later.
+    pub fn replay(&self) -> Option<&Replay> {
+        self.recording.as_ref().map(|(_, replay)| replay)
+    }
+
+    /// Returns a new [`EventSender`] for injecting messages into the input ports of the root model.
+    pub fn sender(&self) -> EventSender {
+        let ports = self.model.get_component().get_in_ports().iter();
+        // Safety: reading port types between simulation phases
+        let ports = ports.map(|p| {
+            (
+                p.get_name().to_string(),
+                unsafe { p.get_values() }.type_id(),
+            )
+        });
+        self.inbox.lock().unwrap().senders += 1;
+        EventSender {
+            inbox: self.inbox.clone(),
+            ports: Arc::new(ports.collect()),
+        }
+    }
+
+    /// Binds a hardware driver to an input port of the root model.
+    /// It returns an error if the port does not exist or its message type is not `V`.
+    pub fn bind_input<V: DynRef + Clone + Send>(
+        &self,
+        port: &str,
+    ) -> Result<InputBinding<V>, SendError> {
+        let sender = self.sender();
+        match sender.ports.get(port) {
+            None => Err(SendError::UnknownPort(port.to_string())),
+            Some(&id) if id != TypeId::of::<Values<V>>() => {
+                Err(SendError::IncompatiblePort(port.to_string()))
+            }
+            _ => Ok(InputBinding {
+                sender,
+                port: port.to_string(),
+                value: PhantomData,
+            }),
+        }
+    }
+
+    /// Binds an actuator to an output port of the root model.
+    /// After every output function that sends messages through the port,
+    /// the actuator is called with the current simulation time and the messages.
+    /// It returns an error if the port does not exist or its message type is not `V`.
+    pub fn bind_output<V, F>(&mut self, port: &str, mut actuator: F) -> Result<(), SendError>
+    where
+        V: DynRef + Clone,
+        F: FnMut(f64, &[V]) + 'static,
+    {
+        let out_port = match self.model.get_component().get_out_port(port) {
+            Some(out_port) => out_port,
+            None => return Err(SendError::UnknownPort(port.to_string())),
+        };
+        // Safety: reading port types between simulation phases
+        if !unsafe { out_port.get_values() }.is::<Values<V>>() {
+            return Err(SendError::IncompatiblePort(port.to_string()));
+        }
+        let actuator = move |t, values: &dyn Any| {
+            // the message type was checked when binding the port
+            actuator(t, values.downcast_ref::<Values<V>>().unwrap())
+        };
+        self.actuators.push((out_port, Box::new(actuator)));
+        Ok(())
+    }
+
+    /// Registers a new [`Listener`] that will be notified of the simulation progress.
+    pub fn add_listener(&mut self, listener: Box<dyn Listener>) {
+        self.listeners.push(listener);
+    }
+
+    /// Runs a simulation as fast as possible until time `t_end`.
+    /// External events are injected at the time of the last simulation cycle.
+    /// When the model has no more cycles before `t_end`, the future waits for more events
+    /// until all the [`EventSender`]s are dropped. It returns the time of the last simulation cycle.
+    /// The future yields control to the runtime after every simulation cycle.
+    pub async fn simulate(&mut self, t_end: f64) -> f64 {
+        let (mut t, mut t_next) = (0., self.start(t_end));
+        loop {
+            let events = std::mem::take(&mut self.inbox.lock().unwrap().events);
+            if !events.is_empty() {
+                t_next = self.inject(t, events.into());
+            } else if t_next < t_end {
+                t = t_next;
+                t_next = self.step(t);
+                yield_now().await;
+            } else {
+                match self.wait(None::<std::future::Pending<()>>).await {
+                    Wake::Events(events) => t_next = self.inject(t, events),
+                    _ => break,
+                }
+            }
+        }
+        self.stop(t);
+        t
+    }
+
+    /// Runs a simulation paced to the wall-clock time until time `t_end`.
+    /// `time_scale` is the number of simulation time units per second, and `sleep` is a function
+    /// that returns a future that completes after a given duration (e.g., `tokio::time::sleep`).
+    /// External events are injected at the current (scaled) wall-clock time.
+    /// If `t_end` is infinite, a passive model stops when all the [`EventSender`]s are dropped.
+    /// It returns the time of the last simulation cycle. It panics if `time_scale` is not positive.
+    ///
+    /// ```ignore
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() {
+    ///     let mut simulator = AsyncRootCoordinator::new(build_model());
+    ///     let sender = simulator.sender();
+    ///     tokio::spawn(async move {
+    ///         tokio::time::sleep(Duration::from_secs(1)).await;
+    ///         sender.send("input", 1.).unwrap();
+    ///     });
+    ///     // One simulation time unit per second, paced with tokio timers
+    ///     simulator.simulate_rt(10., 1., tokio::time::sleep).await;
+    /// }
+    /// ```
+    pub async fn simulate_rt<S, F>(&mut self, t_end: f64, time_scale: f64, mut sleep: S) -> f64
+    where
+        S: FnMut(Duration) -> F,
+        F: Future<Output = ()>,
+    {
+        assert!(time_scale > 0., "time scale must be positive");
+        let origin = Instant::now();
+        let now = || origin.elapsed().as_secs_f64() * time_scale;
+        let (mut t, mut t_next) = (0., self.start(t_end));
+        loop {
+            let t_wake = t_next.min(t_end);
+            let wake = match t_wake.is_finite() {
+                true => {
+                    let delay = ((t_wake - now()) / time_scale).max(0.);
+                    self.wait(Some(sleep(Duration::from_secs_f64(delay)))).await
+                }
+                false => self.wait(None::<F>).await,
+            };
+            match wake {
+                Wake::Events(events) => {
+                    let t_event = now().clamp(t, t_next);
+                    if t_event >= t_end {
+                        break;
+                    }
+                    t = t_event;
+                    t_next = self.inject(t, events);
+                }
+                Wake::Timeout if t_next < t_end => {
+                    t = t_next;
+                    t_next = self.step(t);
+                }
+                _ => break,
+            }
+        }
+        self.stop(t);
+        t
+    }
+
+    /// Starts a simulation at time zero that is meant to stop at time `t_end`.
+    /// It returns the time of the first simulation cycle.
+    fn start(&mut self, t_end: f64) -> f64 {
+        if let Some((_, replay)) = &mut self.recording {
+            *replay = Replay {
+                seed: self.run_info.seed,
+                ..Replay::default()
+            };
+        }
+        let path = self.model.get_name().to_string();
+        self.model.set_path(&path);
+        // listeners read the output ports after the collection phase
+        self.model.set_move_messages(self.listeners.is_empty());
+        self.run_info.start(&self.model);
+        self.run_info.t_end = t_end;
+        self.t_last = 0.;
+        let t_next = self.model.start(0.);
+        if !self.listeners.is_empty() {
+            self.listeners.start(&self.model, 0.);
+        }
+        t_next
+    }
+
+    /// Executes the collection phase of the model at time `t`.
+    fn collection(&mut self, t: f64) {
+        attributed(|| self.model.collection(t));
+        for (port, actuator) in self.actuators.iter_mut() {
+            // Safety: reading messages after the collection phase
+            if !unsafe { port.is_empty() } {
+                actuator(t, unsafe { port.get_values() });
+            }
+        }
+        if !self.listeners.is_empty() {
+            self.listeners.collection(&self.model, t);
+        }
+    }
+
+    /// Executes the transition phase of the model at time `t`. It returns the time of the next simulation cycle.
+    fn transition(&mut self, t: f64) -> f64 {
+        self.t_last = t;
+        self.run_info.n_cycles += 1;
+        let t_next = attributed(|| self.model.transition(t));
+        if !self.listeners.is_empty() {
+            self.listeners.transition(&self.model, t);
+        }
+        t_next
+    }
+
+    /// Executes a simulation cycle at time `t`. It returns the time of the next simulation cycle.
+    fn step(&mut self, t: f64) -> f64 {
+        self.collection(t);
+        self.transition(t)
+    }
+
+    /// Injects external events into the model at time `t` and executes a simulation cycle.
+    /// It returns the time of the next simulation cycle.
+    fn inject(&mut self, t: f64, events: Vec<Event>) -> f64 {
+        let confluent = t >= self.model.get_t_next();
+        if confluent {
+            self.collection(t);
+        }
+        if let Some((ports, replay)) = &mut self.recording {
+            let batch = events
+                .iter()
+                .map(|(port, values)| (port.as_str(), values.as_ref() as _));
+            replay.record(ports, t, confluent, batch);
+        }
+        for (port, values) in events {
+            // senders only accept events for existing ports of the root model
+            let port = self.model.get_component().get_in_port(&port).unwrap();
+            // Safety: coordinator injecting messages between simulation phases
+            unsafe { port.inject(values.as_ref()) };
+        }
+        self.transition(t)
+    }
+
+    /// Stops the simulation at the time of the next simulation cycle.
+    /// `t` is the time of the last simulation cycle.
+    fn stop(&mut self, t: f64) {
+        if let Some((_, replay)) = &mut self.recording {
+            replay.end = t;
+        }
+        let t_stop = self.model.get_t_next();
+        self.model.stop(t_stop);
+        self.run_info.stop();
+        if !self.listeners.is_empty() {
+            let record = self.run_record();
+            self.listeners.stop(t_stop, &record);
+        }
+    }
+
+    /// Polls the inbox for new events. It returns `Ready(None)` if there are no senders left.
+    fn poll_events(&self, cx: &mut Context<'_>) -> Poll<Option<Vec<Event>>> {
+        let mut inbox = self.inbox.lock().unwrap();
+        if !inbox.events.is_empty() {
+            return Poll::Ready(Some(inbox.events.drain(..).collect()));
+        }
+        if inbox.senders == 0 {
+            return Poll::Ready(None);
+        }
+        inbox.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    /// Waits until new events arrive or `sleep` completes (if any).
+    /// Without `sleep`, it also stops waiting when there are no senders left.
+    async fn wait<F: Future<Output = ()>>(&self, sleep: Option<F>) -> Wake {
+        let mut sleep = pin!(sleep);
+        poll_fn(|cx| {
+            match self.poll_events(cx) {
+                Poll::Ready(Some(events)) => return Poll::Ready(Wake::Events(events)),
+                Poll::Ready(None) if sleep.is_none() => return Poll::Ready(Wake::Closed),
+                _ => (),
+            }
+            match sleep.as_mut().as_pin_mut() {
+                Some(sleep) => sleep.poll(cx).map(|_| Wake::Timeout),
+                None => Poll::Pending,
+            }
+        })
+        .await
+    }
+}
+
+impl<M> Drop for AsyncRootCoordinator<M> {
+    fn drop(&mut self) {
+        self.inbox.lock().unwrap().closed = true;
+    }
+}
+
+impl<M> Deref for AsyncRootCoordinator<M> {
+    type Target = M;
+
+    fn deref(&self) -> &Self::Target {
+        &self.model
+    }
+}
+
+impl<M> DerefMut for AsyncRootCoordinator<M> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.model
+    }
+}
+
+/// Yields control to the runtime once.
+async fn yield_now() {
+    let mut yielded = false;
+    poll_fn(|cx| {
+        if yielded {
+            return Poll::Ready(());
+        }
+        yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::Delay;
+    use crate::simulation::{PortValues, RootCoordinator};
+    use std::sync::Mutex;
+    use std::task::Wake;
+    use std::thread::{self, Thread};
+
+    /// Output messages of the root model and the time when they were sent.
+    type Outputs = Arc<Mutex<Vec<(f64, usize)>>>;
+
+    /// Listener that records the time and values of the output messages of the root model.
+    struct Recorder(Outputs);
+
+    impl Listener for Recorder {
+        fn on_output(&mut self, t: f64, path: &str, _port: &str, values: &PortValues) {
+            if path == "delay" {
+                let values = values.downcast::<usize>().unwrap();
+                self.0
+                    .lock()
+                    .unwrap()
+                    .extend(values.iter().map(|&v| (t, v)));
+            }
+        }
+    }
+
+    /// Minimal executor that runs a future in the current thread.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        struct Unparker(Thread);
+
+        impl Wake for Unparker {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let waker = Waker::from(Arc::new(Unparker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+
+    /// Sleep future backed by a thread.
+    fn sleep(duration: Duration) -> impl Future<Output = ()> {
+        let state = Arc::new(Mutex::new((false, None::<Waker>)));
+        let shared = state.clone();
+        thread::spawn(move || {
+            thread::sleep(duration);
+            let mut state = shared.lock().unwrap();
+            state.0 = true;
+            if let Some(waker) = state.1.take() {
+                waker.wake();
+            }
+        });
+        poll_fn(move |cx| {
+            let mut state = state.lock().unwrap();
+            match state.0 {
+                true => Poll::Ready(()),
+                false => {
+                    state.1 = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        })
+    }
+
+    fn coordinator() -> (AsyncRootCoordinator<Delay<usize>>, Outputs) {
+        let mut simulator = AsyncRootCoordinator::new(Delay::new("delay", 1.));
+        let outputs = Arc::new(Mutex::new(Vec::new()));
+        simulator.add_listener(Box::new(Recorder(outputs.clone())));
+        (simulator, outputs)
+    }
+
+    #[test]
+    fn test_simulate() {
+        let (mut simulator, outputs) = coordinator();
+        let sender = simulator.sender();
+        assert_eq!(
+            Err(SendError::UnknownPort("in".into())),
+            sender.send("in", 1usize)
+        );
+        assert_eq!(
+            Err(SendError::IncompatiblePort("input".into())),
+            sender.send("input", 1.)
+        );
+        sender.send("input", 1usize).unwrap();
+        sender.send("input", 2usize).unwrap();
+        let thread = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            sender.send("input", 3usize).unwrap();
+        });
+        // the late event is injected at the time of the last cycle
+        assert_eq!(2., block_on(simulator.simulate(f64::INFINITY)));
+        thread.join().unwrap();
+        assert_eq!(vec![(1., 1), (1., 2), (2., 3)], *outputs.lock().unwrap());
+    }
+
+    #[test]
+    fn test_simulate_rt() {
+        let (mut simulator, outputs) = coordinator();
+        let sender = simulator.sender();
+        let thread = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            sender.send("input", 1usize).unwrap();
+        });
+        let t = block_on(simulator.simulate_rt(f64::INFINITY, 100., sleep));
+        thread.join().unwrap();
+        let outputs = outputs.lock().unwrap();
+        assert_eq!(1, outputs.len());
+        assert_eq!((t, 1), outputs[0]);
+        assert!(t >= 3.);
+
+        drop(outputs);
+        let sender = simulator.sender();
+        drop(simulator);
+        assert_eq!(Err(SendError::Closed), sender.send("input", 1usize));
+    }
+
+    #[test]
+    fn test_record() {
+        let (mut simulator, outputs) = coordinator();
+        let mut ports = ReplayPorts::new();
+        assert!(matches!(
+            simulator.record(ports.clone()),
+            Err(ReplayError::MissingPort(_))
+        ));
+        ports.add::<usize>("input");
+        simulator.record(ports.clone()).unwrap();
+        simulator.set_seed(42);
+        let sender = simulator.sender();
+        let thread = thread::spawn(move || {
+            for i in 0..3usize {
+                sender.send("input", i).unwrap();
+                thread::sleep(Duration::from_millis(5));
+            }
+        });
+        let t = block_on(simulator.simulate_rt(f64::INFINITY, 100., sleep));
+        thread.join().unwrap();
+        let replay = simulator.replay().unwrap().clone();
+        assert_eq!(
+            (Some(42), t, 3),
+            (replay.seed, replay.end, replay.events.len())
+        );
+
+        let mut replayed = RootCoordinator::new(Delay::<usize>::new("delay", 1.));
+        let replayed_outputs = Arc::new(Mutex::new(Vec::new()));
+        replayed.add_listener(Box::new(Recorder(replayed_outputs.clone())));
+        assert_eq!(t, replayed.replay_log(&replay, &ports).unwrap());
+        assert_eq!(*outputs.lock().unwrap(), *replayed_outputs.lock().unwrap());
+    }
+
+    #[test]
+    fn test_bindings() {
+        let mut simulator = AsyncRootCoordinator::new(Delay::<usize>::new("delay", 1.));
+        assert!(matches!(
+            simulator.bind_input::<usize>("in"),
+            Err(SendError::UnknownPort(_))
+        ));
+        assert!(matches!(
+            simulator.bind_input::<f64>("input"),
+            Err(SendError::IncompatiblePort(_))
+        ));
+        assert_eq!(
+            Err(SendError::IncompatiblePort("output".into())),
+            simulator.bind_output("output", |_, _: &[f64]| ())
+        );
+
+        let actuated: Outputs = Arc::default();
+        let outputs = actuated.clone();
+        simulator
+            .bind_output("output", move |t, values: &[usize]| {
+                outputs
+                    .lock()
+                    .unwrap()
+                    .extend(values.iter().map(|&v| (t, v)))
+            })
+            .unwrap();
+        let sensor = simulator.bind_input::<usize>("input").unwrap();
+        assert_eq!("input", sensor.port());
+        let thread = thread::spawn(move || {
+            sensor.trigger(1).unwrap();
+            thread::sleep(Duration::from_millis(20));
+            sensor.clone().trigger(2).unwrap();
+        });
+        let t = block_on(simulator.simulate_rt(f64::INFINITY, 100., sleep));
+        thread.join().unwrap();
+        let actuated = actuated.lock().unwrap();
+        assert_eq!(2, actuated.len());
+        assert_eq!(1, actuated[0].1);
+        assert_eq!((t, 2), actuated[1]);
+    }
+}
@@ -0,0 +1,441 @@
+use super::{attributed, Simulator};
+use crate::modeling::port::{Port, Values};
+use crate::modeling::Coupled;
+use crate::{DynRef, Shared};
+use std::any::Any;
+use std::collections::VecDeque;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+/// Function that builds the coupled model of a partition.
+type Factory = Box<dyn FnOnce() -> Coupled + Send>;
+/// Function that clones the messages of a port (i.e., a reference to `Vec<T>`) to send them to another partition.
+pub(super) type Extractor = fn(&dyn Any) -> Option<Box<dyn Any + Send>>;
+/// Message payload: simulation time and messages of a port (i.e., a boxed `Vec<T>`).
+type Payload = (f64, Box<dyn Any + Send>);
+
+/// Sender of messages to a partition. Messages are tagged with the index of their link.
+type Mailbox = Sender<(usize, Message)>;
+
+/// Messages exchanged by partitions through links.
+enum Message {
+    /// Messages sent at a given simulation time.
+    Values(Payload),
+    /// Null message. The sender will not send messages with a lower simulation time.
+    Null(f64),
+}
+
+/// Link between an output port of a partition and an input port of another partition.
+pub(super) struct Link {
+    pub(super) from: usize,
+    pub(super) port_from: String,
+    pub(super) to: usize,
+    pub(super) port_to: String,
+    pub(super) extract: Extractor,
+}
+
+/// Clones the messages of a port of type `T`.
+pub(super) fn extract<T: DynRef + Clone + Send>(values: &dyn Any) -> Option<Box<dyn Any + Send>> {
+    let values = values.downcast_ref::<Values<T>>()?;
+    Some(Box::new(values.to_vec()))
+}
+
+/// Outgoing links of a partition. When dropped (even due to a panic),
+/// it sends a null message with infinite time to all the destinations to avoid deadlocks.
+struct Outbox {
+    /// Outgoing links: link index, source port, message extractor, and destination channel.
+    links: Vec<(usize, Shared<dyn Port>, Extractor, Mailbox)>,
+    /// Time of the last null message sent.
+    t_null: f64,
+}
+
+impl Outbox {
+    /// Sends the messages of the source ports generated at time `t`.
+    fn send_values(&self, t: f64) {
+        for (i, port, extract, sender) in &self.links {
+            // Safety: coordinator reading messages between simulation phases
+            if !unsafe { port.is_empty() } {
+                // Safety: coordinator reading messages between simulation phases
+                let values = extract(unsafe { port.get_values() }).unwrap();
+                let _ = sender.send((*i, Message::Values((t, values))));
+            }
+        }
+    }
+
+    /// Sends a null message with time `t` if it is greater than the previous one.
+    fn send_null(&mut self, t: f64) {
+        if t > self.t_null {
+            self.t_null = t;
+            for (i, _, _, sender) in &self.links {
+                let _ = sender.send((*i, Message::Null(t)));
+            }
+        }
+    }
+}
+
+impl Drop for Outbox {
+    fn drop(&mut self) {
+        self.send_null(f64::INFINITY);
+    }
+}
+
+/// Incoming link of a partition.
+struct Inbox {
+    /// Destination port of the link.
+    port: Shared<dyn Port>,
+    /// Messages received but not processed yet.
+    queue: VecDeque<Payload>,
+    /// Channel clock. The sender will not send messages with a lower simulation time.
+    clock: f64,
+}
+
+/// Coordinator for conservative parallel simulations following the Chandy-Misra-Bryant
+/// null-message protocol. Every partition is a coupled model simulated by its own thread.
+/// Partitions exchange messages through links that connect output ports of one partition
+/// with input ports of another partition.
+///
+/// Partitions use the lookahead of their atomic models (see [`crate::modeling::Atomic::lookahead`])
+/// to promise that they will not send messages before a given time. Cycles of partitions
+/// require a positive lookahead to guarantee progress.
+#[derive(Default)]
+pub struct CmbCoordinator {
+    /// Factories of the partitions.
+    partitions: Vec<Factory>,
+    /// Links between partitions.
+    links: Vec<Link>,
+}
+
+impl CmbCoordinator {
+    /// Creates a new coordinator without partitions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a new partition and returns its index. The coupled model of the partition
+    /// is created by `factory` in the thread that simulates the partition.
+    pub fn add_partition(&mut self, factory: impl FnOnce() -> Coupled + Send + 'static) -> usize {
+        self.partitions.push(Box::new(factory));
+        self.partitions.len() - 1
+    }
+
+    /// Adds a new link from the output port `port_from` of the partition `from`
+    /// to the input port `port_to` of the partition `to`. Both ports must be of type `T`.
+    /// It panics if any of the partitions does not exist or if the link connects a partition with itself.
+    /// Ports are checked when partitions are created.
+    pub fn add_link<T: DynRef + Clone + Send>(
+        &mut self,
+        from: usize,
+        port_from: &str,
+        to: usize,
+        port_to: &str,
+    ) {
+        if from >= self.partitions.len() || to >= self.partitions.len() {
+            panic!("partition does not exist")
+        }
+        if from == to {
+            panic!("links must connect different partitions")
+        }
+        self.links.push(Link {
+            from,
+            port_from: port_from.to_string(),
+            to,
+            port_to: port_to.to_string(),
+            extract: extract::<T>,
+        });
+    }
+
+    /// Runs a simulation for a given period of time. It blocks until all the partitions finish.
+    /// If the simulation of any partition panics, this method panics too.
+    pub fn simulate(self, t_end: f64) {
+        let n = self.partitions.len();
+        let (senders, receivers): (Vec<_>, Vec<_>) = (0..n).map(|_| channel()).unzip();
+        let handles: Vec<_> = self
+            .partitions
+            .into_iter()
+            .zip(receivers)
+            .enumerate()
+            .map(|(i, (factory, receiver))| {
+                let outgoing: Vec<_> = (self.links.iter().enumerate())
+                    .filter(|(_, l)| l.from == i)
+                    .map(|(j, l)| (j, l.port_from.clone(), l.extract, senders[l.to].clone()))
+                    .collect();
+                let incoming: Vec<_> = (self.links.iter().enumerate())
+                    .filter(|(_, l)| l.to == i)
+                    .map(|(j, l)| (j, l.port_to.clone()))
+                    .collect();
+                thread::spawn(move || {
+                    Partition::new(factory(), outgoing, incoming, receiver).simulate(t_end)
+                })
+            })
+            .collect();
+        drop(senders);
+        for handle in handles {
+            if let Err(e) = handle.join() {
+                std::panic::resume_unwind(e);
+            }
+        }
+    }
+}
+
+/// Partition of a [`CmbCoordinator`].
+struct Partition {
+    /// Coupled model of the partition.
+    model: Coupled,
+    /// Outgoing links.
+    outbox: Outbox,
+    /// Incoming links, indexed by link index.
+    inboxes: Vec<(usize, Inbox)>,
+    /// Channel for receiving messages from other partitions.
+    receiver: Receiver<(usize, Message)>,
+}
+
+impl Partition {
+    fn new(
+        model: Coupled,
+        outgoing: Vec<(usize, String, Extractor, Mailbox)>,
+        incoming: Vec<(usize, String)>,
+        receiver: Receiver<(usize, Message)>,
+    ) -> Self {
+        let links = outgoing
+            .into_iter()
+            .map(|(i, name, extract, sender)| {
+                let port = model
+                    .get_component()
+                    .get_out_port(&name)
+                    .expect("port_from does not exist");
+                // Safety: coordinator reading messages before the simulation
+                if extract(unsafe { port.get_values() }).is_none() {
+                    panic!("port_from is not compatible with the link")
+                }
+                (i, port, extract, sender)
+            })
+            .collect();
+        let inboxes = incoming
+            .into_iter()
+            .map(|(i, name)| {
+                let port = model
+                    .get_component()
+                    .get_in_port(&name)
+                    .expect("port_to does not exist");
+                let inbox = Inbox {
+                    port,
+                    queue: VecDeque::new(),
+                    clock: 0.,
+                };
+                (i, inbox)
+            })
+            .collect();
+        Self {
+            model,
+            outbox: Outbox {
+                links,
+                t_null: f64::NEG_INFINITY,
+            },
+            inboxes,
+            receiver,
+        }
+    }
+
+    /// Stores a message received from another partition.
+    fn receive(&mut self, (link, message): (usize, Message)) {
+        let (_, inbox) = self.inboxes.iter_mut().find(|(i, _)| *i == link).unwrap();
+        match message {
+            Message::Values((t, values)) => {
+                inbox.clock = inbox.clock.max(t);
+                inbox.queue.push_back((t, values));
+            }
+            Message::Null(t) => inbox.clock = inbox.clock.max(t),
+        }
+    }
+
+    /// Injects all the messages received for time `t` into the input ports of the model.
+    fn inject(&mut self, t: f64) {
+        for (_, inbox) in self.inboxes.iter_mut() {
+            while inbox.queue.front().is_some_and(|(tm, _)| *tm == t) {
+                let (_, values) = inbox.queue.pop_front().unwrap();
+                // Safety: coordinator injecting messages between simulation phases
+                if !unsafe { inbox.port.inject(values.as_ref()) } {
+                    panic!("port_to is not compatible with the link")
+                }
+            }
+        }
+    }
+
+    /// Simulates the partition until `t_end`.
+    fn simulate(mut self, t_end: f64) {
+        let lookahead = self.model.lookahead();
+        let path = self.model.get_name().to_string();
+        self.model.set_path(&path);
+        let mut t_next = self.model.start(0.);
+        loop {
+            // Time of the next event (internal or external) and time until which it is safe to simulate
+            let t_input = (self.inboxes.iter())
+                .filter_map(|(_, inbox)| inbox.queue.front().map(|(t, _)| *t))
+                .fold(f64::INFINITY, f64::min);
+            let t = t_next.min(t_input);
+            let t_safe = (self.inboxes.iter())
+                .map(|(_, inbox)| inbox.clock)
+                .fold(f64::INFINITY, f64::min);
+            if t >= t_end && t_safe >= t_end {
+                break;
+            }
+            if t < t_end && t < t_safe {
+                attributed(|| self.model.collection(t));
+                self.outbox.send_values(t);
+                self.inject(t);
+                t_next = attributed(|| self.model.transition(t));
+                self.outbox.send_null(t_next.min(t_safe + lookahead));
+                continue;
+            }
+            // We cannot progress: we send null messages and wait for new messages
+            self.outbox.send_null(t_next.min(t_safe + lookahead));
+            match self.receiver.recv() {
+                Ok(message) => self.receive(message),
+                Err(_) => break,
+            }
+            while let Ok(message) = self.receiver.try_recv() {
+                self.receive(message);
+            }
+        }
+        self.model.stop(t_next);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::{Atomic, Component, InPort, OutPort};
+    use std::sync::{Arc, Mutex};
+
+    /// Atomic model that sends a message after a fixed delay every time it receives a message.
+    struct Echo {
+        component: Component,
+        input: InPort<usize>,
+        output: OutPort<usize>,
+        delay: f64,
+        sigma: f64,
+        value: usize,
+        received: Arc<Mutex<Vec<(f64, usize)>>>,
+    }
+
+    impl Echo {
+        fn new(sigma: f64, delay: f64, received: Arc<Mutex<Vec<(f64, usize)>>>) -> Self {
+            let mut component = Component::new("echo");
+            let input = component.add_in_port("input");
+            let output = component.add_out_port("output");
+            Self {
+                component,
+                input,
+                output,
+                delay,
+                sigma,
+                value: 0,
+                received,
+            }
+        }
+
+        fn partition(
+            name: &str,
+            sigma: f64,
+            delay: f64,
+            received: Arc<Mutex<Vec<(f64, usize)>>>,
+        ) -> Coupled {
+            let mut coupled = Coupled::new(name);
+            coupled.add_in_port::<usize>("input");
+            coupled.add_out_port::<usize>("output");
+            coupled.add_component(Box::new(Echo::new(sigma, delay, received)));
+            coupled.add_eic("input", "echo", "input");
+            coupled.add_eoc("echo", "output", "output");
+            coupled
+        }
+    }
+
+    impl Atomic for Echo {
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+
+        fn lambda(&self) {
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.output.add_value(self.value + 1) };
+        }
+
+        fn delta_int(&mut self) {
+            self.sigma = f64::INFINITY;
+        }
+
+        fn delta_ext(&mut self, e: f64) {
+            // Safety: reading messages on atomic model's input port at delta_ext
+            self.value = *unsafe { self.input.get_values() }.last().unwrap();
+            let t = self.component.get_t_last() + e;
+            self.received.lock().unwrap().push((t, self.value));
+            self.sigma = self.delay;
+        }
+
+        fn ta(&self) -> f64 {
+            self.sigma
+        }
+
+        fn lookahead(&self) -> f64 {
+            self.delay
+        }
+    }
+
+    #[test]
+    fn test_pipeline() {
+        let n = 5;
+        let received: Vec<_> = (0..n).map(|_| Arc::new(Mutex::new(Vec::new()))).collect();
+        let mut coordinator = CmbCoordinator::new();
+        for (i, r) in received.iter().enumerate() {
+            let (r, sigma) = (r.clone(), if i == 0 { 1. } else { f64::INFINITY });
+            coordinator.add_partition(move || Echo::partition(&format!("p{i}"), sigma, 1., r));
+        }
+        for i in 1..n {
+            coordinator.add_link::<usize>(i - 1, "output", i, "input");
+        }
+        coordinator.simulate(f64::INFINITY);
+        assert!(received[0].lock().unwrap().is_empty());
+        for (i, r) in received.iter().enumerate().skip(1) {
+            assert_eq!(vec![(i as f64, i)], *r.lock().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_cycle() {
+        let (ping, pong) = (
+            Arc::new(Mutex::new(Vec::new())),
+            Arc::new(Mutex::new(Vec::new())),
+        );
+        let mut coordinator = CmbCoordinator::new();
+        let r = ping.clone();
+        let a = coordinator.add_partition(move || Echo::partition("ping", 0.5, 1., r));
+        let r = pong.clone();
+        let b = coordinator.add_partition(move || Echo::partition("pong", f64::INFINITY, 1., r));
+        coordinator.add_link::<usize>(a, "output", b, "input");
+        coordinator.add_link::<usize>(b, "output", a, "input");
+        coordinator.simulate(10.);
+        assert_eq!(
+            vec![(1.5, 2), (3.5, 4), (5.5, 6), (7.5, 8), (9.5, 10)],
+            *ping.lock().unwrap()
+        );
+        assert_eq!(
+            vec![(0.5, 1), (2.5, 3), (4.5, 5), (6.5, 7), (8.5, 9)],
+            *pong.lock().unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "port_to does not exist")]
+    fn test_wrong_link() {
+        let mut coordinator = CmbCoordinator::new();
+        let r = Arc::new(Mutex::new(Vec::new()));
+        let a = coordinator.add_partition(move || Echo::partition("a", 1., 1., r));
+        let b = coordinator.add_partition(|| Coupled::new("b"));
+        coordinator.add_link::<usize>(a, "output", b, "input");
+        coordinator.simulate(10.);
+    }
+}
@@ -0,0 +1,112 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+
+/// Control commands pending to be processed by a running simulation.
+#[derive(Debug, Default)]
+struct Commands {
+    /// It is `true` if the simulation is paused.
+    paused: bool,
+    /// Number of simulation cycles that a paused simulation may execute.
+    steps: u64,
+    /// It is `true` if the simulation must be aborted.
+    aborted: bool,
+}
+
+/// Shared state of a [`SimulationHandle`].
+#[derive(Debug, Default)]
+struct Control {
+    /// It is `true` if the simulation is paused or aborted. It avoids locking the mutex on every cycle.
+    interrupted: AtomicBool,
+    /// Number of simulation cycles executed since the simulation started.
+    cycles: AtomicU64,
+    /// Pending control commands.
+    commands: Mutex<Commands>,
+    /// Condition variable for waking up paused simulations.
+    wake: Condvar,
+}
+
+/// Handle for controlling a simulation run by a [`super::RootCoordinator`] from another thread.
+/// Handles are cheap to clone, and all the clones control the same root coordinator.
+/// Commands take effect at the next simulation cycle boundary of [`super::RootCoordinator::simulate`]
+/// and [`super::RootCoordinator::simulate_until`], so models are never interrupted in the middle of a cycle.
+#[derive(Debug, Clone, Default)]
+pub struct SimulationHandle(Arc<Control>);
+
+impl SimulationHandle {
+    /// Pauses the simulation before its next cycle. It has no effect if the simulation is already paused.
+    pub fn pause(&self) {
+        let mut commands = self.0.commands.lock().unwrap();
+        commands.paused = true;
+        self.0.interrupted.store(true, Ordering::Release);
+    }
+
+    /// Resumes a paused simulation. Pending single steps are discarded.
+    pub fn resume(&self) {
+        let mut commands = self.0.commands.lock().unwrap();
+        commands.paused = false;
+        commands.steps = 0;
+        self.0
+            .interrupted
+            .store(commands.aborted, Ordering::Release);
+        self.0.wake.notify_all();
+    }
+
+    /// Allows a paused simulation to execute one more cycle. It has no effect if the simulation is not paused.
+    pub fn step(&self) {
+        let mut commands = self.0.commands.lock().unwrap();
+        if commands.paused {
+            commands.steps += 1;
+            self.0.wake.notify_all();
+        }
+    }
+
+    /// Aborts the simulation before its next cycle, even if it is paused.
+    /// The root coordinator stops the model as if the simulation had finished.
+    /// If the simulation is not running, the next one is aborted before executing its first cycle.
+    pub fn abort(&self) {
+        let mut commands = self.0.commands.lock().unwrap();
+        commands.aborted = true;
+        self.0.interrupted.store(true, Ordering::Release);
+        self.0.wake.notify_all();
+    }
+
+    /// Returns `true` if the simulation is paused.
+    pub fn is_paused(&self) -> bool {
+        self.0.commands.lock().unwrap().paused
+    }
+
+    /// Returns the number of cycles executed by the current (or last) simulation.
+    pub fn cycles(&self) -> u64 {
+        self.0.cycles.load(Ordering::Acquire)
+    }
+
+    /// Resets the cycle counter when a new simulation starts.
+    pub(crate) fn reset(&self) {
+        self.0.cycles.store(0, Ordering::Release);
+    }
+
+    /// Called by the root coordinator before executing a simulation cycle. It blocks while the simulation
+    /// is paused, and returns `false` if the simulation must be aborted. Abort commands are consumed.
+    pub(crate) fn proceed(&self) -> bool {
+        if self.0.interrupted.load(Ordering::Acquire) {
+            let mut commands = self.0.commands.lock().unwrap();
+            loop {
+                if commands.aborted {
+                    commands.aborted = false;
+                    self.0.interrupted.store(commands.paused, Ordering::Release);
+                    return false;
+                }
+                if !commands.paused {
+                    break;
+                }
+                if commands.steps > 0 {
+                    commands.steps -= 1;
+                    break;
+                }
+                commands = self.0.wake.wait(commands).unwrap();
+            }
+        }
+        self.0.cycles.fetch_add(1, Ordering::Release);
+        true
+    }
+}
@@ -0,0 +1,227 @@
+use super::Simulator;
+use crate::time::Time;
+
+/// Breakpoint for pausing simulations with [`super::RootCoordinator::run_to_breakpoint`].
+///
+/// Breakpoints are checked in every simulation cycle right after the collection phase, so the output
+/// ports of the imminent components contain their output messages and state transitions have not been
+/// executed yet. Use [`super::RootCoordinator::output_values`] and [`super::RootCoordinator::input_values`]
+/// to inspect the ports, and the root model (e.g., [`crate::modeling::Coupled::find`]) to inspect
+/// the state and simulation times of the components.
+pub struct Breakpoint<M, T: Time = f64> {
+    kind: Kind<M, T>,
+}
+
+/// Condition of a breakpoint.
+enum Kind<M, T: Time> {
+    /// The simulation reaches a given time. It stores whether the breakpoint already triggered.
+    Time(T, bool),
+    /// A component with a given path is imminent.
+    Component(String),
+    /// A predicate on the root model holds.
+    When(Box<dyn FnMut(&M) -> bool>),
+}
+
+impl<M, T: Time> Breakpoint<M, T> {
+    /// Breakpoint that triggers once per simulation in the first simulation cycle at or after time `t`.
+    pub fn at_time(t: T) -> Self {
+        Self {
+            kind: Kind::Time(t, false),
+        }
+    }
+
+    /// Breakpoint that triggers in every simulation cycle in which the component with the provided path
+    /// is imminent, i.e., it executes its output function. Paths start with the name of the root model
+    /// (e.g., `"root.subcoupled.atomic"`). If the component is a coupled model, the breakpoint triggers
+    /// when any of its subcomponents is imminent.
+    pub fn on_component(path: &str) -> Self {
+        Self {
+            kind: Kind::Component(path.to_string()),
+        }
+    }
+
+    /// Breakpoint that triggers in every simulation cycle in which `predicate` returns `true`
+    /// for the root model.
+    pub fn when<F: FnMut(&M) -> bool + 'static>(predicate: F) -> Self {
+        Self {
+            kind: Kind::When(Box::new(predicate)),
+        }
+    }
+}
+
+/// Simulation cycle in which a simulation was paused by one or more breakpoints.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Break<T: Time = f64> {
+    /// Time of the simulation cycle.
+    pub t: T,
+    /// Identifiers of the breakpoints that triggered (see [`super::RootCoordinator::add_breakpoint`]).
+    pub breakpoints: Vec<usize>,
+}
+
+/// Progress of the simulation driven by the debugger.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum DebugState<T> {
+    /// There is no simulation in progress.
+    Idle,
+    /// A simulation is in progress between two simulation cycles.
+    Running,
+    /// A simulation is paused right after the collection phase of the simulation cycle at the given time.
+    Paused(T),
+}
+
+/// Breakpoints of a root coordinator.
+pub(crate) struct Debugger<M, T: Time> {
+    /// Registered breakpoints, together with their identifiers.
+    breakpoints: Vec<(usize, Breakpoint<M, T>)>,
+    /// Identifier of the next breakpoint.
+    next_id: usize,
+    /// Progress of the simulation.
+    pub(crate) state: DebugState<T>,
+}
+
+impl<M, T: Time> Default for Debugger<M, T> {
+    fn default() -> Self {
+        Self {
+            breakpoints: Vec::new(),
+            next_id: 0,
+            state: DebugState::Idle,
+        }
+    }
+}
+
+impl<M: Simulator<T>, T: Time> Debugger<M, T> {
+    /// Registers a new breakpoint and returns its identifier.
+    pub(crate) fn add(&mut self, breakpoint: Breakpoint<M, T>) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.breakpoints.push((id, breakpoint));
+        id
+    }
+
+    /// Removes a breakpoint. It returns `false` if there is no breakpoint with the provided identifier.
+    pub(crate) fn remove(&mut self, id: usize) -> bool {
+        let len = self.breakpoints.len();
+        self.breakpoints.retain(|(i, _)| *i != id);
+        self.breakpoints.len() != len
+    }
+
+    /// Returns `true` if there are no breakpoints.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.breakpoints.is_empty()
+    }
+
+    /// Removes all the breakpoints.
+    pub(crate) fn clear(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    /// Rearms the time breakpoints for a new simulation.
+    pub(crate) fn start(&mut self) {
+        for (_, breakpoint) in self.breakpoints.iter_mut() {
+            if let Kind::Time(_, triggered) = &mut breakpoint.kind {
+                *triggered = false;
+            }
+        }
+        self.state = DebugState::Running;
+    }
+
+    /// Checks all the breakpoints after the collection phase of the simulation cycle at time `t`.
+    pub(crate) fn check(&mut self, model: &M, t: T) -> Option<Break<T>> {
+        let mut breakpoints = Vec::new();
+        for (id, breakpoint) in self.breakpoints.iter_mut() {
+            let hit = match &mut breakpoint.kind {
+                Kind::Time(t_break, triggered) => {
+                    let hit = !*triggered && t >= *t_break;
+                    *triggered |= hit;
+                    hit
+                }
+                Kind::Component(path) => {
+                    find(model, path).is_some_and(|component| component.get_t_next() == t)
+                }
+                Kind::When(predicate) => predicate(model),
+            };
+            if hit {
+                breakpoints.push(*id);
+            }
+        }
+        (!breakpoints.is_empty()).then_some(Break { t, breakpoints })
+    }
+}
+
+/// Returns the component with the provided path in the model hierarchy.
+pub(crate) fn find<'a, T: Time>(
+    model: &'a dyn Simulator<T>,
+    path: &str,
+) -> Option<&'a dyn Simulator<T>> {
+    match model.as_coupled() {
+        Some(coupled) => coupled.find(path),
+        None => (model.get_name() == path).then_some(model),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::{Generator, Job, Processor};
+    use crate::modeling::Coupled;
+    use crate::simulation::RootCoordinator;
+
+    #[test]
+    fn test_breakpoints() {
+        let mut root = Coupled::new("root");
+        root.add_component(Box::new(Generator::new("generator", 2.)));
+        root.add_component(Box::new(Processor::<Job>::new("processor", 3.)));
+        root.add_ic("generator", "output", "processor", "input");
+        let mut simulator = RootCoordinator::new(root);
+
+        let at_time = simulator.add_breakpoint(Breakpoint::at_time(5.));
+        let busy = simulator.add_breakpoint(Breakpoint::on_component("root.processor"));
+        let brk = simulator.run_to_breakpoint(10.).unwrap();
+        assert_eq!((5., vec![at_time, busy]), (brk.t, brk.breakpoints));
+        assert_eq!(
+            1,
+            simulator
+                .output_values("root.processor", "output")
+                .unwrap()
+                .len()
+        );
+        assert!(simulator
+            .output_values("root.generator", "output")
+            .unwrap()
+            .is_empty());
+        assert!(simulator
+            .output_values("root.processor", "unknown")
+            .is_none());
+        assert!(simulator.remove_breakpoint(busy));
+        assert!(!simulator.remove_breakpoint(busy));
+
+        let dropped = simulator.add_breakpoint(Breakpoint::when(|root: &Coupled| {
+            let processor = root.find_as::<Processor<Job>>("root.processor").unwrap();
+            processor.n_dropped() > 1
+        }));
+        let brk = simulator.run_to_breakpoint(10.).unwrap();
+        assert_eq!((9., vec![dropped]), (brk.t, brk.breakpoints));
+        let jobs = simulator.output_values("root.processor", "output").unwrap();
+        assert_eq!(2, jobs.downcast::<Job>().unwrap()[0].id);
+        assert!(simulator
+            .input_values("root.processor", "input")
+            .unwrap()
+            .is_empty());
+        let processor = simulator.find("root.processor").unwrap();
+        assert_eq!((8., 9.), (processor.get_t_last(), processor.get_t_next()));
+
+        simulator.clear_breakpoints();
+        assert!(simulator.run_to_breakpoint(10.).is_none());
+        assert_eq!(
+            4,
+            simulator
+                .find_as::<Generator>("root.generator")
+                .unwrap()
+                .count()
+        );
+        // time breakpoints are rearmed in every simulation
+        let at_time = simulator.add_breakpoint(Breakpoint::at_time(5.));
+        let brk = simulator.run_to_breakpoint(10.).unwrap();
+        assert_eq!((5., vec![at_time]), (brk.t, brk.breakpoints));
+    }
+}
@@ -0,0 +1,483 @@
+use super::{attributed, Simulator};
+use crate::modeling::coupled::port_key;
+use crate::modeling::Coupled;
+use crate::time::Time;
+use std::collections::HashMap;
+use std::ops::Deref;
+
+/// Minimum number of buckets of a calendar queue.
+const MIN_BUCKETS: usize = 2;
+/// Maximum number of event times sampled to estimate the width of the buckets of a calendar queue.
+const WIDTH_SAMPLES: usize = 25;
+
+/// Calendar queue of components ordered by their next simulation time (R. Brown, 1988).
+/// Events are hashed into buckets that span `width` time units, as days in a yearly calendar.
+/// The queue visits the buckets in order, so finding the next event takes constant time on average,
+/// and it resizes its buckets as the number of events grows or shrinks.
+/// Components with an infinite next time are passive, so they are not stored in the queue.
+struct CalendarQueue<T> {
+    /// Buckets of events (next simulation time and index of the component).
+    buckets: Vec<Vec<(T, usize)>>,
+    /// Position of the event of every component in its bucket.
+    slots: Vec<usize>,
+    /// Time span of every bucket.
+    width: f64,
+    /// Bucket where the search for the next event starts.
+    current: usize,
+    /// Upper bound (exclusive) of the current bucket in the current year.
+    top: f64,
+    /// Number of events in the queue.
+    len: usize,
+}
+
+impl<T: Time> CalendarQueue<T> {
+    /// Creates a new queue for `n` components with their next simulation time.
+    fn new(times: &[T]) -> Self {
+        let mut queue = Self {
+            buckets: Vec::new(),
+            slots: vec![0; times.len()],
+            width: 1.,
+            current: 0,
+            top: 1.,
+            len: 0,
+        };
+        let events = (times.iter().copied().zip(0..))
+            .filter(|(t, _)| *t < T::INFINITY)
+            .collect();
+        queue.resize(events);
+        queue
+    }
+
+    /// Returns the index of the bucket of a simulation time.
+    #[inline]
+    fn bucket(&self, t: f64) -> usize {
+        (t / self.width) as usize & (self.buckets.len() - 1)
+    }
+
+    /// Adds the event of a component. Passive components (i.e., with infinite next time) are ignored.
+    #[inline]
+    fn push(&mut self, t: T, i: usize) {
+        if t < T::INFINITY {
+            let b = self.bucket(t.as_f64());
+            self.slots[i] = self.buckets[b].len();
+            self.buckets[b].push((t, i));
+            self.len += 1;
+            if self.len > 2 * self.buckets.len() {
+                self.resize(Vec::new());
+            }
+        }
+    }
+
+    /// Removes the event of a component with the provided next time.
+    #[inline]
+    fn remove(&mut self, t: T, i: usize) {
+        if t < T::INFINITY {
+            self.remove_at(self.bucket(t.as_f64()), self.slots[i]);
+        }
+    }
+
+    /// Removes the event at the provided bucket and position.
+    #[inline]
+    fn remove_at(&mut self, b: usize, k: usize) -> (T, usize) {
+        let event = self.buckets[b].swap_remove(k);
+        if let Some(&(_, j)) = self.buckets[b].get(k) {
+            self.slots[j] = k;
+        }
+        self.len -= 1;
+        event
+    }
+
+    /// Returns the time of the next event, or infinity if the queue is empty.
+    #[inline]
+    fn t_next(&mut self) -> T {
+        match self.locate() {
+            Some((b, k)) => self.buckets[b][k].0,
+            None => T::INFINITY,
+        }
+    }
+
+    /// Removes all the events with the minimum time and appends their components to `imminent`.
+    /// It returns the time of the removed events, or infinity if the queue is empty.
+    fn pop_imminent(&mut self, imminent: &mut Vec<usize>) -> T {
+        let Some((b, k)) = self.locate() else {
+            return T::INFINITY;
+        };
+        // events with the same time are always in the same bucket
+        let t = self.buckets[b][k].0;
+        for k in (0..self.buckets[b].len()).rev() {
+            if self.buckets[b][k].0 == t {
+                imminent.push(self.remove_at(b, k).1);
+            }
+        }
+        if 4 * self.len < self.buckets.len() && self.buckets.len() > MIN_BUCKETS {
+            self.resize(Vec::new());
+        }
+        t
+    }
+
+    /// Finds the bucket and position of the next event. The current bucket is moved to that bucket.
+    fn locate(&mut self) -> Option<(usize, usize)> {
+        if self.len == 0 {
+            return None;
+        }
+        let n = self.buckets.len();
+        for _ in 0..n {
+            if let Some(k) = self.min_in(self.current, self.top) {
+                return Some((self.current, k));
+            }
+            self.current = (self.current + 1) & (n - 1);
+            self.top += self.width;
+        }
+        // no event in the next year: we jump directly to the bucket of the next event
+        let (b, k) = (0..n)
+            .filter_map(|b| Some((b, self.min_in(b, f64::INFINITY)?)))
+            .min_by(|&(b1, k1), &(b2, k2)| {
+                let (t1, t2) = (self.buckets[b1][k1].0, self.buckets[b2][k2].0);
+                t1.total_cmp(&t2)
+            })?;
+        let t = self.buckets[b][k].0.as_f64();
+        self.current = b;
+        self.top = ((t / self.width).floor() + 1.) * self.width;
+        Some((b, k))
+    }
+
+    /// Returns the position of the next event of a bucket with a time lower than `top` (if any).
+    #[inline]
+    fn min_in(&self, b: usize, top: f64) -> Option<usize> {
+        (self.buckets[b].iter().enumerate())
+            .filter(|(_, (t, _))| t.as_f64() < top)
+            .min_by(|(_, (t1, _)), (_, (t2, _))| t1.total_cmp(t2))
+            .map(|(k, _)| k)
+    }
+
+    /// Rebuilds the queue with one bucket per event, estimating the width of the buckets from its events.
+    fn resize(&mut self, mut events: Vec<(T, usize)>) {
+        events.extend(self.buckets.drain(..).flatten());
+        events.sort_unstable_by(|(t1, _), (t2, _)| t1.total_cmp(t2));
+        // the width is three times the average separation of the next events
+        let sample = &events[..events.len().min(WIDTH_SAMPLES)];
+        let span = match (sample.first(), sample.last()) {
+            (Some((first, _)), Some((last, _))) => last.as_f64() - first.as_f64(),
+            _ => 0.,
+        };
+        self.width = match span > 0. {
+            true => 3. * span / (sample.len() - 1) as f64,
+            false => 1.,
+        };
+        let n = events.len().max(MIN_BUCKETS).next_power_of_two();
+        self.buckets = (0..n).map(|_| Vec::new()).collect();
+        let t = events.first().map_or(0., |(t, _)| t.as_f64());
+        self.current = self.bucket(t);
+        self.top = ((t / self.width).floor() + 1.) * self.width;
+        self.len = 0;
+        for (t, i) in events {
+            self.push(t, i);
+        }
+    }
+}
+
+/// Coordinator for sequential simulations of flattened models. It flattens the model (see [`Coupled::flatten`])
+/// without merging coupling paths, so components receive as many messages as in the original hierarchy,
+/// and keeps all its components in a single calendar queue ordered by their next simulation time,
+/// so every simulation cycle only visits the imminent components and the components that receive messages.
+/// Finding the next imminent component takes constant time on average regardless of the number of components,
+/// so it scales better than [`super::RootCoordinator`] for models with millions of atomic models.
+///
+/// It only simulates Parallel DEVS models. Input ports of the model do not receive messages, and the messages
+/// that components send to the output ports of the model are discarded. Nested models that cannot be flattened
+/// (e.g., statically composed models) are simulated as any other component.
+pub struct FlatCoordinator<T: Time = f64> {
+    /// Flattened DEVS model to be simulated.
+    model: Coupled<T>,
+    /// Next simulation time of every component.
+    times: Vec<T>,
+    /// Calendar queue of the components.
+    queue: CalendarQueue<T>,
+    /// Indices of the ICs grouped by source component.
+    out_ics: Vec<Vec<usize>>,
+    /// Index of the destination component of every IC.
+    ic_targets: Vec<usize>,
+    /// It is `true` for the ICs whose source port has no other coupling, so they can move messages.
+    moving_ics: Vec<bool>,
+    /// Components with a next simulation time equal to the current simulation time.
+    imminent: Vec<usize>,
+    /// It is `true` for components that must execute their transition in the current simulation cycle.
+    active: Vec<bool>,
+    /// Components that must execute their transition in the current simulation cycle.
+    active_list: Vec<usize>,
+}
+
+impl<T: Time> FlatCoordinator<T> {
+    /// Creates a new coordinator from a coupled model. The model is flattened before simulating.
+    /// It panics if the model follows Classic DEVS semantics (see [`Coupled::set_select`]).
+    pub fn new(model: Coupled<T>) -> Self {
+        let model = model.flatten();
+        assert!(
+            !model.is_classic(),
+            "flat coordinators only simulate Parallel DEVS models"
+        );
+        let n = model.components.len();
+        let mut inputs = HashMap::new();
+        let mut outputs = HashMap::new();
+        for (i, component) in model.components.iter().enumerate() {
+            let component = component.get_component();
+            inputs.extend(component.get_in_ports().iter().map(|p| (port_key(p), i)));
+            outputs.extend(component.get_out_ports().iter().map(|p| (port_key(p), i)));
+        }
+        let mut out_ics = vec![Vec::new(); n];
+        let mut ic_targets = Vec::with_capacity(model.ics.len());
+        let mut n_couplings: HashMap<usize, usize> = HashMap::new();
+        for (k, (port_to, port_from)) in model.ics.iter().enumerate() {
+            out_ics[outputs[&port_key(port_from)]].push(k);
+            ic_targets.push(inputs[&port_key(port_to)]);
+            *n_couplings.entry(port_key(port_from)).or_default() += 1;
+        }
+        let moving_ics = (model.ics.iter())
+            .map(|(_, port_from)| n_couplings[&port_key(port_from)] == 1)
+            .collect();
+        Self {
+            model,
+            times: vec![T::INFINITY; n],
+            queue: CalendarQueue::new(&[]),
+            out_ics,
+            ic_targets,
+            moving_ics,
+            imminent: Vec::new(),
+            active: vec![false; n],
+            active_list: Vec::new(),
+        }
+    }
+
+    /// Returns the flattened model.
+    pub fn into_model(self) -> Coupled<T> {
+        self.model
+    }
+
+    /// Starts the simulation at time zero. It returns the time of the first simulation cycle.
+    pub fn start_simulation(&mut self) -> T {
+        let path = self.model.get_name().to_string();
+        self.model.set_path(&path);
+        self.model.start(T::ZERO);
+        for (i, component) in self.model.components.iter().enumerate() {
+            self.times[i] = component.get_t_next();
+        }
+        self.queue = CalendarQueue::new(&self.times);
+        self.queue.t_next()
+    }
+
+    /// Executes the next simulation cycle. It returns the time of the following simulation cycle.
+    pub fn step(&mut self) -> T {
+        let t = self.queue.pop_imminent(&mut self.imminent);
+        if t == T::INFINITY {
+            return t;
+        }
+        // imminent components are sorted, so the schedule is deterministic
+        self.imminent.sort_unstable();
+        for k in 0..self.imminent.len() {
+            let i = self.imminent[k];
+            self.activate(i);
+            attributed(|| self.model.components[i].collection(t));
+        }
+        for &i in &self.imminent {
+            for &k in &self.out_ics[i] {
+                let (port_to, port_from) = &self.model.ics[k];
+                // Safety: coordinator checking the output ports of the components
+                if !unsafe { port_from.is_empty() } {
+                    // Safety: coordinator propagating messages
+                    unsafe {
+                        match self.moving_ics[k] {
+                            true => port_from.propagate_move(&**port_to),
+                            false => port_from.propagate(&**port_to),
+                        }
+                    }
+                    let j = self.ic_targets[k];
+                    if !self.active[j] {
+                        self.active[j] = true;
+                        self.active_list.push(j);
+                    }
+                }
+            }
+        }
+        for &i in &self.active_list {
+            self.active[i] = false;
+            let t_next = attributed(|| self.model.components[i].transition(t));
+            // imminent components were already removed from the queue
+            if self.times[i] != t {
+                self.queue.remove(self.times[i], i);
+            }
+            self.times[i] = t_next;
+            self.queue.push(t_next, i);
+        }
+        self.imminent.clear();
+        self.active_list.clear();
+        // messages sent to the output ports of the model are discarded
+        self.model.clear_output();
+        self.model.set_sim_t(t, self.queue.t_next());
+        self.model.get_t_next()
+    }
+
+    /// Marks a component as active.
+    #[inline]
+    fn activate(&mut self, i: usize) {
+        if !self.active[i] {
+            self.active[i] = true;
+            self.active_list.push(i);
+        }
+    }
+
+    /// Stops the simulation at the time of the next simulation cycle.
+    pub fn stop_simulation(&mut self) {
+        let t_stop = self.queue.t_next();
+        self.model.stop(t_stop);
+    }
+
+    /// Runs a simulation for a given period of time.
+    pub fn simulate(&mut self, t_end: T) {
+        let mut t_next = self.start_simulation();
+        while t_next < t_end {
+            t_next = self.step();
+        }
+        self.stop_simulation();
+    }
+}
+
+impl<T: Time> Deref for FlatCoordinator<T> {
+    type Target = Coupled<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.model
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devstone::bench::{self, Params, Variant};
+    use crate::lib::{Generator, Job, Processor, Transducer};
+    use crate::simulation::RootCoordinator;
+
+    #[test]
+    fn test_calendar_queue() {
+        let mut times = vec![3., 1., f64::INFINITY, 1., 250., 0.5, 7.];
+        times.extend((7..20).map(|i| 1e6 + i as f64));
+        let mut queue = CalendarQueue::new(&times[..7]);
+        assert_eq!(0.5, queue.t_next());
+        queue.remove(250., 4);
+        queue.push(2., 2);
+        for (i, &t) in times.iter().enumerate().skip(7) {
+            queue.slots.push(0);
+            queue.push(t, i);
+        }
+        let mut events = Vec::new();
+        loop {
+            let mut imminent = Vec::new();
+            match queue.pop_imminent(&mut imminent) {
+                t if t == f64::INFINITY => break,
+                t => {
+                    imminent.sort_unstable();
+                    events.push((t, imminent));
+                }
+            }
+        }
+        let mut expected = vec![
+            (0.5, vec![5]),
+            (1., vec![1, 3]),
+            (2., vec![2]),
+            (3., vec![0]),
+            (7., vec![6]),
+        ];
+        expected.extend((7..20).map(|i| (1e6 + i as f64, vec![i])));
+        assert_eq!(expected, events);
+        assert_eq!(0, queue.len);
+    }
+
+    #[test]
+    fn test_flat_coordinator() {
+        let build = || {
+            let mut ef = Coupled::new("ef");
+            ef.add_in_port::<Job>("solved");
+            ef.add_out_port::<Job>("output");
+            ef.add_component(Box::new(Generator::new("generator", 3.)));
+            ef.add_component(Box::new(Transducer::new("transducer", 100.)));
+            ef.add_ic("transducer", "stop", "generator", "stop");
+            ef.add_ic("generator", "output", "transducer", "arrived");
+            ef.add_eic("solved", "transducer", "solved");
+            ef.add_eoc("generator", "output", "output");
+            let mut efp = Coupled::new("efp");
+            efp.add_component(Box::new(ef));
+            efp.add_component(Box::new(Processor::<Job>::new("processor", 5.)));
+            efp.add_ic("ef", "output", "processor", "input");
+            efp.add_ic("processor", "output", "ef", "solved");
+            efp
+        };
+        let mut simulator = RootCoordinator::new(build());
+        simulator.simulate(f64::INFINITY);
+        let transducer = simulator
+            .find_as::<Transducer>("efp.ef.transducer")
+            .unwrap();
+        let expected = (transducer.n_arrived(), transducer.n_solved());
+
+        let mut simulator = FlatCoordinator::new(build());
+        for _ in 0..2 {
+            simulator.simulate(f64::INFINITY);
+            // flattened components are named after their path in the original model
+            let components = simulator.get_components();
+            let transducer = components.iter().find(|c| c.get_name() == "ef.transducer");
+            let transducer = transducer.unwrap().downcast_ref::<Transducer>().unwrap();
+            assert_eq!(expected, (transducer.n_arrived(), transducer.n_solved()));
+        }
+    }
+
+    #[test]
+    fn test_flat_parallel_paths() {
+        // jobs reach the transducer through two output ports of the same coupled model
+        let build = || {
+            let mut g = Coupled::new("g");
+            g.add_out_port::<Job>("o1");
+            g.add_out_port::<Job>("o2");
+            g.add_component(Box::new(Generator::new("generator", 3.)));
+            g.add_eoc("generator", "output", "o1");
+            g.add_eoc("generator", "output", "o2");
+            let mut gt = Coupled::new("gt");
+            gt.add_component(Box::new(g));
+            gt.add_component(Box::new(Transducer::new("transducer", 100.)));
+            gt.add_ic("g", "o1", "transducer", "arrived");
+            gt.add_ic("g", "o2", "transducer", "arrived");
+            gt
+        };
+        let mut simulator = RootCoordinator::new(build());
+        simulator.simulate(50.);
+        let transducer = simulator.find_as::<Transducer>("gt.transducer").unwrap();
+        let expected = transducer.n_arrived();
+        assert!(expected > 0 && expected % 2 == 0);
+
+        let mut simulator = FlatCoordinator::new(build());
+        simulator.simulate(50.);
+        let components = simulator.get_components();
+        let transducer = components.iter().find(|c| c.get_name() == "transducer");
+        let transducer = transducer.unwrap().downcast_ref::<Transducer>().unwrap();
+        assert_eq!(expected, transducer.n_arrived());
+    }
+
+    /// Returns the number of state transitions of all the atomic models of a model.
+    fn n_transitions(model: &dyn Simulator) -> usize {
+        match model.get_components() {
+            components if components.is_empty() => model.get_component().get_n_transitions(),
+            components => components.into_iter().map(n_transitions).sum(),
+        }
+    }
+
+    #[test]
+    fn test_flat_devstone() {
+        for variant in Variant::ALL {
+            let params = Params::new(variant, 10, 10);
+            let simulator = bench::end_to_end(&params);
+            let expected = n_transitions(&*simulator);
+
+            let mut simulator = FlatCoordinator::new(bench::build(&params));
+            simulator.simulate(f64::INFINITY);
+            assert_eq!(f64::INFINITY, simulator.get_t_next());
+            assert_eq!(expected, n_transitions(&*simulator));
+        }
+    }
+}
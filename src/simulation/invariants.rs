@@ -0,0 +1,137 @@
+use super::Simulator;
+use crate::modeling::port::Port;
+use crate::modeling::Component;
+use crate::time::Time;
+use crate::Shared;
+
+/// Checks the invariants that every model must fulfill at the beginning of the simulation cycle at time `t`
+/// (i.e., right before the collection phase). Coordinators call it in every simulation cycle when the
+/// `debug_checks` feature is enabled, and custom coordinators can call it to find bugs in their simulation loop.
+/// It panics with the path of the offending component if:
+///
+/// - the last and next simulation times of any component are not such that `t_last <= t <= t_next`.
+/// - the next simulation time of any coupled model is not the minimum next simulation time of its subcomponents.
+/// - any port contains messages. Input ports of `model` are not checked, as coordinators
+///   may inject external messages in them before the simulation cycle.
+pub fn check_invariants<T: Time>(model: &dyn Simulator<T>, t: T) {
+    check_model(model, t);
+    // Safety: coordinator reading the ports between simulation cycles
+    unsafe {
+        check_empty(
+            model.get_component(),
+            model.get_component().get_out_ports(),
+            "at the beginning of a simulation cycle",
+        )
+    };
+    for component in model.get_components() {
+        check_recursive(component, t);
+    }
+}
+
+fn check_recursive<T: Time>(model: &dyn Simulator<T>, t: T) {
+    check_model(model, t);
+    let component = model.get_component();
+    // Safety: coordinator reading the ports between simulation cycles
+    unsafe {
+        check_empty(
+            component,
+            component.get_in_ports(),
+            "at the beginning of a simulation cycle",
+        );
+        check_empty(
+            component,
+            component.get_out_ports(),
+            "at the beginning of a simulation cycle",
+        );
+    }
+    for component in model.get_components() {
+        check_recursive(component, t);
+    }
+}
+
+/// Checks the simulation times of a model.
+fn check_model<T: Time>(model: &dyn Simulator<T>, t: T) {
+    let component = model.get_component();
+    let (t_last, t_next) = (component.get_t_last(), component.get_t_next());
+    assert!(
+        t_last <= t && t <= t_next,
+        "invariant violated: component {} has t_last = {t_last:?} and t_next = {t_next:?} at time {t:?}",
+        component.get_path(),
+    );
+    let components = model.get_components();
+    if let Some(t_min) = (components.iter())
+        .map(|c| c.get_t_next())
+        .min_by(|a, b| a.total_cmp(b))
+    {
+        assert!(
+            t_next.total_cmp(&t_min).is_eq(),
+            "invariant violated: coupled model {} has t_next = {t_next:?}, but the minimum t_next of its subcomponents is {t_min:?}",
+            component.get_path(),
+        );
+    }
+}
+
+/// Panics if any port of a component contains messages. `when` describes the moment of the check.
+///
+/// # Safety
+///
+/// This method can only be executed when the ports of the component are not being modified.
+pub(crate) unsafe fn check_empty<T: Time>(
+    component: &Component<T>,
+    ports: &[Shared<dyn Port>],
+    when: &str,
+) {
+    for port in ports {
+        assert!(
+            port.is_empty(),
+            "invariant violated: port {}.{} contains messages {when}",
+            component.get_path(),
+            port.get_name(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::{Generator, Job, Processor};
+    use crate::modeling::Coupled;
+    use crate::simulation::RootCoordinator;
+
+    fn model() -> Coupled {
+        let mut coupled = Coupled::new("root");
+        coupled.add_component(Box::new(Generator::new("generator", 1.)));
+        coupled.add_component(Box::new(Processor::<Job>::new("processor", 2.)));
+        coupled.add_ic("generator", "output", "processor", "input");
+        coupled.set_path("root");
+        coupled
+    }
+
+    #[test]
+    fn test_check_invariants() {
+        let mut simulator = RootCoordinator::new(model());
+        simulator.simulate(10.);
+        let mut model = model();
+        let t = model.start(0.);
+        check_invariants(&model, t);
+    }
+
+    #[test]
+    #[should_panic(expected = "invariant violated: coupled model root has t_next = 5.0")]
+    fn test_stale_t_next() {
+        let mut model = model();
+        model.start(0.);
+        model.set_sim_t(0., 5.);
+        check_invariants(&model, 0.);
+    }
+
+    #[test]
+    #[should_panic(expected = "contains messages at the beginning of a simulation cycle")]
+    fn test_messages_left() {
+        let mut model = model();
+        let t = model.start(0.);
+        // A coordinator that forgets the transition phase leaves messages in the ports
+        model.collection(t);
+        check_invariants(&model, t);
+    }
+}
@@ -0,0 +1,539 @@
+use super::{RunRecord, Simulator};
+use crate::modeling::port::{Port, Values};
+use crate::time::Time;
+use std::any::Any;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// Read-only view of the messages of an output port after a collection phase.
+pub struct PortValues<'a> {
+    port: &'a dyn Port,
+}
+
+impl<'a> PortValues<'a> {
+    /// Ports are only read between simulation phases, when no model is manipulating them.
+    pub(crate) fn new(port: &'a dyn Port) -> Self {
+        Self { port }
+    }
+
+    /// Returns the number of messages in the port.
+    #[inline]
+    pub fn len(&self) -> usize {
+        // Safety: listeners reading messages between simulation phases
+        unsafe { self.port.len() }
+    }
+
+    /// Returns `true` if the port contains no messages.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        // Safety: listeners reading messages between simulation phases
+        unsafe { self.port.is_empty() }
+    }
+
+    /// Returns a reference to the messages of the port as an [`Any`] trait object.
+    /// Use [`PortValues::downcast`] to get the messages as a slice.
+    #[inline]
+    pub fn as_any(&self) -> &dyn Any {
+        // Safety: listeners reading messages between simulation phases
+        unsafe { self.port.get_values() }
+    }
+
+    /// Returns the messages of the port if their type is `T`.
+    #[inline]
+    pub fn downcast<T: 'static>(&self) -> Option<&[T]> {
+        self.as_any()
+            .downcast_ref::<Values<T>>()
+            .map(|v| v.as_slice())
+    }
+}
+
+/// Value of an observable state variable of a model.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StateValue {
+    /// Boolean value.
+    Bool(bool),
+    /// Integer value.
+    Int(i64),
+    /// Floating-point value.
+    Float(f64),
+    /// Text value.
+    Text(String),
+}
+
+impl StateValue {
+    /// Returns the value as a [`f64`] (e.g., for plotting). Booleans are mapped to 0 and 1,
+    /// and texts are mapped to [`None`].
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Bool(b) => Some(*b as u8 as f64),
+            Self::Int(i) => Some(*i as f64),
+            Self::Float(f) => Some(*f),
+            Self::Text(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for StateValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::Int(i) => write!(f, "{i}"),
+            Self::Float(x) => write!(f, "{x}"),
+            Self::Text(t) => write!(f, "{t}"),
+        }
+    }
+}
+
+macro_rules! impl_state_value {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$ty> for StateValue {
+                #[inline]
+                fn from(value: $ty) -> Self {
+                    Self::$variant(value.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_state_value!(bool => Bool, i32 => Int, i64 => Int, u32 => Int, f32 => Float, f64 => Float, String => Text, &str => Text);
+
+impl From<usize> for StateValue {
+    #[inline]
+    fn from(value: usize) -> Self {
+        Self::Int(value as i64)
+    }
+}
+
+/// Interface for atomic models whose state can be observed by listeners (see [`Listener::on_state`]).
+/// Models must also override [`crate::modeling::Atomic::as_state_snapshot`] to return `Some(self)`.
+pub trait StateSnapshot {
+    /// Returns the name and current value of the observable state variables of the model.
+    fn snapshot(&self) -> Vec<(&'static str, StateValue)>;
+}
+
+/// Interface for observing simulations without modifying the models.
+/// Listeners are registered in a [`super::RootCoordinator`], which notifies them of the simulation progress.
+/// Components are identified by their path in the model hierarchy (e.g., `"root.subcoupled.atomic"`).
+/// `T` is the simulation time type (by default, [`f64`]). All the methods do nothing by default.
+#[allow(unused_variables)]
+pub trait Listener<T: Time = f64> {
+    /// Called after the model has been started at time `t_start`.
+    fn on_start(&mut self, t_start: T) {}
+
+    /// Called after the collection phase at time `t` has finished.
+    /// Messages generated during the phase are notified via [`Listener::on_output`] right before.
+    fn on_collection(&mut self, t: T) {}
+
+    /// Called for every output port that contains messages after the collection phase at time `t`.
+    fn on_output(&mut self, t: T, path: &str, port: &str, values: &PortValues) {}
+
+    /// Called for every atomic model that triggered a state transition at time `t`.
+    fn on_transition(&mut self, t: T, path: &str) {}
+
+    /// Called after the model has been stopped, right before [`Listener::on_stop`],
+    /// with the metadata of the simulation run.
+    fn on_run_record(&mut self, record: &RunRecord) {}
+
+    /// Called after the model has been stopped at time `t_stop`.
+    fn on_stop(&mut self, t_stop: T) {}
+
+    /// Returns the sampling period of the state of the models. If it returns [`None`] (default),
+    /// [`Listener::on_state`] is never called. Otherwise, it must return a positive period.
+    /// It is queried when the simulation starts.
+    fn state_interval(&self) -> Option<T> {
+        None
+    }
+
+    /// Called for every atomic model that implements [`StateSnapshot`] at every sampling time `t`,
+    /// from the simulation start until the last simulation cycle. `state` is the state of the model
+    /// at time `t` (i.e., after the transitions at time `t`). Samples are notified in time order,
+    /// but with some delay, as they are taken when the next state transition occurs.
+    fn on_state(&mut self, t: T, path: &str, state: &[(&'static str, StateValue)]) {}
+}
+
+/// Sample of the state of an atomic model, recorded by a [`StateRecorder`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateSample<T: Time = f64> {
+    /// Sampling time.
+    pub t: T,
+    /// Path of the atomic model in the model hierarchy.
+    pub path: String,
+    /// Name and value of the observable state variables.
+    pub state: Vec<(&'static str, StateValue)>,
+}
+
+/// [`Listener`] that samples the state of the atomic models that implement [`StateSnapshot`]
+/// with a fixed period. Cloned recorders share the same buffer, so a clone can be kept
+/// to inspect the time series after the simulation.
+#[derive(Debug, Clone)]
+pub struct StateRecorder<T: Time = f64> {
+    /// Sampling period.
+    interval: T,
+    /// Recorded samples.
+    samples: Arc<Mutex<Vec<StateSample<T>>>>,
+}
+
+impl<T: Time> StateRecorder<T> {
+    /// Creates a new recorder with the given sampling period. It panics if `interval` is not positive.
+    pub fn new(interval: T) -> Self {
+        assert!(interval > T::ZERO, "state interval must be positive");
+        Self {
+            interval,
+            samples: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Returns a copy of the samples recorded so far.
+    pub fn samples(&self) -> Vec<StateSample<T>> {
+        self.samples.lock().unwrap().clone()
+    }
+
+    /// Returns the time series of a state variable of the atomic model with the given path.
+    pub fn series(&self, path: &str, variable: &str) -> Vec<(T, StateValue)> {
+        let samples = self.samples.lock().unwrap();
+        (samples.iter().filter(|s| s.path == path))
+            .filter_map(|s| {
+                let (_, value) = s.state.iter().find(|(name, _)| *name == variable)?;
+                Some((s.t, value.clone()))
+            })
+            .collect()
+    }
+}
+
+impl<T: Time> Listener<T> for StateRecorder<T> {
+    fn on_start(&mut self, _t_start: T) {
+        self.samples.lock().unwrap().clear();
+    }
+
+    fn state_interval(&self) -> Option<T> {
+        Some(self.interval)
+    }
+
+    fn on_state(&mut self, t: T, path: &str, state: &[(&'static str, StateValue)]) {
+        self.samples.lock().unwrap().push(StateSample {
+            t,
+            path: path.to_string(),
+            state: state.to_vec(),
+        });
+    }
+}
+
+/// Component of the model hierarchy observed by a [`Listeners`] set.
+struct Node {
+    /// Path of the component in the model hierarchy.
+    path: String,
+    /// Number of state transitions of the component in the previous simulation cycle.
+    n_transitions: usize,
+    /// Last observable state of the component. It is only stored if any listener samples states.
+    state: Option<Vec<(&'static str, StateValue)>>,
+}
+
+/// State sampling schedule of a listener.
+struct Sampler<T: Time> {
+    /// Index of the listener.
+    listener: usize,
+    /// Sampling period.
+    interval: T,
+    /// Next sampling time.
+    t_next: T,
+}
+
+/// Set of listeners registered in a [`super::RootCoordinator`].
+/// It flattens the model hierarchy to notify events in a deterministic order.
+pub(crate) struct Listeners<T: Time> {
+    /// Registered listeners.
+    listeners: Vec<Box<dyn Listener<T>>>,
+    /// Components of the model hierarchy in depth-first order.
+    nodes: Vec<Node>,
+    /// State sampling schedules of the listeners that sample states.
+    samplers: Vec<Sampler<T>>,
+    /// Time of the last simulation cycle.
+    t_last: T,
+}
+
+impl<T: Time> Default for Listeners<T> {
+    fn default() -> Self {
+        Self {
+            listeners: Vec::new(),
+            nodes: Vec::new(),
+            samplers: Vec::new(),
+            t_last: T::ZERO,
+        }
+    }
+}
+
+impl<T: Time> Listeners<T> {
+    /// Returns `true` if there are no registered listeners.
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.listeners.is_empty()
+    }
+
+    /// Registers a new listener.
+    #[inline]
+    pub(crate) fn push(&mut self, listener: Box<dyn Listener<T>>) {
+        self.listeners.push(listener);
+    }
+
+    /// Builds the component paths of the model and notifies the simulation start.
+    pub(crate) fn start(&mut self, model: &dyn Simulator<T>, t_start: T) {
+        self.nodes.clear();
+        build_nodes(model, "", &mut self.nodes);
+        self.samplers.clear();
+        for (listener, l) in self.listeners.iter().enumerate() {
+            if let Some(interval) = l.state_interval() {
+                assert!(interval > T::ZERO, "state interval must be positive");
+                self.samplers.push(Sampler {
+                    listener,
+                    interval,
+                    t_next: t_start,
+                });
+            }
+        }
+        if !self.samplers.is_empty() {
+            let mut i = 0;
+            walk(model, &mut |component| {
+                self.nodes[i].state = component.state_snapshot();
+                i += 1;
+            });
+        }
+        self.t_last = t_start;
+        self.listeners.iter_mut().for_each(|l| l.on_start(t_start));
+    }
+
+    /// Notifies the output messages of all the components and the end of the collection phase.
+    pub(crate) fn collection(&mut self, model: &dyn Simulator<T>, t: T) {
+        let mut i = 0;
+        let (nodes, listeners) = (&self.nodes, &mut self.listeners);
+        walk(model, &mut |component| {
+            let path = &nodes[i].path;
+            for port in component.get_component().get_out_ports() {
+                // Safety: listeners reading messages between simulation phases
+                if !unsafe { port.is_empty() } {
+                    let values = PortValues::new(port.as_ref());
+                    for listener in listeners.iter_mut() {
+                        listener.on_output(t, path, port.get_name(), &values);
+                    }
+                }
+            }
+            i += 1;
+        });
+        self.listeners.iter_mut().for_each(|l| l.on_collection(t));
+    }
+
+    /// Notifies the atomic models that triggered a state transition in the last transition phase.
+    /// It also notifies the state samples before time `t`, as states are about to change.
+    pub(crate) fn transition(&mut self, model: &dyn Simulator<T>, t: T) {
+        let sample = !self.samplers.is_empty();
+        if sample {
+            self.sample(t, false);
+        }
+        self.t_last = t;
+        let mut i = 0;
+        let (nodes, listeners) = (&mut self.nodes, &mut self.listeners);
+        walk(model, &mut |component| {
+            let node = &mut nodes[i];
+            // Only atomic models count their state transitions
+            let n_transitions = component.get_component().get_n_transitions();
+            if n_transitions != node.n_transitions {
+                node.n_transitions = n_transitions;
+                if sample {
+                    node.state = component.state_snapshot();
+                }
+                for listener in listeners.iter_mut() {
+                    listener.on_transition(t, &node.path);
+                }
+            }
+            i += 1;
+        });
+    }
+
+    /// Notifies the remaining state samples until the last simulation cycle and the simulation stop.
+    pub(crate) fn stop(&mut self, t_stop: T, record: &RunRecord) {
+        self.sample(self.t_last, true);
+        self.listeners
+            .iter_mut()
+            .for_each(|l| l.on_run_record(record));
+        self.listeners.iter_mut().for_each(|l| l.on_stop(t_stop));
+    }
+
+    /// Notifies the state samples before time `t` (or until time `t` if `inclusive` is `true`).
+    fn sample(&mut self, t: T, inclusive: bool) {
+        let (nodes, listeners) = (&self.nodes, &mut self.listeners);
+        for sampler in self.samplers.iter_mut() {
+            let listener = &mut listeners[sampler.listener];
+            while sampler.t_next < t || (inclusive && sampler.t_next == t) {
+                for node in nodes {
+                    if let Some(state) = &node.state {
+                        listener.on_state(sampler.t_next, &node.path, state);
+                    }
+                }
+                sampler.t_next = sampler.t_next + sampler.interval;
+            }
+        }
+    }
+}
+
+/// Pushes the nodes of a model hierarchy in depth-first order.
+/// `prefix` corresponds to the path of the parent component.
+fn build_nodes<T: Time>(model: &dyn Simulator<T>, prefix: &str, nodes: &mut Vec<Node>) {
+    let path = match prefix.is_empty() {
+        true => model.get_name().to_string(),
+        false => format!("{prefix}.{}", model.get_name()),
+    };
+    for component in model.get_components() {
+        build_nodes(component, &path, nodes);
+    }
+    nodes.push(Node {
+        path,
+        n_transitions: model.get_component().get_n_transitions(),
+        state: None,
+    });
+}
+
+/// Visits all the components of a model hierarchy in depth-first order.
+fn walk<T: Time>(model: &dyn Simulator<T>, f: &mut impl FnMut(&dyn Simulator<T>)) {
+    for component in model.get_components() {
+        walk(component, f);
+    }
+    f(model);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devstone::{TestProbe, LI};
+    use crate::modeling::{Atomic, Component, Coupled};
+    use crate::simulation::RootCoordinator;
+
+    #[derive(Debug, Default)]
+    struct Counts {
+        n_starts: usize,
+        n_collections: usize,
+        n_outputs: usize,
+        n_transitions: usize,
+        n_stops: usize,
+        paths: Vec<String>,
+    }
+
+    struct TestListener(Arc<Mutex<Counts>>);
+
+    impl Listener for TestListener {
+        fn on_start(&mut self, _t_start: f64) {
+            self.0.lock().unwrap().n_starts += 1;
+        }
+
+        fn on_collection(&mut self, _t: f64) {
+            self.0.lock().unwrap().n_collections += 1;
+        }
+
+        fn on_output(&mut self, _t: f64, path: &str, port: &str, values: &PortValues) {
+            assert_eq!("output", port);
+            let values = values.downcast::<usize>().unwrap();
+            let mut counts = self.0.lock().unwrap();
+            counts.n_outputs += values.len();
+            counts.paths.push(path.to_string());
+        }
+
+        fn on_transition(&mut self, _t: f64, _path: &str) {
+            self.0.lock().unwrap().n_transitions += 1;
+        }
+
+        fn on_stop(&mut self, _t_stop: f64) {
+            self.0.lock().unwrap().n_stops += 1;
+        }
+    }
+
+    /// Atomic model that increments a counter every time unit.
+    struct Ticker {
+        component: Component,
+        count: usize,
+    }
+
+    impl Atomic for Ticker {
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+
+        fn lambda(&self) {}
+
+        fn delta_int(&mut self) {
+            self.count += 1;
+        }
+
+        fn delta_ext(&mut self, _e: f64) {}
+
+        fn ta(&self) -> f64 {
+            1.
+        }
+
+        fn as_state_snapshot(&self) -> Option<&dyn StateSnapshot> {
+            Some(self)
+        }
+    }
+
+    impl StateSnapshot for Ticker {
+        fn snapshot(&self) -> Vec<(&'static str, StateValue)> {
+            vec![
+                ("count", self.count.into()),
+                ("even", self.count.is_multiple_of(2).into()),
+            ]
+        }
+    }
+
+    #[test]
+    fn test_state_recorder() {
+        let mut coupled = Coupled::new("root");
+        coupled.add_component(Box::new(Ticker {
+            component: Component::new("ticker"),
+            count: 0,
+        }));
+        let probe = Arc::new(Mutex::new(TestProbe::default()));
+        coupled.add_component(Box::new(LI::create(2, 2, 0, 0, probe)));
+        let recorder = StateRecorder::new(0.5);
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.add_listener(Box::new(recorder.clone()));
+        simulator.simulate(3.);
+
+        // only the ticker is observable, and it is sampled until its last transition at time 2
+        assert_eq!(5, recorder.samples().len());
+        let counts: Vec<_> = (recorder.series("root.ticker", "count").into_iter())
+            .map(|(t, v)| (t, v.as_f64().unwrap()))
+            .collect();
+        assert_eq!(
+            vec![(0., 0.), (0.5, 0.), (1., 1.), (1.5, 1.), (2., 2.)],
+            counts
+        );
+        let even = recorder.series("root.ticker", "even");
+        assert_eq!((2., StateValue::Bool(true)), even[4]);
+        assert_eq!("false", even[2].1.to_string());
+    }
+
+    #[test]
+    fn test_listener() {
+        let (width, depth) = (3, 4);
+        let n_atomics = (width - 1) * (depth - 1) + 1;
+        let probe = Arc::new(Mutex::new(TestProbe::default()));
+        let counts = Arc::new(Mutex::new(Counts::default()));
+        let mut simulator = RootCoordinator::new(LI::create(width, depth, 0, 0, probe));
+        simulator.add_listener(Box::new(TestListener(counts.clone())));
+        simulator.simulate(f64::INFINITY);
+
+        let counts = counts.lock().unwrap();
+        assert_eq!(1, counts.n_starts);
+        assert_eq!(2, counts.n_collections);
+        // seeder + all the atomics + EOCs of the inner coupled models
+        assert_eq!(1 + n_atomics + depth, counts.n_outputs);
+        // one internal transition per atomic model plus the seeder, and one external per atomic
+        assert_eq!(2 * n_atomics + 1, counts.n_transitions);
+        assert_eq!(1, counts.n_stops);
+        assert!(counts.paths.contains(&"LI.seeder".to_string()));
+        assert!(counts.paths.contains(&"LI.coupled_4.coupled_3".to_string()));
+    }
+}
@@ -0,0 +1,141 @@
+use super::Simulator;
+use crate::time::Time;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Simulation functions measured by the profiler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Phase {
+    Lambda,
+    DeltaInt,
+    DeltaExt,
+    DeltaConf,
+    Propagation,
+}
+
+/// Wall-clock time spent by a component in every simulation function.
+/// Propagation only applies to coupled models, and transition functions only apply to atomic models.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ComponentProfile {
+    /// Path of the component in the model hierarchy (e.g., `"root.subcoupled.atomic"`).
+    pub path: String,
+    /// Time spent in the output function.
+    pub lambda: Duration,
+    /// Time spent in the internal transition function.
+    pub delta_int: Duration,
+    /// Time spent in the external transition function.
+    pub delta_ext: Duration,
+    /// Time spent in the confluent transition function.
+    pub delta_conf: Duration,
+    /// Time spent propagating messages through the couplings of the model.
+    pub propagation: Duration,
+    /// Number of measured function calls.
+    pub calls: u64,
+}
+
+impl ComponentProfile {
+    /// Returns the total time spent by the component.
+    pub fn total(&self) -> Duration {
+        self.lambda + self.delta_int + self.delta_ext + self.delta_conf + self.propagation
+    }
+
+    /// Adds the time elapsed since `start` to a simulation function.
+    #[inline]
+    pub(crate) fn record(&mut self, phase: Phase, start: Instant) {
+        let elapsed = start.elapsed();
+        let duration = match phase {
+            Phase::Lambda => &mut self.lambda,
+            Phase::DeltaInt => &mut self.delta_int,
+            Phase::DeltaExt => &mut self.delta_ext,
+            Phase::DeltaConf => &mut self.delta_conf,
+            Phase::Propagation => &mut self.propagation,
+        };
+        *duration += elapsed;
+        self.calls += 1;
+    }
+}
+
+/// Report of the time spent by every component of a model during a simulation.
+/// It is obtained with [`super::RootCoordinator::profile`], and components are sorted
+/// by their total time in descending order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    /// Profiles of the components.
+    components: Vec<ComponentProfile>,
+}
+
+impl Profile {
+    /// Collects the profiles of a model and its subcomponents.
+    pub(crate) fn new<T: Time>(model: &dyn Simulator<T>) -> Self {
+        let mut components = Vec::new();
+        collect(model, &mut components);
+        components.sort_by(|a, b| b.total().cmp(&a.total()).then(a.path.cmp(&b.path)));
+        Self { components }
+    }
+
+    /// Returns the profiles of the components, sorted by their total time in descending order.
+    pub fn components(&self) -> &[ComponentProfile] {
+        &self.components
+    }
+
+    /// Returns the profile of the component with the given path (if any).
+    pub fn get(&self, path: &str) -> Option<&ComponentProfile> {
+        self.components.iter().find(|c| c.path == path)
+    }
+
+    /// Returns the total time spent by all the components.
+    pub fn total(&self) -> Duration {
+        self.components.iter().map(|c| c.total()).sum()
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = (self.components.iter())
+            .map(|c| c.path.len())
+            .chain(std::iter::once(9))
+            .max()
+            .unwrap();
+        writeln!(
+            f,
+            "{:<width$} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12} {:>10}",
+            "component",
+            "total",
+            "lambda",
+            "delta_int",
+            "delta_ext",
+            "delta_conf",
+            "propagation",
+            "calls"
+        )?;
+        for c in self.components.iter() {
+            writeln!(
+                f,
+                "{:<width$} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12} {:>10}",
+                c.path,
+                format!("{:.3?}", c.total()),
+                format!("{:.3?}", c.lambda),
+                format!("{:.3?}", c.delta_int),
+                format!("{:.3?}", c.delta_ext),
+                format!("{:.3?}", c.delta_conf),
+                format!("{:.3?}", c.propagation),
+                c.calls
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Recursively collects the profiles of a model and its subcomponents.
+fn collect<T: Time>(model: &dyn Simulator<T>, components: &mut Vec<ComponentProfile>) {
+    let component = model.get_component();
+    if let Some(profile) = component.get_profile() {
+        components.push(ComponentProfile {
+            path: component.get_path().to_string(),
+            ..profile.clone()
+        });
+    }
+    for child in model.get_components() {
+        collect(child, components);
+    }
+}
@@ -0,0 +1,78 @@
+use crate::time::Time;
+use std::time::Duration;
+
+/// Progress of a simulation run by [`super::RootCoordinator::simulate_with_progress`]
+/// or [`super::RootCoordinator::simulate_with_budget`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress<T> {
+    /// Time of the last simulation cycle.
+    pub t: T,
+    /// Simulation time at which the simulation stops.
+    pub t_end: T,
+    /// Number of simulation cycles executed so far.
+    pub n_cycles: usize,
+    /// Wall-clock time elapsed since the simulation started.
+    pub elapsed: Duration,
+    /// It is `true` if the simulation is over.
+    pub finished: bool,
+}
+
+impl<T: Time> Progress<T> {
+    /// Returns the fraction of the simulation time already simulated, between 0 and 1.
+    /// It returns [`None`] if the simulation has no finite end time.
+    pub fn fraction(&self) -> Option<f64> {
+        let t_end = self.t_end.as_f64();
+        if !t_end.is_finite() || t_end <= 0. {
+            return None;
+        }
+        match self.finished {
+            true => Some(1.),
+            false => Some((self.t.as_f64() / t_end).clamp(0., 1.)),
+        }
+    }
+
+    /// Returns the number of simulation cycles executed per wall-clock second.
+    pub fn cycles_per_second(&self) -> f64 {
+        match self.elapsed.is_zero() {
+            true => 0.,
+            false => self.n_cycles as f64 / self.elapsed.as_secs_f64(),
+        }
+    }
+
+    /// Returns an estimate of the wall-clock time left to complete the simulation, assuming that the rest
+    /// of the simulation advances the simulation time at the same rate as so far.
+    /// It returns [`None`] if the simulation has no finite end time or has not advanced the simulation time yet.
+    pub fn eta(&self) -> Option<Duration> {
+        let fraction = self.fraction()?;
+        if fraction <= 0. {
+            return None;
+        }
+        Some(self.elapsed.mul_f64((1. - fraction) / fraction))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress() {
+        let mut progress = Progress {
+            t: 25.,
+            t_end: 100.,
+            n_cycles: 50,
+            elapsed: Duration::from_secs(2),
+            finished: false,
+        };
+        assert_eq!(Some(0.25), progress.fraction());
+        assert_eq!(25., progress.cycles_per_second());
+        assert_eq!(Some(Duration::from_secs(6)), progress.eta());
+
+        progress.finished = true;
+        assert_eq!(Some(Duration::ZERO), progress.eta());
+
+        progress.t_end = f64::INFINITY;
+        assert_eq!(None, progress.fraction());
+        assert_eq!(None, progress.eta());
+    }
+}
@@ -0,0 +1,326 @@
+use crate::modeling::port::Values;
+use crate::modeling::Component;
+use crate::time::Time;
+use crate::DynRef;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Display};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Function that converts a bag of messages (i.e., `Vec<V>`) to a list of strings.
+type Encode = fn(&dyn Any) -> Vec<String>;
+
+/// Function that parses a message and returns it in a boxed bag (i.e., `Vec<V>`).
+type Decode = fn(&str) -> Result<Box<dyn Any + Send>, String>;
+
+/// Errors that may occur when recording or replaying a simulation run.
+#[derive(Debug)]
+pub enum ReplayError {
+    /// The replay file could not be read or written.
+    Io(io::Error),
+    /// A line of the replay file is not valid. It contains the line number (starting at 1) and the error message.
+    Parse(usize, String),
+    /// The input port of the root model with the given name is not registered in the [`ReplayPorts`].
+    MissingPort(String),
+    /// The replay contains events for an unregistered input port with the given name.
+    UnknownPort(String),
+    /// The message type registered for the input port with the given name does not match the port.
+    IncompatiblePort(String),
+    /// A message for the input port with the given name could not be parsed.
+    Value(String, String),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "could not access replay: {e}"),
+            Self::Parse(line, e) => write!(f, "invalid replay at line {line}: {e}"),
+            Self::MissingPort(p) => write!(f, "input port {p} is not registered for replays"),
+            Self::UnknownPort(p) => write!(f, "input port {p} does not exist"),
+            Self::IncompatiblePort(p) => {
+                write!(f, "registered type is not compatible with input port {p}")
+            }
+            Self::Value(p, e) => write!(f, "invalid message for input port {p}: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Message types of the input ports of a root model, used for writing and parsing replays.
+/// Messages are written with [`Display`] and parsed with [`FromStr`], so both must round-trip
+/// and messages must be written in a single line.
+#[derive(Default, Clone)]
+pub struct ReplayPorts {
+    /// Type ID of the bags, encoder, and decoder of every input port.
+    ports: HashMap<String, (TypeId, Encode, Decode)>,
+}
+
+impl ReplayPorts {
+    /// Creates an empty set of replay ports.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the message type `V` of an input port of the root model.
+    pub fn add<V>(&mut self, port: &str) -> &mut Self
+    where
+        V: DynRef + Clone + Send + Display + FromStr,
+        V::Err: Display,
+    {
+        let codec = (
+            TypeId::of::<Values<V>>(),
+            encode::<V> as Encode,
+            decode::<V> as Decode,
+        );
+        self.ports.insert(port.to_string(), codec);
+        self
+    }
+
+    /// Checks that all the input ports of a root model are registered with their message type.
+    pub(crate) fn check<T: Time>(&self, component: &Component<T>) -> Result<(), ReplayError> {
+        for port in component.get_in_ports() {
+            let name = port.get_name();
+            let (type_id, _, _) = self
+                .ports
+                .get(name)
+                .ok_or_else(|| ReplayError::MissingPort(name.to_string()))?;
+            // Safety: reading port types between simulation phases
+            if *type_id != unsafe { port.get_values() }.type_id() {
+                return Err(ReplayError::IncompatiblePort(name.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Converts a bag of messages sent to an input port to a list of strings.
+    #[cfg(feature = "async")]
+    pub(crate) fn encode(&self, port: &str, values: &dyn Any) -> Vec<String> {
+        self.ports
+            .get(port)
+            .map(|(_, encode, _)| encode(values))
+            .unwrap_or_default()
+    }
+
+    /// Parses a message sent to an input port and returns it in a boxed bag.
+    pub(crate) fn decode(
+        &self,
+        port: &str,
+        value: &str,
+    ) -> Result<Box<dyn Any + Send>, ReplayError> {
+        let (_, _, decode) = self
+            .ports
+            .get(port)
+            .ok_or_else(|| ReplayError::UnknownPort(port.to_string()))?;
+        decode(value).map_err(|e| ReplayError::Value(port.to_string(), e))
+    }
+}
+
+fn encode<V: Display + 'static>(values: &dyn Any) -> Vec<String> {
+    match values.downcast_ref::<Vec<V>>() {
+        Some(values) => values.iter().map(V::to_string).collect(),
+        None => Vec::new(),
+    }
+}
+
+fn decode<V>(value: &str) -> Result<Box<dyn Any + Send>, String>
+where
+    V: FromStr + Send + 'static,
+    V::Err: Display,
+{
+    match value.parse::<V>() {
+        Ok(value) => Ok(Box::new(vec![value])),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Message injected into an input port of the root model during a recorded simulation run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayEvent {
+    /// Index of the injection. Messages with the same index were injected in the same external transition.
+    pub batch: usize,
+    /// Simulation time of the injection.
+    pub time: f64,
+    /// It is `true` if the messages were injected in a confluent transition, i.e., together with
+    /// a simulation cycle at the same time. Otherwise, cycles at the same time were executed before.
+    pub confluent: bool,
+    /// Name of the input port.
+    pub port: String,
+    /// Message, as written by [`Display`].
+    pub value: String,
+}
+
+/// Log of a simulation run with everything needed for re-executing it deterministically.
+/// Random numbers are not stored draw by draw: the streams of all the components are derived from
+/// the master seed, so the seed alone reproduces every draw.
+///
+/// Replay files are text files with one record per line:
+/// `seed,<seed>` (if the run was seeded), `end,<time of the last simulation cycle>`,
+/// and `<batch>,<time>,<confluent>,<port>,<value>` for every injected message. Values may contain commas.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Replay {
+    /// Master seed of the random number generators. If [`None`], the run was not seeded.
+    pub seed: Option<u64>,
+    /// Time of the last simulation cycle.
+    pub end: f64,
+    /// Injected messages, sorted by batch.
+    pub events: Vec<ReplayEvent>,
+}
+
+impl Replay {
+    /// Reads a replay from a reader.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, ReplayError> {
+        let mut replay = Self::default();
+        for (i, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+            let error = |e: String| ReplayError::Parse(i + 1, e);
+            match line.splitn(5, ',').collect::<Vec<_>>()[..] {
+                [""] => (),
+                ["seed", seed] => {
+                    let seed = seed
+                        .parse()
+                        .map_err(|e| error(format!("invalid seed: {e}")))?;
+                    replay.seed = Some(seed);
+                }
+                ["end", end] => {
+                    replay.end = end
+                        .parse()
+                        .map_err(|e| error(format!("invalid time: {e}")))?
+                }
+                [batch, time, confluent, port, value] => replay.events.push(ReplayEvent {
+                    batch: batch
+                        .parse()
+                        .map_err(|e| error(format!("invalid batch: {e}")))?,
+                    time: time
+                        .parse()
+                        .map_err(|e| error(format!("invalid time: {e}")))?,
+                    confluent: confluent
+                        .parse()
+                        .map_err(|e| error(format!("invalid confluent flag: {e}")))?,
+                    port: port.to_string(),
+                    value: value.to_string(),
+                }),
+                _ => return Err(error("unknown record".to_string())),
+            }
+        }
+        Ok(replay)
+    }
+
+    /// Reads a replay from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ReplayError> {
+        Self::from_reader(std::fs::File::open(path)?)
+    }
+
+    /// Writes the replay to a writer.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        if let Some(seed) = self.seed {
+            writeln!(writer, "seed,{seed}")?;
+        }
+        writeln!(writer, "end,{}", self.end)?;
+        for event in &self.events {
+            let ReplayEvent {
+                batch,
+                time,
+                confluent,
+                port,
+                value,
+            } = event;
+            writeln!(writer, "{batch},{time},{confluent},{port},{value}")?;
+        }
+        writer.flush()
+    }
+
+    /// Writes the replay to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write(io::BufWriter::new(std::fs::File::create(path)?))
+    }
+
+    /// Appends the messages injected in the same external transition to the replay.
+    #[cfg(feature = "async")]
+    pub(crate) fn record<'a, I>(
+        &mut self,
+        ports: &ReplayPorts,
+        time: f64,
+        confluent: bool,
+        batch: I,
+    ) where
+        I: IntoIterator<Item = (&'a str, &'a dyn Any)>,
+    {
+        let index = self.events.last().map_or(0, |e| e.batch + 1);
+        for (port, values) in batch {
+            for value in ports.encode(port, values) {
+                self.events.push(ReplayEvent {
+                    batch: index,
+                    time,
+                    confluent,
+                    port: port.to_string(),
+                    value,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::Delay;
+    use crate::simulation::{Listener, PortValues, RootCoordinator};
+    use std::sync::{Arc, Mutex};
+
+    struct Recorder(Arc<Mutex<Vec<(f64, usize)>>>);
+
+    impl Listener for Recorder {
+        fn on_output(&mut self, t: f64, path: &str, _port: &str, values: &PortValues) {
+            if path == "delay" {
+                let values = values.downcast::<usize>().unwrap();
+                self.0
+                    .lock()
+                    .unwrap()
+                    .extend(values.iter().map(|&v| (t, v)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_replay() {
+        let file =
+            "seed,7\nend,2.5\n0,0.5,false,input,1\n0,0.5,false,input,2\n1,1.5,true,input,3\n";
+        let replay = Replay::from_reader(file.as_bytes()).unwrap();
+        assert_eq!(
+            (Some(7), 2.5, 3),
+            (replay.seed, replay.end, replay.events.len())
+        );
+        let mut written = Vec::new();
+        replay.write(&mut written).unwrap();
+        assert_eq!(file.as_bytes(), written);
+        assert!(matches!(
+            Replay::from_reader("end,2.5\nfoo".as_bytes()),
+            Err(ReplayError::Parse(2, _))
+        ));
+
+        let mut simulator = RootCoordinator::new(Delay::<usize>::new("delay", 1.));
+        let outputs = Arc::new(Mutex::new(Vec::new()));
+        simulator.add_listener(Box::new(Recorder(outputs.clone())));
+        assert!(matches!(
+            simulator.replay_log(&replay, &ReplayPorts::new()),
+            Err(ReplayError::MissingPort(_))
+        ));
+        assert!(matches!(
+            simulator.replay_log(&replay, ReplayPorts::new().add::<u8>("input")),
+            Err(ReplayError::IncompatiblePort(_))
+        ));
+        let mut ports = ReplayPorts::new();
+        ports.add::<usize>("input");
+        simulator.replay_log(&replay, &ports).unwrap();
+        assert_eq!(vec![(1.5, 1), (1.5, 2), (2.5, 3)], *outputs.lock().unwrap());
+    }
+}
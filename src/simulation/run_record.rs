@@ -0,0 +1,291 @@
+use super::Simulator;
+use crate::modeling::{Distribution, ModelStats};
+use crate::time::Time;
+use crate::tracing::encode_string;
+use std::fmt::{self, Write as _};
+use std::time::{Duration, Instant};
+
+/// Features of the crate that may be enabled at compile time.
+/// Features that only enable other features (e.g., `par_all`) are not listed.
+const FEATURES: [(&str, bool); 25] = [
+    ("par_start", cfg!(feature = "par_start")),
+    ("par_collection", cfg!(feature = "par_collection")),
+    ("par_transition", cfg!(feature = "par_transition")),
+    ("par_stop", cfg!(feature = "par_stop")),
+    ("par_couplings", cfg!(feature = "par_couplings")),
+    ("par_buffers", cfg!(feature = "par_buffers")),
+    ("par_experiment", cfg!(feature = "par_experiment")),
+    ("python", cfg!(feature = "python")),
+    ("wasm", cfg!(feature = "wasm")),
+    ("fmi", cfg!(feature = "fmi")),
+    ("small_bags", cfg!(feature = "small_bags")),
+    ("single_thread", cfg!(feature = "single_thread")),
+    ("unchecked_ports", cfg!(feature = "unchecked_ports")),
+    ("checked_ta", cfg!(feature = "checked_ta")),
+    ("debug_checks", cfg!(feature = "debug_checks")),
+    ("test_util", cfg!(feature = "test_util")),
+    ("async", cfg!(feature = "async")),
+    ("ws", cfg!(feature = "ws")),
+    ("mqtt", cfg!(feature = "mqtt")),
+    ("shm", cfg!(feature = "shm")),
+    ("plot", cfg!(feature = "plot")),
+    ("server", cfg!(feature = "server")),
+    ("track_alloc", cfg!(feature = "track_alloc")),
+    ("devstone_busy", cfg!(feature = "devstone_busy")),
+    ("serde", cfg!(feature = "serde")),
+];
+
+/// Metadata of a simulation run, required to reproduce it. It is obtained with
+/// [`super::RootCoordinator::run_record`], and it is also notified to every [`super::Listener`]
+/// (and thus to every [`crate::tracing::TraceSink`]) when the simulation stops.
+/// Times are converted to [`f64`] with [`Time::as_f64`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunRecord {
+    /// Version of the crate.
+    pub version: &'static str,
+    /// Features of the crate enabled at compile time.
+    pub features: Vec<&'static str>,
+    /// Master seed of the random number generator streams of the model, if any.
+    pub seed: Option<u64>,
+    /// Structure statistics of the simulated model.
+    pub model: ModelStats,
+    /// Simulation time at which the simulation was meant to stop. It is infinity if the simulation
+    /// was driven cycle by cycle or with [`super::RootCoordinator::simulate_until`].
+    pub t_end: f64,
+    /// Time of the last simulation cycle.
+    pub t_last: f64,
+    /// Wall-clock time elapsed from the start to the end of the simulation.
+    /// It is [`None`] when targeting WebAssembly.
+    pub wall_time: Option<Duration>,
+    /// Number of simulation cycles executed.
+    pub n_cycles: usize,
+    /// Number of state transitions triggered by the atomic models.
+    pub n_transitions: usize,
+}
+
+impl RunRecord {
+    /// Returns every feature of the crate that may be enabled at compile time and whether it is enabled.
+    /// Features that only enable other features (e.g., `par_all`) are not listed.
+    pub fn all_features() -> &'static [(&'static str, bool)] {
+        &FEATURES
+    }
+
+    /// Returns the JSON representation of the record (e.g., `{"version":"0.3.0","features":["par_start"],
+    /// "seed":42,"t_end":100,"t_last":99.5,"wall_time_s":0.012,"n_cycles":200,"n_transitions":600,"model":{...}}`).
+    /// Unknown values and non-finite times are represented as `null`.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\"version\":");
+        encode_string(&mut json, self.version);
+        json.push_str(",\"features\":[");
+        for (i, feature) in self.features.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            encode_string(&mut json, feature);
+        }
+        json.push_str("],\"seed\":");
+        match self.seed {
+            Some(seed) => write!(json, "{seed}").unwrap(),
+            None => json.push_str("null"),
+        }
+        json.push_str(",\"t_end\":");
+        encode_f64(&mut json, self.t_end);
+        json.push_str(",\"t_last\":");
+        encode_f64(&mut json, self.t_last);
+        json.push_str(",\"wall_time_s\":");
+        encode_f64(
+            &mut json,
+            self.wall_time.map_or(f64::NAN, |d| d.as_secs_f64()),
+        );
+        let model = &self.model;
+        write!(
+            json,
+            ",\"n_cycles\":{},\"n_transitions\":{},\"model\":{{\"depth\":{},\"n_atomics\":{},\"n_coupled\":{},\
+\"n_eics\":{},\"n_ics\":{},\"n_eocs\":{}",
+            self.n_cycles,
+            self.n_transitions,
+            model.depth,
+            model.n_atomics,
+            model.n_coupled,
+            model.n_eics,
+            model.n_ics,
+            model.n_eocs
+        )
+        .unwrap();
+        json.push_str(",\"fan_in\":");
+        encode_distribution(&mut json, &model.fan_in);
+        json.push_str(",\"fan_out\":");
+        encode_distribution(&mut json, &model.fan_out);
+        json.push_str("}}");
+        json
+    }
+}
+
+impl fmt::Display for RunRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Version: {} {}", env!("CARGO_PKG_NAME"), self.version)?;
+        match self.features.is_empty() {
+            true => writeln!(f, "Features: none")?,
+            false => writeln!(f, "Features: {}", self.features.join(", "))?,
+        }
+        match self.seed {
+            Some(seed) => writeln!(f, "Seed: {seed}")?,
+            None => writeln!(f, "Seed: none")?,
+        }
+        writeln!(
+            f,
+            "Simulation time: {} (last simulation cycle at {})",
+            self.t_end, self.t_last
+        )?;
+        if let Some(wall_time) = self.wall_time {
+            writeln!(f, "Wall-clock time: {wall_time:?}")?;
+        }
+        writeln!(
+            f,
+            "Events: {} simulation cycles, {} state transitions",
+            self.n_cycles, self.n_transitions
+        )?;
+        write!(f, "{}", self.model)
+    }
+}
+
+/// Encodes a number as a JSON number. Non-finite numbers are encoded as `null`.
+fn encode_f64(json: &mut String, x: f64) {
+    match x.is_finite() {
+        true => write!(json, "{x}").unwrap(),
+        false => json.push_str("null"),
+    }
+}
+
+/// Encodes a distribution as a JSON object (e.g., `{"0":1,"1":4}`).
+fn encode_distribution(json: &mut String, distribution: &Distribution) {
+    json.push('{');
+    for (i, (k, v)) in distribution.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        write!(json, "\"{k}\":{v}").unwrap();
+    }
+    json.push('}');
+}
+
+/// Bookkeeping of the simulation runs of a root coordinator.
+#[derive(Debug)]
+pub(crate) struct RunInfo<T> {
+    /// Master seed of the random number generator streams of the model, if any.
+    pub(crate) seed: Option<u64>,
+    /// Simulation time at which the current simulation is meant to stop.
+    pub(crate) t_end: T,
+    /// Number of simulation cycles executed in the current simulation.
+    pub(crate) n_cycles: usize,
+    /// Number of state transitions triggered by the atomic models before the current simulation.
+    n_transitions: usize,
+    /// Wall-clock time at which the current simulation started.
+    started: Option<Instant>,
+    /// Wall-clock time elapsed in the last simulation. It is [`None`] until the simulation stops.
+    wall_time: Option<Duration>,
+}
+
+impl<T: Time> RunInfo<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            seed: None,
+            t_end: T::INFINITY,
+            n_cycles: 0,
+            n_transitions: 0,
+            started: None,
+            wall_time: None,
+        }
+    }
+
+    /// Resets the bookkeeping when a new simulation starts.
+    pub(crate) fn start(&mut self, model: &dyn Simulator<T>) {
+        self.t_end = T::INFINITY;
+        self.n_cycles = 0;
+        self.n_transitions = count_transitions(model);
+        self.wall_time = None;
+        // Instant::now panics when targeting WebAssembly
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.started = Some(Instant::now());
+        }
+    }
+
+    /// Measures the wall-clock time of the simulation when it stops.
+    pub(crate) fn stop(&mut self) {
+        self.wall_time = self.started.map(|started| started.elapsed());
+    }
+
+    /// Returns the metadata of the current (or last) simulation run.
+    pub(crate) fn record(&self, model: &dyn Simulator<T>, t_last: T) -> RunRecord {
+        let features = (RunRecord::all_features().iter()).filter(|(_, on)| *on);
+        RunRecord {
+            version: env!("CARGO_PKG_VERSION"),
+            features: features.map(|(f, _)| *f).collect(),
+            seed: self.seed,
+            model: ModelStats::of(model),
+            t_end: self.t_end.as_f64(),
+            t_last: t_last.as_f64(),
+            wall_time: (self.wall_time).or_else(|| self.started.map(|s| s.elapsed())),
+            n_cycles: self.n_cycles,
+            n_transitions: count_transitions(model) - self.n_transitions,
+        }
+    }
+}
+
+/// Returns the number of state transitions triggered by all the atomic models of a model.
+fn count_transitions<T: Time>(model: &dyn Simulator<T>) -> usize {
+    let n = model.get_component().get_n_transitions();
+    n + (model.get_components().into_iter())
+        .map(count_transitions)
+        .sum::<usize>()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lib::{Generator, Job, Processor};
+    use crate::modeling::Coupled;
+    use crate::simulation::RootCoordinator;
+
+    #[test]
+    fn test_run_record() {
+        let mut coupled = Coupled::new("root");
+        coupled.add_component(Box::new(Generator::new("generator", 1.)));
+        coupled.add_component(Box::new(Processor::<Job>::new("processor", 0.5)));
+        coupled.add_ic("generator", "output", "processor", "input");
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.set_seed(42);
+        simulator.simulate(3.);
+
+        let record = simulator.run_record();
+        assert_eq!(env!("CARGO_PKG_VERSION"), record.version);
+        assert_eq!(Some(42), record.seed);
+        assert_eq!((3., 2.5), (record.t_end, record.t_last));
+        // The generator triggers transitions at 1 and 2, the processor at 1, 1.5, 2, and 2.5
+        assert_eq!((4, 6), (record.n_cycles, record.n_transitions));
+        assert_eq!((2, 1), (record.model.n_atomics, record.model.n_ics));
+        assert!(record.wall_time.is_some());
+        let enabled = super::RunRecord::all_features()
+            .iter()
+            .filter(|(_, on)| *on);
+        assert!(enabled.map(|(f, _)| *f).eq(record.features.iter().copied()));
+
+        let json = record.to_json();
+        assert!(json.contains(r#""seed":42,"t_end":3,"t_last":2.5,"#));
+        assert!(
+            json.contains(r#""n_cycles":4,"n_transitions":6,"model":{"depth":1,"n_atomics":2,"#)
+        );
+        assert!(json.ends_with(r#""fan_out":{"0":1,"1":1}}}"#));
+        assert!(record.to_string().contains("Seed: 42\n"));
+
+        // Counts only include the last simulation
+        simulator.simulate(1.);
+        assert_eq!(
+            (0, 0),
+            (
+                simulator.run_record().n_cycles,
+                simulator.run_record().n_transitions
+            )
+        );
+    }
+}
@@ -0,0 +1,585 @@
+use crate::error::ModelingError;
+use crate::modeling::coupled::{port_key, Coupling};
+use crate::modeling::{Coupled, Subcomponent};
+use crate::time::Time;
+#[cfg(not(feature = "par_couplings"))]
+use std::collections::HashMap;
+
+/// Scheduler of the subcomponents of a [`Coupled`] model.
+/// It keeps an indexed binary heap of subcomponents ordered by their next simulation time,
+/// so coupled models only visit imminent subcomponents and subcomponents that received messages.
+/// Couplings are also grouped by source, so coupled models only visit the couplings of active ports.
+/// Ties are broken by the index of the subcomponent, so the schedule is deterministic.
+pub(crate) struct Scheduler<T> {
+    /// Next simulation time of every subcomponent.
+    times: Vec<T>,
+    /// Binary heap of subcomponent indices ordered by their next simulation time.
+    heap: Vec<usize>,
+    /// Position of every subcomponent in the heap.
+    pos: Vec<usize>,
+    /// Index of the destination subcomponent of every EIC.
+    eic_targets: Vec<usize>,
+    /// Index of the destination subcomponent of every IC.
+    ic_targets: Vec<usize>,
+    /// Index of the destination subcomponent of every EIC and key of its input port, if it is a direct-connected
+    /// coupled model that must be signaled when the EIC propagates messages (see [`Coupled::direct_connect`]).
+    eic_entries: Vec<Option<(usize, usize)>>,
+    /// Index of the destination subcomponent of every IC and key of its input port, if it is a direct-connected
+    /// coupled model that must be signaled when the IC propagates messages (see [`Coupled::direct_connect`]).
+    ic_entries: Vec<Option<(usize, usize)>>,
+    /// Sorted keys of the input ports of a direct-connected coupled model and their index.
+    own_inputs: Vec<(usize, usize)>,
+    /// It is `true` for the input ports of a direct-connected coupled model that were signaled.
+    signaled: Vec<bool>,
+    /// Input ports of a direct-connected coupled model that were signaled.
+    signaled_list: Vec<usize>,
+    /// Indices of the ICs grouped by source subcomponent.
+    /// The ICs of the `i`-th subcomponent are `out_ics[ic_offsets[i]..ic_offsets[i + 1]]`.
+    out_ics: Vec<usize>,
+    /// Offsets of the ICs of every subcomponent in `out_ics`.
+    ic_offsets: Vec<usize>,
+    /// Indices of the EOCs grouped by source subcomponent.
+    /// The EOCs of the `i`-th subcomponent are `out_eocs[eoc_offsets[i]..eoc_offsets[i + 1]]`.
+    out_eocs: Vec<usize>,
+    /// Offsets of the EOCs of every subcomponent in `out_eocs`.
+    eoc_offsets: Vec<usize>,
+    /// Indices of the EICs grouped by the input port of the coupled model.
+    /// The EICs of the `j`-th input port are `in_eics[eic_offsets[j]..eic_offsets[j + 1]]`.
+    in_eics: Vec<usize>,
+    /// Offsets of the EICs of every input port of the coupled model in `in_eics`.
+    eic_offsets: Vec<usize>,
+    /// It is `true` for the EICs whose source port has no other coupling, so they can move messages.
+    #[cfg(not(feature = "par_couplings"))]
+    moving_eics: Vec<bool>,
+    /// It is `true` for the ICs whose source port has no other coupling, so they can move messages.
+    #[cfg(not(feature = "par_couplings"))]
+    moving_ics: Vec<bool>,
+    /// It is `true` for the EOCs whose source port has no other coupling, so they can move messages.
+    #[cfg(not(feature = "par_couplings"))]
+    moving_eocs: Vec<bool>,
+    /// Subcomponents with a time of next event equal to the current simulation time.
+    imminent: Vec<usize>,
+    /// It is `true` for subcomponents that must execute their transition in the current simulation cycle.
+    active: Vec<bool>,
+    /// Subcomponents that must execute their transition in the current simulation cycle.
+    active_list: Vec<usize>,
+    /// It is `true` if the heap must be rebuilt after updating the next time of the active subcomponents.
+    rebuild: bool,
+}
+
+impl<T> Default for Scheduler<T> {
+    fn default() -> Self {
+        Self {
+            times: Vec::new(),
+            heap: Vec::new(),
+            pos: Vec::new(),
+            eic_targets: Vec::new(),
+            ic_targets: Vec::new(),
+            eic_entries: Vec::new(),
+            ic_entries: Vec::new(),
+            own_inputs: Vec::new(),
+            signaled: Vec::new(),
+            signaled_list: Vec::new(),
+            out_ics: Vec::new(),
+            ic_offsets: Vec::new(),
+            out_eocs: Vec::new(),
+            eoc_offsets: Vec::new(),
+            in_eics: Vec::new(),
+            eic_offsets: Vec::new(),
+            #[cfg(not(feature = "par_couplings"))]
+            moving_eics: Vec::new(),
+            #[cfg(not(feature = "par_couplings"))]
+            moving_ics: Vec::new(),
+            #[cfg(not(feature = "par_couplings"))]
+            moving_eocs: Vec::new(),
+            imminent: Vec::new(),
+            active: Vec::new(),
+            active_list: Vec::new(),
+            rebuild: false,
+        }
+    }
+}
+
+impl<T: Time> Scheduler<T> {
+    /// Creates a new scheduler for a coupled model, given the next simulation time of its subcomponents.
+    /// It returns an error if a coupling connects a port that does not belong to the model or its subcomponents.
+    pub(crate) fn new<C: Subcomponent<T>>(
+        coupled: &Coupled<T, C>,
+        times: Vec<T>,
+    ) -> Result<Self, ModelingError> {
+        let n = coupled.components.len();
+        // sorted lists of port keys and the index of their subcomponent
+        let (mut inputs, mut outputs) = (Vec::new(), Vec::new());
+        let mut direct: Vec<bool> = Vec::with_capacity(n);
+        for (i, component) in coupled.components.iter().enumerate() {
+            let component = component.as_simulator();
+            direct.push(component.as_coupled().is_some_and(|c| c.direct));
+            let component = component.get_component();
+            inputs.extend(component.get_in_ports().iter().map(|p| (port_key(p), i)));
+            outputs.extend(component.get_out_ports().iter().map(|p| (port_key(p), i)));
+        }
+        let mut own_inputs: Vec<_> = (coupled.component.get_in_ports().iter().enumerate())
+            .map(|(j, p)| (port_key(p), j))
+            .collect();
+        inputs.sort_unstable();
+        outputs.sort_unstable();
+        own_inputs.sort_unstable();
+        // couplings composed by direct-connected models are looked up by their original ports
+        let find = |ports: &[(usize, usize)], key, port: &Coupling, to: bool| {
+            let k = ports.partition_point(|&(k, _)| k < key);
+            match ports.get(k) {
+                Some(&(k, i)) if k == key => Ok(i),
+                _ => {
+                    let port = if to { &port.0 } else { &port.1 };
+                    Err(ModelingError::ForeignPort(port.get_name().to_string()))
+                }
+            }
+        };
+        let target = |c| find(&inputs, coupled.coupling_ends(c).0, c, true);
+        let source = |c| find(&outputs, coupled.coupling_ends(c).1, c, false);
+        let eic_targets: Vec<_> = coupled.eics.iter().map(target).collect::<Result<_, _>>()?;
+        let ic_targets: Vec<_> = coupled.ics.iter().map(target).collect::<Result<_, _>>()?;
+        // couplings to direct-connected subcomponents signal their original destination port
+        let entry = |(coupling, &i): (&Coupling, &usize)| {
+            let key = coupled.coupling_ends(coupling).0;
+            direct[i].then_some((i, key))
+        };
+        let eic_entries = coupled.eics.iter().zip(&eic_targets).map(entry).collect();
+        let ic_entries = coupled.ics.iter().zip(&ic_targets).map(entry).collect();
+        let ic_sources: Vec<_> = coupled.ics.iter().map(source).collect::<Result<_, _>>()?;
+        let eoc_sources: Vec<_> = coupled.eocs.iter().map(source).collect::<Result<_, _>>()?;
+        let ((out_ics, ic_offsets), (out_eocs, eoc_offsets)) = (
+            group_by_source(n, ic_sources.into_iter()),
+            group_by_source(n, eoc_sources.into_iter()),
+        );
+        let n_inputs = own_inputs.len();
+        let eic_sources: Vec<_> = (coupled.eics.iter())
+            .map(|c| find(&own_inputs, port_key(&c.1), c, false))
+            .collect::<Result<_, _>>()?;
+        let (in_eics, eic_offsets) = group_by_source(n_inputs, eic_sources.into_iter());
+        #[cfg(not(feature = "par_couplings"))]
+        let (moving_eics, moving_ics, moving_eocs) =
+            moving_couplings(coupled, &in_eics, &eic_offsets);
+        let mut scheduler = Self {
+            times,
+            heap: (0..n).collect(),
+            pos: (0..n).collect(),
+            eic_targets,
+            ic_targets,
+            eic_entries,
+            ic_entries,
+            signaled: vec![false; n_inputs],
+            signaled_list: Vec::new(),
+            own_inputs: match coupled.direct {
+                true => own_inputs,
+                false => Vec::new(),
+            },
+            out_ics,
+            ic_offsets,
+            out_eocs,
+            eoc_offsets,
+            in_eics,
+            eic_offsets,
+            #[cfg(not(feature = "par_couplings"))]
+            moving_eics,
+            #[cfg(not(feature = "par_couplings"))]
+            moving_ics,
+            #[cfg(not(feature = "par_couplings"))]
+            moving_eocs,
+            imminent: Vec::new(),
+            active: vec![false; n],
+            active_list: Vec::new(),
+            rebuild: false,
+        };
+        scheduler.heapify();
+        Ok(scheduler)
+    }
+
+    /// Returns the minimum next simulation time of all the subcomponents.
+    #[inline]
+    pub(crate) fn t_next(&self) -> T {
+        match self.heap.first() {
+            Some(&i) => self.times[i],
+            None => T::INFINITY,
+        }
+    }
+
+    /// Updates the next simulation time of a subcomponent.
+    /// While many subcomponents are being updated (see [`Scheduler::take_active`]),
+    /// the heap is rebuilt at once in [`Scheduler::restore_active`] instead.
+    #[inline]
+    pub(crate) fn update(&mut self, i: usize, t_next: T) {
+        self.times[i] = t_next;
+        if !self.rebuild {
+            let pos = self.pos[i];
+            self.sift_up(pos);
+            self.sift_down(self.pos[i]);
+        }
+    }
+
+    /// Finds the subcomponents with a next simulation time less than or equal to `t`, sorted in ascending order.
+    /// They are marked as active, so they execute their transition in the current simulation cycle.
+    pub(crate) fn find_imminent(&mut self, t: T) -> &[usize] {
+        self.imminent.clear();
+        if self.t_next() <= t {
+            self.imminent.push(self.heap[0]);
+        }
+        // breadth-first traversal of the heap, pruning subtrees with a next time greater than t
+        let mut k = 0;
+        while k < self.imminent.len() {
+            let pos = self.pos[self.imminent[k]];
+            for child in [2 * pos + 1, 2 * pos + 2] {
+                if child < self.heap.len() && self.times[self.heap[child]] <= t {
+                    self.imminent.push(self.heap[child]);
+                }
+            }
+            k += 1;
+        }
+        if self.imminent.len() == self.heap.len() {
+            // all the subcomponents are imminent, so we do not need to sort them
+            self.imminent.clear();
+            self.imminent.extend(0..self.heap.len());
+        } else {
+            self.imminent.sort_unstable();
+        }
+        for k in 0..self.imminent.len() {
+            self.activate(self.imminent[k]);
+        }
+        &self.imminent
+    }
+
+    /// Returns the subcomponents found by the last call to [`Scheduler::find_imminent`].
+    #[inline]
+    pub(crate) fn imminent(&self) -> &[usize] {
+        &self.imminent
+    }
+
+    /// Keeps the `k`-th imminent subcomponent as the only imminent subcomponent.
+    /// It is used by Classic DEVS coupled models, which only execute one imminent subcomponent per cycle.
+    /// It must be called right after [`Scheduler::find_imminent`].
+    pub(crate) fn select_imminent(&mut self, k: usize) {
+        let selected = self.imminent[k];
+        for &i in self.imminent.iter() {
+            self.active[i] = false;
+        }
+        self.imminent.clear();
+        self.active_list.clear();
+        self.imminent.push(selected);
+        self.activate(selected);
+    }
+
+    /// Returns `true` if a large fraction of the subcomponents are imminent. In this case, iterating over all the couplings
+    /// is faster than iterating over the couplings of the imminent subcomponents.
+    #[inline]
+    pub(crate) fn is_dense(&self) -> bool {
+        is_dense(self.imminent.len(), self.heap.len())
+    }
+
+    /// Returns the indices of the EOCs and ICs whose source is an imminent subcomponent, sorted in ascending order.
+    pub(crate) fn imminent_couplings(&self) -> (Vec<usize>, Vec<usize>) {
+        let couplings = |out: &[usize], offsets: &[usize]| {
+            let mut res: Vec<_> = (self.imminent.iter())
+                .flat_map(|&i| &out[offsets[i]..offsets[i + 1]])
+                .copied()
+                .collect();
+            res.sort_unstable();
+            res
+        };
+        (
+            couplings(&self.out_eocs, &self.eoc_offsets),
+            couplings(&self.out_ics, &self.ic_offsets),
+        )
+    }
+
+    /// Returns the indices of the EICs whose source is one of the provided input ports of the coupled model,
+    /// sorted in ascending order.
+    pub(crate) fn input_couplings(&self, ports: impl Iterator<Item = usize>) -> Vec<usize> {
+        let mut res: Vec<_> = ports
+            .flat_map(|j| &self.in_eics[self.eic_offsets[j]..self.eic_offsets[j + 1]])
+            .copied()
+            .collect();
+        res.sort_unstable();
+        res
+    }
+
+    /// Marks the destination subcomponent of the IC with index `k` as active.
+    #[inline]
+    pub(crate) fn activate_ic(&mut self, k: usize) {
+        self.activate(self.ic_targets[k]);
+    }
+
+    /// Marks the destination subcomponent of the EIC with index `k` as active.
+    #[inline]
+    pub(crate) fn activate_eic(&mut self, k: usize) {
+        self.activate(self.eic_targets[k]);
+    }
+
+    /// Returns `true` if the EIC with index `k` can move messages, as its source port has no other coupling.
+    #[cfg(not(feature = "par_couplings"))]
+    #[inline]
+    pub(crate) fn moves_eic(&self, k: usize) -> bool {
+        self.moving_eics[k]
+    }
+
+    /// Returns `true` if the IC with index `k` can move messages, as its source port has no other coupling.
+    #[cfg(not(feature = "par_couplings"))]
+    #[inline]
+    pub(crate) fn moves_ic(&self, k: usize) -> bool {
+        self.moving_ics[k]
+    }
+
+    /// Returns `true` if the EOC with index `k` can move messages, as its source port has no other coupling.
+    #[cfg(not(feature = "par_couplings"))]
+    #[inline]
+    pub(crate) fn moves_eoc(&self, k: usize) -> bool {
+        self.moving_eocs[k]
+    }
+
+    /// Returns the direct-connected subcomponent that must be signaled when the IC with index `k`
+    /// propagates messages, and the key of the input port to signal.
+    #[inline]
+    pub(crate) fn ic_entry(&self, k: usize) -> Option<(usize, usize)> {
+        self.ic_entries[k]
+    }
+
+    /// Returns the direct-connected subcomponent that must be signaled when the EIC with index `k`
+    /// propagates messages, and the key of the input port to signal.
+    #[inline]
+    pub(crate) fn eic_entry(&self, k: usize) -> Option<(usize, usize)> {
+        self.eic_entries[k]
+    }
+
+    /// Records that messages were propagated to the atomic models connected to the input port with the provided key
+    /// of a direct-connected coupled model. It stands for the messages that the input port no longer receives.
+    pub(crate) fn signal(&mut self, key: usize) {
+        let k = self.own_inputs.partition_point(|&(k, _)| k < key);
+        let j = self.own_inputs[k].1;
+        if !self.signaled[j] {
+            self.signaled[j] = true;
+            self.signaled_list.push(j);
+        }
+    }
+
+    /// Takes the list of input ports that were signaled and resets their flags.
+    pub(crate) fn take_signaled(&mut self) -> Vec<usize> {
+        let signaled = std::mem::take(&mut self.signaled_list);
+        signaled.iter().for_each(|&j| self.signaled[j] = false);
+        signaled
+    }
+
+    /// Takes the list of active subcomponents and resets their active flags.
+    /// The list must be returned with [`Scheduler::restore_active`] after updating their next simulation times.
+    pub(crate) fn take_active(&mut self) -> Vec<usize> {
+        let active = std::mem::take(&mut self.active_list);
+        active.iter().for_each(|&i| self.active[i] = false);
+        self.rebuild = is_dense(active.len(), self.heap.len());
+        active
+    }
+
+    /// Returns the buffer of the list of active subcomponents and restores the heap property if needed.
+    pub(crate) fn restore_active(&mut self, mut active: Vec<usize>) {
+        active.clear();
+        self.active_list = active;
+        if std::mem::take(&mut self.rebuild) {
+            self.heapify();
+        }
+    }
+
+    /// Marks a subcomponent as active.
+    #[inline]
+    fn activate(&mut self, i: usize) {
+        if !self.active[i] {
+            self.active[i] = true;
+            self.active_list.push(i);
+        }
+    }
+
+    /// Restores the heap property of the whole heap.
+    fn heapify(&mut self) {
+        for pos in (0..self.heap.len() / 2).rev() {
+            self.sift_down(pos);
+        }
+    }
+
+    /// Returns `true` if the subcomponent at position `a` of the heap must precede the one at position `b`.
+    #[inline]
+    fn precedes(&self, a: usize, b: usize) -> bool {
+        let (i, j) = (self.heap[a], self.heap[b]);
+        self.times[i]
+            .total_cmp(&self.times[j])
+            .then(i.cmp(&j))
+            .is_lt()
+    }
+
+    /// Swaps two positions of the heap.
+    #[inline]
+    fn swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.pos[self.heap[a]] = a;
+        self.pos[self.heap[b]] = b;
+    }
+
+    /// Moves the subcomponent at position `pos` of the heap up until the heap property is restored.
+    fn sift_up(&mut self, mut pos: usize) {
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+            if !self.precedes(pos, parent) {
+                break;
+            }
+            self.swap(pos, parent);
+            pos = parent;
+        }
+    }
+
+    /// Moves the subcomponent at position `pos` of the heap down until the heap property is restored.
+    fn sift_down(&mut self, mut pos: usize) {
+        loop {
+            let (left, right) = (2 * pos + 1, 2 * pos + 2);
+            let mut first = pos;
+            if left < self.heap.len() && self.precedes(left, first) {
+                first = left;
+            }
+            if right < self.heap.len() && self.precedes(right, first) {
+                first = right;
+            }
+            if first == pos {
+                break;
+            }
+            self.swap(pos, first);
+            pos = first;
+        }
+    }
+}
+
+/// Returns `true` if `k` out of `n` subcomponents is a large fraction of them.
+#[inline]
+fn is_dense(k: usize, n: usize) -> bool {
+    4 * k > n
+}
+
+/// Groups the indices of a list of couplings by the index of their source subcomponent.
+/// It returns the grouped indices and the offset of the group of every subcomponent.
+fn group_by_source(n: usize, sources: impl Iterator<Item = usize>) -> (Vec<usize>, Vec<usize>) {
+    let sources: Vec<_> = sources.collect();
+    let mut offsets = vec![0; n + 1];
+    sources.iter().for_each(|&i| offsets[i + 1] += 1);
+    for i in 0..n {
+        offsets[i + 1] += offsets[i];
+    }
+    let mut next = offsets.clone();
+    let mut grouped = vec![0; sources.len()];
+    for (k, &i) in sources.iter().enumerate() {
+        grouped[next[i]] = k;
+        next[i] += 1;
+    }
+    (grouped, offsets)
+}
+
+/// Finds the EICs, ICs, and EOCs of a coupled model whose source port has no other coupling.
+/// Their messages can be moved to the destination port instead of cloned, as nobody reads them afterwards.
+/// EICs are grouped by source port in `in_eics` and `eic_offsets` (see [`Scheduler`]).
+#[cfg(not(feature = "par_couplings"))]
+fn moving_couplings<T: Time, C: Subcomponent<T>>(
+    coupled: &Coupled<T, C>,
+    in_eics: &[usize],
+    eic_offsets: &[usize],
+) -> (Vec<bool>, Vec<bool>, Vec<bool>) {
+    let mut moving_eics = vec![false; coupled.eics.len()];
+    if !coupled.is_move_safe() {
+        return (
+            moving_eics,
+            vec![false; coupled.ics.len()],
+            vec![false; coupled.eocs.len()],
+        );
+    }
+    for offsets in eic_offsets.windows(2) {
+        if offsets[1] - offsets[0] == 1 {
+            moving_eics[in_eics[offsets[0]]] = true;
+        }
+    }
+    // output ports of subcomponents may be the source of both ICs and EOCs
+    let mut n_couplings: HashMap<usize, usize> = HashMap::new();
+    for (_, port_from) in coupled.ics.iter().chain(&coupled.eocs) {
+        *n_couplings.entry(port_key(port_from)).or_default() += 1;
+    }
+    let moving = |couplings: &[Coupling]| {
+        (couplings.iter())
+            .map(|(_, port_from)| n_couplings[&port_key(port_from)] == 1)
+            .collect()
+    };
+    (moving_eics, moving(&coupled.ics), moving(&coupled.eocs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_couplings() {
+        let mut coupled = Coupled::new("coupled");
+        coupled.add_in_port::<usize>("a");
+        coupled.add_in_port::<usize>("b");
+        for name in ["x", "y"] {
+            let mut component = Coupled::new(name);
+            component.add_in_port::<usize>("in");
+            coupled.add_component(Box::new(component));
+        }
+        coupled.add_eic("b", "x", "in");
+        coupled.add_eic("a", "y", "in");
+        coupled.add_eic("b", "y", "in");
+        let scheduler = Scheduler::new(&coupled, vec![f64::INFINITY; 2]).unwrap();
+        assert_eq!(vec![1], scheduler.input_couplings([0].into_iter()));
+        assert_eq!(vec![0, 2], scheduler.input_couplings([1].into_iter()));
+        assert_eq!(vec![0, 1, 2], scheduler.input_couplings(0..2));
+        assert_eq!(vec![0, 1, 1], scheduler.eic_targets);
+    }
+
+    #[test]
+    fn test_foreign_ports() {
+        let mut coupled = Coupled::new("coupled");
+        coupled.add_in_port::<usize>("a");
+        let mut inner = Coupled::new("x");
+        inner.add_in_port::<usize>("in");
+        coupled.add_component(Box::new(inner));
+        let mut foreign = Coupled::<f64>::new("y");
+        foreign.add_in_port::<usize>("foreign");
+        // couplings are pushed directly, as coupling methods reject ports outside the model
+        let p_to = foreign.component.get_in_ports()[0].clone();
+        let p_from = coupled.component.get_in_ports()[0].clone();
+        coupled.eics.push((p_to, p_from));
+        assert_eq!(
+            Some(ModelingError::ForeignPort("foreign".into())),
+            Scheduler::new(&coupled, vec![f64::INFINITY]).err()
+        );
+    }
+
+    #[test]
+    fn test_heap() {
+        let coupled = Coupled::new("coupled");
+        let mut scheduler = Scheduler::new(&coupled, Vec::new()).unwrap();
+        assert_eq!(f64::INFINITY, scheduler.t_next());
+        // we build the heap manually, as the coupled model does not have subcomponents
+        let times = vec![5., 3., 3., f64::INFINITY, 1.];
+        let n = times.len();
+        scheduler = Scheduler {
+            times,
+            heap: (0..n).collect(),
+            pos: (0..n).collect(),
+            active: vec![false; n],
+            ..Scheduler::default()
+        };
+        scheduler.heapify();
+        assert_eq!(1., scheduler.t_next());
+        assert_eq!(&[4], scheduler.find_imminent(1.));
+        scheduler.update(4, 3.);
+        assert_eq!(&[1, 2, 4], scheduler.find_imminent(3.));
+        let active = scheduler.take_active();
+        assert_eq!(vec![4, 1, 2], active);
+        scheduler.update(1, 4.);
+        scheduler.update(2, 6.);
+        scheduler.update(4, 7.);
+        scheduler.restore_active(active);
+        assert_eq!(4., scheduler.t_next());
+        assert_eq!(&[0, 1], scheduler.find_imminent(5.));
+    }
+}
@@ -0,0 +1,57 @@
+use crate::modeling::Component;
+use crate::random::Rng;
+use crate::time::Time;
+use std::any::Any;
+
+/// Saved state of a model and its subcomponents between two simulation cycles.
+/// It is used by optimistic coordinators to roll simulations back (see [`super::Simulator::save_snapshot`]).
+pub struct Snapshot<T: Time = f64> {
+    /// Time of the last state transition of the component.
+    t_last: T,
+    /// Time of the next state transition of the component.
+    t_next: T,
+    /// Number of state transitions of the component.
+    n_transitions: usize,
+    /// Random number generator stream of the component.
+    rng: Rng,
+    /// State of the atomic model, as returned by [`crate::modeling::Atomic::save_state`].
+    state: Option<Box<dyn Any>>,
+    /// Snapshots of the subcomponents, in the same order as in the coupled model.
+    children: Vec<Snapshot<T>>,
+}
+
+impl<T: Time> Snapshot<T> {
+    /// Saves the simulation state of a component, the state of its model (if atomic),
+    /// and the snapshots of its subcomponents (if coupled).
+    pub(crate) fn new(
+        component: &Component<T>,
+        state: Option<Box<dyn Any>>,
+        children: Vec<Snapshot<T>>,
+    ) -> Self {
+        Self {
+            t_last: component.get_t_last(),
+            t_next: component.get_t_next(),
+            n_transitions: component.get_n_transitions(),
+            rng: component.get_rng().clone(),
+            state,
+            children,
+        }
+    }
+
+    /// Restores the simulation state of a component.
+    pub(crate) fn restore(&self, component: &mut Component<T>) {
+        component.set_sim_t(self.t_last, self.t_next);
+        component.set_n_transitions(self.n_transitions);
+        component.set_rng(self.rng.clone());
+    }
+
+    /// Returns the state of the atomic model (if any).
+    pub(crate) fn state(&self) -> Option<&dyn Any> {
+        self.state.as_deref()
+    }
+
+    /// Returns the snapshots of the subcomponents.
+    pub(crate) fn children(&self) -> &[Snapshot<T>] {
+        &self.children
+    }
+}
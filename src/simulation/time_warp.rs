@@ -0,0 +1,666 @@
+use super::cmb::{extract, Extractor, Link};
+use super::{attributed, Simulator, Snapshot};
+use crate::modeling::port::Port;
+use crate::modeling::Coupled;
+use crate::{DynRef, Shared};
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Function that builds the coupled model of a partition.
+type Factory = Box<dyn FnOnce() -> Coupled + Send>;
+
+/// Sender of messages to a partition. Messages are tagged with the index of their link.
+type Mailbox = Sender<(usize, Message)>;
+
+/// Number of simulation cycles that a partition executes before requesting a new GVT computation.
+const GVT_PERIOD: usize = 256;
+
+/// Time that an idle partition waits for new messages before requesting a new GVT computation.
+const IDLE_TIMEOUT: Duration = Duration::from_millis(1);
+
+/// Messages exchanged by partitions through links.
+enum Message {
+    /// Messages of a port sent at a given simulation time. Every message has a unique ID within its link.
+    Positive {
+        id: u64,
+        t: f64,
+        values: Box<dyn Any + Send>,
+    },
+    /// Anti-message. It cancels the positive message with the same ID.
+    Anti { id: u64, t: f64 },
+}
+
+/// Messages received from another partition.
+struct Input {
+    /// Index of the link.
+    link: usize,
+    /// ID of the message within its link.
+    id: u64,
+    /// Simulation time of the message.
+    t: f64,
+    /// Messages of the port (i.e., a boxed `Vec<T>`).
+    values: Box<dyn Any + Send>,
+}
+
+/// Statistics of a partition of a [`TimeWarpCoordinator`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeWarpStats {
+    /// Number of simulation cycles executed (including the ones that were rolled back).
+    pub cycles: usize,
+    /// Number of rollbacks.
+    pub rollbacks: usize,
+    /// Number of simulation cycles that were rolled back.
+    pub rolled_back: usize,
+    /// Number of anti-messages sent.
+    pub anti_messages: usize,
+}
+
+/// State of a [`Gvt`] round.
+struct GvtState {
+    /// Number of partitions that have arrived at the current round.
+    count: usize,
+    /// Round number. It increases every time all the partitions arrive.
+    round: u64,
+    /// Partial result of the current round.
+    partial: (bool, f64),
+    /// Result of the last round.
+    result: (bool, f64),
+    /// It is `true` if any partition panicked.
+    aborted: bool,
+}
+
+/// Shared state for computing the global virtual time (GVT) of a simulation.
+/// GVT computations stop all the partitions until there are no messages in transit.
+/// The GVT is then the minimum local time of all the partitions.
+struct Gvt {
+    /// Number of partitions.
+    n: usize,
+    /// It is `true` if any partition requested a new GVT computation.
+    requested: AtomicBool,
+    state: Mutex<GvtState>,
+    condvar: Condvar,
+}
+
+impl Gvt {
+    fn new(n: usize) -> Self {
+        Self {
+            n,
+            requested: AtomicBool::new(false),
+            state: Mutex::new(GvtState {
+                count: 0,
+                round: 0,
+                partial: (false, f64::INFINITY),
+                result: (false, f64::INFINITY),
+                aborted: false,
+            }),
+            condvar: Condvar::new(),
+        }
+    }
+
+    /// Waits until all the partitions arrive and returns the aggregated values
+    /// (i.e., if any partition sent messages and the minimum local time).
+    /// It returns [`None`] if any partition panicked.
+    fn reduce(&self, sent: bool, t: f64) -> Option<(bool, f64)> {
+        let mut state = self.state.lock().unwrap();
+        if state.aborted {
+            return None;
+        }
+        state.partial = (state.partial.0 || sent, state.partial.1.min(t));
+        state.count += 1;
+        if state.count == self.n {
+            // all the partitions are in the round, so no one can request a new computation
+            self.requested.store(false, Ordering::SeqCst);
+            state.result = state.partial;
+            state.partial = (false, f64::INFINITY);
+            state.count = 0;
+            state.round += 1;
+            self.condvar.notify_all();
+            return Some(state.result);
+        }
+        let round = state.round;
+        let state = (self.condvar)
+            .wait_while(state, |s| s.round == round && !s.aborted)
+            .unwrap();
+        (state.round != round).then_some(state.result)
+    }
+
+    /// Requests a new GVT computation.
+    fn request(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns `true` if any partition requested a new GVT computation.
+    fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    /// Aborts the simulation, waking up all the partitions waiting for a GVT computation.
+    fn abort(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.aborted = true;
+        self.condvar.notify_all();
+    }
+
+    /// Returns `true` if any partition panicked.
+    fn is_aborted(&self) -> bool {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).aborted
+    }
+}
+
+/// Aborts the simulation if the thread of a partition panics, so other partitions do not wait forever.
+struct AbortGuard(Arc<Gvt>);
+
+impl Drop for AbortGuard {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            self.0.abort();
+        }
+    }
+}
+
+/// Coordinator for optimistic parallel simulations following the Time Warp protocol.
+/// Every partition is a coupled model simulated by its own thread.
+/// Partitions exchange messages through links that connect output ports of one partition
+/// with input ports of another partition.
+///
+/// Partitions do not wait for other partitions: they simulate their models as fast as possible,
+/// saving their state before every simulation cycle. When a partition receives a message in its past
+/// (i.e., a straggler), it rolls back to the state before the message and cancels the messages it sent
+/// since then with anti-messages. Thus, unlike [`super::CmbCoordinator`], it does not rely on the lookahead
+/// of the models, which is useful for models with poor lookahead. Partitions periodically compute the
+/// global virtual time (GVT) to discard old states and detect the end of the simulation.
+///
+/// All the atomic models of the partitions must support state saving (see [`crate::modeling::Atomic::save_state`]).
+/// Side effects of the transition functions (e.g., shared buffers or statistics accumulators) are not
+/// rolled back, so models should keep their results in their state and publish them in
+/// [`crate::modeling::Atomic::stop`]. Links must not form cycles of messages with the same simulation time.
+#[derive(Default)]
+pub struct TimeWarpCoordinator {
+    /// Factories of the partitions.
+    partitions: Vec<Factory>,
+    /// Links between partitions.
+    links: Vec<Link>,
+}
+
+impl TimeWarpCoordinator {
+    /// Creates a new coordinator without partitions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a new partition and returns its index. The coupled model of the partition
+    /// is created by `factory` in the thread that simulates the partition.
+    pub fn add_partition(&mut self, factory: impl FnOnce() -> Coupled + Send + 'static) -> usize {
+        self.partitions.push(Box::new(factory));
+        self.partitions.len() - 1
+    }
+
+    /// Adds a new link from the output port `port_from` of the partition `from`
+    /// to the input port `port_to` of the partition `to`. Both ports must be of type `T`.
+    /// It panics if any of the partitions does not exist or if the link connects a partition with itself.
+    /// Ports are checked when partitions are created.
+    pub fn add_link<T: DynRef + Clone + Send>(
+        &mut self,
+        from: usize,
+        port_from: &str,
+        to: usize,
+        port_to: &str,
+    ) {
+        if from >= self.partitions.len() || to >= self.partitions.len() {
+            panic!("partition does not exist")
+        }
+        if from == to {
+            panic!("links must connect different partitions")
+        }
+        self.links.push(Link {
+            from,
+            port_from: port_from.to_string(),
+            to,
+            port_to: port_to.to_string(),
+            extract: extract::<T>,
+        });
+    }
+
+    /// Runs a simulation for a given period of time. It blocks until all the partitions finish
+    /// and returns the statistics of every partition. If the simulation of any partition panics,
+    /// this method panics too.
+    pub fn simulate(self, t_end: f64) -> Vec<TimeWarpStats> {
+        let n = self.partitions.len();
+        let gvt = Arc::new(Gvt::new(n));
+        let (senders, receivers): (Vec<_>, Vec<_>) = (0..n).map(|_| channel()).unzip();
+        let handles: Vec<_> = self
+            .partitions
+            .into_iter()
+            .zip(receivers)
+            .enumerate()
+            .map(|(i, (factory, receiver))| {
+                let outgoing: Vec<_> = (self.links.iter().enumerate())
+                    .filter(|(_, l)| l.from == i)
+                    .map(|(j, l)| (j, l.port_from.clone(), l.extract, senders[l.to].clone()))
+                    .collect();
+                let incoming: Vec<_> = (self.links.iter().enumerate())
+                    .filter(|(_, l)| l.to == i)
+                    .map(|(j, l)| (j, l.port_to.clone()))
+                    .collect();
+                let (gvt, mailbox) = (gvt.clone(), senders[i].clone());
+                thread::spawn(move || {
+                    let _guard = AbortGuard(gvt.clone());
+                    let channel = (mailbox, receiver);
+                    Partition::new(factory(), outgoing, incoming, channel, gvt).simulate(t_end)
+                })
+            })
+            .collect();
+        drop(senders);
+        let mut stats = Vec::with_capacity(n);
+        for handle in handles {
+            match handle.join() {
+                Ok(s) => stats.push(s),
+                Err(e) => std::panic::resume_unwind(e),
+            }
+        }
+        stats
+    }
+}
+
+/// Partition of a [`TimeWarpCoordinator`].
+struct Partition {
+    /// Coupled model of the partition.
+    model: Coupled,
+    /// Time of the next internal event of the model.
+    t_next: f64,
+    /// Outgoing links: link index, source port, message extractor, and destination channel.
+    outgoing: Vec<(usize, Shared<dyn Port>, Extractor, Mailbox)>,
+    /// Incoming links: link index and destination port.
+    incoming: Vec<(usize, Shared<dyn Port>)>,
+    /// Channel for receiving messages from other partitions. The partition keeps its own
+    /// sender so the channel is never disconnected while the partition is alive.
+    channel: (Mailbox, Receiver<(usize, Message)>),
+    /// Shared state for GVT computations.
+    gvt: Arc<Gvt>,
+    /// ID of the next message sent by the partition.
+    next_id: u64,
+    /// Messages received but not processed yet.
+    pending: Vec<Input>,
+    /// Messages processed since the last GVT computation.
+    processed: Vec<Input>,
+    /// Link index, ID, and simulation time of the messages sent since the last GVT computation.
+    sent: Vec<(usize, u64, f64)>,
+    /// Simulation time and saved state of the model before every simulation cycle since the last GVT computation.
+    snapshots: Vec<(f64, Snapshot)>,
+    /// Statistics of the partition.
+    stats: TimeWarpStats,
+}
+
+impl Partition {
+    fn new(
+        model: Coupled,
+        outgoing: Vec<(usize, String, Extractor, Mailbox)>,
+        incoming: Vec<(usize, String)>,
+        channel: (Mailbox, Receiver<(usize, Message)>),
+        gvt: Arc<Gvt>,
+    ) -> Self {
+        let outgoing = outgoing
+            .into_iter()
+            .map(|(i, name, extract, sender)| {
+                let port = model
+                    .get_component()
+                    .get_out_port(&name)
+                    .expect("port_from does not exist");
+                // Safety: coordinator reading messages before the simulation
+                if extract(unsafe { port.get_values() }).is_none() {
+                    panic!("port_from is not compatible with the link")
+                }
+                (i, port, extract, sender)
+            })
+            .collect();
+        let incoming = incoming
+            .into_iter()
+            .map(|(i, name)| {
+                let port = model
+                    .get_component()
+                    .get_in_port(&name)
+                    .expect("port_to does not exist");
+                (i, port)
+            })
+            .collect();
+        Self {
+            model,
+            t_next: f64::INFINITY,
+            outgoing,
+            incoming,
+            channel,
+            gvt,
+            next_id: 0,
+            pending: Vec::new(),
+            processed: Vec::new(),
+            sent: Vec::new(),
+            snapshots: Vec::new(),
+            stats: TimeWarpStats::default(),
+        }
+    }
+
+    /// Returns the time of the next simulation cycle of the partition.
+    fn t_local(&self) -> f64 {
+        (self.pending.iter())
+            .map(|input| input.t)
+            .fold(self.t_next, f64::min)
+    }
+
+    /// Processes a message received from another partition. Stragglers trigger a rollback.
+    fn receive(&mut self, (link, message): (usize, Message)) {
+        match message {
+            Message::Positive { id, t, values } => {
+                self.rollback(t);
+                self.pending.push(Input {
+                    link,
+                    id,
+                    t,
+                    values,
+                });
+            }
+            Message::Anti { id, t } => {
+                if self.processed.iter().any(|i| i.link == link && i.id == id) {
+                    self.rollback(t);
+                }
+                self.pending.retain(|i| i.link != link || i.id != id);
+            }
+        }
+    }
+
+    /// Rolls the model back to its state before the first simulation cycle at time `t` or later.
+    /// Messages processed since then are processed again, and messages sent since then are cancelled.
+    fn rollback(&mut self, t: f64) {
+        let k = self.snapshots.partition_point(|(tc, _)| *tc < t);
+        if k == self.snapshots.len() {
+            return;
+        }
+        self.model.restore_snapshot(&self.snapshots[k].1);
+        self.t_next = self.model.get_t_next();
+        self.stats.rollbacks += 1;
+        self.stats.rolled_back += self.snapshots.len() - k;
+        self.snapshots.truncate(k);
+
+        let (processed, pending) = self.processed.drain(..).partition(|i| i.t < t);
+        self.processed = processed;
+        self.pending.extend::<Vec<_>>(pending);
+
+        let (kept, cancelled): (Vec<_>, Vec<_>) = self.sent.drain(..).partition(|s| s.2 < t);
+        self.sent = kept;
+        for (link, id, t) in cancelled {
+            let (_, _, _, sender) = self.outgoing.iter().find(|o| o.0 == link).unwrap();
+            let _ = sender.send((link, Message::Anti { id, t }));
+            self.stats.anti_messages += 1;
+        }
+    }
+
+    /// Executes a simulation cycle at time `t`, saving the state of the model before.
+    fn cycle(&mut self, t: f64) {
+        let snapshot = self.model.save_snapshot().unwrap();
+        self.snapshots.push((t, snapshot));
+        self.stats.cycles += 1;
+
+        attributed(|| self.model.collection(t));
+        for (link, port, extract, sender) in &self.outgoing {
+            // Safety: coordinator reading messages between simulation phases
+            if !unsafe { port.is_empty() } {
+                // Safety: coordinator reading messages between simulation phases
+                let values = extract(unsafe { port.get_values() }).unwrap();
+                let id = self.next_id;
+                self.next_id += 1;
+                self.sent.push((*link, id, t));
+                let _ = sender.send((*link, Message::Positive { id, t, values }));
+            }
+        }
+
+        // messages are injected in a deterministic order, regardless of their arrival order
+        let (mut inputs, pending) = self.pending.drain(..).partition::<Vec<_>, _>(|i| i.t == t);
+        self.pending = pending;
+        inputs.sort_by_key(|i| (i.link, i.id));
+        for input in inputs {
+            let (_, port) = self
+                .incoming
+                .iter()
+                .find(|(i, _)| *i == input.link)
+                .unwrap();
+            // Safety: coordinator injecting messages between simulation phases
+            if !unsafe { port.inject(input.values.as_ref()) } {
+                panic!("port_to is not compatible with the link")
+            }
+            self.processed.push(input);
+        }
+        self.t_next = attributed(|| self.model.transition(t));
+    }
+
+    /// Participates in a GVT computation and returns the new GVT.
+    /// It returns [`None`] if any partition panicked.
+    fn compute_gvt(&mut self) -> Option<f64> {
+        // messages may have been sent before the computation started
+        let mut sent = true;
+        loop {
+            let (any, gvt) = self.gvt.reduce(sent, self.t_local())?;
+            if !any {
+                return Some(gvt);
+            }
+            let n_sent = (self.next_id, self.stats.anti_messages);
+            while let Ok(message) = self.channel.1.try_recv() {
+                self.receive(message);
+            }
+            sent = n_sent != (self.next_id, self.stats.anti_messages);
+        }
+    }
+
+    /// Discards the saved states and messages that are older than the GVT, as they cannot be rolled back.
+    fn collect_fossils(&mut self, gvt: f64) {
+        let k = self.snapshots.partition_point(|(tc, _)| *tc < gvt);
+        self.snapshots.drain(..k);
+        self.processed.retain(|i| i.t >= gvt);
+        self.sent.retain(|s| s.2 >= gvt);
+    }
+
+    /// Simulates the partition until `t_end` and returns its statistics.
+    fn simulate(mut self, t_end: f64) -> TimeWarpStats {
+        let path = self.model.get_name().to_string();
+        self.model.set_path(&path);
+        self.t_next = self.model.start(0.);
+        if self.model.save_snapshot().is_none() {
+            panic!("atomic models must implement Atomic::save_state")
+        }
+        let mut cycles = 0;
+        loop {
+            if self.gvt.is_aborted() {
+                return self.stats;
+            }
+            if self.gvt.is_requested() {
+                let Some(gvt) = self.compute_gvt() else {
+                    return self.stats;
+                };
+                if gvt >= t_end {
+                    break;
+                }
+                self.collect_fossils(gvt);
+                cycles = 0;
+                continue;
+            }
+            while let Ok(message) = self.channel.1.try_recv() {
+                self.receive(message);
+            }
+            let t = self.t_local();
+            if t < t_end {
+                self.cycle(t);
+                cycles += 1;
+                if cycles >= GVT_PERIOD {
+                    self.gvt.request();
+                }
+                continue;
+            }
+            // Nothing to simulate: we wait for new messages or request a new GVT computation
+            match self.channel.1.recv_timeout(IDLE_TIMEOUT) {
+                Ok(message) => self.receive(message),
+                Err(RecvTimeoutError::Timeout) => self.gvt.request(),
+                Err(RecvTimeoutError::Disconnected) => unreachable!(),
+            }
+        }
+        self.model.stop(self.t_next);
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::{Atomic, Component, InPort, OutPort};
+    use crate::simulation::RootCoordinator;
+
+    type Received = Arc<Mutex<Vec<(f64, usize)>>>;
+
+    /// Atomic model that periodically sends a counter. Received messages are added to the counter.
+    /// It keeps the received messages in its state and publishes them when the simulation stops.
+    struct Node {
+        component: Component,
+        input: InPort<usize>,
+        output: OutPort<usize>,
+        period: f64,
+        sleep: Duration,
+        sigma: f64,
+        count: usize,
+        history: Vec<(f64, usize)>,
+        received: Received,
+    }
+
+    impl Node {
+        fn new(name: &str, period: f64, sleep: u64, received: Received) -> Self {
+            let mut component = Component::new(name);
+            let input = component.add_in_port("input");
+            let output = component.add_out_port("output");
+            Self {
+                component,
+                input,
+                output,
+                period,
+                sleep: Duration::from_millis(sleep),
+                sigma: period,
+                count: 0,
+                history: Vec::new(),
+                received,
+            }
+        }
+
+        fn partition(name: &str, period: f64, sleep: u64, received: Received) -> Coupled {
+            let mut coupled = Coupled::new(name);
+            coupled.add_in_port::<usize>("input");
+            coupled.add_out_port::<usize>("output");
+            coupled.add_component(Box::new(Node::new("node", period, sleep, received)));
+            coupled.add_eic("input", "node", "input");
+            coupled.add_eoc("node", "output", "output");
+            coupled
+        }
+    }
+
+    impl Atomic for Node {
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+
+        fn stop(&mut self) {
+            self.received.lock().unwrap().extend(&self.history);
+        }
+
+        fn lambda(&self) {
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.output.add_value(self.count + 1) };
+        }
+
+        fn delta_int(&mut self) {
+            self.count += 1;
+            self.sigma = self.period;
+            thread::sleep(self.sleep);
+        }
+
+        fn delta_ext(&mut self, e: f64) {
+            let t = self.component.get_t_last() + e;
+            // Safety: reading messages on atomic model's input port at delta_ext
+            for &value in unsafe { self.input.get_values() }.iter() {
+                self.history.push((t, value));
+                self.count += value;
+            }
+            self.sigma -= e;
+        }
+
+        fn ta(&self) -> f64 {
+            self.sigma
+        }
+
+        fn save_state(&self) -> Option<Box<dyn Any>> {
+            Some(Box::new((self.sigma, self.count, self.history.clone())))
+        }
+
+        fn restore_state(&mut self, state: &dyn Any) {
+            let (sigma, count, history) = state.downcast_ref::<(f64, usize, Vec<_>)>().unwrap();
+            (self.sigma, self.count, self.history) = (*sigma, *count, history.clone());
+        }
+    }
+
+    /// Parameters of the nodes of the chain: name, period, and sleep time (in milliseconds).
+    const CHAIN: [(&str, f64, u64); 3] = [("source", 1., 2), ("middle", 0.3, 0), ("sink", 0.7, 0)];
+
+    /// Simulates the chain of nodes sequentially.
+    fn sequential(t_end: f64) -> Vec<Received> {
+        let mut coupled = Coupled::new("chain");
+        let mut received = Vec::new();
+        for (name, period, sleep) in CHAIN {
+            let r = Arc::new(Mutex::new(Vec::new()));
+            coupled.add_component(Box::new(Node::partition(name, period, sleep, r.clone())));
+            received.push(r);
+        }
+        coupled.add_ic("source", "output", "middle", "input");
+        coupled.add_ic("middle", "output", "sink", "input");
+        RootCoordinator::new(coupled).simulate(t_end);
+        received
+    }
+
+    #[test]
+    fn test_chain() {
+        let t_end = 10.;
+        let expected = sequential(t_end);
+        let mut coordinator = TimeWarpCoordinator::new();
+        let mut received = Vec::new();
+        for (name, period, sleep) in CHAIN {
+            let r = Arc::new(Mutex::new(Vec::new()));
+            received.push(r.clone());
+            coordinator.add_partition(move || Node::partition(name, period, sleep, r));
+        }
+        coordinator.add_link::<usize>(0, "output", 1, "input");
+        coordinator.add_link::<usize>(1, "output", 2, "input");
+        let stats = coordinator.simulate(t_end);
+
+        assert!(!expected[2].lock().unwrap().is_empty());
+        for (expected, received) in expected.iter().zip(&received) {
+            assert_eq!(*expected.lock().unwrap(), *received.lock().unwrap());
+        }
+        // the middle node runs ahead of the slow source, so it must roll back
+        assert!(stats[1].rollbacks > 0);
+        assert!(stats[1].cycles - stats[1].rolled_back >= (t_end / 0.3) as usize);
+    }
+
+    #[test]
+    #[should_panic(expected = "save_state")]
+    fn test_no_state_saving() {
+        let mut coordinator = TimeWarpCoordinator::new();
+        coordinator.add_partition(|| {
+            let mut coupled = Coupled::new("a");
+            coupled.add_component(Box::new(crate::lib::Delay::<usize>::new("delay", 1.)));
+            coupled
+        });
+        coordinator.add_partition(|| Coupled::new("b"));
+        coordinator.simulate(10.);
+    }
+}
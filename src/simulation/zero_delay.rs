@@ -0,0 +1,107 @@
+use super::Simulator;
+use crate::time::Time;
+
+/// Default maximum number of consecutive simulation cycles at the same simulation time.
+pub(super) const DEFAULT_LIMIT: usize = 100_000;
+/// Maximum number of simulation cycles that are observed after reaching the limit
+/// to find out which components are involved in the zero-delay loop.
+const DIAGNOSTIC_CYCLES: usize = 100;
+
+/// Guard against illegitimate models, i.e., models that execute an infinite number of state transitions
+/// without advancing the simulation time (e.g., a cycle of components with zero time advance).
+/// It counts the consecutive simulation cycles at the same simulation time. When the count reaches the limit,
+/// it records the number of transitions of every atomic model, runs a few more cycles, and panics
+/// with the paths of the atomic models that kept transitioning.
+pub(super) struct ZeroDelayGuard<T> {
+    /// Maximum number of consecutive simulation cycles at the same simulation time.
+    /// If [`None`], the guard is disabled.
+    limit: Option<usize>,
+    /// Time of the last simulation cycle.
+    t: T,
+    /// Number of consecutive simulation cycles at time `t`.
+    cycles: usize,
+    /// Number of transitions of every atomic model when the limit was reached.
+    snapshot: Vec<(String, usize)>,
+}
+
+impl<T: Time> Default for ZeroDelayGuard<T> {
+    fn default() -> Self {
+        Self {
+            limit: Some(DEFAULT_LIMIT),
+            t: T::INFINITY,
+            cycles: 0,
+            snapshot: Vec::new(),
+        }
+    }
+}
+
+impl<T: Time> ZeroDelayGuard<T> {
+    /// Sets the maximum number of consecutive simulation cycles at the same simulation time.
+    pub(super) fn set_limit(&mut self, limit: Option<usize>) {
+        self.limit = limit;
+    }
+
+    /// Resets the guard at the beginning of a simulation.
+    pub(super) fn reset(&mut self) {
+        self.t = T::INFINITY;
+        self.cycles = 0;
+        self.snapshot.clear();
+    }
+
+    /// Counts a simulation cycle at time `t`. It panics if the model is in a zero-delay loop.
+    #[inline]
+    pub(super) fn check(&mut self, model: &dyn Simulator<T>, t: T) {
+        let Some(limit) = self.limit else {
+            return;
+        };
+        if t != self.t {
+            self.t = t;
+            self.cycles = 0;
+            self.snapshot.clear();
+        }
+        self.cycles += 1;
+        if self.cycles == limit {
+            transitions(model, &mut self.snapshot);
+        } else if self.cycles == limit + limit.min(DIAGNOSTIC_CYCLES) {
+            self.diagnose(model);
+        }
+    }
+
+    /// Panics with the atomic models that transitioned since the limit was reached.
+    #[cold]
+    fn diagnose(&self, model: &dyn Simulator<T>) -> ! {
+        let mut current = Vec::with_capacity(self.snapshot.len());
+        transitions(model, &mut current);
+        let mut involved: Vec<_> = (current.iter().zip(&self.snapshot))
+            .filter(|((_, n), (_, n_old))| n > n_old)
+            .map(|((path, n), (_, n_old))| (path.as_str(), n - n_old))
+            .collect();
+        involved.sort_by(|(p1, n1), (p2, n2)| n2.cmp(n1).then(p1.cmp(p2)));
+        let components: Vec<_> = (involved.iter())
+            .map(|(path, n)| format!("{path} ({n} transitions)"))
+            .collect();
+        panic!(
+            "zero-delay loop detected: more than {} simulation cycles at time {:?}. \
+            Components involved in the last {} cycles: {}",
+            self.limit.unwrap(),
+            self.t,
+            self.cycles - self.limit.unwrap(),
+            components.join(", ")
+        );
+    }
+}
+
+/// Appends the path and number of transitions of all the atomic models of a model to `res`.
+fn transitions<T: Time>(model: &dyn Simulator<T>, res: &mut Vec<(String, usize)>) {
+    let components = model.get_components();
+    if components.is_empty() {
+        let component = model.get_component();
+        res.push((
+            component.get_path().to_string(),
+            component.get_n_transitions(),
+        ));
+    }
+    for component in components {
+        transitions(component, res);
+    }
+}
@@ -0,0 +1,641 @@
+//! Compiler of hierarchical state machines (statecharts) to DEVS models.
+//! Declare the regions, states, and transitions of a [`Statechart`], and compile it into a single
+//! [`StatechartAtomic`] with [`Statechart::build`] or into a coupled model with one atomic model
+//! per region with [`Statechart::build_coupled`]. Both models behave the same way.
+//!
+//! The semantics of the statecharts are as follows:
+//!
+//! - A statechart has one or more orthogonal regions, which evolve concurrently and independently.
+//!   Every region has exactly one active top-level state. The first state declared in a region is its initial state.
+//! - States may contain child states. When a composite state is active, exactly one of its children is active,
+//!   and entering a composite state enters its first child (recursively).
+//! - Event transitions fire when the statechart receives a given message through its `input` port.
+//!   If several active states of a region react to the same message, the innermost one prevails.
+//!   Messages without a matching transition are ignored.
+//! - Timed transitions fire after their source state has been active for a given time.
+//!   The timer of a state restarts every time it is entered, but transitions between its descendants do not reset it.
+//!   If several timed transitions of a region are due at the same time, the innermost one prevails.
+//! - Transitions exit every state from the innermost active state up to the least common ancestor of the source
+//!   and the target states (the source state is always exited), and then enter the target state and its initial descendants.
+//! - Transitions may send a message through the `output` port. Messages of timed transitions are sent right before
+//!   the transition, while messages of event transitions are sent right after it (i.e., with no delay).
+
+use crate::modeling::{Atomic, Component, Coupled, InPort, OutPort};
+use crate::simulation::Simulator;
+use crate::time::Time;
+use crate::DynRef;
+use std::collections::HashMap;
+use std::fmt::{self, Debug};
+
+/// Error when compiling a [`Statechart`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatechartError {
+    /// The statechart has no regions.
+    NoRegions,
+    /// Two regions or states share the same name.
+    DuplicateName(String),
+    /// A state has a parent that is neither a region nor a previously declared state.
+    UnknownParent(String),
+    /// A transition refers to a state that does not exist.
+    UnknownState(String),
+    /// A region has no states.
+    EmptyRegion(String),
+    /// A transition connects states of different regions. It contains the source and target states.
+    CrossRegion(String, String),
+    /// A timed transition from the given state has a negative delay.
+    NegativeDelay(String),
+}
+
+impl fmt::Display for StatechartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoRegions => write!(f, "statechart has no regions"),
+            Self::DuplicateName(name) => write!(f, "name {name} is declared more than once"),
+            Self::UnknownParent(name) => write!(f, "parent {name} is not a region or a state"),
+            Self::UnknownState(name) => write!(f, "state {name} does not exist"),
+            Self::EmptyRegion(name) => write!(f, "region {name} has no states"),
+            Self::CrossRegion(from, to) => {
+                write!(f, "transition from {from} to {to} crosses regions")
+            }
+            Self::NegativeDelay(name) => {
+                write!(f, "timed transition from state {name} has a negative delay")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StatechartError {}
+
+/// Trigger of a transition.
+#[derive(Debug, Clone)]
+enum Trigger<I, T> {
+    /// Reception of a message.
+    Event(I),
+    /// Time spent in the source state.
+    After(T),
+}
+
+/// Transition of a statechart.
+#[derive(Debug, Clone)]
+struct Transition<S, I, O, T> {
+    /// Source state.
+    from: S,
+    /// Trigger of the transition.
+    trigger: Trigger<I, T>,
+    /// Target state.
+    to: S,
+    /// Message sent through the `output` port, if any.
+    output: Option<O>,
+}
+
+/// State of a compiled statechart.
+#[derive(Debug, Clone)]
+struct Node {
+    /// Name of the state.
+    name: String,
+    /// Index of the parent state, if any.
+    parent: Option<usize>,
+    /// Index of the region of the state.
+    region: usize,
+    /// Index of the initial child state, if any.
+    initial: Option<usize>,
+    /// Indices of the transitions from the state.
+    transitions: Vec<usize>,
+}
+
+/// Compiled statechart shared by its atomic models.
+#[derive(Debug, Clone)]
+struct Chart<I, O, T> {
+    /// States of the statechart.
+    states: Vec<Node>,
+    /// Name and initial state of every region.
+    regions: Vec<(String, usize)>,
+    /// Transitions of the statechart.
+    transitions: Vec<Transition<usize, I, O, T>>,
+}
+
+impl<I, O, T: Time> Chart<I, O, T> {
+    /// Returns the path from the top-level state of its region to a state.
+    fn path(&self, mut state: usize) -> Vec<usize> {
+        let mut path = vec![state];
+        while let Some(parent) = self.states[state].parent {
+            path.push(parent);
+            state = parent;
+        }
+        path.reverse();
+        path
+    }
+
+    /// Executes a transition in a configuration at time `t`. See the module documentation for details.
+    fn fire(&self, active: &mut Vec<(usize, T)>, transition: usize, t: T) {
+        let Transition { from, to, .. } = self.transitions[transition];
+        let source = active.iter().position(|(s, _)| *s == from).unwrap();
+        let target = self.path(to);
+        let common = (active[..source].iter().zip(&target))
+            .take_while(|((a, _), b)| a == *b)
+            .count();
+        active.truncate(common);
+        active.extend(target[common..].iter().map(|s| (*s, t)));
+        self.enter_initial(active, t);
+    }
+
+    /// Enters the initial descendants of the innermost state of a configuration at time `t`.
+    fn enter_initial(&self, active: &mut Vec<(usize, T)>, t: T) {
+        while let Some(child) = active.last().and_then(|(s, _)| self.states[*s].initial) {
+            active.push((child, t));
+        }
+    }
+
+    /// Returns the time and index of the next timed transition of a configuration, if any.
+    fn next_timeout(&self, active: &[(usize, T)]) -> Option<(T, usize)> {
+        let mut next: Option<(T, usize)> = None;
+        for (state, entry) in active {
+            let mut timeout: Option<(T, usize)> = None;
+            for &i in &self.states[*state].transitions {
+                if let Trigger::After(delay) = self.transitions[i].trigger {
+                    let t = *entry + delay;
+                    // the first transition of a state prevails in case of a tie
+                    if timeout.is_none_or(|(t_next, _)| t < t_next) {
+                        timeout = Some((t, i));
+                    }
+                }
+            }
+            // inner states prevail in case of a tie
+            if let Some((t, i)) = timeout {
+                if next.is_none_or(|(t_next, _)| t <= t_next) {
+                    next = Some((t, i));
+                }
+            }
+        }
+        next
+    }
+}
+
+/// Builder of hierarchical state machines. States are identified by their names,
+/// and messages of type `I` and `O` are received and sent through the `input` and `output` ports.
+/// See the [module documentation](self) for the semantics of the compiled models.
+#[derive(Debug, Clone)]
+pub struct Statechart<I, O, T: Time = f64> {
+    /// Name of the statechart.
+    name: String,
+    /// Names of the regions.
+    regions: Vec<String>,
+    /// Names of the states and their parents.
+    states: Vec<(String, String)>,
+    /// Transitions of the statechart.
+    transitions: Vec<Transition<String, I, O, T>>,
+}
+
+impl<I, O, T> Statechart<I, O, T>
+where
+    I: DynRef + Clone + PartialEq,
+    O: DynRef + Clone,
+    T: Time,
+{
+    /// Creates a new statechart without regions.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            regions: Vec::new(),
+            states: Vec::new(),
+            transitions: Vec::new(),
+        }
+    }
+
+    /// Adds an orthogonal region.
+    pub fn region(mut self, name: &str) -> Self {
+        self.regions.push(name.to_string());
+        self
+    }
+
+    /// Adds a state to a region or as a child of a previously declared state.
+    /// The first state of every region or composite state is its initial state.
+    pub fn state(mut self, name: &str, parent: &str) -> Self {
+        self.states.push((name.to_string(), parent.to_string()));
+        self
+    }
+
+    /// Adds a transition from state `from` to state `to` when the statechart receives `event` while in `from`.
+    /// If `output` is not [`None`], it is sent right after the transition.
+    /// If there are several transitions for the same state and event, the first one prevails.
+    pub fn on(mut self, from: &str, event: I, to: &str, output: Option<O>) -> Self {
+        self.transitions.push(Transition {
+            from: from.to_string(),
+            trigger: Trigger::Event(event),
+            to: to.to_string(),
+            output,
+        });
+        self
+    }
+
+    /// Adds a timed transition from state `from` to state `to` after spending `delay` in `from`.
+    /// If `output` is not [`None`], it is sent right before the transition.
+    /// If there are several timed transitions with the same delay for the same state, the first one prevails.
+    pub fn after(mut self, from: &str, delay: T, to: &str, output: Option<O>) -> Self {
+        self.transitions.push(Transition {
+            from: from.to_string(),
+            trigger: Trigger::After(delay),
+            to: to.to_string(),
+            output,
+        });
+        self
+    }
+
+    /// Compiles the statechart into a single atomic model with the name of the statechart.
+    pub fn build(self) -> Result<StatechartAtomic<I, O, T>, StatechartError> {
+        let name = self.name.clone();
+        let chart = self.compile()?;
+        let regions = (0..chart.regions.len()).collect();
+        Ok(StatechartAtomic::new(&name, chart, regions))
+    }
+
+    /// Compiles the statechart into a coupled model with the name of the statechart.
+    /// Every region is simulated by a [`StatechartAtomic`] with the name of the region.
+    /// The `input` and `output` ports of the coupled model are connected to the ports of every atomic model.
+    pub fn build_coupled(self) -> Result<Coupled<T>, StatechartError>
+    where
+        StatechartAtomic<I, O, T>: Simulator<T>,
+    {
+        let mut coupled = Coupled::new(&self.name);
+        coupled.add_in_port::<I>("input");
+        coupled.add_out_port::<O>("output");
+        let chart = self.compile()?;
+        for (i, (region, _)) in chart.regions.iter().enumerate() {
+            let atomic = StatechartAtomic::new(region, chart.clone(), vec![i]);
+            coupled.add_component(Box::new(atomic));
+            coupled.add_eic("input", region, "input");
+            coupled.add_eoc(region, "output", "output");
+        }
+        Ok(coupled)
+    }
+
+    /// Resolves the names of the statechart and checks that it is well-formed.
+    fn compile(self) -> Result<Chart<I, O, T>, StatechartError> {
+        if self.regions.is_empty() {
+            return Err(StatechartError::NoRegions);
+        }
+        let mut regions = HashMap::new();
+        for (i, region) in self.regions.iter().enumerate() {
+            if regions.insert(region.as_str(), i).is_some() {
+                return Err(StatechartError::DuplicateName(region.clone()));
+            }
+        }
+        let mut initials = vec![None; self.regions.len()];
+        let mut indices: HashMap<&str, usize> = HashMap::new();
+        let mut states: Vec<Node> = Vec::new();
+        for (i, (name, parent)) in self.states.iter().enumerate() {
+            if regions.contains_key(name.as_str()) || indices.contains_key(name.as_str()) {
+                return Err(StatechartError::DuplicateName(name.clone()));
+            }
+            let (parent, region) =
+                match (indices.get(parent.as_str()), regions.get(parent.as_str())) {
+                    (Some(&p), _) => {
+                        states[p].initial.get_or_insert(i);
+                        (Some(p), states[p].region)
+                    }
+                    (None, Some(&r)) => {
+                        initials[r].get_or_insert(i);
+                        (None, r)
+                    }
+                    (None, None) => return Err(StatechartError::UnknownParent(parent.clone())),
+                };
+            indices.insert(name.as_str(), i);
+            states.push(Node {
+                name: name.clone(),
+                parent,
+                region,
+                initial: None,
+                transitions: Vec::new(),
+            });
+        }
+        let regions = (self.regions.iter().zip(initials))
+            .map(|(name, initial)| match initial {
+                Some(initial) => Ok((name.clone(), initial)),
+                None => Err(StatechartError::EmptyRegion(name.clone())),
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut transitions = Vec::new();
+        for transition in self.transitions {
+            let index = |name: &String| {
+                (indices.get(name.as_str()).copied())
+                    .ok_or_else(|| StatechartError::UnknownState(name.clone()))
+            };
+            let (from, to) = (index(&transition.from)?, index(&transition.to)?);
+            if states[from].region != states[to].region {
+                return Err(StatechartError::CrossRegion(transition.from, transition.to));
+            }
+            if let Trigger::After(delay) = transition.trigger {
+                if delay < T::ZERO {
+                    return Err(StatechartError::NegativeDelay(transition.from));
+                }
+            }
+            states[from].transitions.push(transitions.len());
+            transitions.push(Transition {
+                from,
+                trigger: transition.trigger,
+                to,
+                output: transition.output,
+            });
+        }
+        Ok(Chart {
+            states,
+            regions,
+            transitions,
+        })
+    }
+}
+
+/// Atomic model that simulates one or more regions of a [`Statechart`].
+/// It is created with [`Statechart::build`] or [`Statechart::build_coupled`].
+pub struct StatechartAtomic<I, O: Clone, T: Time = f64> {
+    /// Component of the atomic model.
+    component: Component<T>,
+    /// Compiled statechart.
+    chart: Chart<I, O, T>,
+    /// Indices of the regions simulated by the model.
+    regions: Vec<usize>,
+    /// Active states (from the outermost to the innermost) of every region and the time when they were entered.
+    active: Vec<Vec<(usize, T)>>,
+    /// Current simulation time.
+    clock: T,
+    /// Messages of event transitions to be sent.
+    pending: Vec<O>,
+    /// Input port for receiving events.
+    input: InPort<I>,
+    /// Output port for sending events.
+    output: OutPort<O>,
+}
+
+impl<I, O, T> StatechartAtomic<I, O, T>
+where
+    I: DynRef + Clone + PartialEq,
+    O: DynRef + Clone,
+    T: Time,
+{
+    fn new(name: &str, chart: Chart<I, O, T>, regions: Vec<usize>) -> Self {
+        let mut component = Component::new(name);
+        let input = component.add_in_port::<I>("input");
+        let output = component.add_out_port::<O>("output");
+        let mut atomic = Self {
+            component,
+            chart,
+            active: vec![Vec::new(); regions.len()],
+            regions,
+            clock: T::ZERO,
+            pending: Vec::new(),
+            input,
+            output,
+        };
+        atomic.reset();
+        atomic
+    }
+
+    /// Enters the initial configuration of every region.
+    fn reset(&mut self) {
+        self.clock = T::ZERO;
+        self.pending.clear();
+        for (active, &r) in self.active.iter_mut().zip(&self.regions) {
+            *active = vec![(self.chart.regions[r].1, T::ZERO)];
+            self.chart.enter_initial(active, T::ZERO);
+        }
+    }
+
+    /// Returns the names of the active states, from the outermost to the innermost state of every region.
+    pub fn configuration(&self) -> Vec<&str> {
+        (self.active.iter().flatten())
+            .map(|(s, _)| self.chart.states[*s].name.as_str())
+            .collect()
+    }
+
+    /// Returns `true` if the given state is active.
+    pub fn is_active(&self, state: &str) -> bool {
+        self.configuration().contains(&state)
+    }
+
+    /// Returns the time of the next internal transition and the timed transitions to be fired for every region.
+    /// If there are messages of event transitions to be sent, only timed transitions that are due now are fired.
+    /// Otherwise, the earliest timed transitions are fired.
+    fn due(&self) -> (T, Vec<(usize, usize)>) {
+        let timeouts: Vec<_> = (self.active.iter().enumerate())
+            .filter_map(|(r, active)| self.chart.next_timeout(active).map(|(t, i)| (r, t, i)))
+            .collect();
+        let t_next = match self.pending.is_empty() {
+            true => (timeouts.iter()).fold(T::INFINITY, |t_next, (_, t, _)| match *t < t_next {
+                true => *t,
+                false => t_next,
+            }),
+            false => self.clock,
+        };
+        let due = (timeouts.into_iter())
+            .filter(|(_, t, _)| *t <= t_next)
+            .map(|(r, _, i)| (r, i))
+            .collect();
+        (t_next, due)
+    }
+}
+
+impl<I, O, T> Atomic<T> for StatechartAtomic<I, O, T>
+where
+    I: DynRef + Clone + PartialEq,
+    O: DynRef + Clone,
+    T: Time,
+{
+    fn get_component(&self) -> &Component<T> {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component<T> {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.reset();
+    }
+
+    fn lambda(&self) {
+        let due = self.due().1.into_iter().map(|(_, i)| i);
+        let outputs = (self.pending.iter())
+            .chain(due.filter_map(|i| self.chart.transitions[i].output.as_ref()));
+        for output in outputs {
+            // Safety: adding message on atomic model's output port at lambda
+            unsafe { self.output.add_value(output.clone()) };
+        }
+    }
+
+    fn delta_int(&mut self) {
+        // timers are exact, regardless of rounding errors in the simulation time
+        let (t, due) = self.due();
+        self.clock = t;
+        self.pending.clear();
+        for (r, i) in due {
+            self.chart.fire(&mut self.active[r], i, t);
+        }
+    }
+
+    fn delta_ext(&mut self, e: T) {
+        self.clock = self.clock + e;
+        // Safety: reading messages on atomic model's input port at delta_ext
+        let events = unsafe { self.input.get_values() }.to_vec();
+        for event in events {
+            for active in self.active.iter_mut() {
+                let transition = (active.iter().rev())
+                    .flat_map(|(s, _)| &self.chart.states[*s].transitions)
+                    .find(|i| matches!(&self.chart.transitions[**i].trigger, Trigger::Event(e) if *e == event))
+                    .copied();
+                if let Some(i) = transition {
+                    self.chart.fire(active, i, self.clock);
+                    self.pending
+                        .extend(self.chart.transitions[i].output.clone());
+                }
+            }
+        }
+    }
+
+    fn ta(&self) -> T {
+        if !self.pending.is_empty() {
+            return T::ZERO;
+        }
+        (self.active.iter())
+            .filter_map(|active| self.chart.next_timeout(active))
+            .fold(T::INFINITY, |ta, (t, _)| match t - self.clock < ta {
+                true => t - self.clock,
+                false => ta,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenario::{ScenarioPlayer, ScheduledEvent};
+    use crate::simulation::RootCoordinator;
+    use crate::tracing::{MemorySink, TracePhase, ValueFormatter};
+
+    /// Traffic light that switches off after 11 time units, and a clock that ticks every 4 time units.
+    fn chart() -> Statechart<&'static str, &'static str> {
+        Statechart::new("chart")
+            .region("light")
+            .state("on", "light")
+            .state("red", "on")
+            .state("green", "on")
+            .state("off", "light")
+            .after("red", 3., "green", Some("green"))
+            .after("green", 2., "red", Some("red"))
+            .after("on", 11., "off", Some("timeout"))
+            .on("on", "off", "off", Some("off"))
+            .on("off", "on", "on", None)
+            .region("clock")
+            .state("tick", "clock")
+            .after("tick", 4., "tick", Some("tick"))
+            .on("tick", "reset", "tick", None)
+    }
+
+    /// Simulates a statechart model with the given input events and returns its output messages.
+    fn simulate<S: Simulator>(model: Box<S>, inputs: &[(f64, &'static str)]) -> Vec<(f64, String)> {
+        let schedule = (inputs.iter())
+            .map(|(t, event)| ScheduledEvent::new(*t, "output", *event))
+            .collect();
+        let mut coupled = Coupled::new("root");
+        coupled.add_component(Box::new(ScenarioPlayer::new("player", schedule)));
+        coupled.add_component(model);
+        coupled.add_ic("player", "output", "chart", "input");
+        let sink = MemorySink::new();
+        let mut simulator = RootCoordinator::new(coupled);
+        let formatter = ValueFormatter::new().debug::<&str>();
+        simulator.add_trace_sinks(vec![Box::new(sink.clone())], Some(formatter));
+        simulator.simulate(19.);
+        let mut outputs: Vec<_> = (sink.records().into_iter())
+            .filter(|r| r.phase == TracePhase::Output && r.path == "root.chart")
+            .flat_map(|r| r.values.into_iter().map(move |v| (r.t, v)))
+            .collect();
+        outputs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        outputs
+    }
+
+    fn expected(outputs: &[(f64, &str)]) -> Vec<(f64, String)> {
+        (outputs.iter())
+            .map(|(t, v)| (*t, format!("{v:?}")))
+            .collect()
+    }
+
+    #[test]
+    fn test_statechart_timed() {
+        // the timer of the composite state is not reset by the transitions between its children,
+        // and unmatched events are ignored
+        let expected = expected(&[
+            (3., "green"),
+            (4., "tick"),
+            (5., "red"),
+            (8., "green"),
+            (8., "tick"),
+            (10., "red"),
+            (11., "timeout"),
+            (12., "tick"),
+            (16., "tick"),
+        ]);
+        let atomic = chart().build().unwrap();
+        assert_eq!(vec!["on", "red", "tick"], atomic.configuration());
+        assert_eq!(expected, simulate(Box::new(atomic), &[(1., "on")]));
+        let coupled = chart().build_coupled().unwrap();
+        assert_eq!(expected, simulate(Box::new(coupled), &[(1., "on")]));
+    }
+
+    #[test]
+    fn test_statechart_inputs() {
+        // re-entering a state restarts its timer
+        let inputs = [(6., "off"), (7., "on"), (9., "reset"), (14., "on")];
+        let expected = expected(&[
+            (3., "green"),
+            (4., "tick"),
+            (5., "red"),
+            (6., "off"),
+            (8., "tick"),
+            (10., "green"),
+            (12., "red"),
+            (13., "tick"),
+            (15., "green"),
+            (17., "red"),
+            (17., "tick"),
+            (18., "timeout"),
+        ]);
+        let atomic = chart().build().unwrap();
+        assert_eq!(expected, simulate(Box::new(atomic), &inputs));
+        let coupled = chart().build_coupled().unwrap();
+        assert_eq!(expected, simulate(Box::new(coupled), &inputs));
+    }
+
+    #[test]
+    fn test_statechart_invalid() {
+        let errors = [
+            (Statechart::new("chart"), StatechartError::NoRegions),
+            (
+                chart().state("red", "off"),
+                StatechartError::DuplicateName("red".to_string()),
+            ),
+            (
+                chart().state("blue", "purple"),
+                StatechartError::UnknownParent("purple".to_string()),
+            ),
+            (
+                chart().on("red", "off", "blue", None),
+                StatechartError::UnknownState("blue".to_string()),
+            ),
+            (
+                chart().region("empty"),
+                StatechartError::EmptyRegion("empty".to_string()),
+            ),
+            (
+                chart().on("red", "tick", "tick", None),
+                StatechartError::CrossRegion("red".to_string(), "tick".to_string()),
+            ),
+            (
+                chart().after("off", -1., "on", None),
+                StatechartError::NegativeDelay("off".to_string()),
+            ),
+        ];
+        for (chart, error) in errors {
+            assert_eq!(error, chart.build().err().unwrap());
+        }
+    }
+}
@@ -0,0 +1,705 @@
+use crate::simulation::Simulator;
+use crate::time::Time;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Accumulator that counts events (e.g., the number of served jobs).
+/// It is created with [`crate::modeling::Component::add_counter`], so it is reset when the simulation
+/// starts and gathered into the [`StatsReport`] of the run. Clones share the same count.
+#[derive(Debug, Clone, Default)]
+pub struct Counter(Arc<AtomicU64>);
+
+impl Counter {
+    /// Increments the counter by one.
+    #[inline]
+    pub fn increment(&self) {
+        self.add(1);
+    }
+
+    /// Increments the counter by `n`.
+    #[inline]
+    pub fn add(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Returns the current count.
+    #[inline]
+    pub fn value(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Resets the count to zero.
+    fn reset(&self) {
+        self.0.store(0, Ordering::Relaxed);
+    }
+}
+
+/// State of a [`Tally`].
+#[derive(Debug, Clone, Copy)]
+struct TallyState {
+    /// Number of observations.
+    count: u64,
+    /// Mean of the observations.
+    mean: f64,
+    /// Sum of squared differences from the mean (Welford's algorithm).
+    m2: f64,
+    /// Minimum observation.
+    min: f64,
+    /// Maximum observation.
+    max: f64,
+}
+
+impl Default for TallyState {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            mean: 0.,
+            m2: 0.,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+/// Accumulator of independent observations (e.g., the waiting time of every job).
+/// It computes their mean, variance, minimum, and maximum in constant memory.
+/// It is created with [`crate::modeling::Component::add_tally`], so it is reset when the simulation
+/// starts and gathered into the [`StatsReport`] of the run. Clones share the same observations.
+#[derive(Debug, Clone, Default)]
+pub struct Tally(Arc<Mutex<TallyState>>);
+
+impl Tally {
+    /// Records a new observation.
+    pub fn record(&self, x: f64) {
+        let mut s = self.0.lock().unwrap();
+        s.count += 1;
+        let delta = x - s.mean;
+        s.mean += delta / s.count as f64;
+        s.m2 += delta * (x - s.mean);
+        s.min = s.min.min(x);
+        s.max = s.max.max(x);
+    }
+
+    /// Returns the number of observations.
+    pub fn count(&self) -> u64 {
+        self.0.lock().unwrap().count
+    }
+
+    /// Returns the mean of the observations, or NaN if there are none.
+    pub fn mean(&self) -> f64 {
+        let s = self.0.lock().unwrap();
+        match s.count {
+            0 => f64::NAN,
+            _ => s.mean,
+        }
+    }
+
+    /// Returns the sample variance of the observations, or NaN if there are less than two.
+    pub fn variance(&self) -> f64 {
+        let s = self.0.lock().unwrap();
+        match s.count {
+            0 | 1 => f64::NAN,
+            n => s.m2 / (n - 1) as f64,
+        }
+    }
+
+    /// Returns the sample standard deviation of the observations, or NaN if there are less than two.
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// Returns the minimum observation, or infinity if there are none.
+    pub fn min(&self) -> f64 {
+        self.0.lock().unwrap().min
+    }
+
+    /// Returns the maximum observation, or negative infinity if there are none.
+    pub fn max(&self) -> f64 {
+        self.0.lock().unwrap().max
+    }
+
+    /// Returns the confidence interval of the mean of the observations at a given confidence level
+    /// (e.g., 0.95), or [`None`] if there are less than two observations.
+    pub fn confidence_interval(&self, level: f64) -> Option<ConfidenceInterval> {
+        let n = self.count() as usize;
+        (n >= 2).then(|| ConfidenceInterval::new(n, self.mean(), self.variance(), level))
+    }
+
+    /// Removes all the observations.
+    fn reset(&self) {
+        *self.0.lock().unwrap() = TallyState::default();
+    }
+}
+
+/// State of a [`TimeWeighted`] accumulator.
+#[derive(Debug, Clone, Copy)]
+struct TimeWeightedState {
+    /// Value at the beginning of the simulation.
+    initial: f64,
+    /// Current value.
+    value: f64,
+    /// Time of the last update.
+    t_last: f64,
+    /// Integral of the value from time zero to `t_last`.
+    integral: f64,
+    /// Minimum value.
+    min: f64,
+    /// Maximum value.
+    max: f64,
+}
+
+impl TimeWeightedState {
+    fn new(initial: f64) -> Self {
+        Self {
+            initial,
+            value: initial,
+            t_last: 0.,
+            integral: 0.,
+            min: initial,
+            max: initial,
+        }
+    }
+}
+
+/// Accumulator of a piecewise constant variable (e.g., the length of a queue).
+/// It computes its time-weighted average from time zero, minimum, and maximum.
+/// It is created with [`crate::modeling::Component::add_time_weighted`], so it is reset when the simulation
+/// starts and gathered into the [`StatsReport`] of the run. Clones share the same variable.
+#[derive(Debug, Clone)]
+pub struct TimeWeighted(Arc<Mutex<TimeWeightedState>>);
+
+impl TimeWeighted {
+    /// Creates a new accumulator with the value of the variable at time zero.
+    pub fn new(initial: f64) -> Self {
+        Self(Arc::new(Mutex::new(TimeWeightedState::new(initial))))
+    }
+
+    /// Sets the value of the variable at time `t`. Updates must be sorted by time.
+    pub fn update(&self, t: f64, value: f64) {
+        let mut s = self.0.lock().unwrap();
+        debug_assert!(t >= s.t_last, "updates must be sorted by time");
+        s.integral += s.value * (t - s.t_last);
+        s.t_last = t;
+        s.value = value;
+        s.min = s.min.min(value);
+        s.max = s.max.max(value);
+    }
+
+    /// Returns the current value of the variable.
+    pub fn value(&self) -> f64 {
+        self.0.lock().unwrap().value
+    }
+
+    /// Returns the time-weighted average of the variable from time zero to time `t`.
+    /// If `t` is before the last update, the average is computed until the last update.
+    /// If both times are zero, it returns the current value.
+    pub fn mean(&self, t: f64) -> f64 {
+        let s = self.0.lock().unwrap();
+        let t = t.max(s.t_last);
+        match t > 0. {
+            true => (s.integral + s.value * (t - s.t_last)) / t,
+            false => s.value,
+        }
+    }
+
+    /// Returns the minimum value of the variable.
+    pub fn min(&self) -> f64 {
+        self.0.lock().unwrap().min
+    }
+
+    /// Returns the maximum value of the variable.
+    pub fn max(&self) -> f64 {
+        self.0.lock().unwrap().max
+    }
+
+    /// Resets the variable to its initial value at time zero.
+    fn reset(&self) {
+        let mut s = self.0.lock().unwrap();
+        *s = TimeWeightedState::new(s.initial);
+    }
+}
+
+/// Confidence interval of the mean of a set of independent samples (e.g., the results of the
+/// replications of an experiment, or the batch means of a long run), based on Student's t distribution.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfidenceInterval {
+    /// Number of samples.
+    pub n: usize,
+    /// Sample mean.
+    pub mean: f64,
+    /// Sample variance.
+    pub variance: f64,
+    /// Confidence level (e.g., 0.95).
+    pub level: f64,
+    /// Half width of the interval.
+    pub half_width: f64,
+}
+
+impl ConfidenceInterval {
+    /// Creates a new confidence interval from the number of samples, their mean, and their variance.
+    /// It panics if there are less than two samples or the confidence level is not between 0 and 1.
+    pub fn new(n: usize, mean: f64, variance: f64, level: f64) -> Self {
+        assert!(n >= 2, "confidence intervals require at least two samples");
+        assert!(
+            level > 0. && level < 1.,
+            "confidence level must be between 0 and 1"
+        );
+        let t = student_t_quantile(0.5 + level / 2., (n - 1) as f64);
+        Self {
+            n,
+            mean,
+            variance,
+            level,
+            half_width: t * (variance / n as f64).sqrt(),
+        }
+    }
+
+    /// Computes the confidence interval of the mean of a set of samples,
+    /// or returns [`None`] if there are less than two samples.
+    pub fn from_samples(samples: &[f64], level: f64) -> Option<Self> {
+        let tally = Tally::default();
+        samples.iter().for_each(|&x| tally.record(x));
+        tally.confidence_interval(level)
+    }
+
+    /// Computes the confidence interval of the steady-state mean of a long time series with the method of batch means.
+    /// The series is split into `n_batches` batches of the same size (discarding the remainder at the beginning),
+    /// and the batch averages are treated as independent samples. Remove the warm-up period from the series
+    /// beforehand. It returns [`None`] if there are less than two batches or the series is shorter than `n_batches`.
+    pub fn batch_means(series: &[f64], n_batches: usize, level: f64) -> Option<Self> {
+        if n_batches < 2 || series.len() < n_batches {
+            return None;
+        }
+        let size = series.len() / n_batches;
+        let series = &series[series.len() % n_batches..];
+        let means: Vec<_> = (series.chunks(size))
+            .map(|batch| batch.iter().sum::<f64>() / size as f64)
+            .collect();
+        Self::from_samples(&means, level)
+    }
+
+    /// Returns the lower bound of the interval.
+    pub fn lower(&self) -> f64 {
+        self.mean - self.half_width
+    }
+
+    /// Returns the upper bound of the interval.
+    pub fn upper(&self) -> f64 {
+        self.mean + self.half_width
+    }
+
+    /// Returns `true` if the interval contains a given value.
+    pub fn contains(&self, x: f64) -> bool {
+        self.lower() <= x && x <= self.upper()
+    }
+}
+
+impl fmt::Display for ConfidenceInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.6} ± {:.6} ({}% CI, n={})",
+            self.mean,
+            self.half_width,
+            self.level * 100.,
+            self.n
+        )
+    }
+}
+
+/// Natural logarithm of the gamma function (Lanczos approximation, g = 7).
+fn ln_gamma(x: f64) -> f64 {
+    const COEFS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // reflection formula
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1. - x);
+    }
+    let x = x - 1.;
+    let a = (1..9).fold(COEFS[0], |a, i| a + COEFS[i] / (x + i as f64));
+    let t = x + 7.5;
+    0.5 * (2. * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + a.ln()
+}
+
+/// Regularized incomplete beta function I_x(a, b), evaluated with Lentz's continued fraction.
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0. || x >= 1. {
+        return x.clamp(0., 1.);
+    }
+    // the continued fraction converges quickly for x < (a + 1) / (a + b + 2)
+    if x > (a + 1.) / (a + b + 2.) {
+        return 1. - incomplete_beta(b, a, 1. - x);
+    }
+    const TINY: f64 = 1e-300;
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1. - x).ln()).exp();
+    let (mut c, mut d) = (1., 1. - (a + b) * x / (a + 1.));
+    d = 1. / if d.abs() < TINY { TINY } else { d };
+    let mut h = d;
+    for m in 1..300 {
+        let m = m as f64;
+        for numerator in [
+            m * (b - m) * x / ((a + 2. * m - 1.) * (a + 2. * m)),
+            -(a + m) * (a + b + m) * x / ((a + 2. * m) * (a + 2. * m + 1.)),
+        ] {
+            d = 1. + numerator * d;
+            d = 1. / if d.abs() < TINY { TINY } else { d };
+            c = 1. + numerator / c;
+            c = if c.abs() < TINY { TINY } else { c };
+            h *= c * d;
+        }
+        if (c * d - 1.).abs() < 1e-15 {
+            break;
+        }
+    }
+    front * h / a
+}
+
+/// Cumulative distribution function of Student's t distribution with `df` degrees of freedom.
+fn student_t_cdf(t: f64, df: f64) -> f64 {
+    let tail = 0.5 * incomplete_beta(df / 2., 0.5, df / (df + t * t));
+    match t > 0. {
+        true => 1. - tail,
+        false => tail,
+    }
+}
+
+/// Quantile function of Student's t distribution with `df` degrees of freedom for `p` in (0.5, 1).
+fn student_t_quantile(p: f64, df: f64) -> f64 {
+    let mut high = 1.;
+    while student_t_cdf(high, df) < p {
+        high *= 2.;
+    }
+    let mut low = 0.;
+    for _ in 0..100 {
+        let mid = 0.5 * (low + high);
+        match student_t_cdf(mid, df) < p {
+            true => low = mid,
+            false => high = mid,
+        }
+    }
+    0.5 * (low + high)
+}
+
+/// Accumulator owned by a [`crate::modeling::Component`].
+#[derive(Debug, Clone)]
+pub(crate) enum Statistic {
+    Counter(Counter),
+    Tally(Tally),
+    TimeWeighted(TimeWeighted),
+}
+
+impl Statistic {
+    /// Resets the accumulator.
+    pub(crate) fn reset(&self) {
+        match self {
+            Self::Counter(c) => c.reset(),
+            Self::Tally(t) => t.reset(),
+            Self::TimeWeighted(w) => w.reset(),
+        }
+    }
+
+    /// Returns a summary of the accumulator. Time-weighted averages are computed until time `t_end`.
+    fn summary(&self, t_end: f64) -> Summary {
+        match self {
+            Self::Counter(c) => Summary::Counter(c.value()),
+            Self::Tally(t) => Summary::Tally {
+                count: t.count(),
+                mean: t.mean(),
+                std_dev: t.std_dev(),
+                min: t.min(),
+                max: t.max(),
+            },
+            Self::TimeWeighted(w) => Summary::TimeWeighted {
+                mean: w.mean(t_end),
+                min: w.min(),
+                max: w.max(),
+                last: w.value(),
+            },
+        }
+    }
+}
+
+/// Summary of an accumulator at the end of a simulation run.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Summary {
+    /// Final count of a [`Counter`].
+    Counter(u64),
+    /// Statistics of the observations of a [`Tally`].
+    Tally {
+        /// Number of observations.
+        count: u64,
+        /// Mean of the observations.
+        mean: f64,
+        /// Sample standard deviation of the observations.
+        std_dev: f64,
+        /// Minimum observation.
+        min: f64,
+        /// Maximum observation.
+        max: f64,
+    },
+    /// Statistics of the variable of a [`TimeWeighted`] accumulator.
+    TimeWeighted {
+        /// Time-weighted average of the variable.
+        mean: f64,
+        /// Minimum value of the variable.
+        min: f64,
+        /// Maximum value of the variable.
+        max: f64,
+        /// Final value of the variable.
+        last: f64,
+    },
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Counter(count) => write!(f, "count={count}"),
+            Self::Tally {
+                count,
+                mean,
+                std_dev,
+                min,
+                max,
+            } => write!(
+                f,
+                "count={count} mean={mean:.6} std_dev={std_dev:.6} min={min:.6} max={max:.6}"
+            ),
+            Self::TimeWeighted {
+                mean,
+                min,
+                max,
+                last,
+            } => write!(f, "mean={mean:.6} min={min:.6} max={max:.6} last={last:.6}"),
+        }
+    }
+}
+
+/// Report of the accumulators of every component of a model after a simulation run.
+/// It is obtained with [`crate::simulation::RootCoordinator::stats`], and it is keyed by component path
+/// and accumulator name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatsReport {
+    /// Summaries of the accumulators. Keys are component paths, and values are maps from accumulator names.
+    components: BTreeMap<String, BTreeMap<String, Summary>>,
+}
+
+impl StatsReport {
+    /// Collects the accumulators of a model and its subcomponents.
+    /// Time-weighted averages are computed until time `t_end`.
+    pub(crate) fn new<T: Time>(model: &dyn Simulator<T>, t_end: f64) -> Self {
+        let mut report = Self::default();
+        report.collect(model, t_end);
+        report
+    }
+
+    /// Recursively collects the accumulators of a model and its subcomponents.
+    fn collect<T: Time>(&mut self, model: &dyn Simulator<T>, t_end: f64) {
+        let component = model.get_component();
+        let stats = component.get_stats();
+        if !stats.is_empty() {
+            let summaries = stats
+                .iter()
+                .map(|(name, s)| (name.clone(), s.summary(t_end)));
+            (self.components).insert(component.get_path().to_string(), summaries.collect());
+        }
+        for child in model.get_components() {
+            self.collect(child, t_end);
+        }
+    }
+
+    /// Returns `true` if the report contains no accumulators.
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// Returns the summaries of the accumulators of the component with the given path (if any).
+    pub fn component(&self, path: &str) -> Option<&BTreeMap<String, Summary>> {
+        self.components.get(path)
+    }
+
+    /// Returns the summary of an accumulator of the component with the given path (if any).
+    pub fn get(&self, path: &str, name: &str) -> Option<&Summary> {
+        self.components.get(path)?.get(name)
+    }
+
+    /// Iterates over all the summaries, sorted by component path and accumulator name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str, &Summary)> {
+        (self.components.iter()).flat_map(|(path, stats)| {
+            (stats.iter()).map(move |(name, summary)| (path.as_str(), name.as_str(), summary))
+        })
+    }
+}
+
+impl fmt::Display for StatsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (path, name, summary) in self.iter() {
+            writeln!(f, "{path}.{name}: {summary}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::{Atomic, Component, Coupled};
+    use crate::simulation::RootCoordinator;
+
+    /// Atomic model that fills a buffer one unit every second, and empties it when it is full.
+    struct Buffer {
+        component: Component,
+        clock: f64,
+        level: usize,
+        size: usize,
+        flushes: Counter,
+        levels: TimeWeighted,
+    }
+
+    impl Buffer {
+        fn new(name: &str, size: usize) -> Self {
+            let mut component = Component::new(name);
+            let flushes = component.add_counter("flushes");
+            let levels = component.add_time_weighted("level", 0.);
+            Self {
+                component,
+                clock: 0.,
+                level: 0,
+                size,
+                flushes,
+                levels,
+            }
+        }
+    }
+
+    impl Atomic for Buffer {
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+
+        fn start(&mut self) {
+            (self.clock, self.level) = (0., 0);
+        }
+
+        fn lambda(&self) {}
+
+        fn delta_int(&mut self) {
+            self.clock += 1.;
+            self.level = match self.level == self.size {
+                true => {
+                    self.flushes.increment();
+                    0
+                }
+                false => self.level + 1,
+            };
+            self.levels.update(self.clock, self.level as f64);
+        }
+
+        fn delta_ext(&mut self, _e: f64) {}
+
+        fn ta(&self) -> f64 {
+            1.
+        }
+    }
+
+    #[test]
+    fn test_accumulators() {
+        let counter = Counter::default();
+        counter.increment();
+        counter.clone().add(2);
+        assert_eq!(3, counter.value());
+
+        let tally = Tally::default();
+        assert!(tally.mean().is_nan());
+        [2., 4., 4., 4., 5., 5., 7., 9.]
+            .into_iter()
+            .for_each(|x| tally.record(x));
+        assert_eq!((8, 5.), (tally.count(), tally.mean()));
+        assert!((tally.variance() - 32. / 7.).abs() < 1e-12);
+        assert_eq!((2., 9.), (tally.min(), tally.max()));
+
+        let queue = TimeWeighted::new(0.);
+        queue.update(1., 3.);
+        queue.update(2., 0.);
+        assert_eq!(1.5, queue.mean(2.));
+        assert_eq!(1., queue.mean(3.));
+        assert_eq!((0., 3., 0.), (queue.min(), queue.max(), queue.value()));
+        Statistic::TimeWeighted(queue.clone()).reset();
+        assert_eq!((0., 0.), (queue.value(), queue.mean(0.)));
+    }
+
+    #[test]
+    fn test_confidence_interval() {
+        // reference quantiles of Student's t distribution
+        for (p, df, t) in [
+            (0.975, 1., 12.7062),
+            (0.975, 9., 2.2622),
+            (0.995, 30., 2.7500),
+        ] {
+            assert!((student_t_quantile(p, df) - t).abs() < 1e-4);
+        }
+        let samples = [2., 4., 4., 4., 5., 5., 7., 9.];
+        let ci = ConfidenceInterval::from_samples(&samples, 0.95).unwrap();
+        assert_eq!((8, 5.), (ci.n, ci.mean));
+        assert!((ci.half_width - 2.3646 * (32. / 7. / 8f64).sqrt()).abs() < 1e-3);
+        assert!(ci.contains(5.) && !ci.contains(7.));
+        assert!(ConfidenceInterval::from_samples(&[1.], 0.95).is_none());
+
+        let series: Vec<_> = (0..103).map(|i| (i % 4) as f64).collect();
+        let ci = ConfidenceInterval::batch_means(&series, 5, 0.9).unwrap();
+        assert_eq!((5, 1.5), (ci.n, ci.mean));
+        assert!(ConfidenceInterval::batch_means(&series, 1, 0.9).is_none());
+    }
+
+    #[test]
+    fn test_report() {
+        let mut coupled = Coupled::new("root");
+        coupled.add_component(Box::new(Buffer::new("small", 1)));
+        coupled.add_component(Box::new(Buffer::new("large", 3)));
+        let mut simulator = RootCoordinator::new(coupled);
+        // paths are known before simulating, so accumulators are reported from the beginning
+        assert_eq!(
+            Some(&Summary::Counter(0)),
+            simulator.stats().get("root.small", "flushes")
+        );
+        // runs twice to check that accumulators are reset
+        for _ in 0..2 {
+            simulator.simulate(8.5);
+            let report = simulator.stats();
+            assert_eq!(4, report.iter().count());
+            assert_eq!(
+                Some(&Summary::Counter(4)),
+                report.get("root.small", "flushes")
+            );
+            assert_eq!(
+                Some(&Summary::Counter(2)),
+                report.get("root.large", "flushes")
+            );
+            // levels 0, 1, 2, 3, 0, 1, 2, 3, 0 at times 0 to 8
+            let expected = Summary::TimeWeighted {
+                mean: 1.5,
+                min: 0.,
+                max: 3.,
+                last: 0.,
+            };
+            assert_eq!(Some(&expected), report.get("root.large", "level"));
+            assert_eq!(4, report.to_string().lines().count());
+        }
+    }
+}
@@ -0,0 +1,241 @@
+#[cfg(feature = "test_util")]
+mod random_model;
+
+use crate::modeling::port::{Port, Values};
+use crate::modeling::Atomic;
+use crate::simulation::Simulator;
+use crate::time::Time;
+use crate::{DynRef, Shared};
+use std::any::Any;
+
+#[cfg(feature = "test_util")]
+pub use random_model::{RandomModel, RandomModelConfig};
+
+/// Harness for unit testing atomic models without wrapping them in a [`crate::modeling::Coupled`] model.
+/// It injects messages into the input ports of the model, advances the simulation time,
+/// and triggers the output and transition functions following the DEVS simulation protocol.
+/// Messages sent through the output ports are recorded and can be inspected with [`AtomicTester::outputs`].
+///
+/// All the methods panic if a port does not exist or if the message type does not match the port.
+pub struct AtomicTester<A, T: Time = f64> {
+    /// Atomic model under test.
+    model: A,
+    /// Current simulation time.
+    time: T,
+    /// Messages to be injected in the next simulation cycle: input port and boxed `Vec<M>`.
+    inputs: Vec<(Shared<dyn Port>, Box<dyn Any>)>,
+    /// Messages sent through the output ports: time, port name, and boxed `Vec<M>`.
+    outputs: Vec<(T, String, Box<dyn Any>)>,
+}
+
+impl<T: Time, A: Atomic<T> + Simulator<T>> AtomicTester<A, T> {
+    /// Creates a new tester. The model is started at time zero.
+    pub fn new(mut model: A) -> Self {
+        Simulator::start(&mut model, T::ZERO);
+        Self {
+            model,
+            time: T::ZERO,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Returns a reference to the model under test.
+    pub fn model(&self) -> &A {
+        &self.model
+    }
+
+    /// Returns a mutable reference to the model under test.
+    pub fn model_mut(&mut self) -> &mut A {
+        &mut self.model
+    }
+
+    /// Returns the current simulation time.
+    pub fn time(&self) -> T {
+        self.time
+    }
+
+    /// Returns the time of the next internal event of the model.
+    pub fn t_next(&self) -> T {
+        self.model.get_t_next()
+    }
+
+    /// Injects a message into an input port. The message is delivered in the next
+    /// simulation cycle triggered by [`AtomicTester::advance`] or [`AtomicTester::advance_to`].
+    pub fn inject<M: DynRef + Clone>(&mut self, port: &str, value: M) -> &mut Self {
+        let name = self.model.get_name();
+        let port = Simulator::get_component(&self.model)
+            .get_in_port(port)
+            .unwrap_or_else(|| panic!("model {name} does not have an input port {port}"));
+        // Safety: tester reading messages between simulation phases
+        if !unsafe { port.get_values() }.is::<Values<M>>() {
+            panic!(
+                "message type is not compatible with input port {}",
+                port.get_name()
+            );
+        }
+        match self
+            .inputs
+            .iter_mut()
+            .find(|(p, _)| Shared::ptr_eq(p, &port))
+        {
+            Some((_, values)) => values.downcast_mut::<Vec<M>>().unwrap().push(value),
+            None => self.inputs.push((port, Box::new(vec![value]))),
+        }
+        self
+    }
+
+    /// Advances the simulation time by `e`. See [`AtomicTester::advance_to`].
+    pub fn advance(&mut self, e: T) {
+        self.advance_to(self.time + e);
+    }
+
+    /// Advances the simulation time to `t`. First, it executes all the internal events scheduled before `t`.
+    /// Then, if there are injected messages or an internal event is scheduled at `t`,
+    /// it executes a simulation cycle at `t` (i.e., an external, internal, or confluent transition).
+    /// Output messages recorded in previous calls are discarded.
+    pub fn advance_to(&mut self, t: T) {
+        assert!(t >= self.time, "simulation time cannot go backwards");
+        self.outputs.clear();
+        while self.model.get_t_next() < t {
+            self.cycle(self.model.get_t_next());
+        }
+        if !self.inputs.is_empty() || self.model.get_t_next() == t {
+            for (port, values) in self.inputs.drain(..) {
+                // Safety: tester injecting messages between simulation phases
+                unsafe { port.inject(values.as_ref()) };
+            }
+            self.cycle(t);
+        }
+        self.time = t;
+    }
+
+    /// Returns the messages sent through an output port during the last call
+    /// to [`AtomicTester::advance`] or [`AtomicTester::advance_to`], together with their time.
+    pub fn outputs<M: DynRef + Clone>(&self, port: &str) -> Vec<(T, M)> {
+        let name = self.model.get_name();
+        if Simulator::get_component(&self.model)
+            .get_out_port(port)
+            .is_none()
+        {
+            panic!("model {name} does not have an output port {port}");
+        }
+        let mut result = Vec::new();
+        for (t, _, values) in self.outputs.iter().filter(|(_, p, _)| p == port) {
+            let values = values
+                .downcast_ref::<Vec<M>>()
+                .unwrap_or_else(|| panic!("message type is not compatible with port {port}"));
+            result.extend(values.iter().map(|v| (*t, v.clone())));
+        }
+        result
+    }
+
+    /// Stops the model and returns it.
+    pub fn finish(mut self) -> A {
+        Simulator::stop(&mut self.model, self.time);
+        self.model
+    }
+
+    /// Executes a simulation cycle at time `t` and records the output messages.
+    fn cycle(&mut self, t: T) {
+        self.model.collection(t);
+        for port in Simulator::get_component(&self.model).get_out_ports() {
+            // Safety: tester reading messages between simulation phases
+            if !unsafe { port.is_empty() } {
+                let values = unsafe { port.clone_values() };
+                self.outputs.push((t, port.get_name().to_string(), values));
+            }
+        }
+        self.model.transition(t);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::{Component, InPort, OutPort};
+
+    /// Atomic model that outputs every received message after a delay.
+    struct Delay {
+        component: Component,
+        input: InPort<i32>,
+        output: OutPort<i32>,
+        queue: Vec<(f64, i32)>,
+        clock: f64,
+    }
+
+    impl Delay {
+        fn new() -> Self {
+            let mut component = Component::new("delay");
+            let input = component.add_in_port("input");
+            let output = component.add_out_port("output");
+            Self {
+                component,
+                input,
+                output,
+                queue: Vec::new(),
+                clock: 0.,
+            }
+        }
+    }
+
+    impl Atomic for Delay {
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+
+        fn lambda(&self) {
+            // Safety: adding messages on atomic model's output port at lambda
+            unsafe { self.output.add_value(self.queue[0].1) };
+        }
+
+        fn delta_int(&mut self) {
+            self.clock = self.queue.remove(0).0;
+        }
+
+        fn delta_ext(&mut self, e: f64) {
+            self.clock += e;
+            // Safety: reading messages on atomic model's input port at delta_ext
+            for value in unsafe { self.input.get_values() } {
+                self.queue.push((self.clock + 2., *value));
+            }
+        }
+
+        fn ta(&self) -> f64 {
+            match self.queue.first() {
+                Some((t, _)) => t - self.clock,
+                None => f64::INFINITY,
+            }
+        }
+    }
+
+    #[test]
+    fn test_atomic_tester() {
+        let mut tester = AtomicTester::new(Delay::new());
+        assert_eq!(f64::INFINITY, tester.t_next());
+        tester.inject("input", 1).inject("input", 2);
+        tester.advance(1.);
+        assert_eq!(2, tester.model().queue.len());
+        assert_eq!(3., tester.t_next());
+        assert!(tester.outputs::<i32>("output").is_empty());
+
+        tester.inject("input", 3);
+        tester.advance_to(3.);
+        assert_eq!(vec![(3., 1)], tester.outputs::<i32>("output"));
+
+        tester.advance(10.);
+        assert_eq!(vec![(3., 2), (5., 3)], tester.outputs::<i32>("output"));
+        assert_eq!(13., tester.time());
+        assert!(tester.finish().queue.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "does not have an input port")]
+    fn test_unknown_port() {
+        AtomicTester::new(Delay::new()).inject("unknown", 1);
+    }
+}
@@ -0,0 +1,597 @@
+use crate::modeling::{Atomic, Component, Coupled, InPort, OutPort};
+use crate::random::Rng;
+use crate::simulation::Simulator;
+use std::collections::HashMap;
+
+/// Parameters of the random models generated by [`RandomModel::generate`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RandomModelConfig {
+    /// Maximum number of levels of coupled models below the root model.
+    pub max_depth: usize,
+    /// Maximum number of subcomponents of every coupled model.
+    pub max_width: usize,
+    /// Maximum number of input and output ports of every component.
+    pub max_ports: usize,
+    /// Probability of every possible coupling between the ports of a coupled model and its subcomponents.
+    pub coupling_prob: f64,
+    /// Maximum number of messages sent by every atomic model.
+    pub max_events: usize,
+    /// Atomic models send their messages at random multiples of 0.5 lower than this time.
+    pub horizon: f64,
+}
+
+impl Default for RandomModelConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: 3,
+            max_width: 4,
+            max_ports: 3,
+            coupling_prob: 0.3,
+            max_events: 10,
+            horizon: 10.,
+        }
+    }
+}
+
+/// Random but valid DEVS model with a known expected behavior, for testing simulation engines.
+///
+/// The model is a random hierarchy of coupled models with random EICs, ICs, and EOCs.
+/// Its atomic models send messages at random times that do not depend on the messages they receive,
+/// so the messages that every atomic model must receive are known without simulating the model.
+/// After a simulation, [`RandomModel::check`] verifies that every message sent was received
+/// exactly once per coupling path at the time it was sent, and nothing else was received.
+/// Atomic models also panic if their simulation time goes backwards or if they are not
+/// triggered at the time of their internal events.
+///
+/// As the expected behavior only depends on the seed and the configuration, it can be used to
+/// test any coordinator and any combination of features (e.g., with `proptest`) against the DEVS semantics.
+/// Models with the same seed and configuration are always identical.
+#[derive(Debug, Clone)]
+pub struct RandomModel {
+    /// Structure of the root coupled model.
+    root: CoupledSpec,
+    /// Atomic models of the hierarchy.
+    atomics: Vec<AtomicSpec>,
+    /// Atomic input ports reached by every output port of an atomic model (one entry per coupling path).
+    destinations: Vec<Vec<Vec<(usize, usize)>>>,
+}
+
+/// Structure of an atomic model.
+#[derive(Debug, Clone)]
+struct AtomicSpec {
+    name: String,
+    n_in: usize,
+    n_out: usize,
+    /// Messages sent by the model: time, output port, and value. They are sorted by time.
+    emissions: Vec<(f64, usize, u64)>,
+}
+
+/// Structure of a coupled model.
+#[derive(Debug, Clone)]
+struct CoupledSpec {
+    name: String,
+    n_in: usize,
+    n_out: usize,
+    children: Vec<Spec>,
+    /// Input port of the coupled model, subcomponent, and input port of the subcomponent.
+    eics: Vec<(usize, usize, usize)>,
+    /// Subcomponent, output port, subcomponent, and input port.
+    ics: Vec<(usize, usize, usize, usize)>,
+    /// Subcomponent, output port of the subcomponent, and output port of the coupled model.
+    eocs: Vec<(usize, usize, usize)>,
+}
+
+#[derive(Debug, Clone)]
+enum Spec {
+    Atomic(usize),
+    Coupled(CoupledSpec),
+}
+
+/// Destination of a port in the graph of couplings.
+#[derive(Debug, Clone, Copy)]
+enum PortNode {
+    /// Input port of an atomic model: atomic model index and port index.
+    AtomicIn(usize, usize),
+    /// Any other port, which forwards messages to other ports.
+    Forward,
+}
+
+/// State of the generator of random models.
+struct Generator<'a> {
+    config: &'a RandomModelConfig,
+    rng: Rng,
+    atomics: Vec<AtomicSpec>,
+    /// Ids of the output ports of every atomic model.
+    atomic_outs: Vec<Vec<usize>>,
+    n_coupled: usize,
+    ports: Vec<PortNode>,
+    /// Couplings between ports, by port id.
+    edges: Vec<Vec<usize>>,
+}
+
+impl Generator<'_> {
+    fn new_port(&mut self, node: PortNode) -> usize {
+        self.ports.push(node);
+        self.edges.push(Vec::new());
+        self.ports.len() - 1
+    }
+
+    fn n_ports(&mut self) -> usize {
+        1 + self.rng.below(self.config.max_ports as u64) as usize
+    }
+
+    /// Generates a component and returns it with the ids of its input and output ports.
+    fn component(&mut self, depth: usize) -> (Spec, Vec<usize>, Vec<usize>) {
+        if depth < self.config.max_depth && self.rng.bernoulli(0.5) {
+            let (n_in, n_out) = (self.n_ports(), self.n_ports());
+            let (spec, ins, outs) = self.coupled(depth + 1, n_in, n_out);
+            (Spec::Coupled(spec), ins, outs)
+        } else {
+            self.atomic()
+        }
+    }
+
+    fn atomic(&mut self) -> (Spec, Vec<usize>, Vec<usize>) {
+        let index = self.atomics.len();
+        let (n_in, n_out) = (self.n_ports(), self.n_ports());
+        let ins = (0..n_in)
+            .map(|i| self.new_port(PortNode::AtomicIn(index, i)))
+            .collect();
+        let outs: Vec<_> = (0..n_out)
+            .map(|_| self.new_port(PortNode::Forward))
+            .collect();
+        let n_events = self.rng.below(self.config.max_events as u64 + 1);
+        let n_times = (2. * self.config.horizon).ceil().max(1.) as u64;
+        let mut emissions: Vec<_> = (0..n_events)
+            .map(|i| {
+                let t = self.rng.below(n_times) as f64 * 0.5;
+                let port = self.rng.below(n_out as u64) as usize;
+                (t, port, (index as u64) << 32 | i)
+            })
+            .collect();
+        emissions.sort_by(|a, b| a.0.total_cmp(&b.0));
+        self.atomics.push(AtomicSpec {
+            name: format!("atomic_{index}"),
+            n_in,
+            n_out,
+            emissions,
+        });
+        self.atomic_outs.push(outs.clone());
+        (Spec::Atomic(index), ins, outs)
+    }
+
+    fn coupled(
+        &mut self,
+        depth: usize,
+        n_in: usize,
+        n_out: usize,
+    ) -> (CoupledSpec, Vec<usize>, Vec<usize>) {
+        let name = format!("coupled_{}", self.n_coupled);
+        self.n_coupled += 1;
+        let ins: Vec<_> = (0..n_in)
+            .map(|_| self.new_port(PortNode::Forward))
+            .collect();
+        let outs: Vec<_> = (0..n_out)
+            .map(|_| self.new_port(PortNode::Forward))
+            .collect();
+        let n_children = 1 + self.rng.below(self.config.max_width as u64) as usize;
+        let mut children = Vec::with_capacity(n_children);
+        let (mut child_ins, mut child_outs) = (Vec::new(), Vec::new());
+        for _ in 0..n_children {
+            let (child, i, o) = self.component(depth);
+            children.push(child);
+            child_ins.push(i);
+            child_outs.push(o);
+        }
+        let (mut eics, mut ics, mut eocs) = (Vec::new(), Vec::new(), Vec::new());
+        let p = self.config.coupling_prob;
+        for (port_from, &from) in ins.iter().enumerate() {
+            for (child, ports) in child_ins.iter().enumerate() {
+                for (port_to, &to) in ports.iter().enumerate() {
+                    if self.rng.bernoulli(p) {
+                        eics.push((port_from, child, port_to));
+                        self.edges[from].push(to);
+                    }
+                }
+            }
+        }
+        for (child_from, ports_from) in child_outs.iter().enumerate() {
+            for (port_from, &from) in ports_from.iter().enumerate() {
+                for (child_to, ports_to) in child_ins.iter().enumerate() {
+                    for (port_to, &to) in ports_to.iter().enumerate() {
+                        if self.rng.bernoulli(p) {
+                            ics.push((child_from, port_from, child_to, port_to));
+                            self.edges[from].push(to);
+                        }
+                    }
+                }
+                for (port_to, &to) in outs.iter().enumerate() {
+                    if self.rng.bernoulli(p) {
+                        eocs.push((child_from, port_from, port_to));
+                        self.edges[from].push(to);
+                    }
+                }
+            }
+        }
+        let spec = CoupledSpec {
+            name,
+            n_in,
+            n_out,
+            children,
+            eics,
+            ics,
+            eocs,
+        };
+        (spec, ins, outs)
+    }
+
+    /// Appends the atomic input ports reached from a port to `res` (once per coupling path).
+    /// The graph of couplings is acyclic, as couplings only go from input ports to input ports of
+    /// subcomponents, from output ports to input ports of siblings, or from output ports to output ports of parents.
+    fn destinations(&self, port: usize, res: &mut Vec<(usize, usize)>) {
+        if let PortNode::AtomicIn(atomic, port) = self.ports[port] {
+            res.push((atomic, port));
+        }
+        for &to in &self.edges[port] {
+            self.destinations(to, res);
+        }
+    }
+}
+
+impl RandomModel {
+    /// Generates a random model from a seed. The root model has no ports.
+    pub fn generate(seed: u64, config: &RandomModelConfig) -> Self {
+        assert!(
+            config.max_width > 0,
+            "coupled models need at least one subcomponent"
+        );
+        assert!(config.max_ports > 0, "components need at least one port");
+        let mut generator = Generator {
+            config,
+            rng: Rng::new(seed),
+            atomics: Vec::new(),
+            atomic_outs: Vec::new(),
+            n_coupled: 0,
+            ports: Vec::new(),
+            edges: Vec::new(),
+        };
+        let (root, _, _) = generator.coupled(0, 0, 0);
+        let destinations = (generator.atomic_outs.iter())
+            .map(|outs| {
+                (outs.iter())
+                    .map(|&port| {
+                        let mut res = Vec::new();
+                        generator.destinations(port, &mut res);
+                        res
+                    })
+                    .collect()
+            })
+            .collect();
+        Self {
+            root,
+            atomics: generator.atomics,
+            destinations,
+        }
+    }
+
+    /// Builds a new instance of the model. Names of atomic models are unique across the hierarchy,
+    /// so the model can be flattened.
+    pub fn build(&self) -> Coupled {
+        self.build_coupled(&self.root)
+    }
+
+    /// Returns the number of atomic models of the model.
+    pub fn n_atomics(&self) -> usize {
+        self.atomics.len()
+    }
+
+    /// Returns the number of messages that atomic models must receive in a simulation that ends at `t_end`.
+    pub fn n_expected(&self, t_end: f64) -> usize {
+        self.expected(t_end).iter().map(|r| r.len()).sum()
+    }
+
+    /// Checks that a model built with [`RandomModel::build`] behaved as expected in a simulation
+    /// that ended at `t_end` (i.e., with simulation cycles strictly before `t_end`).
+    /// The model can be nested in other models, flattened, or direct-connected. Messages are expected once per
+    /// coupling path, and messages received at the same time can be in any order.
+    /// It returns a description of the first violation found.
+    pub fn check(&self, model: &dyn Simulator, t_end: f64) -> Result<(), String> {
+        let mut found = HashMap::new();
+        collect_atomics(model, &mut found);
+        for (spec, mut expected) in self.atomics.iter().zip(self.expected(t_end)) {
+            let atomic = found
+                .get(spec.name.as_str())
+                .ok_or_else(|| format!("atomic model {} not found", spec.name))?;
+            let mut received = atomic.received.clone();
+            received.sort_by(cmp_message);
+            expected.sort_by(cmp_message);
+            if let Some(i) = (0..received.len().max(expected.len()))
+                .find(|&i| received.get(i) != expected.get(i))
+            {
+                return Err(format!(
+                    "atomic model {} received {} messages, but {} were expected. \
+                    First difference (time, port, value): received {:?}, expected {:?}",
+                    spec.name,
+                    received.len(),
+                    expected.len(),
+                    received.get(i),
+                    expected.get(i),
+                ));
+            }
+            let n_sent = spec.emissions.iter().filter(|e| e.0 < t_end).count();
+            if atomic.next != n_sent {
+                return Err(format!(
+                    "atomic model {} sent {} messages, but {} were expected",
+                    spec.name, atomic.next, n_sent
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the messages that every atomic model must receive in a simulation that ends at `t_end`.
+    fn expected(&self, t_end: f64) -> Vec<Vec<(f64, usize, u64)>> {
+        let mut expected = vec![Vec::new(); self.atomics.len()];
+        for (spec, destinations) in self.atomics.iter().zip(&self.destinations) {
+            for &(t, port, value) in spec.emissions.iter().filter(|e| e.0 < t_end) {
+                for &(atomic, port_to) in &destinations[port] {
+                    expected[atomic].push((t, port_to, value));
+                }
+            }
+        }
+        expected
+    }
+
+    fn build_coupled(&self, spec: &CoupledSpec) -> Coupled {
+        let mut coupled = Coupled::new(&spec.name);
+        for i in 0..spec.n_in {
+            coupled.add_in_port::<u64>(&format!("in_{i}"));
+        }
+        for i in 0..spec.n_out {
+            coupled.add_out_port::<u64>(&format!("out_{i}"));
+        }
+        let names: Vec<_> = (spec.children.iter())
+            .map(|child| match child {
+                Spec::Atomic(i) => {
+                    let atomic = &self.atomics[*i];
+                    coupled.add_component(Box::new(RandomAtomic::new(atomic)));
+                    atomic.name.clone()
+                }
+                Spec::Coupled(child) => {
+                    coupled.add_component(Box::new(self.build_coupled(child)));
+                    child.name.clone()
+                }
+            })
+            .collect();
+        for &(port_from, child, port_to) in &spec.eics {
+            coupled.add_eic(
+                &format!("in_{port_from}"),
+                &names[child],
+                &format!("in_{port_to}"),
+            );
+        }
+        for &(child_from, port_from, child_to, port_to) in &spec.ics {
+            coupled.add_ic(
+                &names[child_from],
+                &format!("out_{port_from}"),
+                &names[child_to],
+                &format!("in_{port_to}"),
+            );
+        }
+        for &(child, port_from, port_to) in &spec.eocs {
+            coupled.add_eoc(
+                &names[child],
+                &format!("out_{port_from}"),
+                &format!("out_{port_to}"),
+            );
+        }
+        coupled
+    }
+}
+
+/// Orders messages by time, port, and value.
+fn cmp_message(a: &(f64, usize, u64), b: &(f64, usize, u64)) -> std::cmp::Ordering {
+    (a.0.total_cmp(&b.0))
+        .then(a.1.cmp(&b.1))
+        .then(a.2.cmp(&b.2))
+}
+
+/// Collects all the atomic models of a random model by name.
+/// Flattened models prepend the names of their former parents, so they are removed.
+fn collect_atomics<'a>(model: &'a dyn Simulator, res: &mut HashMap<&'a str, &'a RandomAtomic>) {
+    if let Some(atomic) = model.downcast_ref::<RandomAtomic>() {
+        let name = atomic.component.get_name();
+        res.insert(name.rsplit('.').next().unwrap(), atomic);
+    }
+    for component in model.get_components() {
+        collect_atomics(component, res);
+    }
+}
+
+/// Atomic model of random models. It sends messages at predefined times and records every message it receives.
+struct RandomAtomic {
+    component: Component,
+    inputs: Vec<InPort<u64>>,
+    outputs: Vec<OutPort<u64>>,
+    /// Messages to send: time, output port, and value. They are sorted by time.
+    emissions: Vec<(f64, usize, u64)>,
+    /// Index of the next message to send.
+    next: usize,
+    /// Time of the last state transition.
+    clock: f64,
+    /// Messages received: time, input port, and value.
+    received: Vec<(f64, usize, u64)>,
+}
+
+impl RandomAtomic {
+    fn new(spec: &AtomicSpec) -> Self {
+        let mut component = Component::new(&spec.name);
+        let inputs = (0..spec.n_in)
+            .map(|i| component.add_in_port(&format!("in_{i}")))
+            .collect();
+        let outputs = (0..spec.n_out)
+            .map(|i| component.add_out_port(&format!("out_{i}")))
+            .collect();
+        Self {
+            component,
+            inputs,
+            outputs,
+            emissions: spec.emissions.clone(),
+            next: 0,
+            clock: 0.,
+            received: Vec::new(),
+        }
+    }
+
+    /// Sets the simulation time of a state transition. It panics if the time goes backwards.
+    fn advance_clock(&mut self, t: f64) {
+        assert!(
+            t >= self.clock,
+            "simulation time went backwards from {} to {t}",
+            self.clock
+        );
+        self.clock = t;
+    }
+
+    /// Returns the number of messages to send at the time of the next internal event.
+    fn n_imminent(&self) -> usize {
+        let t = self.emissions[self.next].0;
+        (self.emissions[self.next..].iter())
+            .take_while(|e| e.0 == t)
+            .count()
+    }
+
+    /// Executes an internal event. It panics if it is not executed at the time of the next message.
+    fn internal(&mut self, t: f64) {
+        let scheduled = self.emissions.get(self.next).map(|e| e.0);
+        assert_eq!(Some(t), scheduled, "internal event at an unexpected time");
+        self.next += self.n_imminent();
+    }
+
+    fn receive(&mut self) {
+        for (port, input) in self.inputs.iter().enumerate() {
+            // Safety: reading messages on atomic model's input port at delta_ext
+            for value in unsafe { input.get_values() } {
+                self.received.push((self.clock, port, *value));
+            }
+        }
+    }
+}
+
+impl Atomic for RandomAtomic {
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+
+    fn start(&mut self) {
+        self.next = 0;
+        self.clock = 0.;
+        self.received.clear();
+    }
+
+    fn lambda(&self) {
+        for &(_, port, value) in &self.emissions[self.next..self.next + self.n_imminent()] {
+            // Safety: adding messages on atomic model's output port at lambda
+            unsafe { self.outputs[port].add_value(value) };
+        }
+    }
+
+    fn delta_int(&mut self) {
+        let t = self.component.get_t_next();
+        self.advance_clock(t);
+        self.internal(t);
+    }
+
+    fn delta_ext(&mut self, e: f64) {
+        self.advance_clock(self.component.get_t_last() + e);
+        self.receive();
+    }
+
+    fn delta_conf(&mut self) {
+        self.delta_int();
+        self.receive();
+    }
+
+    fn ta(&self) -> f64 {
+        match self.emissions.get(self.next) {
+            Some(&(t, _, _)) => t - self.clock,
+            None => f64::INFINITY,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{FlatCoordinator, RootCoordinator};
+
+    const T_END: f64 = 8.;
+
+    fn config() -> RandomModelConfig {
+        RandomModelConfig {
+            coupling_prob: 0.5,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_random_model() {
+        let config = config();
+        let mut n_expected = 0;
+        for seed in 0..50 {
+            let random = RandomModel::generate(seed, &config);
+            let mut simulator = RootCoordinator::new(random.build());
+            simulator.simulate(T_END);
+            random.check(&*simulator, T_END).unwrap();
+            n_expected += random.n_expected(T_END);
+        }
+        assert!(n_expected > 0);
+    }
+
+    #[test]
+    fn test_random_model_transformations() {
+        let config = config();
+        for seed in 0..50 {
+            let random = RandomModel::generate(seed, &config);
+
+            let mut simulator = RootCoordinator::new(random.build().flatten());
+            simulator.simulate(T_END);
+            random.check(&*simulator, T_END).unwrap();
+
+            let mut simulator = FlatCoordinator::new(random.build());
+            simulator.simulate(T_END);
+            random.check(&*simulator, T_END).unwrap();
+
+            let mut simulator = RootCoordinator::new(random.build().direct_connect());
+            simulator.simulate(T_END);
+            random.check(&*simulator, T_END).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_random_model_determinism() {
+        let config = config();
+        let a = RandomModel::generate(7, &config);
+        let b = RandomModel::generate(7, &config);
+        assert_eq!(a.n_atomics(), b.n_atomics());
+        assert_eq!(a.n_expected(T_END), b.n_expected(T_END));
+    }
+
+    #[test]
+    fn test_random_model_violation() {
+        let config = config();
+        let random = (0..)
+            .map(|seed| RandomModel::generate(seed, &config))
+            .find(|random| random.n_expected(T_END) > random.n_expected(T_END / 2.))
+            .unwrap();
+        let mut simulator = RootCoordinator::new(random.build());
+        simulator.simulate(T_END / 2.);
+        // The simulation ended earlier, so atomic models received fewer messages than expected
+        assert!(random.check(&*simulator, T_END).is_err());
+        random.check(&*simulator, T_END / 2.).unwrap();
+    }
+}
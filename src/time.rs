@@ -0,0 +1,377 @@
+use crate::DynRef;
+use std::cmp::Ordering;
+use std::fmt::{self, Debug, Display};
+use std::ops::{Add, Sub};
+
+mod private {
+    /// Prevents implementing [`super::Time`] outside this crate.
+    pub trait Sealed {}
+
+    impl Sealed for f64 {}
+    impl<const DECIMALS: u32> Sealed for super::Fixed<DECIMALS> {}
+    impl Sealed for super::Rational {}
+}
+
+/// Interface for simulation time types.
+/// By default, models use [`f64`] time. Models that require exact time arithmetic
+/// (e.g., to avoid floating-point drift when comparing `t == t_next`) can use [`Fixed`] or [`Rational`] instead.
+/// This trait is sealed, as [`crate::simulation::Simulator`] is implemented for each supported time type.
+///
+/// Implementers guarantee that `INFINITY + t == INFINITY` and `INFINITY - t == INFINITY` for any finite `t`.
+pub trait Time:
+    private::Sealed + DynRef + Copy + Debug + PartialOrd + Add<Output = Self> + Sub<Output = Self>
+{
+    /// Time zero. Simulations start at this time.
+    const ZERO: Self;
+    /// Infinite time. Passive models return it as time advance.
+    const INFINITY: Self;
+
+    /// Returns a total ordering between `self` and `other`.
+    fn total_cmp(&self, other: &Self) -> Ordering;
+
+    /// Converts the time to [`f64`]. It is used for reporting simulation events.
+    fn as_f64(&self) -> f64;
+}
+
+impl Time for f64 {
+    const ZERO: Self = 0.;
+    const INFINITY: Self = f64::INFINITY;
+
+    #[inline]
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f64::total_cmp(self, other)
+    }
+
+    #[inline]
+    fn as_f64(&self) -> f64 {
+        *self
+    }
+}
+
+/// Fixed-point time with `DECIMALS` decimal digits (e.g., `Fixed<3>` has millisecond resolution).
+/// Internally, it is an integer number of ticks. Additions and subtractions saturate to infinity.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Fixed<const DECIMALS: u32>(i64);
+
+impl<const DECIMALS: u32> Fixed<DECIMALS> {
+    /// Number of ticks in one time unit.
+    const SCALE: i64 = 10_i64.pow(DECIMALS);
+
+    /// Creates a new fixed-point time from a number of ticks.
+    #[inline]
+    pub const fn from_ticks(ticks: i64) -> Self {
+        Self(ticks)
+    }
+
+    /// Returns the number of ticks of the fixed-point time.
+    #[inline]
+    pub const fn ticks(&self) -> i64 {
+        self.0
+    }
+
+    /// Creates a new fixed-point time from a number of time units.
+    #[inline]
+    pub const fn from_units(units: i64) -> Self {
+        Self(units.saturating_mul(Self::SCALE))
+    }
+
+    /// Returns `true` if the time is infinite.
+    #[inline]
+    pub const fn is_infinite(&self) -> bool {
+        self.0 == i64::MAX
+    }
+}
+
+impl<const DECIMALS: u32> Add for Fixed<DECIMALS> {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        match self.is_infinite() || rhs.is_infinite() {
+            true => Self::INFINITY,
+            false => Self(self.0.saturating_add(rhs.0)),
+        }
+    }
+}
+
+impl<const DECIMALS: u32> Sub for Fixed<DECIMALS> {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        match self.is_infinite() {
+            true => Self::INFINITY,
+            false => Self(self.0 - rhs.0),
+        }
+    }
+}
+
+impl<const DECIMALS: u32> Debug for Fixed<DECIMALS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl<const DECIMALS: u32> Display for Fixed<DECIMALS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.is_infinite() {
+            true => write!(f, "inf"),
+            false => {
+                let (sign, ticks) = (if self.0 < 0 { "-" } else { "" }, self.0.unsigned_abs());
+                let (units, frac) = (ticks / Self::SCALE as u64, ticks % Self::SCALE as u64);
+                match DECIMALS {
+                    0 => write!(f, "{sign}{units}"),
+                    _ => write!(f, "{sign}{units}.{frac:0width$}", width = DECIMALS as usize),
+                }
+            }
+        }
+    }
+}
+
+impl<const DECIMALS: u32> Time for Fixed<DECIMALS> {
+    const ZERO: Self = Self(0);
+    const INFINITY: Self = Self(i64::MAX);
+
+    #[inline]
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        self.cmp(other)
+    }
+
+    #[inline]
+    fn as_f64(&self) -> f64 {
+        match self.is_infinite() {
+            true => f64::INFINITY,
+            false => self.0 as f64 / Self::SCALE as f64,
+        }
+    }
+}
+
+/// Rational time. It is always stored in its irreducible form with a positive denominator.
+/// Infinity is represented with a zero denominator.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    /// Numerator of the rational number.
+    num: i64,
+    /// Denominator of the rational number.
+    den: i64,
+}
+
+impl Rational {
+    /// Creates a new rational time. It panics if `den` is zero.
+    pub fn new(num: i64, den: i64) -> Self {
+        if den == 0 {
+            panic!("denominator must not be zero");
+        }
+        Self::reduce(num as i128, den as i128)
+    }
+
+    /// Creates a new rational time from an integer.
+    #[inline]
+    pub const fn from_integer(num: i64) -> Self {
+        Self { num, den: 1 }
+    }
+
+    /// Returns the numerator of the rational time.
+    #[inline]
+    pub const fn numer(&self) -> i64 {
+        self.num
+    }
+
+    /// Returns the denominator of the rational time. It is zero for infinite times.
+    #[inline]
+    pub const fn denom(&self) -> i64 {
+        self.den
+    }
+
+    /// Returns `true` if the time is infinite.
+    #[inline]
+    pub const fn is_infinite(&self) -> bool {
+        self.den == 0
+    }
+
+    /// Creates a rational time in its irreducible form. It panics if the result overflows.
+    fn reduce(num: i128, den: i128) -> Self {
+        let (mut a, mut b) = (num.abs(), den.abs());
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        let g = den.signum() * a.max(1);
+        let num = i64::try_from(num / g).expect("rational time overflow");
+        let den = i64::try_from(den / g).expect("rational time overflow");
+        Self { num, den }
+    }
+}
+
+impl Default for Rational {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl Add for Rational {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        match self.is_infinite() || rhs.is_infinite() {
+            true => Self::INFINITY,
+            false => {
+                let num = self.num as i128 * rhs.den as i128 + rhs.num as i128 * self.den as i128;
+                Self::reduce(num, self.den as i128 * rhs.den as i128)
+            }
+        }
+    }
+}
+
+impl Sub for Rational {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        match self.is_infinite() {
+            true => Self::INFINITY,
+            false => {
+                let num = self.num as i128 * rhs.den as i128 - rhs.num as i128 * self.den as i128;
+                Self::reduce(num, self.den as i128 * rhs.den as i128)
+            }
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.is_infinite(), other.is_infinite()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => {
+                let lhs = self.num as i128 * other.den as i128;
+                lhs.cmp(&(other.num as i128 * self.den as i128))
+            }
+        }
+    }
+}
+
+impl Debug for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.is_infinite(), self.den) {
+            (true, _) => write!(f, "inf"),
+            (false, 1) => write!(f, "{}", self.num),
+            (false, den) => write!(f, "{}/{den}", self.num),
+        }
+    }
+}
+
+impl Time for Rational {
+    const ZERO: Self = Self { num: 0, den: 1 };
+    const INFINITY: Self = Self { num: 1, den: 0 };
+
+    #[inline]
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        self.cmp(other)
+    }
+
+    #[inline]
+    fn as_f64(&self) -> f64 {
+        match self.is_infinite() {
+            true => f64::INFINITY,
+            false => self.num as f64 / self.den as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::{Atomic, Component};
+    use crate::simulation::{RootCoordinator, Simulator};
+
+    /// Atomic model that counts its internal transitions.
+    struct Clock<T: Time> {
+        component: Component<T>,
+        period: T,
+        n_ticks: usize,
+    }
+
+    impl<T: Time> Atomic<T> for Clock<T> {
+        fn get_component(&self) -> &Component<T> {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component<T> {
+            &mut self.component
+        }
+
+        fn lambda(&self) {}
+
+        fn delta_int(&mut self) {
+            self.n_ticks += 1;
+        }
+
+        fn delta_ext(&mut self, _e: T) {}
+
+        fn ta(&self) -> T {
+            self.period
+        }
+    }
+
+    /// Returns the number of ticks of a clock with the given period before `t_end`.
+    fn n_ticks<T: Time>(period: T, t_end: T) -> usize
+    where
+        Clock<T>: Simulator<T>,
+    {
+        let mut simulator = RootCoordinator::new(Clock {
+            component: Component::new("clock"),
+            period,
+            n_ticks: 0,
+        });
+        simulator.simulate(t_end);
+        simulator.n_ticks
+    }
+
+    #[test]
+    fn test_fixed() {
+        type Millis = Fixed<3>;
+        let t = Millis::from_ticks(100);
+        let mut sum = Millis::ZERO;
+        (0..10).for_each(|_| sum = sum + t);
+        assert_eq!(Millis::from_units(1), sum);
+        assert_eq!("1.000", sum.to_string());
+        assert_eq!("-0.100", (Millis::ZERO - t).to_string());
+        assert_eq!(Millis::INFINITY, Millis::INFINITY + t);
+        assert_eq!(Millis::INFINITY, Millis::INFINITY - t);
+        assert!(sum < Millis::INFINITY);
+    }
+
+    #[test]
+    fn test_rational() {
+        let third = Rational::new(2, 6);
+        assert_eq!(Rational::new(-1, -3), third);
+        assert_eq!(Rational::from_integer(1), third + third + third);
+        assert_eq!("1/3", third.to_string());
+        assert_eq!("-1/3", (Rational::ZERO - third).to_string());
+        assert_eq!(Rational::INFINITY, Rational::INFINITY - third);
+        assert!(third < Rational::new(1, 2));
+        assert!(Rational::new(1_000_000, 1) < Rational::INFINITY);
+    }
+
+    #[test]
+    fn test_exact_simulation() {
+        // 0.1 cannot be represented exactly, so the tenth tick happens slightly before 1
+        assert_eq!(10, n_ticks(0.1, 1.));
+        assert_eq!(9, n_ticks(Fixed::<1>::from_ticks(1), Fixed::from_units(1)));
+        assert_eq!(9, n_ticks(Rational::new(1, 10), Rational::from_integer(1)));
+    }
+}
@@ -0,0 +1,368 @@
+mod csv;
+#[cfg(not(target_arch = "wasm32"))]
+mod monitor;
+#[cfg(not(target_arch = "wasm32"))]
+mod otlp;
+#[cfg(feature = "plot")]
+mod plot;
+mod recorder;
+#[cfg(feature = "server")]
+mod server;
+#[cfg(feature = "ws")]
+mod ws;
+
+pub use csv::CsvRecorder;
+#[cfg(not(target_arch = "wasm32"))]
+pub use monitor::Monitor;
+#[cfg(not(target_arch = "wasm32"))]
+pub use otlp::{OtlpClock, OtlpConfig, OtlpSink};
+pub use recorder::{Columns, PortRecorder, WindowStats};
+#[cfg(feature = "server")]
+pub use server::HttpServer;
+#[cfg(feature = "ws")]
+pub use ws::WsServer;
+
+use crate::modeling::port::Values;
+use crate::simulation::{Listener, PortValues, RunRecord};
+use crate::time::Time;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Write as _};
+use std::sync::{Arc, Mutex};
+
+/// Simulation phase that produced a [`TraceRecord`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TracePhase {
+    /// The model has been started.
+    Start,
+    /// A component generated output messages during the collection phase.
+    Output,
+    /// An atomic model triggered a state transition.
+    Transition,
+    /// The model has been stopped.
+    Stop,
+}
+
+impl fmt::Display for TracePhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let phase = match self {
+            Self::Start => "start",
+            Self::Output => "output",
+            Self::Transition => "transition",
+            Self::Stop => "stop",
+        };
+        write!(f, "{phase}")
+    }
+}
+
+/// Structured record of a simulation event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceRecord {
+    /// Simulation time of the event.
+    pub t: f64,
+    /// Path of the component in the model hierarchy (e.g., `"root.subcoupled.atomic"`).
+    pub path: String,
+    /// Simulation phase of the event.
+    pub phase: TracePhase,
+    /// Name of the output port. Only [`TracePhase::Output`] records have a port.
+    pub port: Option<String>,
+    /// Formatted messages of the port. It is empty if there is no formatter for the message type of the port.
+    pub values: Vec<String>,
+}
+
+impl fmt::Display for TraceRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[t={}] {} {}", self.t, self.phase, self.path)?;
+        if let Some(port) = &self.port {
+            write!(f, ".{port} [{}]", self.values.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl TraceRecord {
+    /// Returns the JSON representation of the record (e.g., `{"t":0,"path":"root.atomic","phase":"output",
+    /// "port":"output","values":["0"]}`). Non-finite times are represented as `null`,
+    /// and records without port omit the `port` and `values` fields.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\"t\":");
+        match self.t.is_finite() {
+            true => write!(json, "{}", self.t).unwrap(),
+            false => json.push_str("null"),
+        }
+        json.push_str(",\"path\":");
+        encode_string(&mut json, &self.path);
+        write!(json, ",\"phase\":\"{}\"", self.phase).unwrap();
+        if let Some(port) = &self.port {
+            json.push_str(",\"port\":");
+            encode_string(&mut json, port);
+            json.push_str(",\"values\":[");
+            for (i, value) in self.values.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                encode_string(&mut json, value);
+            }
+            json.push(']');
+        }
+        json.push('}');
+        json
+    }
+}
+
+/// Function that formats the messages of a port of a given type.
+type FormatFn = Arc<dyn Fn(&PortValues) -> Vec<String> + Send + Sync>;
+
+/// Formats the messages of output ports as strings for trace sinks and recorders.
+/// Ports do not require their messages to be printable, so message types must be registered beforehand.
+/// Messages of types without a formatter are not formatted.
+#[derive(Clone, Default)]
+pub struct ValueFormatter {
+    /// Formatting functions, indexed by the type of the storage of the messages.
+    formatters: HashMap<TypeId, FormatFn>,
+}
+
+impl ValueFormatter {
+    /// Creates a new formatter without any message type.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Formats messages of type `T` with their debug representation.
+    pub fn debug<T: Debug + 'static>(self) -> Self {
+        self.with(|v: &T| format!("{v:?}"))
+    }
+
+    /// Formats messages of type `T` with a custom function.
+    pub fn with<T: 'static>(mut self, f: impl Fn(&T) -> String + Send + Sync + 'static) -> Self {
+        let format: FormatFn = Arc::new(move |values: &PortValues| {
+            values.downcast::<T>().unwrap().iter().map(&f).collect()
+        });
+        self.formatters.insert(TypeId::of::<Values<T>>(), format);
+        self
+    }
+
+    /// Returns the formatted messages of a port.
+    /// It returns [`None`] if there is no formatter for the message type of the port.
+    pub fn format(&self, values: &PortValues) -> Option<Vec<String>> {
+        let format = self.formatters.get(&values.as_any().type_id())?;
+        Some(format(values))
+    }
+}
+
+impl Debug for ValueFormatter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ValueFormatter")
+            .field("n_types", &self.formatters.len())
+            .finish()
+    }
+}
+
+/// Interface for consuming [`TraceRecord`]s generated during a simulation.
+#[allow(unused_variables)]
+pub trait TraceSink {
+    /// Consumes a new trace record.
+    fn record(&mut self, record: &TraceRecord);
+
+    /// Consumes the metadata of the simulation run. It is called when the simulation stops,
+    /// right before the [`TracePhase::Stop`] record. By default, it is ignored.
+    fn run_record(&mut self, record: &RunRecord) {}
+
+    /// Called when the simulation stops. Sinks that buffer records must write them here.
+    fn flush(&mut self) {}
+}
+
+/// Trace sink that prints every record in the standard output.
+#[derive(Debug, Default)]
+pub struct StdoutSink;
+
+impl TraceSink for StdoutSink {
+    fn record(&mut self, record: &TraceRecord) {
+        println!("{record}");
+    }
+
+    fn run_record(&mut self, record: &RunRecord) {
+        println!("{record}");
+    }
+}
+
+/// Trace sink that stores every record in memory.
+/// Cloned sinks share the same buffer, so a clone can be kept to inspect the records after the simulation.
+#[derive(Debug, Default, Clone)]
+pub struct MemorySink {
+    records: Arc<Mutex<Vec<TraceRecord>>>,
+    run_record: Arc<Mutex<Option<RunRecord>>>,
+}
+
+impl MemorySink {
+    /// Creates a new, empty in-memory sink.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a copy of the records collected so far.
+    pub fn records(&self) -> Vec<TraceRecord> {
+        self.records.lock().unwrap().clone()
+    }
+
+    /// Returns a copy of the metadata of the last simulation run, if any.
+    pub fn run_record(&self) -> Option<RunRecord> {
+        self.run_record.lock().unwrap().clone()
+    }
+}
+
+impl TraceSink for MemorySink {
+    fn record(&mut self, record: &TraceRecord) {
+        self.records.lock().unwrap().push(record.clone());
+    }
+
+    fn run_record(&mut self, record: &RunRecord) {
+        *self.run_record.lock().unwrap() = Some(record.clone());
+    }
+}
+
+/// [`Listener`] that translates simulation events into [`TraceRecord`]s and forwards them to a set of sinks.
+pub struct Tracer {
+    /// Path of the root model. It is used for start and stop records.
+    root: String,
+    /// Sinks that receive the trace records.
+    sinks: Vec<Box<dyn TraceSink>>,
+    /// Formatter of the messages of output records. If [`None`], messages are not formatted.
+    formatter: Option<ValueFormatter>,
+}
+
+impl Tracer {
+    /// Creates a new tracer for the model with the given root path.
+    /// If `formatter` is [`None`], output records do not contain the messages of the port.
+    pub fn new(
+        root: &str,
+        sinks: Vec<Box<dyn TraceSink>>,
+        formatter: Option<ValueFormatter>,
+    ) -> Self {
+        Self {
+            root: root.to_string(),
+            sinks,
+            formatter,
+        }
+    }
+
+    /// Forwards a record to all the sinks.
+    fn record(&mut self, record: TraceRecord) {
+        self.sinks.iter_mut().for_each(|s| s.record(&record));
+    }
+}
+
+/// Times are converted to [`f64`] with [`Time::as_f64`].
+impl<T: Time> Listener<T> for Tracer {
+    fn on_run_record(&mut self, record: &RunRecord) {
+        self.sinks.iter_mut().for_each(|s| s.run_record(record));
+    }
+
+    fn on_start(&mut self, t_start: T) {
+        let path = self.root.clone();
+        self.record(TraceRecord {
+            t: t_start.as_f64(),
+            path,
+            phase: TracePhase::Start,
+            port: None,
+            values: Vec::new(),
+        });
+    }
+
+    fn on_output(&mut self, t: T, path: &str, port: &str, values: &PortValues) {
+        self.record(TraceRecord {
+            t: t.as_f64(),
+            path: path.to_string(),
+            phase: TracePhase::Output,
+            port: Some(port.to_string()),
+            values: (self.formatter.as_ref())
+                .and_then(|f| f.format(values))
+                .unwrap_or_default(),
+        });
+    }
+
+    fn on_transition(&mut self, t: T, path: &str) {
+        self.record(TraceRecord {
+            t: t.as_f64(),
+            path: path.to_string(),
+            phase: TracePhase::Transition,
+            port: None,
+            values: Vec::new(),
+        });
+    }
+
+    fn on_stop(&mut self, t_stop: T) {
+        let path = self.root.clone();
+        self.record(TraceRecord {
+            t: t_stop.as_f64(),
+            path,
+            phase: TracePhase::Stop,
+            port: None,
+            values: Vec::new(),
+        });
+        self.sinks.iter_mut().for_each(|s| s.flush());
+    }
+}
+
+/// Encodes a string as a JSON string literal.
+pub(crate) fn encode_string(json: &mut String, s: &str) {
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devstone::{TestProbe, LI};
+    use crate::simulation::RootCoordinator;
+
+    #[test]
+    fn test_memory_sink() {
+        let probe = Arc::new(Mutex::new(TestProbe::default()));
+        let sink = MemorySink::new();
+        let mut simulator = RootCoordinator::new(LI::create(2, 2, 0, 0, probe));
+        let formatter = ValueFormatter::new().debug::<usize>();
+        simulator.add_trace_sinks(vec![Box::new(sink.clone())], Some(formatter));
+        simulator.simulate(f64::INFINITY);
+
+        let records = sink.records();
+        assert_eq!(TracePhase::Start, records.first().unwrap().phase);
+        assert_eq!(TracePhase::Stop, records.last().unwrap().phase);
+        assert_eq!("LI", records.last().unwrap().path);
+        assert_eq!(Some(simulator.run_record()), sink.run_record());
+
+        let seeder = records
+            .iter()
+            .find(|r| r.phase == TracePhase::Output && r.path == "LI.seeder")
+            .unwrap();
+        assert_eq!(Some("output".to_string()), seeder.port);
+        assert_eq!(vec!["0".to_string()], seeder.values);
+        assert_eq!("[t=0] output LI.seeder.output [0]", seeder.to_string());
+        assert_eq!(
+            r#"{"t":0,"path":"LI.seeder","phase":"output","port":"output","values":["0"]}"#,
+            seeder.to_json()
+        );
+        assert_eq!(
+            r#"{"t":null,"path":"LI","phase":"stop"}"#,
+            records.last().unwrap().to_json()
+        );
+    }
+
+    #[test]
+    fn test_encode_string() {
+        let mut json = String::new();
+        encode_string(&mut json, "a\"b\\c\nd\u{1}");
+        assert_eq!(r#""a\"b\\c\nd\u0001""#, json);
+    }
+}
@@ -0,0 +1,132 @@
+use super::ValueFormatter;
+use crate::simulation::{Listener, PortValues};
+use crate::time::Time;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// [`Listener`] that writes the messages of selected output ports into a CSV file.
+/// Every message is written in a `t,path,port,value` row, where `value` is the message formatted by the
+/// [`ValueFormatter`] of the recorder. It is empty if the recorder has no formatter for the message type.
+/// If no port is subscribed, the recorder writes the messages of all the output ports.
+pub struct CsvRecorder {
+    /// Destination of the CSV rows.
+    writer: Box<dyn Write>,
+    /// Subscribed ports, identified by component path and port name.
+    ports: HashSet<(String, String)>,
+    /// Formatter of the messages. If [`None`], messages are not formatted.
+    formatter: Option<ValueFormatter>,
+}
+
+impl CsvRecorder {
+    /// Creates a new recorder that writes CSV rows to a new file in `path`.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+
+    /// Creates a new recorder that writes CSV rows to an arbitrary writer.
+    pub fn new<W: Write + 'static>(writer: W) -> Self {
+        Self {
+            writer: Box::new(writer),
+            ports: HashSet::new(),
+            formatter: None,
+        }
+    }
+
+    /// Sets the formatter of the messages written by the recorder.
+    pub fn formatter(mut self, formatter: ValueFormatter) -> Self {
+        self.formatter = Some(formatter);
+        self
+    }
+
+    /// Subscribes the recorder to the output port `port` of the component with path `path`
+    /// (e.g., `"root.subcoupled.atomic"`).
+    pub fn subscribe(mut self, path: &str, port: &str) -> Self {
+        self.ports.insert((path.to_string(), port.to_string()));
+        self
+    }
+
+    /// Returns `true` if the recorder must write the messages of the given port.
+    fn is_subscribed(&self, path: &str, port: &str) -> bool {
+        self.ports.is_empty() || self.ports.contains(&(path.to_string(), port.to_string()))
+    }
+
+    /// Writes a row in the CSV file. It panics if the writer fails.
+    fn write_row(&mut self, fields: [&str; 4]) {
+        let row = fields.map(escape).join(",");
+        writeln!(self.writer, "{row}").expect("failed to write CSV row");
+    }
+}
+
+/// Times are converted to [`f64`] with [`Time::as_f64`].
+impl<T: Time> Listener<T> for CsvRecorder {
+    fn on_start(&mut self, _t_start: T) {
+        self.write_row(["t", "path", "port", "value"]);
+    }
+
+    fn on_output(&mut self, t: T, path: &str, port: &str, values: &PortValues) {
+        if self.is_subscribed(path, port) {
+            let t = t.as_f64().to_string();
+            let values = (self.formatter.as_ref())
+                .and_then(|f| f.format(values))
+                .unwrap_or_else(|| vec![String::new(); values.len()]);
+            for value in values {
+                self.write_row([&t, path, port, &value]);
+            }
+        }
+    }
+
+    fn on_stop(&mut self, _t_stop: T) {
+        self.writer.flush().expect("failed to flush CSV file");
+    }
+}
+
+/// Quotes a CSV field if it contains commas, quotes, or line breaks.
+fn escape(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devstone::{TestProbe, LI};
+    use crate::simulation::RootCoordinator;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_csv_recorder() {
+        let file = std::env::temp_dir().join(format!("xdevs_csv_{}.csv", std::process::id()));
+        let probe = Arc::new(Mutex::new(TestProbe::default()));
+        let recorder = CsvRecorder::create(&file)
+            .unwrap()
+            .subscribe("LI.seeder", "output")
+            .subscribe("LI.coupled_2", "output")
+            .formatter(ValueFormatter::new().debug::<usize>());
+        let mut simulator = RootCoordinator::new(LI::create(2, 2, 0, 0, probe));
+        simulator.add_listener(Box::new(recorder));
+        simulator.simulate(f64::INFINITY);
+
+        let csv = std::fs::read_to_string(&file).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        let rows: Vec<_> = csv.lines().collect();
+        assert_eq!(
+            vec![
+                "t,path,port,value",
+                "0,LI.seeder,output,0",
+                "0,LI.coupled_2,output,1",
+            ],
+            rows
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!("plain", escape("plain"));
+        assert_eq!("\"(1, 2)\"", escape("(1, 2)"));
+        assert_eq!("\"say \"\"hi\"\"\"", escape("say \"hi\""));
+    }
+}
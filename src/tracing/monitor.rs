@@ -0,0 +1,243 @@
+use super::ValueFormatter;
+use crate::simulation::{Listener, PortValues};
+use crate::time::Time;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+/// Number of imminent components shown by the monitor.
+const MAX_IMMINENT: usize = 8;
+
+/// [`Listener`] that renders a live terminal dashboard of the simulation progress: simulated time
+/// versus wall-clock time, number of simulation cycles, transition throughput, the components that
+/// triggered a state transition in the last simulation cycle, and the most recent output events.
+///
+/// The dashboard is redrawn with ANSI escape sequences at a fixed refresh period, so the overhead
+/// of monitoring is low even for simulations with millions of cycles. It is useful for demos and
+/// long simulations that would otherwise run silently until completion.
+pub struct Monitor {
+    /// Destination of the dashboard.
+    writer: Box<dyn Write>,
+    /// Minimum time between two redraws.
+    refresh: Duration,
+    /// Maximum number of recent events shown.
+    capacity: usize,
+    /// Wall-clock time at which the simulation started.
+    start: Instant,
+    /// Wall-clock time of the last redraw, together with the number of transitions at that moment.
+    last_draw: (Instant, usize),
+    /// Transition throughput (transitions per second) since the previous redraw.
+    throughput: f64,
+    /// Simulation time of the last simulation cycle.
+    t: f64,
+    /// Number of simulation cycles.
+    n_cycles: usize,
+    /// Number of state transitions.
+    n_transitions: usize,
+    /// Paths of the components that triggered a state transition in the last simulation cycle.
+    imminent: Vec<String>,
+    /// Paths of the components that triggered a state transition in the current simulation cycle.
+    current: Vec<String>,
+    /// Most recent output events.
+    recent: VecDeque<String>,
+    /// Formatter of the messages of output events. If [`None`], only the number of messages is shown.
+    formatter: Option<ValueFormatter>,
+}
+
+impl Default for Monitor {
+    fn default() -> Self {
+        Self::new(io::stdout())
+    }
+}
+
+impl Monitor {
+    /// Creates a new monitor that renders the dashboard in an arbitrary writer
+    /// (e.g., [`io::stdout`]). By default, it redraws the dashboard every 100 milliseconds
+    /// and shows the 10 most recent output events.
+    pub fn new<W: Write + 'static>(writer: W) -> Self {
+        let now = Instant::now();
+        Self {
+            writer: Box::new(writer),
+            refresh: Duration::from_millis(100),
+            capacity: 10,
+            start: now,
+            last_draw: (now, 0),
+            throughput: 0.,
+            t: 0.,
+            n_cycles: 0,
+            n_transitions: 0,
+            imminent: Vec::new(),
+            current: Vec::new(),
+            recent: VecDeque::new(),
+            formatter: None,
+        }
+    }
+
+    /// Sets the minimum time between two redraws of the dashboard.
+    pub fn refresh(mut self, refresh: Duration) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    /// Sets the maximum number of recent output events shown in the dashboard.
+    pub fn recent_events(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Sets the formatter of the messages of the output events shown in the dashboard.
+    /// Without formatter, the dashboard only shows the number of messages of every output event.
+    pub fn formatter(mut self, formatter: ValueFormatter) -> Self {
+        self.formatter = Some(formatter);
+        self
+    }
+
+    /// Redraws the dashboard if the refresh period has elapsed since the last redraw.
+    fn maybe_draw(&mut self) {
+        if self.last_draw.0.elapsed() >= self.refresh {
+            self.draw();
+        }
+    }
+
+    /// Redraws the dashboard. It panics if the writer fails.
+    fn draw(&mut self) {
+        let now = Instant::now();
+        let (last, n_transitions) = self.last_draw;
+        let elapsed = now.duration_since(last).as_secs_f64();
+        if elapsed > 0. {
+            self.throughput = (self.n_transitions - n_transitions) as f64 / elapsed;
+        }
+        self.last_draw = (now, self.n_transitions);
+        let wall = now.duration_since(self.start).as_secs_f64();
+        let ratio = match wall > 0. {
+            true => format!("{:.3}", self.t / wall),
+            false => "-".to_string(),
+        };
+
+        let mut imminent = self.imminent[..self.imminent.len().min(MAX_IMMINENT)].join(", ");
+        if self.imminent.len() > MAX_IMMINENT {
+            imminent.push_str(&format!(" (+{} more)", self.imminent.len() - MAX_IMMINENT));
+        }
+        // we move the cursor to the top-left corner and clear the screen before drawing
+        let mut screen = String::from("\x1b[H\x1b[2J");
+        screen.push_str("xDEVS simulation monitor\n\n");
+        screen.push_str(&format!(
+            "Simulated time: {}\nWall-clock time: {wall:.3} s (simulated/wall ratio: {ratio})\n",
+            self.t
+        ));
+        screen.push_str(&format!(
+            "Cycles: {}\nTransitions: {} ({:.0} transitions/s)\n\n",
+            self.n_cycles, self.n_transitions, self.throughput
+        ));
+        screen.push_str(&format!(
+            "Imminent components: {imminent}\n\nRecent events:\n"
+        ));
+        for event in &self.recent {
+            screen.push_str(&format!("  {event}\n"));
+        }
+        self.writer
+            .write_all(screen.as_bytes())
+            .and_then(|_| self.writer.flush())
+            .expect("failed to draw monitor");
+    }
+}
+
+/// Times are converted to [`f64`] with [`Time::as_f64`].
+impl<T: Time> Listener<T> for Monitor {
+    fn on_start(&mut self, t_start: T) {
+        let now = Instant::now();
+        (self.start, self.last_draw, self.throughput) = (now, (now, 0), 0.);
+        (self.t, self.n_cycles, self.n_transitions) = (t_start.as_f64(), 0, 0);
+        self.imminent.clear();
+        self.current.clear();
+        self.recent.clear();
+        self.draw();
+    }
+
+    fn on_collection(&mut self, t: T) {
+        self.t = t.as_f64();
+        self.n_cycles += 1;
+        if !self.current.is_empty() {
+            std::mem::swap(&mut self.imminent, &mut self.current);
+            self.current.clear();
+        }
+        self.maybe_draw();
+    }
+
+    fn on_output(&mut self, t: T, path: &str, port: &str, values: &PortValues) {
+        if self.capacity > 0 {
+            if self.recent.len() == self.capacity {
+                self.recent.pop_front();
+            }
+            let values = match self.formatter.as_ref().and_then(|f| f.format(values)) {
+                Some(values) => format!("[{}]", values.join(", ")),
+                None => format!("({} messages)", values.len()),
+            };
+            let event = format!("[t={}] {path}.{port} {values}", t.as_f64());
+            self.recent.push_back(event);
+        }
+    }
+
+    fn on_transition(&mut self, _t: T, path: &str) {
+        self.n_transitions += 1;
+        self.current.push(path.to_string());
+    }
+
+    fn on_stop(&mut self, t_stop: T) {
+        if !self.current.is_empty() {
+            std::mem::swap(&mut self.imminent, &mut self.current);
+            self.current.clear();
+        }
+        if t_stop.as_f64().is_finite() {
+            self.t = t_stop.as_f64();
+        }
+        self.draw();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devstone::{TestProbe, LI};
+    use crate::simulation::RootCoordinator;
+    use std::sync::{Arc, Mutex};
+
+    /// Writer that keeps the last screen drawn by the monitor.
+    #[derive(Clone, Default)]
+    struct Screen(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Screen {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let mut screen = self.0.lock().unwrap();
+            if buf.starts_with(b"\x1b[H") {
+                screen.clear();
+            }
+            screen.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_monitor() {
+        let screen = Screen::default();
+        let monitor = (Monitor::new(screen.clone()).recent_events(1))
+            .formatter(ValueFormatter::new().debug::<usize>());
+        let probe = Arc::new(Mutex::new(TestProbe::default()));
+        let mut simulator = RootCoordinator::new(LI::create(3, 2, 0, 0, probe));
+        simulator.add_listener(Box::new(monitor));
+        simulator.simulate(f64::INFINITY);
+
+        let screen = String::from_utf8(screen.0.lock().unwrap().clone()).unwrap();
+        assert!(screen.starts_with("\x1b[H\x1b[2JxDEVS simulation monitor\n"));
+        assert!(screen.contains("Simulated time: 0\n"));
+        assert!(screen.contains("Cycles: 2\nTransitions: 7 ("));
+        let imminent =
+            "LI.coupled_2.coupled_1.inner_atomic, LI.coupled_2.atomic_1, LI.coupled_2.atomic_2";
+        assert!(screen.contains(&format!("Imminent components: {imminent}\n")));
+        assert!(screen.ends_with("Recent events:\n  [t=0] LI.coupled_2.output [1]\n"));
+    }
+}
@@ -0,0 +1,453 @@
+use super::{encode_string, TracePhase, TraceRecord, TraceSink};
+use crate::random::derive_seed;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Clock used for mapping trace records to the timestamps of OTLP spans.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OtlpClock {
+    /// Spans are stamped with the wall-clock time at which the records are received.
+    /// Use it for correlating simulations that run in different processes at the same time.
+    WallClock,
+    /// Spans are stamped with the simulation time of the records, mapped to `epoch_unix_nano + t * nanos_per_unit`.
+    /// Use it for correlating simulations that share the same simulation time.
+    Simulation {
+        epoch_unix_nano: u64,
+        nanos_per_unit: f64,
+    },
+}
+
+/// Configuration of an [`OtlpSink`].
+#[derive(Debug, Clone)]
+pub struct OtlpConfig {
+    /// Name of the service that generates the spans (i.e., the `service.name` resource attribute).
+    service_name: String,
+    /// Additional resource attributes.
+    attributes: Vec<(String, String)>,
+    /// Trace ID of all the spans.
+    trace_id: u128,
+    /// Span ID of the parent of the simulation span.
+    parent_span_id: Option<u64>,
+    /// Clock for stamping spans.
+    clock: OtlpClock,
+    /// Number of simulation cycles written in every batch.
+    batch_size: usize,
+}
+
+impl OtlpConfig {
+    /// Creates a new configuration for the given service name. By default, spans get a new random trace ID,
+    /// are stamped with the wall clock, and are written in batches of 512 simulation cycles.
+    pub fn new(service_name: &str) -> Self {
+        let salt = salt();
+        let trace_id = (derive_seed(salt, 0) as u128) << 64 | derive_seed(salt, 1) as u128;
+        Self {
+            service_name: service_name.to_string(),
+            attributes: Vec::new(),
+            trace_id,
+            parent_span_id: None,
+            clock: OtlpClock::WallClock,
+            batch_size: 512,
+        }
+    }
+
+    /// Adds a resource attribute (e.g., `"host.name"`).
+    pub fn with_attribute(mut self, key: &str, value: &str) -> Self {
+        self.attributes.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Sets the trace ID of the spans. Federated simulations running in different processes
+    /// must share the same trace ID to be shown in the same timeline.
+    pub fn with_trace_id(mut self, trace_id: u128) -> Self {
+        self.trace_id = trace_id;
+        self
+    }
+
+    /// Sets the span ID of the parent of the simulation span (e.g., a span of the process that
+    /// orchestrates a federated co-simulation).
+    pub fn with_parent_span(mut self, span_id: u64) -> Self {
+        self.parent_span_id = Some(span_id);
+        self
+    }
+
+    /// Sets the clock for stamping spans.
+    pub fn with_clock(mut self, clock: OtlpClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Sets the number of simulation cycles written in every batch. It panics if `batch_size` is 0.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        assert!(batch_size > 0, "batch size must be positive");
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Returns the trace ID of the spans.
+    #[inline]
+    pub fn trace_id(&self) -> u128 {
+        self.trace_id
+    }
+}
+
+/// Value of a span attribute.
+enum Value {
+    String(String),
+    Double(f64),
+}
+
+/// Span event (i.e., output messages of a component).
+struct Event {
+    time: u64,
+    name: &'static str,
+    attributes: Vec<(&'static str, Value)>,
+}
+
+/// Span that has not been exported yet.
+struct Span {
+    span_id: u64,
+    parent_span_id: Option<u64>,
+    name: String,
+    start: u64,
+    end: u64,
+    attributes: Vec<(&'static str, Value)>,
+    events: Vec<Event>,
+}
+
+/// [`TraceSink`] that exports trace records as OpenTelemetry spans in the OTLP/JSON format.
+/// The simulation is mapped to a root span, every simulation cycle is mapped to a child span,
+/// and every state transition is mapped to a child span of its cycle. Output messages are
+/// events of their cycle span. Consecutive records with the same simulation time belong to the same cycle.
+///
+/// Spans are written in batches, one `ExportTraceServiceRequest` per line, so files can be ingested
+/// by the OpenTelemetry Collector (e.g., with its `otlpjsonfile` receiver) or posted to any
+/// OTLP/HTTP endpoint that accepts JSON.
+pub struct OtlpSink {
+    /// Configuration of the sink.
+    config: OtlpConfig,
+    /// Destination of the batches.
+    writer: Box<dyn Write>,
+    /// Salt for generating span IDs that are unique across processes.
+    salt: u64,
+    /// Number of span IDs generated so far.
+    n_spans: u64,
+    /// Simulation span. It is exported when the simulation stops.
+    simulation: Option<Span>,
+    /// Current simulation cycle: its span and simulation time.
+    cycle: Option<(Span, f64)>,
+    /// Phase of the last record.
+    last_phase: Option<TracePhase>,
+    /// Last timestamp. It is used for records with non-finite simulation times.
+    last_time: u64,
+    /// Spans pending to be exported.
+    spans: Vec<Span>,
+    /// Number of simulation cycles pending to be exported.
+    n_cycles: usize,
+}
+
+impl OtlpSink {
+    /// Creates a new sink that writes batches to a new file in `path`.
+    pub fn create<P: AsRef<Path>>(path: P, config: OtlpConfig) -> io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?), config))
+    }
+
+    /// Creates a new sink that writes batches to an arbitrary writer.
+    pub fn new<W: Write + 'static>(writer: W, config: OtlpConfig) -> Self {
+        Self {
+            config,
+            writer: Box::new(writer),
+            salt: salt(),
+            n_spans: 0,
+            simulation: None,
+            cycle: None,
+            last_phase: None,
+            last_time: 0,
+            spans: Vec::new(),
+            n_cycles: 0,
+        }
+    }
+
+    /// Returns the timestamp (in nanoseconds since the Unix epoch) of a record with simulation time `t`.
+    fn timestamp(&mut self, t: f64) -> u64 {
+        let time = match self.config.clock {
+            OtlpClock::WallClock => Some(unix_nano()),
+            OtlpClock::Simulation {
+                epoch_unix_nano,
+                nanos_per_unit,
+            } => {
+                let delta = t * nanos_per_unit;
+                (delta.is_finite() && delta >= 0.)
+                    .then(|| epoch_unix_nano.saturating_add(delta as u64))
+            }
+        };
+        self.last_time = time.unwrap_or(self.last_time);
+        self.last_time
+    }
+
+    /// Creates a new span that starts and ends at `time`.
+    fn span(&mut self, name: String, parent_span_id: Option<u64>, time: u64) -> Span {
+        self.n_spans += 1;
+        Span {
+            // zero is not a valid span ID
+            span_id: derive_seed(self.salt, self.n_spans).max(1),
+            parent_span_id,
+            name,
+            start: time,
+            end: time,
+            attributes: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Closes the current simulation cycle and writes a batch if it is full.
+    fn close_cycle(&mut self) {
+        if let Some((span, _)) = self.cycle.take() {
+            if let Some(simulation) = self.simulation.as_mut() {
+                simulation.end = simulation.end.max(span.end);
+            }
+            self.spans.push(span);
+            self.n_cycles += 1;
+            if self.n_cycles >= self.config.batch_size {
+                self.write_batch();
+            }
+        }
+    }
+
+    /// Returns the span of the simulation cycle at time `t`, creating a new one if needed.
+    fn open_cycle(&mut self, t: f64, phase: TracePhase, time: u64) -> &mut Span {
+        let same_cycle = match (&self.cycle, self.last_phase) {
+            // outputs after transitions at the same time belong to a new cycle
+            (Some((_, tc)), Some(last)) => {
+                *tc == t && !(phase == TracePhase::Output && last == TracePhase::Transition)
+            }
+            _ => false,
+        };
+        if !same_cycle {
+            self.close_cycle();
+            let parent = self.simulation.as_ref().map(|s| s.span_id);
+            let mut span = self.span("cycle".to_string(), parent, time);
+            span.attributes.push(("xdevs.time", Value::Double(t)));
+            self.cycle = Some((span, t));
+        }
+        let (span, _) = self.cycle.as_mut().unwrap();
+        span.end = span.end.max(time);
+        span
+    }
+
+    /// Writes all the pending spans in a new line. It panics if the writer fails.
+    fn write_batch(&mut self) {
+        if !self.spans.is_empty() {
+            let batch = self.encode(&self.spans);
+            writeln!(self.writer, "{batch}").expect("failed to write OTLP spans");
+            self.spans.clear();
+            self.n_cycles = 0;
+        }
+    }
+
+    /// Encodes a list of spans as an `ExportTraceServiceRequest` in the OTLP/JSON format.
+    fn encode(&self, spans: &[Span]) -> String {
+        let mut resource = vec![("service.name", self.config.service_name.as_str())];
+        resource.extend(
+            self.config
+                .attributes
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_str())),
+        );
+        let resource: Vec<_> = resource
+            .into_iter()
+            .map(|(k, v)| (k, Value::String(v.to_string())))
+            .collect();
+
+        let mut json = String::new();
+        json.push_str(r#"{"resourceSpans":[{"resource":{"attributes":"#);
+        encode_attributes(&mut json, &resource);
+        json.push_str(r#"},"scopeSpans":[{"scope":{"name":"xdevs","version":""#);
+        json.push_str(env!("CARGO_PKG_VERSION"));
+        json.push_str(r#""},"spans":["#);
+        for (i, span) in spans.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            let _ = write!(
+                json,
+                r#"{{"traceId":"{:032x}","spanId":"{:016x}","#,
+                self.config.trace_id, span.span_id
+            );
+            if let Some(parent) = span.parent_span_id {
+                let _ = write!(json, r#""parentSpanId":"{parent:016x}","#);
+            }
+            json.push_str(r#""name":"#);
+            encode_string(&mut json, &span.name);
+            let _ = write!(
+                json,
+                r#","kind":1,"startTimeUnixNano":"{}","endTimeUnixNano":"{}","attributes":"#,
+                span.start, span.end
+            );
+            encode_attributes(&mut json, &span.attributes);
+            json.push_str(r#","events":["#);
+            for (j, event) in span.events.iter().enumerate() {
+                if j > 0 {
+                    json.push(',');
+                }
+                let _ = write!(json, r#"{{"timeUnixNano":"{}","name":"#, event.time);
+                encode_string(&mut json, event.name);
+                json.push_str(r#","attributes":"#);
+                encode_attributes(&mut json, &event.attributes);
+                json.push('}');
+            }
+            json.push_str("]}");
+        }
+        json.push_str("]}]}]}");
+        json
+    }
+}
+
+impl TraceSink for OtlpSink {
+    fn record(&mut self, record: &TraceRecord) {
+        let time = self.timestamp(record.t);
+        match record.phase {
+            TracePhase::Start => {
+                self.close_cycle();
+                let name = format!("simulation {}", record.path);
+                let parent = self.config.parent_span_id;
+                let mut span = self.span(name, parent, time);
+                span.attributes.extend([
+                    ("xdevs.model", Value::String(record.path.clone())),
+                    ("xdevs.t_start", Value::Double(record.t)),
+                ]);
+                self.simulation = Some(span);
+            }
+            TracePhase::Output => {
+                let event = Event {
+                    time,
+                    name: "output",
+                    attributes: vec![
+                        ("xdevs.path", Value::String(record.path.clone())),
+                        (
+                            "xdevs.port",
+                            Value::String(record.port.clone().unwrap_or_default()),
+                        ),
+                        ("xdevs.values", Value::String(record.values.join(", "))),
+                    ],
+                };
+                self.open_cycle(record.t, record.phase, time)
+                    .events
+                    .push(event);
+            }
+            TracePhase::Transition => {
+                let parent = self.open_cycle(record.t, record.phase, time).span_id;
+                let mut span = self.span(record.path.clone(), Some(parent), time);
+                span.attributes
+                    .push(("xdevs.path", Value::String(record.path.clone())));
+                self.spans.push(span);
+            }
+            TracePhase::Stop => {
+                self.close_cycle();
+                if let Some(mut span) = self.simulation.take() {
+                    span.end = span.end.max(time);
+                    span.attributes
+                        .push(("xdevs.t_stop", Value::Double(record.t)));
+                    self.spans.push(span);
+                }
+            }
+        }
+        self.last_phase = Some(record.phase);
+    }
+
+    fn flush(&mut self) {
+        self.close_cycle();
+        self.write_batch();
+        self.writer.flush().expect("failed to flush OTLP spans");
+    }
+}
+
+/// Returns the current wall-clock time in nanoseconds since the Unix epoch.
+fn unix_nano() -> u64 {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    elapsed.as_nanos() as u64
+}
+
+/// Returns a seed that is unique for every process and call, used for generating IDs.
+fn salt() -> u64 {
+    derive_seed(unix_nano(), std::process::id() as u64)
+}
+
+/// Encodes a list of attributes as an array of OTLP key-value pairs.
+fn encode_attributes(json: &mut String, attributes: &[(&str, Value)]) {
+    json.push('[');
+    for (i, (key, value)) in attributes.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        json.push_str(r#"{"key":"#);
+        encode_string(json, key);
+        match value {
+            Value::String(s) => {
+                json.push_str(r#","value":{"stringValue":"#);
+                encode_string(json, s);
+            }
+            // JSON does not support non-finite numbers, so OTLP encodes them as strings
+            Value::Double(d) if !d.is_finite() => {
+                let d = match *d {
+                    d if d.is_nan() => "NaN",
+                    d if d > 0. => "Infinity",
+                    _ => "-Infinity",
+                };
+                let _ = write!(json, r#","value":{{"doubleValue":"{d}""#);
+            }
+            Value::Double(d) => {
+                let _ = write!(json, r#","value":{{"doubleValue":{d}"#);
+            }
+        }
+        json.push_str("}}");
+    }
+    json.push(']');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devstone::{TestProbe, LI};
+    use crate::simulation::RootCoordinator;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_otlp_sink() {
+        let file = std::env::temp_dir().join(format!("xdevs_otlp_{}.json", std::process::id()));
+        let config = OtlpConfig::new("li")
+            .with_attribute("host.name", "lab \"01\"")
+            .with_trace_id(0xabc)
+            .with_parent_span(0x12)
+            .with_clock(OtlpClock::Simulation {
+                epoch_unix_nano: 1_000,
+                nanos_per_unit: 1e9,
+            })
+            .with_batch_size(1);
+        let sink = OtlpSink::create(&file, config).unwrap();
+        let probe = Arc::new(Mutex::new(TestProbe::default()));
+        let mut simulator = RootCoordinator::new(LI::create(2, 2, 0, 0, probe));
+        simulator.add_trace_sinks(vec![Box::new(sink)], None);
+        simulator.simulate(f64::INFINITY);
+
+        let json = std::fs::read_to_string(&file).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        let batches: Vec<_> = json.lines().collect();
+        // every batch contains one cycle, and the last batch contains the simulation span
+        assert!(batches.len() > 1);
+        for batch in &batches {
+            assert!(batch.starts_with(r#"{"resourceSpans":[{"resource":{"attributes":[{"key":"service.name","value":{"stringValue":"li"}},{"key":"host.name","value":{"stringValue":"lab \"01\""}}]}"#));
+            assert!(batch.contains(r#""traceId":"00000000000000000000000000000abc""#));
+        }
+        assert!(batches[0].contains(r#""name":"cycle""#));
+        assert!(batches[0].contains(r#""startTimeUnixNano":"1000""#));
+        assert!(batches[0].contains(r#"{"key":"xdevs.path","value":{"stringValue":"LI.seeder"}}"#));
+        let last = batches.last().unwrap();
+        assert!(last.contains(r#""parentSpanId":"0000000000000012","name":"simulation LI""#));
+        assert!(last.contains(r#"{"key":"xdevs.t_stop","value":{"doubleValue":"Infinity"}}"#));
+    }
+}
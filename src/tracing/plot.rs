@@ -0,0 +1,229 @@
+//! Quick-look charts of the series recorded by a [`PortRecorder`]. Charts are written as SVG by hand:
+//! the crate does not depend on `plotters`, so PNG output is not supported.
+
+use super::PortRecorder;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Width of the charts in pixels.
+const WIDTH: f64 = 800.;
+/// Height of the charts in pixels.
+const HEIGHT: f64 = 480.;
+/// Margins between the plot area and the borders of the chart (left, right, top, bottom).
+const MARGINS: (f64, f64, f64, f64) = (70., 20., 20., 50.);
+/// Colors of the series, in the order of [`PortRecorder::ports`] (cycled if there are more series).
+const COLORS: [&str; 8] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f",
+];
+
+/// Axis of a chart, with round bounds and tick spacing.
+struct Axis {
+    min: f64,
+    max: f64,
+    step: f64,
+}
+
+impl Axis {
+    /// Creates an axis that contains all the given values with about five ticks.
+    fn new(values: impl Iterator<Item = f64>) -> Self {
+        let (mut min, mut max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+            (lo.min(v), hi.max(v))
+        });
+        if min > max {
+            (min, max) = (0., 1.);
+        } else if min == max {
+            (min, max) = (min - 1., max + 1.);
+        }
+        let raw = (max - min) / 5.;
+        let magnitude = 10f64.powf(raw.log10().floor());
+        let step = [1., 2., 5., 10.]
+            .into_iter()
+            .map(|m| m * magnitude)
+            .find(|step| *step >= raw)
+            .unwrap();
+        Self {
+            min: (min / step).floor() * step,
+            max: (max / step).ceil() * step,
+            step,
+        }
+    }
+
+    /// Returns the values of the ticks of the axis.
+    fn ticks(&self) -> impl Iterator<Item = f64> + '_ {
+        let n = ((self.max - self.min) / self.step).round() as usize;
+        (0..=n).map(|i| self.min + i as f64 * self.step)
+    }
+
+    /// Returns the label of a tick, with as many decimals as the tick spacing requires.
+    fn label(&self, v: f64) -> String {
+        let decimals = (-self.step.log10().floor()).max(0.) as usize;
+        let label = format!("{v:.decimals$}");
+        match label
+            .trim_start_matches('-')
+            .chars()
+            .all(|c| c == '0' || c == '.')
+        {
+            true => label.trim_start_matches('-').to_string(),
+            false => label,
+        }
+    }
+
+    /// Maps a value of the axis to a pixel coordinate in `[from, to]`.
+    fn scale(&self, v: f64, from: f64, to: f64) -> f64 {
+        from + (v - self.min) / (self.max - self.min) * (to - from)
+    }
+}
+
+/// Escapes the special characters of a text for XML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+impl<V: Clone + Into<f64> + 'static> PortRecorder<V, f64> {
+    /// Writes a quick-look SVG chart of the recorded series in a new file in `path`
+    /// (e.g., `recorder.plot("queue_length.svg")`). See [`PortRecorder::to_svg`].
+    pub fn plot<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_svg())
+    }
+
+    /// Renders the recorded series as an SVG line chart, with a line per subscribed port and a legend.
+    /// Messages hold their value until the next message of the same port (i.e., lines are drawn as steps).
+    /// Non-finite times and values are skipped.
+    pub fn to_svg(&self) -> String {
+        let series: Vec<_> = (self.ports().into_iter())
+            .map(|(path, port)| {
+                let points: Vec<(f64, f64)> = (self.series(&path, &port).into_iter())
+                    .map(|(t, v)| (t, v.into()))
+                    .filter(|(t, v)| t.is_finite() && v.is_finite())
+                    .collect();
+                (format!("{path}.{port}"), points)
+            })
+            .collect();
+        let points = || series.iter().flat_map(|(_, points)| points.iter());
+        let (x, y) = (
+            Axis::new(points().map(|p| p.0)),
+            Axis::new(points().map(|p| p.1)),
+        );
+        let (left, right) = (MARGINS.0, WIDTH - MARGINS.1);
+        let (top, bottom) = (MARGINS.2, HEIGHT - MARGINS.3);
+
+        let mut svg = String::new();
+        // Writing to a string cannot fail
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" font-family="sans-serif" font-size="12">"#
+        );
+        let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+        for t in x.ticks() {
+            let px = x.scale(t, left, right);
+            let _ = writeln!(
+                svg,
+                r##"<line x1="{px:.1}" y1="{top}" x2="{px:.1}" y2="{bottom}" stroke="#e0e0e0"/><text x="{px:.1}" y="{:.1}" text-anchor="middle">{}</text>"##,
+                bottom + 18.,
+                x.label(t)
+            );
+        }
+        for v in y.ticks() {
+            let py = y.scale(v, bottom, top);
+            let _ = writeln!(
+                svg,
+                r##"<line x1="{left}" y1="{py:.1}" x2="{right}" y2="{py:.1}" stroke="#e0e0e0"/><text x="{:.1}" y="{:.1}" text-anchor="end">{}</text>"##,
+                left - 6.,
+                py + 4.,
+                y.label(v)
+            );
+        }
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{left}" y="{top}" width="{}" height="{}" fill="none" stroke="black"/>"#,
+            right - left,
+            bottom - top
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="middle">time</text>"#,
+            (left + right) / 2.,
+            HEIGHT - 10.
+        );
+        for (i, (name, points)) in series.iter().enumerate() {
+            let color = COLORS[i % COLORS.len()];
+            let mut line = Vec::with_capacity(2 * points.len());
+            for (j, (t, v)) in points.iter().enumerate() {
+                if j > 0 {
+                    line.push((*t, points[j - 1].1));
+                }
+                line.push((*t, *v));
+            }
+            let line: Vec<_> = (line.into_iter())
+                .map(|(t, v)| {
+                    format!(
+                        "{:.1},{:.1}",
+                        x.scale(t, left, right),
+                        y.scale(v, bottom, top)
+                    )
+                })
+                .collect();
+            if !line.is_empty() {
+                let _ = writeln!(
+                    svg,
+                    r#"<polyline points="{}" fill="none" stroke="{color}" stroke-width="1.5"/>"#,
+                    line.join(" ")
+                );
+            }
+            let ly = top + 16. * (i + 1) as f64;
+            let _ = writeln!(
+                svg,
+                r#"<text x="{:.1}" y="{ly:.1}" text-anchor="end" fill="{color}">{}</text>"#,
+                right - 8.,
+                escape(name)
+            );
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::Coupled;
+    use crate::scenario::{ScenarioPlayer, ScheduledEvent};
+    use crate::simulation::RootCoordinator;
+
+    #[test]
+    fn test_plot() {
+        let schedule = [(0., 1.), (2., 3.5), (4., -1.)]
+            .into_iter()
+            .map(|(t, v)| ScheduledEvent::new(t, "output", v))
+            .collect();
+        let mut coupled = Coupled::new("root");
+        coupled.add_component(Box::new(ScenarioPlayer::new("player", schedule)));
+        let recorder = (PortRecorder::<f64>::new())
+            .subscribe("root.player", "output")
+            .subscribe("root.idle", "output");
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.add_listener(Box::new(recorder.clone()));
+        simulator.simulate(f64::INFINITY);
+
+        let svg = recorder.to_svg();
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+        // only the player sent messages, but both ports appear in the legend
+        assert_eq!(1, svg.matches("<polyline").count());
+        assert!(svg.contains(">root.player.output</text>"));
+        assert!(svg.contains(">root.idle.output</text>"));
+        // the step line has a vertical segment at every message after the first one
+        let points = svg.split("points=\"").nth(1).unwrap().split('"').next();
+        assert_eq!(5, points.unwrap().split(' ').count());
+        // y axis ticks are round numbers that cover all the values
+        assert!(svg.contains(">-1</text>") && svg.contains(">4</text>"));
+
+        let path = std::env::temp_dir().join("xdevs_test_plot.svg");
+        recorder.plot(&path).unwrap();
+        assert_eq!(svg, fs::read_to_string(&path).unwrap());
+        fs::remove_file(path).unwrap();
+    }
+}
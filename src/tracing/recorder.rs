@@ -0,0 +1,328 @@
+use crate::simulation::{Listener, PortValues};
+use crate::time::Time;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Recorded messages of every subscribed port, identified by component path and port name.
+type Series<V, T> = HashMap<(String, String), Vec<(T, V)>>;
+
+/// Summary of the messages recorded in a time window by a [`PortRecorder`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowStats {
+    /// Number of messages in the window.
+    pub count: usize,
+    /// Minimum value.
+    pub min: f64,
+    /// Maximum value.
+    pub max: f64,
+    /// Mean value (every message has the same weight, regardless of how long it holds).
+    pub mean: f64,
+}
+
+/// Messages recorded by a [`PortRecorder`] in a columnar layout: the `i`-th row is made of the `i`-th
+/// element of every column. xDEVS does not depend on any data frame library: the columns are plain vectors
+/// that callers can load into the library of their choice.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Columns<V, T: Time = f64> {
+    /// Path of the component that sent every message.
+    pub component: Vec<String>,
+    /// Name of the port that sent every message.
+    pub port: Vec<String>,
+    /// Simulation time of every message.
+    pub time: Vec<T>,
+    /// Messages.
+    pub value: Vec<V>,
+}
+
+impl<V, T: Time> Columns<V, T> {
+    /// Returns the number of rows.
+    pub fn len(&self) -> usize {
+        self.time.len()
+    }
+
+    /// Returns `true` if there are no rows.
+    pub fn is_empty(&self) -> bool {
+        self.time.is_empty()
+    }
+}
+
+/// [`Listener`] that stores in memory the messages of type `V` sent through selected output ports,
+/// together with their simulation times, so they can be queried after the simulation.
+/// Messages of other types are ignored. Cloned recorders share the same buffer, so a clone can be kept
+/// to inspect the results after the simulation.
+#[derive(Debug)]
+pub struct PortRecorder<V, T: Time = f64> {
+    /// Recorded messages. Subscribed ports are always present, even if they did not send any message.
+    series: Arc<Mutex<Series<V, T>>>,
+}
+
+impl<V, T: Time> Clone for PortRecorder<V, T> {
+    fn clone(&self) -> Self {
+        Self {
+            series: self.series.clone(),
+        }
+    }
+}
+
+impl<V: Clone + 'static, T: Time> Default for PortRecorder<V, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: Clone + 'static, T: Time> PortRecorder<V, T> {
+    /// Creates a new recorder without subscribed ports.
+    pub fn new() -> Self {
+        Self {
+            series: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Subscribes the recorder to the output port `port` of the component with path `path`
+    /// (e.g., `"root.subcoupled.atomic"`).
+    pub fn subscribe(self, path: &str, port: &str) -> Self {
+        let key = (path.to_string(), port.to_string());
+        self.series.lock().unwrap().insert(key, Vec::new());
+        self
+    }
+
+    /// Returns the messages sent through a port and their simulation times, in chronological order.
+    /// It panics if the recorder is not subscribed to the port.
+    pub fn series(&self, path: &str, port: &str) -> Vec<(T, V)> {
+        self.with_series(path, port, |s| s.to_vec())
+    }
+
+    /// Returns the messages sent through a port, without their simulation times.
+    /// It panics if the recorder is not subscribed to the port.
+    pub fn values(&self, path: &str, port: &str) -> Vec<V> {
+        self.with_series(path, port, |s| s.iter().map(|(_, v)| v.clone()).collect())
+    }
+
+    /// Returns the last message sent through a port before time `t` (inclusive), if any.
+    /// It panics if the recorder is not subscribed to the port.
+    pub fn value_at(&self, path: &str, port: &str, t: T) -> Option<V> {
+        self.with_series(path, port, |s| {
+            let n = s.partition_point(|(t_msg, _)| *t_msg <= t);
+            n.checked_sub(1).map(|i| s[i].1.clone())
+        })
+    }
+
+    /// Returns the messages of all the subscribed ports in a columnar layout, sorted by simulation time.
+    /// Messages sent at the same time are sorted by component path and port name, and then in order of arrival.
+    pub fn columns(&self) -> Columns<V, T> {
+        let series = self.series.lock().unwrap();
+        let mut rows: Vec<_> = (series.iter())
+            .flat_map(|((path, port), s)| s.iter().map(move |(t, v)| (*t, path, port, v)))
+            .collect();
+        rows.sort_by(|a, b| (a.0.total_cmp(&b.0)).then_with(|| (a.1, a.2).cmp(&(b.1, b.2))));
+        let mut columns = Columns {
+            component: Vec::with_capacity(rows.len()),
+            port: Vec::with_capacity(rows.len()),
+            time: Vec::with_capacity(rows.len()),
+            value: Vec::with_capacity(rows.len()),
+        };
+        for (t, path, port, v) in rows {
+            columns.component.push(path.clone());
+            columns.port.push(port.clone());
+            columns.time.push(t);
+            columns.value.push(v.clone());
+        }
+        columns
+    }
+
+    /// Returns the subscribed ports as pairs of component path and port name, sorted by path and name.
+    pub fn ports(&self) -> Vec<(String, String)> {
+        let mut ports: Vec<_> = self.series.lock().unwrap().keys().cloned().collect();
+        ports.sort();
+        ports
+    }
+
+    /// Calls `f` with the messages of a port. It panics if the recorder is not subscribed to the port.
+    fn with_series<R>(&self, path: &str, port: &str, f: impl FnOnce(&[(T, V)]) -> R) -> R {
+        let series = self.series.lock().unwrap();
+        match series.get(&(path.to_string(), port.to_string())) {
+            Some(s) => f(s),
+            None => panic!("recorder is not subscribed to port {port} of {path}"),
+        }
+    }
+}
+
+impl<V: Clone + Into<f64> + 'static, T: Time> PortRecorder<V, T> {
+    /// Returns the number, minimum, maximum, and mean of the messages sent through a port
+    /// in the time window `[from, to)`, or [`None`] if there are no messages in the window.
+    /// It panics if the recorder is not subscribed to the port.
+    pub fn window(&self, path: &str, port: &str, from: T, to: T) -> Option<WindowStats> {
+        self.with_series(path, port, |s| {
+            let values = (s.iter())
+                .filter(|(t, _)| from <= *t && *t < to)
+                .map(|(_, v)| v.clone().into());
+            window_stats(values)
+        })
+    }
+}
+
+impl<V: Clone + 'static> PortRecorder<V, f64> {
+    /// Resamples the messages of a port with a zero-order hold: it returns the last message sent
+    /// at or before every time `start + k * period` up to `end` (inclusive).
+    /// Sampling times before the first message are skipped.
+    /// It panics if the period is not positive or if the recorder is not subscribed to the port.
+    pub fn resample(
+        &self,
+        path: &str,
+        port: &str,
+        start: f64,
+        period: f64,
+        end: f64,
+    ) -> Vec<(f64, V)> {
+        assert!(period > 0., "resampling period must be positive");
+        self.with_series(path, port, |s| {
+            let mut samples = Vec::new();
+            let mut i = 0;
+            let mut held = None;
+            for t in (0..)
+                .map(|k| start + k as f64 * period)
+                .take_while(|t| *t <= end)
+            {
+                while i < s.len() && s[i].0 <= t {
+                    held = Some(&s[i].1);
+                    i += 1;
+                }
+                if let Some(v) = held {
+                    samples.push((t, v.clone()));
+                }
+            }
+            samples
+        })
+    }
+}
+
+impl<V: Clone + Into<f64> + 'static> PortRecorder<V, f64> {
+    /// Splits `[start, end)` into consecutive windows of the given width and returns the start time and
+    /// the summary of every window with messages (see [`PortRecorder::window`]).
+    /// It panics if the width is not positive or if the recorder is not subscribed to the port.
+    pub fn windows(
+        &self,
+        path: &str,
+        port: &str,
+        start: f64,
+        width: f64,
+        end: f64,
+    ) -> Vec<(f64, WindowStats)> {
+        assert!(width > 0., "window width must be positive");
+        (0..)
+            .map(|k| start + k as f64 * width)
+            .take_while(|t| *t < end)
+            .filter_map(|t| {
+                let stats = self.window(path, port, t, (t + width).min(end))?;
+                Some((t, stats))
+            })
+            .collect()
+    }
+}
+
+impl<V: Clone + Send + 'static, T: Time> Listener<T> for PortRecorder<V, T> {
+    fn on_start(&mut self, _t_start: T) {
+        self.series
+            .lock()
+            .unwrap()
+            .values_mut()
+            .for_each(Vec::clear);
+    }
+
+    fn on_output(&mut self, t: T, path: &str, port: &str, values: &PortValues) {
+        let mut series = self.series.lock().unwrap();
+        if let Some(s) = series.get_mut(&(path.to_string(), port.to_string())) {
+            if let Some(values) = values.downcast::<V>() {
+                s.extend(values.iter().map(|v| (t, v.clone())));
+            }
+        }
+    }
+}
+
+/// Computes the summary of a sequence of values, or [`None`] if it is empty.
+fn window_stats(values: impl Iterator<Item = f64>) -> Option<WindowStats> {
+    let mut stats: Option<WindowStats> = None;
+    for v in values {
+        stats = Some(match stats {
+            None => WindowStats {
+                count: 1,
+                min: v,
+                max: v,
+                mean: v,
+            },
+            Some(s) => WindowStats {
+                count: s.count + 1,
+                min: s.min.min(v),
+                max: s.max.max(v),
+                mean: s.mean + (v - s.mean) / (s.count + 1) as f64,
+            },
+        });
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::Coupled;
+    use crate::scenario::{ScenarioPlayer, ScheduledEvent};
+    use crate::simulation::RootCoordinator;
+
+    #[test]
+    fn test_port_recorder() {
+        let schedule = [(0.5, 1.), (1., 4.), (1., 2.), (2.5, 3.)]
+            .into_iter()
+            .map(|(t, v)| ScheduledEvent::new(t, "output", v))
+            .collect();
+        let mut coupled = Coupled::new("root");
+        coupled.add_component(Box::new(ScenarioPlayer::new("player", schedule)));
+        let recorder = PortRecorder::<f64>::new().subscribe("root.player", "output");
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.add_listener(Box::new(recorder.clone()));
+        simulator.simulate(f64::INFINITY);
+
+        let series = recorder.series("root.player", "output");
+        assert_eq!(vec![(0.5, 1.), (1., 4.), (1., 2.), (2.5, 3.)], series);
+        assert_eq!(None, recorder.value_at("root.player", "output", 0.));
+        assert_eq!(Some(2.), recorder.value_at("root.player", "output", 2.));
+        assert_eq!(
+            vec![(1., 2.), (2., 2.), (3., 3.)],
+            recorder.resample("root.player", "output", 0., 1., 3.)
+        );
+        let stats = recorder.window("root.player", "output", 0., 2.).unwrap();
+        assert_eq!((3, 1., 4.), (stats.count, stats.min, stats.max));
+        assert!((stats.mean - 7. / 3.).abs() < 1e-12);
+        let windows = recorder.windows("root.player", "output", 0., 1., 4.);
+        let starts: Vec<_> = windows.iter().map(|(t, s)| (*t, s.count)).collect();
+        assert_eq!(vec![(0., 1), (1., 2), (2., 1)], starts);
+    }
+
+    #[test]
+    fn test_columns() {
+        let schedule = |values: &[(f64, f64)]| {
+            (values.iter())
+                .map(|(t, v)| ScheduledEvent::new(*t, "output", *v))
+                .collect()
+        };
+        let mut coupled = Coupled::new("root");
+        let b = ScenarioPlayer::new("b", schedule(&[(1., 10.), (1., 11.), (3., 12.)]));
+        coupled.add_component(Box::new(b));
+        coupled.add_component(Box::new(ScenarioPlayer::new("a", schedule(&[(1., 0.)]))));
+        let recorder = (PortRecorder::<f64>::new())
+            .subscribe("root.a", "output")
+            .subscribe("root.b", "output");
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.add_listener(Box::new(recorder.clone()));
+        simulator.simulate(f64::INFINITY);
+
+        let columns = recorder.columns();
+        assert_eq!(4, columns.len());
+        assert_eq!(
+            vec!["root.a", "root.b", "root.b", "root.b"],
+            columns.component
+        );
+        assert_eq!(vec!["output"; 4], columns.port);
+        assert_eq!(vec![1., 1., 1., 3.], columns.time);
+        assert_eq!(vec![0., 10., 11., 12.], columns.value);
+    }
+}
@@ -0,0 +1,440 @@
+use super::{encode_string, TracePhase, TraceRecord, TraceSink};
+use crate::simulation::{SimulationHandle, Simulator};
+use crate::time::Time;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Maximum time that reading a request or writing a response may take.
+const TIMEOUT: Duration = Duration::from_secs(1);
+/// Maximum length of the request line and of every header line, in bytes.
+const MAX_LINE: usize = 8 * 1024;
+/// Maximum number of header lines of a request.
+const MAX_HEADERS: usize = 64;
+/// Maximum length of the body of a request, in bytes.
+const MAX_BODY: usize = 64 * 1024;
+
+/// Function that parses the body of an injection request and sends the message to an input port.
+type Injector = Box<dyn Fn(&str) -> Result<(), String> + Send>;
+
+/// State of the simulation shared between an [`HttpServer`] and its background thread.
+struct State {
+    /// Time of the last trace record, or [`None`] if the simulation did not start.
+    t: Option<f64>,
+    /// It is `true` while the simulation is running.
+    running: bool,
+    /// JSON representation of the model hierarchy, if any.
+    model: Option<String>,
+    /// JSON representation of the most recent trace records.
+    events: VecDeque<String>,
+    /// Maximum number of recent trace records.
+    capacity: usize,
+    /// Handle for controlling the simulation, if any.
+    handle: Option<SimulationHandle>,
+    /// Injectors of the input ports of the root model.
+    inputs: HashMap<String, Injector>,
+}
+
+/// HTTP response: status line and JSON body.
+type Response = (&'static str, String);
+
+/// [`TraceSink`] that runs an HTTP server for inspecting and controlling a running simulation
+/// (e.g., a digital twin that runs in real time).
+/// Requests are served by a background thread until the server is dropped, and responses are JSON documents:
+///
+/// - `GET /time`: current simulation time, whether the simulation is running, and, if the server has
+///   a [`SimulationHandle`], whether it is paused and the number of executed cycles.
+/// - `GET /model`: model hierarchy, with the name, input ports, output ports, and subcomponents of every component.
+/// - `GET /events`: most recent trace records (see [`TraceRecord::to_json`]), from oldest to newest.
+/// - `POST /pause`, `POST /resume`, `POST /step`, and `POST /abort`: forwarded to the [`SimulationHandle`].
+/// - `POST /inputs/<port>`: injects the message in the request body into an input port of the root model.
+///
+/// Requests are served one at a time, so long requests delay the rest. Request and header lines are limited
+/// to 8 KiB, and bodies to 64 KiB: larger requests are rejected with status 431 or 413, respectively.
+pub struct HttpServer {
+    /// Address of the server.
+    addr: SocketAddr,
+    /// State shared with the background thread.
+    state: Arc<Mutex<State>>,
+    /// It is set to `true` when the server is dropped to stop serving requests.
+    closed: Arc<AtomicBool>,
+}
+
+impl HttpServer {
+    /// Creates a new server listening on the given address (e.g., `"127.0.0.1:8080"`).
+    /// By default, it keeps the 100 most recent trace records.
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State {
+            t: None,
+            running: false,
+            model: None,
+            events: VecDeque::new(),
+            capacity: 100,
+            handle: None,
+            inputs: HashMap::new(),
+        }));
+        let closed = Arc::new(AtomicBool::new(false));
+        let (s, cl) = (state.clone(), closed.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if cl.load(Ordering::SeqCst) {
+                    break;
+                }
+                // failed connections are ignored
+                if let Ok(stream) = stream {
+                    let _ = serve(stream, &s);
+                }
+            }
+        });
+        Ok(Self {
+            addr,
+            state,
+            closed,
+        })
+    }
+
+    /// Returns the address of the server. It is useful when binding to port 0.
+    #[inline]
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Sets the maximum number of recent trace records returned by `GET /events`.
+    pub fn recent_events(self, capacity: usize) -> Self {
+        self.state.lock().unwrap().capacity = capacity;
+        self
+    }
+
+    /// Sets the model whose hierarchy is returned by `GET /model`.
+    pub fn model<T: Time>(self, model: &dyn Simulator<T>) -> Self {
+        let mut json = String::new();
+        encode_model(&mut json, model);
+        self.state.lock().unwrap().model = Some(json);
+        self
+    }
+
+    /// Sets the handle that serves the `pause`, `resume`, `step`, and `abort` requests
+    /// (see [`crate::simulation::RootCoordinator::handle`]).
+    pub fn handle(self, handle: SimulationHandle) -> Self {
+        self.state.lock().unwrap().handle = Some(handle);
+        self
+    }
+
+    /// Serves the `POST /inputs/<port>` requests with the given injector. The injector receives the trimmed
+    /// request body, and returns an error message if the message is invalid or could not be injected.
+    pub fn input<F>(self, port: &str, injector: F) -> Self
+    where
+        F: Fn(&str) -> Result<(), String> + Send + 'static,
+    {
+        let mut state = self.state.lock().unwrap();
+        state.inputs.insert(port.to_string(), Box::new(injector));
+        drop(state);
+        self
+    }
+
+    /// Serves the `POST /inputs/<port>` requests by parsing the request body with [`std::str::FromStr`]
+    /// and sending the message with an `EventSender` of an `AsyncRootCoordinator`.
+    #[cfg(feature = "async")]
+    pub fn sender_input<V>(self, port: &str, sender: crate::simulation::EventSender) -> Self
+    where
+        V: crate::DynRef + Clone + Send + std::str::FromStr,
+        V::Err: std::fmt::Display,
+    {
+        let port_name = port.to_string();
+        self.input(port, move |body| {
+            let value = body.parse::<V>().map_err(|e| e.to_string())?;
+            sender.send(&port_name, value).map_err(|e| e.to_string())
+        })
+    }
+}
+
+impl TraceSink for HttpServer {
+    fn record(&mut self, record: &TraceRecord) {
+        let mut state = self.state.lock().unwrap();
+        match record.phase {
+            TracePhase::Start => state.running = true,
+            TracePhase::Stop => state.running = false,
+            _ => (),
+        }
+        if record.t.is_finite() {
+            state.t = Some(record.t);
+        }
+        state.events.push_back(record.to_json());
+        while state.events.len() > state.capacity {
+            state.events.pop_front();
+        }
+    }
+}
+
+impl Drop for HttpServer {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::SeqCst);
+        // wakes up the background thread so it notices that the server is closed
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+/// Reads a request from a client, and writes the response.
+/// Requests with overlong lines, too many headers, or overlong bodies are rejected without reading them.
+fn serve(mut stream: TcpStream, state: &Mutex<State>) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let request = read_request(&mut reader)?;
+    let (status, json) = match &request {
+        Ok((request, body)) => match request.split_whitespace().collect::<Vec<_>>()[..] {
+            [method, target, _] => {
+                let path = target.split_once('?').map_or(target, |(path, _)| path);
+                respond(method, path, body.trim(), &state.lock().unwrap())
+            }
+            _ => error("400 Bad Request", "invalid request line"),
+        },
+        Err(response) => response.clone(),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{json}",
+        json.len()
+    )?;
+    if request.is_err() {
+        // the rest of a rejected request is discarded (up to a limit), so closing the connection
+        // does not reset it before the client reads the response
+        stream.shutdown(Shutdown::Write)?;
+        io::copy(&mut reader.take(2 * MAX_BODY as u64), &mut io::sink())?;
+    }
+    Ok(())
+}
+
+/// Reads the request line and the body of a request. If the request exceeds the limits of the server,
+/// it returns the error response instead.
+fn read_request(reader: &mut impl BufRead) -> io::Result<Result<(String, String), Response>> {
+    const TOO_LARGE: &str = "431 Request Header Fields Too Large";
+    let Some(request) = read_line(reader)? else {
+        return Ok(Err(error(TOO_LARGE, "request line too long")));
+    };
+    let mut content_length = 0;
+    for i in 0.. {
+        if i == MAX_HEADERS {
+            return Ok(Err(error(TOO_LARGE, "too many header fields")));
+        }
+        let Some(line) = read_line(reader)? else {
+            return Ok(Err(error(TOO_LARGE, "header field too long")));
+        };
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    if content_length > MAX_BODY {
+        return Ok(Err(error("413 Payload Too Large", "request body too long")));
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(Ok((request, String::from_utf8_lossy(&body).into_owned())))
+}
+
+/// Reads a line of at most [`MAX_LINE`] bytes. It returns [`None`] if the line is longer.
+/// At the end of the stream, it returns an empty line.
+fn read_line(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut line = Vec::new();
+    let n = reader.take(MAX_LINE as u64).read_until(b'\n', &mut line)?;
+    match n == MAX_LINE && !line.ends_with(b"\n") {
+        true => Ok(None),
+        false => Ok(Some(String::from_utf8_lossy(&line).into_owned())),
+    }
+}
+
+/// Computes the response to a request.
+fn respond(method: &str, path: &str, body: &str, state: &State) -> Response {
+    const OK: &str = "200 OK";
+    let path = path.trim_end_matches('/');
+    match (method, path) {
+        ("GET", "/time") => {
+            let mut json = String::from("{\"t\":");
+            match state.t {
+                Some(t) => write!(json, "{t}").unwrap(),
+                None => json.push_str("null"),
+            }
+            write!(json, ",\"running\":{}", state.running).unwrap();
+            if let Some(handle) = &state.handle {
+                write!(
+                    json,
+                    ",\"paused\":{},\"cycles\":{}",
+                    handle.is_paused(),
+                    handle.cycles()
+                )
+                .unwrap();
+            }
+            json.push('}');
+            (OK, json)
+        }
+        ("GET", "/model") => match &state.model {
+            Some(model) => (OK, model.clone()),
+            None => error("404 Not Found", "model not available"),
+        },
+        ("GET", "/events") => {
+            let events: Vec<_> = state.events.iter().map(String::as_str).collect();
+            (OK, format!("[{}]", events.join(",")))
+        }
+        ("POST", "/pause" | "/resume" | "/step" | "/abort") => {
+            let Some(handle) = &state.handle else {
+                return error("404 Not Found", "simulation control not available");
+            };
+            match path {
+                "/pause" => handle.pause(),
+                "/resume" => handle.resume(),
+                "/step" => handle.step(),
+                _ => handle.abort(),
+            }
+            (OK, format!("{{\"paused\":{}}}", handle.is_paused()))
+        }
+        ("POST", _) if path.starts_with("/inputs/") => {
+            let port = &path["/inputs/".len()..];
+            match state.inputs.get(port) {
+                Some(injector) => match injector(body) {
+                    Ok(()) => (OK, "{}".to_string()),
+                    Err(e) => error("400 Bad Request", &e),
+                },
+                None => error("404 Not Found", &format!("unknown input port {port}")),
+            }
+        }
+        (_, "/time" | "/model" | "/events" | "/pause" | "/resume" | "/step" | "/abort") => {
+            error("405 Method Not Allowed", "method not allowed")
+        }
+        _ => error("404 Not Found", "unknown resource"),
+    }
+}
+
+/// Builds an error response.
+fn error(status: &'static str, message: &str) -> Response {
+    let mut json = String::from("{\"error\":");
+    encode_string(&mut json, message);
+    json.push('}');
+    (status, json)
+}
+
+/// Encodes the hierarchy of a model as a JSON object.
+fn encode_model<T: Time>(json: &mut String, model: &dyn Simulator<T>) {
+    let component = model.get_component();
+    json.push_str("{\"name\":");
+    encode_string(json, model.get_name());
+    for (key, ports) in [
+        ("in_ports", component.get_in_ports()),
+        ("out_ports", component.get_out_ports()),
+    ] {
+        write!(json, ",\"{key}\":[").unwrap();
+        for (i, port) in ports.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            encode_string(json, port.get_name());
+        }
+        json.push(']');
+    }
+    json.push_str(",\"components\":[");
+    for (i, child) in model.get_components().into_iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        encode_model(json, child);
+    }
+    json.push_str("]}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devstone::{TestProbe, LI};
+    use crate::simulation::RootCoordinator;
+
+    /// Sends a request to the server and returns the status code and body of the response.
+    fn request(addr: SocketAddr, method: &str, path: &str, body: &str) -> (u16, String) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+        (status, body.to_string())
+    }
+
+    #[test]
+    fn test_http_server() {
+        let probe = Arc::new(Mutex::new(TestProbe::default()));
+        let model = LI::create(2, 2, 0, 0, probe);
+        let injected = Arc::new(Mutex::new(Vec::new()));
+        let inj = injected.clone();
+        let server = (HttpServer::bind("127.0.0.1:0").unwrap())
+            .recent_events(2)
+            .model(&model)
+            .input("input", move |body| {
+                let value = body.parse::<usize>().map_err(|e| e.to_string())?;
+                inj.lock().unwrap().push(value);
+                Ok(())
+            });
+        let mut simulator = RootCoordinator::new(model);
+        let server = server.handle(simulator.handle());
+        let addr = server.local_addr();
+
+        assert_eq!(
+            (
+                200,
+                r#"{"t":null,"running":false,"paused":false,"cycles":0}"#.to_string()
+            ),
+            request(addr, "GET", "/time", "")
+        );
+        let (status, model) = request(addr, "GET", "/model", "");
+        assert_eq!(200, status);
+        assert!(model.starts_with(
+            r#"{"name":"LI","in_ports":[],"out_ports":[],"components":[{"name":"seeder","in_ports":[],"out_ports":["output"],"components":[]},"#
+        ));
+        assert_eq!(
+            (200, "{}".to_string()),
+            request(addr, "POST", "/inputs/input", " 7\n")
+        );
+        assert_eq!(400, request(addr, "POST", "/inputs/input", "seven").0);
+        assert_eq!(404, request(addr, "POST", "/inputs/output", "7").0);
+        assert_eq!(405, request(addr, "POST", "/events", "").0);
+        let long = format!("/{}", "a".repeat(MAX_LINE));
+        assert_eq!(431, request(addr, "GET", &long, "").0);
+        let body = "7".repeat(MAX_BODY + 1);
+        assert_eq!(413, request(addr, "POST", "/inputs/input", &body).0);
+        assert_eq!(vec![7], *injected.lock().unwrap());
+        assert_eq!(
+            (200, r#"{"paused":true}"#.to_string()),
+            request(addr, "POST", "/pause", "")
+        );
+        assert_eq!(
+            (200, r#"{"paused":false}"#.to_string()),
+            request(addr, "POST", "/resume", "")
+        );
+
+        simulator.add_trace_sinks(vec![Box::new(server)], None);
+        simulator.simulate(f64::INFINITY);
+        let (status, events) = request(addr, "GET", "/events", "");
+        assert_eq!(200, status);
+        assert_eq!(
+            r#"[{"t":0,"path":"LI.coupled_2.atomic_1","phase":"transition"},{"t":null,"path":"LI","phase":"stop"}]"#,
+            events
+        );
+        let (_, time) = request(addr, "GET", "/time", "");
+        assert!(time.starts_with(r#"{"t":0,"running":false"#));
+    }
+}
@@ -0,0 +1,494 @@
+use super::{TracePhase, TraceRecord, TraceSink};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// GUID for computing the `Sec-WebSocket-Accept` header of handshakes (see RFC 6455).
+const WS_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Maximum time that sending a record to a client may take. Slower clients are disconnected.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+/// Maximum time that reading every line of the opening handshake may take.
+const READ_TIMEOUT: Duration = Duration::from_secs(1);
+/// Maximum length of the request line and of every header line of the opening handshake, in bytes.
+const MAX_LINE: usize = 8 * 1024;
+/// Maximum number of header lines of the opening handshake.
+const MAX_HEADERS: usize = 64;
+
+/// Maximum number of frames pending to be sent to a client. Clients that fall further behind are disconnected.
+const MAX_BACKLOG: usize = 256;
+
+/// Client connected to a [`WsServer`].
+struct Client {
+    /// Connection with the client. It is shut down when the client is disconnected.
+    stream: TcpStream,
+    /// Frames pending to be sent by the writer thread of the client.
+    frames: SyncSender<Arc<[u8]>>,
+    /// Component paths subscribed by the client. If empty, the client receives all the records.
+    filters: Vec<String>,
+}
+
+impl Client {
+    /// Creates a new client and spawns its writer thread, which sends the frames queued with [`Client::send`].
+    /// The thread stops when the client is dropped or when a frame cannot be sent.
+    fn new(stream: TcpStream, filters: Vec<String>) -> io::Result<Self> {
+        let mut writer = stream.try_clone()?;
+        let (frames, pending) = mpsc::sync_channel::<Arc<[u8]>>(MAX_BACKLOG);
+        thread::spawn(move || {
+            for frame in pending {
+                if writer.write_all(&frame).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            stream,
+            frames,
+            filters,
+        })
+    }
+
+    /// Queues a frame for the writer thread. It returns `false` if the client fell behind
+    /// or its writer thread stopped. In that case, the client must be disconnected.
+    fn send(&self, frame: &Arc<[u8]>) -> bool {
+        self.frames.try_send(frame.clone()).is_ok()
+    }
+
+    /// Returns `true` if the client must receive the record.
+    /// Start and stop records are sent to all the clients.
+    fn is_subscribed(&self, record: &TraceRecord) -> bool {
+        matches!(record.phase, TracePhase::Start | TracePhase::Stop)
+            || self.filters.is_empty()
+            || self.filters.iter().any(|f| {
+                record.path == *f
+                    || (record.path.starts_with(f.as_str())
+                        && record.path[f.len()..].starts_with('.'))
+            })
+    }
+}
+
+/// [`TraceSink`] that streams trace records as JSON text messages (see [`TraceRecord::to_json`])
+/// to the clients connected to a WebSocket server, so live dashboards can follow the simulation.
+///
+/// Clients subscribe to the components of their interest with `path` query parameters
+/// (e.g., `ws://localhost:8080/?path=root.subcoupled&path=root.atomic`). Clients receive
+/// the records of the subscribed components and their subcomponents. Clients without
+/// subscriptions receive all the records. Start and stop records are sent to all the clients.
+///
+/// Every client has its own writer thread, so slow clients do not slow the simulation down.
+/// Clients that fall more than 256 records behind or that do not receive a record within one second are disconnected.
+pub struct WsServer {
+    /// Address of the server.
+    addr: SocketAddr,
+    /// Connected clients.
+    clients: Arc<Mutex<Vec<Client>>>,
+    /// It is set to `true` when the server is dropped to stop accepting clients.
+    closed: Arc<AtomicBool>,
+}
+
+impl WsServer {
+    /// Creates a new server listening on the given address (e.g., `"127.0.0.1:8080"`).
+    /// Clients are accepted by a background thread until the server is dropped.
+    /// Every client performs the opening handshake in its own thread, so slow clients do not block others.
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let closed = Arc::new(AtomicBool::new(false));
+        let (c, cl) = (clients.clone(), closed.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if cl.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(stream) = stream else { continue };
+                let (c, cl) = (c.clone(), cl.clone());
+                thread::spawn(move || {
+                    // clients with failed handshakes are ignored
+                    let client = handshake(stream);
+                    let Ok(client) = client.and_then(|(s, filters)| Client::new(s, filters)) else {
+                        return;
+                    };
+                    let mut clients = c.lock().unwrap();
+                    match cl.load(Ordering::SeqCst) {
+                        // the server was dropped during the handshake, so the client is closed right away
+                        true => {
+                            client.send(&Arc::from([0x88, 0x00]));
+                        }
+                        false => clients.push(client),
+                    }
+                });
+            }
+        });
+        Ok(Self {
+            addr,
+            clients,
+            closed,
+        })
+    }
+
+    /// Returns the address of the server. It is useful when binding to port 0.
+    #[inline]
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Returns the number of connected clients.
+    pub fn n_clients(&self) -> usize {
+        self.clients.lock().unwrap().len()
+    }
+
+    /// Blocks until at least `n` clients are connected or the timeout expires.
+    /// It returns `true` if `n` clients are connected.
+    pub fn wait_for_clients(&self, n: usize, timeout: Duration) -> bool {
+        let start = Instant::now();
+        while self.n_clients() < n {
+            if start.elapsed() >= timeout {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        true
+    }
+}
+
+impl TraceSink for WsServer {
+    fn record(&mut self, record: &TraceRecord) {
+        let mut clients = self.clients.lock().unwrap();
+        if clients.iter().any(|c| c.is_subscribed(record)) {
+            let frame: Arc<[u8]> = text_frame(&record.to_json()).into();
+            clients.retain(|c| {
+                let keep = !c.is_subscribed(record) || c.send(&frame);
+                if !keep {
+                    let _ = c.stream.shutdown(Shutdown::Both);
+                }
+                keep
+            });
+        }
+    }
+}
+
+impl Drop for WsServer {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::SeqCst);
+        // wakes up the background thread so it notices that the server is closed
+        let _ = TcpStream::connect(self.addr);
+        // writer threads send the close frame after the pending frames, and then they stop
+        let close: Arc<[u8]> = Arc::new([0x88, 0x00]);
+        for client in self.clients.lock().unwrap().drain(..) {
+            if !client.send(&close) {
+                let _ = client.stream.shutdown(Shutdown::Both);
+            }
+        }
+    }
+}
+
+/// Performs the WebSocket opening handshake with a new client and parses its subscriptions.
+/// Requests with lines longer than [`MAX_LINE`] bytes or more than [`MAX_HEADERS`] headers are rejected.
+fn handshake(mut stream: TcpStream) -> io::Result<(TcpStream, Vec<String>)> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut too_large = |msg: &str| {
+        stream.write_all(
+            b"HTTP/1.1 431 Request Header Fields Too Large\r\nContent-Length: 0\r\n\r\n",
+        )?;
+        Err(invalid(msg))
+    };
+    let Some(request) = read_line(&mut reader)? else {
+        return too_large("request line too long");
+    };
+    let target = match request.split_whitespace().collect::<Vec<_>>()[..] {
+        ["GET", target, _] => target.to_string(),
+        _ => return Err(invalid("invalid request line")),
+    };
+    let mut key = None;
+    for i in 0.. {
+        if i == MAX_HEADERS {
+            return too_large("too many header fields");
+        }
+        let Some(line) = read_line(&mut reader)? else {
+            return too_large("header field too long");
+        };
+        if line.is_empty() {
+            return Err(invalid("unexpected end of request"));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("sec-websocket-key") {
+                key = Some(value.trim().to_string());
+            }
+        }
+    }
+    let Some(key) = key else {
+        stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n")?;
+        return Err(invalid("missing Sec-WebSocket-Key header"));
+    };
+    let accept = base64(&sha1(format!("{key}{WS_GUID}").as_bytes()));
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {accept}\r\n\r\n"
+    )?;
+    let filters = target
+        .split_once('?')
+        .map(|(_, query)| query)
+        .unwrap_or_default()
+        .split('&')
+        .filter_map(|param| param.strip_prefix("path="))
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect();
+    Ok((stream, filters))
+}
+
+/// Reads a line of at most [`MAX_LINE`] bytes. It returns [`None`] if the line is longer.
+/// At the end of the stream, it returns an empty line.
+fn read_line(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut line = Vec::new();
+    let n = reader.take(MAX_LINE as u64).read_until(b'\n', &mut line)?;
+    match n == MAX_LINE && !line.ends_with(b"\n") {
+        true => Ok(None),
+        false => Ok(Some(String::from_utf8_lossy(&line).into_owned())),
+    }
+}
+
+/// Builds an unmasked WebSocket text frame (servers must not mask their frames).
+fn text_frame(payload: &str) -> Vec<u8> {
+    let len = payload.len();
+    let mut frame = Vec::with_capacity(len + 10);
+    frame.push(0x81);
+    match len {
+        0..=125 => frame.push(len as u8),
+        126..=0xFFFF => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        _ => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload.as_bytes());
+    frame
+}
+
+/// Computes the SHA-1 digest of a message. It is only used for WebSocket handshakes.
+fn sha1(message: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut data = message.to_vec();
+    data.push(0x80);
+    while data.len() % 64 != 56 {
+        data.push(0);
+    }
+    data.extend_from_slice(&((message.len() as u64) * 8).to_be_bytes());
+    for chunk in data.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in chunk.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, &wi) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = (a.rotate_left(5))
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(wi);
+            (e, d, c, b, a) = (d, c, b.rotate_left(30), a, temp);
+        }
+        for (hi, x) in h.iter_mut().zip([a, b, c, d, e]) {
+            *hi = hi.wrapping_add(x);
+        }
+    }
+    let mut digest = [0; 20];
+    for (i, hi) in h.iter().enumerate() {
+        digest[4 * i..4 * i + 4].copy_from_slice(&hi.to_be_bytes());
+    }
+    digest
+}
+
+/// Encodes bytes in standard base64 with padding.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char),
+                false => encoded.push('='),
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devstone::{TestProbe, LI};
+    use crate::simulation::RootCoordinator;
+    use crate::tracing::ValueFormatter;
+    use std::io::Read;
+
+    /// Reads an unmasked WebSocket frame and returns its opcode and payload.
+    fn read_frame(stream: &mut TcpStream) -> (u8, String) {
+        let mut header = [0; 2];
+        stream.read_exact(&mut header).unwrap();
+        let len = match header[1] {
+            126 => {
+                let mut len = [0; 2];
+                stream.read_exact(&mut len).unwrap();
+                u16::from_be_bytes(len) as usize
+            }
+            127 => {
+                let mut len = [0; 8];
+                stream.read_exact(&mut len).unwrap();
+                u64::from_be_bytes(len) as usize
+            }
+            len => len as usize,
+        };
+        let mut payload = vec![0; len];
+        stream.read_exact(&mut payload).unwrap();
+        (header[0] & 0x0F, String::from_utf8(payload).unwrap())
+    }
+
+    #[test]
+    fn test_handshake_helpers() {
+        // example of RFC 6455
+        let key = "dGhlIHNhbXBsZSBub25jZQ==";
+        let accept = base64(&sha1(format!("{key}{WS_GUID}").as_bytes()));
+        assert_eq!("s3pPLMBiTxaQ9kYGzzhZRbK+xOo=", accept);
+        assert_eq!("", base64(b""));
+        assert_eq!("Zm8=", base64(b"fo"));
+        assert_eq!(vec![0x81, 2, b'h', b'i'], text_frame("hi"));
+        assert_eq!([0x81, 126, 1, 44], text_frame(&"x".repeat(300))[..4]);
+    }
+
+    /// Connects a new client to the server with the given request target and waits for the server to accept it.
+    fn connect(server: &WsServer, target: &str) -> TcpStream {
+        let n_clients = server.n_clients();
+        let mut client = TcpStream::connect(server.local_addr()).unwrap();
+        write!(
+            client,
+            "GET {target} HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n"
+        )
+        .unwrap();
+        let mut reader = BufReader::new(client.try_clone().unwrap());
+        let mut response = String::new();
+        while !response.ends_with("\r\n\r\n") {
+            reader.read_line(&mut response).unwrap();
+        }
+        assert!(response.starts_with("HTTP/1.1 101"));
+        assert!(response.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n"));
+        assert!(server.wait_for_clients(n_clients + 1, Duration::from_secs(5)));
+        client
+    }
+
+    #[test]
+    fn test_ws_server() {
+        let server = WsServer::bind("127.0.0.1:0").unwrap();
+        let mut client = connect(&server, "/?path=LI.seeder");
+
+        let probe = Arc::new(Mutex::new(TestProbe::default()));
+        let mut simulator = RootCoordinator::new(LI::create(2, 2, 0, 0, probe));
+        let formatter = ValueFormatter::new().debug::<usize>();
+        simulator.add_trace_sinks(vec![Box::new(server)], Some(formatter));
+        simulator.simulate(f64::INFINITY);
+        // the server is dropped with the simulator, so the client receives a close frame
+        drop(simulator);
+
+        let mut messages = Vec::new();
+        loop {
+            match read_frame(&mut client) {
+                (0x1, message) => messages.push(message),
+                (opcode, _) => {
+                    assert_eq!(0x8, opcode);
+                    break;
+                }
+            }
+        }
+        assert_eq!(
+            vec![
+                r#"{"t":0,"path":"LI","phase":"start"}"#,
+                r#"{"t":0,"path":"LI.seeder","phase":"output","port":"output","values":["0"]}"#,
+                r#"{"t":0,"path":"LI.seeder","phase":"transition"}"#,
+                r#"{"t":null,"path":"LI","phase":"stop"}"#,
+            ],
+            messages
+        );
+    }
+
+    #[test]
+    fn test_handshake_limits() {
+        let server = WsServer::bind("127.0.0.1:0").unwrap();
+        // a client that never finishes its handshake does not prevent others from connecting
+        let mut stalled = TcpStream::connect(server.local_addr()).unwrap();
+        write!(stalled, "GET / HTTP/1.1\r\nHost: localhost\r\n").unwrap();
+        let _client = connect(&server, "/");
+
+        let mut client = TcpStream::connect(server.local_addr()).unwrap();
+        write!(
+            client,
+            "GET / HTTP/1.1\r\nX-Long: {}\r\n\r\n",
+            "x".repeat(MAX_LINE)
+        )
+        .unwrap();
+        let mut response = String::new();
+        BufReader::new(client).read_line(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 431"));
+
+        let mut client = TcpStream::connect(server.local_addr()).unwrap();
+        write!(
+            client,
+            "GET / HTTP/1.1\r\n{}\r\n",
+            "X-Header: x\r\n".repeat(MAX_HEADERS)
+        )
+        .unwrap();
+        let mut response = String::new();
+        BufReader::new(client).read_line(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 431"));
+        assert_eq!(1, server.n_clients());
+    }
+
+    #[test]
+    fn test_slow_client() {
+        let mut server = WsServer::bind("127.0.0.1:0").unwrap();
+        let _client = connect(&server, "/");
+        let record = TraceRecord {
+            t: 0.,
+            path: "root".to_string(),
+            phase: TracePhase::Output,
+            port: Some("output".to_string()),
+            values: vec!["x".repeat(1 << 14)],
+        };
+        // the client never reads its messages, so it eventually falls behind and it is disconnected
+        let start = Instant::now();
+        while server.n_clients() > 0 {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "slow client was not disconnected"
+            );
+            server.record(&record);
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use crate::devstone::{HOmod, HI, HO, LI};
+use crate::modeling::Coupled;
+use crate::simulation::{Listener, PortValues, RootCoordinator};
+use crate::tracing::ValueFormatter;
+use std::sync::{Arc, Mutex};
+use wasm_bindgen::prelude::*;
+
+/// Output event observed during a simulation.
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortEvent {
+    /// Simulation time of the event.
+    pub t: f64,
+    /// Path of the component in the model hierarchy (e.g., `"root.subcoupled.atomic"`).
+    pub path: String,
+    /// Name of the output port.
+    pub port: String,
+    /// Formatted messages of the port. It is empty if there is no formatter for the message type of the port.
+    pub values: Vec<String>,
+}
+
+/// Observed output ports and output events pending to be taken.
+#[derive(Debug, Default)]
+struct Observations {
+    /// Observed output ports (path and port name). If empty, all the output ports are observed.
+    ports: Vec<(String, String)>,
+    /// Output events observed since the last call to [`Simulation::take_outputs`].
+    events: Vec<PortEvent>,
+    /// Formatter of the messages of output events. If [`None`], messages are not formatted.
+    formatter: Option<ValueFormatter>,
+}
+
+/// [`Listener`] that stores the messages of the observed output ports.
+struct Observer(Arc<Mutex<Observations>>);
+
+impl Listener for Observer {
+    fn on_output(&mut self, t: f64, path: &str, port: &str, values: &PortValues) {
+        let mut observations = self.0.lock().unwrap();
+        let observed = observations.ports.is_empty()
+            || observations
+                .ports
+                .iter()
+                .any(|(a, p)| a == path && p == port);
+        if observed {
+            let values = (observations.formatter.as_ref())
+                .and_then(|f| f.format(values))
+                .unwrap_or_default();
+            observations.events.push(PortEvent {
+                t,
+                path: path.to_string(),
+                port: port.to_string(),
+                values,
+            });
+        }
+    }
+}
+
+/// JavaScript handle of a simulation. The simulation is driven one cycle at a time,
+/// so browsers can render intermediate results between simulation steps.
+#[wasm_bindgen]
+pub struct Simulation {
+    /// Root coordinator of the simulation.
+    coordinator: RootCoordinator<Coupled>,
+    /// Output events observed during the simulation.
+    observations: Arc<Mutex<Observations>>,
+    /// Time of the next simulation cycle. It is `None` if the simulation has not started yet.
+    t_next: Option<f64>,
+}
+
+impl Simulation {
+    /// Creates a new simulation of a coupled model. Crates that build their own WebAssembly
+    /// modules can use it to expose their models to JavaScript.
+    pub fn new(model: Coupled) -> Self {
+        let observations = Arc::new(Mutex::new(Observations::default()));
+        let mut coordinator = RootCoordinator::new(model);
+        coordinator.add_listener(Box::new(Observer(observations.clone())));
+        Self {
+            coordinator,
+            observations,
+            t_next: None,
+        }
+    }
+
+    /// Sets the formatter of the messages of the output events returned by [`Simulation::take_outputs`].
+    pub fn set_formatter(&mut self, formatter: ValueFormatter) {
+        self.observations.lock().unwrap().formatter = Some(formatter);
+    }
+
+    /// Returns the time of the next simulation cycle, starting the simulation if needed.
+    fn ensure_started(&mut self) -> f64 {
+        match self.t_next {
+            Some(t_next) => t_next,
+            None => *self.t_next.insert(self.coordinator.start_simulation()),
+        }
+    }
+}
+
+impl From<Coupled> for Simulation {
+    fn from(model: Coupled) -> Self {
+        Self::new(model)
+    }
+}
+
+#[wasm_bindgen]
+impl Simulation {
+    /// Creates a new simulation of a DEVStone model.
+    /// `kind` must be one of `"LI"`, `"HI"`, `"HO"`, or `"HOmod"`.
+    pub fn devstone(
+        kind: &str,
+        width: usize,
+        depth: usize,
+        int_delay: u64,
+        ext_delay: u64,
+    ) -> Result<Simulation, JsError> {
+        let create = match kind {
+            "LI" => LI::create,
+            "HI" => HI::create,
+            "HO" => HO::create,
+            "HOmod" => HOmod::create,
+            _ => return Err(JsError::new(&format!("unknown DEVStone model {kind}"))),
+        };
+        #[cfg(test)]
+        let probe = Arc::new(Mutex::new(crate::devstone::TestProbe::default()));
+        let model = create(
+            width,
+            depth,
+            int_delay,
+            ext_delay,
+            #[cfg(test)]
+            probe,
+        );
+        let mut simulation = Self::new(model);
+        simulation.set_formatter(ValueFormatter::new().debug::<usize>());
+        Ok(simulation)
+    }
+
+    /// Observes an output port of a component. Its messages are returned by [`Simulation::take_outputs`].
+    /// `path` is the path of the component in the model hierarchy (e.g., `"root.subcoupled.atomic"`).
+    /// If no port is observed, all the output ports are observed.
+    pub fn observe(&mut self, path: &str, port: &str) {
+        let ports = &mut self.observations.lock().unwrap().ports;
+        ports.push((path.to_string(), port.to_string()));
+    }
+
+    /// Returns the time of the next simulation cycle. The simulation is started if needed.
+    #[wasm_bindgen(js_name = nextTime)]
+    pub fn next_time(&mut self) -> f64 {
+        self.ensure_started()
+    }
+
+    /// Executes the next simulation cycle and returns the time of the following one.
+    /// The simulation is started if needed. If the model is passive, it does nothing.
+    pub fn step(&mut self) -> f64 {
+        let mut t_next = self.ensure_started();
+        if t_next < f64::INFINITY {
+            t_next = self.coordinator.step();
+            self.t_next = Some(t_next);
+        }
+        t_next
+    }
+
+    /// Executes all the simulation cycles scheduled before `t_end` and returns the time of the next one.
+    /// The simulation is started if needed.
+    #[wasm_bindgen(js_name = advanceUntil)]
+    pub fn advance_until(&mut self, t_end: f64) -> f64 {
+        let mut t_next = self.ensure_started();
+        while t_next < t_end {
+            t_next = self.step();
+        }
+        t_next
+    }
+
+    /// Stops the simulation. Further steps will start a new simulation.
+    pub fn stop(&mut self) {
+        if self.t_next.take().is_some() {
+            self.coordinator.stop_simulation();
+        }
+    }
+
+    /// Returns the output events observed since the last call to this method.
+    #[wasm_bindgen(js_name = takeOutputs)]
+    pub fn take_outputs(&mut self) -> Vec<PortEvent> {
+        std::mem::take(&mut self.observations.lock().unwrap().events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulation() {
+        let mut simulation = Simulation::devstone("LI", 2, 2, 0, 0).unwrap();
+        simulation.observe("LI.seeder", "output");
+        assert_eq!(0., simulation.next_time());
+        assert_eq!(f64::INFINITY, simulation.advance_until(f64::INFINITY));
+        let outputs = simulation.take_outputs();
+        assert_eq!(1, outputs.len());
+        assert_eq!(
+            ("LI.seeder", "output"),
+            (&*outputs[0].path, &*outputs[0].port)
+        );
+        assert_eq!(vec!["0".to_string()], outputs[0].values);
+        assert!(simulation.take_outputs().is_empty());
+        simulation.stop();
+    }
+}